@@ -55,6 +55,55 @@ CREATE TABLE IF NOT EXISTS `settings` (
     INDEX `idx_type` (`type`)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='配置表';
 
+-- =====================================================
+-- lspc_playlist 表 - 屏幕播放列表
+-- =====================================================
+CREATE TABLE IF NOT EXISTS `lspc_playlist` (
+    `id` VARCHAR(64) NOT NULL PRIMARY KEY COMMENT '主键ID（UUID）',
+    `name` VARCHAR(255) NOT NULL COMMENT '名称',
+    `screen_id` VARCHAR(64) NOT NULL COMMENT '所属屏幕ID',
+    `active` TINYINT(1) NOT NULL DEFAULT 1 COMMENT '是否参与自动调度',
+    `valid_from` DATETIME NULL COMMENT '生效开始时间',
+    `valid_until` DATETIME NULL COMMENT '生效结束时间',
+    `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP COMMENT '创建时间',
+    `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP COMMENT '更新时间',
+    INDEX `idx_screen_id` (`screen_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='播放列表表';
+
+-- =====================================================
+-- lspc_playlist_item 表 - 播放列表条目
+-- =====================================================
+CREATE TABLE IF NOT EXISTS `lspc_playlist_item` (
+    `playlist_id` VARCHAR(64) NOT NULL COMMENT '播放列表ID',
+    `position` INT NOT NULL COMMENT '播放顺序（从 0 开始）',
+    `material_id` VARCHAR(64) NOT NULL COMMENT '素材ID',
+    `duration_ms` BIGINT NOT NULL COMMENT '播放时长（毫秒）',
+    PRIMARY KEY (`playlist_id`, `position`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='播放列表条目表';
+
+-- =====================================================
+-- lspc_config_revision 表 - 通道/节点/场景配置版本（database.config_store）
+-- =====================================================
+CREATE TABLE IF NOT EXISTS `lspc_config_revision` (
+    `id` BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY COMMENT '版本号',
+    `content` LONGTEXT NOT NULL COMMENT '配置内容（JSON：channels / nodes / scenes）',
+    `operator` VARCHAR(255) NOT NULL DEFAULT '' COMMENT '保存人',
+    `comment` VARCHAR(1024) NOT NULL DEFAULT '' COMMENT '备注',
+    `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP COMMENT '保存时间'
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='配置版本表';
+
+-- =====================================================
+-- lspc_current_values 表 - 节点当前值（database.current_values）
+-- =====================================================
+CREATE TABLE IF NOT EXISTS `lspc_current_values` (
+    `global_id` INT UNSIGNED NOT NULL PRIMARY KEY COMMENT '节点ID',
+    `channel_id` INT UNSIGNED NOT NULL COMMENT '通道ID',
+    `alias` VARCHAR(255) NOT NULL DEFAULT '' COMMENT '节点别名',
+    `value` INT NOT NULL COMMENT '当前值',
+    `forced` TINYINT(1) NOT NULL DEFAULT 0 COMMENT '是否为调试强制值',
+    `updated_at` DATETIME(3) NOT NULL COMMENT '写入时间（UTC）'
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='节点当前值表';
+
 -- =====================================================
 -- 示例数据（可选）
 -- =====================================================
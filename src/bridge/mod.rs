@@ -0,0 +1,391 @@
+//! 事件总线桥接
+//! 把设备事件发布到 NATS / Redis 发布订阅，并接收总线上的命令，
+//! 供外部微服务（数据分析、自定义规则引擎等）无需轮询 HTTP 即可接入。
+//!
+//! 主题（NATS subject / Redis channel）：
+//! - `<前缀>.events.<事件类型>`：设备事件，如 `dm-rust.events.node_state_changed`
+//! - `<前缀>.commands`：命令（`accept_commands` 为 true 时订阅）
+//! - 命令回复：NATS 请求的 reply 主题，或命令中的 `reply_to`
+
+#[cfg(feature = "nats-bridge")]
+mod nats;
+#[cfg(feature = "redis-bridge")]
+mod redis;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use crate::config::{EventBridgeBackend, EventBridgeConfig};
+use crate::utils::{DeviceError, Result};
+use crate::web::state::SharedController;
+
+/// 连接断开后的重试间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// 总线上收到的消息
+struct Incoming {
+    payload: Vec<u8>,
+    /// 回复主题（NATS 请求）
+    reply: Option<String>,
+}
+
+/// 总线传输层
+#[async_trait]
+trait Transport: Send + Sync {
+    /// 总线名称（日志用）
+    fn name(&self) -> &'static str;
+
+    /// 发布消息
+    async fn publish(&self, subject: &str, payload: Vec<u8>) -> Result<()>;
+
+    /// 订阅主题，连接断开时流结束
+    async fn subscribe(&self, subject: &str) -> Result<BoxStream<'static, Incoming>>;
+}
+
+/// 总线命令
+#[derive(Debug, Deserialize)]
+struct BusCommand {
+    /// 请求 ID，原样带回回复
+    #[serde(default)]
+    id: Option<Value>,
+    /// 回复主题（Redis 无请求-回复机制时使用）
+    #[serde(default)]
+    reply_to: Option<String>,
+    #[serde(flatten)]
+    action: BusAction,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum BusAction {
+    /// 写节点（需要写入确认的节点返回确认令牌，不直接写入）
+    Write { global_id: u32, value: i32 },
+    /// 读节点
+    Read { global_id: u32 },
+    /// 执行场景
+    Scene { name: String },
+    /// 执行通道命令
+    Execute {
+        channel_id: u32,
+        command: String,
+        #[serde(default)]
+        params: Value,
+    },
+    /// 调用通道自定义方法
+    CallMethod {
+        channel_id: u32,
+        method: String,
+        #[serde(default)]
+        args: Value,
+    },
+}
+
+/// 命令回复
+#[derive(Debug, Serialize)]
+struct BusReply {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// 按配置启动事件总线桥接（未启用时不做任何事）
+///
+/// 总线连接失败不影响启动，后台每隔 [`RECONNECT_DELAY`] 重试；
+/// 编译时未启用对应特性则返回配置错误。
+pub fn start(config: Option<&EventBridgeConfig>, controller: SharedController) -> Result<()> {
+    let Some(config) = config.filter(|c| c.enable) else {
+        return Ok(());
+    };
+    check_backend(config.backend)?;
+
+    let config = config.clone();
+    tokio::spawn(async move {
+        let transport = loop {
+            match connect(&config).await {
+                Ok(transport) => break transport,
+                Err(e) => {
+                    warn!(
+                        "事件总线连接失败: {}，{} 秒后重试",
+                        e,
+                        RECONNECT_DELAY.as_secs()
+                    );
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        };
+        info!(
+            "事件总线桥接已启动: {} {} (前缀 '{}', 接收命令: {})",
+            transport.name(),
+            config.url,
+            config.subject_prefix,
+            config.accept_commands
+        );
+
+        if config.accept_commands {
+            tokio::spawn(run_commands(
+                transport.clone(),
+                config.clone(),
+                controller.clone(),
+            ));
+        }
+        run_events(transport, config, controller).await;
+    });
+    Ok(())
+}
+
+/// 未编译对应特性时的配置错误
+fn not_compiled(backend: EventBridgeBackend) -> DeviceError {
+    let feature = match backend {
+        EventBridgeBackend::Nats => "nats-bridge",
+        EventBridgeBackend::Redis => "redis-bridge",
+    };
+    DeviceError::ConfigError(format!(
+        "事件总线 {:?} 需要启用 {} 编译特性",
+        backend, feature
+    ))
+}
+
+fn check_backend(backend: EventBridgeBackend) -> Result<()> {
+    let compiled = match backend {
+        EventBridgeBackend::Nats => cfg!(feature = "nats-bridge"),
+        EventBridgeBackend::Redis => cfg!(feature = "redis-bridge"),
+    };
+    if compiled {
+        Ok(())
+    } else {
+        Err(not_compiled(backend))
+    }
+}
+
+async fn connect(config: &EventBridgeConfig) -> Result<Arc<dyn Transport>> {
+    match config.backend {
+        #[cfg(feature = "nats-bridge")]
+        EventBridgeBackend::Nats => Ok(Arc::new(nats::NatsTransport::connect(&config.url).await?)),
+        #[cfg(not(feature = "nats-bridge"))]
+        EventBridgeBackend::Nats => Err(not_compiled(config.backend)),
+        #[cfg(feature = "redis-bridge")]
+        EventBridgeBackend::Redis => {
+            Ok(Arc::new(redis::RedisTransport::connect(&config.url).await?))
+        }
+        #[cfg(not(feature = "redis-bridge"))]
+        EventBridgeBackend::Redis => Err(not_compiled(config.backend)),
+    }
+}
+
+/// 事件主题
+fn event_subject(prefix: &str, event: &Value) -> Option<String> {
+    let kind = event.get("type")?.as_str()?;
+    Some(format!("{}.events.{}", prefix, kind))
+}
+
+/// 转发设备事件；配置热重载替换控制器后重新订阅新的事件总线
+async fn run_events(
+    transport: Arc<dyn Transport>,
+    config: EventBridgeConfig,
+    controller: SharedController,
+) {
+    loop {
+        let mut event_rx = controller.read().await.subscribe_events();
+        loop {
+            let event = match event_rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(n)) => {
+                    crate::device::record_lagged_events(n);
+                    warn!("事件总线桥接处理过慢，丢失 {} 个事件", n);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            // 不能持有控制器副本（其中的事件发送端会阻止热重载后总线关闭）
+            let value = controller.read().await.enrich_event(&event);
+            publish_event(transport.as_ref(), &config, value).await;
+        }
+        debug!("设备事件总线已关闭，重新订阅");
+    }
+}
+
+/// 发布事件（已补充节点与通道信息的 JSON）
+async fn publish_event(transport: &dyn Transport, config: &EventBridgeConfig, mut value: Value) {
+    let Some(subject) = event_subject(&config.subject_prefix, &value) else {
+        return;
+    };
+    if !config.events.is_empty() && !config.events.iter().any(|e| value["type"] == *e) {
+        return;
+    }
+    value["timestamp"] = chrono::Utc::now().timestamp_millis().into();
+    value["source"] = config.subject_prefix.clone().into();
+
+    if let Err(e) = transport
+        .publish(&subject, value.to_string().into_bytes())
+        .await
+    {
+        warn!("事件发布到 {} 失败: {}", subject, e);
+    }
+}
+
+/// 订阅命令主题，连接断开后重新订阅
+async fn run_commands(
+    transport: Arc<dyn Transport>,
+    config: EventBridgeConfig,
+    controller: SharedController,
+) {
+    let subject = format!("{}.commands", config.subject_prefix);
+    loop {
+        match transport.subscribe(&subject).await {
+            Ok(mut messages) => {
+                info!("事件总线桥接: 已订阅命令主题 {}", subject);
+                while let Some(message) = messages.next().await {
+                    let transport = transport.clone();
+                    let controller = controller.clone();
+                    tokio::spawn(async move {
+                        handle_command(transport.as_ref(), &controller, message).await;
+                    });
+                }
+                warn!("命令主题 {} 订阅已断开", subject);
+            }
+            Err(e) => warn!("订阅命令主题 {} 失败: {}", subject, e),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn handle_command(
+    transport: &dyn Transport,
+    controller: &SharedController,
+    message: Incoming,
+) {
+    let command: BusCommand = match serde_json::from_slice(&message.payload) {
+        Ok(command) => command,
+        Err(e) => {
+            warn!("总线命令解析失败: {}", e);
+            if let Some(ref reply) = message.reply {
+                let reply_body = BusReply {
+                    id: None,
+                    success: false,
+                    data: None,
+                    error: Some(format!("命令解析失败: {}", e)),
+                };
+                send_reply(transport, reply, &reply_body).await;
+            }
+            return;
+        }
+    };
+    debug!("收到总线命令: {:?}", command.action);
+
+    let result = execute(controller, command.action).await;
+    let reply_body = match result {
+        Ok(data) => BusReply {
+            id: command.id,
+            success: true,
+            data: Some(data),
+            error: None,
+        },
+        Err(e) => BusReply {
+            id: command.id,
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+        },
+    };
+    if let Some(reply) = message.reply.or(command.reply_to) {
+        send_reply(transport, &reply, &reply_body).await;
+    }
+}
+
+async fn send_reply(transport: &dyn Transport, subject: &str, reply: &BusReply) {
+    let payload = serde_json::to_vec(reply).unwrap_or_default();
+    if let Err(e) = transport.publish(subject, payload).await {
+        warn!("命令回复发送到 {} 失败: {}", subject, e);
+    }
+}
+
+async fn execute(controller: &SharedController, action: BusAction) -> Result<Value> {
+    let controller = controller.read().await;
+    match action {
+        BusAction::Write { global_id, value } => {
+            match controller.hold_for_confirmation(
+                global_id,
+                value.into(),
+                Some("event-bridge".into()),
+            )? {
+                Some(pending) => Err(DeviceError::Other(format!(
+                    "节点 {} 写入需要确认，确认令牌: {}",
+                    global_id, pending.token
+                ))),
+                None => {
+                    controller.write_node(global_id, value).await?;
+                    Ok(Value::Null)
+                }
+            }
+        }
+        BusAction::Read { global_id } => Ok(controller.read_node(global_id).await?.into()),
+        BusAction::Scene { name } => {
+            controller.execute_scene(&name).await?;
+            Ok(Value::Null)
+        }
+        BusAction::Execute {
+            channel_id,
+            command,
+            params,
+        } => {
+            controller
+                .execute_channel_command(channel_id, &command, params)
+                .await
+        }
+        BusAction::CallMethod {
+            channel_id,
+            method,
+            args,
+        } => {
+            controller
+                .call_channel_method(channel_id, &method, args)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::DeviceEvent;
+
+    #[test]
+    fn event_subject_and_command_format() {
+        let event = serde_json::to_value(DeviceEvent::NodeStateChanged {
+            global_id: 7,
+            old_value: 0,
+            new_value: 1,
+            forced: false,
+        })
+        .unwrap();
+        assert_eq!(event["global_id"], 7);
+        assert_eq!(
+            event_subject("hall-a", &event).as_deref(),
+            Some("hall-a.events.node_state_changed")
+        );
+
+        let command: BusCommand = serde_json::from_str(
+            r#"{"id": 3, "reply_to": "rules.replies", "action": "write", "global_id": 7, "value": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(command.reply_to.as_deref(), Some("rules.replies"));
+        assert!(matches!(
+            command.action,
+            BusAction::Write {
+                global_id: 7,
+                value: 1
+            }
+        ));
+    }
+}
@@ -0,0 +1,51 @@
+//! NATS 总线传输（断线后客户端自动重连，订阅随之恢复）
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+
+use super::{Incoming, Transport};
+use crate::utils::{DeviceError, Result};
+
+pub struct NatsTransport {
+    client: async_nats::Client,
+}
+
+impl NatsTransport {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = async_nats::ConnectOptions::new()
+            .name("dm-rust")
+            .connect(url)
+            .await
+            .map_err(|e| DeviceError::ConnectionError(format!("连接 NATS 失败: {}", e)))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl Transport for NatsTransport {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn publish(&self, subject: &str, payload: Vec<u8>) -> Result<()> {
+        self.client
+            .publish(subject.to_string(), payload.into())
+            .await
+            .map_err(|e| DeviceError::ConnectionError(e.to_string()))
+    }
+
+    async fn subscribe(&self, subject: &str) -> Result<BoxStream<'static, Incoming>> {
+        let subscriber = self
+            .client
+            .subscribe(subject.to_string())
+            .await
+            .map_err(|e| DeviceError::ConnectionError(e.to_string()))?;
+        Ok(subscriber
+            .map(|message| Incoming {
+                payload: message.payload.to_vec(),
+                reply: message.reply.map(|reply| reply.to_string()),
+            })
+            .boxed())
+    }
+}
@@ -0,0 +1,62 @@
+//! Redis 发布订阅总线传输（无请求-回复机制，命令通过 `reply_to` 指定回复频道）
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+
+use super::{Incoming, Transport};
+use crate::utils::{DeviceError, Result};
+
+pub struct RedisTransport {
+    client: redis::Client,
+    /// 发布用连接（断线后自动重连）
+    conn: ConnectionManager,
+}
+
+impl RedisTransport {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| DeviceError::ConfigError(format!("Redis 地址无效: {}", e)))?;
+        let conn = ConnectionManager::new(client.clone())
+            .await
+            .map_err(|e| DeviceError::ConnectionError(format!("连接 Redis 失败: {}", e)))?;
+        Ok(Self { client, conn })
+    }
+}
+
+#[async_trait]
+impl Transport for RedisTransport {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn publish(&self, subject: &str, payload: Vec<u8>) -> Result<()> {
+        self.conn
+            .clone()
+            .publish::<_, _, ()>(subject, payload)
+            .await
+            .map_err(|e| DeviceError::ConnectionError(e.to_string()))
+    }
+
+    async fn subscribe(&self, subject: &str) -> Result<BoxStream<'static, Incoming>> {
+        // 订阅需要独占连接，连接断开时消息流结束，由调用方重新订阅
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| DeviceError::ConnectionError(e.to_string()))?;
+        pubsub
+            .subscribe(subject)
+            .await
+            .map_err(|e| DeviceError::ConnectionError(e.to_string()))?;
+        Ok(pubsub
+            .into_on_message()
+            .map(|message| Incoming {
+                payload: message.get_payload_bytes().to_vec(),
+                reply: None,
+            })
+            .boxed())
+    }
+}
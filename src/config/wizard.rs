@@ -0,0 +1,440 @@
+//! 配置生成向导（`dm-rust init`）
+//!
+//! 按常见布局生成起步配置：N 台 Modbus TCP PLC（每台若干输出点，映射为保持寄存器
+//! 0..N-1）、M 台 PJLink 投影机，以及控制全部 PLC 输出的 `all-on` / `all-off` 场景。
+//! 同时生成与 PLC 寄存器布局一致的 Modbus 模拟器模板，便于在没有现场设备时联调。
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::net::IpAddr;
+use std::path::Path;
+
+use super::Config;
+
+const MODBUS_DEFAULT_PORT: u16 = 502;
+const PJLINK_DEFAULT_PORT: u16 = 4352;
+/// Modbus 从站地址的有效范围（0 为广播地址）
+const MAX_SLAVE_ID: u8 = 247;
+
+/// `dm-rust init` 参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct InitArgs {
+    /// 生成的配置文件路径
+    #[arg(short, long, default_value = "config.json")]
+    pub output: String,
+
+    /// Modbus TCP PLC 地址（ADDR[:PORT]，默认端口 502，可重复）
+    #[arg(long = "plc", value_name = "ADDR[:PORT]")]
+    pub plcs: Vec<String>,
+
+    /// 每台 PLC 的输出点数（保持寄存器 0 起连续编号）
+    #[arg(long, default_value_t = 8)]
+    pub outputs: u16,
+
+    /// PLC 从站地址
+    #[arg(long, default_value_t = 1)]
+    pub slave_id: u8,
+
+    /// PJLink 投影机地址（ADDR[:PORT]，默认端口 4352，可重复）
+    #[arg(long = "projector", value_name = "ADDR[:PORT]")]
+    pub projectors: Vec<String>,
+
+    /// Web 服务端口
+    #[arg(long, default_value_t = 8080)]
+    pub web_port: u16,
+
+    /// 模拟器模板输出路径
+    #[arg(long, default_value = "simulator-templates.json")]
+    pub simulators: String,
+
+    /// 不生成模拟器模板
+    #[arg(long)]
+    pub no_simulators: bool,
+
+    /// 覆盖已存在的文件
+    #[arg(long)]
+    pub force: bool,
+
+    /// 不进入交互模式，只使用命令行参数
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+}
+
+/// 设备地址
+#[derive(Debug, Clone, PartialEq)]
+struct Endpoint {
+    addr: String,
+    port: u16,
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.addr, self.port)
+    }
+}
+
+/// 解析并校验 `ADDR[:PORT]`（IPv4 或主机名）
+fn parse_endpoint(input: &str, default_port: u16) -> Result<Endpoint> {
+    let input = input.trim();
+    let (addr, port) = match input.rsplit_once(':') {
+        Some((addr, port)) => {
+            let port: u16 = port.parse().map_err(|_| anyhow!("端口无效: '{}'", input))?;
+            (addr, port)
+        }
+        None => (input, default_port),
+    };
+    if port == 0 {
+        bail!("端口不能为 0: '{}'", input);
+    }
+    let valid_host = !addr.is_empty()
+        && addr.len() <= 253
+        && addr.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    // 全数字的点分形式必须是合法的 IPv4 地址（排除 192.168.1.300 之类的笔误）
+    let numeric = addr.chars().all(|c| c.is_ascii_digit() || c == '.');
+    if !valid_host || (numeric && addr.parse::<IpAddr>().is_err()) {
+        bail!("地址无效: '{}'", addr);
+    }
+    Ok(Endpoint {
+        addr: addr.to_string(),
+        port,
+    })
+}
+
+/// 生成计划
+#[derive(Debug, Clone)]
+struct Plan {
+    plcs: Vec<Endpoint>,
+    outputs: u16,
+    slave_id: u8,
+    projectors: Vec<Endpoint>,
+    web_port: u16,
+}
+
+impl Plan {
+    fn from_args(args: &InitArgs) -> Result<Self> {
+        let plan = Self {
+            plcs: args
+                .plcs
+                .iter()
+                .map(|s| parse_endpoint(s, MODBUS_DEFAULT_PORT))
+                .collect::<Result<_>>()?,
+            outputs: args.outputs,
+            slave_id: args.slave_id,
+            projectors: args
+                .projectors
+                .iter()
+                .map(|s| parse_endpoint(s, PJLINK_DEFAULT_PORT))
+                .collect::<Result<_>>()?,
+            web_port: args.web_port,
+        };
+        plan.validate()?;
+        Ok(plan)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.plcs.is_empty() && self.projectors.is_empty() {
+            bail!("至少需要一台 PLC 或投影机");
+        }
+        if !self.plcs.is_empty() && self.outputs == 0 {
+            bail!("每台 PLC 的输出点数必须大于 0");
+        }
+        if !(1..=MAX_SLAVE_ID).contains(&self.slave_id) {
+            bail!("从站地址必须在 1-{} 之间", MAX_SLAVE_ID);
+        }
+        if self.web_port == 0 {
+            bail!("Web 服务端口不能为 0");
+        }
+        let mut seen = HashSet::new();
+        if let Some(dup) = self
+            .plcs
+            .iter()
+            .chain(&self.projectors)
+            .find(|e| !seen.insert(e.to_string()))
+        {
+            bail!("设备地址重复: {}", dup);
+        }
+        Ok(())
+    }
+
+    fn projector_channel(&self, index: usize) -> u32 {
+        (self.plcs.len() + index + 1) as u32
+    }
+
+    /// 生成配置（序列化为 `Config` 校验后返回 JSON）
+    fn config(&self) -> Result<Value> {
+        let mut channels = Vec::new();
+        let mut nodes = Vec::new();
+        for (i, plc) in self.plcs.iter().enumerate() {
+            let channel_id = i as u32 + 1;
+            channels.push(json!({
+                "channel_id": channel_id,
+                "enable": true,
+                "statute": "modbus",
+                "arguments": {
+                    "type": "tcp",
+                    "addr": plc.addr,
+                    "port": plc.port,
+                    "slave_id": self.slave_id,
+                },
+            }));
+            for output in 0..self.outputs {
+                nodes.push(json!({
+                    "global_id": nodes.len() + 1,
+                    "channel_id": channel_id,
+                    "id": output,
+                    "category": "output",
+                    "alias": format!("PLC{} 输出{}", channel_id, output + 1),
+                }));
+            }
+        }
+        for (i, projector) in self.projectors.iter().enumerate() {
+            channels.push(json!({
+                "channel_id": self.projector_channel(i),
+                "enable": true,
+                "statute": "pjlink",
+                "arguments": {
+                    "addr": projector.addr,
+                    "port": projector.port,
+                },
+            }));
+        }
+
+        let scene = |name: &str, value: i32| {
+            json!({
+                "name": name,
+                "nodes": nodes
+                    .iter()
+                    .map(|n| json!({ "id": n["global_id"], "value": value }))
+                    .collect::<Vec<_>>(),
+            })
+        };
+        let config = json!({
+            "channels": channels,
+            "nodes": nodes,
+            "scenes": if nodes.is_empty() {
+                vec![]
+            } else {
+                vec![scene("all-on", 1), scene("all-off", 0)]
+            },
+            "web_server": { "port": self.web_port },
+        });
+        serde_json::from_value::<Config>(config.clone())
+            .map_err(|e| anyhow!("生成的配置无效: {}", e))?;
+        Ok(config)
+    }
+
+    /// 生成 Modbus 模拟器模板（每台 PLC 一个，寄存器布局与节点一致）
+    fn simulator_templates(&self) -> Value {
+        let now = chrono::Utc::now().to_rfc3339();
+        let templates: Vec<Value> = self
+            .plcs
+            .iter()
+            .enumerate()
+            .map(|(i, plc)| {
+                let registers: Vec<Value> = (0..self.outputs)
+                    .map(|output| {
+                        json!({
+                            "address": output,
+                            "dataType": "uint16",
+                            "type": "holding_register",
+                            "name": format!("输出{}", output + 1),
+                            "readonly": false,
+                            "value": 0,
+                        })
+                    })
+                    .collect();
+                json!({
+                    "id": uuid::Uuid::new_v4().to_string(),
+                    "name": format!("PLC{}", i + 1),
+                    "description": format!("dm-rust init 生成，对应 {}", plc),
+                    "protocol": "modbus",
+                    "transport": "tcp",
+                    "config": {
+                        "defaultSlaveId": self.slave_id,
+                        "slaves": [{ "slaveId": self.slave_id, "registers": registers }],
+                    },
+                    "values": null,
+                    "created_at": now,
+                    "updated_at": now,
+                })
+            })
+            .collect();
+        Value::Array(templates)
+    }
+}
+
+/// 读取一行输入，空输入返回默认值
+fn ask(prompt: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("{}: ", prompt);
+    } else {
+        print!("{} [{}]: ", prompt, default);
+    }
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        bail!("输入已结束");
+    }
+    let line = line.trim();
+    Ok(if line.is_empty() { default } else { line }.to_string())
+}
+
+/// 反复提示直到输入通过校验
+fn ask_valid<T>(prompt: &str, default: &str, parse: impl Fn(&str) -> Result<T>) -> Result<T> {
+    loop {
+        match parse(&ask(prompt, default)?) {
+            Ok(value) => return Ok(value),
+            Err(e) => println!("  {}，请重新输入", e),
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(input: &str) -> Result<T> {
+    input
+        .parse()
+        .map_err(|_| anyhow!("不是有效的数字: '{}'", input))
+}
+
+/// 交互式补全设备列表
+fn prompt_devices(args: &mut InitArgs) -> Result<()> {
+    println!("dm-rust 配置生成向导（直接回车使用方括号中的默认值）");
+    let plc_count: usize = ask_valid("Modbus PLC 数量", "1", parse_number)?;
+    for i in 0..plc_count {
+        let default = format!("192.168.1.{}", 10 + i);
+        let plc = ask_valid(
+            &format!("  PLC{} 地址 (ADDR[:PORT])", i + 1),
+            &default,
+            |s| parse_endpoint(s, MODBUS_DEFAULT_PORT),
+        )?;
+        args.plcs.push(plc.to_string());
+    }
+    if plc_count > 0 {
+        args.outputs = ask_valid(
+            "每台 PLC 的输出点数",
+            &args.outputs.to_string(),
+            |s| match parse_number::<u16>(s)? {
+                0 => bail!("输出点数必须大于 0"),
+                n => Ok(n),
+            },
+        )?;
+        args.slave_id =
+            ask_valid(
+                "PLC 从站地址",
+                &args.slave_id.to_string(),
+                |s| match parse_number::<u8>(s)? {
+                    id @ 1..=MAX_SLAVE_ID => Ok(id),
+                    _ => bail!("从站地址必须在 1-{} 之间", MAX_SLAVE_ID),
+                },
+            )?;
+    }
+    let projector_count: usize = ask_valid("PJLink 投影机数量", "0", parse_number)?;
+    for i in 0..projector_count {
+        let default = format!("192.168.1.{}", 50 + i);
+        let projector = ask_valid(
+            &format!("  投影机{} 地址 (ADDR[:PORT])", i + 1),
+            &default,
+            |s| parse_endpoint(s, PJLINK_DEFAULT_PORT),
+        )?;
+        args.projectors.push(projector.to_string());
+    }
+    args.web_port = ask_valid("Web 服务端口", &args.web_port.to_string(), parse_number)?;
+    Ok(())
+}
+
+fn write_json(path: &str, value: &Value) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)?)
+        .map_err(|e| anyhow!("写入 {} 失败: {}", path, e))
+}
+
+/// 执行 `dm-rust init`
+pub fn run(mut args: InitArgs) -> Result<()> {
+    if !args.yes && args.plcs.is_empty() && args.projectors.is_empty() {
+        prompt_devices(&mut args)?;
+    }
+    let plan = Plan::from_args(&args)?;
+    let config = plan.config()?;
+    // 先检查全部目标文件，避免只写出一半
+    if !args.force {
+        let targets = std::iter::once(&args.output)
+            .chain((!args.no_simulators && !plan.plcs.is_empty()).then_some(&args.simulators));
+        if let Some(existing) = targets.into_iter().find(|p| Path::new(p).exists()) {
+            bail!("文件已存在: {}（使用 --force 覆盖）", existing);
+        }
+    }
+    write_json(&args.output, &config)?;
+    println!(
+        "已生成配置 {}: {} 台 PLC（每台 {} 个输出）、{} 台投影机",
+        args.output,
+        plan.plcs.len(),
+        plan.outputs,
+        plan.projectors.len()
+    );
+    if !plan.plcs.is_empty() {
+        println!("  场景 all-on / all-off 控制全部 PLC 输出");
+        if !args.no_simulators {
+            write_json(&args.simulators, &plan.simulator_templates())?;
+            println!(
+                "已生成模拟器模板 {}（导入 TCP 模拟器后，将配置中的 PLC 地址改为模拟器地址即可联调）",
+                args.simulators
+            );
+        }
+    }
+    if !plan.projectors.is_empty() {
+        let channels: Vec<String> = (0..plan.projectors.len())
+            .map(|i| plan.projector_channel(i).to_string())
+            .collect();
+        println!(
+            "  投影机（通道 {}）通过通道命令 powerOn / powerOff 控制，不参与场景",
+            channels.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_valid_config_and_rejects_bad_addresses() {
+        assert_eq!(
+            parse_endpoint("plc-1.local", MODBUS_DEFAULT_PORT).unwrap(),
+            Endpoint {
+                addr: "plc-1.local".to_string(),
+                port: 502
+            }
+        );
+        for bad in ["192.168.1.300", "10.0.0.1:0", "10.0.0.1:70000", "", "a b"] {
+            assert!(parse_endpoint(bad, MODBUS_DEFAULT_PORT).is_err(), "{}", bad);
+        }
+
+        let plan = Plan {
+            plcs: vec![
+                parse_endpoint("10.0.0.1", MODBUS_DEFAULT_PORT).unwrap(),
+                parse_endpoint("10.0.0.2:1502", MODBUS_DEFAULT_PORT).unwrap(),
+            ],
+            outputs: 3,
+            slave_id: 1,
+            projectors: vec![parse_endpoint("10.0.0.50", PJLINK_DEFAULT_PORT).unwrap()],
+            web_port: 8080,
+        };
+        plan.validate().unwrap();
+        let config: Config = serde_json::from_value(plan.config().unwrap()).unwrap();
+        assert_eq!(config.channels.len(), 3);
+        assert_eq!(config.nodes.len(), 6);
+        assert_eq!(config.nodes[5].global_id, 6);
+        assert_eq!(config.scenes[0].name, "all-on");
+        assert_eq!(config.scenes[1].nodes.len(), 6);
+        assert_eq!(plan.simulator_templates().as_array().unwrap().len(), 2);
+
+        let mut duplicate = plan.clone();
+        duplicate.plcs[1] = duplicate.plcs[0].clone();
+        assert!(duplicate.validate().is_err());
+    }
+}
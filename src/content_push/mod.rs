@@ -0,0 +1,436 @@
+//! 内容推送
+//! 把资源库中的素材推送到支持文件上传的显示设备（LED 控制器等），内容分发与设备控制在同一系统中完成。
+//!
+//! 按协议给出的分块大小顺序上传（单个分块失败重试），推送期间发送 `content_push_progress` 事件，
+//! 结束时发送 `content_push_finished` 事件；设备返回 SHA-256 时与源文件比对校验。
+//! 协议通过 [`Protocol::content_chunk_size`](crate::protocols::Protocol::content_chunk_size)
+//! 等方法声明并实现文件上传。
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::config::ResourceConfig;
+use crate::db::Database;
+use crate::device::DeviceEvent;
+use crate::protocols::ContentInfo;
+use crate::utils::{time, DeviceError, Result};
+use crate::web::state::SharedController;
+
+/// 单个分块的最大尝试次数
+const CHUNK_ATTEMPTS: u32 = 3;
+/// 分块重试间隔
+const CHUNK_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// 进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+/// 保留的已结束推送记录数
+const MAX_FINISHED: usize = 100;
+
+/// 推送状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentPushState {
+    /// 上传中
+    Uploading,
+    /// 上传完成，等待设备确认与校验
+    Verifying,
+    Completed,
+    Failed,
+}
+
+/// 内容推送记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ContentPushStatus {
+    pub id: String,
+    pub channel_id: u32,
+    pub material_id: String,
+    /// 推送到设备的文件名
+    pub name: String,
+    pub state: ContentPushState,
+    pub sent_bytes: u64,
+    pub total_bytes: u64,
+    /// 源文件 SHA-256
+    pub sha256: String,
+    /// 设备返回的 SHA-256 是否与源文件一致（设备无法提供校验和时为空）
+    pub verified: Option<bool>,
+    pub error: Option<String>,
+    /// 开始时间（RFC 3339）
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl ContentPushStatus {
+    fn is_running(&self) -> bool {
+        matches!(
+            self.state,
+            ContentPushState::Uploading | ContentPushState::Verifying
+        )
+    }
+}
+
+/// 内容推送管理
+pub struct ContentPusher {
+    database: Arc<Database>,
+    resource_root: PathBuf,
+    controller: SharedController,
+    pushes: DashMap<String, ContentPushStatus>,
+}
+
+impl ContentPusher {
+    pub fn new(
+        database: Arc<Database>,
+        resource: &ResourceConfig,
+        controller: SharedController,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            database,
+            resource_root: PathBuf::from(&resource.path),
+            controller,
+            pushes: DashMap::new(),
+        })
+    }
+
+    /// 开始推送素材到通道，立即返回推送记录，上传在后台进行
+    ///
+    /// 同一通道同时只允许一个推送。
+    pub async fn push(
+        self: &Arc<Self>,
+        channel_id: u32,
+        material_id: &str,
+    ) -> Result<ContentPushStatus> {
+        if self
+            .pushes
+            .iter()
+            .any(|p| p.channel_id == channel_id && p.is_running())
+        {
+            return Err(DeviceError::Other(format!(
+                "通道 {} 正在推送内容，请等待完成",
+                channel_id
+            )));
+        }
+        let chunk_size = self
+            .controller
+            .read()
+            .await
+            .content_chunk_size(channel_id)
+            .await?
+            .ok_or_else(|| {
+                DeviceError::Other(format!("通道 {} 的协议不支持内容推送", channel_id))
+            })?;
+
+        let material = self
+            .database
+            .materials()
+            .find_by_id(material_id)
+            .await
+            .map_err(|e| DeviceError::Other(format!("查询素材失败: {}", e)))?
+            .ok_or_else(|| DeviceError::Other(format!("素材 {} 不存在", material_id)))?;
+        let path = resolve_material_path(&self.resource_root, &material.path)
+            .ok_or_else(|| DeviceError::Other(format!("素材文件不存在: {}", material.path)))?;
+        let size = tokio::fs::metadata(&path).await?.len();
+        let hash_path = path.clone();
+        let sha256 =
+            tokio::task::spawn_blocking(move || crate::resource_sync::sha256_file(&hash_path))
+                .await
+                .map_err(|e| DeviceError::Other(format!("计算校验和失败: {}", e)))??;
+
+        let name = if material.original_name.is_empty() {
+            material.name.clone()
+        } else {
+            material.original_name.clone()
+        };
+        let info = ContentInfo {
+            material_id: material.id.clone(),
+            name: name.clone(),
+            mime_type: material.mime_type.clone(),
+            size,
+            sha256: sha256.clone(),
+        };
+        let status = ContentPushStatus {
+            id: uuid::Uuid::new_v4().to_string(),
+            channel_id,
+            material_id: material.id,
+            name,
+            state: ContentPushState::Uploading,
+            sent_bytes: 0,
+            total_bytes: size,
+            sha256,
+            verified: None,
+            error: None,
+            started_at: time::now_rfc3339(),
+            finished_at: None,
+        };
+        self.pushes.insert(status.id.clone(), status.clone());
+        self.prune();
+
+        info!(
+            "开始推送素材 {} 到通道 {} ({} 字节，分块 {} 字节)",
+            info.material_id, channel_id, size, chunk_size
+        );
+        tokio::spawn(
+            self.clone()
+                .run(status.id.clone(), channel_id, path, info, chunk_size),
+        );
+        Ok(status)
+    }
+
+    /// 获取推送记录
+    pub fn status(&self, id: &str) -> Option<ContentPushStatus> {
+        self.pushes.get(id).map(|p| p.clone())
+    }
+
+    /// 全部推送记录（新的在前）
+    pub fn list(&self) -> Vec<ContentPushStatus> {
+        let mut pushes: Vec<_> = self.pushes.iter().map(|p| p.clone()).collect();
+        pushes.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        pushes
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        id: String,
+        channel_id: u32,
+        path: PathBuf,
+        info: ContentInfo,
+        chunk_size: usize,
+    ) {
+        let result = self.upload(&id, channel_id, &path, &info, chunk_size).await;
+        let error = match result {
+            Ok(verified) => {
+                info!(
+                    "素材 {} 已推送到通道 {}（校验: {}）",
+                    info.material_id,
+                    channel_id,
+                    if verified.is_some() {
+                        "通过"
+                    } else {
+                        "设备不支持"
+                    }
+                );
+                self.update(&id, |p| {
+                    p.state = ContentPushState::Completed;
+                    p.verified = verified;
+                    p.finished_at = Some(time::now_rfc3339());
+                });
+                None
+            }
+            Err(e) => {
+                warn!(
+                    "推送素材 {} 到通道 {} 失败: {}",
+                    info.material_id, channel_id, e
+                );
+                self.update(&id, |p| {
+                    p.state = ContentPushState::Failed;
+                    p.error = Some(e.to_string());
+                    p.finished_at = Some(time::now_rfc3339());
+                });
+                Some(e.to_string())
+            }
+        };
+
+        self.controller
+            .read()
+            .await
+            .publish_event(DeviceEvent::ContentPushFinished {
+                push_id: id,
+                channel_id,
+                material_id: info.material_id,
+                success: error.is_none(),
+                error,
+            });
+    }
+
+    /// 上传并校验，返回校验结果（设备无法提供校验和时为 None）
+    async fn upload(
+        &self,
+        id: &str,
+        channel_id: u32,
+        path: &Path,
+        info: &ContentInfo,
+        chunk_size: usize,
+    ) -> Result<Option<bool>> {
+        let session = self
+            .controller
+            .read()
+            .await
+            .begin_content_upload(channel_id, info)
+            .await?;
+        if let Err(e) = self
+            .send_chunks(id, channel_id, &session, path, info, chunk_size)
+            .await
+        {
+            self.controller
+                .read()
+                .await
+                .abort_content_upload(channel_id, &session)
+                .await;
+            return Err(e);
+        }
+
+        self.update(id, |p| p.state = ContentPushState::Verifying);
+        let device_sha256 = self
+            .controller
+            .read()
+            .await
+            .finish_content_upload(channel_id, &session)
+            .await?;
+        match device_sha256 {
+            Some(sha256) if sha256.eq_ignore_ascii_case(&info.sha256) => Ok(Some(true)),
+            Some(sha256) => {
+                self.update(id, |p| p.verified = Some(false));
+                Err(DeviceError::Other(format!(
+                    "校验失败: 设备 SHA-256 {} 与源文件 {} 不一致",
+                    sha256, info.sha256
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn send_chunks(
+        &self,
+        id: &str,
+        channel_id: u32,
+        session: &str,
+        path: &Path,
+        info: &ContentInfo,
+        chunk_size: usize,
+    ) -> Result<()> {
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut buffer = vec![0u8; chunk_size];
+        let mut offset = 0u64;
+        let mut last_progress: Option<Instant> = None;
+
+        loop {
+            let n = read_chunk(&mut file, &mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            self.send_chunk(channel_id, session, offset, &buffer[..n])
+                .await?;
+            offset += n as u64;
+            self.update(id, |p| p.sent_bytes = offset);
+
+            if offset == info.size || last_progress.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL)
+            {
+                last_progress = Some(Instant::now());
+                self.controller
+                    .read()
+                    .await
+                    .publish_event(DeviceEvent::ContentPushProgress {
+                        push_id: id.to_string(),
+                        channel_id,
+                        material_id: info.material_id.clone(),
+                        sent_bytes: offset,
+                        total_bytes: info.size,
+                    });
+            }
+        }
+
+        if offset != info.size {
+            return Err(DeviceError::Other("素材文件在推送期间发生变化".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn send_chunk(
+        &self,
+        channel_id: u32,
+        session: &str,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .controller
+                .read()
+                .await
+                .upload_content_chunk(channel_id, session, offset, data)
+                .await;
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < CHUNK_ATTEMPTS => {
+                    warn!(
+                        "通道 {} 分块（偏移 {}）上传失败，第 {} 次重试: {}",
+                        channel_id, offset, attempt, e
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(CHUNK_RETRY_DELAY).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut ContentPushStatus)) {
+        if let Some(mut push) = self.pushes.get_mut(id) {
+            f(&mut push);
+        }
+    }
+
+    /// 只保留最近的 MAX_FINISHED 条已结束记录
+    fn prune(&self) {
+        let mut finished: Vec<(String, String)> = self
+            .pushes
+            .iter()
+            .filter(|p| !p.is_running())
+            .map(|p| (p.started_at.clone(), p.id.clone()))
+            .collect();
+        if finished.len() <= MAX_FINISHED {
+            return;
+        }
+        finished.sort();
+        for (_, id) in finished.iter().take(finished.len() - MAX_FINISHED) {
+            self.pushes.remove(id);
+        }
+    }
+}
+
+/// 读取一个完整分块（文件末尾可能不足一块），返回读取的字节数
+async fn read_chunk(file: &mut tokio::fs::File, buffer: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let n = file.read(&mut buffer[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// 素材路径转换为资源目录下的文件路径，拒绝目录之外的路径
+fn resolve_material_path(root: &Path, relative: &str) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let path = root
+        .join(relative.trim_start_matches('/'))
+        .canonicalize()
+        .ok()?;
+    (path.starts_with(&root) && path.is_file()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_only_files_inside_resource_root() {
+        let root = std::env::temp_dir().join(format!("content_push_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("video")).unwrap();
+        std::fs::write(root.join("video/a.mp4"), b"data").unwrap();
+
+        assert!(resolve_material_path(&root, "/video/a.mp4").is_some());
+        assert!(resolve_material_path(&root, "video/missing.mp4").is_none());
+        assert!(resolve_material_path(&root, "video").is_none());
+        assert!(resolve_material_path(&root.join("video"), "../video/a.mp4").is_some());
+        assert!(resolve_material_path(&root.join("video"), "../../etc/passwd").is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,203 @@
+//! 配置版本仓库 - 数据库配置源
+//!
+//! 通道、节点与场景以 JSON 整体保存为一个版本，最新版本即当前配置；
+//! 每次保存追加新版本，超过 `history_limit` 的旧版本被清理。
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+
+use super::models::{ConfigRevision, ConfigRevisionSummary};
+use crate::config::{ChannelConfig, Config, ConfigStoreConfig, NodeConfig, SceneConfig};
+
+/// 数据库中保存的配置内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceConfigContent {
+    pub channels: Vec<ChannelConfig>,
+    pub nodes: Vec<NodeConfig>,
+    pub scenes: Vec<SceneConfig>,
+}
+
+impl DeviceConfigContent {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            channels: config.channels.clone(),
+            nodes: config.nodes.clone(),
+            scenes: config.scenes.clone(),
+        }
+    }
+
+    /// 用数据库中的通道、节点与场景替换配置中的对应部分，其余配置保持不变
+    pub fn apply_to(self, config: &mut Config) {
+        config.channels = self.channels;
+        config.nodes = self.nodes;
+        config.scenes = self.scenes;
+    }
+}
+
+/// 配置版本仓库
+#[derive(Clone)]
+pub struct ConfigRepository {
+    pool: MySqlPool,
+}
+
+impl ConfigRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// 获取最新版本
+    pub async fn find_latest(&self) -> Result<Option<ConfigRevision>> {
+        let revision = sqlx::query_as::<_, ConfigRevision>(
+            "SELECT id, content, operator, comment, created_at FROM lspc_config_revision ORDER BY id DESC LIMIT 1"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(revision)
+    }
+
+    /// 根据版本号获取
+    pub async fn find_by_id(&self, id: i64) -> Result<Option<ConfigRevision>> {
+        let revision = sqlx::query_as::<_, ConfigRevision>(
+            "SELECT id, content, operator, comment, created_at FROM lspc_config_revision WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(revision)
+    }
+
+    /// 获取最近的版本摘要（新版本在前）
+    pub async fn list(&self, limit: u32) -> Result<Vec<ConfigRevisionSummary>> {
+        let revisions = sqlx::query_as::<_, ConfigRevisionSummary>(
+            "SELECT id, operator, comment, created_at FROM lspc_config_revision ORDER BY id DESC LIMIT ?"
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(revisions)
+    }
+
+    /// 追加版本，返回版本号
+    pub async fn create(&self, content: &str, operator: &str, comment: &str) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO lspc_config_revision (content, operator, comment, created_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(content)
+        .bind(operator)
+        .bind(comment)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    /// 只保留最新的 `keep` 个版本，返回删除数量
+    pub async fn prune(&self, keep: u32) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM lspc_config_revision WHERE id <= (SELECT id FROM (SELECT id FROM lspc_config_revision ORDER BY id DESC LIMIT 1 OFFSET ?) AS t)"
+        )
+        .bind(keep)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// 数据库配置源
+pub struct ConfigStore {
+    repo: ConfigRepository,
+    history_limit: u32,
+}
+
+impl ConfigStore {
+    pub fn new(repo: ConfigRepository, config: &ConfigStoreConfig) -> Self {
+        Self {
+            repo,
+            history_limit: config.history_limit,
+        }
+    }
+
+    /// 用数据库最新版本替换配置中的通道、节点与场景，返回版本号
+    ///
+    /// 数据库中还没有版本时，以当前配置（来自配置文件）初始化第一个版本。
+    pub async fn load_into(&self, config: &mut Config) -> Result<i64> {
+        match self.repo.find_latest().await? {
+            Some(revision) => {
+                let content: DeviceConfigContent = serde_json::from_str(&revision.content)
+                    .with_context(|| format!("解析配置版本 {} 失败", revision.id))?;
+                content.apply_to(config);
+                Ok(revision.id)
+            }
+            None => {
+                let id = self.save(config, "system", "从配置文件初始化").await?;
+                tracing::info!("数据库中没有配置，已从配置文件初始化版本 {}", id);
+                Ok(id)
+            }
+        }
+    }
+
+    /// 保存配置中的通道、节点与场景为新版本，返回版本号
+    pub async fn save(&self, config: &Config, operator: &str, comment: &str) -> Result<i64> {
+        let content = serde_json::to_string(&DeviceConfigContent::from_config(config))?;
+        let id = self.repo.create(&content, operator, comment).await?;
+        if self.history_limit > 0 {
+            let pruned = self.repo.prune(self.history_limit).await?;
+            if pruned > 0 {
+                tracing::debug!("已清理 {} 个旧配置版本", pruned);
+            }
+        }
+        Ok(id)
+    }
+
+    /// 最新版本号（数据库为空时为 None）
+    pub async fn latest_id(&self) -> Result<Option<i64>> {
+        Ok(self.repo.find_latest().await?.map(|r| r.id))
+    }
+
+    /// 历史版本摘要
+    pub async fn history(&self, limit: u32) -> Result<Vec<ConfigRevisionSummary>> {
+        self.repo.list(limit).await
+    }
+
+    /// 获取指定版本的内容
+    pub async fn revision(&self, id: i64) -> Result<Option<(ConfigRevision, DeviceConfigContent)>> {
+        let Some(revision) = self.repo.find_by_id(id).await? else {
+            return Ok(None);
+        };
+        let content = serde_json::from_str(&revision.content)
+            .with_context(|| format!("解析配置版本 {} 失败", revision.id))?;
+        Ok(Some((revision, content)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_replaces_only_device_sections() {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "channels": [],
+            "nodes": [],
+            "scenes": [],
+            "web_server": { "port": 18080 }
+        }))
+        .unwrap();
+        let content: DeviceConfigContent = serde_json::from_value(serde_json::json!({
+            "channels": [{ "channel_id": 1, "enable": true, "statute": "mock" }],
+            "nodes": [{ "global_id": 10, "channel_id": 1, "id": 1, "category": "light", "alias": "灯光" }],
+            "scenes": []
+        }))
+        .unwrap();
+
+        content.apply_to(&mut config);
+        assert_eq!(config.channels.len(), 1);
+        assert_eq!(config.nodes[0].alias, "灯光");
+        assert_eq!(config.web_server.port, 18080);
+
+        let saved = serde_json::to_value(DeviceConfigContent::from_config(&config)).unwrap();
+        assert_eq!(saved["nodes"][0]["global_id"], 10);
+    }
+}
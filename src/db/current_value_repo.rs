@@ -0,0 +1,104 @@
+//! 节点当前值仓库 - 外部报表工具通过 SQL 查询设备当前状态
+//!
+//! 每个节点一行，按 `global_id` upsert。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+
+/// 表名
+pub const CURRENT_VALUES_TABLE: &str = "lspc_current_values";
+
+/// lspc_current_values 表的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrentValue {
+    pub global_id: u32,
+    pub channel_id: u32,
+    pub alias: String,
+    pub value: i32,
+    /// 当前值为调试强制值
+    pub forced: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 节点当前值仓库
+#[derive(Clone)]
+pub struct CurrentValueRepository {
+    pool: MySqlPool,
+}
+
+impl CurrentValueRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// 表不存在时创建（结构与 doc/database.sql 相同）
+    pub async fn ensure_table(&self) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS `{}` (\
+                `global_id` INT UNSIGNED NOT NULL PRIMARY KEY, \
+                `channel_id` INT UNSIGNED NOT NULL, \
+                `alias` VARCHAR(255) NOT NULL DEFAULT '', \
+                `value` INT NOT NULL, \
+                `forced` TINYINT(1) NOT NULL DEFAULT 0, \
+                `updated_at` DATETIME(3) NOT NULL\
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci",
+            CURRENT_VALUES_TABLE
+        ))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 批量写入（已存在的节点更新为新值）
+    pub async fn upsert_many(&self, rows: &[CurrentValue]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        upsert_query(rows).build().execute(&self.pool).await?;
+        Ok(())
+    }
+}
+
+fn upsert_query(rows: &[CurrentValue]) -> QueryBuilder<'_, MySql> {
+    let mut query = QueryBuilder::new(format!(
+        "INSERT INTO {} (global_id, channel_id, alias, value, forced, updated_at) ",
+        CURRENT_VALUES_TABLE
+    ));
+    query.push_values(rows, |mut row, v| {
+        row.push_bind(v.global_id)
+            .push_bind(v.channel_id)
+            .push_bind(&v.alias)
+            .push_bind(v.value)
+            .push_bind(v.forced)
+            .push_bind(v.updated_at);
+    });
+    query.push(
+        " ON DUPLICATE KEY UPDATE channel_id = VALUES(channel_id), alias = VALUES(alias), \
+         value = VALUES(value), forced = VALUES(forced), updated_at = VALUES(updated_at)",
+    );
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_batched_upsert() {
+        let row = |global_id| CurrentValue {
+            global_id,
+            channel_id: 1,
+            alias: format!("节点{}", global_id),
+            value: 1,
+            forced: false,
+            updated_at: Utc::now(),
+        };
+        let rows = [row(1), row(2)];
+        let sql = upsert_query(&rows).into_sql();
+        assert!(sql.starts_with("INSERT INTO lspc_current_values (global_id,"));
+        assert!(
+            sql.contains("VALUES (?, ?, ?, ?, ?, ?), (?, ?, ?, ?, ?, ?) ON DUPLICATE KEY UPDATE")
+        );
+    }
+}
@@ -1,14 +1,21 @@
 //! 数据库模块
 
+pub mod config_repo;
+pub mod current_value_repo;
 pub mod material_repo;
 pub mod models;
+pub mod playlist_repo;
 pub mod screen_repo;
+pub mod write_behind;
 
 use anyhow::Result;
 use sqlx::mysql::MySqlPool;
 
+pub use config_repo::{ConfigRepository, ConfigStore, DeviceConfigContent};
+pub use current_value_repo::{CurrentValue, CurrentValueRepository};
 pub use material_repo::MaterialRepository;
 pub use models::*;
+pub use playlist_repo::PlaylistRepository;
 pub use screen_repo::ScreenRepository;
 
 /// 数据库连接池
@@ -41,6 +48,21 @@ impl Database {
         ScreenRepository::new(self.pool.clone())
     }
 
+    /// 获取配置版本仓库
+    pub fn configs(&self) -> ConfigRepository {
+        ConfigRepository::new(self.pool.clone())
+    }
+
+    /// 获取节点当前值仓库
+    pub fn current_values(&self) -> CurrentValueRepository {
+        CurrentValueRepository::new(self.pool.clone())
+    }
+
+    /// 获取 Playlist 仓库
+    pub fn playlists(&self) -> PlaylistRepository {
+        PlaylistRepository::new(self.pool.clone())
+    }
+
     /// 获取 Material 仓库
     pub fn materials(&self) -> MaterialRepository {
         let repo = MaterialRepository::new(self.pool.clone());
@@ -92,6 +92,10 @@ fn generate_uuid() -> String {
     uuid::Uuid::new_v4().to_string()
 }
 
+fn default_true() -> bool {
+    true
+}
+
 /// 更新 Screen 请求
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateScreenRequest {
@@ -299,3 +303,132 @@ pub struct UpdateSettingRequest {
     /// 设置值（可选）
     pub value: Option<String>,
 }
+
+/// lspc_config_revision 表模型（通道/节点/场景配置版本）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ConfigRevision {
+    /// 版本号（自增）
+    pub id: i64,
+    /// 配置内容（JSON：channels / nodes / scenes）
+    pub content: String,
+    /// 保存人
+    pub operator: String,
+    /// 备注
+    pub comment: String,
+    /// 保存时间
+    pub created_at: DateTime<Utc>,
+}
+
+/// 配置版本摘要（历史列表）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ConfigRevisionSummary {
+    /// 版本号
+    pub id: i64,
+    /// 保存人
+    pub operator: String,
+    /// 备注
+    pub comment: String,
+    /// 保存时间
+    pub created_at: DateTime<Utc>,
+}
+
+/// lspc_playlist 表模型
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Playlist {
+    /// 播放列表唯一标识
+    pub id: String,
+    /// 名称
+    pub name: String,
+    /// 所属屏幕ID
+    pub screen_id: String,
+    /// 是否参与自动调度
+    pub active: bool,
+    /// 生效开始时间（为空表示不限）
+    pub valid_from: Option<DateTime<Utc>>,
+    /// 生效结束时间（为空表示不限）
+    pub valid_until: Option<DateTime<Utc>>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Playlist {
+    /// 指定时间是否在生效日期范围内
+    pub fn is_valid_at(&self, now: DateTime<Utc>) -> bool {
+        self.valid_from.is_none_or(|from| from <= now)
+            && self.valid_until.is_none_or(|until| now < until)
+    }
+}
+
+/// lspc_playlist_item 表模型
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct PlaylistItem {
+    /// 所属播放列表ID
+    pub playlist_id: String,
+    /// 播放顺序（从 0 开始）
+    pub position: i32,
+    /// 素材ID
+    pub material_id: String,
+    /// 播放时长（毫秒）
+    pub duration_ms: i64,
+}
+
+/// 播放列表（含条目）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlaylistResponse {
+    #[serde(flatten)]
+    pub playlist: Playlist,
+    /// 按播放顺序排列的条目
+    pub items: Vec<PlaylistItem>,
+}
+
+/// 播放列表条目请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlaylistItemRequest {
+    /// 素材ID
+    pub material_id: String,
+    /// 播放时长（毫秒）
+    pub duration_ms: i64,
+}
+
+/// 创建播放列表请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatePlaylistRequest {
+    /// 播放列表ID（可选，不提供则自动生成）
+    #[serde(default = "generate_uuid")]
+    pub id: String,
+    /// 名称
+    pub name: String,
+    /// 所属屏幕ID
+    pub screen_id: String,
+    /// 是否参与自动调度
+    #[serde(default = "default_true")]
+    pub active: bool,
+    /// 生效开始时间
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    /// 生效结束时间
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// 条目（按播放顺序）
+    #[serde(default)]
+    pub items: Vec<PlaylistItemRequest>,
+}
+
+/// 更新播放列表请求（未提供的字段保持不变，提供 items 时整体替换条目）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePlaylistRequest {
+    /// 名称（可选）
+    pub name: Option<String>,
+    /// 所属屏幕ID（可选）
+    pub screen_id: Option<String>,
+    /// 是否参与自动调度（可选）
+    pub active: Option<bool>,
+    /// 生效开始时间（可选）
+    pub valid_from: Option<DateTime<Utc>>,
+    /// 生效结束时间（可选）
+    pub valid_until: Option<DateTime<Utc>>,
+    /// 条目（可选）
+    pub items: Option<Vec<PlaylistItemRequest>>,
+}
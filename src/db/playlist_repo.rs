@@ -0,0 +1,211 @@
+//! Playlist 数据仓库
+
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::MySqlPool;
+
+use super::models::{
+    CreatePlaylistRequest, Playlist, PlaylistItem, PlaylistItemRequest, PlaylistResponse,
+    UpdatePlaylistRequest,
+};
+
+const PLAYLIST_COLUMNS: &str =
+    "id, name, screen_id, active, valid_from, valid_until, created_at, updated_at";
+
+/// Playlist 仓库
+#[derive(Clone)]
+pub struct PlaylistRepository {
+    pool: MySqlPool,
+}
+
+impl PlaylistRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// 获取所有播放列表（含条目）
+    pub async fn find_all(&self) -> Result<Vec<PlaylistResponse>> {
+        let playlists = sqlx::query_as::<_, Playlist>(&format!(
+            "SELECT {} FROM lspc_playlist ORDER BY created_at",
+            PLAYLIST_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        self.with_items(playlists).await
+    }
+
+    /// 获取屏幕的播放列表（含条目）
+    pub async fn find_by_screen_id(&self, screen_id: &str) -> Result<Vec<PlaylistResponse>> {
+        let playlists = sqlx::query_as::<_, Playlist>(&format!(
+            "SELECT {} FROM lspc_playlist WHERE screen_id = ? ORDER BY created_at",
+            PLAYLIST_COLUMNS
+        ))
+        .bind(screen_id)
+        .fetch_all(&self.pool)
+        .await?;
+        self.with_items(playlists).await
+    }
+
+    /// 根据 ID 获取播放列表（含条目）
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<PlaylistResponse>> {
+        let playlist = sqlx::query_as::<_, Playlist>(&format!(
+            "SELECT {} FROM lspc_playlist WHERE id = ?",
+            PLAYLIST_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        match playlist {
+            Some(playlist) => Ok(self.with_items(vec![playlist]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    /// 创建播放列表
+    pub async fn create(&self, req: &CreatePlaylistRequest) -> Result<PlaylistResponse> {
+        let now = Utc::now();
+        let id = if req.id.is_empty() {
+            uuid::Uuid::new_v4().to_string()
+        } else {
+            req.id.clone()
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO lspc_playlist (id, name, screen_id, active, valid_from, valid_until, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&id)
+        .bind(&req.name)
+        .bind(&req.screen_id)
+        .bind(req.active)
+        .bind(req.valid_from)
+        .bind(req.valid_until)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        let items = Self::insert_items(&mut tx, &id, &req.items).await?;
+        tx.commit().await?;
+
+        Ok(PlaylistResponse {
+            playlist: Playlist {
+                id,
+                name: req.name.clone(),
+                screen_id: req.screen_id.clone(),
+                active: req.active,
+                valid_from: req.valid_from,
+                valid_until: req.valid_until,
+                created_at: now,
+                updated_at: now,
+            },
+            items,
+        })
+    }
+
+    /// 更新播放列表（提供 items 时整体替换条目）
+    pub async fn update(
+        &self,
+        id: &str,
+        req: &UpdatePlaylistRequest,
+    ) -> Result<Option<PlaylistResponse>> {
+        let Some(existing) = self.find_by_id(id).await? else {
+            return Ok(None);
+        };
+        let existing = existing.playlist;
+
+        let now = Utc::now();
+        let playlist = Playlist {
+            id: existing.id,
+            name: req.name.clone().unwrap_or(existing.name),
+            screen_id: req.screen_id.clone().unwrap_or(existing.screen_id),
+            active: req.active.unwrap_or(existing.active),
+            valid_from: req.valid_from.or(existing.valid_from),
+            valid_until: req.valid_until.or(existing.valid_until),
+            created_at: existing.created_at,
+            updated_at: now,
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE lspc_playlist SET name = ?, screen_id = ?, active = ?, valid_from = ?, valid_until = ?, updated_at = ? WHERE id = ?"
+        )
+        .bind(&playlist.name)
+        .bind(&playlist.screen_id)
+        .bind(playlist.active)
+        .bind(playlist.valid_from)
+        .bind(playlist.valid_until)
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(ref items) = req.items {
+            sqlx::query("DELETE FROM lspc_playlist_item WHERE playlist_id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            Self::insert_items(&mut tx, id, items).await?;
+        }
+        tx.commit().await?;
+
+        Ok(self.find_by_id(id).await?.map(|response| PlaylistResponse {
+            playlist,
+            items: response.items,
+        }))
+    }
+
+    /// 删除播放列表及其条目
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM lspc_playlist_item WHERE playlist_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM lspc_playlist WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_items(
+        tx: &mut sqlx::Transaction<'_, sqlx::MySql>,
+        playlist_id: &str,
+        items: &[PlaylistItemRequest],
+    ) -> Result<Vec<PlaylistItem>> {
+        let mut inserted = Vec::with_capacity(items.len());
+        for (position, item) in items.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO lspc_playlist_item (playlist_id, position, material_id, duration_ms) VALUES (?, ?, ?, ?)"
+            )
+            .bind(playlist_id)
+            .bind(position as i32)
+            .bind(&item.material_id)
+            .bind(item.duration_ms)
+            .execute(&mut **tx)
+            .await?;
+            inserted.push(PlaylistItem {
+                playlist_id: playlist_id.to_string(),
+                position: position as i32,
+                material_id: item.material_id.clone(),
+                duration_ms: item.duration_ms,
+            });
+        }
+        Ok(inserted)
+    }
+
+    /// 查询播放列表的条目
+    async fn with_items(&self, playlists: Vec<Playlist>) -> Result<Vec<PlaylistResponse>> {
+        let mut results = Vec::with_capacity(playlists.len());
+        for playlist in playlists {
+            let items = sqlx::query_as::<_, PlaylistItem>(
+                "SELECT playlist_id, position, material_id, duration_ms FROM lspc_playlist_item WHERE playlist_id = ? ORDER BY position"
+            )
+            .bind(&playlist.id)
+            .fetch_all(&self.pool)
+            .await?;
+            results.push(PlaylistResponse { playlist, items });
+        }
+        Ok(results)
+    }
+}
@@ -0,0 +1,149 @@
+//! 节点当前值写入数据库（write-behind）
+//!
+//! 监听节点状态变化，按节点合并后定时或攒满一批时 upsert 到 `lspc_current_values`，
+//! 不阻塞设备读写。启动、配置热重载与事件积压丢失后补写全部节点的当前值；
+//! 写入失败时保留待写入的值，下次合并写入时重试。
+
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use super::current_value_repo::{CurrentValue, CurrentValueRepository};
+use super::Database;
+use crate::config::CurrentValuesConfig;
+use crate::device::{DeviceController, DeviceEvent};
+use crate::web::state::SharedController;
+
+/// 节点当前值写入任务
+struct CurrentValueWriter {
+    repo: CurrentValueRepository,
+    batch_size: usize,
+    flush_interval: Duration,
+    controller: SharedController,
+    /// global_id -> 待写入的行（同一节点只保留最后的值）
+    pending: BTreeMap<u32, CurrentValue>,
+}
+
+/// 按配置启动节点当前值写入（未启用时不启动）
+pub fn start(
+    config: Option<&CurrentValuesConfig>,
+    database: &Database,
+    controller: SharedController,
+) {
+    let Some(config) = config.filter(|c| c.enable) else {
+        return;
+    };
+    let writer = CurrentValueWriter {
+        repo: database.current_values(),
+        batch_size: config.batch_size.max(1),
+        flush_interval: Duration::from_millis(config.flush_interval_ms.max(100)),
+        controller,
+        pending: BTreeMap::new(),
+    };
+    info!(
+        "节点当前值写入数据库已启用: 每 {}ms 或每 {} 个节点写入一次",
+        writer.flush_interval.as_millis(),
+        writer.batch_size
+    );
+    tokio::spawn(writer.run());
+}
+
+impl CurrentValueWriter {
+    async fn run(mut self) {
+        if let Err(e) = self.repo.ensure_table().await {
+            warn!("创建节点当前值表失败: {:?}", e);
+        }
+        let mut ticker = tokio::time::interval(self.flush_interval);
+
+        // 配置热重载替换控制器后事件总线关闭，重新订阅并补写全部节点
+        loop {
+            let (mut event_rx, nodes) = {
+                let shared = self.controller.clone();
+                let controller = shared.read().await;
+                self.queue_all(&controller);
+                let nodes: HashMap<u32, (u32, String)> = controller
+                    .get_all_node_configs()
+                    .into_iter()
+                    .map(|n| (n.global_id, (n.channel_id, n.alias)))
+                    .collect();
+                (controller.subscribe_events(), nodes)
+            };
+            loop {
+                tokio::select! {
+                    event = event_rx.recv() => match event {
+                        Ok(DeviceEvent::NodeStateChanged { global_id, new_value, forced, .. }) => {
+                            let Some((channel_id, alias)) = nodes.get(&global_id) else {
+                                continue;
+                            };
+                            self.pending.insert(global_id, CurrentValue {
+                                global_id,
+                                channel_id: *channel_id,
+                                alias: alias.clone(),
+                                value: new_value,
+                                forced,
+                                updated_at: Utc::now(),
+                            });
+                            if self.pending.len() >= self.batch_size {
+                                self.flush().await;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(n)) => {
+                            crate::device::record_lagged_events(n);
+                            warn!("节点当前值写入任务落后，丢失 {} 个事件，补写全部节点", n);
+                            break;
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = ticker.tick() => self.flush().await,
+                }
+            }
+        }
+    }
+
+    /// 加入全部已知当前值的节点
+    fn queue_all(&mut self, controller: &DeviceController) {
+        let now = Utc::now();
+        for (global_id, state) in controller.get_all_node_states() {
+            if let Some(value) = state.current_value {
+                self.pending.insert(
+                    global_id,
+                    CurrentValue {
+                        global_id,
+                        channel_id: state.channel_id,
+                        alias: state.alias,
+                        value,
+                        forced: state.forced,
+                        updated_at: now,
+                    },
+                );
+            }
+        }
+    }
+
+    /// 分批写入待写入的值；写入失败时保留，等待下次重试
+    async fn flush(&mut self) {
+        while !self.pending.is_empty() {
+            let batch: Vec<CurrentValue> = self
+                .pending
+                .values()
+                .take(self.batch_size)
+                .cloned()
+                .collect();
+            match self.repo.upsert_many(&batch).await {
+                Ok(()) => {
+                    debug!("写入 {} 个节点当前值", batch.len());
+                    for row in &batch {
+                        self.pending.remove(&row.global_id);
+                    }
+                }
+                Err(e) => {
+                    warn!("写入节点当前值失败: {:?}", e);
+                    return;
+                }
+            }
+        }
+    }
+}
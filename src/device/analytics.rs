@@ -0,0 +1,296 @@
+//! 节点值异常分析 - 卡死值与变化率检测
+//!
+//! - 卡死：节点值超过 `stuck_after_ms` 没有变化，但期间仍被成功轮询（疑似传感器失效）
+//! - 变化过快：相邻两次变化的速率（变化量 / 间隔秒数）超过 `max_rate`（疑似接线故障）
+//!
+//! 异常出现与消除时分别发送 `node_anomaly` / `node_anomaly_cleared` 事件。
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use super::node_manager::NodeManager;
+use super::DeviceEvent;
+use crate::config::{AnalyticsConfig, NodeConfig};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::time;
+
+/// 异常类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// 持续轮询但值长时间不变
+    StuckValue,
+    /// 变化快于物理上可能
+    ImplausibleRate,
+}
+
+/// 节点当前异常
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NodeAnomaly {
+    pub global_id: u32,
+    pub alias: String,
+    pub kind: AnomalyKind,
+    pub detail: String,
+    /// 检测到的时间（RFC 3339）
+    pub detected_at: String,
+}
+
+/// 异常分析报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnalyticsReport {
+    /// 是否启用分析
+    pub enabled: bool,
+    /// 参与分析的节点数
+    pub tracked_nodes: usize,
+    /// 当前异常（按节点排序）
+    pub anomalies: Vec<NodeAnomaly>,
+}
+
+/// 单个节点的检测阈值
+#[derive(Debug, Clone, Copy)]
+struct Thresholds {
+    stuck_after: Option<Duration>,
+    max_rate: Option<f64>,
+}
+
+/// 节点值异常分析器
+pub struct NodeAnalytics {
+    /// global_id -> (别名, 阈值)
+    nodes: HashMap<u32, (String, Thresholds)>,
+    /// 上次值变化时间（启动时视为变化）
+    last_change: DashMap<u32, Instant>,
+    anomalies: DashMap<(u32, AnomalyKind), NodeAnomaly>,
+    node_manager: Arc<NodeManager>,
+    event_tx: broadcast::Sender<DeviceEvent>,
+}
+
+impl NodeAnalytics {
+    pub(crate) fn new(
+        config: &AnalyticsConfig,
+        nodes: &[NodeConfig],
+        node_manager: Arc<NodeManager>,
+        event_tx: broadcast::Sender<DeviceEvent>,
+    ) -> Self {
+        let now = Instant::now();
+        let nodes: HashMap<u32, (String, Thresholds)> = nodes
+            .iter()
+            .filter(|n| config.nodes.is_empty() || config.nodes.contains(&n.global_id))
+            .map(|n| (n.global_id, (n.alias.clone(), thresholds(config, n))))
+            .collect();
+        let last_change = nodes.keys().map(|id| (*id, now)).collect();
+        info!("节点值异常分析: {} 个节点", nodes.len());
+
+        Self {
+            nodes,
+            last_change,
+            anomalies: DashMap::new(),
+            node_manager,
+            event_tx,
+        }
+    }
+
+    /// 启动分析任务：监听值变化检测变化率，并定期检查卡死值
+    pub(crate) fn spawn(
+        self: &Arc<Self>,
+        tasks: &TaskRegistry,
+        check_interval: Duration,
+        mut event_rx: broadcast::Receiver<DeviceEvent>,
+    ) {
+        let analytics = self.clone();
+        tasks.spawn("node-analytics", async move {
+            let mut ticker = tokio::time::interval(check_interval.max(Duration::from_secs(1)));
+            loop {
+                tokio::select! {
+                    event = event_rx.recv() => match event {
+                        Ok(DeviceEvent::NodeStateChanged { global_id, old_value, new_value, forced: false }) => {
+                            analytics.on_change(global_id, old_value, new_value);
+                        }
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            super::record_lagged_events(n);
+                            warn!("节点值异常分析任务落后，丢失 {} 个事件", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = ticker.tick() => analytics.check_stuck(),
+                }
+            }
+        });
+    }
+
+    /// 当前异常报告
+    pub fn report(&self) -> AnalyticsReport {
+        let mut anomalies: Vec<NodeAnomaly> =
+            self.anomalies.iter().map(|a| a.value().clone()).collect();
+        anomalies.sort_by_key(|a| (a.global_id, a.kind as u8));
+        AnalyticsReport {
+            enabled: true,
+            tracked_nodes: self.nodes.len(),
+            anomalies,
+        }
+    }
+
+    fn on_change(&self, global_id: u32, old_value: i32, new_value: i32) {
+        let Some((_, thresholds)) = self.nodes.get(&global_id) else {
+            return;
+        };
+        let now = Instant::now();
+        let previous = self.last_change.insert(global_id, now);
+        self.clear(global_id, AnomalyKind::StuckValue);
+
+        let (Some(max_rate), Some(previous)) = (thresholds.max_rate, previous) else {
+            return;
+        };
+        let rate = rate_per_sec(old_value, new_value, now - previous);
+        if rate > max_rate {
+            self.raise(
+                global_id,
+                AnomalyKind::ImplausibleRate,
+                format!(
+                    "值 {} -> {} 变化速率 {:.2}/秒，超过上限 {}/秒",
+                    old_value, new_value, rate, max_rate
+                ),
+            );
+        } else {
+            self.clear(global_id, AnomalyKind::ImplausibleRate);
+        }
+    }
+
+    fn check_stuck(&self) {
+        let now = Instant::now();
+        for (global_id, (_, thresholds)) in &self.nodes {
+            let Some(stuck_after) = thresholds.stuck_after else {
+                continue;
+            };
+            let Some(last_change) = self.last_change.get(global_id).map(|t| *t) else {
+                continue;
+            };
+            let Some(state) = self.node_manager.get_state(*global_id) else {
+                continue;
+            };
+            if !state.online || !is_stuck(last_change, state.last_update, now, stuck_after) {
+                continue;
+            }
+            self.raise(
+                *global_id,
+                AnomalyKind::StuckValue,
+                format!(
+                    "值 {} 已 {} 秒未变化（期间仍在轮询）",
+                    state
+                        .current_value
+                        .map_or_else(|| "-".to_string(), |v| v.to_string()),
+                    (now - last_change).as_secs()
+                ),
+            );
+        }
+    }
+
+    /// 记录异常，首次出现时发送事件
+    fn raise(&self, global_id: u32, kind: AnomalyKind, detail: String) {
+        if self.anomalies.contains_key(&(global_id, kind)) {
+            return;
+        }
+        let alias = self
+            .nodes
+            .get(&global_id)
+            .map(|(alias, _)| alias.clone())
+            .unwrap_or_default();
+        warn!("节点 {} ({}) 异常: {}", global_id, alias, detail);
+        let _ = self.event_tx.send(DeviceEvent::NodeAnomaly {
+            global_id,
+            kind,
+            detail: detail.clone(),
+        });
+        self.anomalies.insert(
+            (global_id, kind),
+            NodeAnomaly {
+                global_id,
+                alias,
+                kind,
+                detail,
+                detected_at: time::now_rfc3339(),
+            },
+        );
+    }
+
+    /// 消除异常并发送事件
+    fn clear(&self, global_id: u32, kind: AnomalyKind) {
+        if self.anomalies.remove(&(global_id, kind)).is_some() {
+            info!("节点 {} 异常已消除: {:?}", global_id, kind);
+            let _ = self
+                .event_tx
+                .send(DeviceEvent::NodeAnomalyCleared { global_id, kind });
+        }
+    }
+}
+
+/// 节点阈值：metadata 中的 `stuck_after_ms` / `max_rate` 优先于全局配置
+fn thresholds(config: &AnalyticsConfig, node: &NodeConfig) -> Thresholds {
+    let metadata = node.metadata.as_ref();
+    let stuck_after_ms = metadata
+        .and_then(|m| m.get("stuck_after_ms"))
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(config.stuck_after_ms);
+    let max_rate = metadata
+        .and_then(|m| m.get("max_rate"))
+        .and_then(serde_json::Value::as_f64)
+        .or(config.max_rate);
+    Thresholds {
+        stuck_after: (stuck_after_ms > 0).then(|| Duration::from_millis(stuck_after_ms)),
+        max_rate,
+    }
+}
+
+/// 每秒变化量（绝对值），间隔过短时按 1 毫秒计算
+fn rate_per_sec(old_value: i32, new_value: i32, elapsed: Duration) -> f64 {
+    let delta = (new_value as f64 - old_value as f64).abs();
+    delta / elapsed.as_secs_f64().max(0.001)
+}
+
+/// 值在 `stuck_after` 内未变化，且最近一次轮询发生在这段时间之后
+fn is_stuck(
+    last_change: Instant,
+    last_poll: Option<Instant>,
+    now: Instant,
+    stuck_after: Duration,
+) -> bool {
+    let deadline = last_change + stuck_after;
+    now >= deadline && last_poll.is_some_and(|poll| poll >= deadline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_rates_and_stuck_values() {
+        assert_eq!(rate_per_sec(10, 40, Duration::from_secs(2)), 15.0);
+        assert_eq!(rate_per_sec(40, 10, Duration::from_secs(3)), 10.0);
+
+        let start = Instant::now();
+        let hour = Duration::from_secs(3600);
+        let now = start + hour + Duration::from_secs(60);
+        // 超时后仍在轮询
+        assert!(is_stuck(start, Some(now), now, hour));
+        // 超时前最后一次轮询（通道断开等），不视为卡死
+        assert!(!is_stuck(
+            start,
+            Some(start + Duration::from_secs(60)),
+            now,
+            hour
+        ));
+        assert!(!is_stuck(start, None, now, hour));
+        assert!(!is_stuck(
+            start,
+            Some(start),
+            start + Duration::from_secs(60),
+            hour
+        ));
+    }
+}
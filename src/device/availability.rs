@@ -0,0 +1,114 @@
+//! 通道可用时段
+//!
+//! 某些设备夜间会物理断电，时段外通道标记为计划离线：不与设备通信、暂停轮询，
+//! 写入进入任务队列等待时段开始。
+
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime};
+
+use crate::config::AvailabilityWindow;
+use crate::utils::{DeviceError, Result};
+
+/// 解析后的单个时段
+#[derive(Debug, Clone)]
+struct Window {
+    /// 生效星期（1=周一 … 7=周日），None 为每天
+    days: Option<Vec<u32>>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl Window {
+    fn applies_on(&self, weekday: u32) -> bool {
+        self.days
+            .as_ref()
+            .is_none_or(|days| days.contains(&weekday))
+    }
+}
+
+/// 通道可用时段表
+#[derive(Debug, Clone)]
+pub struct ChannelAvailability {
+    windows: Vec<Window>,
+}
+
+impl ChannelAvailability {
+    /// 从配置解析
+    pub fn from_config(windows: &[AvailabilityWindow]) -> Result<Self> {
+        let windows = windows
+            .iter()
+            .map(|w| {
+                if let Some(day) = w.days.iter().flatten().find(|d| !(1..=7).contains(*d)) {
+                    return Err(DeviceError::ConfigError(format!(
+                        "可用时段星期值无效: {} (应为 1-7)",
+                        day
+                    )));
+                }
+                Ok(Window {
+                    days: w.days.clone(),
+                    start: parse_time(&w.start)?,
+                    end: parse_time(&w.end)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { windows })
+    }
+
+    /// 当前本地时间是否处于可用时段
+    pub fn is_available(&self) -> bool {
+        self.is_available_at(chrono::Local::now().naive_local())
+    }
+
+    /// 指定时间是否处于可用时段
+    pub fn is_available_at(&self, now: NaiveDateTime) -> bool {
+        let time = now.time();
+        let today = now.weekday().number_from_monday();
+        let yesterday = (now - Duration::days(1)).weekday().number_from_monday();
+
+        self.windows.iter().any(|w| {
+            if w.start == w.end {
+                // 全天
+                w.applies_on(today)
+            } else if w.start < w.end {
+                w.applies_on(today) && time >= w.start && time < w.end
+            } else {
+                // 跨午夜：星期按开始时间所在日计算
+                (w.applies_on(today) && time >= w.start)
+                    || (w.applies_on(yesterday) && time < w.end)
+            }
+        })
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| DeviceError::ConfigError(format!("可用时段时间格式无效: '{}'", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-01-01 为周一
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let availability = ChannelAvailability::from_config(&[AvailabilityWindow {
+            days: Some(vec![5]),
+            start: "22:00".into(),
+            end: "06:00".into(),
+        }])
+        .unwrap();
+
+        assert!(availability.is_available_at(at(5, 23, 0)));
+        assert!(availability.is_available_at(at(6, 5, 59)));
+        assert!(!availability.is_available_at(at(6, 6, 0)));
+        assert!(!availability.is_available_at(at(4, 23, 0)));
+    }
+}
@@ -1,82 +1,385 @@
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
+use serde::Serialize;
 /// 通道管理器 - 负责物理设备通信层
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, RwLock};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
 
+use super::availability::ChannelAvailability;
+use super::health::{ChannelHealth, ChannelHealthSnapshot};
+use super::ping::{self, PingMode, PingReport, PingSample};
+use super::startup_order::{
+    self, ChannelStartup, StartupOrderReport, StartupViolation, StartupViolationKind,
+};
 use super::DeviceEvent;
-use crate::config::{ChannelConfig, StatuteType};
+use crate::config::{ChannelConfig, ChannelGroupConfig, StatuteType, StoreForwardConfig};
 use crate::protocols::{
-    ComputerControlProtocol, CustomProtocol, HsPowerSequencerProtocol, MockProtocol,
-    ModbusProtocol, ModbusSlaveProtocol, NovastarProtocol, PjlinkProtocol, Protocol,
-    QnSmartPlcProtocol, ScreenNjlgPlcProtocol, Splicer3dProtocol, TprisPduProtocol,
-    Wdy8enProtocol, XFusionProtocol, XinkeQ1Protocol, YkVapProtocol,
+    CommandQueueStats, ComputerControlProtocol, ContentInfo, CustomProtocol,
+    HsPowerSequencerProtocol, MockProtocol, ModbusProtocol, ModbusSlaveProtocol, NovastarProtocol,
+    PjlinkProtocol, Protocol, QnSmartPlcProtocol, ScreenNjlgPlcProtocol, Splicer3dProtocol,
+    TprisPduProtocol, Wdy8enProtocol, XFusionProtocol, XinkeQ1Protocol, YkVapProtocol,
 };
-use crate::utils::{DeviceError, Result};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::{net, DeviceError, Result};
+
+/// 可用时段检查间隔
+const AVAILABILITY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// 等待依赖通道就绪时的状态查询间隔
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 通道共享的协议实例
+type SharedProtocol = Arc<RwLock<Box<dyn Protocol>>>;
 
 /// 通道管理器
 pub struct ChannelManager {
     channels: DashMap<u32, Channel>,
+    /// 运行时停用的通道配置（不修改配置文件，重新启用时据此重建协议实例）
+    disabled: DashMap<u32, ChannelConfig>,
+    /// 正在切换配置的独占资源通道（期间拒绝设备通信，调用方可稍后重试）
+    reloading: DashSet<u32>,
+    groups: Vec<ChannelGroupConfig>,
     event_tx: broadcast::Sender<DeviceEvent>,
+    tasks: TaskRegistry,
+    /// 启动时计算的启动顺序与依赖违例
+    startup: StartupOrderReport,
+    /// 通道通信健康度（停用 / 重新启用通道时保留）
+    health: DashMap<u32, Arc<ChannelHealth>>,
+}
+
+/// 通道诊断信息
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChannelDiagnostics {
+    pub channel_id: u32,
+    /// 协议类型（当前驱动）
+    pub statute: String,
+    /// 当前驱动序号（0 为主驱动，其后为备用驱动）
+    pub driver: usize,
+    /// 协议正被独占使用（执行命令或写入中）
+    pub busy: bool,
+    /// 打开的设备连接数，协议不统计或通道忙时为 null
+    pub open_connections: Option<usize>,
+    /// 命令优先级队列统计（排队等待时间等），协议不排队或通道忙时为 null
+    pub command_queue: Option<CommandQueueStats>,
+    /// 通信健康度
+    pub health: ChannelHealthSnapshot,
+}
+
+/// 通道命令 / 方法调用的统一结果
+///
+/// 各协议返回的数据结构不同，API 响应与审计日志统一使用此结构；驱动数据原样放在 `data` 中。
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CommandResult {
+    pub success: bool,
+    pub channel_id: u32,
+    /// 命令或方法名称
+    pub command: String,
+    /// 驱动返回的数据（失败时为 null）
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+    /// 设备原始报文（十六进制），驱动结果中带 `raw` 字符串字段时提取
+    pub raw: Option<String>,
+    /// 执行耗时（毫秒，含等待通道锁）
+    pub duration_ms: u64,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+impl CommandResult {
+    fn new(
+        channel_id: u32,
+        command: &str,
+        duration: Duration,
+        result: &Result<serde_json::Value>,
+    ) -> Self {
+        let (data, error) = match result {
+            Ok(data) => (data.clone(), None),
+            Err(e) => (serde_json::Value::Null, Some(e.to_string())),
+        };
+        let raw = data
+            .get("raw")
+            .and_then(|raw| raw.as_str())
+            .map(str::to_string);
+        Self {
+            success: error.is_none(),
+            channel_id,
+            command: command.to_string(),
+            data,
+            raw,
+            duration_ms: duration.as_millis() as u64,
+            error,
+        }
+    }
 }
 
 /// 单个通道
 struct Channel {
     id: u32,
-    protocol: Arc<RwLock<Box<dyn Protocol>>>,
-    config: ChannelConfig,
+    protocol: SharedProtocol,
+    /// 驱动链（0 为主驱动，其后为备用驱动）
+    drivers: Vec<ChannelConfig>,
+    /// 当前使用的驱动序号
+    active_driver: AtomicUsize,
+    /// 可用时段（未配置时始终可用）
+    availability: Option<ChannelAvailability>,
+    /// 通道级后台任务（可用时段监视等）
+    tasks: TaskRegistry,
+}
+
+impl Channel {
+    /// 当前使用的驱动序号
+    fn active_driver(&self) -> usize {
+        self.active_driver.load(Ordering::Acquire)
+    }
+
+    /// 当前驱动的协议类型
+    fn statute(&self) -> &StatuteType {
+        &self.drivers[self.active_driver()].statute
+    }
 }
 
 impl ChannelManager {
     /// 创建通道管理器
     pub async fn new(
         configs: &[ChannelConfig],
+        groups: &[ChannelGroupConfig],
         event_tx: broadcast::Sender<DeviceEvent>,
+        tasks: &TaskRegistry,
     ) -> Result<Self> {
-        let channels = DashMap::new();
+        let mut manager = Self {
+            channels: DashMap::new(),
+            disabled: DashMap::new(),
+            reloading: DashSet::new(),
+            groups: groups.to_vec(),
+            event_tx,
+            tasks: tasks.clone(),
+            startup: StartupOrderReport::default(),
+            health: DashMap::new(),
+        };
 
-        for config in configs {
-            if !config.enable {
+        // 按启动依赖排序，依赖通道就绪（或等待超时）后再初始化
+        let enabled: Vec<&ChannelConfig> = configs.iter().filter(|c| c.enable).collect();
+        let (order, violations) = startup_order::plan(&enabled);
+        let enabled_ids: HashSet<u32> = enabled.iter().map(|c| c.channel_id).collect();
+        let mut report = StartupOrderReport {
+            order: order.clone(),
+            channels: Vec::with_capacity(order.len()),
+            violations,
+        };
+
+        for channel_id in order {
+            let Some(config) = enabled.iter().find(|c| c.channel_id == channel_id) else {
+                continue;
+            };
+            let started_at = std::time::Instant::now();
+            manager
+                .wait_for_dependencies(config, &enabled_ids, &mut report.violations)
+                .await;
+            let waited_ms = started_at.elapsed().as_millis() as u64;
+
+            let started = match manager.start_channel(config).await {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("通道 {} 初始化失败: {:?}", config.channel_id, e);
+                    false
+                }
+            };
+            report.channels.push(ChannelStartup {
+                channel_id,
+                after: startup_order::dependencies(config).to_vec(),
+                started,
+                waited_ms,
+            });
+        }
+
+        for violation in &report.violations {
+            warn!(
+                "通道 {} 启动依赖违例: {}",
+                violation.channel_id, violation.message
+            );
+        }
+        manager.startup = report;
+        Ok(manager)
+    }
+
+    /// 等待通道的启动依赖就绪（已初始化且状态查询成功），共用一个超时
+    async fn wait_for_dependencies(
+        &self,
+        config: &ChannelConfig,
+        enabled: &HashSet<u32>,
+        violations: &mut Vec<StartupViolation>,
+    ) {
+        let dependencies = startup_order::dependencies(config);
+        if dependencies.is_empty() {
+            return;
+        }
+        let timeout = config.startup.as_ref().map_or(0, |s| s.timeout_ms);
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout);
+
+        for &dep in dependencies {
+            // 未配置的依赖已在排序时记录
+            if !enabled.contains(&dep) {
                 continue;
             }
+            let Some(protocol) = self.channels.get(&dep).map(|c| c.protocol.clone()) else {
+                violations.push(StartupViolation::new(
+                    config.channel_id,
+                    StartupViolationKind::DependencyFailed,
+                    format!("依赖的通道 {} 初始化失败", dep),
+                ));
+                continue;
+            };
 
-            match Self::create_channel(config).await {
-                Ok(channel) => {
-                    info!(
-                        "通道 {} ({:?}) 初始化成功",
-                        config.channel_id, config.statute
-                    );
-                    channels.insert(config.channel_id, channel);
+            let ready = tokio::time::timeout_at(deadline, async {
+                while protocol.read().await.get_status().await.is_err() {
+                    tokio::time::sleep(STARTUP_POLL_INTERVAL).await;
+                }
+            })
+            .await
+            .is_ok();
+            if !ready {
+                violations.push(StartupViolation::new(
+                    config.channel_id,
+                    StartupViolationKind::Timeout,
+                    format!("等待通道 {} 就绪超时（{} 毫秒）", dep, timeout),
+                ));
+            }
+        }
+    }
 
-                    // 发送连接事件
-                    let _ = event_tx.send(DeviceEvent::ChannelConnected {
-                        channel_id: config.channel_id,
-                    });
+    /// 启动顺序与依赖违例
+    pub fn startup_order(&self) -> StartupOrderReport {
+        self.startup.clone()
+    }
+
+    /// 创建并启动通道（协议实例与可用时段监视），成功后发送连接事件
+    async fn start_channel(&self, config: &ChannelConfig) -> Result<()> {
+        let channel =
+            Self::create_channel(config, &self.groups, &self.event_tx, &self.tasks).await?;
+        info!(
+            "通道 {} ({:?}) 初始化成功",
+            config.channel_id, config.statute
+        );
+        if let Some(availability) = channel.availability.clone() {
+            Self::start_availability_watcher(
+                &channel.tasks,
+                config.channel_id,
+                availability,
+                channel.protocol.clone(),
+                self.event_tx.clone(),
+            );
+        }
+        self.channels.insert(config.channel_id, channel);
+
+        // 发送连接事件
+        let _ = self.event_tx.send(DeviceEvent::ChannelConnected {
+            channel_id: config.channel_id,
+        });
+        Ok(())
+    }
+
+    /// 创建单个通道
+    async fn create_channel(
+        config: &ChannelConfig,
+        groups: &[ChannelGroupConfig],
+        event_tx: &broadcast::Sender<DeviceEvent>,
+        tasks: &TaskRegistry,
+    ) -> Result<Channel> {
+        // 可用时段：通道自身配置优先，其次为所属分组
+        let windows = config.availability.as_deref().or_else(|| {
+            groups
+                .iter()
+                .find(|g| g.channels.contains(&config.channel_id))
+                .map(|g| g.availability.as_slice())
+        });
+        let availability = match windows {
+            Some(windows) if !windows.is_empty() => {
+                Some(ChannelAvailability::from_config(windows)?)
+            }
+            _ => None,
+        };
+
+        // 依次尝试主驱动与备用驱动，第一个创建成功的作为当前驱动
+        let drivers = Self::driver_chain(config);
+        let mut created = None;
+        let mut last_error = None;
+        for (index, driver) in drivers.iter().enumerate() {
+            match Self::build_protocol(driver, event_tx) {
+                Ok(protocol) => {
+                    if index > 0 {
+                        warn!(
+                            "通道 {} 主驱动不可用，使用备用驱动 {} ({:?})",
+                            config.channel_id, index, driver.statute
+                        );
+                    }
+                    created = Some((index, protocol));
+                    break;
                 }
                 Err(e) => {
-                    warn!("通道 {} 初始化失败: {:?}", config.channel_id, e);
+                    if drivers.len() > 1 {
+                        warn!(
+                            "通道 {} 驱动 {} ({:?}) 创建失败: {}",
+                            config.channel_id, index, driver.statute, e
+                        );
+                    }
+                    last_error = Some(e);
                 }
             }
         }
+        let Some((active_driver, protocol)) = created else {
+            return Err(last_error.unwrap_or_else(|| {
+                DeviceError::ConfigError(format!("通道 {} 没有可用驱动", config.channel_id))
+            }));
+        };
 
-        Ok(Self { channels, event_tx })
+        Ok(Channel {
+            id: config.channel_id,
+            protocol: Arc::new(RwLock::new(protocol)),
+            drivers,
+            active_driver: AtomicUsize::new(active_driver),
+            availability,
+            tasks: tasks.child(),
+        })
     }
 
-    /// 创建单个通道
-    async fn create_channel(config: &ChannelConfig) -> Result<Channel> {
-        // 合并参数：优先使用 arguments，如果没有则使用 params（兼容旧配置）
-        let mut params = if let Some(args) = &config.arguments {
+    /// 驱动链：主驱动配置在前，备用驱动替换协议类型与参数，其余配置沿用主驱动
+    fn driver_chain(config: &ChannelConfig) -> Vec<ChannelConfig> {
+        let mut drivers = vec![config.clone()];
+        drivers.extend(config.fallback.iter().map(|driver| ChannelConfig {
+            statute: driver.statute.clone(),
+            arguments: driver.arguments.clone(),
+            params: Default::default(),
+            fallback: Vec::new(),
+            ..config.clone()
+        }));
+        drivers
+    }
+
+    /// 合并参数：优先使用 arguments，如果没有则使用 params（兼容旧配置）
+    fn protocol_params(config: &ChannelConfig) -> HashMap<String, serde_json::Value> {
+        if let Some(args) = &config.arguments {
             // 如果 arguments 是对象，转换为 HashMap
             if let Some(obj) = args.as_object() {
                 obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
             } else {
                 // 如果不是对象，使用空 HashMap
-                std::collections::HashMap::new()
+                HashMap::new()
             }
         } else {
             // 使用旧的 params
             config.params.clone()
-        };
+        }
+    }
+
+    /// 按驱动配置创建协议实例
+    fn build_protocol(
+        config: &ChannelConfig,
+        event_tx: &broadcast::Sender<DeviceEvent>,
+    ) -> Result<Box<dyn Protocol>> {
+        let mut params = Self::protocol_params(config);
 
         // 添加 auto_call 配置（如果存在）
         if let Some(auto_call) = &config.auto_call {
@@ -87,7 +390,7 @@ impl ChannelManager {
         }
 
         // 使用协议的 from_config 方法创建实例，协议自己解析配置
-        let protocol: Box<dyn Protocol> = match config.statute {
+        let mut protocol: Box<dyn Protocol> = match config.statute {
             StatuteType::Pjlink => PjlinkProtocol::from_config(config.channel_id, &params)?,
 
             StatuteType::Modbus => ModbusProtocol::from_config(config.channel_id, &params)?,
@@ -136,33 +439,475 @@ impl ChannelManager {
             }
         };
 
-        Ok(Channel {
-            id: config.channel_id,
-            protocol: Arc::new(RwLock::new(protocol)),
-            config: config.clone(),
+        protocol.set_event_sender(event_tx.clone());
+
+        Ok(protocol)
+    }
+
+    /// 启动可用时段监视任务：进入 / 离开时段时暂停或恢复协议轮询并发送通道事件
+    fn start_availability_watcher(
+        tasks: &TaskRegistry,
+        channel_id: u32,
+        availability: ChannelAvailability,
+        protocol: SharedProtocol,
+        event_tx: broadcast::Sender<DeviceEvent>,
+    ) {
+        let name = format!("channel:{}:availability", channel_id);
+        tasks.spawn(name, async move {
+            let mut interval = tokio::time::interval(AVAILABILITY_CHECK_INTERVAL);
+            let mut last_available = None;
+
+            loop {
+                interval.tick().await;
+
+                let available = availability.is_available();
+                if last_available == Some(available) {
+                    continue;
+                }
+
+                protocol.read().await.set_polling_paused(!available);
+                if available {
+                    if last_available.is_some() {
+                        info!("通道 {} 进入可用时段，恢复通信", channel_id);
+                        let _ = event_tx.send(DeviceEvent::ChannelConnected { channel_id });
+                    }
+                } else {
+                    info!("通道 {} 处于计划离线时段，暂停通信", channel_id);
+                    let _ = event_tx.send(DeviceEvent::ChannelDisconnected {
+                        channel_id,
+                        reason: "计划离线时段".to_string(),
+                    });
+                }
+                last_available = Some(available);
+            }
+        });
+    }
+
+    /// 删除通道并停止其后台任务
+    pub async fn remove_channel(&self, channel_id: u32) -> Result<()> {
+        if self.disabled.remove(&channel_id).is_some() {
+            info!("通道 {} 已删除", channel_id);
+            return Ok(());
+        }
+        let (_, channel) = self
+            .channels
+            .remove(&channel_id)
+            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+
+        Self::shutdown_channel(channel_id, &channel.tasks, &channel.protocol).await;
+        let _ = self.event_tx.send(DeviceEvent::ChannelDisconnected {
+            channel_id,
+            reason: "通道已删除".to_string(),
+        });
+        info!("通道 {} 已删除", channel_id);
+        Ok(())
+    }
+
+    /// 运行时停用通道：停止协议实例及其轮询任务，不修改配置文件（已停用时无操作）
+    pub async fn disable_channel(&self, channel_id: u32) -> Result<()> {
+        if self.disabled.contains_key(&channel_id) {
+            return Ok(());
+        }
+        let (_, channel) = self
+            .channels
+            .remove(&channel_id)
+            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+
+        Self::shutdown_channel(channel_id, &channel.tasks, &channel.protocol).await;
+        // 重新启用时从主驱动开始
+        let mut drivers = channel.drivers;
+        self.disabled.insert(channel_id, drivers.swap_remove(0));
+        let _ = self.event_tx.send(DeviceEvent::ChannelDisconnected {
+            channel_id,
+            reason: "通道已停用".to_string(),
+        });
+        info!("通道 {} 已停用", channel_id);
+        Ok(())
+    }
+
+    /// 重新启用运行时停用的通道：按原配置重建协议实例（已启用时无操作）
+    pub async fn enable_channel(&self, channel_id: u32) -> Result<()> {
+        if self.channels.contains_key(&channel_id) {
+            return Ok(());
+        }
+        let (_, config) = self
+            .disabled
+            .remove(&channel_id)
+            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+
+        if let Err(e) = self.start_channel(&config).await {
+            // 重建失败时保持停用，便于排查后重试
+            self.disabled.insert(channel_id, config);
+            return Err(e);
+        }
+        info!("通道 {} 已重新启用", channel_id);
+        Ok(())
+    }
+
+    /// 用新配置替换运行中的通道，其他通道不受影响
+    ///
+    /// 新旧实例都能与另一实例同时运行时，先在旧实例旁创建新的协议实例（影子实例），状态查询在
+    /// `verify_timeout` 内成功后再原子地替换并停止旧实例；验证失败时停止影子实例，旧实例继续
+    /// 工作。独占设备连接、串口或本地监听端口的驱动（见 [`Self::supports_shadow`]）由旧实例先
+    /// 释放资源，再创建并验证新实例，失败时恢复旧实例，替换期间请求返回可重试的切换中错误。
+    /// 运行时停用的通道只更新重新启用时使用的配置。
+    pub async fn replace_channel(
+        &self,
+        config: &ChannelConfig,
+        verify_timeout: Duration,
+    ) -> Result<()> {
+        let channel_id = config.channel_id;
+        if let Some(mut disabled) = self.disabled.get_mut(&channel_id) {
+            *disabled = config.clone();
+            info!("通道 {} 已停用，新配置在重新启用时生效", channel_id);
+            return Ok(());
+        }
+        let shadow = match self.channels.get(&channel_id) {
+            Some(current) => {
+                current.drivers.iter().all(Self::supports_shadow)
+                    && Self::driver_chain(config).iter().all(Self::supports_shadow)
+            }
+            None => return Err(DeviceError::ChannelNotFound(channel_id)),
+        };
+        if !shadow {
+            return self.replace_exclusive(config, verify_timeout).await;
+        }
+
+        let shadow =
+            Self::create_channel(config, &self.groups, &self.event_tx, &self.tasks).await?;
+        if let Err(error) = Self::verify_channel(&shadow, verify_timeout).await {
+            Self::shutdown_channel(channel_id, &shadow.tasks, &shadow.protocol).await;
+            return Err(DeviceError::ConnectionError(format!(
+                "通道 {} 新配置连接验证失败，继续使用原配置: {}",
+                channel_id, error
+            )));
+        }
+
+        if let Some(availability) = shadow.availability.clone() {
+            Self::start_availability_watcher(
+                &shadow.tasks,
+                channel_id,
+                availability,
+                shadow.protocol.clone(),
+                self.event_tx.clone(),
+            );
+        }
+        if let Some(previous) = self.channels.insert(channel_id, shadow) {
+            Self::shutdown_channel(channel_id, &previous.tasks, &previous.protocol).await;
+        }
+        info!("通道 {} ({:?}) 已切换到新配置", channel_id, config.statute);
+        Ok(())
+    }
+
+    /// 驱动能否与同一通道的另一实例同时运行
+    ///
+    /// 只有不持有独占资源的驱动可以影子替换：mock、computerControl（每次发送使用临时 UDP
+    /// 端口）以及未配置 `agent_port` 的 xFusion（只访问 iBMC HTTP 接口）。其余驱动持有设备
+    /// 长连接（Modbus-TCP、PJLink 等设备通常只接受一个连接）、串口或本地监听端口
+    /// （xFusion `agent_port`），两个实例同时运行会互相抢占或绑定失败。
+    fn supports_shadow(config: &ChannelConfig) -> bool {
+        match config.statute {
+            StatuteType::Mock | StatuteType::ComputerControl => true,
+            StatuteType::XFusion => Self::protocol_params(config)
+                .get("agent_port")
+                .and_then(|port| port.as_u64())
+                .is_none(),
+            _ => false,
+        }
+    }
+
+    /// 独占资源驱动的替换：旧实例释放资源后创建并验证新实例，失败时恢复旧实例
+    ///
+    /// 替换期间通道保持注册并标记为切换中，请求返回 [`DeviceError::ChannelReloading`]。
+    /// 旧实例的协议锁一直持有到验证结束，已在执行的请求完成后才会释放资源。
+    async fn replace_exclusive(
+        &self,
+        config: &ChannelConfig,
+        verify_timeout: Duration,
+    ) -> Result<()> {
+        let channel_id = config.channel_id;
+        let (protocol, availability) = match self.channels.get(&channel_id) {
+            Some(current) => (current.protocol.clone(), current.availability.clone()),
+            None => return Err(DeviceError::ChannelNotFound(channel_id)),
+        };
+        info!(
+            "通道 {} 驱动独占设备连接或端口，先释放旧实例资源再验证新配置",
+            channel_id
+        );
+        self.reloading.insert(channel_id);
+
+        let verified = {
+            let previous = protocol.write().await;
+            previous.set_polling_paused(true);
+            previous.release().await;
+
+            let verified =
+                match Self::create_channel(config, &self.groups, &self.event_tx, &self.tasks).await
+                {
+                    Ok(channel) => match Self::verify_channel(&channel, verify_timeout).await {
+                        Ok(()) => Ok(channel),
+                        Err(error) => {
+                            Self::shutdown_channel(channel_id, &channel.tasks, &channel.protocol)
+                                .await;
+                            Err(error)
+                        }
+                    },
+                    Err(error) => Err(error),
+                };
+            if verified.is_err() {
+                previous.resume().await;
+                previous.set_polling_paused(availability.is_some_and(|a| !a.is_available()));
+            }
+            verified
+        };
+
+        // 释放旧实例的协议锁后再替换映射：等待该锁的请求持有映射的读引用
+        let result = match verified {
+            Ok(channel) => {
+                if let Some(availability) = channel.availability.clone() {
+                    Self::start_availability_watcher(
+                        &channel.tasks,
+                        channel_id,
+                        availability,
+                        channel.protocol.clone(),
+                        self.event_tx.clone(),
+                    );
+                }
+                if let Some(previous) = self.channels.insert(channel_id, channel) {
+                    Self::shutdown_channel(channel_id, &previous.tasks, &previous.protocol).await;
+                }
+                info!("通道 {} ({:?}) 已切换到新配置", channel_id, config.statute);
+                Ok(())
+            }
+            Err(error) => Err(DeviceError::ConnectionError(format!(
+                "通道 {} 新配置连接验证失败，已恢复原配置: {}",
+                channel_id, error
+            ))),
+        };
+        self.reloading.remove(&channel_id);
+        result
+    }
+
+    /// 在 `timeout` 内验证新建的协议实例能与设备通信
+    ///
+    /// 使用 TCP 传输的驱动先建立一次到设备地址的连接，随后查询一次状态；状态成功返回但
+    /// `connected` / `online` 为 false（设备不可达）时同样视为失败。
+    async fn verify_channel(channel: &Channel, timeout: Duration) -> Result<()> {
+        let params = Self::protocol_params(&channel.drivers[channel.active_driver()]);
+        let verified = tokio::time::timeout(timeout, async {
+            if let Some((host, port)) = ping::tcp_target(&params) {
+                net::probe_connect(&host, port, timeout).await?;
+            }
+            let status = channel.protocol.read().await.get_status().await?;
+            match ping::status_unreachable(&status) {
+                Some(reason) => Err(DeviceError::ConnectionError(reason)),
+                None => Ok(()),
+            }
         })
+        .await;
+        match verified {
+            Ok(result) => result,
+            Err(_) => Err(DeviceError::ConnectionError(format!(
+                "{} 毫秒内无响应",
+                timeout.as_millis()
+            ))),
+        }
+    }
+
+    /// 自检：按配置创建通道的协议实例，`timeout` 不为空时在超时内按 [`Self::verify_channel`]
+    /// 验证连接，随后停止实例
+    ///
+    /// 实例不加入任何通道管理器，不影响运行中的通道。
+    pub async fn probe_channel(config: &ChannelConfig, timeout: Option<Duration>) -> Result<()> {
+        let (event_tx, _) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let channel = Self::create_channel(config, &[], &event_tx, &tasks).await?;
+        let result = match timeout {
+            Some(timeout) => Self::verify_channel(&channel, timeout).await,
+            None => Ok(()),
+        };
+        Self::shutdown_channel(config.channel_id, &channel.tasks, &channel.protocol).await;
+        result
+    }
+
+    /// 通道是否已在运行时停用
+    pub fn is_disabled(&self, channel_id: u32) -> bool {
+        self.disabled.contains_key(&channel_id)
+    }
+
+    /// 通道当前驱动的协议类型（停用的通道为主驱动的协议类型）
+    pub fn statute(&self, channel_id: u32) -> Option<String> {
+        match self.channels.get(&channel_id) {
+            Some(channel) => Some(format!("{:?}", channel.statute())),
+            None => self
+                .disabled
+                .get(&channel_id)
+                .map(|config| format!("{:?}", config.statute)),
+        }
+    }
+
+    /// 通道所属站点
+    pub fn site(&self, channel_id: u32) -> Option<String> {
+        match self.channels.get(&channel_id) {
+            Some(channel) => channel.drivers[0].site.clone(),
+            None => self
+                .disabled
+                .get(&channel_id)
+                .and_then(|config| config.site.clone()),
+        }
+    }
+
+    /// 通道不存在时的错误（区分运行时停用）
+    fn missing(&self, channel_id: u32) -> DeviceError {
+        if self.is_disabled(channel_id) {
+            DeviceError::ChannelDisabled(channel_id)
+        } else {
+            DeviceError::ChannelNotFound(channel_id)
+        }
+    }
+
+    /// 停止所有通道的后台任务（配置热重载或服务退出时调用）
+    pub async fn shutdown(&self) {
+        let channels: Vec<(u32, TaskRegistry, SharedProtocol)> = self
+            .channels
+            .iter()
+            .map(|c| (c.id, c.tasks.clone(), c.protocol.clone()))
+            .collect();
+
+        for (channel_id, tasks, protocol) in channels {
+            Self::shutdown_channel(channel_id, &tasks, &protocol).await;
+        }
+        info!("所有通道后台任务已停止");
+    }
+
+    /// 停止单个通道：先停通道级任务（避免监视器继续操作协议），再停协议自身任务
+    async fn shutdown_channel(channel_id: u32, tasks: &TaskRegistry, protocol: &SharedProtocol) {
+        debug!("停止通道 {} 后台任务", channel_id);
+        tasks.shutdown().await;
+        protocol.read().await.shutdown().await;
+    }
+
+    /// 通道当前是否处于可用时段（未配置时段或通道不存在时视为可用）
+    pub fn is_available(&self, channel_id: u32) -> bool {
+        self.channels
+            .get(&channel_id)
+            .and_then(|c| c.availability.as_ref().map(|a| a.is_available()))
+            .unwrap_or(true)
+    }
+
+    /// 通道通信健康度计数器
+    pub fn health(&self, channel_id: u32) -> Arc<ChannelHealth> {
+        self.health.entry(channel_id).or_default().clone()
+    }
+
+    /// 通道的离线写入暂存配置
+    pub fn store_and_forward(&self, channel_id: u32) -> Option<StoreForwardConfig> {
+        self.channels
+            .get(&channel_id)
+            .and_then(|c| c.drivers[0].store_and_forward.clone())
+    }
+
+    /// 切换配置期间或计划离线时段内拒绝与设备通信
+    fn ensure_available(&self, channel_id: u32) -> Result<()> {
+        if self.reloading.contains(&channel_id) {
+            debug!("通道 {} 正在切换配置，跳过设备通信", channel_id);
+            Err(DeviceError::ChannelReloading(channel_id))
+        } else if self.is_available(channel_id) {
+            Ok(())
+        } else {
+            debug!("通道 {} 处于计划离线时段，跳过设备通信", channel_id);
+            Err(DeviceError::ScheduledOffline(channel_id))
+        }
+    }
+
+    /// 连接失败时切换到驱动链中的下一个驱动（循环），切换成功后发送事件
+    ///
+    /// `failed_driver` 为出错时使用的驱动，并发请求已完成切换时不再重复切换。
+    async fn failover(&self, channel: &Channel, failed_driver: usize, error: &DeviceError) {
+        if channel.drivers.len() < 2
+            || !matches!(
+                error,
+                DeviceError::ConnectionError(_) | DeviceError::Timeout | DeviceError::Io(_)
+            )
+        {
+            return;
+        }
+
+        let mut protocol = channel.protocol.write().await;
+        if channel.active_driver() != failed_driver {
+            return;
+        }
+        let count = channel.drivers.len();
+        for step in 1..count {
+            let index = (failed_driver + step) % count;
+            let driver = &channel.drivers[index];
+            match Self::build_protocol(driver, &self.event_tx) {
+                Ok(next) => {
+                    protocol.shutdown().await;
+                    if channel
+                        .availability
+                        .as_ref()
+                        .is_some_and(|a| !a.is_available())
+                    {
+                        next.set_polling_paused(true);
+                    }
+                    *protocol = next;
+                    channel.active_driver.store(index, Ordering::Release);
+                    self.health(channel.id).record_switch();
+                    warn!(
+                        "通道 {} 驱动 {} 连接失败 ({})，切换到驱动 {} ({:?})",
+                        channel.id, failed_driver, error, index, driver.statute
+                    );
+                    let _ = self.event_tx.send(DeviceEvent::ChannelDriverSwitched {
+                        channel_id: channel.id,
+                        driver: index,
+                        statute: format!("{:?}", driver.statute),
+                        reason: error.to_string(),
+                    });
+                    return;
+                }
+                Err(e) => warn!(
+                    "通道 {} 驱动 {} ({:?}) 创建失败: {}",
+                    channel.id, index, driver.statute, e
+                ),
+            }
+        }
     }
 
     /// 写入数据到指定通道的设备
     pub async fn write(&self, channel_id: u32, device_id: u32, value: i32) -> Result<()> {
+        self.ensure_available(channel_id)?;
         let channel = self
             .channels
             .get(&channel_id)
-            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+            .ok_or_else(|| self.missing(channel_id))?;
 
-        let mut protocol = channel.protocol.write().await;
-        protocol.write(device_id, value).await
+        let driver = channel.active_driver();
+        let started = Instant::now();
+        let result = channel.protocol.write().await.write(device_id, value).await;
+        self.health(channel_id).record(started.elapsed(), &result);
+        if let Err(ref e) = result {
+            self.failover(&channel, driver, e).await;
+        }
+        result
     }
 
     /// 从指定通道的设备读取数据
     pub async fn read(&self, channel_id: u32, device_id: u32) -> Result<i32> {
+        self.ensure_available(channel_id)?;
         let channel = self
             .channels
             .get(&channel_id)
-            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+            .ok_or_else(|| self.missing(channel_id))?;
 
-        let protocol = channel.protocol.read().await;
-        protocol.read(device_id).await
+        let driver = channel.active_driver();
+        let started = Instant::now();
+        let result = channel.protocol.read().await.read(device_id).await;
+        self.health(channel_id).record(started.elapsed(), &result);
+        if let Err(ref e) = result {
+            self.failover(&channel, driver, e).await;
+        }
+        result
     }
 
     /// 执行通道命令
@@ -175,36 +920,228 @@ impl ChannelManager {
         let channel = self
             .channels
             .get(&channel_id)
-            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+            .ok_or_else(|| self.missing(channel_id))?;
+
+        let driver = channel.active_driver();
+        let started = Instant::now();
+        let result = channel
+            .protocol
+            .write()
+            .await
+            .execute(command, params)
+            .await;
+        self.health(channel_id).record(started.elapsed(), &result);
+        if let Err(ref e) = result {
+            self.failover(&channel, driver, e).await;
+        }
+        result
+    }
+
+    /// 执行通道命令，返回统一结构的结果
+    pub async fn execute_command(
+        &self,
+        channel_id: u32,
+        command: &str,
+        params: serde_json::Value,
+    ) -> CommandResult {
+        let started = Instant::now();
+        let result = self.execute(channel_id, command, params).await;
+        CommandResult::new(channel_id, command, started.elapsed(), &result)
+    }
+
+    /// 通道延迟测试：按 `interval` 间隔测试 `count` 次，每次在 `timeout` 内完成
+    ///
+    /// 测试不计入通道健康度，也不触发备用驱动切换。
+    pub async fn ping(
+        &self,
+        channel_id: u32,
+        mode: PingMode,
+        count: u32,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<PingReport> {
+        self.ensure_available(channel_id)?;
+        let (protocol, target) = {
+            let channel = self
+                .channels
+                .get(&channel_id)
+                .ok_or_else(|| self.missing(channel_id))?;
+            let target = match mode {
+                PingMode::Status => None,
+                PingMode::Connect => {
+                    let params = Self::protocol_params(&channel.drivers[channel.active_driver()]);
+                    Some(ping::transport_target(&params).ok_or_else(|| {
+                        DeviceError::ConfigError(format!(
+                            "通道 {} 未配置 addr / port，无法测试连接",
+                            channel_id
+                        ))
+                    })?)
+                }
+            };
+            (channel.protocol.clone(), target)
+        };
+
+        let mut samples = Vec::with_capacity(count as usize);
+        for seq in 0..count {
+            if seq > 0 {
+                tokio::time::sleep(interval).await;
+            }
+            let started = Instant::now();
+            let result = match &target {
+                Some((host, port)) => net::probe_connect(host, *port, timeout).await,
+                None => tokio::time::timeout(timeout, async {
+                    protocol.read().await.get_status().await.map(|_| ())
+                })
+                .await
+                .unwrap_or(Err(DeviceError::Timeout)),
+            };
+            samples.push(PingSample::new(seq, started.elapsed(), result));
+        }
+        let target = target.map(|(host, port)| net::display_addr(&host, port));
+        Ok(PingReport::new(channel_id, mode, target, samples))
+    }
+
+    /// 通过通道传输层发送原始数据，返回设备原始响应
+    pub async fn send_raw(
+        &self,
+        channel_id: u32,
+        payload: &[u8],
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        self.ensure_available(channel_id)?;
+        let channel = self
+            .channels
+            .get(&channel_id)
+            .ok_or_else(|| self.missing(channel_id))?;
 
         let mut protocol = channel.protocol.write().await;
-        protocol.execute(command, params).await
+        protocol.send_raw(payload, timeout).await
     }
 
     /// 获取所有通道状态
     pub async fn get_all_status(&self) -> Result<serde_json::Value> {
         let mut statuses = Vec::new();
+        for channel_id in self.channel_ids() {
+            if let Some(status) = self.channel_status(channel_id).await {
+                statuses.push(status);
+            }
+        }
+        Ok(serde_json::json!(statuses))
+    }
 
-        for entry in self.channels.iter() {
-            let channel_id = *entry.key();
-            let channel = entry.value();
-            let protocol = channel.protocol.read().await;
-
-            match protocol.get_status().await {
-                Ok(status) => {
-                    statuses.push(serde_json::json!({
-                        "channel_id": channel_id,
-                        "statute": format!("{:?}", channel.config.statute),
-                        "status": status,
-                    }));
-                }
-                Err(e) => {
-                    warn!("获取通道 {} 状态失败: {:?}", channel_id, e);
-                }
+    /// 全部通道 ID（运行中的通道在前，其后为运行时停用的通道）
+    pub fn channel_ids(&self) -> Vec<u32> {
+        self.channels
+            .iter()
+            .map(|c| *c.key())
+            .chain(self.disabled.iter().map(|c| *c.key()))
+            .collect()
+    }
+
+    /// 通道状态是否无需访问设备即可得到（运行时停用、正在切换配置或处于计划离线时段）
+    pub fn is_status_local(&self, channel_id: u32) -> bool {
+        self.is_disabled(channel_id)
+            || self.reloading.contains(&channel_id)
+            || !self.is_available(channel_id)
+    }
+
+    /// 单个通道的状态条目（通道不存在或查询失败时为 `None`）
+    pub async fn channel_status(&self, channel_id: u32) -> Option<serde_json::Value> {
+        if let Some(config) = self.disabled.get(&channel_id) {
+            return Some(serde_json::json!({
+                "channel_id": channel_id,
+                "statute": format!("{:?}", config.statute),
+                "status": { "online": false },
+                "availability": "disabled",
+            }));
+        }
+
+        let (statute, driver, availability, protocol) = {
+            let channel = self.channels.get(&channel_id)?;
+            let availability = match &channel.availability {
+                _ if self.reloading.contains(&channel_id) => "reloading",
+                None => "always",
+                Some(a) if a.is_available() => "available",
+                Some(_) => "offline_by_schedule",
+            };
+            (
+                format!("{:?}", channel.statute()),
+                channel.active_driver(),
+                availability,
+                channel.protocol.clone(),
+            )
+        };
+        if matches!(availability, "offline_by_schedule" | "reloading") {
+            return Some(serde_json::json!({
+                "channel_id": channel_id,
+                "statute": statute,
+                "driver": driver,
+                "status": { "online": false },
+                "availability": availability,
+            }));
+        }
+
+        let protocol = protocol.read().await;
+        match protocol.get_status().await {
+            Ok(status) => Some(serde_json::json!({
+                "channel_id": channel_id,
+                "statute": statute,
+                "driver": driver,
+                "status": status,
+                "availability": availability,
+            })),
+            Err(e) => {
+                warn!("获取通道 {} 状态失败: {:?}", channel_id, e);
+                None
             }
         }
+    }
 
-        Ok(serde_json::json!(statuses))
+    /// 获取各通道诊断信息（不等待正在执行命令的通道）
+    pub fn channel_diagnostics(&self) -> Vec<ChannelDiagnostics> {
+        let mut channels: Vec<ChannelDiagnostics> = self
+            .channels
+            .iter()
+            .map(|channel| {
+                let protocol = channel.protocol.try_read().ok();
+                ChannelDiagnostics {
+                    channel_id: channel.id,
+                    statute: format!("{:?}", channel.statute()),
+                    driver: channel.active_driver(),
+                    busy: protocol.is_none(),
+                    open_connections: protocol.as_ref().and_then(|p| p.open_connections()),
+                    command_queue: protocol.and_then(|p| p.command_queue_stats()),
+                    health: self.health(channel.id).snapshot(),
+                }
+            })
+            .collect();
+        channels.sort_by_key(|c| c.channel_id);
+        channels
+    }
+
+    /// 在超时时间内无法获取协议锁的通道（看门狗判定通道卡死用）
+    pub async fn unresponsive_channels(&self, timeout: Duration) -> Vec<u32> {
+        let protocols: Vec<(u32, SharedProtocol)> = self
+            .channels
+            .iter()
+            .map(|channel| (channel.id, channel.protocol.clone()))
+            .collect();
+
+        let checks = protocols
+            .into_iter()
+            .map(|(channel_id, protocol)| async move {
+                tokio::time::timeout(timeout, protocol.read())
+                    .await
+                    .is_err()
+                    .then_some(channel_id)
+            });
+        let mut unresponsive: Vec<u32> = futures::future::join_all(checks)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        unresponsive.sort_unstable();
+        unresponsive
     }
 
     /// 获取通道数量
@@ -222,10 +1159,33 @@ impl ChannelManager {
         let channel = self
             .channels
             .get(&channel_id)
-            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+            .ok_or_else(|| self.missing(channel_id))?;
 
-        let mut protocol = channel.protocol.write().await;
-        protocol.call_method(method_name, args).await
+        let driver = channel.active_driver();
+        let started = Instant::now();
+        let result = channel
+            .protocol
+            .write()
+            .await
+            .call_method(method_name, args)
+            .await;
+        self.health(channel_id).record(started.elapsed(), &result);
+        if let Err(ref e) = result {
+            self.failover(&channel, driver, e).await;
+        }
+        result
+    }
+
+    /// 调用通道的自定义方法，返回统一结构的结果
+    pub async fn invoke_method(
+        &self,
+        channel_id: u32,
+        method_name: &str,
+        args: serde_json::Value,
+    ) -> CommandResult {
+        let started = Instant::now();
+        let result = self.call_method(channel_id, method_name, args).await;
+        CommandResult::new(channel_id, method_name, started.elapsed(), &result)
     }
 
     /// 获取通道支持的方法列表
@@ -233,9 +1193,289 @@ impl ChannelManager {
         let channel = self
             .channels
             .get(&channel_id)
-            .ok_or_else(|| DeviceError::ChannelNotFound(channel_id))?;
+            .ok_or_else(|| self.missing(channel_id))?;
 
         let protocol = channel.protocol.read().await;
         Ok(protocol.get_methods())
     }
+
+    /// 通道的内容推送分块大小（协议不支持内容推送时为 None）
+    pub async fn content_chunk_size(&self, channel_id: u32) -> Result<Option<usize>> {
+        let channel = self
+            .channels
+            .get(&channel_id)
+            .ok_or_else(|| self.missing(channel_id))?;
+
+        let protocol = channel.protocol.read().await;
+        Ok(protocol.content_chunk_size())
+    }
+
+    /// 开始内容推送，返回设备端上传会话
+    pub async fn begin_content_upload(
+        &self,
+        channel_id: u32,
+        info: &ContentInfo,
+    ) -> Result<String> {
+        self.ensure_available(channel_id)?;
+        let channel = self
+            .channels
+            .get(&channel_id)
+            .ok_or_else(|| self.missing(channel_id))?;
+
+        let mut protocol = channel.protocol.write().await;
+        protocol.begin_content_upload(info).await
+    }
+
+    /// 上传内容分块（每个分块单独加锁，推送期间不阻塞其他命令）
+    pub async fn upload_content_chunk(
+        &self,
+        channel_id: u32,
+        session: &str,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        self.ensure_available(channel_id)?;
+        let channel = self
+            .channels
+            .get(&channel_id)
+            .ok_or_else(|| self.missing(channel_id))?;
+
+        let mut protocol = channel.protocol.write().await;
+        protocol.upload_content_chunk(session, offset, data).await
+    }
+
+    /// 完成内容推送，返回设备计算的 SHA-256
+    pub async fn finish_content_upload(
+        &self,
+        channel_id: u32,
+        session: &str,
+    ) -> Result<Option<String>> {
+        let channel = self
+            .channels
+            .get(&channel_id)
+            .ok_or_else(|| self.missing(channel_id))?;
+
+        let mut protocol = channel.protocol.write().await;
+        protocol.finish_content_upload(session).await
+    }
+
+    /// 放弃未完成的内容推送
+    pub async fn abort_content_upload(&self, channel_id: u32, session: &str) {
+        if let Some(channel) = self.channels.get(&channel_id) {
+            channel
+                .protocol
+                .write()
+                .await
+                .abort_content_upload(session)
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn switches_to_fallback_driver_on_connection_failure() {
+        // 绑定后立即释放，得到一个拒绝连接的端口
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config: ChannelConfig = serde_json::from_value(serde_json::json!({
+            "channel_id": 1,
+            "enable": true,
+            "statute": "pjlink",
+            "arguments": { "type": "tcp", "addr": "127.0.0.1", "port": port },
+            "fallback": [{ "statute": "mock" }]
+        }))
+        .unwrap();
+        let (event_tx, mut event_rx) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let manager = ChannelManager::new(&[config], &[], event_tx, &tasks)
+            .await
+            .unwrap();
+        assert_eq!(manager.channel_diagnostics()[0].driver, 0);
+
+        assert!(manager
+            .execute(1, "powerOn", serde_json::Value::Null)
+            .await
+            .is_err());
+        let diagnostics = &manager.channel_diagnostics()[0];
+        assert_eq!(
+            (diagnostics.driver, diagnostics.statute.as_str()),
+            (1, "Mock")
+        );
+        let switched = std::iter::from_fn(|| event_rx.try_recv().ok())
+            .any(|e| matches!(e, DeviceEvent::ChannelDriverSwitched { driver: 1, .. }));
+        assert!(switched);
+
+        manager.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn wraps_command_results_in_envelope() {
+        let config: ChannelConfig = serde_json::from_value(serde_json::json!({
+            "channel_id": 3,
+            "enable": true,
+            "statute": "mock"
+        }))
+        .unwrap();
+        let (event_tx, _) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let manager = ChannelManager::new(&[config], &[], event_tx, &tasks)
+            .await
+            .unwrap();
+
+        let result = manager
+            .execute_command(3, "ping", serde_json::json!({}))
+            .await;
+        assert!(result.success);
+        assert_eq!((result.channel_id, result.command.as_str()), (3, "ping"));
+        assert_eq!(result.data["message"], "pong");
+        assert!(result.error.is_none());
+
+        let result = manager
+            .invoke_method(9, "get_statistics", serde_json::json!({}))
+            .await;
+        assert!(!result.success);
+        assert!(result.data.is_null());
+        assert!(result.error.is_some());
+
+        let raw = CommandResult::new(
+            3,
+            "get_output",
+            Duration::from_millis(12),
+            &Ok(serde_json::json!({ "state": 1, "raw": "AA5603" })),
+        );
+        assert_eq!((raw.raw.as_deref(), raw.duration_ms), (Some("AA5603"), 12));
+
+        manager.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn replaces_channel_only_after_verification() {
+        let mock = |delay_ms: u64| -> ChannelConfig {
+            serde_json::from_value(serde_json::json!({
+                "channel_id": 1,
+                "enable": true,
+                "statute": "mock",
+                "arguments": { "delay_ms": delay_ms }
+            }))
+            .unwrap()
+        };
+        let (event_tx, _) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let manager = ChannelManager::new(&[mock(0)], &[], event_tx, &tasks)
+            .await
+            .unwrap();
+        let delay = |status: serde_json::Value| status["status"]["delay_ms"].clone();
+
+        // 新实例在验证超时内无响应：保留旧实例
+        let verify = Duration::from_millis(100);
+        assert!(manager.replace_channel(&mock(5000), verify).await.is_err());
+        assert_eq!(delay(manager.channel_status(1).await.unwrap()), 0);
+
+        manager.replace_channel(&mock(10), verify).await.unwrap();
+        assert_eq!(delay(manager.channel_status(1).await.unwrap()), 10);
+
+        manager.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn exclusive_driver_is_released_before_verification() {
+        let channel = |statute: &str, arguments: serde_json::Value| -> ChannelConfig {
+            serde_json::from_value(serde_json::json!({
+                "channel_id": 1,
+                "enable": true,
+                "statute": statute,
+                "arguments": arguments
+            }))
+            .unwrap()
+        };
+        let pjlink = channel(
+            "pjlink",
+            serde_json::json!({ "addr": "127.0.0.1", "port": 4352 }),
+        );
+        let xfusion = |agent_port: serde_json::Value| {
+            channel(
+                "xFusion",
+                serde_json::json!({ "nodes": [], "agent_port": agent_port }),
+            )
+        };
+        assert!(!ChannelManager::supports_shadow(&pjlink));
+        assert!(!ChannelManager::supports_shadow(&xfusion(
+            serde_json::json!(9100)
+        )));
+        assert!(ChannelManager::supports_shadow(&xfusion(
+            serde_json::Value::Null
+        )));
+
+        let (event_tx, _) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let manager = ChannelManager::new(&[pjlink], &[], event_tx, &tasks)
+            .await
+            .unwrap();
+        let verify = Duration::from_millis(300);
+        let previous = manager.channels.get(&1).unwrap().protocol.clone();
+
+        // 切换期间通道保持注册，请求返回可重试的切换中错误；新实例验证失败后恢复原实例
+        let slow = channel("mock", serde_json::json!({ "delay_ms": 5000 }));
+        let (replaced, during) = tokio::join!(manager.replace_channel(&slow, verify), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            manager.read(1, 1).await
+        });
+        assert!(matches!(during, Err(DeviceError::ChannelReloading(1))));
+        assert!(replaced.unwrap_err().to_string().contains("已恢复原配置"));
+        assert_eq!(manager.statute(1).as_deref(), Some("Pjlink"));
+        assert!(Arc::ptr_eq(
+            &previous,
+            &manager.channels.get(&1).unwrap().protocol
+        ));
+        assert!(manager.ensure_available(1).is_ok());
+
+        let mock = channel("mock", serde_json::json!({}));
+        manager.replace_channel(&mock, verify).await.unwrap();
+        assert_eq!(manager.statute(1).as_deref(), Some("Mock"));
+
+        manager.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn rolls_back_reload_to_unreachable_modbus_device() {
+        let closed_port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let modbus = |port: u16| -> ChannelConfig {
+            serde_json::from_value(serde_json::json!({
+                "channel_id": 1,
+                "enable": true,
+                "statute": "modbus",
+                "arguments": { "type": "tcp", "addr": "127.0.0.1", "port": port }
+            }))
+            .unwrap()
+        };
+        let (event_tx, _) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let manager = ChannelManager::new(&[modbus(502)], &[], event_tx, &tasks)
+            .await
+            .unwrap();
+
+        let error = manager
+            .replace_channel(&modbus(closed_port), Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("原配置"), "{}", error);
+        let port = manager.channels.get(&1).unwrap().drivers[0]
+            .arguments
+            .as_ref()
+            .unwrap()["port"]
+            .clone();
+        assert_eq!(port, 502);
+
+        manager.shutdown().await;
+    }
 }
@@ -0,0 +1,141 @@
+//! 危险节点写入确认
+//!
+//! 配置了 `confirm` 的节点通过 API 写入时不会立即执行，而是生成待确认请求并返回令牌，
+//! 需在有效期内由操作员再次调用确认接口后才真正写入。
+
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+
+use crate::config::ConfirmConfig;
+use crate::utils::{DeviceError, Result};
+
+/// 待确认的写入请求
+#[derive(Debug, Clone)]
+pub struct PendingWrite {
+    pub token: String,
+    pub global_id: u32,
+    pub value: f64,
+    /// 发起写入的操作员
+    pub requested_by: Option<String>,
+    /// 要求由发起人以外的操作员确认
+    pub different_operator: bool,
+    pub created_at: Instant,
+    pub expires_at: Instant,
+}
+
+/// 待确认写入表
+#[derive(Default)]
+pub struct ConfirmationManager {
+    pending: DashMap<String, PendingWrite>,
+}
+
+impl ConfirmationManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记待确认写入，返回确认令牌
+    pub fn request(
+        &self,
+        global_id: u32,
+        value: f64,
+        operator: Option<String>,
+        config: &ConfirmConfig,
+    ) -> PendingWrite {
+        self.purge_expired();
+        let now = Instant::now();
+        let pending = PendingWrite {
+            token: uuid::Uuid::new_v4().to_string(),
+            global_id,
+            value,
+            requested_by: operator,
+            different_operator: config.different_operator,
+            created_at: now,
+            expires_at: now + Duration::from_secs(config.timeout),
+        };
+        self.pending.insert(pending.token.clone(), pending.clone());
+        pending
+    }
+
+    /// 校验并取出待确认写入（令牌只能使用一次）
+    pub fn take(&self, token: &str, operator: Option<&str>) -> Result<PendingWrite> {
+        let pending = match self.pending.get(token) {
+            Some(p) => p.clone(),
+            None => return Err(token_not_found(token)),
+        };
+
+        if pending.expires_at <= Instant::now() {
+            self.pending.remove(token);
+            return Err(DeviceError::Other(format!(
+                "确认令牌 '{}' 已过期，请重新发起写入",
+                token
+            )));
+        }
+
+        if pending.different_operator {
+            match (operator, pending.requested_by.as_deref()) {
+                (None, _) => {
+                    return Err(DeviceError::Other(
+                        "该节点要求由其他操作员确认，请提供 operator".to_string(),
+                    ))
+                }
+                (Some(confirmer), Some(requester)) if confirmer == requester => {
+                    return Err(DeviceError::Other(format!(
+                        "该节点要求由发起人 '{}' 以外的操作员确认",
+                        requester
+                    )))
+                }
+                _ => {}
+            }
+        }
+
+        // 并发确认时只有一个请求能取出
+        self.pending
+            .remove(token)
+            .map(|(_, p)| p)
+            .ok_or_else(|| token_not_found(token))
+    }
+
+    /// 取消待确认写入
+    pub fn cancel(&self, token: &str) -> Option<PendingWrite> {
+        self.pending.remove(token).map(|(_, p)| p)
+    }
+
+    /// 全部未过期的待确认写入（按创建时间排序）
+    pub fn list(&self) -> Vec<PendingWrite> {
+        self.purge_expired();
+        let mut pending: Vec<PendingWrite> = self.pending.iter().map(|p| p.clone()).collect();
+        pending.sort_by_key(|p| p.created_at);
+        pending
+    }
+
+    fn purge_expired(&self) {
+        let now = Instant::now();
+        self.pending.retain(|_, p| p.expires_at > now);
+    }
+}
+
+fn token_not_found(token: &str) -> DeviceError {
+    DeviceError::Other(format!("确认令牌 '{}' 不存在或已使用", token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirm_by_different_operator_once() {
+        let manager = ConfirmationManager::new();
+        let config = ConfirmConfig {
+            timeout: 30,
+            different_operator: true,
+        };
+        let pending = manager.request(7, 1.0, Some("alice".to_string()), &config);
+
+        assert!(manager.take(&pending.token, None).is_err());
+        assert!(manager.take(&pending.token, Some("alice")).is_err());
+        let confirmed = manager.take(&pending.token, Some("bob")).unwrap();
+        assert_eq!((confirmed.global_id, confirmed.value), (7, 1.0));
+        assert!(manager.take(&pending.token, Some("bob")).is_err());
+    }
+}
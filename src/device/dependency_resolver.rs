@@ -1,10 +1,10 @@
 /// 依赖解析器 - 负责依赖条件检查和满足
 use std::sync::Arc;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use super::{DeviceController, NodeManager};
-use crate::config::Dependency;
-use crate::utils::{DeviceError, Result};
+use crate::config::{Dependency, DependencyMode};
+use crate::utils::{DeviceError, Result, UnmetDependency};
 
 /// 依赖解析器
 pub struct DependencyResolver {
@@ -17,18 +17,38 @@ impl DependencyResolver {
         Self { node_manager }
     }
 
-    /// 检查依赖列表是否全部满足
+    /// 检查依赖列表是否全部满足（仅警告的依赖不影响结果）
     pub async fn check_dependencies(&self, dependencies: &[Dependency]) -> Result<bool> {
+        Ok(self.unmet_dependencies(dependencies).await?.is_empty())
+    }
+
+    /// 列出阻止写入的未满足依赖
+    ///
+    /// 仅警告（`mode = "warn"`）的依赖不计入结果，在其余依赖都满足、写入即将执行时记录警告日志。
+    pub async fn unmet_dependencies(
+        &self,
+        dependencies: &[Dependency],
+    ) -> Result<Vec<UnmetDependency>> {
+        let mut blocking = Vec::new();
+        let mut warnings = Vec::new();
         for dep in dependencies {
-            if !self.check_single_dependency(dep).await? {
-                return Ok(false);
+            if let Some(unmet) = self.check_single_dependency(dep).await? {
+                match dep.mode {
+                    DependencyMode::Block => blocking.push(unmet),
+                    DependencyMode::Warn => warnings.push(unmet),
+                }
+            }
+        }
+        if blocking.is_empty() {
+            for unmet in warnings {
+                warn!("依赖条件未满足（仅警告，继续写入）: {}", unmet);
             }
         }
-        Ok(true)
+        Ok(blocking)
     }
 
-    /// 检查单个依赖条件
-    async fn check_single_dependency(&self, dep: &Dependency) -> Result<bool> {
+    /// 检查单个依赖条件，未满足时返回说明
+    async fn check_single_dependency(&self, dep: &Dependency) -> Result<Option<UnmetDependency>> {
         // 获取依赖节点的全局ID
         let global_id = if let Some(channel_id) = dep.channel_id {
             // 通过channel_id和id查找
@@ -54,6 +74,20 @@ impl DependencyResolver {
             .get_state(global_id)
             .ok_or_else(|| DeviceError::DeviceNotFound(format!("节点 {}", global_id)))?;
 
+        let unmet = |condition: &str, expected: serde_json::Value, actual: serde_json::Value| {
+            Some(UnmetDependency {
+                global_id,
+                alias: self
+                    .node_manager
+                    .get_node(global_id)
+                    .map(|n| n.alias)
+                    .unwrap_or_default(),
+                condition: condition.to_string(),
+                expected,
+                actual,
+            })
+        };
+
         // 检查条件
         if let Some(expected_value) = dep.value {
             // 检查值是否匹配
@@ -63,11 +97,15 @@ impl DependencyResolver {
                         "依赖节点 {} 值不匹配: 期望 {}, 实际 {}",
                         global_id, expected_value, current_value
                     );
-                    return Ok(false);
+                    return Ok(unmet("value", expected_value.into(), current_value.into()));
                 }
             } else {
                 debug!("依赖节点 {} 当前值未知", global_id);
-                return Ok(false);
+                return Ok(unmet(
+                    "value",
+                    expected_value.into(),
+                    serde_json::Value::Null,
+                ));
             }
         }
 
@@ -78,11 +116,11 @@ impl DependencyResolver {
                     "依赖节点 {} 状态不匹配: 期望 {}, 实际 {}",
                     global_id, expected_status, state.online
                 );
-                return Ok(false);
+                return Ok(unmet("online", expected_status.into(), state.online.into()));
             }
         }
 
-        Ok(true)
+        Ok(None)
     }
 
     /// 自动满足依赖条件（用于auto策略）
@@ -93,7 +131,8 @@ impl DependencyResolver {
     ) -> Result<()> {
         info!("自动满足依赖条件...");
 
-        for dep in dependencies {
+        // 仅警告的依赖不自动满足
+        for dep in dependencies.iter().filter(|d| d.mode.is_block()) {
             if let (Some(node_id), Some(target_value)) = (dep.id, dep.value) {
                 // 获取当前状态
                 let state = self
@@ -101,11 +140,23 @@ impl DependencyResolver {
                     .get_state(node_id)
                     .ok_or_else(|| DeviceError::DeviceNotFound(format!("节点 {}", node_id)))?;
 
-                // 检查是否需要改变
-                if state.current_value != Some(target_value) {
+                // 检查是否需要改变（被强制的节点不下发到设备）
+                if state.current_value != Some(target_value)
+                    && self.node_manager.suppress_write(node_id).is_none()
+                {
                     info!("设置依赖节点 {} = {}", node_id, target_value);
+                    let device_value = match self.node_manager.get_node(node_id) {
+                        Some(node) => {
+                            DeviceController::to_device_value(&node, target_value.into())?
+                        }
+                        None => target_value as f64,
+                    };
                     controller
-                        .execute_write(state.channel_id, state.device_id, target_value)
+                        .execute_write(
+                            state.channel_id,
+                            state.device_id,
+                            device_value.round() as i32,
+                        )
                         .await?;
 
                     // 等待一小段时间让设备响应
@@ -117,3 +168,33 @@ impl DependencyResolver {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NodeConfig;
+    use tokio::sync::broadcast;
+
+    #[tokio::test]
+    async fn explains_blocking_dependencies_and_skips_warn_only() {
+        let node: NodeConfig = serde_json::from_value(serde_json::json!({
+            "global_id": 3, "channel_id": 1, "id": 3, "alias": "投影幕"
+        }))
+        .unwrap();
+        let (tx, _) = broadcast::channel(16);
+        let node_manager = Arc::new(NodeManager::new(&[node], tx));
+        node_manager.update_value(3, 1);
+        let resolver = DependencyResolver::new(node_manager);
+
+        let deps: Vec<Dependency> = serde_json::from_value(serde_json::json!([
+            { "id": 3, "value": 0 },
+            { "id": 3, "value": 2, "mode": "warn" },
+            { "id": 3, "status": true }
+        ]))
+        .unwrap();
+        let unmet = resolver.unmet_dependencies(&deps).await.unwrap();
+        assert_eq!(unmet.len(), 1);
+        assert_eq!(unmet[0].to_string(), "节点 3（投影幕）value 期望 0，实际 1");
+        assert!(resolver.check_dependencies(&deps[1..]).await.unwrap());
+    }
+}
@@ -0,0 +1,307 @@
+//! 通道通信健康度与诊断节点
+//!
+//! 通道管理器统计每个通道经由它发出的请求（读、写、命令、方法调用）：连续失败次数、
+//! 最近一次请求耗时、恢复连接次数与累计错误数。启用 `diagnostic_nodes` 后，这些指标以只读
+//! 节点的形式出现在保留的 global_id 区间内，现有的节点看板、告警规则、事件订阅可直接监视
+//! 通信状况；累计计数通过节点值持久化在重启后保留。
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use super::{ChannelManager, NodeManager};
+use crate::config::{DiagnosticNodesConfig, NodeConfig};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::{DeviceError, Result};
+
+/// 每个通道占用的 global_id 数量（为后续指标预留）
+const IDS_PER_CHANNEL: u32 = 10;
+
+/// 通道健康度计数器
+#[derive(Default)]
+pub struct ChannelHealth {
+    consecutive_failures: AtomicU32,
+    last_latency_ms: AtomicU32,
+    reconnects: AtomicU64,
+    errors: AtomicU64,
+    /// 最近一次失败是连接类错误，下次成功时计为恢复连接
+    disconnected: AtomicBool,
+}
+
+/// 通道健康度快照
+#[derive(Debug, Clone, Copy, Default, Serialize, ToSchema)]
+pub struct ChannelHealthSnapshot {
+    /// 连续失败次数（成功后清零）
+    pub consecutive_failures: u32,
+    /// 最近一次请求耗时（毫秒）
+    pub last_latency_ms: u32,
+    /// 恢复连接次数（连接失败后重新成功，或切换备用驱动）
+    pub reconnects: u64,
+    /// 累计错误数
+    pub errors: u64,
+}
+
+impl ChannelHealth {
+    /// 记录一次请求结果
+    pub fn record<T>(&self, elapsed: Duration, result: &Result<T>) {
+        self.last_latency_ms.store(
+            elapsed.as_millis().min(u32::MAX as u128) as u32,
+            Ordering::Relaxed,
+        );
+        match result {
+            Ok(_) => {
+                self.consecutive_failures.store(0, Ordering::Relaxed);
+                if self.disconnected.swap(false, Ordering::Relaxed) {
+                    self.reconnects.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Err(e) => {
+                self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                if matches!(
+                    e,
+                    DeviceError::ConnectionError(_) | DeviceError::Timeout | DeviceError::Io(_)
+                ) {
+                    self.disconnected.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    /// 记录一次备用驱动切换
+    pub fn record_switch(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+        self.disconnected.store(false, Ordering::Relaxed);
+    }
+
+    /// 以持久化的累计值为起点继续计数
+    pub fn seed(&self, reconnects: u64, errors: u64) {
+        self.reconnects.fetch_add(reconnects, Ordering::Relaxed);
+        self.errors.fetch_add(errors, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ChannelHealthSnapshot {
+        ChannelHealthSnapshot {
+            consecutive_failures: self.consecutive_failures.load(Ordering::Relaxed),
+            last_latency_ms: self.last_latency_ms.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 诊断指标（偏移量为 global_id 在通道区间内的位置）
+#[derive(Debug, Clone, Copy)]
+enum Metric {
+    ConsecutiveFailures = 0,
+    LastLatency = 1,
+    Reconnects = 2,
+    Errors = 3,
+}
+
+impl Metric {
+    const ALL: [Metric; 4] = [
+        Metric::ConsecutiveFailures,
+        Metric::LastLatency,
+        Metric::Reconnects,
+        Metric::Errors,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Metric::ConsecutiveFailures => "连续失败次数",
+            Metric::LastLatency => "最近请求耗时",
+            Metric::Reconnects => "恢复连接次数",
+            Metric::Errors => "累计错误数",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Metric::ConsecutiveFailures => "consecutive_failures",
+            Metric::LastLatency => "last_latency_ms",
+            Metric::Reconnects => "reconnects",
+            Metric::Errors => "errors",
+        }
+    }
+
+    /// 累计计数持久化，瞬时值不持久化
+    fn persistent(self) -> bool {
+        matches!(self, Metric::Reconnects | Metric::Errors)
+    }
+
+    fn value(self, snapshot: &ChannelHealthSnapshot) -> i32 {
+        let value = match self {
+            Metric::ConsecutiveFailures => snapshot.consecutive_failures as u64,
+            Metric::LastLatency => snapshot.last_latency_ms as u64,
+            Metric::Reconnects => snapshot.reconnects,
+            Metric::Errors => snapshot.errors,
+        };
+        value.min(i32::MAX as u64) as i32
+    }
+}
+
+/// 诊断节点布局：通道 c 的指标 m 的 global_id 为 `base_id + c * 10 + m`
+#[derive(Debug, Clone)]
+pub struct DiagnosticNodes {
+    base_id: u32,
+    end_id: u32,
+    channels: Vec<u32>,
+}
+
+impl DiagnosticNodes {
+    /// 按通道列表计算保留区间，区间溢出或与配置的节点冲突时返回错误
+    pub fn new(
+        config: &DiagnosticNodesConfig,
+        channels: &[u32],
+        nodes: &[NodeConfig],
+    ) -> Result<Self> {
+        let max_channel = channels.iter().copied().max().unwrap_or(0);
+        let end_id = max_channel
+            .checked_add(1)
+            .and_then(|n| n.checked_mul(IDS_PER_CHANNEL))
+            .and_then(|n| n.checked_add(config.base_id))
+            .ok_or_else(|| {
+                DeviceError::ConfigError(format!(
+                    "诊断节点起始 ID {} 与通道 ID {} 超出 global_id 范围",
+                    config.base_id, max_channel
+                ))
+            })?;
+        let layout = Self {
+            base_id: config.base_id,
+            end_id,
+            channels: channels.to_vec(),
+        };
+        if let Some(node) = nodes.iter().find(|n| layout.contains(n.global_id)) {
+            return Err(DeviceError::ConfigError(format!(
+                "节点 {} ({}) 位于诊断节点保留区间 [{}, {})",
+                node.global_id, node.alias, layout.base_id, layout.end_id
+            )));
+        }
+        Ok(layout)
+    }
+
+    /// global_id 是否位于保留区间
+    pub fn contains(&self, global_id: u32) -> bool {
+        (self.base_id..self.end_id).contains(&global_id)
+    }
+
+    fn global_id(&self, channel_id: u32, metric: Metric) -> u32 {
+        self.base_id + channel_id * IDS_PER_CHANNEL + metric as u32
+    }
+
+    /// 生成诊断节点配置
+    pub fn nodes(&self) -> Vec<NodeConfig> {
+        self.channels
+            .iter()
+            .flat_map(|&channel_id| {
+                Metric::ALL.into_iter().map(move |metric| {
+                    let mut metadata = serde_json::Map::new();
+                    metadata.insert("writeable".into(), false.into());
+                    metadata.insert("diagnostic".into(), metric.key().into());
+                    if matches!(metric, Metric::LastLatency) {
+                        metadata.insert("unit".into(), "ms".into());
+                    }
+                    serde_json::from_value::<NodeConfig>(serde_json::json!({
+                        "global_id": self.global_id(channel_id, metric),
+                        "channel_id": channel_id,
+                        "id": metric as u32,
+                        "category": "diagnostic",
+                        "alias": format!("通道 {} {}", channel_id, metric.label()),
+                        "metadata": metadata,
+                        "persist": metric.persistent(),
+                    }))
+                    .expect("诊断节点配置")
+                })
+            })
+            .collect()
+    }
+
+    /// 以节点恢复的持久化值为起点继续累计
+    pub fn seed(&self, channel_manager: &ChannelManager, node_manager: &NodeManager) {
+        for &channel_id in &self.channels {
+            let restored = |metric| {
+                node_manager
+                    .get_state(self.global_id(channel_id, metric))
+                    .and_then(|s| s.current_value)
+                    .map_or(0, |v| v.max(0) as u64)
+            };
+            channel_manager
+                .health(channel_id)
+                .seed(restored(Metric::Reconnects), restored(Metric::Errors));
+        }
+    }
+
+    /// 启动同步任务：定期把通道健康度写入诊断节点
+    pub fn spawn(
+        self,
+        tasks: &TaskRegistry,
+        interval: Duration,
+        channel_manager: Arc<ChannelManager>,
+        node_manager: Arc<NodeManager>,
+    ) {
+        tasks.spawn("diagnostic-nodes", async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for &channel_id in &self.channels {
+                    let snapshot = channel_manager.health(channel_id).snapshot();
+                    for metric in Metric::ALL {
+                        node_manager.update_value(
+                            self.global_id(channel_id, metric),
+                            metric.value(&snapshot),
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_failures_and_recoveries() {
+        let health = ChannelHealth::default();
+        health.seed(2, 10);
+        health.record::<()>(
+            Duration::from_millis(5),
+            &Err(DeviceError::ConnectionError("refused".into())),
+        );
+        health.record::<()>(Duration::from_millis(7), &Err(DeviceError::Timeout));
+        let snapshot = health.snapshot();
+        assert_eq!(
+            (
+                snapshot.consecutive_failures,
+                snapshot.errors,
+                snapshot.reconnects
+            ),
+            (2, 12, 2)
+        );
+
+        health.record(Duration::from_millis(30), &Ok(()));
+        let snapshot = health.snapshot();
+        assert_eq!(
+            (
+                snapshot.consecutive_failures,
+                snapshot.last_latency_ms,
+                snapshot.reconnects
+            ),
+            (0, 30, 3)
+        );
+
+        let config = DiagnosticNodesConfig {
+            enable: true,
+            base_id: 900_000,
+            interval_ms: 1000,
+        };
+        let layout = DiagnosticNodes::new(&config, &[1, 3], &[]).unwrap();
+        let ids: Vec<u32> = layout.nodes().iter().map(|n| n.global_id).collect();
+        assert_eq!(ids[..4], [900_010, 900_011, 900_012, 900_013]);
+        assert!(layout.contains(900_033) && !layout.contains(900_040));
+    }
+}
@@ -1,30 +1,155 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 use tracing::{debug, info};
+use utoipa::ToSchema;
 
-use crate::config::Config;
+use crate::config::{Config, NodeConfig, SceneConfig, StoreForwardConfig};
+use crate::protocols::ContentInfo;
+use crate::utils::tasks::TaskRegistry;
 use crate::utils::{DeviceError, Result};
 
+mod analytics;
+mod availability;
 mod channel_manager;
+mod confirmation;
 mod dependency_resolver;
+mod health;
 mod node_manager;
+mod persistence;
+mod ping;
+mod profile;
+mod retry;
 mod scene_executor;
+pub(crate) mod scene_transfer;
+mod startup_order;
+mod stats;
+mod status_cache;
+mod store_forward;
 mod task_scheduler;
+mod telemetry;
+pub(crate) mod transform;
 
-pub use channel_manager::ChannelManager;
+pub use analytics::{AnalyticsReport, AnomalyKind, NodeAnalytics, NodeAnomaly};
+pub use channel_manager::{ChannelDiagnostics, ChannelManager, CommandResult};
+pub use confirmation::{ConfirmationManager, PendingWrite};
 pub use dependency_resolver::DependencyResolver;
-pub use node_manager::{NodeManager, NodeState};
-pub use scene_executor::{SceneExecutionStatus, SceneExecutor};
+pub use health::{ChannelHealth, ChannelHealthSnapshot, DiagnosticNodes};
+pub use node_manager::{ForcedNode, NodeManager, NodeState};
+pub use ping::{PingMode, PingReport, PingSample};
+pub use profile::{ProfileRunner, ProfileStatus};
+pub use scene_executor::{
+    SceneCue, SceneExecutionStatus, SceneExecutor, SceneRunResult, SceneRunStatus, SceneStepDiff,
+    SceneStepFailure,
+};
+pub use startup_order::{
+    ChannelStartup, StartupOrderReport, StartupViolation, StartupViolationKind,
+};
+pub use stats::{ExecutionStats, ExecutionStatsReport, NodeWriteStats, SceneStats};
+pub use status_cache::ChannelStatusCache;
+pub use store_forward::{QueuedWrite, StoreForwardQueue};
 pub use task_scheduler::TaskScheduler;
 
+/// 设备事件广播队列容量
+const EVENT_BUS_CAPACITY: usize = 1000;
+
+/// 热重载替换通道时，新协议实例的连接验证超时
+const CHANNEL_SWAP_VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 事件订阅者处理过慢而丢失的事件累计数（所有订阅者合计）
+static LAGGED_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// 记录订阅者丢失的事件数（收到 `RecvError::Lagged` 时调用）
+pub(crate) fn record_lagged_events(count: u64) {
+    LAGGED_EVENTS.fetch_add(count, Ordering::Relaxed);
+}
+
+/// 事件总线统计
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EventBusStats {
+    /// 当前订阅者数量
+    pub receivers: usize,
+    /// 队列中尚未被全部订阅者取走的事件数
+    pub queued: usize,
+    /// 队列容量
+    pub capacity: usize,
+    /// 订阅者丢失的事件累计数（进程启动以来）
+    pub lagged_total: u64,
+}
+
 /// 设备事件
-#[derive(Debug, Clone)]
+///
+/// 序列化为带 `type` 字段的 JSON（如 `{"type": "node_state_changed", "global_id": 1, ...}`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum DeviceEvent {
     /// 节点状态变化
     NodeStateChanged {
         global_id: u32,
         old_value: i32,
         new_value: i32,
+        /// 新值为调试强制值（持久化、遥测与异常分析忽略强制值）
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        forced: bool,
+    },
+
+    /// 节点被强制为固定值
+    NodeForced {
+        global_id: u32,
+        value: i32,
+        operator: String,
+    },
+
+    /// 节点解除强制
+    NodeForceReleased {
+        global_id: u32,
+    },
+
+    /// 通道离线，节点写入已暂存，通道恢复后下发
+    NodeWriteQueued {
+        global_id: u32,
+        channel_id: u32,
+        value: i32,
+        reason: String,
+    },
+
+    /// 暂存的写入已下发到设备
+    NodeWriteReplayed {
+        global_id: u32,
+        value: i32,
+    },
+
+    /// 暂存的写入超过有效期未能下发，已作废
+    NodeWriteExpired {
+        global_id: u32,
+        value: i32,
+    },
+
+    /// 时间曲线开始运行
+    ProfileStarted {
+        profile: String,
+        global_id: u32,
+    },
+
+    /// 时间曲线停止运行
+    ProfileStopped {
+        profile: String,
+        global_id: u32,
+    },
+
+    /// 节点值异常（卡死或变化过快）
+    NodeAnomaly {
+        global_id: u32,
+        kind: AnomalyKind,
+        detail: String,
+    },
+
+    /// 节点值异常已消除
+    NodeAnomalyCleared {
+        global_id: u32,
+        kind: AnomalyKind,
     },
 
     /// 通道连接状态变化
@@ -37,6 +162,15 @@ pub enum DeviceEvent {
         reason: String,
     },
 
+    /// 通道因连接失败切换到备用驱动
+    ChannelDriverSwitched {
+        channel_id: u32,
+        /// 切换后的驱动序号（0 为主驱动）
+        driver: usize,
+        statute: String,
+        reason: String,
+    },
+
     /// 任务状态变化
     TaskCompleted {
         task_id: String,
@@ -51,7 +185,117 @@ pub enum DeviceEvent {
     SceneCompleted {
         scene_name: String,
         success: bool,
+        /// 结构化执行结果（失败步骤、是否取消 / 中止）
+        result: SceneRunResult,
     },
+
+    /// 场景请求切换屏幕播放列表（由播放列表调度处理）
+    PlaylistSwitch {
+        screen_id: String,
+        /// 为空时恢复按日期自动选择
+        playlist_id: Option<String>,
+        scene_name: String,
+    },
+
+    /// 屏幕开始播放新的素材
+    PlaylistItemChanged {
+        screen_id: String,
+        playlist_id: String,
+        material_id: String,
+        channel_id: u32,
+    },
+
+    /// 内容推送进度
+    ContentPushProgress {
+        push_id: String,
+        channel_id: u32,
+        material_id: String,
+        sent_bytes: u64,
+        total_bytes: u64,
+    },
+
+    /// 内容推送结束（成功且校验通过，或失败）
+    ContentPushFinished {
+        push_id: String,
+        channel_id: u32,
+        material_id: String,
+        success: bool,
+        error: Option<String>,
+    },
+
+    /// 协议自定义事件（告警、动作完成等）
+    ProtocolEvent {
+        channel_id: u32,
+        event: String,
+        data: serde_json::Value,
+    },
+
+    /// 进程内发生 panic（崩溃报告已写入 report_file）
+    Crashed {
+        thread: String,
+        message: String,
+        location: Option<String>,
+        report_file: Option<String>,
+    },
+}
+
+impl DeviceEvent {
+    /// 事件关联的节点
+    pub fn global_id(&self) -> Option<u32> {
+        match self {
+            DeviceEvent::NodeStateChanged { global_id, .. }
+            | DeviceEvent::NodeAnomaly { global_id, .. }
+            | DeviceEvent::NodeAnomalyCleared { global_id, .. }
+            | DeviceEvent::NodeForced { global_id, .. }
+            | DeviceEvent::NodeForceReleased { global_id }
+            | DeviceEvent::NodeWriteQueued { global_id, .. }
+            | DeviceEvent::NodeWriteReplayed { global_id, .. }
+            | DeviceEvent::NodeWriteExpired { global_id, .. }
+            | DeviceEvent::ProfileStarted { global_id, .. }
+            | DeviceEvent::ProfileStopped { global_id, .. } => Some(*global_id),
+            _ => None,
+        }
+    }
+
+    /// 事件关联的通道
+    pub fn channel_id(&self) -> Option<u32> {
+        match self {
+            DeviceEvent::ChannelConnected { channel_id }
+            | DeviceEvent::ChannelDisconnected { channel_id, .. }
+            | DeviceEvent::ChannelDriverSwitched { channel_id, .. }
+            | DeviceEvent::PlaylistItemChanged { channel_id, .. }
+            | DeviceEvent::ContentPushProgress { channel_id, .. }
+            | DeviceEvent::ContentPushFinished { channel_id, .. }
+            | DeviceEvent::ProtocolEvent { channel_id, .. } => Some(*channel_id),
+            _ => None,
+        }
+    }
+}
+
+/// 在序列化后的事件中补充节点信息（别名、分类、通道、单位）与通道协议类型，已有字段不覆盖
+fn enrich_event_value(
+    value: &mut serde_json::Value,
+    node: Option<&NodeState>,
+    unit: Option<String>,
+    statute: Option<String>,
+) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    if let Some(node) = node {
+        object.entry("alias").or_insert(node.alias.clone().into());
+        object
+            .entry("category")
+            .or_insert(node.category.clone().into());
+        object.entry("channel_id").or_insert(node.channel_id.into());
+        object.entry("device_id").or_insert(node.device_id.into());
+    }
+    if let Some(unit) = unit {
+        object.entry("unit").or_insert(unit.into());
+    }
+    if let Some(statute) = statute {
+        object.entry("statute").or_insert(statute.into());
+    }
 }
 
 /// 设备控制器 - 系统核心协调器
@@ -74,6 +318,33 @@ pub struct DeviceController {
 
     /// 事件广播器
     event_tx: broadcast::Sender<DeviceEvent>,
+
+    /// 待确认的危险节点写入
+    confirmations: Arc<ConfirmationManager>,
+
+    /// 节点值异常分析（未启用时为空）
+    analytics: Option<Arc<NodeAnalytics>>,
+
+    /// 通道离线时暂存的节点写入
+    store_forward: Arc<StoreForwardQueue>,
+
+    /// 时间曲线运行器
+    profiles: Arc<ProfileRunner>,
+
+    /// 通道健康度诊断节点（未启用时为空）
+    diagnostics: Option<DiagnosticNodes>,
+
+    /// 场景与节点写入执行统计
+    stats: Arc<ExecutionStats>,
+
+    /// 节点读写瞬时错误重试策略（未启用时为空）
+    retry: Option<retry::RetryPolicy>,
+
+    /// 通道状态缓存（未启用时为空）
+    status_cache: Option<Arc<ChannelStatusCache>>,
+
+    /// 后台任务（调度循环、通道监视器等）
+    tasks: TaskRegistry,
 }
 
 impl DeviceController {
@@ -81,19 +352,138 @@ impl DeviceController {
     pub async fn new(config: Config) -> Result<Self> {
         info!("初始化设备控制器...");
 
+        // 站点声明与通道引用
+        config.validate_sites().map_err(DeviceError::ConfigError)?;
+
+        // 设置 HTTP 客户端全局默认配置
+        crate::utils::http::set_global_defaults(config.http.clone().unwrap_or_default());
+
+        // 设置主机别名表与解析缓存
+        crate::utils::dns::configure(&config.dns.clone().unwrap_or_default())?;
+
+        // 设置 API 单位制换算的单位对
+        crate::utils::units::configure(&config.units.clone().unwrap_or_default())?;
+
         // 创建事件广播器
-        let (event_tx, _) = broadcast::channel(1000);
+        let (event_tx, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+
+        // 时序数据库导出目标（此处只校验配置，写入时才连接）
+        let telemetry = match config.telemetry.as_ref().filter(|t| t.enable) {
+            Some(telemetry_config) => Some((
+                telemetry_config.clone(),
+                telemetry::sink_from_config(telemetry_config)?,
+            )),
+            None => None,
+        };
+
+        // 崩溃报告配置；panic 时通过事件总线发出最后一条事件
+        crate::utils::crash::configure(&config.crash.clone().unwrap_or_default());
+        let crash_tx = event_tx.clone();
+        crate::utils::crash::set_notifier(move |report| {
+            let _ = crash_tx.send(DeviceEvent::Crashed {
+                thread: report.thread.clone(),
+                message: report.message.clone(),
+                location: report.location.clone(),
+                report_file: report.report_file.clone(),
+            });
+        });
+
+        let tasks = TaskRegistry::new();
 
         // 创建通道管理器
-        let channel_manager =
-            Arc::new(ChannelManager::new(&config.channels, event_tx.clone()).await?);
+        let channel_manager = Arc::new(
+            ChannelManager::new(
+                &config.channels,
+                &config.channel_groups,
+                event_tx.clone(),
+                &tasks,
+            )
+            .await?,
+        );
+
+        // 解析节点引用的符号标签并校验，生成诊断节点，失败时停止已启动的通道任务
+        let prepared = match Self::prepare_nodes(&channel_manager, config.nodes).await {
+            Ok(nodes) => {
+                Self::diagnostic_layout(&config.channels, config.diagnostic_nodes.as_ref(), &nodes)
+                    .map(|diagnostics| (nodes, diagnostics))
+            }
+            Err(e) => Err(e),
+        };
+        let (mut nodes, diagnostics) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                channel_manager.shutdown().await;
+                tasks.shutdown().await;
+                return Err(e);
+            }
+        };
+
+        if let Some(diagnostics) = &diagnostics {
+            nodes.extend(diagnostics.nodes());
+        }
 
         // 创建节点管理器
-        let node_manager = Arc::new(NodeManager::new(&config.nodes, event_tx.clone()));
+        let node_manager = Arc::new(NodeManager::new(&nodes, event_tx.clone()));
+
+        // 恢复持久化节点的最后已知值，并在值变化时写回存储
+        if nodes.iter().any(|n| n.persist) {
+            let storage = crate::protocols::storage::get_or_init_storage().await;
+            persistence::restore(&nodes, &node_manager, &storage).await;
+            persistence::spawn_persister(&tasks, &nodes, storage, event_tx.subscribe());
+        }
+
+        // 诊断节点从持久化的累计值继续计数
+        if let Some(diagnostics) = &diagnostics {
+            diagnostics.seed(&channel_manager, &node_manager);
+            diagnostics.clone().spawn(
+                &tasks,
+                Duration::from_millis(
+                    config
+                        .diagnostic_nodes
+                        .as_ref()
+                        .map_or(1000, |d| d.interval_ms.max(100)),
+                ),
+                channel_manager.clone(),
+                node_manager.clone(),
+            );
+        }
+
+        if let Some((telemetry_config, sink)) = telemetry {
+            telemetry::spawn_exporter(
+                &tasks,
+                &telemetry_config,
+                &nodes,
+                sink,
+                event_tx.subscribe(),
+            );
+        }
+
+        // 节点值异常分析（卡死值与变化率）
+        let analytics = config
+            .analytics
+            .as_ref()
+            .filter(|a| a.enable)
+            .map(|analytics_config| {
+                let analytics = Arc::new(NodeAnalytics::new(
+                    analytics_config,
+                    &nodes,
+                    node_manager.clone(),
+                    event_tx.clone(),
+                ));
+                analytics.spawn(
+                    &tasks,
+                    Duration::from_millis(analytics_config.check_interval_ms),
+                    event_tx.subscribe(),
+                );
+                analytics
+            });
 
         // 创建依赖解析器
         let dependency_resolver = Arc::new(DependencyResolver::new(node_manager.clone()));
 
+        // 场景与节点写入执行统计
+        let stats = Arc::new(ExecutionStats::default());
+
         // 创建任务调度器
         let task_scheduler = Arc::new(
             TaskScheduler::new(
@@ -102,14 +492,56 @@ impl DeviceController {
                 node_manager.clone(),
                 dependency_resolver.clone(),
                 event_tx.clone(),
+                stats.clone(),
+                &tasks,
             )
             .await,
         );
 
+        // 时间曲线（写入经任务调度器下发）
+        let profiles = match ProfileRunner::new(
+            &config.profiles,
+            task_scheduler.clone(),
+            node_manager.clone(),
+            event_tx.clone(),
+            &tasks,
+        ) {
+            Ok(profiles) => Arc::new(profiles),
+            Err(e) => {
+                channel_manager.shutdown().await;
+                tasks.shutdown().await;
+                return Err(e);
+            }
+        };
+        profiles.autostart();
+
+        // 离线写入暂存，通道恢复连接时下发
+        let store_forward = Arc::new(StoreForwardQueue::new(event_tx.clone()));
+        store_forward.spawn(
+            &tasks,
+            channel_manager.clone(),
+            node_manager.clone(),
+            dependency_resolver.clone(),
+            event_tx.subscribe(),
+        );
+
+        // 通道状态缓存，后台定期刷新
+        let status_cache_config = config.status_cache.clone().unwrap_or_default();
+        let status_cache = status_cache_config.enable.then(|| {
+            let cache = Arc::new(ChannelStatusCache::new(
+                channel_manager.clone(),
+                &status_cache_config,
+            ));
+            cache.spawn(
+                &tasks,
+                Duration::from_millis(status_cache_config.refresh_interval_ms.max(500)),
+            );
+            cache
+        });
+
         // 创建场景执行器
         let scene_executor = Arc::new(SceneExecutor::new(
             config.scenes.clone(),
-            channel_manager.clone(),
             node_manager.clone(),
             event_tx.clone(),
         ));
@@ -123,17 +555,197 @@ impl DeviceController {
             scene_executor,
             dependency_resolver,
             event_tx,
+            confirmations: Arc::new(ConfirmationManager::new()),
+            analytics,
+            store_forward,
+            profiles,
+            diagnostics,
+            stats,
+            retry: config
+                .retry
+                .as_ref()
+                .and_then(retry::RetryPolicy::from_config),
+            status_cache,
+            tasks,
         })
     }
 
+    /// 停止全部后台任务（配置热重载替换控制器或服务退出时调用）
+    pub async fn shutdown(&self) {
+        info!("停止设备控制器后台任务...");
+        self.channel_manager.shutdown().await;
+        self.tasks.shutdown().await;
+        info!("设备控制器后台任务已停止");
+    }
+
+    /// 启用诊断节点时计算保留区间，并校验与配置的节点不冲突
+    fn diagnostic_layout(
+        channels: &[crate::config::ChannelConfig],
+        config: Option<&crate::config::DiagnosticNodesConfig>,
+        nodes: &[NodeConfig],
+    ) -> Result<Option<DiagnosticNodes>> {
+        let Some(config) = config.filter(|d| d.enable) else {
+            return Ok(None);
+        };
+        let channel_ids: Vec<u32> = channels.iter().map(|c| c.channel_id).collect();
+        let diagnostics = DiagnosticNodes::new(config, &channel_ids, nodes)?;
+        info!("已生成 {} 个通道的诊断节点", channel_ids.len());
+        Ok(Some(diagnostics))
+    }
+
+    /// 诊断节点只读
+    fn ensure_writable(&self, global_id: u32) -> Result<()> {
+        if self
+            .diagnostics
+            .as_ref()
+            .is_some_and(|d| d.contains(global_id))
+        {
+            return Err(DeviceError::Other(format!(
+                "节点 {} 是只读的诊断节点",
+                global_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// 解析节点标签并校验值变换链与状态名称映射
+    async fn prepare_nodes(
+        channel_manager: &ChannelManager,
+        nodes: Vec<NodeConfig>,
+    ) -> Result<Vec<NodeConfig>> {
+        let nodes = Self::resolve_node_tags(channel_manager, nodes).await?;
+
+        for node in &nodes {
+            if let Some(labels) = &node.value_labels {
+                if let Some(key) = labels.keys().find(|k| k.trim().parse::<i32>().is_err()) {
+                    return Err(DeviceError::ConfigError(format!(
+                        "节点 {} ({}) 的 value_labels 键 '{}' 不是整数",
+                        node.global_id, node.alias, key
+                    )));
+                }
+            }
+            if let Some(steps) = &node.transform {
+                transform::validate(steps).map_err(|e| {
+                    DeviceError::ConfigError(format!(
+                        "节点 {} ({}) 的 transform 配置无效: {}",
+                        node.global_id, node.alias, e
+                    ))
+                })?;
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    /// 将配置了 tag 的节点解析为协议内部节点 id
+    ///
+    /// 标签不存在、同一标签被多个节点引用或通道协议不支持标签时返回配置错误
+    async fn resolve_node_tags(
+        channel_manager: &ChannelManager,
+        mut nodes: Vec<NodeConfig>,
+    ) -> Result<Vec<NodeConfig>> {
+        // (通道, 标签) -> 引用该标签的节点
+        let mut bound = std::collections::HashMap::new();
+        for node in nodes.iter_mut() {
+            let tag = match &node.tag {
+                Some(tag) => tag.clone(),
+                None => continue,
+            };
+            if node.data_point.is_some() {
+                return Err(DeviceError::ConfigError(format!(
+                    "节点 {} 不能同时配置 tag 和 data_point",
+                    node.global_id
+                )));
+            }
+            if let Some(other) = bound.insert((node.channel_id, tag.clone()), node.global_id) {
+                return Err(DeviceError::ConfigError(format!(
+                    "节点 {} 与节点 {} 重复引用通道 {} 的标签 '{}'",
+                    other, node.global_id, node.channel_id, tag
+                )));
+            }
+
+            let resolved = channel_manager
+                .execute(
+                    node.channel_id,
+                    "resolve_tag",
+                    serde_json::json!({ "tag": tag }),
+                )
+                .await
+                .map_err(|e| {
+                    DeviceError::ConfigError(format!(
+                        "节点 {} ({}) 的标签 '{}' 解析失败: {}",
+                        node.global_id, node.alias, tag, e
+                    ))
+                })?;
+            let id = resolved.get("id").and_then(|v| v.as_u64()).ok_or_else(|| {
+                DeviceError::ConfigError(format!(
+                    "通道 {} 未返回标签 '{}' 的节点 id",
+                    node.channel_id, tag
+                ))
+            })? as u32;
+
+            debug!(
+                "节点 {} 标签 '{}' 解析为通道 {} id {}",
+                node.global_id, tag, node.channel_id, id
+            );
+            node.id = id;
+        }
+        Ok(nodes)
+    }
+
     /// 订阅设备事件
     pub fn subscribe_events(&self) -> broadcast::Receiver<DeviceEvent> {
         self.event_tx.subscribe()
     }
 
+    /// 发布控制器外部组件（播放列表调度等）产生的事件
+    pub fn publish_event(&self, event: DeviceEvent) {
+        let _ = self.event_tx.send(event);
+    }
+
+    /// 序列化事件并补充节点别名、分类、单位与通道协议类型，
+    /// 外部消费者无需再查询配置即可展示事件
+    pub fn enrich_event(&self, event: &DeviceEvent) -> serde_json::Value {
+        let mut value = serde_json::to_value(event).unwrap_or_default();
+        let node = event
+            .global_id()
+            .and_then(|id| self.node_manager.get_state(id));
+        let unit = node.as_ref().and_then(|n| self.node_unit(n.global_id));
+        let statute = node
+            .as_ref()
+            .map(|n| n.channel_id)
+            .or_else(|| event.channel_id())
+            .and_then(|id| self.channel_manager.statute(id));
+        enrich_event_value(&mut value, node.as_ref(), unit, statute);
+        value
+    }
+
+    /// 节点单位：数据点配置优先，其次为节点 metadata 中的 unit
+    pub fn node_unit(&self, global_id: u32) -> Option<String> {
+        let node = self.node_manager.get_node(global_id)?;
+        node.data_point
+            .as_ref()
+            .and_then(|dp| dp.unit.clone())
+            .or_else(|| {
+                node.metadata
+                    .as_ref()
+                    .and_then(|m| m.get("unit"))
+                    .and_then(serde_json::Value::as_str)
+                    .map(str::to_string)
+            })
+    }
+
     /// 写入单个节点（带依赖检查）
     pub async fn write_node(&self, global_id: u32, value: i32) -> Result<()> {
+        self.write_node_value(global_id, value.into()).await
+    }
+
+    /// 写入单个节点，值可带小数（经值变换 / 数据点缩放后写入设备）
+    ///
+    /// 节点状态、强制值与延后执行的写入（任务队列、离线暂存）按整数保存，写入其中时取整。
+    pub async fn write_node_value(&self, global_id: u32, value: f64) -> Result<()> {
         debug!("写入节点 {} = {}", global_id, value);
+        self.ensure_writable(global_id)?;
 
         // 获取节点配置
         let node = self
@@ -141,18 +753,49 @@ impl DeviceController {
             .get_node(global_id)
             .ok_or_else(|| DeviceError::DeviceNotFound(format!("节点 {}", global_id)))?;
 
+        // 节点被强制时不下发到设备
+        if let Some(forced) = self.node_manager.suppress_write(global_id) {
+            info!(
+                "节点 {} 已强制为 {}，写入 {} 被抑制",
+                global_id, forced, value
+            );
+            return Ok(());
+        }
+
+        // 通道处于计划离线时段，写入进入任务队列等待时段开始
+        if !self.channel_manager.is_available(node.channel_id) {
+            info!(
+                "节点 {} 所在通道 {} 处于计划离线时段，加入任务队列",
+                global_id, node.channel_id
+            );
+            return self
+                .task_scheduler
+                .submit_task(node, value.round() as i32)
+                .await;
+        }
+
         // 检查是否有依赖
         if let Some(dependencies) = &node.depend {
             // 检查依赖是否满足
-            let deps_met = self
+            let unmet = self
                 .dependency_resolver
-                .check_dependencies(dependencies)
+                .unmet_dependencies(dependencies)
                 .await?;
 
-            if !deps_met {
-                // 依赖未满足，提交任务到调度器
-                info!("节点 {} 依赖未满足，加入任务队列", global_id);
-                return self.task_scheduler.submit_task(node, value).await;
+            if !unmet.is_empty() {
+                // reject 策略直接返回未满足的依赖，其余策略提交任务到调度器
+                if node.depend_strategy.as_deref() == Some("reject") {
+                    return Err(DeviceError::DependencyNotMet(unmet));
+                }
+                info!(
+                    "节点 {} {}，加入任务队列",
+                    global_id,
+                    DeviceError::DependencyNotMet(unmet)
+                );
+                return self
+                    .task_scheduler
+                    .submit_task(node, value.round() as i32)
+                    .await;
             }
 
             // 如果策略是自动，先满足依赖
@@ -163,35 +806,112 @@ impl DeviceController {
             }
         }
 
+        let result = self
+            .with_retry(&format!("写入节点 {}", global_id), || {
+                Self::write_to_device(&self.channel_manager, &self.node_manager, &node, value)
+            })
+            .await;
+        self.stats.record_write(global_id, &result);
+        match result {
+            Ok(()) => {
+                // 新的写入已下发，暂存的旧值不再需要
+                self.store_forward.discard(global_id);
+                Ok(())
+            }
+            Err(e) if store_forward::is_offline_error(&e) => {
+                match self.store_forward_config(&node) {
+                    Some(config) => {
+                        self.store_forward
+                            .enqueue(&node, value.round() as i32, &e, &config);
+                        Ok(())
+                    }
+                    None => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 按节点配置（值变换、数据点）把逻辑值写入设备
+    pub(crate) async fn write_to_device(
+        channel_manager: &ChannelManager,
+        node_manager: &NodeManager,
+        node: &NodeConfig,
+        value: f64,
+    ) -> Result<()> {
+        // 应用值变换链的反变换
+        let device_value = Self::to_device_value(node, value)?;
+
         // 如果节点有 data_point 配置（Modbus数据点），使用特殊写入逻辑
         if let Some(data_point) = &node.data_point {
             // 应用反向缩放（如果有scale）
             let actual_value = if let Some(scale) = data_point.scale {
-                (value as f64 / scale) as i32
+                (device_value / scale) as i32
             } else {
-                value
+                device_value.round() as i32
             };
 
-            self.channel_manager
+            channel_manager
                 .execute(
                     node.channel_id,
                     "write_typed",
                     serde_json::json!({
                         "addr": data_point.addr,
                         "type": data_point.r#type,
-                        "value": actual_value
+                        "value": actual_value,
+                        "slave_id": data_point.slave_id,
+                        "word_order": data_point.word_order,
+                        "byte_swap": data_point.byte_swap
                     }),
                 )
                 .await?;
 
             // 更新节点状态
-            self.node_manager.update_value(global_id, value);
+            node_manager.update_value(node.global_id, value.round() as i32);
 
             return Ok(());
         }
 
         // 普通节点，直接执行写入
-        self.execute_write(node.channel_id, node.id, value).await
+        channel_manager
+            .write(node.channel_id, node.id, device_value.round() as i32)
+            .await
+    }
+
+    /// 按重试策略执行设备读写，未启用重试时只执行一次
+    async fn with_retry<T, F, Fut>(&self, what: &str, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        match &self.retry {
+            Some(policy) => policy.run(what, op).await,
+            None => op().await,
+        }
+    }
+
+    /// 节点生效的离线写入暂存配置（节点配置优先于通道配置）
+    fn store_forward_config(&self, node: &NodeConfig) -> Option<StoreForwardConfig> {
+        node.store_and_forward
+            .clone()
+            .or_else(|| self.channel_manager.store_and_forward(node.channel_id))
+            .filter(|config| config.enable)
+    }
+
+    /// 逻辑值 → 设备原始值（未配置 transform 时原样返回）
+    pub(crate) fn to_device_value(node: &NodeConfig, value: f64) -> Result<f64> {
+        match &node.transform {
+            Some(steps) => transform::to_device(steps, value),
+            None => Ok(value),
+        }
+    }
+
+    /// 设备原始值 → 逻辑值（未配置 transform 时原样返回）
+    fn from_device_value(node: &NodeConfig, raw: f64) -> f64 {
+        match &node.transform {
+            Some(steps) => transform::from_device(steps, raw),
+            None => raw,
+        }
     }
 
     /// 执行实际的写入操作（内部方法）
@@ -206,26 +926,52 @@ impl DeviceController {
             .await
     }
 
-    /// 读取节点当前值
+    /// 读取节点当前值（节点被强制时返回强制值，不访问设备）
     pub async fn read_node(&self, global_id: u32) -> Result<f64> {
         let node = self
             .node_manager
             .get_node(global_id)
             .ok_or_else(|| DeviceError::DeviceNotFound(format!("节点 {}", global_id)))?;
 
+        if let Some(forced) = self.node_manager.forced_value(global_id) {
+            return Ok(forced as f64);
+        }
+
+        // 诊断节点的值由健康度同步任务维护，不访问设备
+        if self
+            .diagnostics
+            .as_ref()
+            .is_some_and(|d| d.contains(global_id))
+        {
+            let value = self
+                .node_manager
+                .get_state(global_id)
+                .and_then(|s| s.current_value)
+                .unwrap_or(0);
+            return Ok(value as f64);
+        }
+
+        if !self.channel_manager.is_available(node.channel_id) {
+            return Err(DeviceError::ScheduledOffline(node.channel_id));
+        }
+
         // 如果节点有 data_point 配置（Modbus数据点），使用特殊读取逻辑
         if let Some(data_point) = &node.data_point {
             let result = self
-                .channel_manager
-                .execute(
-                    node.channel_id,
-                    "read_typed",
-                    serde_json::json!({
-                        "addr": data_point.addr,
-                        "type": data_point.r#type,
-                        "use_cache": true
-                    }),
-                )
+                .with_retry(&format!("读取节点 {}", global_id), || {
+                    self.channel_manager.execute(
+                        node.channel_id,
+                        "read_typed",
+                        serde_json::json!({
+                            "addr": data_point.addr,
+                            "type": data_point.r#type,
+                            "use_cache": true,
+                            "slave_id": data_point.slave_id,
+                            "word_order": data_point.word_order,
+                            "byte_swap": data_point.byte_swap
+                        }),
+                    )
+                })
                 .await?;
 
             // 从结果中提取值
@@ -237,11 +983,12 @@ impl DeviceController {
                 };
 
                 // 应用缩放比例
-                let final_value = if let Some(scale) = data_point.scale {
+                let scaled_value = if let Some(scale) = data_point.scale {
                     raw_value * scale
                 } else {
                     raw_value
                 };
+                let final_value = Self::from_device_value(&node, scaled_value);
 
                 // 更新节点状态（存储为整数）
                 self.node_manager
@@ -252,9 +999,15 @@ impl DeviceController {
         }
 
         // 普通节点，使用传统方式
-        let value = self.channel_manager.read(node.channel_id, node.id).await?;
-        self.node_manager.update_value(global_id, value);
-        Ok(value as f64)
+        let raw = self
+            .with_retry(&format!("读取节点 {}", global_id), || {
+                self.channel_manager.read(node.channel_id, node.id)
+            })
+            .await?;
+        let value = Self::from_device_value(&node, raw as f64);
+        self.node_manager
+            .update_value(global_id, value.round() as i32);
+        Ok(value)
     }
 
     /// 获取节点状态
@@ -267,10 +1020,190 @@ impl DeviceController {
         self.node_manager.get_all_states()
     }
 
+    /// 强制节点值（调试用）：读取返回强制值，写入不下发到设备
+    pub fn force_node(
+        &self,
+        global_id: u32,
+        value: i32,
+        operator: &str,
+        reason: Option<String>,
+    ) -> Result<ForcedNode> {
+        let forced = self
+            .node_manager
+            .force_value(global_id, value, operator, reason)
+            .ok_or_else(|| DeviceError::DeviceNotFound(format!("节点 {}", global_id)))?;
+        info!("节点 {} 被 {} 强制为 {}", global_id, operator, value);
+        Ok(forced)
+    }
+
+    /// 解除节点强制
+    pub fn release_node_force(&self, global_id: u32) -> Option<ForcedNode> {
+        let released = self.node_manager.release_force(global_id)?;
+        info!("节点 {} 解除强制", global_id);
+        Some(released)
+    }
+
+    /// 解除全部节点强制
+    pub fn release_all_forces(&self) -> Vec<ForcedNode> {
+        let released = self.node_manager.release_all_forces();
+        if !released.is_empty() {
+            info!("已解除 {} 个节点的强制", released.len());
+        }
+        released
+    }
+
+    /// 当前强制的节点
+    pub fn forced_nodes(&self) -> Vec<ForcedNode> {
+        self.node_manager.forced_nodes()
+    }
+
+    /// 通道离线时暂存、尚未下发的写入
+    pub fn offline_writes(&self) -> Vec<QueuedWrite> {
+        self.store_forward.list()
+    }
+
+    /// 节点暂存的写入
+    pub fn offline_write(&self, global_id: u32) -> Option<QueuedWrite> {
+        self.store_forward.get(global_id)
+    }
+
+    /// 取消节点暂存的写入
+    pub fn cancel_offline_write(&self, global_id: u32) -> Option<QueuedWrite> {
+        let cancelled = self.store_forward.discard(global_id)?;
+        info!("节点 {} 暂存的写入值 {} 已取消", global_id, cancelled.value);
+        Some(cancelled)
+    }
+
+    /// 全部时间曲线状态
+    pub fn profiles(&self) -> Vec<ProfileStatus> {
+        self.profiles.list()
+    }
+
+    /// 启动时间曲线
+    pub fn start_profile(&self, name: &str) -> Result<ProfileStatus> {
+        self.profiles.start(name)
+    }
+
+    /// 停止时间曲线
+    pub async fn stop_profile(&self, name: &str) -> Result<ProfileStatus> {
+        self.profiles.stop(name).await
+    }
+
+    /// 获取节点值对应的状态名称
+    pub fn get_value_label(&self, global_id: u32, value: i32) -> Option<String> {
+        self.node_manager.value_label(global_id, value)
+    }
+
+    /// 将状态名称解析为节点值
+    pub fn resolve_value_label(&self, global_id: u32, label: &str) -> Result<i32> {
+        if self.node_manager.get_node(global_id).is_none() {
+            return Err(DeviceError::DeviceNotFound(format!("节点 {}", global_id)));
+        }
+        self.node_manager
+            .value_from_label(global_id, label)
+            .ok_or_else(|| {
+                DeviceError::Other(format!("节点 {} 未定义状态名称 '{}'", global_id, label))
+            })
+    }
+
+    /// 节点需要写入确认时登记待确认写入并返回，不需要确认时返回 None（由调用方直接写入）
+    pub fn hold_for_confirmation(
+        &self,
+        global_id: u32,
+        value: f64,
+        operator: Option<String>,
+    ) -> Result<Option<PendingWrite>> {
+        let node = self
+            .node_manager
+            .get_node(global_id)
+            .ok_or_else(|| DeviceError::DeviceNotFound(format!("节点 {}", global_id)))?;
+        Ok(node.confirm.as_ref().map(|config| {
+            let pending = self
+                .confirmations
+                .request(global_id, value, operator, config);
+            info!(
+                "节点 {} 写入 {} 等待确认 (发起人: {})",
+                global_id,
+                value,
+                pending.requested_by.as_deref().unwrap_or("-")
+            );
+            pending
+        }))
+    }
+
+    /// 确认并执行待确认写入
+    pub async fn confirm_write(&self, token: &str, operator: Option<&str>) -> Result<PendingWrite> {
+        let pending = self.confirmations.take(token, operator)?;
+        info!(
+            "节点 {} 写入 {} 已确认 (发起人: {}, 确认人: {})",
+            pending.global_id,
+            pending.value,
+            pending.requested_by.as_deref().unwrap_or("-"),
+            operator.unwrap_or("-")
+        );
+        self.write_node_value(pending.global_id, pending.value)
+            .await?;
+        Ok(pending)
+    }
+
+    /// 取消待确认写入
+    pub fn cancel_confirmation(&self, token: &str) -> Option<PendingWrite> {
+        self.confirmations.cancel(token)
+    }
+
+    /// 全部待确认写入
+    pub fn list_confirmations(&self) -> Vec<PendingWrite> {
+        self.confirmations.list()
+    }
+
+    /// 获取所有节点配置（标签已解析为节点 id）
+    pub fn get_all_node_configs(&self) -> Vec<NodeConfig> {
+        self.node_manager.get_all_nodes()
+    }
+
     /// 执行场景
     pub async fn execute_scene(&self, scene_name: &str) -> Result<()> {
-        info!("执行场景: {}", scene_name);
-        self.scene_executor.execute(scene_name, self).await
+        self.start_scene(scene_name, false).await.map(|_| ())
+    }
+
+    /// 启动场景并返回执行编号；cue 模式下每个步骤等待 [`DeviceController::advance_scene`] 放行
+    pub async fn start_scene(&self, scene_name: &str, cue: bool) -> Result<u64> {
+        info!(
+            "执行场景: {}{}",
+            scene_name,
+            if cue { "（cue 模式）" } else { "" }
+        );
+        self.scene_executor.execute(scene_name, self, cue).await
+    }
+
+    /// cue 模式下放行等待中的场景步骤
+    pub fn advance_scene(&self, run_id: u64) -> Result<SceneCue> {
+        self.scene_executor.advance(run_id)
+    }
+
+    /// 取消一次排队中或执行中的场景
+    pub fn cancel_scene_run(&self, run_id: u64) -> Result<String> {
+        self.scene_executor.cancel_run(run_id)
+    }
+
+    /// 排队中或执行中的一次场景
+    pub fn scene_run(&self, run_id: u64) -> Option<SceneRunStatus> {
+        self.scene_executor.run_status(run_id)
+    }
+
+    /// 预览场景执行效果（不写入设备）
+    pub fn diff_scene(&self, scene_name: &str) -> Result<Vec<SceneStepDiff>> {
+        self.scene_executor.diff(scene_name)
+    }
+
+    /// 获取全部场景配置
+    pub fn get_all_scenes(&self) -> Vec<SceneConfig> {
+        self.scene_executor.get_all_scenes()
+    }
+
+    /// 新增或替换场景（运行中生效，不写入配置文件）
+    pub fn upsert_scenes(&self, scenes: Vec<SceneConfig>) {
+        self.scene_executor.upsert_scenes(scenes);
     }
 
     /// 获取场景执行状态
@@ -280,7 +1213,10 @@ impl DeviceController {
 
     /// 获取所有通道状态
     pub async fn get_all_channel_status(&self) -> Result<serde_json::Value> {
-        self.channel_manager.get_all_status().await
+        match &self.status_cache {
+            Some(cache) => Ok(cache.snapshot().await),
+            None => self.channel_manager.get_all_status().await,
+        }
     }
 
     /// 执行通道命令
@@ -295,6 +1231,44 @@ impl DeviceController {
             .await
     }
 
+    /// 通道延迟测试
+    pub async fn ping_channel(
+        &self,
+        channel_id: u32,
+        mode: PingMode,
+        count: u32,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<PingReport> {
+        self.channel_manager
+            .ping(channel_id, mode, count, interval, timeout)
+            .await
+    }
+
+    /// 执行通道命令，返回统一结构的结果
+    pub async fn run_channel_command(
+        &self,
+        channel_id: u32,
+        command: &str,
+        params: serde_json::Value,
+    ) -> CommandResult {
+        self.channel_manager
+            .execute_command(channel_id, command, params)
+            .await
+    }
+
+    /// 通过通道传输层发送原始数据（调试控制台）
+    pub async fn send_raw(
+        &self,
+        channel_id: u32,
+        payload: &[u8],
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        self.channel_manager
+            .send_raw(channel_id, payload, timeout)
+            .await
+    }
+
     /// 调用通道的自定义方法
     pub async fn call_channel_method(
         &self,
@@ -307,8 +1281,262 @@ impl DeviceController {
             .await
     }
 
+    /// 调用通道的自定义方法，返回统一结构的结果
+    pub async fn invoke_channel_method(
+        &self,
+        channel_id: u32,
+        method_name: &str,
+        args: serde_json::Value,
+    ) -> CommandResult {
+        self.channel_manager
+            .invoke_method(channel_id, method_name, args)
+            .await
+    }
+
     /// 获取通道支持的方法列表
     pub async fn get_channel_methods(&self, channel_id: u32) -> Result<Vec<String>> {
         self.channel_manager.get_channel_methods(channel_id).await
     }
+
+    /// 通道的内容推送分块大小（协议不支持内容推送时为 None）
+    pub async fn content_chunk_size(&self, channel_id: u32) -> Result<Option<usize>> {
+        self.channel_manager.content_chunk_size(channel_id).await
+    }
+
+    /// 开始内容推送，返回设备端上传会话
+    pub async fn begin_content_upload(
+        &self,
+        channel_id: u32,
+        info: &ContentInfo,
+    ) -> Result<String> {
+        self.channel_manager
+            .begin_content_upload(channel_id, info)
+            .await
+    }
+
+    /// 上传内容分块
+    pub async fn upload_content_chunk(
+        &self,
+        channel_id: u32,
+        session: &str,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        self.channel_manager
+            .upload_content_chunk(channel_id, session, offset, data)
+            .await
+    }
+
+    /// 完成内容推送，返回设备计算的 SHA-256
+    pub async fn finish_content_upload(
+        &self,
+        channel_id: u32,
+        session: &str,
+    ) -> Result<Option<String>> {
+        self.channel_manager
+            .finish_content_upload(channel_id, session)
+            .await
+    }
+
+    /// 放弃未完成的内容推送
+    pub async fn abort_content_upload(&self, channel_id: u32, session: &str) {
+        self.channel_manager
+            .abort_content_upload(channel_id, session)
+            .await
+    }
+
+    /// 运行时停用通道（不修改配置文件，配置热重载后恢复），通道下的节点标记为离线
+    pub async fn disable_channel(&self, channel_id: u32) -> Result<()> {
+        self.channel_manager.disable_channel(channel_id).await?;
+        for node in self.node_manager.get_all_nodes() {
+            if node.channel_id == channel_id {
+                self.node_manager.set_online(node.global_id, false);
+            }
+        }
+        Ok(())
+    }
+
+    /// 重新启用运行时停用的通道
+    pub async fn enable_channel(&self, channel_id: u32) -> Result<()> {
+        self.channel_manager.enable_channel(channel_id).await
+    }
+
+    /// 通道所属站点（未配置站点时为 `None`）
+    pub fn channel_site(&self, channel_id: u32) -> Option<String> {
+        self.channel_manager.site(channel_id)
+    }
+
+    /// 通道能否单独替换：下属节点的标签由通道解析，这类通道变更需要重建控制器
+    pub fn can_replace_channel(&self, channel_id: u32) -> bool {
+        !self
+            .node_manager
+            .get_all_nodes()
+            .iter()
+            .any(|n| n.channel_id == channel_id && n.tag.is_some())
+    }
+
+    /// 用新配置替换单个通道：新协议实例验证连接后再切换，其他通道的控制不中断
+    ///
+    /// 独占设备连接或端口的驱动先停止旧实例再验证，替换期间该通道短暂不可用。
+    pub async fn replace_channel(&self, config: &crate::config::ChannelConfig) -> Result<()> {
+        self.channel_manager
+            .replace_channel(config, CHANNEL_SWAP_VERIFY_TIMEOUT)
+            .await
+    }
+
+    /// 节点值异常分析报告
+    pub fn analytics_report(&self) -> AnalyticsReport {
+        match self.analytics {
+            Some(ref analytics) => analytics.report(),
+            None => AnalyticsReport {
+                enabled: false,
+                tracked_nodes: 0,
+                anomalies: Vec::new(),
+            },
+        }
+    }
+
+    /// 场景与节点写入执行统计
+    pub fn execution_stats(&self) -> ExecutionStatsReport {
+        self.stats.report()
+    }
+
+    /// 清空执行统计
+    pub fn reset_execution_stats(&self) {
+        self.stats.reset();
+    }
+
+    /// 通道启动顺序与启动依赖违例
+    pub fn channel_startup_order(&self) -> StartupOrderReport {
+        self.channel_manager.startup_order()
+    }
+
+    /// 获取各通道诊断信息
+    pub fn channel_diagnostics(&self) -> Vec<ChannelDiagnostics> {
+        self.channel_manager.channel_diagnostics()
+    }
+
+    /// 在超时时间内无法获取协议锁的通道
+    pub async fn unresponsive_channels(&self, timeout: Duration) -> Vec<u32> {
+        self.channel_manager.unresponsive_channels(timeout).await
+    }
+
+    /// 获取事件总线统计
+    pub fn event_bus_stats(&self) -> EventBusStats {
+        EventBusStats {
+            receivers: self.event_tx.receiver_count(),
+            queued: self.event_tx.len(),
+            capacity: EVENT_BUS_CAPACITY,
+            lagged_total: LAGGED_EVENTS.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enriches_node_events() {
+        let node = NodeState {
+            global_id: 12,
+            channel_id: 2,
+            device_id: 1,
+            category: Some("sensor".to_string()),
+            alias: "展厅温度".to_string(),
+            current_value: Some(235),
+            online: true,
+            metadata: None,
+            last_update: None,
+            restored: false,
+            forced: false,
+        };
+        let mut value = serde_json::to_value(DeviceEvent::NodeStateChanged {
+            global_id: 12,
+            old_value: 230,
+            new_value: 235,
+            forced: false,
+        })
+        .unwrap();
+        enrich_event_value(
+            &mut value,
+            Some(&node),
+            Some("°C".to_string()),
+            Some("Modbus".to_string()),
+        );
+
+        assert_eq!(value["type"], "node_state_changed");
+        assert_eq!(value["alias"], "展厅温度");
+        assert_eq!(value["category"], "sensor");
+        assert_eq!(value["channel_id"], 2);
+        assert_eq!(value["unit"], "°C");
+        assert_eq!(value["statute"], "Modbus");
+
+        // 事件自带的字段不被覆盖
+        let mut value = serde_json::to_value(DeviceEvent::ChannelDriverSwitched {
+            channel_id: 2,
+            driver: 1,
+            statute: "Mock".to_string(),
+            reason: "连接失败".to_string(),
+        })
+        .unwrap();
+        enrich_event_value(&mut value, None, None, Some("Modbus".to_string()));
+        assert_eq!(value["statute"], "Mock");
+    }
+
+    #[tokio::test]
+    async fn test_resolves_node_tags_and_rejects_unknown_or_duplicate() {
+        let channel: crate::config::ChannelConfig = serde_json::from_value(serde_json::json!({
+            "channel_id": 7,
+            "enable": true,
+            "statute": "qn-smart-plc",
+            "arguments": {
+                "addr": "127.0.0.1",
+                "tags": {
+                    "stage_power": { "area": "switch", "addr": 1 },
+                    "cabinet_temp": { "area": "register", "addr": "0x0400", "scale": 0.1 }
+                }
+            }
+        }))
+        .unwrap();
+        let (event_tx, _) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let manager = ChannelManager::new(&[channel], &[], event_tx, &tasks)
+            .await
+            .unwrap();
+        let node = |global_id: u32, tag: &str| -> NodeConfig {
+            serde_json::from_value(serde_json::json!({
+                "global_id": global_id,
+                "channel_id": 7,
+                "id": 0,
+                "alias": format!("节点{}", global_id),
+                "tag": tag
+            }))
+            .unwrap()
+        };
+
+        // 标签按名称排序分配 id：cabinet_temp=1000, stage_power=1001
+        let nodes = DeviceController::resolve_node_tags(
+            &manager,
+            vec![node(1, "stage_power"), node(2, "cabinet_temp")],
+        )
+        .await
+        .unwrap();
+        assert_eq!((nodes[0].id, nodes[1].id), (1001, 1000));
+
+        let err = DeviceController::resolve_node_tags(&manager, vec![node(1, "missing")])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing"), "{}", err);
+
+        let err = DeviceController::resolve_node_tags(
+            &manager,
+            vec![node(1, "stage_power"), node(2, "stage_power")],
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("重复引用"), "{}", err);
+
+        manager.shutdown().await;
+    }
 }
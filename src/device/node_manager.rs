@@ -1,8 +1,10 @@
 use dashmap::DashMap;
+use serde::Serialize;
 /// 节点管理器 - 负责逻辑设备状态管理
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::debug;
+use utoipa::ToSchema;
 
 use super::DeviceEvent;
 use crate::config::NodeConfig;
@@ -17,13 +19,41 @@ pub struct NodeState {
     pub alias: String,
     pub current_value: Option<i32>,
     pub online: bool,
+    /// 节点自定义元数据（来自配置）
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
     pub last_update: Option<std::time::Instant>,
+    /// 当前值来自持久化存储（重启前的最后已知值），尚未被实际读写刷新
+    pub restored: bool,
+    /// 当前值为调试强制值（见 [`ForcedNode`]）
+    pub forced: bool,
+}
+
+/// 节点强制值
+///
+/// 调试期间以固定值替代设备的实际值：读取返回强制值，写入不下发到设备。
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ForcedNode {
+    pub global_id: u32,
+    pub alias: String,
+    /// 强制值
+    pub value: i32,
+    /// 设备的实际值（强制前的最后已知值，强制期间随设备上报更新）
+    pub actual_value: Option<i32>,
+    /// 强制期间被抑制的写入次数
+    pub suppressed_writes: u64,
+    /// 操作人
+    pub operator: String,
+    /// 强制原因
+    pub reason: Option<String>,
+    /// 强制时间（RFC 3339）
+    pub forced_at: String,
 }
 
 /// 节点管理器
 pub struct NodeManager {
     nodes: DashMap<u32, NodeConfig>,
     states: DashMap<u32, NodeState>,
+    forced: DashMap<u32, ForcedNode>,
     event_tx: broadcast::Sender<DeviceEvent>,
 }
 
@@ -46,7 +76,10 @@ impl NodeManager {
                 alias: config.alias.clone(),
                 current_value: None,
                 online: false,
+                metadata: config.metadata.clone(),
                 last_update: None,
+                restored: false,
+                forced: false,
             };
             states.insert(config.global_id, state);
         }
@@ -54,6 +87,7 @@ impl NodeManager {
         Self {
             nodes,
             states,
+            forced: DashMap::new(),
             event_tx,
         }
     }
@@ -63,6 +97,13 @@ impl NodeManager {
         self.nodes.get(&global_id).map(|n| n.clone())
     }
 
+    /// 获取所有节点配置（按全局 ID 排序）
+    pub fn get_all_nodes(&self) -> Vec<NodeConfig> {
+        let mut nodes: Vec<NodeConfig> = self.nodes.iter().map(|n| n.value().clone()).collect();
+        nodes.sort_by_key(|n| n.global_id);
+        nodes
+    }
+
     /// 获取节点状态
     pub fn get_state(&self, global_id: u32) -> Option<NodeState> {
         self.states.get(&global_id).map(|s| s.clone())
@@ -77,12 +118,28 @@ impl NodeManager {
     }
 
     /// 更新节点值
+    ///
+    /// 节点被强制时只记录设备的实际值，当前值保持为强制值。
     pub fn update_value(&self, global_id: u32, new_value: i32) {
+        if let Some(mut forced) = self.forced.get_mut(&global_id) {
+            forced.actual_value = Some(new_value);
+            if let Some(mut state) = self.states.get_mut(&global_id) {
+                state.last_update = Some(std::time::Instant::now());
+                state.online = true;
+            }
+            return;
+        }
+        self.set_value(global_id, new_value, false);
+    }
+
+    fn set_value(&self, global_id: u32, new_value: i32, forced: bool) {
         if let Some(mut state) = self.states.get_mut(&global_id) {
             let old_value = state.current_value.unwrap_or(0);
             state.current_value = Some(new_value);
             state.last_update = Some(std::time::Instant::now());
             state.online = true;
+            state.restored = false;
+            state.forced = forced;
 
             // 发送状态变化事件
             if old_value != new_value {
@@ -90,16 +147,144 @@ impl NodeManager {
                     global_id,
                     old_value,
                     new_value,
+                    forced,
                 });
 
                 debug!(
                     "节点 {} 状态更新: {} -> {}",
-                    global_id, old_value, new_value
+                    global_id,
+                    self.describe_value(global_id, old_value),
+                    self.describe_value(global_id, new_value)
                 );
             }
         }
     }
 
+    /// 恢复持久化的最后已知值（不视为在线，不发送状态变化事件）
+    pub fn restore_value(&self, global_id: u32, value: i32) {
+        if let Some(mut state) = self.states.get_mut(&global_id) {
+            if state.current_value.is_none() {
+                state.current_value = Some(value);
+                state.restored = true;
+            }
+        }
+    }
+
+    /// 强制节点值，返回强制记录（节点不存在时为 None）
+    ///
+    /// 已强制的节点再次强制时只更新强制值，保留设备的实际值。
+    pub fn force_value(
+        &self,
+        global_id: u32,
+        value: i32,
+        operator: &str,
+        reason: Option<String>,
+    ) -> Option<ForcedNode> {
+        let state = self.get_state(global_id)?;
+        let forced = match self.forced.get(&global_id) {
+            Some(existing) => ForcedNode {
+                value,
+                operator: operator.to_string(),
+                reason,
+                forced_at: crate::utils::time::now_rfc3339(),
+                ..existing.clone()
+            },
+            None => ForcedNode {
+                global_id,
+                alias: state.alias.clone(),
+                value,
+                actual_value: state.current_value.filter(|_| !state.restored),
+                suppressed_writes: 0,
+                operator: operator.to_string(),
+                reason,
+                forced_at: crate::utils::time::now_rfc3339(),
+            },
+        };
+        self.forced.insert(global_id, forced.clone());
+        self.set_value(global_id, value, true);
+        let _ = self.event_tx.send(DeviceEvent::NodeForced {
+            global_id,
+            value,
+            operator: operator.to_string(),
+        });
+        Some(forced)
+    }
+
+    /// 解除强制，当前值恢复为设备的实际值（未知时保持强制值直到下次读取）
+    pub fn release_force(&self, global_id: u32) -> Option<ForcedNode> {
+        let (_, forced) = self.forced.remove(&global_id)?;
+        match forced.actual_value {
+            Some(actual) => self.set_value(global_id, actual, false),
+            None => {
+                if let Some(mut state) = self.states.get_mut(&global_id) {
+                    state.forced = false;
+                }
+            }
+        }
+        let _ = self
+            .event_tx
+            .send(DeviceEvent::NodeForceReleased { global_id });
+        Some(forced)
+    }
+
+    /// 解除全部强制
+    pub fn release_all_forces(&self) -> Vec<ForcedNode> {
+        let ids: Vec<u32> = self.forced.iter().map(|f| *f.key()).collect();
+        let mut released: Vec<ForcedNode> = ids
+            .into_iter()
+            .filter_map(|id| self.release_force(id))
+            .collect();
+        released.sort_by_key(|f| f.global_id);
+        released
+    }
+
+    /// 当前强制的节点（按全局 ID 排序）
+    pub fn forced_nodes(&self) -> Vec<ForcedNode> {
+        let mut forced: Vec<ForcedNode> = self.forced.iter().map(|f| f.value().clone()).collect();
+        forced.sort_by_key(|f| f.global_id);
+        forced
+    }
+
+    /// 节点的强制值（未强制时为 None）
+    pub fn forced_value(&self, global_id: u32) -> Option<i32> {
+        self.forced.get(&global_id).map(|f| f.value)
+    }
+
+    /// 节点被强制时记录一次被抑制的写入并返回强制值
+    pub fn suppress_write(&self, global_id: u32) -> Option<i32> {
+        let mut forced = self.forced.get_mut(&global_id)?;
+        forced.suppressed_writes += 1;
+        Some(forced.value)
+    }
+
+    /// 获取节点值对应的状态名称
+    pub fn value_label(&self, global_id: u32, value: i32) -> Option<String> {
+        let node = self.nodes.get(&global_id)?;
+        node.value_labels
+            .as_ref()?
+            .iter()
+            .find(|(key, _)| key.trim().parse::<i32>().ok() == Some(value))
+            .map(|(_, label)| label.clone())
+    }
+
+    /// 按状态名称查找节点值（不区分大小写）
+    pub fn value_from_label(&self, global_id: u32, label: &str) -> Option<i32> {
+        let node = self.nodes.get(&global_id)?;
+        node.value_labels
+            .as_ref()?
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(label.trim()))
+            .and_then(|(key, _)| key.trim().parse::<i32>().ok())
+    }
+
+    /// 日志用：值及其状态名称
+    fn describe_value(&self, global_id: u32, value: i32) -> String {
+        match self.value_label(global_id, value) {
+            Some(label) => format!("{} ({})", value, label),
+            None => value.to_string(),
+        }
+    }
+
     /// 设置节点在线状态
     pub fn set_online(&self, global_id: u32, online: bool) {
         if let Some(mut state) = self.states.get_mut(&global_id) {
@@ -123,3 +308,40 @@ impl NodeManager {
         self.nodes.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forced_value_masks_device_value_until_released() {
+        let node: NodeConfig = serde_json::from_value(serde_json::json!({
+            "global_id": 1,
+            "channel_id": 1,
+            "id": 1,
+            "alias": "主灯",
+        }))
+        .unwrap();
+        let (tx, _rx) = broadcast::channel(16);
+        let manager = NodeManager::new(&[node], tx);
+        manager.update_value(1, 0);
+
+        let forced = manager.force_value(1, 1, "admin", None).unwrap();
+        assert_eq!(forced.actual_value, Some(0));
+        assert_eq!(manager.suppress_write(1), Some(1));
+
+        // 强制期间轮询到的值只记录为实际值
+        manager.update_value(1, 5);
+        let state = manager.get_state(1).unwrap();
+        assert_eq!((state.current_value, state.forced), (Some(1), true));
+
+        let released = manager.release_force(1).unwrap();
+        assert_eq!(
+            (released.actual_value, released.suppressed_writes),
+            (Some(5), 1)
+        );
+        let state = manager.get_state(1).unwrap();
+        assert_eq!((state.current_value, state.forced), (Some(5), false));
+        assert!(manager.suppress_write(1).is_none());
+    }
+}
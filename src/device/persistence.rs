@@ -0,0 +1,124 @@
+//! 节点值持久化 - 重启后恢复最后已知值
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use super::{DeviceEvent, NodeManager};
+use crate::config::NodeConfig;
+use crate::protocols::storage::ProtocolStorage;
+use crate::utils::tasks::TaskRegistry;
+
+/// 节点值在通道存储中的键前缀（与协议自身的键区分）
+const KEY_PREFIX: &str = "node_value:";
+
+/// 合并写入间隔，避免频繁变化的节点反复重写存储文件
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+fn storage_key(global_id: u32) -> String {
+    format!("{}{}", KEY_PREFIX, global_id)
+}
+
+/// 从存储恢复启用 persist 的节点值
+pub(crate) async fn restore(
+    nodes: &[NodeConfig],
+    node_manager: &NodeManager,
+    storage: &ProtocolStorage,
+) {
+    let mut restored = 0;
+    for node in nodes.iter().filter(|n| n.persist) {
+        let value = storage
+            .get_i64(node.channel_id, &storage_key(node.global_id))
+            .await
+            .and_then(|v| i32::try_from(v).ok());
+        if let Some(value) = value {
+            node_manager.restore_value(node.global_id, value);
+            restored += 1;
+        }
+    }
+    if restored > 0 {
+        info!("已恢复 {} 个节点的最后已知值", restored);
+    }
+}
+
+/// 启动持久化任务：监听节点状态变化，按通道合并后写入存储
+pub(crate) fn spawn_persister(
+    tasks: &TaskRegistry,
+    nodes: &[NodeConfig],
+    storage: Arc<ProtocolStorage>,
+    mut event_rx: broadcast::Receiver<DeviceEvent>,
+) {
+    // global_id -> channel_id
+    let persisted: HashMap<u32, u32> = nodes
+        .iter()
+        .filter(|n| n.persist)
+        .map(|n| (n.global_id, n.channel_id))
+        .collect();
+
+    tasks.spawn("node-value-persister", async move {
+        let mut pending: HashMap<u32, HashMap<String, serde_json::Value>> = HashMap::new();
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                event = event_rx.recv() => match event {
+                    Ok(DeviceEvent::NodeStateChanged { global_id, new_value, forced: false, .. }) => {
+                        if let Some(&channel_id) = persisted.get(&global_id) {
+                            pending
+                                .entry(channel_id)
+                                .or_default()
+                                .insert(storage_key(global_id), serde_json::json!(new_value));
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        super::record_lagged_events(n);
+                        warn!("节点值持久化任务落后，丢失 {} 个事件", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = ticker.tick() => {
+                    for (channel_id, values) in pending.drain() {
+                        debug!("通道 {} 持久化 {} 个节点值", channel_id, values.len());
+                        storage.set_many(channel_id, values).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn restores_persisted_nodes_until_refreshed() {
+        let nodes: Vec<NodeConfig> = serde_json::from_value(serde_json::json!([
+            { "global_id": 1, "channel_id": 7, "id": 1, "alias": "幕布", "persist": true },
+            { "global_id": 2, "channel_id": 7, "id": 2, "alias": "主灯" }
+        ]))
+        .unwrap();
+        let dir = tempdir().unwrap();
+        let storage = ProtocolStorage::new(dir.path().to_path_buf());
+        storage.set(7, &storage_key(1), serde_json::json!(3)).await;
+        storage.set(7, &storage_key(2), serde_json::json!(1)).await;
+
+        // 重新打开存储，模拟重启
+        let storage = ProtocolStorage::new(dir.path().to_path_buf());
+        let (event_tx, _) = broadcast::channel(8);
+        let node_manager = NodeManager::new(&nodes, event_tx);
+        restore(&nodes, &node_manager, &storage).await;
+
+        let state = node_manager.get_state(1).unwrap();
+        assert_eq!(state.current_value, Some(3));
+        assert!(state.restored && !state.online);
+        assert_eq!(node_manager.get_state(2).unwrap().current_value, None);
+
+        node_manager.update_value(1, 3);
+        assert!(!node_manager.get_state(1).unwrap().restored);
+    }
+}
@@ -0,0 +1,232 @@
+//! 通道延迟测试 - 排查现场通信问题时区分网络故障与设备故障
+//!
+//! - `status`：查询通道状态（协议层往返，不改变设备状态），耗时含等待通道空闲
+//! - `connect`：只建立到通道地址的 TCP 连接后立即断开（传输层）
+//!
+//! 连接测试成功而状态查询失败，通常说明网络正常而设备或协议配置异常。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::utils::Result;
+
+/// 测试方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PingMode {
+    /// 查询通道状态
+    #[default]
+    Status,
+    /// 建立 TCP 连接
+    Connect,
+}
+
+/// 单次测试结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PingSample {
+    /// 序号（从 0 开始）
+    pub seq: u32,
+    /// 耗时（毫秒），失败时为 null
+    pub latency_ms: Option<f64>,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+impl PingSample {
+    pub(crate) fn new(seq: u32, elapsed: Duration, result: Result<()>) -> Self {
+        match result {
+            Ok(()) => Self {
+                seq,
+                latency_ms: Some(elapsed.as_secs_f64() * 1000.0),
+                error: None,
+            },
+            Err(e) => Self {
+                seq,
+                latency_ms: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// 通道延迟测试结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PingReport {
+    pub channel_id: u32,
+    pub mode: PingMode,
+    /// 连接测试的目标地址（`status` 方式为 null）
+    pub target: Option<String>,
+    pub sent: u32,
+    pub received: u32,
+    /// 丢失率（百分比）
+    pub loss_percent: f64,
+    /// 成功测试的最小 / 平均 / 最大耗时（毫秒），全部失败时为 null
+    pub min_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub samples: Vec<PingSample>,
+}
+
+impl PingReport {
+    pub(crate) fn new(
+        channel_id: u32,
+        mode: PingMode,
+        target: Option<String>,
+        samples: Vec<PingSample>,
+    ) -> Self {
+        let latencies: Vec<f64> = samples.iter().filter_map(|s| s.latency_ms).collect();
+        let sent = samples.len() as u32;
+        let received = latencies.len() as u32;
+        let (min_ms, avg_ms, max_ms) = if latencies.is_empty() {
+            (None, None, None)
+        } else {
+            (
+                latencies.iter().copied().reduce(f64::min),
+                Some(latencies.iter().sum::<f64>() / latencies.len() as f64),
+                latencies.iter().copied().reduce(f64::max),
+            )
+        };
+        Self {
+            channel_id,
+            mode,
+            target,
+            sent,
+            received,
+            loss_percent: if sent == 0 {
+                0.0
+            } else {
+                (sent - received) as f64 * 100.0 / sent as f64
+            },
+            min_ms,
+            avg_ms,
+            max_ms,
+            samples,
+        }
+    }
+}
+
+/// 通道参数中的设备地址（`addr` 或 `ip`）与端口
+pub(crate) fn transport_target(params: &HashMap<String, Value>) -> Option<(String, u16)> {
+    let host = params
+        .get("addr")
+        .or_else(|| params.get("ip"))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())?;
+    let port = params
+        .get("port")
+        .and_then(|v| v.as_u64())
+        .and_then(|p| u16::try_from(p).ok())?;
+    Some((host.to_string(), port))
+}
+
+/// 使用 TCP 传输的通道的设备地址与端口
+///
+/// `type` / `transport` 指定了 tcp 以外的传输方式，或 `use_udp: true`、`use_tcp: false` 时返回空。
+pub(crate) fn tcp_target(params: &HashMap<String, Value>) -> Option<(String, u16)> {
+    let transport = params
+        .get("type")
+        .or_else(|| params.get("transport"))
+        .and_then(|v| v.as_str());
+    let tcp = match transport {
+        Some(transport) => transport.eq_ignore_ascii_case("tcp"),
+        None => {
+            params.get("use_udp").and_then(|v| v.as_bool()) != Some(true)
+                && params.get("use_tcp").and_then(|v| v.as_bool()) != Some(false)
+        }
+    };
+    if tcp {
+        transport_target(params)
+    } else {
+        None
+    }
+}
+
+/// 状态中的 `connected` / `online` 为 false 时返回不可达原因
+///
+/// 部分驱动在设备不可达时仍成功返回状态，只在状态字段中标记离线。
+pub(crate) fn status_unreachable(status: &Value) -> Option<String> {
+    let key = ["connected", "online"]
+        .into_iter()
+        .find(|key| status.get(*key).and_then(|v| v.as_bool()) == Some(false))?;
+    Some(match status.get("error").and_then(|v| v.as_str()) {
+        Some(error) => format!("状态 {} 为 false: {}", key, error),
+        None => format!("状态 {} 为 false", key),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::DeviceError;
+
+    #[test]
+    fn summarizes_latency_and_loss() {
+        let samples = vec![
+            PingSample::new(0, Duration::from_millis(10), Ok(())),
+            PingSample::new(1, Duration::from_millis(30), Ok(())),
+            PingSample::new(2, Duration::from_millis(5), Err(DeviceError::Timeout)),
+            PingSample::new(3, Duration::from_millis(20), Ok(())),
+        ];
+        let report = PingReport::new(1, PingMode::Status, None, samples);
+        assert_eq!((report.sent, report.received), (4, 3));
+        assert_eq!(report.loss_percent, 25.0);
+        assert_eq!(report.min_ms, Some(10.0));
+        assert_eq!(report.avg_ms, Some(20.0));
+        assert_eq!(report.max_ms, Some(30.0));
+        assert!(report.samples[2].error.is_some());
+
+        let params: HashMap<String, Value> =
+            serde_json::from_value(serde_json::json!({ "ip": "10.0.0.8", "port": 4352 })).unwrap();
+        assert_eq!(
+            transport_target(&params),
+            Some(("10.0.0.8".to_string(), 4352))
+        );
+    }
+
+    #[test]
+    fn detects_tcp_transport_and_unreachable_status() {
+        let params =
+            |value: Value| -> HashMap<String, Value> { serde_json::from_value(value).unwrap() };
+        let target = Some(("10.0.0.8".to_string(), 502));
+        assert_eq!(
+            tcp_target(&params(
+                serde_json::json!({ "addr": "10.0.0.8", "port": 502 })
+            )),
+            target
+        );
+        assert_eq!(
+            tcp_target(&params(
+                serde_json::json!({ "addr": "10.0.0.8", "port": 502, "type": "TCP" })
+            )),
+            target
+        );
+        for transport in [
+            serde_json::json!({ "type": "udp" }),
+            serde_json::json!({ "transport": "serial" }),
+            serde_json::json!({ "use_udp": true }),
+            serde_json::json!({ "use_tcp": false }),
+        ] {
+            let mut p = params(serde_json::json!({ "addr": "10.0.0.8", "port": 502 }));
+            p.extend(params(transport));
+            assert_eq!(tcp_target(&p), None);
+        }
+
+        assert_eq!(
+            status_unreachable(&serde_json::json!({ "connected": true })),
+            None
+        );
+        assert_eq!(
+            status_unreachable(&serde_json::json!({ "status": "ok" })),
+            None
+        );
+        assert_eq!(
+            status_unreachable(&serde_json::json!({ "connected": false, "error": "拒绝连接" }))
+                .as_deref(),
+            Some("状态 connected 为 false: 拒绝连接")
+        );
+        assert!(status_unreachable(&serde_json::json!({ "online": false })).is_some());
+    }
+}
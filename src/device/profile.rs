@@ -0,0 +1,325 @@
+//! 时间曲线（profile）
+//!
+//! 曲线由一天中若干时间点的目标值组成，运行中的曲线按计算间隔取当前时间的插值，
+//! 与节点当前值不同时提交到任务调度器写入（依赖检查、重试、强制抑制与普通任务一致）。
+
+use chrono::{NaiveTime, Timelike};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use super::{DeviceEvent, NodeManager, TaskScheduler};
+use crate::config::{ProfileConfig, ProfileInterpolation};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::time;
+use crate::utils::{DeviceError, Result};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// 时间曲线状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProfileStatus {
+    pub name: String,
+    /// 目标节点
+    pub global_id: u32,
+    pub running: bool,
+    /// 当前时间的曲线值
+    pub target_value: i32,
+    /// 最近一次提交写入的值
+    pub applied_value: Option<i32>,
+    /// 最近一次提交写入的时间（UTC RFC3339）
+    pub applied_at: Option<String>,
+    /// 开始运行时间（UTC RFC3339），未运行时为 null
+    pub started_at: Option<String>,
+}
+
+/// 解析后的时间曲线
+#[derive(Debug, Clone)]
+struct Profile {
+    config: ProfileConfig,
+    /// (当天秒数, 值)，按时间排序
+    points: Vec<(i64, i32)>,
+}
+
+impl Profile {
+    fn parse(config: &ProfileConfig) -> Result<Self> {
+        let invalid = |reason: String| {
+            DeviceError::ConfigError(format!("时间曲线 '{}' 配置无效: {}", config.name, reason))
+        };
+        if config.points.is_empty() {
+            return Err(invalid("points 不能为空".to_string()));
+        }
+        if config.interval == 0 {
+            return Err(invalid("interval 必须大于 0".to_string()));
+        }
+        let mut points = config
+            .points
+            .iter()
+            .map(|p| {
+                let time = NaiveTime::parse_from_str(p.time.trim(), "%H:%M:%S")
+                    .or_else(|_| NaiveTime::parse_from_str(p.time.trim(), "%H:%M"))
+                    .map_err(|_| invalid(format!("时间格式无效: '{}'", p.time)))?;
+                Ok((time.num_seconds_from_midnight() as i64, p.value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        points.sort_by_key(|(t, _)| *t);
+        if let Some(w) = points.windows(2).find(|w| w[0].0 == w[1].0) {
+            return Err(invalid(format!("时间点重复: {} 秒", w[0].0)));
+        }
+        Ok(Self {
+            config: config.clone(),
+            points,
+        })
+    }
+
+    /// 一天中第 `seconds` 秒的曲线值
+    fn value_at(&self, seconds: i64) -> i32 {
+        // 当前时间之前最近的点，当天第一个点之前取前一天的最后一个点
+        let (prev, next) = match self.points.iter().rposition(|(t, _)| *t <= seconds) {
+            Some(i) => {
+                let next = match self.points.get(i + 1) {
+                    Some(p) => *p,
+                    None => (self.points[0].0 + SECONDS_PER_DAY, self.points[0].1),
+                };
+                (self.points[i], next)
+            }
+            None => {
+                let (t, v) = self.points[self.points.len() - 1];
+                ((t - SECONDS_PER_DAY, v), self.points[0])
+            }
+        };
+        match self.config.interpolation {
+            ProfileInterpolation::Step => prev.1,
+            ProfileInterpolation::Linear if next.0 == prev.0 + SECONDS_PER_DAY => prev.1,
+            ProfileInterpolation::Linear => {
+                let ratio = (seconds - prev.0) as f64 / (next.0 - prev.0) as f64;
+                (prev.1 as f64 + (next.1 - prev.1) as f64 * ratio).round() as i32
+            }
+        }
+    }
+}
+
+/// 当前本地时间的当天秒数
+fn seconds_of_day() -> i64 {
+    chrono::Local::now().time().num_seconds_from_midnight() as i64
+}
+
+/// 运行中的时间曲线
+struct RunningProfile {
+    tasks: TaskRegistry,
+    started_at: Instant,
+}
+
+/// 时间曲线运行器
+pub struct ProfileRunner {
+    profiles: Vec<Profile>,
+    running: DashMap<String, RunningProfile>,
+    /// 最近一次提交写入的值与时间（停止后保留）
+    applied: DashMap<String, (i32, Instant)>,
+    task_scheduler: Arc<TaskScheduler>,
+    node_manager: Arc<NodeManager>,
+    event_tx: broadcast::Sender<DeviceEvent>,
+    tasks: TaskRegistry,
+}
+
+impl ProfileRunner {
+    /// 创建运行器并校验曲线配置（名称唯一、目标节点存在、时间点有效）
+    pub fn new(
+        configs: &[ProfileConfig],
+        task_scheduler: Arc<TaskScheduler>,
+        node_manager: Arc<NodeManager>,
+        event_tx: broadcast::Sender<DeviceEvent>,
+        tasks: &TaskRegistry,
+    ) -> Result<Self> {
+        let mut names = HashSet::new();
+        let mut profiles = Vec::with_capacity(configs.len());
+        for config in configs {
+            if !names.insert(config.name.as_str()) {
+                return Err(DeviceError::ConfigError(format!(
+                    "时间曲线名称重复: '{}'",
+                    config.name
+                )));
+            }
+            if node_manager.get_node(config.global_id).is_none() {
+                return Err(DeviceError::ConfigError(format!(
+                    "时间曲线 '{}' 的目标节点 {} 不存在",
+                    config.name, config.global_id
+                )));
+            }
+            profiles.push(Profile::parse(config)?);
+        }
+        info!("时间曲线初始化，共 {} 条", profiles.len());
+        Ok(Self {
+            profiles,
+            running: DashMap::new(),
+            applied: DashMap::new(),
+            task_scheduler,
+            node_manager,
+            event_tx,
+            tasks: tasks.clone(),
+        })
+    }
+
+    /// 启动配置为 autostart 的曲线
+    pub fn autostart(self: &Arc<Self>) {
+        for profile in self.profiles.iter().filter(|p| p.config.autostart) {
+            let _ = self.start(&profile.config.name);
+        }
+    }
+
+    fn find(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .iter()
+            .find(|p| p.config.name == name)
+            .ok_or_else(|| DeviceError::Other(format!("时间曲线 '{}' 不存在", name)))
+    }
+
+    /// 启动曲线（已在运行时不重复启动）
+    pub fn start(self: &Arc<Self>, name: &str) -> Result<ProfileStatus> {
+        let profile = self.find(name)?.clone();
+        if !self.running.contains_key(name) {
+            let tasks = self.tasks.child();
+            let runner = self.clone();
+            let interval = Duration::from_secs(profile.config.interval);
+            let global_id = profile.config.global_id;
+            let profile_name = profile.config.name.clone();
+            tasks.spawn(format!("profile:{}", name), async move {
+                let mut ticker = tokio::time::interval(interval);
+                ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    ticker.tick().await;
+                    runner.apply(&profile).await;
+                }
+            });
+            self.running.insert(
+                name.to_string(),
+                RunningProfile {
+                    tasks,
+                    started_at: Instant::now(),
+                },
+            );
+            info!("时间曲线 '{}' 开始运行（节点 {}）", name, global_id);
+            let _ = self.event_tx.send(DeviceEvent::ProfileStarted {
+                profile: profile_name,
+                global_id,
+            });
+        }
+        self.status(name)
+    }
+
+    /// 停止曲线，节点保持最后写入的值
+    pub async fn stop(&self, name: &str) -> Result<ProfileStatus> {
+        let global_id = self.find(name)?.config.global_id;
+        if let Some((_, running)) = self.running.remove(name) {
+            running.tasks.shutdown().await;
+            info!("时间曲线 '{}' 已停止", name);
+            let _ = self.event_tx.send(DeviceEvent::ProfileStopped {
+                profile: name.to_string(),
+                global_id,
+            });
+        }
+        self.status(name)
+    }
+
+    /// 计算当前曲线值，与节点当前值不同时提交写入任务
+    async fn apply(&self, profile: &Profile) {
+        let name = &profile.config.name;
+        let global_id = profile.config.global_id;
+        let value = profile.value_at(seconds_of_day());
+        let state = self.node_manager.get_state(global_id);
+        if state.as_ref().is_some_and(|s| s.forced) {
+            debug!("时间曲线 '{}': 节点 {} 已强制，跳过写入", name, global_id);
+            return;
+        }
+        if state.and_then(|s| s.current_value) == Some(value) {
+            return;
+        }
+        let Some(node) = self.node_manager.get_node(global_id) else {
+            return;
+        };
+        match self.task_scheduler.submit_task(node, value).await {
+            Ok(()) => {
+                debug!("时间曲线 '{}': 节点 {} 写入 {}", name, global_id, value);
+                self.applied.insert(name.clone(), (value, Instant::now()));
+            }
+            Err(e) => warn!("时间曲线 '{}': 提交写入任务失败: {}", name, e),
+        }
+    }
+
+    /// 单条曲线状态
+    pub fn status(&self, name: &str) -> Result<ProfileStatus> {
+        let profile = self.find(name)?;
+        let applied = self.applied.get(name).map(|a| *a);
+        Ok(ProfileStatus {
+            name: profile.config.name.clone(),
+            global_id: profile.config.global_id,
+            running: self.running.contains_key(name),
+            target_value: profile.value_at(seconds_of_day()),
+            applied_value: applied.map(|(value, _)| value),
+            applied_at: applied.map(|(_, at)| time::instant_rfc3339(at)),
+            started_at: self
+                .running
+                .get(name)
+                .map(|r| time::instant_rfc3339(r.started_at)),
+        })
+    }
+
+    /// 全部曲线状态（按配置顺序）
+    pub fn list(&self) -> Vec<ProfileStatus> {
+        self.profiles
+            .iter()
+            .filter_map(|p| self.status(&p.config.name).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(interpolation: &str, points: serde_json::Value) -> Profile {
+        let config: ProfileConfig = serde_json::from_value(serde_json::json!({
+            "name": "daylight",
+            "global_id": 1,
+            "points": points,
+            "interpolation": interpolation,
+        }))
+        .unwrap();
+        Profile::parse(&config).unwrap()
+    }
+
+    #[test]
+    fn interpolates_between_points_across_midnight() {
+        let points = serde_json::json!([
+            { "time": "18:00", "value": 100 },
+            { "time": "06:00", "value": 20 },
+            { "time": "12:00", "value": 80 },
+        ]);
+        let hour = |h: i64| h * 3600;
+
+        let linear = profile("linear", points.clone());
+        assert_eq!(linear.value_at(hour(6)), 20);
+        assert_eq!(linear.value_at(hour(9)), 50);
+        assert_eq!(linear.value_at(hour(15)), 90);
+        // 18:00 → 次日 06:00 之间跨午夜插值
+        assert_eq!(linear.value_at(0), 60);
+        assert_eq!(linear.value_at(hour(21)), 80);
+
+        let step = profile("step", points);
+        assert_eq!(step.value_at(hour(9)), 20);
+        assert_eq!(step.value_at(hour(3)), 100);
+
+        let invalid: ProfileConfig = serde_json::from_value(serde_json::json!({
+            "name": "bad",
+            "global_id": 1,
+            "points": [{ "time": "25:00", "value": 1 }],
+        }))
+        .unwrap();
+        assert!(Profile::parse(&invalid).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! 节点读写瞬时错误自动重试
+//!
+//! 连接失败、超时与 IO 错误视为瞬时错误，按指数退避加随机抖动重试；其余错误（配置错误、
+//! 协议错误、通道停用等）重试也不会成功，直接返回。
+
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::RetryPolicyConfig;
+use crate::utils::{DeviceError, Result};
+
+/// 重试策略
+#[derive(Debug, Clone)]
+pub(crate) struct RetryPolicy {
+    max_retries: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    jitter: f64,
+}
+
+impl RetryPolicy {
+    /// 未启用时返回 `None`
+    pub(crate) fn from_config(config: &RetryPolicyConfig) -> Option<Self> {
+        if !config.enable || config.max_retries == 0 {
+            return None;
+        }
+        Some(Self {
+            max_retries: config.max_retries,
+            initial_delay: Duration::from_millis(config.initial_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms.max(config.initial_delay_ms)),
+            multiplier: config.multiplier.max(1.0),
+            jitter: config.jitter.clamp(0.0, 1.0),
+        })
+    }
+
+    /// 是否为可重试的瞬时错误
+    pub(crate) fn is_transient(error: &DeviceError) -> bool {
+        matches!(
+            error,
+            DeviceError::ConnectionError(_)
+                | DeviceError::Timeout
+                | DeviceError::Io(_)
+                | DeviceError::ChannelReloading(_)
+        )
+    }
+
+    /// 第 `retry` 次重试（从 1 开始）前的等待时间
+    fn delay(&self, retry: u32) -> Duration {
+        let base = self.initial_delay.as_secs_f64() * self.multiplier.powi(retry as i32 - 1);
+        let base = base.min(self.max_delay.as_secs_f64());
+        let factor = if self.jitter > 0.0 {
+            1.0 + rand::thread_rng().gen_range(-self.jitter..=self.jitter)
+        } else {
+            1.0
+        };
+        Duration::from_secs_f64(base * factor)
+    }
+
+    /// 执行操作，瞬时错误时重试
+    pub(crate) async fn run<T, F, Fut>(&self, what: &str, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match op().await {
+                Err(e) if retry < self.max_retries && Self::is_transient(&e) => {
+                    retry += 1;
+                    let delay = self.delay(retry);
+                    warn!(
+                        "{} 失败: {}，{}ms 后第 {}/{} 次重试",
+                        what,
+                        e,
+                        delay.as_millis(),
+                        retry,
+                        self.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy::from_config(&RetryPolicyConfig {
+            enable: true,
+            max_retries,
+            initial_delay_ms: 10,
+            max_delay_ms: 30,
+            multiplier: 2.0,
+            jitter: 0.2,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_only() {
+        let policy = policy(3);
+        for (retry, low, high) in [(1, 7, 12), (2, 15, 24), (3, 23, 36)] {
+            let delay = policy.delay(retry).as_millis();
+            assert!((low..=high).contains(&delay), "{} -> {}", retry, delay);
+        }
+
+        let calls = AtomicU32::new(0);
+        let value = policy
+            .run("读取", || async {
+                match calls.fetch_add(1, Ordering::Relaxed) {
+                    0 | 1 => Err(DeviceError::Timeout),
+                    _ => Ok(7),
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!((value, calls.load(Ordering::Relaxed)), (7, 3));
+
+        calls.store(0, Ordering::Relaxed);
+        let result: Result<()> = policy
+            .run("写入", || async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err(DeviceError::ProtocolError("非法地址".into()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
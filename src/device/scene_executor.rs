@@ -1,165 +1,1155 @@
 /// 场景执行器 - 负责场景的编排和执行
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{broadcast, Mutex};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex, Notify};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
-use super::{ChannelManager, DeviceController, DeviceEvent, NodeManager};
-use crate::config::SceneConfig;
+use super::{DeviceController, DeviceEvent, NodeManager};
+use crate::config::{SceneConfig, SceneConflictPolicy, SceneNode, SceneStepType, StepErrorPolicy};
 use crate::utils::{DeviceError, Result};
 
+/// 场景步骤等待协议事件的默认超时（毫秒）
+const DEFAULT_WAIT_TIMEOUT_MS: u64 = 60000;
+
+/// ramp 步骤默认写入间隔（毫秒）
+const DEFAULT_RAMP_INTERVAL_MS: u32 = 100;
+
+/// 步骤失败重试前的等待时间（毫秒）
+const STEP_RETRY_DELAY_MS: u64 = 500;
+
+/// 单次场景执行中 jump 跳转的最大次数，防止失败步骤之间无限循环
+const MAX_STEP_JUMPS: u32 = 16;
+
+/// 未配置互斥组的场景所属的默认组
+const DEFAULT_EXCLUSION_GROUP: &str = "default";
+
 /// 场景执行状态
 #[derive(Debug, Clone, Default)]
 pub struct SceneExecutionStatus {
     /// 是否正在执行场景
     pub is_executing: bool,
-    /// 当前执行的场景名称（如果有）
+    /// 当前执行的场景名称（如果有，多个场景同时执行时为最近开始的一个）
     pub current_scene: Option<String>,
     /// 当前执行步骤索引（0-based）
     pub current_step_index: Option<usize>,
     /// 当前执行场景总步骤数
     pub total_steps: Option<usize>,
+    /// 全部排队中和执行中的场景
+    pub runs: Vec<SceneRunStatus>,
+    /// 最近一次结束的场景执行结果
+    pub last_result: Option<SceneRunResult>,
+    /// cue 模式下等待继续的步骤
+    pub cues: Vec<SceneCue>,
+}
+
+/// 单次场景执行的状态
+#[derive(Debug, Clone)]
+pub struct SceneRunStatus {
+    pub run_id: u64,
+    pub scene: String,
+    /// 互斥组
+    pub group: String,
+    /// 是否在排队等待同组场景结束
+    pub queued: bool,
+    pub current_step_index: Option<usize>,
+    pub total_steps: usize,
+    /// 手动逐步执行（cue 模式）
+    pub cue: bool,
+    /// cue 模式下等待继续的步骤索引
+    pub awaiting_step: Option<usize>,
+}
+
+/// cue 模式下的场景步骤
+#[derive(Debug, Clone)]
+pub struct SceneCue {
+    pub run_id: u64,
+    pub scene: String,
+    /// 步骤索引（0-based）
+    pub step: usize,
+    pub total_steps: usize,
+    /// 步骤定义
+    pub definition: SceneNode,
+    /// 步骤涉及的节点（目标值与当前值）
+    pub targets: Vec<SceneStepDiff>,
+}
+
+/// 场景执行结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SceneRunResult {
+    pub run_id: u64,
+    pub scene: String,
+    /// 全部步骤成功且未被取消、中止
+    pub success: bool,
+    /// 被同组场景取消（cancel-previous）
+    pub cancelled: bool,
+    /// 步骤失败且 on_error 为 abort（或跳转次数超限）时中止
+    pub aborted: bool,
+    /// 失败的步骤
+    pub failed_steps: Vec<SceneStepFailure>,
+}
+
+/// 失败的场景步骤
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneStepFailure {
+    /// 步骤索引（0-based）
+    pub step: usize,
+    pub label: Option<String>,
+    pub global_id: u32,
+    /// 失败原因（最后一次尝试）
+    pub error: String,
+    /// 尝试次数（含重试）
+    pub attempts: u32,
+}
+
+/// 场景步骤预览（执行前对比目标值与节点当前值）
+#[derive(Debug, Clone)]
+pub struct SceneStepDiff {
+    /// 步骤索引（0-based）
+    pub step: usize,
+    pub global_id: u32,
+    /// 节点别名（节点不存在时为 None）
+    pub alias: Option<String>,
+    pub target_value: i32,
+    /// 执行到该步骤前节点的值：同一节点在前面步骤中已写入时取前一步的目标值
+    pub current_value: Option<i32>,
+    pub online: bool,
+    /// 执行该步骤会改变节点值（当前值未知时视为会改变，节点不存在时为 false）
+    pub changed: bool,
+}
+
+/// 登记表中的一次场景执行
+struct SceneRun {
+    status: SceneRunStatus,
+    token: CancellationToken,
+    steps: Arc<Vec<SceneNode>>,
+    /// cue 模式下放行下一步骤
+    cue: Option<Arc<Notify>>,
+}
+
+/// 场景运行登记表：记录排队中和执行中的场景，同一互斥组内的场景通过组锁串行执行
+#[derive(Default)]
+struct RunRegistry {
+    next_id: AtomicU64,
+    runs: StdMutex<BTreeMap<u64, SceneRun>>,
+    groups: StdMutex<HashMap<String, Arc<Mutex<()>>>>,
+    last_result: StdMutex<Option<SceneRunResult>>,
+}
+
+impl RunRegistry {
+    fn group_lock(&self, group: &str) -> Arc<Mutex<()>> {
+        let mut groups = self.groups.lock().unwrap();
+        groups.entry(group.to_string()).or_default().clone()
+    }
+
+    fn register(
+        &self,
+        scene: &str,
+        group: &str,
+        steps: Arc<Vec<SceneNode>>,
+        queued: bool,
+        cue: Option<Arc<Notify>>,
+    ) -> (u64, CancellationToken) {
+        let run_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let token = CancellationToken::new();
+        let run = SceneRun {
+            status: SceneRunStatus {
+                run_id,
+                scene: scene.to_string(),
+                group: group.to_string(),
+                queued,
+                current_step_index: None,
+                total_steps: steps.len(),
+                cue: cue.is_some(),
+                awaiting_step: None,
+            },
+            token: token.clone(),
+            steps,
+            cue,
+        };
+        self.runs.lock().unwrap().insert(run_id, run);
+        (run_id, token)
+    }
+
+    fn update(&self, run_id: u64, f: impl FnOnce(&mut SceneRunStatus)) {
+        if let Some(run) = self.runs.lock().unwrap().get_mut(&run_id) {
+            f(&mut run.status);
+        }
+    }
+
+    fn finish(&self, run_id: u64) {
+        self.runs.lock().unwrap().remove(&run_id);
+    }
+
+    fn set_last_result(&self, result: SceneRunResult) {
+        *self.last_result.lock().unwrap() = Some(result);
+    }
+
+    /// 取消同组全部排队中和执行中的场景，返回被取消的场景名称
+    fn cancel_group(&self, group: &str) -> Vec<String> {
+        let runs = self.runs.lock().unwrap();
+        runs.values()
+            .filter(|r| r.status.group == group)
+            .map(|r| {
+                r.token.cancel();
+                r.status.scene.clone()
+            })
+            .collect()
+    }
+
+    /// 放行 cue 模式下等待继续的步骤，返回该步骤索引与定义
+    fn advance(&self, run_id: u64) -> Result<(SceneRunStatus, usize, SceneNode)> {
+        let mut runs = self.runs.lock().unwrap();
+        let run = runs
+            .get_mut(&run_id)
+            .ok_or_else(|| DeviceError::Other(format!("场景执行 {} 不存在或已结束", run_id)))?;
+        let gate = run
+            .cue
+            .clone()
+            .ok_or_else(|| DeviceError::Other(format!("场景执行 {} 不是 cue 模式", run_id)))?;
+        let step = run.status.awaiting_step.take().ok_or_else(|| {
+            DeviceError::Other(format!(
+                "场景执行 {} 未在等待继续（排队中或正在执行步骤）",
+                run_id
+            ))
+        })?;
+        gate.notify_one();
+        Ok((run.status.clone(), step, run.steps[step].clone()))
+    }
+
+    /// 取消一次排队中或执行中的场景，返回场景名称
+    fn cancel(&self, run_id: u64) -> Result<String> {
+        let runs = self.runs.lock().unwrap();
+        let run = runs
+            .get(&run_id)
+            .ok_or_else(|| DeviceError::Other(format!("场景执行 {} 不存在或已结束", run_id)))?;
+        run.token.cancel();
+        Ok(run.status.scene.clone())
+    }
+
+    /// cue 模式下等待继续的步骤定义
+    fn awaiting(&self) -> Vec<(SceneRunStatus, usize, SceneNode)> {
+        let runs = self.runs.lock().unwrap();
+        runs.values()
+            .filter_map(|r| {
+                let step = r.status.awaiting_step?;
+                Some((r.status.clone(), step, r.steps[step].clone()))
+            })
+            .collect()
+    }
+
+    fn active_in_group(&self, group: &str) -> Option<String> {
+        let runs = self.runs.lock().unwrap();
+        runs.values()
+            .find(|r| r.status.group == group)
+            .map(|r| r.status.scene.clone())
+    }
+
+    fn snapshot(&self) -> Vec<SceneRunStatus> {
+        let runs = self.runs.lock().unwrap();
+        runs.values().map(|r| r.status.clone()).collect()
+    }
 }
 
 /// 场景执行器
 pub struct SceneExecutor {
-    scenes: Vec<SceneConfig>,
-    channel_manager: Arc<ChannelManager>,
+    /// 场景列表（导入场景时运行中更新）
+    scenes: std::sync::RwLock<Vec<SceneConfig>>,
     node_manager: Arc<NodeManager>,
     event_tx: broadcast::Sender<DeviceEvent>,
-    /// 当前场景执行状态
-    execution_status: Arc<Mutex<SceneExecutionStatus>>,
+    /// 排队中和执行中的场景
+    registry: Arc<RunRegistry>,
 }
 
 impl SceneExecutor {
     /// 创建场景执行器
     pub fn new(
         scenes: Vec<SceneConfig>,
-        channel_manager: Arc<ChannelManager>,
         node_manager: Arc<NodeManager>,
         event_tx: broadcast::Sender<DeviceEvent>,
     ) -> Self {
         info!("场景执行器初始化，共 {} 个场景", scenes.len());
         Self {
-            scenes,
-            channel_manager,
+            scenes: std::sync::RwLock::new(scenes),
             node_manager,
             event_tx,
-            execution_status: Arc::new(Mutex::new(SceneExecutionStatus::default())),
+            registry: Arc::new(RunRegistry::default()),
         }
     }
 
-    /// 执行场景（异步启动，立即返回）
-    pub async fn execute(&self, scene_name: &str, controller: &DeviceController) -> Result<()> {
+    /// 执行场景（异步启动，立即返回执行编号）
+    ///
+    /// 同一互斥组同一时间只执行一个场景，冲突时按场景的 on_conflict 策略拒绝、排队或取消之前的场景。
+    /// cue 模式下每个步骤执行前暂停，等待 [`SceneExecutor::advance`] 放行。
+    pub async fn execute(
+        &self,
+        scene_name: &str,
+        controller: &DeviceController,
+        cue: bool,
+    ) -> Result<u64> {
         // 查找场景
         let scene = self
-            .scenes
-            .iter()
-            .find(|s| s.name == scene_name)
+            .get_scene(scene_name)
             .ok_or_else(|| DeviceError::Other(format!("场景 '{}' 不存在", scene_name)))?;
+        let group = scene
+            .exclusion_group
+            .clone()
+            .unwrap_or_else(|| DEFAULT_EXCLUSION_GROUP.to_string());
+        let group_lock = self.registry.group_lock(&group);
 
-        // 检查是否已有场景正在执行
-        let mut status = self.execution_status.lock().await;
-        if status.is_executing {
-            let executing_scene = status
-                .current_scene
-                .clone()
-                .unwrap_or_else(|| "未知场景".to_string());
-            return Err(DeviceError::Other(format!(
-                "场景 '{}' 正在执行中，无法同时执行场景 '{}'",
-                executing_scene, scene_name
-            )));
-        }
-
-        // 标记当前场景为正在执行
-        status.is_executing = true;
-        status.current_scene = Some(scene_name.to_string());
-        status.current_step_index = None;
-        status.total_steps = Some(scene.nodes.len());
-        drop(status); // 释放锁
+        // 拒绝策略下立即占用组锁，其余策略在后台任务中等待
+        let guard = match scene.on_conflict {
+            SceneConflictPolicy::Reject => match group_lock.clone().try_lock_owned() {
+                Ok(guard) => Some(guard),
+                Err(_) => {
+                    let executing_scene = self
+                        .registry
+                        .active_in_group(&group)
+                        .unwrap_or_else(|| "未知场景".to_string());
+                    return Err(DeviceError::Other(format!(
+                        "场景 '{}' 正在执行中，无法同时执行场景 '{}'",
+                        executing_scene, scene_name
+                    )));
+                }
+            },
+            SceneConflictPolicy::Queue => None,
+            SceneConflictPolicy::CancelPrevious => {
+                let cancelled = self.registry.cancel_group(&group);
+                if !cancelled.is_empty() {
+                    info!(
+                        "场景 '{}' 取消同组 '{}' 中的场景: {:?}",
+                        scene_name, group, cancelled
+                    );
+                }
+                None
+            }
+        };
 
-        info!("开始执行场景: {}", scene_name);
+        let scene_nodes = Arc::new(scene.nodes);
+        let cue_gate = cue.then(|| Arc::new(Notify::new()));
+        let (run_id, token) = self.registry.register(
+            scene_name,
+            &group,
+            scene_nodes.clone(),
+            guard.is_none(),
+            cue_gate.clone(),
+        );
 
         // 克隆需要的数据用于异步任务
         let scene_name_str = scene_name.to_string();
-        let scene_nodes = scene.nodes.clone();
         let controller_clone = controller.clone();
-        let execution_status = self.execution_status.clone();
+        let registry = self.registry.clone();
         let event_tx = self.event_tx.clone();
-
-        // 发送场景开始事件
-        let _ = event_tx.send(DeviceEvent::SceneStarted {
-            scene_name: scene_name_str.clone(),
-        });
+        let node_manager = self.node_manager.clone();
 
         // 在后台异步执行场景
         tokio::spawn(async move {
-            let mut success = true;
+            let _guard = match guard {
+                Some(guard) => guard,
+                None => {
+                    info!("场景 '{}' 等待互斥组 '{}' 空闲", scene_name_str, group);
+                    tokio::select! {
+                        guard = group_lock.lock_owned() => guard,
+                        _ = token.cancelled() => {
+                            registry.finish(run_id);
+                            warn!("场景 '{}' 在排队中被取消", scene_name_str);
+                            let result = SceneRunResult {
+                                run_id,
+                                scene: scene_name_str.clone(),
+                                cancelled: true,
+                                ..Default::default()
+                            };
+                            registry.set_last_result(result.clone());
+                            controller_clone.stats.record_scene(&result, None);
+                            let _ = event_tx.send(DeviceEvent::SceneCompleted {
+                                scene_name: scene_name_str,
+                                success: false,
+                                result,
+                            });
+                            return;
+                        }
+                    }
+                }
+            };
+            registry.update(run_id, |status| status.queued = false);
 
-            // 按顺序执行场景中的所有成员
-            for (index, member) in scene_nodes.iter().enumerate() {
-                {
-                    let mut status = execution_status.lock().await;
-                    status.current_step_index = Some(index);
+            info!("开始执行场景: {}", scene_name_str);
+            let started = Instant::now();
+            // 发送场景开始事件
+            let _ = event_tx.send(DeviceEvent::SceneStarted {
+                scene_name: scene_name_str.clone(),
+            });
+
+            let mut failed_steps: Vec<SceneStepFailure> = Vec::new();
+            let mut aborted = false;
+            let mut jumps = 0;
+            let mut index = 0;
+
+            // 按顺序执行场景中的所有成员，失败时按步骤的 on_error 处理
+            while let Some(member) = scene_nodes.get(index) {
+                if token.is_cancelled() {
+                    break;
+                }
+                registry.update(run_id, |status| status.current_step_index = Some(index));
+
+                // cue 模式：等待操作员放行该步骤
+                if let Some(gate) = &cue_gate {
+                    registry.update(run_id, |status| status.awaiting_step = Some(index));
+                    info!("场景 '{}': 等待继续执行步骤 {}", scene_name_str, index);
+                    tokio::select! {
+                        _ = gate.notified() => {}
+                        _ = token.cancelled() => break,
+                    }
                 }
 
                 // 延迟执行（如果有配置）
                 if let Some(delay) = member.delay {
-                    tokio::time::sleep(Duration::from_millis(delay as u64)).await;
+                    if !Self::pause(&token, delay as u64).await {
+                        break;
+                    }
+                }
+
+                let max_attempts = match &member.on_error {
+                    StepErrorPolicy::Retry(n) => n + 1,
+                    _ => 1,
+                };
+                let mut attempts = 0;
+                let outcome = loop {
+                    attempts += 1;
+                    let result = Self::run_step(
+                        &controller_clone,
+                        &node_manager,
+                        &event_tx,
+                        &token,
+                        &scene_name_str,
+                        member,
+                    )
+                    .await;
+                    match result {
+                        Err(ref e) if attempts < max_attempts && !token.is_cancelled() => {
+                            warn!(
+                                "场景 '{}': 步骤 {} 失败，第 {} 次重试: {}",
+                                scene_name_str, index, attempts, e
+                            );
+                            if !Self::pause(&token, STEP_RETRY_DELAY_MS).await {
+                                break result;
+                            }
+                        }
+                        result => break result,
+                    }
+                };
+                // 取消导致的中断不计为步骤失败
+                if token.is_cancelled() {
+                    break;
                 }
 
-                // 执行写入
-                match controller_clone.write_node(member.id, member.value).await {
-                    Ok(_) => {
-                        info!(
-                            "场景 '{}': 节点 {} 设置为 {}",
-                            scene_name_str, member.id, member.value
-                        );
+                let error = match outcome {
+                    Ok(()) => {
+                        index += 1;
+                        continue;
                     }
-                    Err(e) => {
-                        warn!(
-                            "场景 '{}': 节点 {} 设置失败: {:?}",
-                            scene_name_str, member.id, e
-                        );
-                        success = false;
+                    Err(e) => e,
+                };
+                warn!("场景 '{}': 步骤 {} 失败: {}", scene_name_str, index, error);
+                failed_steps.push(SceneStepFailure {
+                    step: index,
+                    label: member.label.clone(),
+                    global_id: member.id,
+                    error,
+                    attempts,
+                });
+
+                match &member.on_error {
+                    StepErrorPolicy::Abort => {
+                        warn!("场景 '{}': 步骤 {} 失败，中止场景", scene_name_str, index);
+                        aborted = true;
+                        break;
+                    }
+                    StepErrorPolicy::Continue | StepErrorPolicy::Retry(_) => index += 1,
+                    StepErrorPolicy::Jump(label) => {
+                        let target = scene_nodes
+                            .iter()
+                            .position(|n| n.label.as_deref() == Some(label.as_str()));
+                        match target {
+                            Some(_) if jumps >= MAX_STEP_JUMPS => {
+                                warn!(
+                                    "场景 '{}': 跳转次数超过 {}，中止场景",
+                                    scene_name_str, MAX_STEP_JUMPS
+                                );
+                                aborted = true;
+                                break;
+                            }
+                            Some(target) => {
+                                info!(
+                                    "场景 '{}': 步骤 {} 失败，跳转到 '{}'",
+                                    scene_name_str, index, label
+                                );
+                                jumps += 1;
+                                index = target;
+                            }
+                            None => {
+                                warn!(
+                                    "场景 '{}': 跳转目标 '{}' 不存在，继续执行下一步",
+                                    scene_name_str, label
+                                );
+                                index += 1;
+                            }
+                        }
                     }
                 }
             }
 
-            // 清除执行状态
-            let mut status = execution_status.lock().await;
-            *status = SceneExecutionStatus::default();
-            drop(status);
+            let result = SceneRunResult {
+                run_id,
+                scene: scene_name_str.clone(),
+                success: failed_steps.is_empty() && !aborted && !token.is_cancelled(),
+                cancelled: token.is_cancelled(),
+                aborted,
+                failed_steps,
+            };
 
-            // 发送场景完成事件
-            let _ = event_tx.send(DeviceEvent::SceneCompleted {
-                scene_name: scene_name_str.clone(),
-                success,
-            });
+            // 清除执行状态
+            registry.finish(run_id);
+            registry.set_last_result(result.clone());
+            controller_clone
+                .stats
+                .record_scene(&result, Some(started.elapsed()));
 
-            if success {
+            if result.cancelled {
+                warn!("场景 '{}' 已被取消", scene_name_str);
+            } else if result.aborted {
+                warn!("场景 '{}' 已中止", scene_name_str);
+            } else if result.success {
                 info!("场景 '{}' 执行成功", scene_name_str);
             } else {
-                warn!("场景 '{}' 执行部分失败", scene_name_str);
+                warn!(
+                    "场景 '{}' 执行部分失败（{} 个步骤失败）",
+                    scene_name_str,
+                    result.failed_steps.len()
+                );
             }
+
+            // 发送场景完成事件
+            let _ = event_tx.send(DeviceEvent::SceneCompleted {
+                scene_name: scene_name_str,
+                success: result.success,
+                result,
+            });
         });
 
         // 立即返回，不等待场景执行完成
+        Ok(run_id)
+    }
+
+    /// cue 模式下放行等待中的步骤，返回放行的步骤
+    pub fn advance(&self, run_id: u64) -> Result<SceneCue> {
+        let (status, step, definition) = self.registry.advance(run_id)?;
+        info!("场景 '{}': 继续执行步骤 {}", status.scene, step);
+        Ok(self.cue(status, step, definition))
+    }
+
+    /// 取消一次排队中或执行中的场景，返回场景名称
+    pub fn cancel_run(&self, run_id: u64) -> Result<String> {
+        let scene = self.registry.cancel(run_id)?;
+        info!("场景 '{}' (执行 {}) 被手动取消", scene, run_id);
+        Ok(scene)
+    }
+
+    /// 排队中或执行中的一次场景
+    pub fn run_status(&self, run_id: u64) -> Option<SceneRunStatus> {
+        self.registry
+            .snapshot()
+            .into_iter()
+            .find(|r| r.run_id == run_id)
+    }
+
+    fn cue(&self, status: SceneRunStatus, step: usize, definition: SceneNode) -> SceneCue {
+        let targets = definition
+            .targets()
+            .into_iter()
+            .map(|global_id| {
+                let state = self.node_manager.get_state(global_id);
+                let current_value = state.as_ref().and_then(|s| s.current_value);
+                SceneStepDiff {
+                    step,
+                    global_id,
+                    alias: state.as_ref().map(|s| s.alias.clone()),
+                    target_value: definition.value,
+                    current_value,
+                    online: state.as_ref().is_some_and(|s| s.online),
+                    changed: state.is_some() && current_value != Some(definition.value),
+                }
+            })
+            .collect();
+        SceneCue {
+            run_id: status.run_id,
+            scene: status.scene,
+            step,
+            total_steps: status.total_steps,
+            definition,
+            targets,
+        }
+    }
+
+    /// 等待指定毫秒数，场景被取消时提前返回 false
+    async fn pause(token: &CancellationToken, millis: u64) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(millis)) => true,
+            _ = token.cancelled() => false,
+        }
+    }
+
+    /// 执行单个步骤（写入并按配置等待协议事件），返回失败原因
+    async fn run_step(
+        controller: &DeviceController,
+        node_manager: &NodeManager,
+        event_tx: &broadcast::Sender<DeviceEvent>,
+        token: &CancellationToken,
+        scene_name: &str,
+        member: &SceneNode,
+    ) -> std::result::Result<(), String> {
+        // 需要等待事件时在写入前订阅，避免错过快速完成的事件（stagger-group / playlist 不支持等待事件）
+        let mut event_rx = member
+            .wait_event
+            .as_ref()
+            .filter(|_| matches!(member.step_type, SceneStepType::Set | SceneStepType::Ramp))
+            .map(|_| event_tx.subscribe());
+
+        // 执行写入
+        match member.step_type {
+            SceneStepType::Set => {
+                Self::write_step(controller, scene_name, member.id, member.value).await?
+            }
+            SceneStepType::Ramp => {
+                Self::run_ramp(controller, node_manager, token, scene_name, member).await?
+            }
+            SceneStepType::StaggerGroup => {
+                Self::run_stagger(controller, token, scene_name, member).await?
+            }
+            SceneStepType::Playlist => Self::switch_playlist(event_tx, scene_name, member)?,
+            SceneStepType::Profile => Self::switch_profile(controller, scene_name, member).await?,
+        }
+
+        if let (Some(event), Some(rx)) = (&member.wait_event, event_rx.as_mut()) {
+            let timeout = member
+                .wait_timeout
+                .map(|t| t as u64)
+                .unwrap_or(DEFAULT_WAIT_TIMEOUT_MS);
+            let wait = Self::wait_node_event(node_manager, rx, member.id, event, timeout);
+            let arrived = tokio::select! {
+                arrived = wait => arrived,
+                _ = token.cancelled() => return Ok(()),
+            };
+            if !arrived {
+                return Err(format!(
+                    "节点 {} 等待事件 '{}' 超时 ({}ms)",
+                    member.id, event, timeout
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 写入单个节点
+    async fn write_step(
+        controller: &DeviceController,
+        scene_name: &str,
+        global_id: u32,
+        value: i32,
+    ) -> std::result::Result<(), String> {
+        controller
+            .write_node(global_id, value)
+            .await
+            .map_err(|e| format!("节点 {} 设置为 {} 失败: {}", global_id, value, e))?;
+        info!("场景 '{}': 节点 {} 设置为 {}", scene_name, global_id, value);
+        Ok(())
+    }
+
+    /// ramp 步骤：在 duration 内按 step_interval 从当前值线性过渡到目标值
+    ///
+    /// 节点当前值未知时先读取一次，读取失败则直接写入目标值。
+    async fn run_ramp(
+        controller: &DeviceController,
+        node_manager: &NodeManager,
+        token: &CancellationToken,
+        scene_name: &str,
+        member: &SceneNode,
+    ) -> std::result::Result<(), String> {
+        let current = node_manager
+            .get_state(member.id)
+            .and_then(|s| s.current_value);
+        let from = match current {
+            Some(v) => v,
+            None => match controller.read_node(member.id).await {
+                Ok(v) => v.round() as i32,
+                Err(e) => {
+                    warn!(
+                        "场景 '{}': 节点 {} 当前值未知，直接写入目标值: {:?}",
+                        scene_name, member.id, e
+                    );
+                    return Self::write_step(controller, scene_name, member.id, member.value).await;
+                }
+            },
+        };
+
+        let interval = member
+            .step_interval
+            .filter(|i| *i > 0)
+            .unwrap_or(DEFAULT_RAMP_INTERVAL_MS) as u64;
+        let steps = (member.duration.unwrap_or(0) as u64 / interval).max(1) as i64;
+        let delta = (member.value - from) as i64;
+
+        let mut last = from;
+        for i in 1..=steps {
+            let value = from + (delta * i / steps) as i32;
+            if value != last || i == steps {
+                controller
+                    .write_node(member.id, value)
+                    .await
+                    .map_err(|e| format!("节点 {} 渐变写入 {} 失败: {}", member.id, value, e))?;
+                last = value;
+            }
+            if i < steps && !Self::pause(token, interval).await {
+                info!("场景 '{}': 节点 {} 渐变被取消", scene_name, member.id);
+                return Ok(());
+            }
+        }
+
+        info!(
+            "场景 '{}': 节点 {} 由 {} 渐变到 {}",
+            scene_name, member.id, from, member.value
+        );
         Ok(())
     }
 
+    /// stagger-group 步骤：按顺序向节点列表写入同一个值，单个节点失败不影响其余节点
+    async fn run_stagger(
+        controller: &DeviceController,
+        token: &CancellationToken,
+        scene_name: &str,
+        member: &SceneNode,
+    ) -> std::result::Result<(), String> {
+        let stagger = member.stagger.unwrap_or(0) as u64;
+        let mut errors = Vec::new();
+        for (i, global_id) in member.targets().into_iter().enumerate() {
+            if i > 0 && stagger > 0 && !Self::pause(token, stagger).await {
+                break;
+            }
+            if let Err(e) = Self::write_step(controller, scene_name, global_id, member.value).await
+            {
+                errors.push(e);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// 请求播放列表调度切换屏幕的播放列表（不等待素材推送完成）
+    fn switch_playlist(
+        event_tx: &broadcast::Sender<DeviceEvent>,
+        scene_name: &str,
+        member: &SceneNode,
+    ) -> std::result::Result<(), String> {
+        let screen_id = member
+            .screen_id
+            .clone()
+            .ok_or_else(|| "playlist 步骤缺少 screen_id".to_string())?;
+        info!(
+            "场景 '{}': 屏幕 {} 切换播放列表 {}",
+            scene_name,
+            screen_id,
+            member.playlist_id.as_deref().unwrap_or("（自动）")
+        );
+        event_tx
+            .send(DeviceEvent::PlaylistSwitch {
+                screen_id,
+                playlist_id: member.playlist_id.clone(),
+                scene_name: scene_name.to_string(),
+            })
+            .map(|_| ())
+            .map_err(|_| "播放列表调度未运行".to_string())
+    }
+
+    /// profile 步骤：value 非 0 时启动时间曲线，为 0 时停止
+    async fn switch_profile(
+        controller: &DeviceController,
+        scene_name: &str,
+        member: &SceneNode,
+    ) -> std::result::Result<(), String> {
+        let name = member
+            .profile
+            .as_deref()
+            .ok_or_else(|| "profile 步骤缺少 profile".to_string())?;
+        let result = if member.value != 0 {
+            controller.start_profile(name)
+        } else {
+            controller.stop_profile(name).await
+        };
+        result.map_err(|e| e.to_string())?;
+        info!(
+            "场景 '{}': 时间曲线 '{}' {}",
+            scene_name,
+            name,
+            if member.value != 0 {
+                "启动"
+            } else {
+                "停止"
+            }
+        );
+        Ok(())
+    }
+
+    /// 等待节点所在通道发出指定协议事件
+    ///
+    /// 事件数据中带 `device_id` 时需与节点的设备 ID 一致
+    async fn wait_node_event(
+        node_manager: &NodeManager,
+        rx: &mut broadcast::Receiver<DeviceEvent>,
+        global_id: u32,
+        event_name: &str,
+        timeout_ms: u64,
+    ) -> bool {
+        let state = match node_manager.get_state(global_id) {
+            Some(s) => s,
+            None => return false,
+        };
+
+        let wait = async {
+            loop {
+                match rx.recv().await {
+                    Ok(DeviceEvent::ProtocolEvent {
+                        channel_id,
+                        event,
+                        data,
+                    }) if channel_id == state.channel_id && event == event_name => {
+                        let device_matches = data
+                            .get("device_id")
+                            .and_then(|v| v.as_u64())
+                            .map(|id| id as u32 == state.device_id)
+                            .unwrap_or(true);
+                        if device_matches {
+                            return true;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        super::record_lagged_events(n);
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Closed) => return false,
+                }
+            }
+        };
+
+        tokio::time::timeout(Duration::from_millis(timeout_ms), wait)
+            .await
+            .unwrap_or(false)
+    }
+
     /// 获取所有场景名称
     pub fn list_scenes(&self) -> Vec<String> {
-        self.scenes.iter().map(|s| s.name.clone()).collect()
+        let scenes = self.scenes.read().unwrap();
+        scenes.iter().map(|s| s.name.clone()).collect()
     }
 
     /// 获取场景详情
-    pub fn get_scene(&self, scene_name: &str) -> Option<&SceneConfig> {
-        self.scenes.iter().find(|s| s.name == scene_name)
+    pub fn get_scene(&self, scene_name: &str) -> Option<SceneConfig> {
+        let scenes = self.scenes.read().unwrap();
+        scenes.iter().find(|s| s.name == scene_name).cloned()
+    }
+
+    /// 获取全部场景
+    pub fn get_all_scenes(&self) -> Vec<SceneConfig> {
+        self.scenes.read().unwrap().clone()
+    }
+
+    /// 新增或替换场景（按名称匹配，已存在的场景被替换）
+    pub fn upsert_scenes(&self, new_scenes: Vec<SceneConfig>) {
+        let mut scenes = self.scenes.write().unwrap();
+        for scene in new_scenes {
+            match scenes.iter_mut().find(|s| s.name == scene.name) {
+                Some(existing) => *existing = scene,
+                None => scenes.push(scene),
+            }
+        }
+    }
+
+    /// 预览场景执行效果：逐步对比目标值与节点当前值，不执行写入
+    ///
+    /// stagger-group 步骤按节点展开为多条结果，ramp 步骤按最终目标值对比。
+    pub fn diff(&self, scene_name: &str) -> Result<Vec<SceneStepDiff>> {
+        let scene = self
+            .get_scene(scene_name)
+            .ok_or_else(|| DeviceError::Other(format!("场景 '{}' 不存在", scene_name)))?;
+
+        // 同一节点可能在场景中出现多次，后面的步骤以前一步写入的值为准
+        let mut pending: HashMap<u32, i32> = HashMap::new();
+        let steps = scene
+            .nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(step, member)| {
+                member
+                    .targets()
+                    .into_iter()
+                    .map(move |global_id| (step, global_id, member.value))
+            })
+            .map(|(step, global_id, target_value)| {
+                let state = self.node_manager.get_state(global_id);
+                let current_value = match pending.insert(global_id, target_value) {
+                    Some(previous) => Some(previous),
+                    None => state.as_ref().and_then(|s| s.current_value),
+                };
+                SceneStepDiff {
+                    step,
+                    global_id,
+                    alias: state.as_ref().map(|s| s.alias.clone()),
+                    target_value,
+                    current_value,
+                    online: state.as_ref().is_some_and(|s| s.online),
+                    changed: state.is_some() && current_value != Some(target_value),
+                }
+            })
+            .collect();
+        Ok(steps)
     }
 
     /// 获取当前场景执行状态
     pub async fn get_execution_status(&self) -> SceneExecutionStatus {
-        self.execution_status.lock().await.clone()
+        let runs = self.registry.snapshot();
+        let latest = runs.iter().rev().find(|r| !r.queued);
+        SceneExecutionStatus {
+            is_executing: latest.is_some(),
+            current_scene: latest.map(|r| r.scene.clone()),
+            current_step_index: latest.and_then(|r| r.current_step_index),
+            total_steps: latest.map(|r| r.total_steps),
+            runs,
+            last_result: self.registry.last_result.lock().unwrap().clone(),
+            cues: self
+                .registry
+                .awaiting()
+                .into_iter()
+                .map(|(status, step, definition)| self.cue(status, step, definition))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    /// 两个不落盘的 mock 通道：9101 正常，9102 写入必定失败；节点 9 位于故障通道
+    async fn controller(scenes: serde_json::Value) -> DeviceController {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "channels": [
+                {
+                    "channel_id": 9101, "enable": true, "statute": "mock",
+                    "arguments": { "persist": false, "initial_values": { "1": 20 } }
+                },
+                {
+                    "channel_id": 9102, "enable": true, "statute": "mock",
+                    "arguments": { "persist": false, "error_rate": 1.0 }
+                }
+            ],
+            "nodes": [
+                { "global_id": 1, "channel_id": 9101, "id": 1, "alias": "灯光" },
+                { "global_id": 2, "channel_id": 9101, "id": 2, "alias": "投影" },
+                { "global_id": 3, "channel_id": 9101, "id": 3, "alias": "音响" },
+                { "global_id": 9, "channel_id": 9102, "id": 1, "alias": "故障设备" }
+            ],
+            "scenes": scenes,
+            "web_server": { "port": 8080 }
+        }))
+        .unwrap();
+        DeviceController::new(config).await.unwrap()
+    }
+
+    /// 等待指定执行的场景完成事件
+    async fn finished(rx: &mut broadcast::Receiver<DeviceEvent>, run_id: u64) -> SceneRunResult {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(DeviceEvent::SceneCompleted { result, .. }) = rx.recv().await {
+                    if result.run_id == run_id {
+                        return result;
+                    }
+                }
+            }
+        })
+        .await
+        .expect("场景应在超时前结束")
+    }
+
+    async fn value(controller: &DeviceController, global_id: u32) -> i32 {
+        controller.read_node(global_id).await.unwrap() as i32
+    }
+
+    fn writes(controller: &DeviceController, global_id: u32) -> u64 {
+        controller
+            .execution_stats()
+            .nodes
+            .iter()
+            .find(|n| n.global_id == global_id)
+            .map_or(0, |n| n.writes)
+    }
+
+    #[tokio::test]
+    async fn ramp_and_stagger_steps_write_in_order() {
+        let controller = controller(serde_json::json!([{
+            "name": "开场",
+            "nodes": [
+                { "type": "ramp", "id": 1, "value": 100, "duration": 40, "step_interval": 10 },
+                { "type": "stagger-group", "nodes": [2, 9, 3], "value": 7, "stagger": 20 }
+            ]
+        }]))
+        .await;
+        let mut rx = controller.subscribe_events();
+
+        let started = Instant::now();
+        let run_id = controller.start_scene("开场", false).await.unwrap();
+        let result = finished(&mut rx, run_id).await;
+
+        // 20 -> 100 分 4 次写入：40、60、80、100；stagger 间隔 2 次
+        assert_eq!(value(&controller, 1).await, 100);
+        assert_eq!(writes(&controller, 1), 4);
+        assert!(started.elapsed() >= Duration::from_millis(70));
+
+        // 故障节点不影响组内其余节点，整个步骤记为失败
+        assert_eq!(
+            (value(&controller, 2).await, value(&controller, 3).await),
+            (7, 7)
+        );
+        assert!(!result.success && !result.aborted);
+        assert_eq!(result.failed_steps.len(), 1);
+        assert_eq!(result.failed_steps[0].step, 1);
+        assert!(result.failed_steps[0].error.contains("节点 9"));
+
+        controller.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn cue_run_advances_only_while_awaiting() {
+        let registry = RunRegistry::default();
+        let steps: Vec<SceneNode> = serde_json::from_value(
+            serde_json::json!([{ "id": 1, "value": 1 }, { "id": 2, "value": 0 }]),
+        )
+        .unwrap();
+        let gate = Arc::new(Notify::new());
+        let (run_id, _token) = registry.register(
+            "rehearsal",
+            "default",
+            Arc::new(steps),
+            false,
+            Some(gate.clone()),
+        );
+
+        // 未到达等待点时不能放行
+        assert!(registry.advance(run_id).is_err());
+
+        registry.update(run_id, |status| status.awaiting_step = Some(1));
+        let (status, step, definition) = registry.advance(run_id).unwrap();
+        assert!(status.cue && status.awaiting_step.is_none());
+        assert_eq!((step, definition.id), (1, 2));
+        tokio::time::timeout(Duration::from_millis(100), gate.notified())
+            .await
+            .expect("步骤应已放行");
+        assert!(registry.advance(run_id).is_err());
+
+        let (plain, _) = registry.register("show", "default", Arc::new(Vec::new()), false, None);
+        registry.update(plain, |status| status.awaiting_step = Some(0));
+        assert!(registry.advance(plain).is_err());
+    }
+
+    #[tokio::test]
+    async fn exclusion_group_rejects_queues_or_cancels() {
+        let controller = controller(serde_json::json!([
+            { "name": "暖场", "exclusion_group": "stage",
+              "nodes": [{ "id": 1, "value": 1, "delay": 200 }] },
+            { "name": "抢占", "exclusion_group": "stage",
+              "nodes": [{ "id": 2, "value": 1 }] },
+            { "name": "排队", "exclusion_group": "stage", "on_conflict": "queue",
+              "nodes": [{ "id": 2, "value": 5 }] },
+            { "name": "接管", "exclusion_group": "stage", "on_conflict": "cancel-previous",
+              "nodes": [{ "id": 3, "value": 9 }] },
+            { "name": "独立", "nodes": [{ "id": 3, "value": 4 }] }
+        ]))
+        .await;
+        let mut rx = controller.subscribe_events();
+
+        // reject：同组执行中时立即报错，其他组不受影响
+        let warmup = controller.start_scene("暖场", false).await.unwrap();
+        assert!(controller.start_scene("抢占", false).await.is_err());
+        let other = controller.start_scene("独立", false).await.unwrap();
+        assert!(finished(&mut rx, other).await.success);
+
+        // queue：等待同组场景结束后再执行
+        let queued = controller.start_scene("排队", false).await.unwrap();
+        assert!(controller.scene_run(queued).unwrap().queued);
+        assert_eq!(value(&controller, 2).await, 0);
+        assert!(finished(&mut rx, warmup).await.success);
+        assert!(finished(&mut rx, queued).await.success);
+        assert_eq!(value(&controller, 2).await, 5);
+
+        // cancel-previous：取消同组执行中的场景，被取消的步骤不写入
+        controller.write_node(1, 0).await.unwrap();
+        let warmup = controller.start_scene("暖场", false).await.unwrap();
+        let takeover = controller.start_scene("接管", false).await.unwrap();
+        let cancelled = finished(&mut rx, warmup).await;
+        assert!(cancelled.cancelled && !cancelled.success);
+        assert!(finished(&mut rx, takeover).await.success);
+        assert_eq!(value(&controller, 3).await, 9);
+        assert_eq!(value(&controller, 1).await, 0);
+
+        controller.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn failed_steps_follow_on_error_policy() {
+        let controller = controller(serde_json::json!([
+            { "name": "恢复", "nodes": [
+                { "id": 9, "value": 1, "on_error": { "retry": 2 } },
+                { "id": 9, "value": 1, "on_error": "continue" },
+                { "id": 1, "value": 11 },
+                { "id": 9, "value": 1, "on_error": { "jump": "收尾" } },
+                { "id": 2, "value": 22 },
+                { "id": 3, "value": 33, "label": "收尾" }
+            ] },
+            { "name": "中止", "nodes": [
+                { "id": 9, "value": 1, "on_error": "abort" },
+                { "id": 1, "value": 44 }
+            ] }
+        ]))
+        .await;
+        let mut rx = controller.subscribe_events();
+
+        // retry 共尝试 n+1 次后继续；continue 继续下一步；jump 跳过中间步骤
+        let run_id = controller.start_scene("恢复", false).await.unwrap();
+        let result = finished(&mut rx, run_id).await;
+        assert!(!result.success && !result.aborted);
+        let failed: Vec<(usize, u32)> = result
+            .failed_steps
+            .iter()
+            .map(|f| (f.step, f.attempts))
+            .collect();
+        assert_eq!(failed, vec![(0, 3), (1, 1), (3, 1)]);
+        assert_eq!(writes(&controller, 9), 5);
+        assert_eq!(value(&controller, 1).await, 11);
+        assert_eq!(writes(&controller, 2), 0);
+        assert_eq!(value(&controller, 3).await, 33);
+
+        // abort：不再执行后续步骤
+        let run_id = controller.start_scene("中止", false).await.unwrap();
+        let result = finished(&mut rx, run_id).await;
+        assert!(result.aborted && !result.success);
+        assert_eq!(result.failed_steps.len(), 1);
+        assert_eq!(value(&controller, 1).await, 11);
+
+        controller.shutdown().await;
     }
 }
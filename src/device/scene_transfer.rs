@@ -0,0 +1,311 @@
+//! 场景导入 / 导出
+//!
+//! 场景包是可移植的 JSON 文件：除场景本身外附带场景引用节点的别名等信息。
+//! 导入到另一套系统时先预览节点映射（按别名自动匹配、按 ID 给出建议），
+//! 确认后再按映射改写场景中的节点 ID。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use utoipa::ToSchema;
+
+use crate::config::{NodeConfig, SceneConfig, SceneStepType};
+use crate::utils::{time, DeviceError, Result};
+
+/// 场景包格式标识
+pub const SCENE_PACKAGE_FORMAT: &str = "dm-rust-scenes";
+
+/// 场景包结构版本，格式发生不兼容变化时递增
+pub const SCENE_PACKAGE_VERSION: u32 = 1;
+
+/// 场景包
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScenePackage {
+    /// 格式标识，固定为 `dm-rust-scenes`
+    pub format: String,
+    /// 结构版本
+    pub version: u32,
+    /// 导出时间（UTC RFC3339）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<String>,
+    /// 场景列表（与配置文件中的 scenes 结构相同）
+    #[schema(value_type = Vec<Object>)]
+    pub scenes: Vec<SceneConfig>,
+    /// 场景引用的节点信息（源系统）
+    pub nodes: Vec<PortableNode>,
+}
+
+/// 场景包中的节点信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PortableNode {
+    /// 源系统中的节点全局 ID
+    pub id: u32,
+    /// 节点别名（导入时据此匹配目标系统节点）
+    pub alias: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+/// 节点匹配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// 请求中手动指定
+    Manual,
+    /// 别名唯一匹配（自动应用）
+    Alias,
+    /// 仅 ID 相同、别名不同（仅作建议，需手动确认）
+    Id,
+    /// 未找到匹配
+    None,
+}
+
+/// 单个节点的映射结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NodeMapping {
+    /// 源系统节点 ID
+    pub id: u32,
+    /// 源系统节点别名（场景包未附带时为空）
+    pub alias: String,
+    /// 目标系统节点 ID
+    pub target_id: Option<u32>,
+    /// 目标系统节点别名
+    pub target_alias: Option<String>,
+    pub matched_by: MatchKind,
+}
+
+impl NodeMapping {
+    /// 导入时是否可直接使用（手动指定或别名匹配）
+    pub fn is_resolved(&self) -> bool {
+        matches!(self.matched_by, MatchKind::Manual | MatchKind::Alias)
+    }
+}
+
+/// 导出场景，`names` 为空时导出全部场景
+pub fn export(
+    scenes: &[SceneConfig],
+    nodes: &[NodeConfig],
+    names: &[String],
+) -> Result<ScenePackage> {
+    let selected: Vec<SceneConfig> = if names.is_empty() {
+        scenes.to_vec()
+    } else {
+        names
+            .iter()
+            .map(|name| {
+                scenes
+                    .iter()
+                    .find(|s| &s.name == name)
+                    .cloned()
+                    .ok_or_else(|| DeviceError::Other(format!("场景 '{}' 不存在", name)))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let nodes = referenced_ids(&selected)
+        .into_iter()
+        .filter_map(|id| nodes.iter().find(|n| n.global_id == id))
+        .map(|n| PortableNode {
+            id: n.global_id,
+            alias: n.alias.clone(),
+            category: n.category.clone(),
+        })
+        .collect();
+
+    Ok(ScenePackage {
+        format: SCENE_PACKAGE_FORMAT.to_string(),
+        version: SCENE_PACKAGE_VERSION,
+        exported_at: Some(time::now_rfc3339()),
+        scenes: selected,
+        nodes,
+    })
+}
+
+/// 计算场景包中每个引用节点到目标系统节点的映射
+///
+/// `manual` 为手动指定的映射（源 ID → 目标 ID），优先于自动匹配。
+pub fn plan(
+    package: &ScenePackage,
+    targets: &[NodeConfig],
+    manual: &HashMap<u32, u32>,
+) -> Result<Vec<NodeMapping>> {
+    check_format(package)?;
+
+    referenced_ids(&package.scenes)
+        .into_iter()
+        .map(|id| {
+            let alias = package
+                .nodes
+                .iter()
+                .find(|n| n.id == id)
+                .map(|n| n.alias.clone())
+                .unwrap_or_default();
+
+            let (target, matched_by) = if let Some(target_id) = manual.get(&id) {
+                let target = targets
+                    .iter()
+                    .find(|n| n.global_id == *target_id)
+                    .ok_or_else(|| {
+                        DeviceError::DeviceNotFound(format!(
+                            "节点 {} 映射的目标节点 {}",
+                            id, target_id
+                        ))
+                    })?;
+                (Some(target), MatchKind::Manual)
+            } else {
+                let by_alias: Vec<&NodeConfig> = targets
+                    .iter()
+                    .filter(|n| !alias.is_empty() && n.alias == alias)
+                    .collect();
+                match by_alias.as_slice() {
+                    [only] => (Some(*only), MatchKind::Alias),
+                    _ => match targets.iter().find(|n| n.global_id == id) {
+                        Some(target) => (Some(target), MatchKind::Id),
+                        None => (None, MatchKind::None),
+                    },
+                }
+            };
+
+            Ok(NodeMapping {
+                id,
+                alias,
+                target_id: target.map(|n| n.global_id),
+                target_alias: target.map(|n| n.alias.clone()),
+                matched_by,
+            })
+        })
+        .collect()
+}
+
+/// 按映射改写场景中的节点 ID 并应用重命名
+///
+/// 存在未确认的节点映射，或（未允许覆盖时）与 `existing` 中的场景重名时返回错误。
+pub fn apply(
+    package: &ScenePackage,
+    mappings: &[NodeMapping],
+    rename: &HashMap<String, String>,
+    existing: &[String],
+    overwrite: bool,
+) -> Result<Vec<SceneConfig>> {
+    let unresolved: Vec<String> = mappings
+        .iter()
+        .filter(|m| !m.is_resolved())
+        .map(|m| format!("{} ({})", m.id, m.alias))
+        .collect();
+    if !unresolved.is_empty() {
+        return Err(DeviceError::ConfigError(format!(
+            "以下节点未能映射到本系统，请在 node_map 中指定: {}",
+            unresolved.join(", ")
+        )));
+    }
+
+    let id_map: HashMap<u32, u32> = mappings
+        .iter()
+        .filter_map(|m| m.target_id.map(|target| (m.id, target)))
+        .collect();
+
+    let mut scenes = package.scenes.clone();
+    for scene in scenes.iter_mut() {
+        if let Some(name) = rename.get(&scene.name) {
+            scene.name = name.clone();
+        }
+        if !overwrite && existing.contains(&scene.name) {
+            return Err(DeviceError::ConfigError(format!(
+                "场景 '{}' 已存在，请重命名或允许覆盖",
+                scene.name
+            )));
+        }
+        for step in scene.nodes.iter_mut() {
+            if let Some(nodes) = step.nodes.as_mut() {
+                nodes.iter_mut().for_each(|id| *id = id_map[id]);
+            }
+            let single = match step.step_type {
+                SceneStepType::Playlist | SceneStepType::Profile => false,
+                SceneStepType::StaggerGroup => step.nodes.is_none(),
+                _ => true,
+            };
+            if single {
+                step.id = id_map[&step.id];
+            }
+        }
+    }
+
+    let mut names = BTreeSet::new();
+    if let Some(scene) = scenes.iter().find(|s| !names.insert(s.name.as_str())) {
+        return Err(DeviceError::ConfigError(format!(
+            "导入的场景名称重复: '{}'",
+            scene.name
+        )));
+    }
+
+    Ok(scenes)
+}
+
+fn check_format(package: &ScenePackage) -> Result<()> {
+    if package.format != SCENE_PACKAGE_FORMAT {
+        return Err(DeviceError::ConfigError(format!(
+            "不是场景包文件 (format: '{}')",
+            package.format
+        )));
+    }
+    if package.version > SCENE_PACKAGE_VERSION {
+        return Err(DeviceError::ConfigError(format!(
+            "场景包版本 {} 高于本系统支持的版本 {}",
+            package.version, SCENE_PACKAGE_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// 场景引用的全部节点 ID（去重、升序）
+fn referenced_ids(scenes: &[SceneConfig]) -> BTreeSet<u32> {
+    scenes
+        .iter()
+        .flat_map(|s| s.nodes.iter().flat_map(|n| n.targets()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(global_id: u32, alias: &str) -> NodeConfig {
+        serde_json::from_value(serde_json::json!({
+            "global_id": global_id,
+            "channel_id": 1,
+            "id": global_id,
+            "alias": alias,
+        }))
+        .unwrap()
+    }
+
+    fn scene(name: &str, ids: &[u32]) -> SceneConfig {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "nodes": ids.iter().map(|id| serde_json::json!({"id": id, "value": 1})).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn remaps_nodes_by_alias_and_manual_mapping() {
+        let source = [node(1, "主灯"), node(2, "幕布"), node(3, "投影")];
+        let package = export(&[scene("开场", &[1, 2, 3])], &source, &[]).unwrap();
+
+        let targets = [node(10, "主灯"), node(2, "幕布电机"), node(30, "投影机")];
+        let mappings = plan(&package, &targets, &HashMap::new()).unwrap();
+        let kinds: Vec<MatchKind> = mappings.iter().map(|m| m.matched_by).collect();
+        assert_eq!(kinds, [MatchKind::Alias, MatchKind::Id, MatchKind::None]);
+        // ID 建议不会被自动采用
+        assert!(apply(&package, &mappings, &HashMap::new(), &[], false).is_err());
+
+        let manual = HashMap::from([(2, 2), (3, 30)]);
+        let mappings = plan(&package, &targets, &manual).unwrap();
+        let rename = HashMap::from([("开场".to_string(), "开场-巡演".to_string())]);
+        let existing = ["开场".to_string()];
+        let scenes = apply(&package, &mappings, &rename, &existing, false).unwrap();
+
+        assert_eq!(scenes[0].name, "开场-巡演");
+        let ids: Vec<u32> = scenes[0].nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, [10, 2, 30]);
+    }
+}
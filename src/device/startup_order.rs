@@ -0,0 +1,163 @@
+//! 通道启动顺序 - 按启动依赖（`startup.after`）拓扑排序
+//!
+//! 依赖缺失（未配置或未启用）时忽略该依赖；存在循环依赖时按配置顺序打破循环，
+//! 均记录为违例，启动时等待依赖超时或依赖初始化失败同样记入违例。
+
+use serde::Serialize;
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+use crate::config::ChannelConfig;
+
+/// 启动顺序报告
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct StartupOrderReport {
+    /// 计算出的启动顺序（通道 ID）
+    pub order: Vec<u32>,
+    /// 各通道启动情况（按启动顺序）
+    pub channels: Vec<ChannelStartup>,
+    /// 违反启动依赖的情况
+    pub violations: Vec<StartupViolation>,
+}
+
+/// 单个通道的启动情况
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChannelStartup {
+    pub channel_id: u32,
+    /// 配置的启动依赖
+    pub after: Vec<u32>,
+    /// 是否初始化成功
+    pub started: bool,
+    /// 等待依赖就绪的耗时（毫秒）
+    pub waited_ms: u64,
+}
+
+/// 启动依赖违例
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StartupViolation {
+    pub channel_id: u32,
+    pub kind: StartupViolationKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupViolationKind {
+    /// 依赖的通道未配置或未启用
+    MissingDependency,
+    /// 循环依赖
+    Cycle,
+    /// 依赖的通道初始化失败
+    DependencyFailed,
+    /// 等待依赖就绪超时
+    Timeout,
+}
+
+impl StartupViolation {
+    pub(crate) fn new(channel_id: u32, kind: StartupViolationKind, message: String) -> Self {
+        Self {
+            channel_id,
+            kind,
+            message,
+        }
+    }
+}
+
+/// 通道的启动依赖
+pub(crate) fn dependencies(config: &ChannelConfig) -> &[u32] {
+    config
+        .startup
+        .as_ref()
+        .map(|s| s.after.as_slice())
+        .unwrap_or_default()
+}
+
+/// 计算启动顺序（只包含传入的通道），无依赖约束时保持配置顺序
+pub(crate) fn plan(configs: &[&ChannelConfig]) -> (Vec<u32>, Vec<StartupViolation>) {
+    let known: HashSet<u32> = configs.iter().map(|c| c.channel_id).collect();
+    let mut violations = Vec::new();
+
+    for config in configs {
+        for dep in dependencies(config) {
+            if !known.contains(dep) {
+                violations.push(StartupViolation::new(
+                    config.channel_id,
+                    StartupViolationKind::MissingDependency,
+                    format!("依赖的通道 {} 未配置或未启用，已忽略", dep),
+                ));
+            }
+        }
+    }
+
+    let mut order = Vec::with_capacity(configs.len());
+    let mut placed = HashSet::new();
+    let mut pending: Vec<&ChannelConfig> = configs.to_vec();
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|c| {
+            dependencies(c)
+                .iter()
+                .all(|dep| placed.contains(dep) || !known.contains(dep))
+        });
+        let index = match ready {
+            Some(index) => index,
+            None => {
+                // 剩余通道互相等待：按配置顺序取第一个打破循环
+                let config = pending[0];
+                let waiting: Vec<String> = dependencies(config)
+                    .iter()
+                    .filter(|dep| known.contains(dep) && !placed.contains(*dep))
+                    .map(u32::to_string)
+                    .collect();
+                violations.push(StartupViolation::new(
+                    config.channel_id,
+                    StartupViolationKind::Cycle,
+                    format!("与通道 {} 存在循环依赖，按配置顺序启动", waiting.join(", ")),
+                ));
+                0
+            }
+        };
+        let config = pending.remove(index);
+        placed.insert(config.channel_id);
+        order.push(config.channel_id);
+    }
+    (order, violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(channel_id: u32, after: &[u32]) -> ChannelConfig {
+        serde_json::from_value(serde_json::json!({
+            "channel_id": channel_id,
+            "enable": true,
+            "statute": "mock",
+            "startup": { "after": after }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn orders_by_dependencies_and_reports_violations() {
+        let configs = [
+            channel(1, &[3]),
+            channel(2, &[]),
+            channel(3, &[2, 9]),
+            channel(4, &[5]),
+            channel(5, &[4]),
+        ];
+        let refs: Vec<&ChannelConfig> = configs.iter().collect();
+        let (order, violations) = plan(&refs);
+
+        assert_eq!(order, vec![2, 3, 1, 4, 5]);
+        let kinds: Vec<(u32, StartupViolationKind)> =
+            violations.iter().map(|v| (v.channel_id, v.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (3, StartupViolationKind::MissingDependency),
+                (4, StartupViolationKind::Cycle),
+            ]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! 场景与节点写入执行统计
+//!
+//! - 场景：执行次数、失败次数、取消次数、最近执行时间与结果、平均耗时、失败率
+//! - 节点：写入次数、失败次数、成功率、最近写入时间与最近一次错误
+//!
+//! 统计只保存在内存中，重启或调用重置接口后从零开始，`since` 为统计起点。
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::RwLock;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use super::SceneRunResult;
+use crate::utils::time;
+use crate::utils::Result;
+
+/// 单个场景的累计数据
+#[derive(Debug, Clone, Default)]
+struct SceneCounters {
+    runs: u64,
+    failures: u64,
+    cancelled: u64,
+    total_duration: Duration,
+    last_run_at: Option<String>,
+    last_duration: Duration,
+    last_success: Option<bool>,
+}
+
+/// 单个节点的累计数据
+#[derive(Debug, Clone, Default)]
+struct NodeCounters {
+    writes: u64,
+    failures: u64,
+    last_write_at: Option<String>,
+    last_error: Option<String>,
+}
+
+/// 场景执行统计
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SceneStats {
+    pub scene: String,
+    /// 执行次数（不含排队中被取消的执行）
+    pub runs: u64,
+    /// 失败次数（有步骤失败或被中止）
+    pub failures: u64,
+    /// 取消次数（含排队中被取消）
+    pub cancelled: u64,
+    /// 失败率（失败次数 / 执行次数，未执行时为 0）
+    pub failure_rate: f64,
+    /// 平均耗时（毫秒）
+    pub avg_duration_ms: u64,
+    /// 最近一次耗时（毫秒）
+    pub last_duration_ms: u64,
+    /// 最近一次结束时间（RFC 3339）
+    pub last_run_at: Option<String>,
+    /// 最近一次是否成功
+    pub last_success: Option<bool>,
+}
+
+/// 节点写入统计
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NodeWriteStats {
+    pub global_id: u32,
+    /// 下发到设备的写入次数
+    pub writes: u64,
+    /// 失败次数
+    pub failures: u64,
+    /// 成功率（成功次数 / 写入次数）
+    pub success_rate: f64,
+    /// 最近一次写入时间（RFC 3339）
+    pub last_write_at: Option<String>,
+    /// 最近一次失败原因
+    pub last_error: Option<String>,
+}
+
+/// 执行统计报告
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExecutionStatsReport {
+    /// 统计起点（启动或最近一次重置，RFC 3339）
+    pub since: String,
+    /// 场景统计（按名称排序）
+    pub scenes: Vec<SceneStats>,
+    /// 节点写入统计（按 global_id 排序）
+    pub nodes: Vec<NodeWriteStats>,
+}
+
+/// 执行统计收集器
+pub struct ExecutionStats {
+    since: RwLock<String>,
+    scenes: DashMap<String, SceneCounters>,
+    nodes: DashMap<u32, NodeCounters>,
+}
+
+impl Default for ExecutionStats {
+    fn default() -> Self {
+        Self {
+            since: RwLock::new(time::now_rfc3339()),
+            scenes: DashMap::new(),
+            nodes: DashMap::new(),
+        }
+    }
+}
+
+impl ExecutionStats {
+    /// 记录一次场景执行结束（`elapsed` 为 `None` 表示在排队中被取消，未实际执行）
+    pub fn record_scene(&self, result: &SceneRunResult, elapsed: Option<Duration>) {
+        let mut counters = self.scenes.entry(result.scene.clone()).or_default();
+        if result.cancelled {
+            counters.cancelled += 1;
+        }
+        let Some(elapsed) = elapsed else {
+            return;
+        };
+        counters.runs += 1;
+        if !result.success && !result.cancelled {
+            counters.failures += 1;
+        }
+        counters.total_duration += elapsed;
+        counters.last_duration = elapsed;
+        counters.last_run_at = Some(time::now_rfc3339());
+        counters.last_success = Some(result.success);
+    }
+
+    /// 记录一次节点写入结果
+    pub fn record_write<T>(&self, global_id: u32, result: &Result<T>) {
+        let mut counters = self.nodes.entry(global_id).or_default();
+        counters.writes += 1;
+        counters.last_write_at = Some(time::now_rfc3339());
+        if let Err(e) = result {
+            counters.failures += 1;
+            counters.last_error = Some(e.to_string());
+        }
+    }
+
+    /// 清空全部统计
+    pub fn reset(&self) {
+        self.scenes.clear();
+        self.nodes.clear();
+        *self.since.write().unwrap() = time::now_rfc3339();
+    }
+
+    pub fn report(&self) -> ExecutionStatsReport {
+        let mut scenes: Vec<SceneStats> = self
+            .scenes
+            .iter()
+            .map(|entry| {
+                let c = entry.value();
+                SceneStats {
+                    scene: entry.key().clone(),
+                    runs: c.runs,
+                    failures: c.failures,
+                    cancelled: c.cancelled,
+                    failure_rate: ratio(c.failures, c.runs),
+                    avg_duration_ms: c
+                        .total_duration
+                        .as_millis()
+                        .checked_div(c.runs as u128)
+                        .unwrap_or(0) as u64,
+                    last_duration_ms: c.last_duration.as_millis() as u64,
+                    last_run_at: c.last_run_at.clone(),
+                    last_success: c.last_success,
+                }
+            })
+            .collect();
+        scenes.sort_by(|a, b| a.scene.cmp(&b.scene));
+
+        let mut nodes: Vec<NodeWriteStats> = self
+            .nodes
+            .iter()
+            .map(|entry| {
+                let c = entry.value();
+                NodeWriteStats {
+                    global_id: *entry.key(),
+                    writes: c.writes,
+                    failures: c.failures,
+                    success_rate: ratio(c.writes - c.failures, c.writes),
+                    last_write_at: c.last_write_at.clone(),
+                    last_error: c.last_error.clone(),
+                }
+            })
+            .collect();
+        nodes.sort_by_key(|n| n.global_id);
+
+        ExecutionStatsReport {
+            since: self.since.read().unwrap().clone(),
+            scenes,
+            nodes,
+        }
+    }
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::DeviceError;
+
+    #[test]
+    fn aggregates_scene_runs_and_node_writes() {
+        let stats = ExecutionStats::default();
+        let mut result = SceneRunResult {
+            scene: "开馆".into(),
+            success: true,
+            ..Default::default()
+        };
+        stats.record_scene(&result, Some(Duration::from_millis(100)));
+        result.success = false;
+        stats.record_scene(&result, Some(Duration::from_millis(300)));
+        result.cancelled = true;
+        stats.record_scene(&result, None);
+
+        stats.record_write(7, &Ok(()));
+        stats.record_write::<()>(7, &Err(DeviceError::Timeout));
+
+        let report = stats.report();
+        let scene = &report.scenes[0];
+        assert_eq!((scene.runs, scene.failures, scene.cancelled), (2, 1, 1));
+        assert_eq!(scene.avg_duration_ms, 200);
+        assert_eq!(scene.failure_rate, 0.5);
+        assert_eq!(scene.last_success, Some(false));
+        let node = &report.nodes[0];
+        assert_eq!((node.writes, node.failures), (2, 1));
+        assert_eq!(node.success_rate, 0.5);
+        assert!(node.last_error.is_some());
+
+        stats.reset();
+        let report = stats.report();
+        assert!(report.scenes.is_empty() && report.nodes.is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! 通道状态缓存
+//!
+//! 查询全部通道状态时逐个访问设备，一个无响应的设备就会让接口等待数秒。缓存保存每个通道最近
+//! 一次查询到的状态，后台按间隔并发刷新（单个通道有超时），接口直接返回缓存并附带 `age_ms`；
+//! 尚无缓存的通道在查询时并发读取一次，从未查询成功的通道返回离线占位条目（`age_ms` 为 null）。
+//! 停用或处于计划离线时段的通道不访问设备，始终返回实时状态。
+
+use dashmap::DashMap;
+use futures::future::join_all;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use super::ChannelManager;
+use crate::config::StatusCacheConfig;
+use crate::utils::tasks::TaskRegistry;
+
+/// 通道状态缓存
+pub struct ChannelStatusCache {
+    channel_manager: Arc<ChannelManager>,
+    /// channel_id -> (状态条目, 上次成功刷新时间)
+    entries: DashMap<u32, (serde_json::Value, Option<Instant>)>,
+    timeout: Duration,
+}
+
+impl ChannelStatusCache {
+    pub fn new(channel_manager: Arc<ChannelManager>, config: &StatusCacheConfig) -> Self {
+        Self {
+            channel_manager,
+            entries: DashMap::new(),
+            timeout: Duration::from_millis(config.timeout_ms),
+        }
+    }
+
+    /// 启动后台刷新任务（启动后立即刷新一次）
+    pub fn spawn(self: &Arc<Self>, tasks: &TaskRegistry, interval: Duration) {
+        let cache = self.clone();
+        tasks.spawn("channel-status-cache", async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let ids = cache.channel_manager.channel_ids();
+                join_all(ids.into_iter().map(|id| cache.refresh(id))).await;
+            }
+        });
+    }
+
+    /// 查询并更新单个通道，失败或超时时保留上一次的缓存（没有缓存时写入离线占位条目）
+    async fn refresh(&self, channel_id: u32) {
+        let status = tokio::time::timeout(
+            self.timeout,
+            self.channel_manager.channel_status(channel_id),
+        )
+        .await;
+        match status {
+            Ok(Some(status)) => {
+                self.entries
+                    .insert(channel_id, (status, Some(Instant::now())));
+                return;
+            }
+            Ok(None) => {}
+            Err(_) => warn!(
+                "查询通道 {} 状态超时（{}ms）",
+                channel_id,
+                self.timeout.as_millis()
+            ),
+        }
+        self.entries.entry(channel_id).or_insert_with(|| {
+            let placeholder = serde_json::json!({
+                "channel_id": channel_id,
+                "statute": self.channel_manager.statute(channel_id),
+                "status": { "online": false },
+                "error": "尚未获取到通道状态",
+            });
+            (placeholder, None)
+        });
+    }
+
+    /// 全部通道的状态，每个条目附带 `age_ms`（距上次成功刷新的毫秒数）
+    pub async fn snapshot(&self) -> serde_json::Value {
+        let ids = self.channel_manager.channel_ids();
+
+        // 尚无缓存的通道并发查询一次
+        let missing: Vec<u32> = ids
+            .iter()
+            .copied()
+            .filter(|id| {
+                !self.channel_manager.is_status_local(*id) && !self.entries.contains_key(id)
+            })
+            .collect();
+        if !missing.is_empty() {
+            debug!("通道 {:?} 无状态缓存，实时查询", missing);
+            join_all(missing.into_iter().map(|id| self.refresh(id))).await;
+        }
+
+        let mut statuses = Vec::new();
+        for channel_id in ids {
+            let (mut status, refreshed_at) = if self.channel_manager.is_status_local(channel_id) {
+                match self.channel_manager.channel_status(channel_id).await {
+                    Some(status) => (status, Some(Instant::now())),
+                    None => continue,
+                }
+            } else {
+                match self.entries.get(&channel_id) {
+                    Some(entry) => entry.value().clone(),
+                    None => continue,
+                }
+            };
+            if let Some(object) = status.as_object_mut() {
+                object.insert(
+                    "age_ms".into(),
+                    refreshed_at.map(|t| t.elapsed().as_millis() as u64).into(),
+                );
+            }
+            statuses.push(status);
+        }
+        serde_json::Value::Array(statuses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ChannelConfig;
+    use tokio::sync::broadcast;
+
+    #[tokio::test]
+    async fn slow_channel_does_not_block_snapshot() {
+        let configs: Vec<ChannelConfig> = serde_json::from_value(serde_json::json!([
+            { "channel_id": 1, "enable": true, "statute": "mock", "arguments": {} },
+            { "channel_id": 2, "enable": true, "statute": "mock", "arguments": { "delay_ms": 5000 } }
+        ]))
+        .unwrap();
+        let (event_tx, _) = broadcast::channel(16);
+        let tasks = TaskRegistry::new();
+        let manager = Arc::new(
+            ChannelManager::new(&configs, &[], event_tx, &tasks)
+                .await
+                .unwrap(),
+        );
+        let cache = ChannelStatusCache::new(
+            manager.clone(),
+            &StatusCacheConfig {
+                enable: true,
+                refresh_interval_ms: 5000,
+                timeout_ms: 100,
+            },
+        );
+
+        let started = Instant::now();
+        let snapshot = cache.snapshot().await;
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let entry = |id: u64| {
+            snapshot
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|s| s["channel_id"] == id)
+                .unwrap()
+                .clone()
+        };
+        assert!(entry(1)["age_ms"].is_u64());
+        assert!(entry(2)["age_ms"].is_null() && entry(2)["error"].is_string());
+
+        manager.shutdown().await;
+    }
+}
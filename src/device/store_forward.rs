@@ -0,0 +1,314 @@
+//! 离线写入暂存（store-and-forward）
+//!
+//! 节点或其通道配置了 `store_and_forward` 时，因通道离线（连接失败、超时）而失败的写入
+//! 不返回错误，而是按节点暂存最新一次写入的值；通道恢复连接（`channel_connected` 事件）
+//! 或定期重试时按原写入流程下发，超过有效期仍未下发的写入作废。
+
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, info, warn};
+
+use super::{ChannelManager, DependencyResolver, DeviceController, DeviceEvent, NodeManager};
+use crate::config::{NodeConfig, StoreForwardConfig};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::DeviceError;
+
+/// 未收到连接恢复事件时的重试间隔（部分协议不上报连接状态）
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 暂存的写入
+#[derive(Debug, Clone)]
+pub struct QueuedWrite {
+    pub global_id: u32,
+    pub channel_id: u32,
+    pub value: i32,
+    /// 暂存原因（首次写入失败的错误）
+    pub reason: String,
+    /// 重试下发的次数
+    pub attempts: u32,
+    /// 最近一次重试失败的错误
+    pub last_error: Option<String>,
+    pub queued_at: Instant,
+    pub expires_at: Instant,
+}
+
+/// 写入失败是否因为通道离线（与备用驱动切换的判定一致）
+pub(crate) fn is_offline_error(error: &DeviceError) -> bool {
+    matches!(
+        error,
+        DeviceError::ConnectionError(_) | DeviceError::Timeout | DeviceError::Io(_)
+    )
+}
+
+/// 离线写入暂存表（每个节点只保留最新一次写入）
+pub struct StoreForwardQueue {
+    pending: DashMap<u32, QueuedWrite>,
+    event_tx: broadcast::Sender<DeviceEvent>,
+}
+
+impl StoreForwardQueue {
+    pub fn new(event_tx: broadcast::Sender<DeviceEvent>) -> Self {
+        Self {
+            pending: DashMap::new(),
+            event_tx,
+        }
+    }
+
+    /// 暂存写入，替换该节点之前暂存的值
+    pub fn enqueue(
+        &self,
+        node: &NodeConfig,
+        value: i32,
+        error: &DeviceError,
+        config: &StoreForwardConfig,
+    ) -> QueuedWrite {
+        let now = Instant::now();
+        let queued = QueuedWrite {
+            global_id: node.global_id,
+            channel_id: node.channel_id,
+            value,
+            reason: error.to_string(),
+            attempts: 0,
+            last_error: None,
+            queued_at: now,
+            expires_at: now + Duration::from_secs(config.expire),
+        };
+        info!(
+            "节点 {} 所在通道 {} 离线 ({})，写入值 {} 已暂存，{} 秒内通道恢复后下发",
+            node.global_id, node.channel_id, error, value, config.expire
+        );
+        self.pending.insert(node.global_id, queued.clone());
+        let _ = self.event_tx.send(DeviceEvent::NodeWriteQueued {
+            global_id: node.global_id,
+            channel_id: node.channel_id,
+            value,
+            reason: queued.reason.clone(),
+        });
+        queued
+    }
+
+    /// 丢弃节点暂存的写入（节点已有新的写入下发，或操作员取消）
+    pub fn discard(&self, global_id: u32) -> Option<QueuedWrite> {
+        self.pending.remove(&global_id).map(|(_, q)| q)
+    }
+
+    /// 节点暂存的写入
+    pub fn get(&self, global_id: u32) -> Option<QueuedWrite> {
+        self.pending.get(&global_id).map(|q| q.clone())
+    }
+
+    /// 全部未过期的暂存写入（按暂存时间排序）
+    pub fn list(&self) -> Vec<QueuedWrite> {
+        self.purge_expired();
+        let mut queued: Vec<QueuedWrite> = self.pending.iter().map(|q| q.clone()).collect();
+        queued.sort_by_key(|q| q.queued_at);
+        queued
+    }
+
+    /// 移除过期的写入并发送作废事件
+    fn purge_expired(&self) {
+        let now = Instant::now();
+        let expired: Vec<u32> = self
+            .pending
+            .iter()
+            .filter(|q| q.expires_at <= now)
+            .map(|q| q.global_id)
+            .collect();
+        for global_id in expired {
+            if let Some((_, q)) = self
+                .pending
+                .remove_if(&global_id, |_, q| q.expires_at <= now)
+            {
+                warn!(
+                    "节点 {} 暂存的写入值 {} 超过有效期仍未下发，已作废",
+                    q.global_id, q.value
+                );
+                let _ = self.event_tx.send(DeviceEvent::NodeWriteExpired {
+                    global_id: q.global_id,
+                    value: q.value,
+                });
+            }
+        }
+    }
+
+    /// 下发通道（未指定时为全部通道）暂存的写入
+    ///
+    /// 依赖未满足的写入保留到下次重试；下发期间节点有新的写入时以新写入为准。
+    async fn replay(
+        &self,
+        channel_id: Option<u32>,
+        channel_manager: &ChannelManager,
+        node_manager: &NodeManager,
+        dependency_resolver: &DependencyResolver,
+    ) {
+        self.purge_expired();
+        let due: Vec<QueuedWrite> = self
+            .pending
+            .iter()
+            .filter(|q| channel_id.is_none_or(|id| q.channel_id == id))
+            .filter(|q| channel_manager.is_available(q.channel_id))
+            .map(|q| q.clone())
+            .collect();
+
+        for queued in due {
+            let Some(node) = node_manager.get_node(queued.global_id) else {
+                self.discard(queued.global_id);
+                continue;
+            };
+            if node_manager.suppress_write(queued.global_id).is_some() {
+                info!("节点 {} 已强制，暂存的写入被抑制", queued.global_id);
+                self.remove_if_unchanged(&queued);
+                continue;
+            }
+            if let Some(dependencies) = &node.depend {
+                if !matches!(
+                    dependency_resolver.check_dependencies(dependencies).await,
+                    Ok(true)
+                ) {
+                    debug!("节点 {} 依赖未满足，暂存的写入继续等待", queued.global_id);
+                    continue;
+                }
+            }
+
+            let result = DeviceController::write_to_device(
+                channel_manager,
+                node_manager,
+                &node,
+                queued.value.into(),
+            )
+            .await;
+            match result {
+                Ok(()) => {
+                    if self.remove_if_unchanged(&queued) {
+                        info!(
+                            "节点 {} 暂存的写入值 {} 已下发",
+                            queued.global_id, queued.value
+                        );
+                        let _ = self.event_tx.send(DeviceEvent::NodeWriteReplayed {
+                            global_id: queued.global_id,
+                            value: queued.value,
+                        });
+                    }
+                }
+                Err(e) => {
+                    debug!("节点 {} 暂存的写入下发失败: {}", queued.global_id, e);
+                    if let Some(mut entry) = self.pending.get_mut(&queued.global_id) {
+                        if entry.queued_at == queued.queued_at {
+                            entry.attempts += 1;
+                            entry.last_error = Some(e.to_string());
+                        }
+                    }
+                    // 非离线错误（如值超出范围）重试也不会成功
+                    if !is_offline_error(&e) && self.remove_if_unchanged(&queued) {
+                        warn!(
+                            "节点 {} 暂存的写入值 {} 下发失败，已放弃: {}",
+                            queued.global_id, queued.value, e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// 暂存项仍是下发时取出的那一次写入时移除
+    fn remove_if_unchanged(&self, queued: &QueuedWrite) -> bool {
+        self.pending
+            .remove_if(&queued.global_id, |_, q| q.queued_at == queued.queued_at)
+            .is_some()
+    }
+
+    /// 启动下发任务：通道恢复连接时立即下发，并定期重试与清理过期写入
+    pub fn spawn(
+        self: &Arc<Self>,
+        tasks: &TaskRegistry,
+        channel_manager: Arc<ChannelManager>,
+        node_manager: Arc<NodeManager>,
+        dependency_resolver: Arc<DependencyResolver>,
+        mut events: broadcast::Receiver<DeviceEvent>,
+    ) {
+        let queue = self.clone();
+        tasks.spawn("store_forward", async move {
+            let mut retry = tokio::time::interval(RETRY_INTERVAL);
+            retry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                let channel_id = tokio::select! {
+                    _ = retry.tick() => None,
+                    event = events.recv() => match event {
+                        Ok(DeviceEvent::ChannelConnected { channel_id }) => Some(channel_id),
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(n)) => {
+                            super::record_lagged_events(n);
+                            None
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+                if queue.pending.is_empty() {
+                    continue;
+                }
+                queue
+                    .replay(
+                        channel_id,
+                        &channel_manager,
+                        &node_manager,
+                        &dependency_resolver,
+                    )
+                    .await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_latest_write_per_node_and_expires_stale_ones() {
+        let node: NodeConfig = serde_json::from_value(serde_json::json!({
+            "global_id": 1,
+            "channel_id": 2,
+            "id": 1,
+            "alias": "主灯",
+        }))
+        .unwrap();
+        let (tx, mut rx) = broadcast::channel(16);
+        let queue = StoreForwardQueue::new(tx);
+        let offline = DeviceError::ConnectionError("连接被拒绝".to_string());
+        assert!(is_offline_error(&offline));
+        assert!(!is_offline_error(
+            &DeviceError::DependencyNotMet(Vec::new())
+        ));
+
+        let config = StoreForwardConfig {
+            enable: true,
+            expire: 60,
+        };
+        queue.enqueue(&node, 1, &offline, &config);
+        queue.enqueue(&node, 0, &offline, &config);
+        let queued = queue.list();
+        assert_eq!(queued.len(), 1);
+        assert_eq!((queued[0].channel_id, queued[0].value), (2, 0));
+
+        // 有效期为 0 的写入在下一次检查时作废
+        let config = StoreForwardConfig {
+            enable: true,
+            expire: 0,
+        };
+        queue.enqueue(&node, 1, &offline, &config);
+        assert!(queue.list().is_empty());
+        let mut expired = false;
+        while let Ok(event) = rx.try_recv() {
+            expired |= matches!(
+                event,
+                DeviceEvent::NodeWriteExpired {
+                    global_id: 1,
+                    value: 1
+                }
+            );
+        }
+        assert!(expired);
+    }
+}
@@ -6,8 +6,11 @@ use tokio::sync::{broadcast, RwLock};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use super::{ChannelManager, DependencyResolver, DeviceEvent, NodeManager};
+use super::{
+    ChannelManager, DependencyResolver, DeviceController, DeviceEvent, ExecutionStats, NodeManager,
+};
 use crate::config::{NodeConfig, TaskSettings};
+use crate::utils::tasks::TaskRegistry;
 use crate::utils::Result;
 
 /// 任务状态
@@ -32,6 +35,8 @@ pub struct Task {
     pub status: TaskStatus,
     pub created_at: Instant,
     pub retry_count: u32,
+    /// 因通道计划离线而等待的累计时长（不计入超时）
+    pub schedule_wait: Duration,
     pub node_config: NodeConfig,
 }
 
@@ -47,6 +52,7 @@ impl Task {
             status: TaskStatus::Pending,
             created_at: Instant::now(),
             retry_count: 0,
+            schedule_wait: Duration::ZERO,
             node_config: node,
         }
     }
@@ -60,6 +66,7 @@ pub struct TaskScheduler {
     node_manager: Arc<NodeManager>,
     dependency_resolver: Arc<DependencyResolver>,
     event_tx: broadcast::Sender<DeviceEvent>,
+    stats: Arc<ExecutionStats>,
 }
 
 impl TaskScheduler {
@@ -70,6 +77,8 @@ impl TaskScheduler {
         node_manager: Arc<NodeManager>,
         dependency_resolver: Arc<DependencyResolver>,
         event_tx: broadcast::Sender<DeviceEvent>,
+        stats: Arc<ExecutionStats>,
+        tasks: &TaskRegistry,
     ) -> Self {
         let scheduler = Self {
             queue: Arc::new(RwLock::new(VecDeque::new())),
@@ -78,10 +87,11 @@ impl TaskScheduler {
             node_manager,
             dependency_resolver,
             event_tx,
+            stats,
         };
 
         // 启动后台调度循环
-        scheduler.start_scheduler_loop();
+        scheduler.start_scheduler_loop(tasks);
 
         scheduler
     }
@@ -98,15 +108,16 @@ impl TaskScheduler {
     }
 
     /// 启动调度循环
-    fn start_scheduler_loop(&self) {
+    fn start_scheduler_loop(&self, tasks: &TaskRegistry) {
         let queue = self.queue.clone();
         let settings = self.settings.clone();
         let channel_manager = self.channel_manager.clone();
         let node_manager = self.node_manager.clone();
         let dependency_resolver = self.dependency_resolver.clone();
         let event_tx = self.event_tx.clone();
+        let stats = self.stats.clone();
 
-        tokio::spawn(async move {
+        tasks.spawn("task_scheduler", async move {
             let check_interval = Duration::from_millis(settings.check_interval_ms);
             let timeout = Duration::from_millis(settings.timeout_ms);
 
@@ -122,7 +133,7 @@ impl TaskScheduler {
 
                 for (idx, task) in queue.iter_mut().enumerate() {
                     // 检查超时
-                    if task.created_at.elapsed() > timeout {
+                    if task.created_at.elapsed().saturating_sub(task.schedule_wait) > timeout {
                         warn!("任务 {} ({}) 超时", task.alias, task.id);
                         task.status = TaskStatus::Timeout;
                         completed_indices.push(idx);
@@ -147,44 +158,77 @@ impl TaskScheduler {
                         continue;
                     }
 
-                    // 检查依赖
-                    if let Some(dependencies) = &task.node_config.depend {
-                        match dependency_resolver.check_dependencies(dependencies).await {
-                            Ok(true) => {
-                                // 依赖满足，执行任务
-                                debug!("任务 {} 依赖已满足，开始执行", task.alias);
-                                task.status = TaskStatus::Executing;
-
-                                match channel_manager
-                                    .write(task.channel_id, task.device_id, task.value)
-                                    .await
-                                {
-                                    Ok(_) => {
-                                        info!("任务 {} ({}) 执行成功", task.alias, task.id);
-                                        task.status = TaskStatus::Completed;
-                                        node_manager.update_value(task.global_id, task.value);
-                                        completed_indices.push(idx);
+                    // 通道处于计划离线时段，继续等待
+                    if !channel_manager.is_available(task.channel_id) {
+                        task.schedule_wait += check_interval;
+                        continue;
+                    }
 
-                                        let _ = event_tx.send(DeviceEvent::TaskCompleted {
-                                            task_id: task.id.clone(),
-                                            success: true,
-                                        });
+                    // 检查依赖（无依赖的任务直接执行）
+                    let deps_result = match &task.node_config.depend {
+                        Some(dependencies) => {
+                            dependency_resolver.check_dependencies(dependencies).await
+                        }
+                        None => Ok(true),
+                    };
+                    match deps_result {
+                        Ok(true) => {
+                            // 依赖满足，执行任务
+                            debug!("任务 {} 依赖已满足，开始执行", task.alias);
+                            task.status = TaskStatus::Executing;
+
+                            // 节点被强制时不下发到设备
+                            let suppressed = node_manager.suppress_write(task.global_id).is_some();
+                            let write_result = if suppressed {
+                                info!("任务 {} 的节点已强制，写入被抑制", task.alias);
+                                Ok(())
+                            } else {
+                                let result = match DeviceController::to_device_value(
+                                    &task.node_config,
+                                    task.value.into(),
+                                ) {
+                                    Ok(device_value) => {
+                                        channel_manager
+                                            .write(
+                                                task.channel_id,
+                                                task.device_id,
+                                                device_value.round() as i32,
+                                            )
+                                            .await
                                     }
-                                    Err(e) => {
-                                        warn!("任务 {} 执行失败: {:?}", task.alias, e);
-                                        task.retry_count += 1;
-                                        task.status = TaskStatus::Pending;
+                                    Err(e) => Err(e),
+                                };
+                                stats.record_write(task.global_id, &result);
+                                result
+                            };
+                            match write_result {
+                                Ok(_) => {
+                                    info!("任务 {} ({}) 执行成功", task.alias, task.id);
+                                    task.status = TaskStatus::Completed;
+                                    if !suppressed {
+                                        node_manager.update_value(task.global_id, task.value);
                                     }
+                                    completed_indices.push(idx);
+
+                                    let _ = event_tx.send(DeviceEvent::TaskCompleted {
+                                        task_id: task.id.clone(),
+                                        success: true,
+                                    });
+                                }
+                                Err(e) => {
+                                    warn!("任务 {} 执行失败: {:?}", task.alias, e);
+                                    task.retry_count += 1;
+                                    task.status = TaskStatus::Pending;
                                 }
                             }
-                            Ok(false) => {
-                                // 依赖未满足，继续等待
-                                debug!("任务 {} 依赖未满足，继续等待", task.alias);
-                            }
-                            Err(e) => {
-                                warn!("任务 {} 依赖检查失败: {:?}", task.alias, e);
-                                task.retry_count += 1;
-                            }
+                        }
+                        Ok(false) => {
+                            // 依赖未满足，继续等待
+                            debug!("任务 {} 依赖未满足，继续等待", task.alias);
+                        }
+                        Err(e) => {
+                            warn!("任务 {} 依赖检查失败: {:?}", task.alias, e);
+                            task.retry_count += 1;
                         }
                     }
                 }
@@ -0,0 +1,348 @@
+//! 时序数据库导出 - 节点值变化批量写入 InfluxDB / TimescaleDB
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, OnceCell};
+use tracing::{debug, info, warn};
+
+use super::DeviceEvent;
+use crate::config::{NodeConfig, TelemetryBackend, TelemetryConfig};
+use crate::utils::{http, DeviceError, Result};
+
+/// 写入失败时最多缓存的批次数，超出后丢弃最早的样本
+const MAX_BUFFERED_BATCHES: usize = 20;
+
+/// 节点值样本
+#[derive(Debug, Clone)]
+pub(crate) struct Sample {
+    pub time: DateTime<Utc>,
+    pub global_id: u32,
+    pub channel_id: u32,
+    pub alias: String,
+    pub value: i32,
+    pub old_value: i32,
+}
+
+/// 导出目标
+#[async_trait]
+pub(crate) trait TelemetrySink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn write(&self, samples: &[Sample]) -> Result<()>;
+}
+
+/// 按配置创建导出目标（只校验配置，不连接数据库）
+pub(crate) fn sink_from_config(config: &TelemetryConfig) -> Result<Arc<dyn TelemetrySink>> {
+    let sink: Arc<dyn TelemetrySink> = match config.backend {
+        TelemetryBackend::Influxdb => Arc::new(InfluxDbSink::new(config)?),
+        TelemetryBackend::Timescale => Arc::new(TimescaleSink::new(config)?),
+    };
+    info!("时序数据库导出: {} {}", sink.name(), config.url);
+    Ok(sink)
+}
+
+/// 启动导出任务：监听节点状态变化，按批量大小或间隔写入
+pub(crate) fn spawn_exporter(
+    tasks: &crate::utils::tasks::TaskRegistry,
+    config: &TelemetryConfig,
+    nodes: &[NodeConfig],
+    sink: Arc<dyn TelemetrySink>,
+    mut event_rx: broadcast::Receiver<DeviceEvent>,
+) {
+    // global_id -> (channel_id, alias)
+    let exported: HashMap<u32, (u32, String)> = nodes
+        .iter()
+        .filter(|n| config.nodes.is_empty() || config.nodes.contains(&n.global_id))
+        .map(|n| (n.global_id, (n.channel_id, n.alias.clone())))
+        .collect();
+    let batch_size = config.batch_size.max(1);
+    let flush_interval = Duration::from_millis(config.flush_interval_ms.max(100));
+
+    tasks.spawn("telemetry-exporter", async move {
+        let mut buffer: Vec<Sample> = Vec::new();
+        let mut ticker = tokio::time::interval(flush_interval);
+
+        loop {
+            tokio::select! {
+                event = event_rx.recv() => match event {
+                    Ok(DeviceEvent::NodeStateChanged { global_id, old_value, new_value, forced: false }) => {
+                        let Some((channel_id, alias)) = exported.get(&global_id) else {
+                            continue;
+                        };
+                        buffer.push(Sample {
+                            time: Utc::now(),
+                            global_id,
+                            channel_id: *channel_id,
+                            alias: alias.clone(),
+                            value: new_value,
+                            old_value,
+                        });
+                        if buffer.len() >= batch_size {
+                            flush(sink.as_ref(), &mut buffer, batch_size).await;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        super::record_lagged_events(n);
+                        warn!("时序数据库导出任务落后，丢失 {} 个事件", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = ticker.tick() => flush(sink.as_ref(), &mut buffer, batch_size).await,
+            }
+        }
+    });
+}
+
+/// 分批写入缓存的样本；写入失败时保留样本等待下次重试
+async fn flush(sink: &dyn TelemetrySink, buffer: &mut Vec<Sample>, batch_size: usize) {
+    while !buffer.is_empty() {
+        let count = buffer.len().min(batch_size);
+        match sink.write(&buffer[..count]).await {
+            Ok(()) => {
+                debug!("{} 写入 {} 个样本", sink.name(), count);
+                buffer.drain(..count);
+            }
+            Err(e) => {
+                warn!("{} 写入失败: {}", sink.name(), e);
+                let limit = batch_size * MAX_BUFFERED_BATCHES;
+                if buffer.len() > limit {
+                    let dropped = buffer.len() - limit;
+                    buffer.drain(..dropped);
+                    warn!("{} 缓存已满，丢弃最早的 {} 个样本", sink.name(), dropped);
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// InfluxDB 行协议写入（2.x 使用 org / bucket / token，1.x 使用 database）
+struct InfluxDbSink {
+    client: reqwest::Client,
+    write_url: String,
+    token: Option<String>,
+    measurement: String,
+}
+
+impl InfluxDbSink {
+    fn new(config: &TelemetryConfig) -> Result<Self> {
+        let base = config.url.trim_end_matches('/');
+        let write_url = match (&config.bucket, &config.database) {
+            (Some(bucket), _) => {
+                let org = config
+                    .org
+                    .as_deref()
+                    .ok_or_else(|| DeviceError::ConfigError("telemetry.org 未配置".to_string()))?;
+                reqwest::Url::parse_with_params(
+                    &format!("{}/api/v2/write", base),
+                    &[("org", org), ("bucket", bucket), ("precision", "ms")],
+                )
+            }
+            (None, Some(database)) => reqwest::Url::parse_with_params(
+                &format!("{}/write", base),
+                &[("db", database.as_str()), ("precision", "ms")],
+            ),
+            (None, None) => {
+                return Err(DeviceError::ConfigError(
+                    "InfluxDB 导出需要配置 telemetry.bucket（2.x）或 telemetry.database（1.x）"
+                        .to_string(),
+                ))
+            }
+        }
+        .map_err(|e| DeviceError::ConfigError(format!("telemetry.url 无效: {}", e)))?;
+
+        Ok(Self {
+            client: http::build_client(&http::global_defaults())?,
+            write_url: write_url.to_string(),
+            token: config.token.clone(),
+            measurement: config.measurement.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl TelemetrySink for InfluxDbSink {
+    fn name(&self) -> &'static str {
+        "influxdb"
+    }
+
+    async fn write(&self, samples: &[Sample]) -> Result<()> {
+        let body = line_protocol(&self.measurement, samples);
+        let mut request = self.client.post(&self.write_url).body(body);
+        if let Some(ref token) = self.token {
+            request = request.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| DeviceError::ConnectionError(e.to_string()))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(DeviceError::ProtocolError(format!(
+                "HTTP {}: {}",
+                status,
+                text.trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 转义行协议中的特殊字符
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 生成 InfluxDB 行协议（毫秒精度），别名为空时省略 alias 标签
+fn line_protocol(measurement: &str, samples: &[Sample]) -> String {
+    let measurement = escape(measurement, &[',', ' ']);
+    samples
+        .iter()
+        .map(|s| {
+            let alias = if s.alias.is_empty() {
+                String::new()
+            } else {
+                format!(",alias={}", escape(&s.alias, &[',', '=', ' ']))
+            };
+            format!(
+                "{},global_id={},channel_id={}{} value={}i,old_value={}i {}",
+                measurement,
+                s.global_id,
+                s.channel_id,
+                alias,
+                s.value,
+                s.old_value,
+                s.time.timestamp_millis()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// TimescaleDB 写入：首次写入时建表并尝试转换为超表
+struct TimescaleSink {
+    pool: sqlx::PgPool,
+    table: String,
+    prepared: OnceCell<()>,
+}
+
+impl TimescaleSink {
+    fn new(config: &TelemetryConfig) -> Result<Self> {
+        let table = config.measurement.clone();
+        // 表名直接拼接进 SQL，只允许标识符字符
+        if table.is_empty() || !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(DeviceError::ConfigError(format!(
+                "telemetry.measurement 不是合法的表名: {}",
+                table
+            )));
+        }
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect_lazy(&config.url)
+            .map_err(|e| DeviceError::ConfigError(format!("telemetry.url 无效: {}", e)))?;
+        Ok(Self {
+            pool,
+            table,
+            prepared: OnceCell::new(),
+        })
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        self.prepared
+            .get_or_try_init(|| async {
+                sqlx::query(&format!(
+                    "CREATE TABLE IF NOT EXISTS {} (
+                        time TIMESTAMPTZ NOT NULL,
+                        global_id INTEGER NOT NULL,
+                        channel_id INTEGER NOT NULL,
+                        alias TEXT NOT NULL,
+                        value INTEGER NOT NULL,
+                        old_value INTEGER
+                    )",
+                    self.table
+                ))
+                .execute(&self.pool)
+                .await
+                .map_err(|e| DeviceError::Other(format!("建表失败: {}", e)))?;
+
+                let hypertable = format!(
+                    "SELECT create_hypertable('{}', 'time', if_not_exists => TRUE)",
+                    self.table
+                );
+                if let Err(e) = sqlx::query(&hypertable).execute(&self.pool).await {
+                    warn!(
+                        "{} 转换为超表失败（未安装 TimescaleDB 扩展时按普通表写入）: {}",
+                        self.table, e
+                    );
+                }
+                Ok::<_, DeviceError>(())
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TelemetrySink for TimescaleSink {
+    fn name(&self) -> &'static str {
+        "timescale"
+    }
+
+    async fn write(&self, samples: &[Sample]) -> Result<()> {
+        self.prepare().await?;
+
+        let mut query = sqlx::QueryBuilder::new(format!(
+            "INSERT INTO {} (time, global_id, channel_id, alias, value, old_value) ",
+            self.table
+        ));
+        query.push_values(samples, |mut row, s| {
+            row.push_bind(s.time)
+                .push_bind(s.global_id as i32)
+                .push_bind(s.channel_id as i32)
+                .push_bind(&s.alias)
+                .push_bind(s.value)
+                .push_bind(s.old_value);
+        });
+        query
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DeviceError::Other(format!("写入失败: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_protocol_escapes_tags() {
+        let sample = Sample {
+            time: DateTime::from_timestamp_millis(1_700_000_000_123).unwrap(),
+            global_id: 12,
+            channel_id: 2,
+            alias: "舞台灯 A,主=1".to_string(),
+            value: 1,
+            old_value: 0,
+        };
+        assert_eq!(
+            line_protocol("node state", &[sample.clone(), sample]).lines().next(),
+            Some(
+                "node\\ state,global_id=12,channel_id=2,alias=舞台灯\\ A\\,主\\=1 value=1i,old_value=0i 1700000000123"
+            )
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! 节点值变换链
+//!
+//! 变换按读取方向声明（设备原始值 → 逻辑值）；写入时逆序应用各步骤的反变换，
+//! 保证读写对称。
+
+use crate::config::TransformStep;
+use crate::utils::{DeviceError, Result};
+
+const EPSILON: f64 = 1e-9;
+
+/// 校验变换链配置
+pub fn validate(steps: &[TransformStep]) -> Result<()> {
+    for step in steps {
+        match step {
+            TransformStep::Scale { factor } if factor.abs() < EPSILON => {
+                return Err(DeviceError::ConfigError("scale 系数不能为 0".into()));
+            }
+            TransformStep::Clamp {
+                min: Some(min),
+                max: Some(max),
+            } if min > max => {
+                return Err(DeviceError::ConfigError(format!(
+                    "clamp 范围无效: min {} > max {}",
+                    min, max
+                )));
+            }
+            TransformStep::EnumMap { map } => {
+                for key in map.keys() {
+                    key.trim().parse::<f64>().map_err(|_| {
+                        DeviceError::ConfigError(format!("enum_map 键 '{}' 不是数值", key))
+                    })?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 读取方向：设备原始值 → 逻辑值
+pub fn from_device(steps: &[TransformStep], raw: f64) -> f64 {
+    steps.iter().fold(raw, |value, step| match step {
+        TransformStep::Scale { factor } => value * factor,
+        TransformStep::Offset { value: offset } => value + offset,
+        TransformStep::Clamp { min, max } => clamp(value, *min, *max),
+        TransformStep::EnumMap { map } => map
+            .iter()
+            .find(|(key, _)| matches(key, value))
+            .map(|(_, mapped)| *mapped)
+            .unwrap_or(value),
+        TransformStep::Invert { max } => max - value,
+    })
+}
+
+/// 写入方向：逻辑值 → 设备原始值
+pub fn to_device(steps: &[TransformStep], value: f64) -> Result<f64> {
+    steps.iter().rev().try_fold(value, |value, step| {
+        Ok(match step {
+            TransformStep::Scale { factor } => value / factor,
+            TransformStep::Offset { value: offset } => value - offset,
+            TransformStep::Clamp { min, max } => clamp(value, *min, *max),
+            TransformStep::EnumMap { map } => map
+                .iter()
+                .find(|(_, mapped)| (**mapped - value).abs() < EPSILON)
+                .and_then(|(key, _)| key.trim().parse::<f64>().ok())
+                .ok_or_else(|| DeviceError::Other(format!("值 {} 不在 enum_map 映射中", value)))?,
+            TransformStep::Invert { max } => max - value,
+        })
+    })
+}
+
+fn clamp(value: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    let value = min.map_or(value, |min| value.max(min));
+    max.map_or(value, |max| value.min(max))
+}
+
+fn matches(key: &str, value: f64) -> bool {
+    key.trim()
+        .parse::<f64>()
+        .map(|k| (k - value).abs() < EPSILON)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_scale_offset_roundtrip() {
+        let steps = vec![
+            TransformStep::Scale { factor: 0.1 },
+            TransformStep::Offset { value: -40.0 },
+        ];
+        assert!((from_device(&steps, 650.0) - 25.0).abs() < 1e-6);
+        assert!((to_device(&steps, 25.0).unwrap() - 650.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invert_and_enum_map() {
+        let invert = vec![TransformStep::Invert { max: 1.0 }];
+        assert_eq!(from_device(&invert, 1.0), 0.0);
+        assert_eq!(to_device(&invert, 1.0).unwrap(), 0.0);
+
+        let mut map = BTreeMap::new();
+        map.insert("3".to_string(), 1.0);
+        map.insert("5".to_string(), 2.0);
+        let steps = vec![TransformStep::EnumMap { map }];
+        assert_eq!(from_device(&steps, 5.0), 2.0);
+        assert_eq!(to_device(&steps, 1.0).unwrap(), 3.0);
+        assert!(to_device(&steps, 7.0).is_err());
+    }
+}
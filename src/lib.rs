@@ -1,11 +1,18 @@
 use anyhow::Result;
 use tracing::info;
 
+pub mod bridge;
 pub mod config;
+pub mod content_push;
 pub mod db;
 pub mod device;
+pub mod mqtt;
+pub mod playlist;
 pub mod protocols;
+pub mod resource_sync;
+pub mod self_check;
 pub mod service;
+pub mod session;
 pub mod utils;
 pub mod web;
 
@@ -37,6 +44,32 @@ pub struct Args {
     /// 服务控制命令 (start, stop, restart)
     #[arg(short = 's', long)]
     pub service: Option<String>,
+
+    /// 生成密码哈希（用于 auth.users 的 password_hash）后退出
+    #[arg(long, value_name = "PASSWORD")]
+    pub hash_password: Option<String>,
+
+    /// 自检：校验配置、数据库连接与目录权限后输出 JSON 报告并退出（全部通过时退出码为 0）
+    #[arg(long)]
+    pub check: bool,
+
+    /// 自检时连接每个启用的通道
+    #[arg(long, requires = "check")]
+    pub check_channels: bool,
+
+    /// 自检中数据库与通道连接的超时（毫秒）
+    #[arg(long, default_value_t = 3000, value_name = "MS")]
+    pub check_timeout_ms: u64,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// 子命令
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum Command {
+    /// 生成起步配置（Modbus PLC + PJLink 投影机 + 全开/全关场景）及模拟器模板
+    Init(config::wizard::InitArgs),
 }
 
 /// 启动核心应用 (加载配置, DB, WebServer, DeviceController)
@@ -45,7 +78,7 @@ pub async fn run_app(config_path: &str, log_level: &str) -> Result<()> {
     info!("配置文件: {}", config_path);
 
     // 加载配置
-    let cfg = config::load_config_from_file(config_path)?;
+    let mut cfg = config::load_config_from_file(config_path)?;
     info!("配置加载成功");
 
     // 初始化日志系统（使用配置文件中的日志配置，命令行参数作为默认值）
@@ -53,9 +86,9 @@ pub async fn run_app(config_path: &str, log_level: &str) -> Result<()> {
 
     info!("日志系统初始化完成");
 
-    // 初始化设备控制器
-    let device_controller = device::DeviceController::new(cfg.clone()).await?;
-    info!("设备控制器初始化成功");
+    // 初始化协议存储（需在通道创建之前）
+    protocols::storage::init_global_storage_from_config(&cfg.storage.clone().unwrap_or_default())
+        .await?;
 
     // 初始化数据库（可选）
     let database = if let Some(ref db_config) = cfg.database {
@@ -87,6 +120,33 @@ pub async fn run_app(config_path: &str, log_level: &str) -> Result<()> {
         None
     };
 
+    // 数据库配置源（可选）：用数据库中的通道/节点/场景替换配置文件中的内容
+    let config_store = match (
+        database.as_ref(),
+        cfg.database
+            .as_ref()
+            .and_then(|d| d.config_store.as_ref())
+            .filter(|s| s.enable),
+    ) {
+        (Some(db), Some(store_config)) => {
+            let store = db::ConfigStore::new(db.configs(), store_config);
+            match store.load_into(&mut cfg).await {
+                Ok(revision) => info!("已从数据库加载配置版本 {}", revision),
+                Err(e) => tracing::error!("从数据库加载配置失败，使用配置文件: {:?}", e),
+            }
+            Some(std::sync::Arc::new(store))
+        }
+        (None, Some(_)) => {
+            tracing::warn!("数据库不可用，使用配置文件中的通道/节点/场景配置");
+            None
+        }
+        _ => None,
+    };
+
+    // 初始化设备控制器
+    let device_controller = device::DeviceController::new(cfg.clone()).await?;
+    info!("设备控制器初始化成功");
+
     // 启动Web服务器（HTTP控制接口）
     let web_server = if let Some(db) = database {
         web::WebServer::with_database(
@@ -101,7 +161,8 @@ pub async fn run_app(config_path: &str, log_level: &str) -> Result<()> {
             config_path.to_string(),
             device_controller.clone(),
         )
-    };
+    }
+    .with_config_store(config_store);
 
     // 运行服务器 (这里会阻塞，直到 server 结束)
     if let Err(e) = web_server.run().await {
@@ -1,9 +1,12 @@
 use anyhow::Result;
 use clap::Parser;
-use dm_rust::{run_app, service, Args};
+use dm_rust::{run_app, service, Args, Command};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // 尽早安装 panic hook，启动阶段的 panic 也会生成崩溃报告
+    dm_rust::utils::crash::install_panic_hook();
+
     // 检查是否作为Windows服务运行（没有命令行参数时）
     #[cfg(windows)]
     {
@@ -18,6 +21,37 @@ async fn main() -> Result<()> {
     // 解析命令行参数
     let args = Args::parse();
 
+    if let Some(Command::Init(init)) = args.command {
+        if let Err(e) = dm_rust::config::wizard::run(init) {
+            eprintln!("错误: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(ref password) = args.hash_password {
+        match dm_rust::web::auth::hash_password(password) {
+            Ok(hash) => {
+                println!("{}", hash);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("错误: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if args.check {
+        let options = dm_rust::self_check::SelfCheckOptions {
+            connect_channels: args.check_channels,
+            timeout: std::time::Duration::from_millis(args.check_timeout_ms),
+        };
+        let report = dm_rust::self_check::run(&args.config, &options).await;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(if report.ok { 0 } else { 1 });
+    }
+
     // 处理服务管理命令
     if args.install {
         return service::install_service();
@@ -0,0 +1,203 @@
+//! Home Assistant MQTT 自动发现
+//!
+//! 每个节点发布一条保留的发现配置到 `<发现前缀>/<组件>/<node_id>/node_<global_id>/config`，
+//! 同一通道的节点归为一个设备。组件按节点分类映射，可在节点 metadata 中用 `ha_component` 覆盖：
+//!
+//! | 分类 | 组件 |
+//! |------|------|
+//! | light、lamp | light |
+//! | curtain、cover、blind、shade、shutter | cover |
+//! | sensor 或不可写节点 | sensor |
+//! | 其他 | switch |
+
+use serde_json::{json, Value};
+
+use crate::config::HomeAssistantConfig;
+use crate::device::NodeState;
+
+/// 节点映射到的 Home Assistant 组件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Component {
+    Switch,
+    Light,
+    Cover,
+    Sensor,
+}
+
+impl Component {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Component::Switch => "switch",
+            Component::Light => "light",
+            Component::Cover => "cover",
+            Component::Sensor => "sensor",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "switch" => Some(Component::Switch),
+            "light" => Some(Component::Light),
+            "cover" => Some(Component::Cover),
+            "sensor" => Some(Component::Sensor),
+            _ => None,
+        }
+    }
+}
+
+/// 确定节点的组件；`writable` 为 false（未开启命令接收）时全部映射为 sensor
+pub(super) fn component_for(state: &NodeState, writable: bool) -> Component {
+    let metadata = state.metadata.as_ref();
+    let writeable = metadata
+        .and_then(|m| m.get("writeable"))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    if !writable || !writeable {
+        return Component::Sensor;
+    }
+    if let Some(component) = metadata
+        .and_then(|m| m.get("ha_component"))
+        .and_then(Value::as_str)
+        .and_then(Component::parse)
+    {
+        return component;
+    }
+    match state.category.as_deref().map(str::to_lowercase).as_deref() {
+        Some("light" | "lamp") => Component::Light,
+        Some("curtain" | "cover" | "blind" | "shade" | "shutter") => Component::Cover,
+        Some("sensor") => Component::Sensor,
+        _ => Component::Switch,
+    }
+}
+
+/// 节点的发现主题
+pub(super) fn discovery_topic(
+    config: &HomeAssistantConfig,
+    component: Component,
+    global_id: u32,
+) -> String {
+    format!(
+        "{}/{}/{}/node_{}/config",
+        config.discovery_prefix,
+        component.as_str(),
+        config.node_id,
+        global_id
+    )
+}
+
+/// 节点的发现配置
+pub(super) fn discovery_payload(
+    config: &HomeAssistantConfig,
+    base_topic: &str,
+    state: &NodeState,
+    component: Component,
+) -> Value {
+    let node_topic = super::node_topic(base_topic, state.global_id);
+    let mut payload = json!({
+        "name": state.alias,
+        "unique_id": format!("{}_{}", config.node_id, state.global_id),
+        "state_topic": format!("{}/state", node_topic),
+        "availability_topic": super::status_topic(base_topic),
+        "payload_available": super::ONLINE,
+        "payload_not_available": super::OFFLINE,
+        "device": {
+            "identifiers": [format!("{}_channel_{}", config.node_id, state.channel_id)],
+            "name": format!("通道 {}", state.channel_id),
+            "manufacturer": "dm-rust",
+        },
+    });
+
+    let command_topic = format!("{}/set", node_topic);
+    match component {
+        Component::Switch | Component::Light => {
+            payload["command_topic"] = json!(command_topic);
+            payload["payload_on"] = json!("1");
+            payload["payload_off"] = json!("0");
+            if component == Component::Switch {
+                payload["state_on"] = json!("1");
+                payload["state_off"] = json!("0");
+            }
+        }
+        Component::Cover => {
+            payload["command_topic"] = json!(command_topic);
+            payload["payload_open"] = json!("1");
+            payload["payload_close"] = json!("0");
+            payload["payload_stop"] = Value::Null;
+            payload["state_open"] = json!("1");
+            payload["state_closed"] = json!("0");
+        }
+        Component::Sensor => {
+            let metadata = state.metadata.as_ref();
+            if let Some(unit) = metadata.and_then(|m| m.get("unit")).and_then(Value::as_str) {
+                payload["unit_of_measurement"] = json!(unit);
+            }
+            if let Some(decimals) = metadata
+                .and_then(|m| m.get("decimals"))
+                .and_then(Value::as_u64)
+            {
+                payload["suggested_display_precision"] = json!(decimals);
+            }
+        }
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(category: Option<&str>, metadata: Value) -> NodeState {
+        NodeState {
+            global_id: 12,
+            channel_id: 2,
+            device_id: 1,
+            category: category.map(str::to_string),
+            alias: "舞台灯".to_string(),
+            current_value: Some(1),
+            online: true,
+            metadata: metadata.as_object().cloned(),
+            last_update: None,
+            restored: false,
+            forced: false,
+        }
+    }
+
+    #[test]
+    fn maps_categories_to_components() {
+        let config = HomeAssistantConfig {
+            enable: true,
+            discovery_prefix: "homeassistant".to_string(),
+            node_id: "hall_a".to_string(),
+        };
+
+        let light = node(Some("Light"), json!({}));
+        assert_eq!(component_for(&light, true), Component::Light);
+        assert_eq!(component_for(&light, false), Component::Sensor);
+        assert_eq!(
+            component_for(&node(Some("curtain"), json!({})), true),
+            Component::Cover
+        );
+        assert_eq!(
+            component_for(&node(Some("power"), json!({"writeable": false})), true),
+            Component::Sensor
+        );
+        assert_eq!(
+            component_for(&node(Some("pc"), json!({"ha_component": "light"})), true),
+            Component::Light
+        );
+        assert_eq!(
+            component_for(&node(None, json!({})), true),
+            Component::Switch
+        );
+
+        assert_eq!(
+            discovery_topic(&config, Component::Light, 12),
+            "homeassistant/light/hall_a/node_12/config"
+        );
+        let payload = discovery_payload(&config, "dm-rust", &light, Component::Light);
+        assert_eq!(payload["unique_id"], "hall_a_12");
+        assert_eq!(payload["command_topic"], "dm-rust/node/12/set");
+        assert_eq!(payload["state_topic"], "dm-rust/node/12/state");
+        assert_eq!(payload["device"]["identifiers"][0], "hall_a_channel_2");
+    }
+}
@@ -0,0 +1,287 @@
+//! MQTT 北向发布
+//! 把节点状态以保留消息发布到 MQTT Broker，可选接收写入命令，
+//! 并可按 Home Assistant MQTT 自动发现约定发布实体配置，无需手写 YAML 即可接入仪表盘。
+//!
+//! 主题：
+//! - `<前缀>/status`：在线状态（`online` / `offline`，断线时由遗嘱消息置为 offline）
+//! - `<前缀>/node/<global_id>/state`：节点当前值（保留消息）
+//! - `<前缀>/node/<global_id>/set`：写入命令（`accept_commands` 为 true 时订阅），
+//!   负载为整数值或节点 `value_labels` 中的状态名称
+
+mod home_assistant;
+
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::config::MqttConfig;
+use crate::device::DeviceEvent;
+use crate::utils::{DeviceError, Result};
+use crate::web::state::SharedController;
+
+/// 连接断开后的重试间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+/// 客户端请求队列长度
+const REQUEST_CAPACITY: usize = 256;
+
+const ONLINE: &str = "online";
+const OFFLINE: &str = "offline";
+
+fn status_topic(base_topic: &str) -> String {
+    format!("{}/status", base_topic)
+}
+
+fn node_topic(base_topic: &str, global_id: u32) -> String {
+    format!("{}/node/{}", base_topic, global_id)
+}
+
+/// 从写入命令主题中解析节点 ID
+fn parse_set_topic(base_topic: &str, topic: &str) -> Option<u32> {
+    topic
+        .strip_prefix(base_topic)?
+        .strip_prefix("/node/")?
+        .strip_suffix("/set")?
+        .parse()
+        .ok()
+}
+
+/// 发布器：在 MQTT 客户端上发布状态与自动发现配置
+struct Publisher {
+    client: AsyncClient,
+    config: MqttConfig,
+    controller: SharedController,
+    /// 上次发布过的发现主题，节点删除后据此清除 Home Assistant 中的实体
+    discovered: Mutex<HashSet<String>>,
+}
+
+/// 按配置启动 MQTT 北向发布（未启用时不做任何事）
+///
+/// Broker 连接失败不影响启动，后台每隔 [`RECONNECT_DELAY`] 重连；
+/// 每次连上后重新发布在线状态、发现配置与全部节点当前值。
+pub fn start(config: Option<&MqttConfig>, controller: SharedController) -> Result<()> {
+    let Some(config) = config.filter(|c| c.enable) else {
+        return Ok(());
+    };
+    if config.host.is_empty() {
+        return Err(DeviceError::ConfigError("mqtt.host 未配置".to_string()));
+    }
+
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(KEEP_ALIVE);
+    options.set_last_will(LastWill::new(
+        status_topic(&config.base_topic),
+        OFFLINE,
+        QoS::AtLeastOnce,
+        true,
+    ));
+    if let Some(ref username) = config.username {
+        options.set_credentials(username, config.password.as_deref().unwrap_or_default());
+    }
+    let (client, mut eventloop) = AsyncClient::new(options, REQUEST_CAPACITY);
+
+    let publisher = Arc::new(Publisher {
+        client,
+        config: config.clone(),
+        controller,
+        discovered: Mutex::new(HashSet::new()),
+    });
+    info!(
+        "MQTT 北向发布已启动: {}:{} (前缀 '{}', 接收命令: {}, Home Assistant 自动发现: {})",
+        config.host,
+        config.port,
+        config.base_topic,
+        config.accept_commands,
+        publisher.home_assistant().is_some()
+    );
+
+    tokio::spawn(publisher.clone().run_states());
+    tokio::spawn(async move {
+        let ha_status = publisher
+            .home_assistant()
+            .map(|ha| status_topic(&ha.discovery_prefix));
+        loop {
+            // 客户端请求需要事件循环驱动，事件处理里的发布都放到独立任务中
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!(
+                        "MQTT 已连接: {}:{}",
+                        publisher.config.host, publisher.config.port
+                    );
+                    tokio::spawn(publisher.clone().on_connected());
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    let publisher = publisher.clone();
+                    if ha_status.as_deref() == Some(message.topic.as_str()) {
+                        // Home Assistant 重启后重新发布发现配置与当前值
+                        if message.payload.as_ref() == ONLINE.as_bytes() {
+                            debug!("Home Assistant 上线，重新发布发现配置");
+                            tokio::spawn(publisher.publish_all());
+                        }
+                    } else if let Some(global_id) =
+                        parse_set_topic(&publisher.config.base_topic, &message.topic)
+                    {
+                        let payload = String::from_utf8_lossy(&message.payload).into_owned();
+                        tokio::spawn(async move {
+                            publisher.handle_set(global_id, payload.trim()).await;
+                        });
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(
+                        "MQTT 连接异常: {}，{} 秒后重连",
+                        e,
+                        RECONNECT_DELAY.as_secs()
+                    );
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+impl Publisher {
+    fn home_assistant(&self) -> Option<&crate::config::HomeAssistantConfig> {
+        self.config.home_assistant.as_ref().filter(|ha| ha.enable)
+    }
+
+    async fn publish(&self, topic: String, payload: impl Into<Vec<u8>>) {
+        if let Err(e) = self
+            .client
+            .publish(&topic, QoS::AtLeastOnce, true, payload)
+            .await
+        {
+            warn!("MQTT 发布到 {} 失败: {}", topic, e);
+        }
+    }
+
+    async fn subscribe(&self, topic: String) {
+        if let Err(e) = self.client.subscribe(&topic, QoS::AtLeastOnce).await {
+            warn!("MQTT 订阅 {} 失败: {}", topic, e);
+        }
+    }
+
+    /// 连接（含重连）建立后：上线、订阅并发布全部内容
+    async fn on_connected(self: Arc<Self>) {
+        self.publish(status_topic(&self.config.base_topic), ONLINE)
+            .await;
+        if self.config.accept_commands {
+            self.subscribe(format!("{}/node/+/set", self.config.base_topic))
+                .await;
+        }
+        if let Some(ha) = self.home_assistant() {
+            self.subscribe(status_topic(&ha.discovery_prefix)).await;
+        }
+        self.publish_all().await;
+    }
+
+    /// 发布发现配置与全部节点当前值
+    async fn publish_all(self: Arc<Self>) {
+        let states = self.controller.read().await.get_all_node_states();
+
+        if let Some(ha) = self.home_assistant() {
+            let mut topics = HashSet::new();
+            for (_, state) in &states {
+                let component = home_assistant::component_for(state, self.config.accept_commands);
+                let topic = home_assistant::discovery_topic(ha, component, state.global_id);
+                let payload = home_assistant::discovery_payload(
+                    ha,
+                    &self.config.base_topic,
+                    state,
+                    component,
+                );
+                self.publish(topic.clone(), payload.to_string()).await;
+                topics.insert(topic);
+            }
+
+            // 已删除的节点（或组件变化）发布空的保留消息，Home Assistant 据此移除实体
+            let stale: Vec<String> = {
+                let mut discovered = self.discovered.lock().await;
+                let stale = discovered.difference(&topics).cloned().collect();
+                *discovered = topics;
+                stale
+            };
+            for topic in stale {
+                self.publish(topic, Vec::new()).await;
+            }
+        }
+
+        for (global_id, state) in states {
+            if let Some(value) = state.current_value {
+                self.publish_state(global_id, value).await;
+            }
+        }
+    }
+
+    async fn publish_state(&self, global_id: u32, value: i32) {
+        let topic = format!("{}/state", node_topic(&self.config.base_topic, global_id));
+        self.publish(topic, value.to_string()).await;
+    }
+
+    /// 发布节点状态变化；配置热重载替换控制器后重新订阅并全量发布
+    async fn run_states(self: Arc<Self>) {
+        loop {
+            let mut event_rx = self.controller.read().await.subscribe_events();
+            loop {
+                match event_rx.recv().await {
+                    Ok(DeviceEvent::NodeStateChanged {
+                        global_id,
+                        new_value,
+                        ..
+                    }) => self.publish_state(global_id, new_value).await,
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => {
+                        crate::device::record_lagged_events(n);
+                        warn!("MQTT 发布处理过慢，丢失 {} 个事件", n);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            debug!("设备事件总线已关闭，重新订阅并发布全部节点");
+            self.clone().publish_all().await;
+        }
+    }
+
+    /// 处理写入命令（需要写入确认的节点不直接写入）
+    async fn handle_set(&self, global_id: u32, payload: &str) {
+        let controller = self.controller.read().await;
+        let result = async {
+            let value = match payload.parse::<i32>() {
+                Ok(value) => value,
+                Err(_) => controller.resolve_value_label(global_id, payload)?,
+            };
+            match controller.hold_for_confirmation(global_id, value.into(), Some("mqtt".into()))? {
+                Some(pending) => Err(DeviceError::Other(format!(
+                    "写入需要确认，确认令牌: {}",
+                    pending.token
+                ))),
+                None => controller.write_node(global_id, value).await,
+            }
+        }
+        .await;
+
+        match result {
+            Ok(()) => debug!("MQTT 写入节点 {} = {}", global_id, payload),
+            Err(e) => warn!("MQTT 写入节点 {} ('{}') 失败: {}", global_id, payload, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_set_topics() {
+        assert_eq!(parse_set_topic("dm-rust", "dm-rust/node/12/set"), Some(12));
+        assert_eq!(parse_set_topic("dm-rust", "dm-rust/node/12/state"), None);
+        assert_eq!(parse_set_topic("dm-rust", "other/node/12/set"), None);
+        assert_eq!(parse_set_topic("dm-rust", "dm-rust/node/x/set"), None);
+    }
+}
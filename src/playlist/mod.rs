@@ -0,0 +1,469 @@
+//! 屏幕播放列表调度
+//! 按屏幕当前的播放列表依次播放素材：每个条目播放 `duration_ms` 后切换到下一条，
+//! 切换时调用显示通道的方法（`playlist.displays[].method`）推送素材。
+//!
+//! 屏幕的当前播放列表：
+//! - 场景 `playlist` 步骤或 `POST /lspcapi/playlists/{id}/play` 指定的播放列表优先（不检查启用与日期）
+//! - 否则取该屏幕第一个启用（`active`）、在生效日期内且有条目的播放列表
+//!
+//! 指定的播放列表只保存在内存中，重启或恢复自动选择后按日期重新选择。
+
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::config::{PlaylistConfig, PlaylistDisplayConfig, ResourceConfig};
+use crate::db::{Database, PlaylistResponse};
+use crate::device::DeviceEvent;
+use crate::utils::{time, DeviceError, Result};
+use crate::web::state::SharedController;
+
+/// 检查条目是否到期的间隔
+const TICK: Duration = Duration::from_millis(500);
+
+/// 屏幕播放状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ScreenPlaybackStatus {
+    pub screen_id: String,
+    /// 显示通道
+    pub channel_id: u32,
+    /// 当前播放列表（没有可播放的列表时为空）
+    pub playlist_id: Option<String>,
+    pub playlist_name: Option<String>,
+    /// 播放列表是否为场景或接口指定（否则按日期自动选择）
+    pub pinned: bool,
+    /// 当前条目序号
+    pub position: Option<usize>,
+    pub material_id: Option<String>,
+    /// 当前条目开始时间（RFC 3339）
+    pub started_at: Option<String>,
+    /// 距离切换到下一条的剩余时间（毫秒）
+    pub remaining_ms: Option<u64>,
+    /// 最近一次推送失败原因
+    pub last_error: Option<String>,
+}
+
+/// 播放列表调度状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PlaylistSchedulerStatus {
+    /// 最近一次读取播放列表的时间
+    pub refreshed_at: Option<String>,
+    pub screens: Vec<ScreenPlaybackStatus>,
+}
+
+/// 屏幕当前播放的条目
+#[derive(Debug, Clone)]
+struct Playback {
+    playlist_id: String,
+    position: usize,
+    material_id: String,
+    started: Instant,
+    duration: Duration,
+}
+
+/// 屏幕下一步动作
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    /// 保持当前条目
+    Keep,
+    /// 播放指定播放列表的第 n 条
+    Play(String, usize),
+    /// 没有可播放的列表
+    Stop,
+}
+
+/// 播放列表调度器
+pub struct PlaylistScheduler {
+    config: PlaylistConfig,
+    database: Arc<Database>,
+    /// 素材访问 URL 前缀（资源管理未启用时为空）
+    url_prefix: Option<String>,
+    controller: SharedController,
+    playlists: RwLock<Vec<PlaylistResponse>>,
+    refreshed_at: RwLock<Option<String>>,
+    /// screen_id -> 指定的播放列表
+    pinned: DashMap<String, String>,
+    playback: DashMap<String, Playback>,
+    errors: DashMap<String, String>,
+}
+
+/// 按配置启动播放列表调度（未启用时返回 None）
+pub fn start(
+    config: Option<&PlaylistConfig>,
+    resource: Option<&ResourceConfig>,
+    database: Arc<Database>,
+    controller: SharedController,
+) -> Result<Option<Arc<PlaylistScheduler>>> {
+    let Some(config) = config.filter(|c| c.enable) else {
+        return Ok(None);
+    };
+    if config.displays.is_empty() {
+        return Err(DeviceError::ConfigError(
+            "playlist.displays 未配置显示通道".to_string(),
+        ));
+    }
+
+    let scheduler = Arc::new(PlaylistScheduler {
+        config: config.clone(),
+        database,
+        url_prefix: resource
+            .filter(|r| r.enable)
+            .map(|r| r.url_prefix.trim_end_matches('/').to_string()),
+        controller,
+        playlists: RwLock::new(Vec::new()),
+        refreshed_at: RwLock::new(None),
+        pinned: DashMap::new(),
+        playback: DashMap::new(),
+        errors: DashMap::new(),
+    });
+    info!("播放列表调度已启用: {} 个屏幕", config.displays.len());
+
+    tokio::spawn(scheduler.clone().run());
+    tokio::spawn(scheduler.clone().listen());
+    Ok(Some(scheduler))
+}
+
+impl PlaylistScheduler {
+    /// 指定屏幕的播放列表（None 恢复按日期自动选择），立即生效
+    pub fn switch(&self, screen_id: &str, playlist_id: Option<String>) -> Result<()> {
+        if !self
+            .config
+            .displays
+            .iter()
+            .any(|d| d.screen_id == screen_id)
+        {
+            return Err(DeviceError::ConfigError(format!(
+                "屏幕 {} 未配置显示通道（playlist.displays）",
+                screen_id
+            )));
+        }
+        match playlist_id {
+            Some(id) => {
+                info!("屏幕 {} 指定播放列表 {}", screen_id, id);
+                self.pinned.insert(screen_id.to_string(), id);
+            }
+            None => {
+                info!("屏幕 {} 恢复自动选择播放列表", screen_id);
+                self.pinned.remove(screen_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// 调度状态
+    pub async fn status(&self) -> PlaylistSchedulerStatus {
+        let playlists = self.playlists.read().await;
+        let screens = self
+            .config
+            .displays
+            .iter()
+            .map(|screen| {
+                let playback = self.playback.get(&screen.screen_id).map(|p| p.clone());
+                let name = playback.as_ref().and_then(|p| {
+                    playlists
+                        .iter()
+                        .find(|l| l.playlist.id == p.playlist_id)
+                        .map(|l| l.playlist.name.clone())
+                });
+                ScreenPlaybackStatus {
+                    screen_id: screen.screen_id.clone(),
+                    channel_id: screen.channel_id,
+                    playlist_id: playback.as_ref().map(|p| p.playlist_id.clone()),
+                    playlist_name: name,
+                    pinned: self.pinned.contains_key(&screen.screen_id),
+                    position: playback.as_ref().map(|p| p.position),
+                    material_id: playback.as_ref().map(|p| p.material_id.clone()),
+                    started_at: playback.as_ref().map(|p| time::instant_rfc3339(p.started)),
+                    remaining_ms: playback
+                        .as_ref()
+                        .map(|p| p.duration.saturating_sub(p.started.elapsed()).as_millis() as u64),
+                    last_error: self.errors.get(&screen.screen_id).map(|e| e.clone()),
+                }
+            })
+            .collect();
+        PlaylistSchedulerStatus {
+            refreshed_at: self.refreshed_at.read().await.clone(),
+            screens,
+        }
+    }
+
+    /// 定期读取播放列表并切换到期的条目
+    async fn run(self: Arc<Self>) {
+        let refresh_interval = Duration::from_millis(self.config.refresh_interval_ms.max(1000));
+        let mut last_refresh: Option<Instant> = None;
+        loop {
+            if last_refresh.is_none_or(|t| t.elapsed() >= refresh_interval) {
+                self.refresh().await;
+                last_refresh = Some(Instant::now());
+            }
+            for screen in &self.config.displays {
+                self.advance(screen).await;
+            }
+            tokio::time::sleep(TICK).await;
+        }
+    }
+
+    /// 处理场景发出的播放列表切换请求
+    async fn listen(self: Arc<Self>) {
+        loop {
+            let mut event_rx = self.controller.read().await.subscribe_events();
+            loop {
+                match event_rx.recv().await {
+                    Ok(DeviceEvent::PlaylistSwitch {
+                        screen_id,
+                        playlist_id,
+                        scene_name,
+                    }) => {
+                        debug!(
+                            "场景 '{}' 请求切换屏幕 {} 的播放列表",
+                            scene_name, screen_id
+                        );
+                        if let Err(e) = self.switch(&screen_id, playlist_id) {
+                            warn!("场景 '{}' 切换播放列表失败: {}", scene_name, e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => {
+                        crate::device::record_lagged_events(n);
+                        warn!("播放列表调度处理过慢，丢失 {} 个事件", n);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            debug!("设备事件总线已关闭，重新订阅");
+        }
+    }
+
+    async fn refresh(&self) {
+        match self.database.playlists().find_all().await {
+            Ok(playlists) => {
+                *self.playlists.write().await = playlists;
+                *self.refreshed_at.write().await = Some(time::now_rfc3339());
+            }
+            Err(e) => warn!("读取播放列表失败: {:?}", e),
+        }
+    }
+
+    async fn advance(&self, screen: &PlaylistDisplayConfig) {
+        let step = {
+            let playlists = self.playlists.read().await;
+            let current = self.playback.get(&screen.screen_id).map(|p| p.clone());
+            let pinned = self.pinned.get(&screen.screen_id).map(|p| p.clone());
+            next_step(
+                &playlists,
+                &screen.screen_id,
+                pinned.as_deref(),
+                current.as_ref(),
+            )
+        };
+
+        match step {
+            Step::Keep => {}
+            Step::Stop => {
+                if self.playback.remove(&screen.screen_id).is_some() {
+                    info!("屏幕 {} 没有可播放的播放列表", screen.screen_id);
+                }
+            }
+            Step::Play(playlist_id, position) => {
+                self.play(screen, playlist_id, position).await;
+            }
+        }
+    }
+
+    /// 推送条目到显示通道（推送失败同样按时长计时，避免反复重试）
+    async fn play(&self, screen: &PlaylistDisplayConfig, playlist_id: String, position: usize) {
+        let item = {
+            let playlists = self.playlists.read().await;
+            playlists
+                .iter()
+                .find(|l| l.playlist.id == playlist_id)
+                .and_then(|l| l.items.get(position).cloned())
+        };
+        let Some(item) = item else {
+            return;
+        };
+
+        let result = self.push(screen, &playlist_id, &item).await;
+        match result {
+            Ok(()) => {
+                self.errors.remove(&screen.screen_id);
+                self.controller
+                    .read()
+                    .await
+                    .publish_event(DeviceEvent::PlaylistItemChanged {
+                        screen_id: screen.screen_id.clone(),
+                        playlist_id: playlist_id.clone(),
+                        material_id: item.material_id.clone(),
+                        channel_id: screen.channel_id,
+                    });
+            }
+            Err(e) => {
+                warn!(
+                    "屏幕 {} 推送素材 {} 失败: {}",
+                    screen.screen_id, item.material_id, e
+                );
+                self.errors.insert(screen.screen_id.clone(), e.to_string());
+            }
+        }
+        self.playback.insert(
+            screen.screen_id.clone(),
+            Playback {
+                playlist_id,
+                position,
+                material_id: item.material_id,
+                started: Instant::now(),
+                duration: Duration::from_millis(item.duration_ms.max(1000) as u64),
+            },
+        );
+    }
+
+    async fn push(
+        &self,
+        screen: &PlaylistDisplayConfig,
+        playlist_id: &str,
+        item: &crate::db::PlaylistItem,
+    ) -> Result<()> {
+        let material = self
+            .database
+            .materials()
+            .find_by_id(&item.material_id)
+            .await
+            .map_err(|e| DeviceError::Other(format!("查询素材失败: {}", e)))?
+            .ok_or_else(|| DeviceError::Other(format!("素材 {} 不存在", item.material_id)))?;
+        let url = self
+            .url_prefix
+            .as_ref()
+            .map(|prefix| format!("{}/{}", prefix, material.path));
+
+        let args = serde_json::json!({
+            "screen_id": screen.screen_id,
+            "playlist_id": playlist_id,
+            "position": item.position,
+            "material_id": material.id,
+            "name": material.name,
+            "path": material.path,
+            "url": url,
+            "resource_type": material.resource_type,
+            "mime_type": material.mime_type,
+            "duration_ms": item.duration_ms,
+        });
+        debug!(
+            "屏幕 {} 播放素材 {} ({})",
+            screen.screen_id, material.id, material.name
+        );
+        self.controller
+            .read()
+            .await
+            .call_channel_method(screen.channel_id, &screen.method, args)
+            .await
+            .map(|_| ())
+    }
+}
+
+/// 计算屏幕下一步：播放列表变化时从第一条开始，当前条目到期时播放下一条
+fn next_step(
+    playlists: &[PlaylistResponse],
+    screen_id: &str,
+    pinned: Option<&str>,
+    current: Option<&Playback>,
+) -> Step {
+    let now = Utc::now();
+    let selected = match pinned {
+        Some(id) => playlists.iter().find(|l| l.playlist.id == id),
+        None => playlists.iter().find(|l| {
+            l.playlist.screen_id == screen_id && l.playlist.active && l.playlist.is_valid_at(now)
+        }),
+    };
+    let Some(selected) = selected.filter(|l| !l.items.is_empty()) else {
+        return Step::Stop;
+    };
+
+    let id = selected.playlist.id.clone();
+    match current {
+        Some(current) if current.playlist_id == id => {
+            if current.started.elapsed() < current.duration {
+                Step::Keep
+            } else {
+                Step::Play(id, (current.position + 1) % selected.items.len())
+            }
+        }
+        _ => Step::Play(id, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Playlist, PlaylistItem};
+
+    fn playlist(
+        id: &str,
+        active: bool,
+        valid_until: Option<chrono::DateTime<Utc>>,
+    ) -> PlaylistResponse {
+        let now = Utc::now();
+        PlaylistResponse {
+            playlist: Playlist {
+                id: id.to_string(),
+                name: id.to_string(),
+                screen_id: "lobby".to_string(),
+                active,
+                valid_from: None,
+                valid_until,
+                created_at: now,
+                updated_at: now,
+            },
+            items: (0..2)
+                .map(|position| PlaylistItem {
+                    playlist_id: id.to_string(),
+                    position,
+                    material_id: format!("{}-{}", id, position),
+                    duration_ms: 10_000,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn selects_and_advances_playlists() {
+        let expired = Some(Utc::now() - chrono::Duration::days(1));
+        let playlists = vec![
+            playlist("old", true, expired),
+            playlist("off", false, None),
+            playlist("daily", true, None),
+        ];
+
+        assert_eq!(
+            next_step(&playlists, "lobby", None, None),
+            Step::Play("daily".to_string(), 0)
+        );
+        assert_eq!(
+            next_step(&playlists, "lobby", Some("off"), None),
+            Step::Play("off".to_string(), 0)
+        );
+        assert_eq!(next_step(&playlists, "hall", None, None), Step::Stop);
+
+        let mut current = Playback {
+            playlist_id: "daily".to_string(),
+            position: 1,
+            material_id: "daily-1".to_string(),
+            started: Instant::now(),
+            duration: Duration::from_secs(10),
+        };
+        assert_eq!(
+            next_step(&playlists, "lobby", None, Some(&current)),
+            Step::Keep
+        );
+        current.duration = Duration::ZERO;
+        assert_eq!(
+            next_step(&playlists, "lobby", None, Some(&current)),
+            Step::Play("daily".to_string(), 0)
+        );
+    }
+}
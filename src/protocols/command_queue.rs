@@ -0,0 +1,316 @@
+//! 通道命令优先级队列
+//!
+//! 同一条设备链路上的请求按优先级排队：写入优先于按需读取，按需读取优先于后台轮询，
+//! 同优先级按到达顺序。低优先级请求等待超过老化时间后提前获得链路，避免被持续饿死。
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use utoipa::ToSchema;
+
+/// 默认老化时间
+pub const DEFAULT_PRIORITY_AGING: Duration = Duration::from_millis(1000);
+
+/// 命令优先级（从高到低）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandPriority {
+    /// 写入（操作员写入、场景、定时任务）
+    Write,
+    /// 按需读取（接口读取、状态查询）
+    Read,
+    /// 后台轮询（自动召唤、心跳）
+    Poll,
+}
+
+impl CommandPriority {
+    const ALL: [CommandPriority; 3] = [Self::Write, Self::Read, Self::Poll];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 命令队列统计
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CommandQueueStats {
+    /// 链路正被占用
+    pub busy: bool,
+    /// 老化时间（毫秒）
+    pub aging_ms: u64,
+    /// 各优先级统计（按优先级从高到低）
+    pub classes: Vec<CommandClassStats>,
+}
+
+/// 单个优先级的排队统计
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CommandClassStats {
+    pub priority: CommandPriority,
+    /// 当前排队数
+    pub waiting: usize,
+    /// 已获得链路的请求数
+    pub served: u64,
+    /// 因等待超过老化时间而提前获得链路的次数
+    pub promoted: u64,
+    /// 平均排队时间（毫秒）
+    pub avg_wait_ms: f64,
+    /// 最长排队时间（毫秒）
+    pub max_wait_ms: f64,
+}
+
+/// 通道命令队列，可克隆到后台任务中使用
+#[derive(Clone)]
+pub struct CommandQueue {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    aging: Duration,
+    state: Mutex<QueueState>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    busy: bool,
+    waiters: [VecDeque<Waiter>; 3],
+    stats: [ClassStats; 3],
+}
+
+struct Waiter {
+    since: Instant,
+    tx: oneshot::Sender<()>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct ClassStats {
+    served: u64,
+    promoted: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+}
+
+impl Default for CommandQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_PRIORITY_AGING)
+    }
+}
+
+impl CommandQueue {
+    /// 创建命令队列，`aging` 为低优先级请求的最长让行时间
+    pub fn new(aging: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                aging,
+                state: Mutex::new(QueueState::default()),
+            }),
+        }
+    }
+
+    /// 排队获取链路，返回的许可释放前其他请求等待
+    pub async fn acquire(&self, priority: CommandPriority) -> CommandPermit {
+        let rx = {
+            let mut state = self.inner.state.lock().unwrap();
+            if !state.busy && state.waiters.iter().all(VecDeque::is_empty) {
+                state.busy = true;
+                state.stats[priority.index()].record(Duration::ZERO, false);
+                return CommandPermit {
+                    queue: self.clone(),
+                };
+            }
+            let (tx, rx) = oneshot::channel();
+            state.waiters[priority.index()].push_back(Waiter {
+                since: Instant::now(),
+                tx,
+            });
+            rx
+        };
+
+        let mut pending = PendingPermit {
+            queue: self,
+            rx: Some(rx),
+        };
+        // 发送端只会在移交许可后丢弃
+        let _ = pending.rx.as_mut().expect("等待中").await;
+        pending.rx = None;
+        CommandPermit {
+            queue: self.clone(),
+        }
+    }
+
+    /// 队列统计
+    pub fn stats(&self) -> CommandQueueStats {
+        let state = self.inner.state.lock().unwrap();
+        CommandQueueStats {
+            busy: state.busy,
+            aging_ms: self.inner.aging.as_millis() as u64,
+            classes: CommandPriority::ALL
+                .iter()
+                .map(|&priority| {
+                    let stats = state.stats[priority.index()];
+                    CommandClassStats {
+                        priority,
+                        waiting: state.waiters[priority.index()]
+                            .iter()
+                            .filter(|w| !w.tx.is_closed())
+                            .count(),
+                        served: stats.served,
+                        promoted: stats.promoted,
+                        avg_wait_ms: if stats.served == 0 {
+                            0.0
+                        } else {
+                            stats.total_wait.as_secs_f64() * 1000.0 / stats.served as f64
+                        },
+                        max_wait_ms: stats.max_wait.as_secs_f64() * 1000.0,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// 将链路移交给下一个请求，无人等待时释放
+    fn release(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+        let now = Instant::now();
+        for queue in state.waiters.iter_mut() {
+            queue.retain(|w| !w.tx.is_closed());
+        }
+        while let Some((class, promoted)) = next_class(&state.waiters, now, self.inner.aging) {
+            let waiter = state.waiters[class].pop_front().expect("非空队列");
+            if waiter.tx.send(()).is_ok() {
+                state.stats[class].record(now - waiter.since, promoted);
+                return;
+            }
+        }
+        state.busy = false;
+    }
+}
+
+/// 选择下一个获得链路的优先级：最高的非空优先级；更低优先级中有等待超过老化时间的，
+/// 改为其中等待最久的，返回是否因老化提前
+fn next_class(
+    waiters: &[VecDeque<Waiter>; 3],
+    now: Instant,
+    aging: Duration,
+) -> Option<(usize, bool)> {
+    let highest = waiters.iter().position(|q| !q.is_empty())?;
+    let starved = (highest + 1..waiters.len())
+        .filter_map(|class| waiters[class].front().map(|w| (class, w.since)))
+        .filter(|(_, since)| now.saturating_duration_since(*since) >= aging)
+        .min_by_key(|(_, since)| *since);
+    Some(match starved {
+        Some((class, _)) => (class, true),
+        None => (highest, false),
+    })
+}
+
+impl ClassStats {
+    fn record(&mut self, wait: Duration, promoted: bool) {
+        self.served += 1;
+        self.promoted += u64::from(promoted);
+        self.total_wait += wait;
+        self.max_wait = self.max_wait.max(wait);
+    }
+}
+
+/// 链路许可，释放时移交给下一个请求
+pub struct CommandPermit {
+    queue: CommandQueue,
+}
+
+impl Drop for CommandPermit {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+/// 排队中的请求；等待被取消时若许可已移交过来则继续移交
+struct PendingPermit<'a> {
+    queue: &'a CommandQueue,
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for PendingPermit<'_> {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            rx.close();
+            if rx.try_recv().is_ok() {
+                self.queue.release();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 占用链路后依次排入轮询、读取、写入，释放后按写入、读取、轮询的顺序获得链路
+    async fn grant_order(queue: &CommandQueue, delay_between: Duration) -> Vec<CommandPriority> {
+        let permit = queue.acquire(CommandPriority::Write).await;
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for priority in [
+            CommandPriority::Poll,
+            CommandPriority::Read,
+            CommandPriority::Write,
+        ] {
+            let queue = queue.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = queue.acquire(priority).await;
+                order.lock().unwrap().push(priority);
+            }));
+            tokio::time::sleep(delay_between).await;
+        }
+        drop(permit);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[tokio::test]
+    async fn serves_higher_priority_first_and_promotes_starved_requests() {
+        let queue = CommandQueue::new(Duration::from_secs(60));
+        let order = grant_order(&queue, Duration::from_millis(5)).await;
+        assert_eq!(
+            order,
+            [
+                CommandPriority::Write,
+                CommandPriority::Read,
+                CommandPriority::Poll
+            ]
+        );
+
+        // 轮询请求等待超过老化时间，先于后到的写入
+        let queue = CommandQueue::new(Duration::from_millis(20));
+        let order = grant_order(&queue, Duration::from_millis(15)).await;
+        assert_eq!(order[0], CommandPriority::Poll);
+        let stats = queue.stats();
+        assert!(!stats.busy);
+        assert_eq!(stats.classes[2].promoted, 1);
+        assert!(stats.classes[2].max_wait_ms >= 20.0);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_does_not_block_queue() {
+        let queue = CommandQueue::default();
+        let permit = queue.acquire(CommandPriority::Write).await;
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(10),
+            queue.acquire(CommandPriority::Read),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        drop(permit);
+        let _permit = tokio::time::timeout(
+            Duration::from_millis(100),
+            queue.acquire(CommandPriority::Poll),
+        )
+        .await
+        .expect("取消的请求不应占用链路");
+    }
+}
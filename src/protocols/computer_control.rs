@@ -1,10 +1,10 @@
 use crate::protocols::Protocol;
-use crate::utils::{DeviceError, Result};
+use crate::utils::{net, DeviceError, Result};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::net::{Ipv4Addr, SocketAddrV4};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::time::{Duration, Instant};
 
 use mac_address::MacAddress;
@@ -15,7 +15,7 @@ struct ComputerNode {
     id: u32,
     mac_text: String,
     mac_bytes: [u8; 6],
-    ip: Option<Ipv4Addr>,
+    ip: Option<IpAddr>,
     port: Option<u16>,
     last_heartbeat: Option<Instant>,
 }
@@ -106,20 +106,20 @@ impl ComputerControlProtocol {
 
     async fn send_udp(
         &self,
-        ip: Ipv4Addr,
+        ip: IpAddr,
         port: u16,
         command: &str,
         wait_response: bool,
     ) -> Result<Option<String>> {
+        let to = SocketAddr::new(ip, port);
         debug!(
-            "通道 {} [UDP]: 发送命令 '{}' 到 {}:{}",
-            self.channel_id, command, ip, port
+            "通道 {} [UDP]: 发送命令 '{}' 到 {}",
+            self.channel_id, command, to
         );
-        let socket = tokio::net::UdpSocket::bind((Ipv4Addr::new(0, 0, 0, 0), 0))
+        let socket = tokio::net::UdpSocket::bind(net::unspecified_for(&to, 0))
             .await
             .map_err(|e| DeviceError::ProtocolError(format!("绑定 UDP socket 失败: {}", e)))?;
 
-        let to = SocketAddrV4::new(ip, port);
         socket
             .send_to(command.as_bytes(), to)
             .await
@@ -132,16 +132,13 @@ impl ComputerControlProtocol {
                 Ok(Ok((n, _))) => {
                     let response = String::from_utf8_lossy(&buf[..n]).trim().to_string();
                     debug!(
-                        "通道 {} [UDP]: 收到来自 {}:{} 的响应: {}",
-                        self.channel_id, ip, port, response
+                        "通道 {} [UDP]: 收到来自 {} 的响应: {}",
+                        self.channel_id, to, response
                     );
                     Ok(Some(response))
                 }
                 _ => {
-                    debug!(
-                        "通道 {} [UDP]: 等待 {}:{} 响应超时",
-                        self.channel_id, ip, port
-                    );
+                    debug!("通道 {} [UDP]: 等待 {} 响应超时", self.channel_id, to);
                     Ok(None)
                 }
             }
@@ -256,7 +253,7 @@ impl Protocol for ComputerControlProtocol {
                 })?
                 .bytes();
 
-            let ip = item.ip.as_ref().and_then(|s| s.parse::<Ipv4Addr>().ok());
+            let ip = item.ip.as_deref().and_then(net::parse_ip);
 
             computers.push(ComputerNode {
                 id: item.id,
@@ -1,44 +1,665 @@
-use crate::protocols::Protocol;
-use crate::utils::Result;
+// 自定义二进制协议（声明式帧结构）
+// 通信方式: TCP 短连接（串口设备经串口服务器接入）
+//
+// 在通道参数中声明命令的请求帧与响应帧，无需编写 Rust 代码即可接入简单的厂商协议：
+//
+//   "commands": {
+//     "set_output": {
+//       "request": [
+//         { "type": "bytes", "hex": "AA 55" },
+//         { "type": "length", "width": 1 },
+//         { "type": "uint", "param": "id" },
+//         { "type": "uint", "param": "value", "map": { "on": 1, "off": 0 } },
+//         { "type": "checksum", "algorithm": "crc16_modbus", "from": 2 }
+//       ],
+//       "response": { "length": 6, "header": "AA 55", "fields": { "state": { "offset": 4 } } }
+//     }
+//   }
+//
+// 请求帧字段按顺序拼接；length / checksum 的 from、to 为字段下标（含 from，不含 to）。
+// length 默认覆盖其后到第一个校验字段之前的字段，checksum 默认覆盖其前的全部字段。
+
 use async_trait::async_trait;
-use serde_json::Value;
-use std::collections::HashMap;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::debug;
+
+use crate::protocols::Protocol;
+use crate::utils::checksum::ChecksumAlgorithm;
+use crate::utils::{dns, net, DeviceError, Result};
+
+/// 字节序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Endian {
+    #[default]
+    Big,
+    Little,
+}
+
+/// 校验字段未配置字节序时的默认值
+fn default_endian(algorithm: ChecksumAlgorithm) -> Endian {
+    if algorithm.little_endian_by_default() {
+        Endian::Little
+    } else {
+        Endian::Big
+    }
+}
+
+fn default_width() -> usize {
+    1
+}
+
+/// 请求帧字段
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FieldSpec {
+    /// 固定字节（十六进制，可含空格）
+    Bytes { hex: String },
+    /// 无符号整数：固定值 `value`，或取命令参数 `param`（可经 `map` 把名称映射为数值）
+    Uint {
+        #[serde(default = "default_width")]
+        width: usize,
+        #[serde(default)]
+        endian: Endian,
+        #[serde(default)]
+        value: Option<i64>,
+        #[serde(default)]
+        param: Option<String>,
+        #[serde(default)]
+        map: Option<HashMap<String, i64>>,
+    },
+    /// 长度字段：覆盖字段的字节数加 `adjust`
+    Length {
+        #[serde(default = "default_width")]
+        width: usize,
+        #[serde(default)]
+        endian: Endian,
+        #[serde(default)]
+        from: Option<usize>,
+        #[serde(default)]
+        to: Option<usize>,
+        #[serde(default)]
+        adjust: i64,
+    },
+    /// 校验字段
+    Checksum {
+        algorithm: ChecksumAlgorithm,
+        #[serde(default)]
+        endian: Option<Endian>,
+        #[serde(default)]
+        from: Option<usize>,
+        #[serde(default)]
+        to: Option<usize>,
+    },
+}
+
+/// 响应值字段
+#[derive(Debug, Clone, Deserialize)]
+struct ValueSpec {
+    /// 字节偏移，负数表示从帧尾倒数
+    offset: isize,
+    #[serde(default = "default_width")]
+    width: usize,
+    #[serde(default)]
+    endian: Endian,
+    #[serde(default)]
+    signed: bool,
+    /// 数值到名称的映射（如 {"0": "off", "1": "on"}），未命中时返回数值
+    #[serde(default)]
+    labels: Option<BTreeMap<String, String>>,
+}
+
+/// 响应校验
+#[derive(Debug, Clone, Deserialize)]
+struct ResponseChecksum {
+    algorithm: ChecksumAlgorithm,
+    #[serde(default)]
+    endian: Option<Endian>,
+    /// 校验值所在偏移，负数表示从帧尾倒数（默认位于帧尾）
+    #[serde(default)]
+    offset: Option<isize>,
+    /// 参与校验的字节范围（含 from，不含 to；to 默认为校验值所在偏移）
+    #[serde(default)]
+    from: isize,
+    #[serde(default)]
+    to: Option<isize>,
+}
+
+/// 响应帧
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ResponseSpec {
+    /// 固定响应长度；未配置时读到设备停止发送为止
+    #[serde(default)]
+    length: Option<usize>,
+    /// 期望的帧头（十六进制）
+    #[serde(default)]
+    header: Option<String>,
+    #[serde(default)]
+    checksum: Option<ResponseChecksum>,
+    #[serde(default)]
+    fields: BTreeMap<String, ValueSpec>,
+}
+
+/// 命令
+#[derive(Debug, Clone, Deserialize)]
+struct CommandSpec {
+    request: Vec<FieldSpec>,
+    /// 未配置时只发送不等待响应
+    #[serde(default)]
+    response: Option<ResponseSpec>,
+}
+
+fn default_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_read_field() -> String {
+    "value".to_string()
+}
+
+/// 自定义协议通道参数
+#[derive(Debug, Clone, Deserialize)]
+struct CustomConfig {
+    addr: String,
+    port: u16,
+    /// 连接与等待响应的超时（毫秒）
+    #[serde(default = "default_timeout_ms", alias = "timeout")]
+    timeout_ms: u64,
+    #[serde(default)]
+    commands: HashMap<String, CommandSpec>,
+    /// 节点写入执行的命令，参数为 `{"id": 节点 id, "value": 值}`
+    #[serde(default)]
+    write_command: Option<String>,
+    /// 节点读取执行的命令，参数为 `{"id": 节点 id}`
+    #[serde(default)]
+    read_command: Option<String>,
+    /// 节点读取返回的响应字段
+    #[serde(default = "default_read_field")]
+    read_field: String,
+    /// 状态查询执行的命令（未配置时只返回通道参数）
+    #[serde(default)]
+    status_command: Option<String>,
+}
+
+fn parse_hex(input: &str) -> std::result::Result<Vec<u8>, String> {
+    let digits: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    hex::decode(&digits).map_err(|e| format!("十六进制 '{}' 无效: {}", input, e))
+}
+
+/// 整数编码为指定宽度的字节，超出范围时报错
+fn encode_uint(value: i64, width: usize, endian: Endian) -> std::result::Result<Vec<u8>, String> {
+    let bits = width as u32 * 8;
+    let fits = if bits >= 64 {
+        true
+    } else {
+        value >= -(1i64 << (bits - 1)) && value < (1i64 << bits)
+    };
+    if !fits {
+        return Err(format!("值 {} 超出 {} 字节范围", value, width));
+    }
+    let be = (value as u64).to_be_bytes();
+    let mut bytes = be[8 - width..].to_vec();
+    if endian == Endian::Little {
+        bytes.reverse();
+    }
+    Ok(bytes)
+}
+
+fn decode_uint(bytes: &[u8], endian: Endian) -> u64 {
+    let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+    match endian {
+        Endian::Big => bytes.iter().fold(0, fold),
+        Endian::Little => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+/// 负偏移从帧尾倒数
+fn resolve_offset(offset: isize, len: usize) -> Option<usize> {
+    let index = if offset < 0 {
+        len as isize + offset
+    } else {
+        offset
+    };
+    (0..=len as isize)
+        .contains(&index)
+        .then_some(index as usize)
+}
+
+impl FieldSpec {
+    /// 字段字节数（组帧前即可确定）
+    fn width(&self) -> std::result::Result<usize, String> {
+        match self {
+            FieldSpec::Bytes { hex } => parse_hex(hex).map(|b| b.len()),
+            FieldSpec::Uint { width, .. } | FieldSpec::Length { width, .. } => Ok(*width),
+            FieldSpec::Checksum { algorithm, .. } => Ok(algorithm.width()),
+        }
+    }
+}
+
+impl CommandSpec {
+    /// 检查帧结构（加载配置时调用）
+    fn validate(&self) -> std::result::Result<(), String> {
+        let count = self.request.len();
+        for (index, field) in self.request.iter().enumerate() {
+            let width = field.width()?;
+            match field {
+                FieldSpec::Uint { value, param, .. } => {
+                    if !(1..=8).contains(&width) {
+                        return Err(format!("字段 {} 宽度必须为 1-8", index));
+                    }
+                    if value.is_none() && param.is_none() {
+                        return Err(format!("字段 {} 需要 value 或 param", index));
+                    }
+                }
+                FieldSpec::Length { .. } | FieldSpec::Checksum { .. } => {
+                    if !(1..=8).contains(&width) {
+                        return Err(format!("字段 {} 宽度必须为 1-8", index));
+                    }
+                    let (from, to) = self.range(index);
+                    if from > to || to > count {
+                        return Err(format!("字段 {} 的范围 {}..{} 无效", index, from, to));
+                    }
+                }
+                FieldSpec::Bytes { .. } => {}
+            }
+        }
+        if let Some(header) = self.response.as_ref().and_then(|r| r.header.as_ref()) {
+            parse_hex(header)?;
+        }
+        Ok(())
+    }
+
+    /// length / checksum 字段覆盖的字段下标范围
+    fn range(&self, index: usize) -> (usize, usize) {
+        match &self.request[index] {
+            FieldSpec::Length { from, to, .. } => {
+                let first_checksum = self.request[index + 1..]
+                    .iter()
+                    .position(|f| matches!(f, FieldSpec::Checksum { .. }))
+                    .map(|p| index + 1 + p)
+                    .unwrap_or(self.request.len());
+                (from.unwrap_or(index + 1), to.unwrap_or(first_checksum))
+            }
+            FieldSpec::Checksum { from, to, .. } => (from.unwrap_or(0), to.unwrap_or(index)),
+            _ => (index, index + 1),
+        }
+    }
+
+    /// 按参数组帧
+    fn build(&self, params: &Value) -> std::result::Result<Vec<u8>, String> {
+        let mut parts: Vec<Vec<u8>> = Vec::with_capacity(self.request.len());
+        for (index, field) in self.request.iter().enumerate() {
+            let bytes = match field {
+                FieldSpec::Bytes { hex } => parse_hex(hex)?,
+                FieldSpec::Uint {
+                    width,
+                    endian,
+                    value,
+                    param,
+                    map,
+                } => {
+                    let value = match param {
+                        Some(name) => param_value(params, name, map.as_ref())
+                            .or(*value)
+                            .ok_or_else(|| format!("缺少参数 {}", name))?,
+                        None => value.unwrap_or_default(),
+                    };
+                    encode_uint(value, *width, *endian)
+                        .map_err(|e| format!("字段 {}: {}", index, e))?
+                }
+                // 占位，所有字段就绪后回填
+                FieldSpec::Length { width, .. } => vec![0; *width],
+                FieldSpec::Checksum { algorithm, .. } => vec![0; algorithm.width()],
+            };
+            parts.push(bytes);
+        }
+
+        for (index, field) in self.request.iter().enumerate() {
+            let (from, to) = self.range(index);
+            match field {
+                FieldSpec::Length {
+                    width,
+                    endian,
+                    adjust,
+                    ..
+                } => {
+                    let length = parts[from..to].iter().map(Vec::len).sum::<usize>() as i64;
+                    parts[index] = encode_uint(length + adjust, *width, *endian)
+                        .map_err(|e| format!("长度字段 {}: {}", index, e))?;
+                }
+                FieldSpec::Checksum {
+                    algorithm, endian, ..
+                } => {
+                    let covered = parts[from..to].concat();
+                    let value = algorithm.compute(&covered);
+                    let endian = endian.unwrap_or(default_endian(*algorithm));
+                    parts[index] = encode_uint(value as i64, algorithm.width(), endian)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(parts.concat())
+    }
+}
+
+/// 读取命令参数：数值，或经 `map` 映射的名称
+fn param_value(params: &Value, name: &str, map: Option<&HashMap<String, i64>>) -> Option<i64> {
+    match params.get(name)? {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => map.and_then(|m| m.get(s).copied()),
+        Value::Bool(b) => Some(*b as i64),
+        _ => None,
+    }
+}
+
+impl ResponseSpec {
+    /// 校验并解析响应帧
+    fn parse(&self, frame: &[u8]) -> std::result::Result<Map<String, Value>, String> {
+        if let Some(length) = self.length {
+            if frame.len() < length {
+                return Err(format!(
+                    "响应长度不足: 期望 {}，实际 {}",
+                    length,
+                    frame.len()
+                ));
+            }
+        }
+        let frame = &frame[..self.length.unwrap_or(frame.len())];
+        if let Some(header) = &self.header {
+            let header = parse_hex(header)?;
+            if !frame.starts_with(&header) {
+                return Err(format!("响应帧头不匹配: {}", hex::encode_upper(frame)));
+            }
+        }
+        if let Some(check) = &self.checksum {
+            let width = check.algorithm.width();
+            let at = resolve_offset(check.offset.unwrap_or(-(width as isize)), frame.len())
+                .filter(|at| at + width <= frame.len())
+                .ok_or("校验值偏移超出响应长度")?;
+            let from = resolve_offset(check.from, frame.len());
+            let to = resolve_offset(check.to.unwrap_or(at as isize), frame.len());
+            let (Some(from), Some(to)) = (from, to.filter(|to| from.is_some_and(|f| f <= *to)))
+            else {
+                return Err("校验范围超出响应长度".to_string());
+            };
+            let expected = check.algorithm.compute(&frame[from..to]) as u64;
+            let endian = check.endian.unwrap_or(default_endian(check.algorithm));
+            let actual = decode_uint(&frame[at..at + width], endian);
+            if expected != actual {
+                return Err(format!(
+                    "响应校验失败: 期望 {:0w$X}，实际 {:0w$X}",
+                    expected,
+                    actual,
+                    w = width * 2
+                ));
+            }
+        }
+
+        let mut values = Map::new();
+        for (name, spec) in &self.fields {
+            let at = resolve_offset(spec.offset, frame.len())
+                .filter(|at| spec.width <= 8 && at + spec.width <= frame.len())
+                .ok_or_else(|| format!("字段 {} 超出响应长度", name))?;
+            let raw = decode_uint(&frame[at..at + spec.width], spec.endian);
+            let value = if spec.signed && spec.width < 8 {
+                let shift = 64 - spec.width as u32 * 8;
+                ((raw << shift) as i64) >> shift
+            } else {
+                raw as i64
+            };
+            let label = spec
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get(&value.to_string()));
+            values.insert(
+                name.clone(),
+                label.map_or_else(|| json!(value), |l| json!(l)),
+            );
+        }
+        values.insert("raw".to_string(), json!(hex::encode_upper(frame)));
+        Ok(values)
+    }
+}
 
 /// 自定义协议
 pub struct CustomProtocol {
     channel_id: u32,
+    config: CustomConfig,
 }
 
 impl CustomProtocol {
-    pub fn new() -> Self {
-        Self { channel_id: 0 }
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.config.timeout_ms)
+    }
+
+    /// 发送请求帧，按响应定义接收响应
+    async fn exchange(&self, frame: &[u8], response: Option<&ResponseSpec>) -> Result<Vec<u8>> {
+        let timeout = self.timeout();
+        debug!(
+            "[custom:{}] 发送: {}",
+            self.channel_id,
+            hex::encode_upper(frame)
+        );
+        let Some(response) = response else {
+            let mut stream = self.connect(timeout).await?;
+            stream
+                .write_all(frame)
+                .await
+                .map_err(|e| DeviceError::ConnectionError(format!("发送失败: {}", e)))?;
+            return Ok(Vec::new());
+        };
+        let bytes = match response.length {
+            Some(length) => {
+                let mut stream = self.connect(timeout).await?;
+                stream
+                    .write_all(frame)
+                    .await
+                    .map_err(|e| DeviceError::ConnectionError(format!("发送失败: {}", e)))?;
+                let mut buf = vec![0u8; length];
+                tokio::time::timeout(timeout, stream.read_exact(&mut buf))
+                    .await
+                    .map_err(|_| DeviceError::Timeout)?
+                    .map_err(|e| DeviceError::ConnectionError(format!("接收失败: {}", e)))?;
+                buf
+            }
+            None => net::exchange_raw(&self.config.addr, self.config.port, frame, timeout).await?,
+        };
+        debug!(
+            "[custom:{}] 接收: {}",
+            self.channel_id,
+            hex::encode_upper(&bytes)
+        );
+        if bytes.is_empty() {
+            return Err(DeviceError::Timeout);
+        }
+        Ok(bytes)
+    }
+
+    async fn connect(&self, timeout: Duration) -> Result<TcpStream> {
+        let addr = dns::resolve(&self.config.addr, self.config.port).await?;
+        tokio::time::timeout(timeout, TcpStream::connect(addr))
+            .await
+            .map_err(|_| DeviceError::Timeout)?
+            .map_err(|e| DeviceError::ConnectionError(format!("连接 {} 失败: {}", addr, e)))
+    }
+
+    async fn run_command(&self, name: &str, params: &Value) -> Result<Value> {
+        let spec = self
+            .config
+            .commands
+            .get(name)
+            .ok_or_else(|| DeviceError::Other(format!("未知命令: {}", name)))?;
+        let frame = spec
+            .build(params)
+            .map_err(|e| DeviceError::ProtocolError(format!("命令 {} 组帧失败: {}", name, e)))?;
+        let response = self.exchange(&frame, spec.response.as_ref()).await?;
+        let mut result = match &spec.response {
+            Some(spec) => spec.parse(&response).map_err(|e| {
+                DeviceError::ProtocolError(format!("命令 {} 响应无效: {}", name, e))
+            })?,
+            None => Map::new(),
+        };
+        result.insert("sent".to_string(), json!(hex::encode_upper(&frame)));
+        Ok(Value::Object(result))
+    }
+
+    fn node_command(&self, command: &Option<String>, kind: &str) -> Result<String> {
+        command
+            .clone()
+            .ok_or_else(|| DeviceError::ConfigError(format!("自定义协议未配置 {}", kind)))
     }
 }
 
 #[async_trait]
 impl Protocol for CustomProtocol {
-    fn from_config(channel_id: u32, _params: &HashMap<String, Value>) -> Result<Box<dyn Protocol>> {
-        // TODO: 解析自定义协议配置
-        Ok(Box::new(Self { channel_id }))
+    fn from_config(channel_id: u32, params: &HashMap<String, Value>) -> Result<Box<dyn Protocol>> {
+        let config: CustomConfig = serde_json::from_value(serde_json::to_value(params)?)
+            .map_err(|e| DeviceError::ConfigError(format!("自定义协议参数无效: {}", e)))?;
+        for (name, command) in &config.commands {
+            command.validate().map_err(|e| {
+                DeviceError::ConfigError(format!("自定义协议命令 {} 无效: {}", name, e))
+            })?;
+        }
+        for command in [
+            &config.write_command,
+            &config.read_command,
+            &config.status_command,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !config.commands.contains_key(command) {
+                return Err(DeviceError::ConfigError(format!(
+                    "自定义协议引用的命令 {} 不存在",
+                    command
+                )));
+            }
+        }
+        Ok(Box::new(Self { channel_id, config }))
     }
 
-    async fn execute(&mut self, _command: &str, _params: Value) -> Result<Value> {
-        Ok(serde_json::json!({"status": "ok"}))
+    async fn execute(&mut self, command: &str, params: Value) -> Result<Value> {
+        self.run_command(command, &params).await
     }
 
     async fn get_status(&self) -> Result<Value> {
-        Ok(serde_json::json!({"connected": true}))
+        match &self.config.status_command {
+            Some(command) => self.run_command(command, &json!({})).await,
+            None => Ok(json!({
+                "protocol": "custom",
+                "addr": self.config.addr,
+                "port": self.config.port,
+            })),
+        }
     }
 
-    async fn write(&mut self, _id: u32, _value: i32) -> Result<()> {
-        Ok(())
+    async fn write(&mut self, id: u32, value: i32) -> Result<()> {
+        let command = self.node_command(&self.config.write_command, "write_command")?;
+        self.run_command(&command, &json!({ "id": id, "value": value }))
+            .await
+            .map(|_| ())
     }
 
-    async fn read(&self, _id: u32) -> Result<i32> {
-        Ok(0)
+    async fn read(&self, id: u32) -> Result<i32> {
+        let command = self.node_command(&self.config.read_command, "read_command")?;
+        let result = self.run_command(&command, &json!({ "id": id })).await?;
+        result
+            .get(&self.config.read_field)
+            .and_then(Value::as_i64)
+            .map(|v| v as i32)
+            .ok_or_else(|| {
+                DeviceError::ProtocolError(format!(
+                    "命令 {} 的响应缺少数值字段 {}",
+                    command, self.config.read_field
+                ))
+            })
     }
 
     fn name(&self) -> &str {
         "custom"
     }
+
+    async fn call_method(&mut self, method_name: &str, args: Value) -> Result<Value> {
+        self.run_command(method_name, &args).await
+    }
+
+    fn get_methods(&self) -> Vec<String> {
+        let mut methods: Vec<String> = self.config.commands.keys().cloned().collect();
+        methods.sort();
+        methods
+    }
+
+    async fn send_raw(&mut self, payload: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        net::exchange_raw(&self.config.addr, self.config.port, payload, timeout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(spec: Value) -> CommandSpec {
+        let command: CommandSpec = serde_json::from_value(spec).unwrap();
+        command.validate().unwrap();
+        command
+    }
+
+    #[test]
+    fn builds_frames_with_length_and_checksum() {
+        // Modbus RTU 读保持寄存器：01 03 00 00 00 0A + CRC C5 CD
+        let read = command(json!({
+            "request": [
+                { "type": "uint", "param": "slave", "value": 1 },
+                { "type": "bytes", "hex": "03" },
+                { "type": "uint", "width": 2, "param": "address" },
+                { "type": "uint", "width": 2, "value": 10 },
+                { "type": "checksum", "algorithm": "crc16_modbus" }
+            ]
+        }));
+        let frame = read.build(&json!({ "address": 0 })).unwrap();
+        assert_eq!(hex::encode_upper(frame), "01030000000AC5CD");
+
+        let set = command(json!({
+            "request": [
+                { "type": "bytes", "hex": "AA 55" },
+                { "type": "length", "width": 1 },
+                { "type": "uint", "param": "id" },
+                { "type": "uint", "width": 2, "endian": "little", "param": "value", "map": { "on": 1 } },
+                { "type": "checksum", "algorithm": "xor", "from": 1 }
+            ]
+        }));
+        let frame = set.build(&json!({ "id": 3, "value": "on" })).unwrap();
+        assert_eq!(frame, vec![0xAA, 0x55, 0x03, 0x03, 0x01, 0x00, 0x01]);
+        assert!(set.build(&json!({ "id": 300, "value": 1 })).is_err());
+        assert!(set.build(&json!({ "id": 3 })).is_err());
+    }
+
+    #[test]
+    fn parses_response_fields_and_verifies_checksum() {
+        let response: ResponseSpec = serde_json::from_value(json!({
+            "length": 6,
+            "header": "AA55",
+            "checksum": { "algorithm": "sum", "from": 2 },
+            "fields": {
+                "power": { "offset": 2, "labels": { "0": "off", "1": "on" } },
+                "temperature": { "offset": 3, "width": 2, "signed": true }
+            }
+        }))
+        .unwrap();
+        let frame = [0xAA, 0x55, 0x01, 0xFF, 0xF6, 0xF6];
+        let values = response.parse(&frame).unwrap();
+        assert_eq!(values["power"], "on");
+        assert_eq!(values["temperature"], -10);
+        assert_eq!(values["raw"], "AA5501FFF6F6");
+
+        assert!(response
+            .parse(&[0xAA, 0x55, 0x01, 0xFF, 0xF6, 0x00])
+            .is_err());
+        assert!(response.parse(&[0xAA, 0x55, 0x01]).is_err());
+    }
 }
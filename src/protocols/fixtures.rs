@@ -0,0 +1,144 @@
+//! 协议报文黄金样本（仅测试使用）
+//!
+//! 二进制协议驱动的请求帧 / 响应帧样本保存在 `src/protocols/fixtures/<协议>.frames`，
+//! 每行一个样本：`名称 = 十六进制字节`，`#` 开头的行为注释。驱动测试用当前实现生成的字节
+//! 与样本比对，重构组帧代码时线上字节发生变化会直接失败，并给出可粘贴回样本文件的新行。
+//!
+//! 样本覆盖 modbus（寄存器编解码）、hs-power-sequencer、screen-njlg-plc、tpris-pdu；
+//! BFHD1 目前只有配置枚举没有驱动实现，xinkeQ1 驱动不收发报文，二者暂无样本。
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// 一个协议的全部样本
+pub(crate) struct Captures {
+    protocol: String,
+    frames: BTreeMap<String, Vec<u8>>,
+}
+
+impl Captures {
+    /// 读取 `fixtures/<protocol>.frames`
+    pub(crate) fn load(protocol: &str) -> Self {
+        let path = format!(
+            "{}/src/protocols/fixtures/{}.frames",
+            env!("CARGO_MANIFEST_DIR"),
+            protocol
+        );
+        let content = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("读取报文样本 {} 失败: {}", path, e));
+        let mut frames = BTreeMap::new();
+        for (line_no, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, hex) = line
+                .split_once('=')
+                .unwrap_or_else(|| panic!("{}:{} 缺少 '='", path, line_no + 1));
+            let bytes = parse_hex(hex)
+                .unwrap_or_else(|e| panic!("{}:{} 十六进制无效: {}", path, line_no + 1, e));
+            if frames.insert(name.trim().to_string(), bytes).is_some() {
+                panic!("{}:{} 样本名称重复: {}", path, line_no + 1, name.trim());
+            }
+        }
+        Self {
+            protocol: protocol.to_string(),
+            frames,
+        }
+    }
+
+    /// 样本字节（用作解析测试的输入）
+    pub(crate) fn frame(&self, name: &str) -> &[u8] {
+        self.frames
+            .get(name)
+            .unwrap_or_else(|| panic!("{} 缺少报文样本 '{}'", self.protocol, name))
+    }
+
+    /// 比对实际字节与样本，不一致时指出第一个不同的字节
+    #[track_caller]
+    pub(crate) fn assert_frame(&self, name: &str, actual: &[u8]) {
+        let Some(expected) = self.frames.get(name) else {
+            panic!(
+                "{} 缺少报文样本 '{}'，当前实现输出:\n{} = {}",
+                self.protocol,
+                name,
+                name,
+                to_hex(actual)
+            );
+        };
+        if expected.as_slice() == actual {
+            return;
+        }
+        let offset = expected
+            .iter()
+            .zip(actual)
+            .position(|(e, a)| e != a)
+            .unwrap_or(expected.len().min(actual.len()));
+        panic!(
+            "{} 报文样本 '{}' 不一致（第 {} 字节起）\n样本: {}\n实际: {}\n确认变更符合协议后更新样本:\n{} = {}",
+            self.protocol,
+            name,
+            offset,
+            to_hex(expected),
+            to_hex(actual),
+            name,
+            to_hex(actual)
+        );
+    }
+}
+
+/// 解析空白分隔（或连续）的十六进制字节
+pub(crate) fn parse_hex(input: &str) -> Result<Vec<u8>, String> {
+    let digits: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("长度为奇数: '{}'", input.trim()));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("'{}'", &digits[i..i + 2]))
+        })
+        .collect()
+}
+
+/// 格式化为空格分隔的大写十六进制
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 3);
+    for (i, b) in bytes.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        let _ = write!(out, "{:02X}", b);
+    }
+    out
+}
+
+/// Modbus 寄存器按线上顺序（大端）展开为字节
+pub(crate) fn registers_to_bytes(registers: &[u16]) -> Vec<u8> {
+    registers.iter().flat_map(|r| r.to_be_bytes()).collect()
+}
+
+/// 线上字节还原为 Modbus 寄存器
+pub(crate) fn bytes_to_registers(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(parse_hex("3A 0d0A").unwrap(), vec![0x3A, 0x0D, 0x0A]);
+        assert!(parse_hex("3A 0").is_err());
+        assert_eq!(to_hex(&[0x5B, 0xB5, 0x01]), "5B B5 01");
+        assert_eq!(
+            bytes_to_registers(&registers_to_bytes(&[0x1234, 1])),
+            [0x1234, 1]
+        );
+    }
+}
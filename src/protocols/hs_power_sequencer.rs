@@ -95,13 +95,29 @@ impl HsPowerSequencerProtocol {
     }
 
     /// 构建完整数据帧 (带协议头)
-    fn build_frame(&self, data: &[u8]) -> Vec<u8> {
+    pub(crate) fn build_frame(&self, data: &[u8]) -> Vec<u8> {
         let mut frame = Vec::with_capacity(PROTOCOL_HEADER.len() + data.len());
         frame.extend_from_slice(&PROTOCOL_HEADER);
         frame.extend_from_slice(data);
         frame
     }
 
+    /// 控制命令 (不含协议头)
+    ///
+    /// `target` 为通道号 1-12，或 0x11 延时开 / 0x00 延时关 / 0x12 一键开 / 0x10 一键关
+    pub(crate) fn control_command(&self, on: bool, target: u8) -> [u8; 8] {
+        [
+            self.device_address,
+            FUNC_CONTROL,
+            0x00,
+            0x00,
+            0x00,
+            if on { 0x01 } else { 0x00 },
+            target,
+            RESP_SUCCESS,
+        ]
+    }
+
     /// 发送命令并接收响应
     async fn send_command(&self, command: &[u8]) -> Result<Vec<u8>> {
         let mut stream = self.connect().await?;
@@ -167,18 +183,9 @@ impl HsPowerSequencerProtocol {
             return Err(DeviceError::Other("通道号必须在1-12之间".to_string()).into());
         }
 
-        let command = [
-            self.device_address,
-            FUNC_CONTROL,
-            0x00,
-            0x00,
-            0x00,
-            0x01,
-            channel,
-            RESP_SUCCESS,
-        ];
-
-        let response = self.send_command(&command).await?;
+        let response = self
+            .send_command(&self.control_command(true, channel))
+            .await?;
 
         // 检查响应
         if response[0] == self.device_address && response[1] == RESP_SUCCESS {
@@ -198,18 +205,9 @@ impl HsPowerSequencerProtocol {
             return Err(DeviceError::Other("通道号必须在1-12之间".to_string()).into());
         }
 
-        let command = [
-            self.device_address,
-            FUNC_CONTROL,
-            0x00,
-            0x00,
-            0x00,
-            0x00,
-            channel,
-            RESP_SUCCESS,
-        ];
-
-        let response = self.send_command(&command).await?;
+        let response = self
+            .send_command(&self.control_command(false, channel))
+            .await?;
 
         if response[0] == self.device_address && response[1] == RESP_SUCCESS {
             info!("通道 {} 关闭成功", channel);
@@ -224,70 +222,46 @@ impl HsPowerSequencerProtocol {
 
     /// 延时开
     pub async fn delayed_on(&self) -> Result<bool> {
-        let command = [
-            self.device_address,
-            FUNC_CONTROL,
-            0x00,
-            0x00,
-            0x00,
-            0x01,
-            0x11,
-            RESP_SUCCESS,
-        ];
-        let response = self.send_command(&command).await?;
+        let response = self.send_command(&self.control_command(true, 0x11)).await?;
         Ok(response[1] == RESP_SUCCESS)
     }
 
     /// 延时关
     pub async fn delayed_off(&self) -> Result<bool> {
-        let command = [
-            self.device_address,
-            FUNC_CONTROL,
-            0x00,
-            0x00,
-            0x00,
-            0x00,
-            0x00,
-            RESP_SUCCESS,
-        ];
-        let response = self.send_command(&command).await?;
+        let response = self
+            .send_command(&self.control_command(false, 0x00))
+            .await?;
         Ok(response[1] == RESP_SUCCESS)
     }
 
     /// 一键开
     pub async fn all_on(&self) -> Result<bool> {
-        let command = [
-            self.device_address,
-            FUNC_CONTROL,
-            0x00,
-            0x00,
-            0x00,
-            0x01,
-            0x12,
-            RESP_SUCCESS,
-        ];
-        let response = self.send_command(&command).await?;
+        let response = self.send_command(&self.control_command(true, 0x12)).await?;
         Ok(response[1] == RESP_SUCCESS)
     }
 
     /// 一键关
     pub async fn all_off(&self) -> Result<bool> {
-        let command = [
-            self.device_address,
-            FUNC_CONTROL,
-            0x00,
-            0x00,
-            0x00,
-            0x00,
-            0x10,
-            RESP_SUCCESS,
-        ];
-        let response = self.send_command(&command).await?;
+        let response = self
+            .send_command(&self.control_command(false, 0x10))
+            .await?;
         Ok(response[1] == RESP_SUCCESS)
     }
 
     /// 设置通道延时参数 (单位: ms)
     pub async fn set_channel_delay(&self, channel: u8, delay_ms: u32, is_on: bool) -> Result<bool> {
+        let command = self.channel_delay_command(channel, delay_ms, is_on)?;
+        let response = self.send_command(&command).await?;
+        Ok(response[1] == RESP_SUCCESS)
+    }
+
+    /// 设置通道延时参数的命令 (不含协议头)
+    pub(crate) fn channel_delay_command(
+        &self,
+        channel: u8,
+        delay_ms: u32,
+        is_on: bool,
+    ) -> Result<[u8; 8]> {
         if channel < 1 || channel > 12 {
             return Err(DeviceError::Other("通道号必须在1-12之间".to_string()).into());
         }
@@ -328,7 +302,7 @@ impl HsPowerSequencerProtocol {
             }
         };
 
-        let command = [
+        Ok([
             self.device_address,
             FUNC_WRITE_PARAM,
             (addr >> 8) as u8,
@@ -337,10 +311,7 @@ impl HsPowerSequencerProtocol {
             ((delay_ms >> 16) & 0xFF) as u8,
             ((delay_ms >> 8) & 0xFF) as u8,
             (delay_ms & 0xFF) as u8,
-        ];
-
-        let response = self.send_command(&command).await?;
-        Ok(response[1] == RESP_SUCCESS)
+        ])
     }
 
     /// 读取设备状态 (返回各通道状态)
@@ -357,8 +328,15 @@ impl HsPowerSequencerProtocol {
         ];
 
         let response = self.send_command(&command).await?;
+        let status = Self::parse_device_status(&response);
+        debug!("设备状态: {:?}", status);
+        Ok(status)
+    }
 
-        // 响应格式: [addr, func, addr_hi, addr_lo, ch1, ch2, ..., chN]
+    /// 解析设备状态响应
+    ///
+    /// 响应格式: [addr, func, addr_hi, addr_lo, ch1, ch2, ..., chN]
+    pub(crate) fn parse_device_status(response: &[u8]) -> Vec<bool> {
         let mut status = Vec::new();
         for i in 4..response.len() {
             if response[i] == 0x01 {
@@ -367,9 +345,7 @@ impl HsPowerSequencerProtocol {
                 status.push(false); // 关闭
             }
         }
-
-        debug!("设备状态: {:?}", status);
-        Ok(status)
+        status
     }
 
     /// 设置设备时间
@@ -382,6 +358,21 @@ impl HsPowerSequencerProtocol {
         minute: u8,
         second: u8,
     ) -> Result<bool> {
+        let command = self.set_time_command(year, month, day, hour, minute, second);
+        let response = self.send_command(&command).await?;
+        Ok(response[1] == FUNC_SET_TIME)
+    }
+
+    /// 设置设备时间的命令 (BCD 编码，不含协议头)
+    pub(crate) fn set_time_command(
+        &self,
+        year: u8,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> [u8; 8] {
         // 转换为BCD码
         let year_bcd = ((year / 10) << 4) | (year % 10);
         let month_bcd = ((month / 10) << 4) | (month % 10);
@@ -390,7 +381,7 @@ impl HsPowerSequencerProtocol {
         let minute_bcd = ((minute / 10) << 4) | (minute % 10);
         let second_bcd = ((second / 10) << 4) | (second % 10);
 
-        let command = [
+        [
             self.device_address,
             FUNC_SET_TIME,
             year_bcd,
@@ -399,10 +390,7 @@ impl HsPowerSequencerProtocol {
             hour_bcd,
             minute_bcd,
             second_bcd,
-        ];
-
-        let response = self.send_command(&command).await?;
-        Ok(response[1] == FUNC_SET_TIME)
+        ]
     }
 
     /// 读取设备地址
@@ -747,3 +735,41 @@ impl Protocol for HsPowerSequencerProtocol {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::fixtures::Captures;
+
+    #[test]
+    fn frames_match_golden_captures() {
+        let captures = Captures::load("hs_power_sequencer");
+        let device = HsPowerSequencerProtocol::new("/dev/null".to_string(), 9600, 0x01);
+        let frame = |command: &[u8]| device.build_frame(command);
+
+        captures.assert_frame("channel3_on", &frame(&device.control_command(true, 3)));
+        captures.assert_frame("channel12_off", &frame(&device.control_command(false, 12)));
+        captures.assert_frame("delayed_on", &frame(&device.control_command(true, 0x11)));
+        captures.assert_frame("delayed_off", &frame(&device.control_command(false, 0x00)));
+        captures.assert_frame("all_on", &frame(&device.control_command(true, 0x12)));
+        captures.assert_frame("all_off", &frame(&device.control_command(false, 0x10)));
+        captures.assert_frame(
+            "channel2_on_delay_1500ms",
+            &frame(&device.channel_delay_command(2, 1500, true).unwrap()),
+        );
+        captures.assert_frame(
+            "channel9_off_delay_70000ms",
+            &frame(&device.channel_delay_command(9, 70000, false).unwrap()),
+        );
+        assert!(device.channel_delay_command(13, 0, true).is_err());
+        captures.assert_frame(
+            "set_time_2024_12_31_23_59_08",
+            &frame(&device.set_time_command(24, 12, 31, 23, 59, 8)),
+        );
+
+        assert_eq!(
+            HsPowerSequencerProtocol::parse_device_status(captures.frame("status_response")),
+            [true, false, true, true]
+        );
+    }
+}
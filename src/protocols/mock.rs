@@ -7,6 +7,7 @@
 //! - 延迟模拟
 //! - 错误模拟
 //! - 自定义方法调用
+//! - 内容推送（只校验并记录文件信息，不保存文件内容）
 //!
 //! # 配置示例
 //! ```json
@@ -14,6 +15,8 @@
 //!   "type": "mock",
 //!   "delay_ms": 100,        // 可选，模拟延迟（毫秒）
 //!   "error_rate": 0.0,      // 可选，错误率（0.0-1.0）
+//!   "content_chunk_size": 65536, // 可选，内容推送分块大小（字节）
+//!   "persist": true,        // 可选，是否持久化到 data/mock_storage（默认 true）
 //!   "initial_values": {     // 可选，初始值
 //!     "1": 100,
 //!     "2": 200
@@ -38,15 +41,17 @@
 //! - `simulate_fault`: 模拟设备故障
 //! - `clear_fault`: 清除故障状态
 //! - `get_statistics`: 获取统计信息
+//! - `get_contents`: 获取已推送的内容（文件名、大小、SHA-256）
 
 use async_trait::async_trait;
 use rand::Rng;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokio::time::{sleep, Duration};
 
-use crate::protocols::Protocol;
+use crate::protocols::{ContentInfo, Protocol};
 use crate::utils::{DeviceError, Result};
 
 /// Mock 协议内部状态
@@ -56,6 +61,8 @@ struct MockState {
     values: HashMap<u32, i32>,
     /// 任意 JSON 对象存储（字符串 key -> JSON value）
     json_store: HashMap<String, Value>,
+    /// 已推送的内容（文件名 -> 文件信息），不持久化
+    contents: HashMap<String, Value>,
     /// 是否处于故障状态
     fault: bool,
     /// 统计信息
@@ -69,6 +76,7 @@ impl MockState {
         Self {
             values: HashMap::new(),
             json_store: HashMap::new(),
+            contents: HashMap::new(),
             fault: false,
             read_count: 0,
             write_count: 0,
@@ -77,12 +85,27 @@ impl MockState {
     }
 }
 
+/// 进行中的内容推送
+struct MockUpload {
+    info: ContentInfo,
+    hasher: Sha256,
+    received: u64,
+}
+
+/// 默认内容推送分块大小
+const DEFAULT_CONTENT_CHUNK_SIZE: usize = 64 * 1024;
+
 /// Mock 协议实现
 pub struct MockProtocol {
     channel_id: u32,
     delay_ms: u64,
     error_rate: f64,
+    content_chunk_size: usize,
+    /// 是否把状态持久化到 data/mock_storage
+    persist: bool,
     state: Arc<Mutex<MockState>>,
+    uploads: Mutex<HashMap<String, MockUpload>>,
+    next_upload: u64,
 }
 
 impl MockProtocol {
@@ -92,7 +115,11 @@ impl MockProtocol {
             channel_id,
             delay_ms: 0,
             error_rate: 0.0,
+            content_chunk_size: DEFAULT_CONTENT_CHUNK_SIZE,
+            persist: true,
             state: Arc::new(Mutex::new(MockState::new())),
+            uploads: Mutex::new(HashMap::new()),
+            next_upload: 0,
         }
     }
 
@@ -139,6 +166,9 @@ impl MockProtocol {
 
     /// 将全部状态保存到磁盘
     fn save_to_disk(&self) {
+        if !self.persist {
+            return;
+        }
         let file_path = self.get_storage_path();
 
         // 确保目录存在
@@ -185,6 +215,9 @@ impl MockProtocol {
 
     /// 从磁盘恢复持久化数据到内存
     fn restore_from_storage(&self) {
+        if !self.persist {
+            return;
+        }
         let file_path = self.get_storage_path();
 
         let data: Value = match std::fs::read_to_string(&file_path) {
@@ -254,6 +287,14 @@ impl Protocol for MockProtocol {
             }
         }
 
+        if let Some(size) = params.get("content_chunk_size").and_then(|v| v.as_u64()) {
+            protocol.content_chunk_size = (size as usize).max(1);
+        }
+
+        if let Some(persist) = params.get("persist").and_then(|v| v.as_bool()) {
+            protocol.persist = persist;
+        }
+
         // 解析初始值
         if let Some(initial_values) = params.get("initial_values") {
             if let Some(obj) = initial_values.as_object() {
@@ -531,6 +572,100 @@ impl Protocol for MockProtocol {
         "mock"
     }
 
+    /// 原样回显发送的数据
+    async fn send_raw(&mut self, payload: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        tokio::time::timeout(timeout, self.simulate_delay())
+            .await
+            .map_err(|_| DeviceError::Timeout)?;
+        self.check_fault()?;
+        Ok(payload.to_vec())
+    }
+
+    fn content_chunk_size(&self) -> Option<usize> {
+        Some(self.content_chunk_size)
+    }
+
+    async fn begin_content_upload(&mut self, info: &ContentInfo) -> Result<String> {
+        self.simulate_delay().await;
+        self.check_fault()?;
+
+        self.next_upload += 1;
+        let session = format!("upload-{}", self.next_upload);
+        self.uploads.lock().unwrap().insert(
+            session.clone(),
+            MockUpload {
+                info: info.clone(),
+                hasher: Sha256::new(),
+                received: 0,
+            },
+        );
+        tracing::debug!(
+            "Mock [通道{}] 开始接收内容 {} ({} 字节)",
+            self.channel_id,
+            info.name,
+            info.size
+        );
+        Ok(session)
+    }
+
+    async fn upload_content_chunk(
+        &mut self,
+        session: &str,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        self.simulate_delay().await;
+        self.check_fault()?;
+        if self.should_simulate_error() {
+            self.record_error();
+            return Err(DeviceError::Other("模拟的分块上传错误".to_string()));
+        }
+
+        let mut uploads = self.uploads.lock().unwrap();
+        let upload = uploads
+            .get_mut(session)
+            .ok_or_else(|| DeviceError::Other(format!("上传会话 {} 不存在", session)))?;
+        // 重试已接收的分块时直接确认
+        if offset + data.len() as u64 <= upload.received {
+            return Ok(());
+        }
+        if offset != upload.received {
+            return Err(DeviceError::Other(format!(
+                "分块偏移 {} 不连续，已接收 {} 字节",
+                offset, upload.received
+            )));
+        }
+        upload.hasher.update(data);
+        upload.received += data.len() as u64;
+        Ok(())
+    }
+
+    async fn finish_content_upload(&mut self, session: &str) -> Result<Option<String>> {
+        self.simulate_delay().await;
+        let upload = self
+            .uploads
+            .lock()
+            .unwrap()
+            .remove(session)
+            .ok_or_else(|| DeviceError::Other(format!("上传会话 {} 不存在", session)))?;
+        let sha256 = hex::encode(upload.hasher.finalize());
+
+        let mut state = self.state.lock().unwrap();
+        state.contents.insert(
+            upload.info.name.clone(),
+            json!({
+                "material_id": upload.info.material_id,
+                "size": upload.received,
+                "sha256": sha256,
+            }),
+        );
+        Ok(Some(sha256))
+    }
+
+    async fn abort_content_upload(&mut self, session: &str) {
+        self.uploads.lock().unwrap().remove(session);
+    }
+
     async fn call_method(&mut self, method_name: &str, args: Value) -> Result<Value> {
         self.simulate_delay().await;
 
@@ -562,6 +697,13 @@ impl Protocol for MockProtocol {
                     "total_operations": state.read_count + state.write_count
                 }))
             }
+            "get_contents" => {
+                let state = self.state.lock().unwrap();
+                Ok(json!({
+                    "count": state.contents.len(),
+                    "data": state.contents
+                }))
+            }
             "set_delay" => {
                 if let Some(delay) = args.get("delay_ms").and_then(|v| v.as_u64()) {
                     self.delay_ms = delay;
@@ -610,6 +752,7 @@ impl Protocol for MockProtocol {
             "simulate_fault".to_string(),
             "clear_fault".to_string(),
             "get_statistics".to_string(),
+            "get_contents".to_string(),
             "set_delay".to_string(),
             "get_value".to_string(),
             "set_value".to_string(),
@@ -814,4 +957,44 @@ mod tests {
         let result = protocol.execute("get_all_json", json!({})).await.unwrap();
         assert_eq!(result["count"], 0);
     }
+
+    #[tokio::test]
+    async fn test_mock_content_upload() {
+        let mut protocol = MockProtocol::new(1);
+        let data = b"hello content push";
+        let info = ContentInfo {
+            material_id: "m1".to_string(),
+            name: "hello.txt".to_string(),
+            mime_type: "text/plain".to_string(),
+            size: data.len() as u64,
+            sha256: hex::encode(Sha256::digest(data)),
+        };
+
+        let session = protocol.begin_content_upload(&info).await.unwrap();
+        protocol
+            .upload_content_chunk(&session, 0, &data[..5])
+            .await
+            .unwrap();
+        // 重复的分块直接确认，跳过的分块拒绝
+        protocol
+            .upload_content_chunk(&session, 0, &data[..5])
+            .await
+            .unwrap();
+        assert!(protocol
+            .upload_content_chunk(&session, 10, &data[10..])
+            .await
+            .is_err());
+        protocol
+            .upload_content_chunk(&session, 5, &data[5..])
+            .await
+            .unwrap();
+
+        let sha256 = protocol.finish_content_upload(&session).await.unwrap();
+        assert_eq!(sha256.as_deref(), Some(info.sha256.as_str()));
+        let contents = protocol
+            .call_method("get_contents", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(contents["data"]["hello.txt"]["size"], data.len());
+    }
 }
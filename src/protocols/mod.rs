@@ -1,7 +1,106 @@
+use crate::device::DeviceEvent;
 use crate::utils::Result;
 use async_trait::async_trait;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// 协议事件发送器
+///
+/// 协议在 from_config 中创建（此时尚无事件通道），由 ChannelManager 在通道创建后
+/// 通过 [`Protocol::set_event_sender`] 注入。可克隆到后台任务中使用。
+#[derive(Clone, Default)]
+pub struct EventSink {
+    channel_id: u32,
+    tx: Arc<RwLock<Option<broadcast::Sender<DeviceEvent>>>>,
+}
+
+impl EventSink {
+    pub fn new(channel_id: u32) -> Self {
+        Self {
+            channel_id,
+            tx: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 绑定事件通道
+    pub fn attach(&self, tx: broadcast::Sender<DeviceEvent>) {
+        *self.tx.write().unwrap() = Some(tx);
+    }
+
+    /// 发送协议事件（未绑定时忽略）
+    pub fn emit(&self, event: &str, data: Value) {
+        if let Some(tx) = self.tx.read().unwrap().as_ref() {
+            let _ = tx.send(DeviceEvent::ProtocolEvent {
+                channel_id: self.channel_id,
+                event: event.to_string(),
+                data,
+            });
+        }
+    }
+
+    /// 发送通道连接恢复事件
+    pub fn connected(&self) {
+        if let Some(tx) = self.tx.read().unwrap().as_ref() {
+            let _ = tx.send(DeviceEvent::ChannelConnected {
+                channel_id: self.channel_id,
+            });
+        }
+    }
+
+    /// 发送通道连接断开事件
+    pub fn disconnected(&self, reason: impl Into<String>) {
+        if let Some(tx) = self.tx.read().unwrap().as_ref() {
+            let _ = tx.send(DeviceEvent::ChannelDisconnected {
+                channel_id: self.channel_id,
+                reason: reason.into(),
+            });
+        }
+    }
+}
+
+/// 后台轮询暂停开关
+///
+/// 协议在 from_config 中创建并克隆给轮询任务，框架在通道进入计划离线时段时
+/// 通过 [`Protocol::set_polling_paused`] 暂停轮询。
+#[derive(Clone, Default)]
+pub struct PollGate {
+    paused: Arc<AtomicBool>,
+}
+
+impl PollGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置暂停状态
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// 轮询是否已暂停
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+}
+
+/// 内容推送的文件信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentInfo {
+    /// 素材 ID
+    pub material_id: String,
+    /// 文件名（素材原始文件名）
+    pub name: String,
+    pub mime_type: String,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 文件 SHA-256（小写十六进制）
+    pub sha256: String,
+}
 
 /// 协议trait定义
 ///
@@ -75,10 +174,118 @@ pub trait Protocol: Send + Sync {
     fn get_methods(&self) -> Vec<String> {
         vec![]
     }
+
+    /// 注入设备事件发送器
+    ///
+    /// # 默认实现
+    /// 忽略，需要上报事件的协议应保存到自身的 [`EventSink`]
+    fn set_event_sender(&mut self, _event_tx: broadcast::Sender<DeviceEvent>) {}
+
+    /// 暂停 / 恢复后台轮询（通道计划离线时段）
+    ///
+    /// # 默认实现
+    /// 忽略，有后台轮询任务的协议应转发到自身的 [`PollGate`]
+    fn set_polling_paused(&self, _paused: bool) {}
+
+    /// 当前打开的设备连接数（诊断用）
+    ///
+    /// # 默认实现
+    /// 返回 None（不统计），维持长连接的协议应返回实际连接数
+    fn open_connections(&self) -> Option<usize> {
+        None
+    }
+
+    /// 命令优先级队列统计（诊断用）
+    ///
+    /// # 默认实现
+    /// 返回 None，按 [`CommandQueue`] 排队访问设备链路的协议应返回其统计
+    fn command_queue_stats(&self) -> Option<CommandQueueStats> {
+        None
+    }
+
+    /// 通过通道的传输层发送原始数据并返回设备原始响应（调试控制台用）
+    ///
+    /// # 默认实现
+    /// 返回错误，协议可以选择性实现此方法
+    async fn send_raw(&mut self, _payload: &[u8], _timeout: Duration) -> Result<Vec<u8>> {
+        Err(crate::utils::DeviceError::Other(format!(
+            "协议 {} 不支持发送原始数据",
+            self.name()
+        )))
+    }
+
+    /// 内容推送的分块大小（字节）
+    ///
+    /// # 默认实现
+    /// 返回 None（不支持内容推送）；支持文件上传的协议应返回设备接受的分块大小，
+    /// 并实现 begin / upload / finish / abort 四个方法
+    fn content_chunk_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// 开始内容推送，返回设备端的上传会话标识
+    async fn begin_content_upload(&mut self, _info: &ContentInfo) -> Result<String> {
+        Err(crate::utils::DeviceError::Other(format!(
+            "协议 {} 不支持内容推送",
+            self.name()
+        )))
+    }
+
+    /// 上传一个分块，`offset` 为分块在文件中的偏移，分块按顺序上传
+    async fn upload_content_chunk(
+        &mut self,
+        _session: &str,
+        _offset: u64,
+        _data: &[u8],
+    ) -> Result<()> {
+        Err(crate::utils::DeviceError::Other(format!(
+            "协议 {} 不支持内容推送",
+            self.name()
+        )))
+    }
+
+    /// 完成内容推送，返回设备计算的 SHA-256（设备无法提供时返回 None）
+    async fn finish_content_upload(&mut self, _session: &str) -> Result<Option<String>> {
+        Err(crate::utils::DeviceError::Other(format!(
+            "协议 {} 不支持内容推送",
+            self.name()
+        )))
+    }
+
+    /// 放弃未完成的内容推送，清理设备端的临时文件
+    ///
+    /// # 默认实现
+    /// 无操作
+    async fn abort_content_upload(&mut self, _session: &str) {}
+
+    /// 停止协议的后台任务（轮询、监听等）
+    ///
+    /// 通道删除或配置热重载时调用。
+    ///
+    /// # 默认实现
+    /// 无操作，启动了后台任务的协议应通过自身的 [`TaskRegistry`](crate::utils::tasks::TaskRegistry) 启动并在此关闭
+    async fn shutdown(&self) {}
+
+    /// 暂时释放独占资源（设备长连接、本地监听端口），保留实例
+    ///
+    /// 独占资源驱动热替换时在验证新配置前调用，验证失败后调用 [`Self::resume`] 继续使用本实例。
+    ///
+    /// # 默认实现
+    /// 无操作，适用于每次请求临时连接设备的协议
+    async fn release(&self) {}
+
+    /// 重新获取 [`Self::release`] 释放的资源
+    ///
+    /// # 默认实现
+    /// 无操作
+    async fn resume(&self) {}
 }
 
+pub mod command_queue;
 pub mod computer_control;
 pub mod custom;
+#[cfg(test)]
+pub(crate) mod fixtures;
 pub mod hs_power_sequencer;
 pub mod mock;
 pub mod modbus;
@@ -90,11 +297,13 @@ pub mod screen_njlg_plc;
 pub mod splicer_3d;
 pub mod storage;
 pub mod tpris_pdu;
+pub mod wdy_8en;
 pub mod xfusion;
+mod xfusion_agent;
 pub mod xinke_q1;
-pub mod wdy_8en;
 pub mod yk_vap;
 
+pub use command_queue::{CommandPriority, CommandQueue, CommandQueueStats};
 pub use computer_control::ComputerControlProtocol;
 pub use custom::CustomProtocol;
 pub use hs_power_sequencer::HsPowerSequencerProtocol;
@@ -1,14 +1,27 @@
 use async_trait::async_trait;
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, Mutex, MutexGuard, RwLock};
+use tokio_modbus::client::Client;
 use tokio_modbus::prelude::*;
-use tracing::{debug, info, warn};
-
-use crate::config::AutoCallConfig;
-use crate::protocols::Protocol;
-use crate::utils::{DeviceError, Result};
+use tokio_modbus::{Request, Response};
+use tracing::{debug, info, trace, warn};
+
+use crate::config::{AdaptivePollConfig, AutoCallConfig};
+use crate::device::DeviceEvent;
+use crate::protocols::command_queue::{CommandPermit, DEFAULT_PRIORITY_AGING};
+use crate::protocols::{
+    CommandPriority, CommandQueue, CommandQueueStats, EventSink, PollGate, Protocol,
+};
+use crate::utils::net::{self, KeepaliveConfig};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::{dns, time, DeviceError, Result};
 
 /// Modbus 数据类型
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -74,94 +87,756 @@ impl ModbusDataType {
     }
 }
 
+/// 多寄存器值的字序（A 为最高字节，按寄存器在设备中的先后排列）
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum WordOrder {
+    /// 大端（默认）
+    #[default]
+    Abcd,
+    /// 字交换：低位寄存器在前
+    Cdab,
+    /// 字内字节交换
+    Badc,
+    /// 小端：字交换且字内字节交换
+    Dcba,
+}
+
+/// 从字符串解析字序（不区分大小写）
+impl std::str::FromStr for WordOrder {
+    type Err = DeviceError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "ABCD" => Ok(Self::Abcd),
+            "CDAB" => Ok(Self::Cdab),
+            "BADC" => Ok(Self::Badc),
+            "DCBA" => Ok(Self::Dcba),
+            _ => Err(DeviceError::ConfigError(format!(
+                "不支持的字序: {}，应为 ABCD / CDAB / BADC / DCBA",
+                s
+            ))),
+        }
+    }
+}
+
+/// 寄存器排列：字序与附加的字内字节交换
+///
+/// 16 位类型只受字内字节交换影响（BADC / DCBA 或 byte_swap）；64 位类型的字交换为
+/// 四个寄存器整体倒序。
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RegisterOrder {
+    pub word_order: WordOrder,
+    /// 在字序基础上再交换每个寄存器的两个字节
+    pub byte_swap: bool,
+}
+
+impl RegisterOrder {
+    /// 从命令参数 `word_order` / `byte_swap` 解析，未指定时为 ABCD
+    pub fn from_params(params: &Value) -> Result<Self> {
+        Ok(Self {
+            word_order: params
+                .get("word_order")
+                .and_then(|v| v.as_str())
+                .map(str::parse::<WordOrder>)
+                .transpose()?
+                .unwrap_or_default(),
+            byte_swap: params
+                .get("byte_swap")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        })
+    }
+
+    /// 在设备排列与标准大端排列之间转换（变换自逆，读写共用）
+    fn apply(self, registers: &[u16]) -> Vec<u16> {
+        let swap_words = matches!(self.word_order, WordOrder::Cdab | WordOrder::Dcba);
+        let swap_bytes =
+            matches!(self.word_order, WordOrder::Badc | WordOrder::Dcba) != self.byte_swap;
+        let mut ordered = registers.to_vec();
+        if swap_words {
+            ordered.reverse();
+        }
+        if swap_bytes {
+            ordered.iter_mut().for_each(|r| *r = r.swap_bytes());
+        }
+        ordered
+    }
+}
+
+/// 缓存过期后的读取策略
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StalePolicy {
+    /// 直接返回过期数据（标记 stale）
+    ServeStale,
+    /// 重新从设备读取，设备读取失败时返回过期数据（标记 stale）
+    RefreshOnStale,
+    /// 重新从设备读取，设备读取失败时返回错误
+    ErrorOnStale,
+}
+
+/// 从配置字符串解析缓存过期策略
+impl std::str::FromStr for StalePolicy {
+    type Err = DeviceError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "serve_stale" => Ok(Self::ServeStale),
+            "refresh_on_stale" => Ok(Self::RefreshOnStale),
+            "error_on_stale" => Ok(Self::ErrorOnStale),
+            _ => Err(DeviceError::ConfigError(format!(
+                "不支持的缓存过期策略: {} (可选 serve_stale / refresh_on_stale / error_on_stale)",
+                s
+            ))),
+        }
+    }
+}
+
+/// 自动召唤连续失败时的最大退避间隔
+const AUTO_CALL_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// 建立 TCP 连接的超时时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 心跳请求的响应超时，超时视为连接已失效
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 读写请求的响应超时，超时视为连接已失效（半开连接、网关下从站无响应等）
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 单次请求最多读取的寄存器 / 线圈数量（Modbus 协议限制）
+const MAX_READ_REGISTERS: u16 = 125;
+const MAX_READ_BITS: u16 = 2000;
+
+/// 封装接口传输功能码（0x2B）及读设备标识 MEI 类型（0x0E）
+const FC_ENCAPSULATED_INTERFACE: u8 = 0x2B;
+const MEI_READ_DEVICE_ID: u8 = 0x0E;
+
+/// 读取设备标识时最多分页请求次数（防止设备错误地无限返回后续标志）
+const MAX_DEVICE_ID_REQUESTS: usize = 8;
+
+/// 数据缓存：(从站地址, 寄存器地址) -> (值, 数据类型, 时间戳)
+type ModbusCache = HashMap<(u8, u16), (Value, String, std::time::Instant)>;
+
+/// Modbus TCP 链路
+///
+/// 自动召唤与读写请求共用一条持久连接，使用时独占，按 [`CommandQueue`] 的优先级排队；
+/// 传输层出错后标记失效，下次使用时重新建立连接。同一网关下的多个从站共用该连接，
+/// 每次使用时切换从站地址。连接断开 / 恢复时发送通道事件。可克隆到后台任务中使用。
+#[derive(Clone)]
+struct ModbusLink {
+    addr: String,
+    port: u16,
+    ctx: Arc<Mutex<Option<client::Context>>>,
+    queue: CommandQueue,
+    broken: Arc<AtomicBool>,
+    /// 最近一次已知的连接状态（初始视为已连接，与通道创建时的事件一致）
+    connected: Arc<AtomicBool>,
+    /// 最近一次使用连接的时间（心跳仅在空闲时发送）
+    last_used: Arc<std::sync::Mutex<std::time::Instant>>,
+    keepalive: KeepaliveConfig,
+    events: EventSink,
+}
+
+/// 持有中的连接，释放前其他请求等待
+struct LinkGuard<'a> {
+    ctx: MutexGuard<'a, Option<client::Context>>,
+    /// 释放时把连接移交给队列中的下一个请求
+    _permit: CommandPermit,
+}
+
+impl Deref for LinkGuard<'_> {
+    type Target = client::Context;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx.as_ref().expect("连接已建立")
+    }
+}
+
+impl DerefMut for LinkGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx.as_mut().expect("连接已建立")
+    }
+}
+
+impl ModbusLink {
+    fn new(
+        addr: String,
+        port: u16,
+        keepalive: KeepaliveConfig,
+        queue: CommandQueue,
+        events: EventSink,
+    ) -> Self {
+        Self {
+            addr,
+            port,
+            ctx: Arc::new(Mutex::new(None)),
+            queue,
+            broken: Arc::new(AtomicBool::new(false)),
+            connected: Arc::new(AtomicBool::new(true)),
+            last_used: Arc::new(std::sync::Mutex::new(std::time::Instant::now())),
+            keepalive,
+            events,
+        }
+    }
+
+    /// 按优先级排队获取连接并切换到指定从站，尚未连接或上次出错时重新建立
+    async fn acquire(&self, slave_id: u8, priority: CommandPriority) -> Result<LinkGuard<'_>> {
+        let permit = self.queue.acquire(priority).await;
+        let mut guard = self.ctx.lock().await;
+        if self.broken.swap(false, Ordering::Relaxed) {
+            *guard = None;
+        }
+        if guard.is_none() {
+            let ctx = match self.connect(slave_id).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    self.set_disconnected(&e.to_string());
+                    return Err(e);
+                }
+            };
+            if !self.connected.swap(true, Ordering::Relaxed) {
+                self.events.connected();
+            }
+            *guard = Some(ctx);
+        }
+        *self.last_used.lock().unwrap() = std::time::Instant::now();
+        let mut guard = LinkGuard {
+            ctx: guard,
+            _permit: permit,
+        };
+        guard.set_slave(Slave(slave_id));
+        Ok(guard)
+    }
+
+    /// 建立 TCP 连接并开启保活
+    async fn connect(&self, slave_id: u8) -> Result<client::Context> {
+        let addr = net::display_addr(&self.addr, self.port);
+        debug!("连接到 Modbus TCP 服务器: {}", addr);
+        let socket_addr = dns::resolve(&self.addr, self.port).await?;
+        let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(socket_addr))
+            .await
+            .map_err(|_| DeviceError::Timeout)?
+            .map_err(|e| DeviceError::ConnectionError(format!("Modbus TCP 连接失败: {}", e)))?;
+        if let Err(e) = self.keepalive.apply(&stream) {
+            warn!("Modbus TCP 连接 {} 开启保活失败: {}", addr, e);
+        }
+        info!("Modbus TCP 连接成功: {}", addr);
+        Ok(tcp::attach_slave(stream, Slave(slave_id)))
+    }
+
+    /// 标记连接失效，下次使用时重新连接
+    fn mark_broken(&self, reason: &str) {
+        self.broken.store(true, Ordering::Relaxed);
+        self.set_disconnected(reason);
+    }
+
+    /// 关闭当前连接，下次使用时重新连接
+    async fn release(&self) {
+        if self.ctx.lock().await.take().is_some() {
+            info!(
+                "Modbus TCP 连接 {} 已释放",
+                net::display_addr(&self.addr, self.port)
+            );
+        }
+    }
+
+    /// 由已连接变为断开时发送断开事件
+    fn set_disconnected(&self, reason: &str) {
+        if self.connected.swap(false, Ordering::Relaxed) {
+            warn!(
+                "Modbus TCP 连接 {} 断开: {}",
+                net::display_addr(&self.addr, self.port),
+                reason
+            );
+            self.events.disconnected(reason);
+        }
+    }
+
+    /// 当前是否持有连接（正在使用中视为已连接）
+    fn open_connections(&self) -> usize {
+        match self.ctx.try_lock() {
+            Ok(guard) => usize::from(guard.is_some() && !self.broken.load(Ordering::Relaxed)),
+            Err(_) => 1,
+        }
+    }
+
+    /// 连接空闲时长
+    fn idle_for(&self) -> Duration {
+        self.last_used.lock().unwrap().elapsed()
+    }
+
+    /// 应用层心跳：读取一个保持寄存器，超时或传输层出错时标记连接失效
+    ///
+    /// 设备返回 Modbus 异常响应同样说明链路正常。
+    async fn ping(&self, slave_id: u8, addr: u16) -> Result<()> {
+        let mut ctx = self.acquire(slave_id, CommandPriority::Poll).await?;
+        match tokio::time::timeout(PING_TIMEOUT, ctx.read_holding_registers(addr, 1)).await {
+            Ok(result) => {
+                let _ = result.map_err(self.io_error("心跳"))?;
+                Ok(())
+            }
+            Err(_) => {
+                drop(ctx);
+                self.mark_broken("心跳超时");
+                Err(DeviceError::Timeout)
+            }
+        }
+    }
+
+    /// 在持有的连接上执行一次请求
+    ///
+    /// 超时或传输层出错时标记连接失效，避免一个无响应的请求一直占用连接；
+    /// 设备返回的 Modbus 异常响应转换为协议错误。
+    async fn transact<T>(
+        &self,
+        op: &'static str,
+        request: impl std::future::Future<Output = tokio_modbus::Result<T>>,
+    ) -> Result<T> {
+        match tokio::time::timeout(REQUEST_TIMEOUT, request).await {
+            Ok(result) => result
+                .map_err(self.io_error(op))?
+                .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e))),
+            Err(_) => {
+                self.mark_broken(&format!("{}超时", op));
+                Err(DeviceError::Timeout)
+            }
+        }
+    }
+
+    /// 传输层错误映射：标记连接失效并转换为连接错误
+    fn io_error<E: std::fmt::Display>(&self, op: &'static str) -> impl FnOnce(E) -> DeviceError {
+        let link = self.clone();
+        move |e| {
+            let message = format!("{}失败: {}", op, e);
+            link.mark_broken(&message);
+            DeviceError::ConnectionError(message)
+        }
+    }
+}
+
+/// 自动召唤任务运行状态（通道状态中展示）
+#[derive(Debug, Clone)]
+struct AutoCallState {
+    config: AutoCallConfig,
+    consecutive_failures: u32,
+    backoff: Duration,
+    last_success: Option<std::time::Instant>,
+    last_error: Option<String>,
+    /// 最近一次召唤到的值发生变化的时间
+    last_change: Option<std::time::Instant>,
+}
+
+impl AutoCallState {
+    fn to_json(&self) -> Value {
+        serde_json::json!({
+            "function": self.config.function,
+            "slave_id": self.config.slave_id,
+            "start_addr": self.config.start_addr,
+            "count": self.config.count,
+            "interval_ms": self.config.interval_ms,
+            "consecutive_failures": self.consecutive_failures,
+            "backoff_ms": self.backoff.as_millis() as u64,
+            "adaptive": self.config.adaptive.is_some(),
+            "last_change_at": self.last_change.map(time::instant_rfc3339),
+            "last_success_at": self.last_success.map(time::instant_rfc3339),
+            "last_success_ms_ago": self.last_success.map(|t| t.elapsed().as_millis() as u64),
+            "last_error": self.last_error,
+        })
+    }
+}
+
+/// 自适应召唤的下一次间隔
+///
+/// 值变化时回到最短间隔；值未变化时按倍数放宽，不超过最长间隔。
+fn adaptive_interval(
+    adaptive: &AdaptivePollConfig,
+    base: Duration,
+    current: Duration,
+    changed: bool,
+) -> Duration {
+    let min = adaptive
+        .min_interval_ms
+        .map(Duration::from_millis)
+        .unwrap_or(base);
+    let max = Duration::from_millis(adaptive.max_interval_ms).max(min);
+    if changed {
+        return min;
+    }
+    current
+        .clamp(min, max)
+        .mul_f64(adaptive.factor.max(1.0))
+        .min(max)
+}
+
+/// 合并同一从站、同一功能码下重叠或相邻的召唤区间，合并后的间隔取最小值
+///
+/// 合并结果不超过单次请求的数量上限；自适应配置不同的区间不合并。
+fn merge_auto_calls(configs: &[AutoCallConfig]) -> Vec<AutoCallConfig> {
+    let mut sorted = configs.to_vec();
+    sorted.sort_by(|a, b| {
+        (a.slave_id, a.function.as_str(), a.start_addr).cmp(&(
+            b.slave_id,
+            b.function.as_str(),
+            b.start_addr,
+        ))
+    });
+
+    let mut merged: Vec<AutoCallConfig> = Vec::new();
+    for config in sorted {
+        if config.count == 0 {
+            continue;
+        }
+        if let Some(last) = merged.last_mut() {
+            let limit = match last.function.as_str() {
+                "coil" | "discrete" => MAX_READ_BITS,
+                _ => MAX_READ_REGISTERS,
+            };
+            let last_end = last.start_addr as u32 + last.count as u32;
+            let end = (config.start_addr as u32 + config.count as u32).max(last_end);
+            if last.slave_id == config.slave_id
+                && last.function == config.function
+                && last.adaptive == config.adaptive
+                && config.start_addr as u32 <= last_end
+                && end - last.start_addr as u32 <= limit as u32
+            {
+                last.count = (end - last.start_addr as u32) as u16;
+                last.interval_ms = last.interval_ms.min(config.interval_ms);
+                continue;
+            }
+        }
+        merged.push(config);
+    }
+    merged
+}
+
+/// 设备标识响应的一页
+#[derive(Debug, PartialEq)]
+struct DeviceIdPage {
+    conformity_level: u8,
+    /// 还有后续对象时为下一对象 ID
+    next_object_id: Option<u8>,
+    objects: Vec<(u8, String)>,
+}
+
+/// 解析读设备标识响应（功能码之后的数据）
+///
+/// 格式: MEI 类型, ReadDevId 码, 一致性等级, 后续标志, 下一对象 ID, 对象数量, {对象 ID, 长度, 值}...
+fn parse_device_identification(data: &[u8]) -> Result<DeviceIdPage> {
+    let truncated = || DeviceError::ProtocolError("设备标识响应长度不足".into());
+    if data.len() < 6 {
+        return Err(truncated());
+    }
+    if data[0] != MEI_READ_DEVICE_ID {
+        return Err(DeviceError::ProtocolError(format!(
+            "设备标识响应 MEI 类型无效: 0x{:02X}",
+            data[0]
+        )));
+    }
+
+    let count = data[5] as usize;
+    let mut objects = Vec::with_capacity(count);
+    let mut pos = 6;
+    for _ in 0..count {
+        let header = data.get(pos..pos + 2).ok_or_else(truncated)?;
+        let (id, len) = (header[0], header[1] as usize);
+        let value = data.get(pos + 2..pos + 2 + len).ok_or_else(truncated)?;
+        objects.push((
+            id,
+            String::from_utf8_lossy(value)
+                .trim_end_matches('\0')
+                .to_string(),
+        ));
+        pos += 2 + len;
+    }
+
+    Ok(DeviceIdPage {
+        conformity_level: data[2],
+        next_object_id: (data[3] == 0xFF).then_some(data[4]),
+        objects,
+    })
+}
+
+/// 设备标识对象名称（0x00-0x06 为标准对象，其余按 ID 命名）
+fn device_id_object_name(id: u8) -> String {
+    match id {
+        0x00 => "vendor_name".to_string(),
+        0x01 => "product_code".to_string(),
+        0x02 => "revision".to_string(),
+        0x03 => "vendor_url".to_string(),
+        0x04 => "product_name".to_string(),
+        0x05 => "model_name".to_string(),
+        0x06 => "user_application_name".to_string(),
+        other => format!("object_0x{:02X}", other),
+    }
+}
+
+/// 单次读取结果
+struct ReadOutcome {
+    value: Value,
+    /// 从设备读取的原始寄存器（线圈或缓存读取时为 None）
+    registers: Option<Vec<u16>>,
+    from_cache: bool,
+    stale: bool,
+}
+
+impl ReadOutcome {
+    fn cached(value: Value, stale: bool) -> Self {
+        Self {
+            value,
+            registers: None,
+            from_cache: true,
+            stale,
+        }
+    }
+}
+
 /// Modbus协议实现
 pub struct ModbusProtocol {
     channel_id: u32,
     addr: String,
     port: u16,
+    /// 默认从站地址（数据点 / 召唤区间未指定 slave_id 时使用）
     slave_id: u8,
-    /// 数据缓存：地址 -> (值, 数据类型, 时间戳)
-    cache: Arc<RwLock<HashMap<u16, (Value, String, std::time::Instant)>>>,
-    /// 自动召唤配置
+    /// 共享连接
+    link: ModbusLink,
+    /// 数据缓存
+    cache: Arc<RwLock<ModbusCache>>,
+    /// 自动召唤配置（已合并重叠区间）
     auto_call_configs: Vec<AutoCallConfig>,
+    /// 自动召唤运行状态，与 auto_call_configs 一一对应
+    auto_call_states: Arc<RwLock<Vec<AutoCallState>>>,
+    /// 自动召唤暂停开关
+    poll_gate: PollGate,
+    /// 缓存读取时在 trace 日志中输出完整缓存内容
+    cache_trace: bool,
+    /// 缓存有效期（None 表示永不过期）
+    cache_ttl: Option<std::time::Duration>,
+    /// 缓存过期后的读取策略
+    stale_policy: StalePolicy,
+    /// 后台任务
+    tasks: TaskRegistry,
+    /// 查询状态时读取设备标识（仅首次查询，结果缓存）
+    identify_on_status: bool,
+    /// 最近一次读取的设备标识
+    device_identification: Arc<RwLock<Option<Value>>>,
 }
 
 impl ModbusProtocol {
     pub fn new(addr: String, port: u16, slave_id: u8) -> Self {
         Self {
             channel_id: 0,
+            link: ModbusLink::new(
+                addr.clone(),
+                port,
+                KeepaliveConfig::default(),
+                CommandQueue::default(),
+                EventSink::new(0),
+            ),
             addr,
             port,
             slave_id,
             cache: Arc::new(RwLock::new(HashMap::new())),
             auto_call_configs: Vec::new(),
+            auto_call_states: Arc::new(RwLock::new(Vec::new())),
+            poll_gate: PollGate::new(),
+            tasks: TaskRegistry::new(),
+            cache_trace: false,
+            cache_ttl: None,
+            stale_policy: StalePolicy::RefreshOnStale,
+            identify_on_status: false,
+            device_identification: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 读取设备标识（功能码 0x2B / MEI 0x0E）
+    ///
+    /// `level` 为 ReadDevId 码：1 基本、2 常规、3 扩展。设备分多页返回时自动续读。
+    pub async fn read_device_identification(&self, slave_id: u8, level: u8) -> Result<Value> {
+        let mut ctx = self.link.acquire(slave_id, CommandPriority::Read).await?;
+        let mut identification = serde_json::Map::new();
+        let mut object_id = 0u8;
+
+        for _ in 0..MAX_DEVICE_ID_REQUESTS {
+            let request = Request::Custom(
+                FC_ENCAPSULATED_INTERFACE,
+                Cow::Owned(vec![MEI_READ_DEVICE_ID, level, object_id]),
+            );
+            let response = self
+                .link
+                .transact("读取设备标识", ctx.call(request))
+                .await?;
+            let page = match response {
+                Response::Custom(FC_ENCAPSULATED_INTERFACE, data) => {
+                    parse_device_identification(&data)?
+                }
+                other => {
+                    return Err(DeviceError::ProtocolError(format!(
+                        "设备标识响应无效: {:?}",
+                        other
+                    )))
+                }
+            };
+
+            identification.insert(
+                "conformity_level".to_string(),
+                serde_json::json!(page.conformity_level),
+            );
+            for (id, value) in page.objects {
+                identification.insert(device_id_object_name(id), Value::String(value));
+            }
+            match page.next_object_id {
+                Some(next) if next > object_id => object_id = next,
+                _ => break,
+            }
         }
+        drop(ctx);
+
+        let identification = Value::Object(identification);
+        *self.device_identification.write().await = Some(identification.clone());
+        Ok(identification)
     }
 
     /// 启动自动召唤任务
+    ///
+    /// 每个（合并后的）召唤区间一个任务，共用通道连接；连续失败时按指数退避延长间隔，
+    /// 最长 [`AUTO_CALL_MAX_BACKOFF`]，成功后恢复配置的间隔。
+    /// 配置了 `adaptive` 的区间成功后按值是否变化调整间隔，见 [`adaptive_interval`]。
     pub fn start_auto_call_tasks(&self) {
-        for config in &self.auto_call_configs {
-            let addr = self.addr.clone();
-            let port = self.port;
-            let slave_id = self.slave_id;
+        for (index, config) in self.auto_call_configs.iter().enumerate() {
+            let link = self.link.clone();
             let cache = Arc::clone(&self.cache);
+            let states = Arc::clone(&self.auto_call_states);
             let config = config.clone();
+            let slave_id = config.slave_id.unwrap_or(self.slave_id);
+            let poll_gate = self.poll_gate.clone();
+            let channel_id = self.channel_id;
+            let name = format!(
+                "modbus:{}:auto_call:{}:{}:{}",
+                self.channel_id, slave_id, config.function, config.start_addr
+            );
 
-            tokio::spawn(async move {
-                let mut interval =
-                    tokio::time::interval(std::time::Duration::from_millis(config.interval_ms));
+            self.tasks.spawn(name, async move {
+                let interval = Duration::from_millis(config.interval_ms);
+                let mut delay = interval;
 
                 loop {
-                    interval.tick().await;
-
-                    if let Err(e) =
-                        Self::auto_call_task(&addr, port, slave_id, &config, &cache).await
-                    {
-                        warn!(
-                            "自动召唤失败 (function={}, start_addr={}, count={}): {}",
-                            config.function, config.start_addr, config.count, e
-                        );
+                    tokio::time::sleep(delay).await;
+                    if poll_gate.is_paused() {
+                        continue;
+                    }
+
+                    let result = Self::auto_call_task(&link, slave_id, &config, &cache).await;
+
+                    let mut states = states.write().await;
+                    let Some(state) = states.get_mut(index) else {
+                        continue;
+                    };
+                    match result {
+                        Ok(changed) => {
+                            if changed {
+                                state.last_change = Some(std::time::Instant::now());
+                            }
+                            delay = match &config.adaptive {
+                                // 从失败中恢复时按值已变化处理，尽快刷新
+                                Some(adaptive) => adaptive_interval(
+                                    adaptive,
+                                    interval,
+                                    delay,
+                                    changed || state.consecutive_failures > 0,
+                                ),
+                                None => interval,
+                            };
+                            if state.consecutive_failures > 0 {
+                                info!(
+                                    "通道 {} 自动召唤恢复 (slave_id={}, function={}, start_addr={})",
+                                    channel_id, slave_id, config.function, config.start_addr
+                                );
+                            }
+                            state.consecutive_failures = 0;
+                            state.last_success = Some(std::time::Instant::now());
+                            state.last_error = None;
+                        }
+                        Err(e) => {
+                            state.consecutive_failures += 1;
+                            state.last_error = Some(e.to_string());
+                            let factor = 1u32 << state.consecutive_failures.min(16);
+                            delay = interval
+                                .saturating_mul(factor)
+                                .min(AUTO_CALL_MAX_BACKOFF.max(interval));
+                            warn!(
+                                "通道 {} 自动召唤失败 (slave_id={}, function={}, start_addr={}, count={}, 连续 {} 次, {}ms 后重试): {}",
+                                channel_id,
+                                slave_id,
+                                config.function,
+                                config.start_addr,
+                                config.count,
+                                state.consecutive_failures,
+                                delay.as_millis(),
+                                e
+                            );
+                        }
                     }
+                    state.backoff = delay;
                 }
             });
         }
     }
 
-    /// 执行单次自动召唤任务
+    /// 启动心跳任务：连接空闲超过 `interval` 时读取 `addr` 处的保持寄存器探测链路
+    ///
+    /// 自动召唤正常运行时连接不会空闲，心跳只在没有其他流量时发送。
+    pub fn start_ping_task(&self, interval: Duration, addr: u16) {
+        let link = self.link.clone();
+        let slave_id = self.slave_id;
+        let poll_gate = self.poll_gate.clone();
+        let channel_id = self.channel_id;
+        let name = format!("modbus:{}:ping", self.channel_id);
+
+        self.tasks.spawn(name, async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if poll_gate.is_paused() || link.idle_for() < interval {
+                    continue;
+                }
+                if let Err(e) = link.ping(slave_id, addr).await {
+                    debug!("通道 {} Modbus 心跳失败: {}", channel_id, e);
+                }
+            }
+        });
+    }
+
+    /// 执行单次自动召唤任务，返回召唤到的值与缓存相比是否有变化
     async fn auto_call_task(
-        addr: &str,
-        port: u16,
+        link: &ModbusLink,
         slave_id: u8,
         config: &AutoCallConfig,
-        cache: &Arc<RwLock<HashMap<u16, (Value, String, std::time::Instant)>>>,
-    ) -> Result<()> {
-        let socket_addr = format!("{}:{}", addr, port);
-        let socket_addr = socket_addr
-            .parse()
-            .map_err(|e| DeviceError::ConfigError(format!("无效的地址: {}", e)))?;
-
-        let mut ctx = tcp::connect_slave(socket_addr, Slave(slave_id))
-            .await
-            .map_err(|e| DeviceError::ConnectionError(format!("Modbus TCP 连接失败: {}", e)))?;
+        cache: &Arc<RwLock<ModbusCache>>,
+    ) -> Result<bool> {
+        let mut ctx = link.acquire(slave_id, CommandPriority::Poll).await?;
 
         let now = std::time::Instant::now();
+        let mut changed = false;
 
         match config.function.as_str() {
             "holding" => {
-                let registers = ctx
-                    .read_holding_registers(config.start_addr, config.count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let registers = link
+                    .transact(
+                        "读取",
+                        ctx.read_holding_registers(config.start_addr, config.count),
+                    )
+                    .await?;
 
                 let mut cache_write = cache.write().await;
                 let mut updated_addrs = Vec::new();
                 for (i, &value) in registers.iter().enumerate() {
                     let addr = config.start_addr + i as u16;
-                    cache_write.insert(
-                        addr,
-                        (Value::Number(value.into()), "uint16".to_string(), now),
-                    );
+                    let value_json = Value::Number(value.into());
+                    changed |= cache_write
+                        .insert(
+                            (slave_id, addr),
+                            (value_json.clone(), "uint16".to_string(), now),
+                        )
+                        .is_none_or(|(old, _, _)| old != value_json);
                     updated_addrs.push((addr, value));
                 }
                 drop(cache_write);
@@ -178,20 +853,24 @@ impl ModbusProtocol {
                 );
             }
             "input" => {
-                let registers = ctx
-                    .read_input_registers(config.start_addr, config.count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let registers = link
+                    .transact(
+                        "读取",
+                        ctx.read_input_registers(config.start_addr, config.count),
+                    )
+                    .await?;
 
                 let mut cache_write = cache.write().await;
                 let mut updated_addrs = Vec::new();
                 for (i, &value) in registers.iter().enumerate() {
                     let addr = config.start_addr + i as u16;
-                    cache_write.insert(
-                        addr,
-                        (Value::Number(value.into()), "uint16".to_string(), now),
-                    );
+                    let value_json = Value::Number(value.into());
+                    changed |= cache_write
+                        .insert(
+                            (slave_id, addr),
+                            (value_json.clone(), "uint16".to_string(), now),
+                        )
+                        .is_none_or(|(old, _, _)| old != value_json);
                     updated_addrs.push((addr, value));
                 }
                 drop(cache_write);
@@ -208,17 +887,20 @@ impl ModbusProtocol {
                 );
             }
             "coil" => {
-                let coils = ctx
-                    .read_coils(config.start_addr, config.count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let coils = link
+                    .transact("读取", ctx.read_coils(config.start_addr, config.count))
+                    .await?;
 
                 let mut cache_write = cache.write().await;
                 let mut updated_addrs = Vec::new();
                 for (i, &value) in coils.iter().enumerate() {
                     let addr = config.start_addr + i as u16;
-                    cache_write.insert(addr, (Value::Bool(value), "bool".to_string(), now));
+                    changed |= cache_write
+                        .insert(
+                            (slave_id, addr),
+                            (Value::Bool(value), "bool".to_string(), now),
+                        )
+                        .is_none_or(|(old, _, _)| old != Value::Bool(value));
                     updated_addrs.push(addr);
                 }
                 drop(cache_write);
@@ -231,17 +913,23 @@ impl ModbusProtocol {
                 );
             }
             "discrete" => {
-                let inputs = ctx
-                    .read_discrete_inputs(config.start_addr, config.count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let inputs = link
+                    .transact(
+                        "读取",
+                        ctx.read_discrete_inputs(config.start_addr, config.count),
+                    )
+                    .await?;
 
                 let mut cache_write = cache.write().await;
                 let mut updated_addrs = Vec::new();
                 for (i, &value) in inputs.iter().enumerate() {
                     let addr = config.start_addr + i as u16;
-                    cache_write.insert(addr, (Value::Bool(value), "bool".to_string(), now));
+                    changed |= cache_write
+                        .insert(
+                            (slave_id, addr),
+                            (Value::Bool(value), "bool".to_string(), now),
+                        )
+                        .is_none_or(|(old, _, _)| old != Value::Bool(value));
                     updated_addrs.push(addr);
                 }
                 drop(cache_write);
@@ -261,19 +949,40 @@ impl ModbusProtocol {
             }
         }
 
-        Ok(())
+        Ok(changed)
     }
 
     /// 从缓存读取数据
-    pub async fn read_from_cache(&self, addr: u16, data_type: &str) -> Result<Option<Value>> {
-        println!("尝试从缓存读取: addr={} type={}", addr, data_type);
+    ///
+    /// 返回值与数据年龄（多寄存器类型取最旧寄存器的年龄）
+    pub async fn read_from_cache(
+        &self,
+        slave_id: u8,
+        addr: u16,
+        data_type: &str,
+        order: RegisterOrder,
+    ) -> Result<Option<(Value, std::time::Duration)>> {
+        trace!(
+            channel_id = self.channel_id,
+            slave_id,
+            addr,
+            data_type,
+            "尝试从缓存读取"
+        );
         let cache = self.cache.read().await;
-        // 打印cache所有的值
-        for (k, v) in cache.iter() {
-            println!(
-                "缓存地址: {}, 值: {:?}, 类型: {}, 时间: {:?}",
-                k, v.0, v.1, v.2
-            );
+        // 通道参数 cache_trace 开启时输出完整缓存内容（仅 trace 级别）
+        if self.cache_trace {
+            for ((slave, k), (value, kind, updated)) in cache.iter() {
+                trace!(
+                    channel_id = self.channel_id,
+                    slave_id = *slave,
+                    addr = *k,
+                    value = %value,
+                    kind = %kind,
+                    age_ms = updated.elapsed().as_millis() as u64,
+                    "缓存条目"
+                );
+            }
         }
 
         // 根据数据类型需要读取的寄存器数量
@@ -282,15 +991,17 @@ impl ModbusProtocol {
 
         if data_type_enum.is_coil() {
             // 线圈类型直接从缓存读取
-            if let Some((value, _, _)) = cache.get(&addr) {
-                return Ok(Some(value.clone()));
+            if let Some((value, _, updated)) = cache.get(&(slave_id, addr)) {
+                return Ok(Some((value.clone(), updated.elapsed())));
             }
         } else {
             // 寄存器类型需要读取多个连续地址
             let mut registers = Vec::new();
+            let mut age = std::time::Duration::ZERO;
             for i in 0..count {
-                if let Some((value, _, _)) = cache.get(&(addr + i as u16)) {
-                    println!("缓存命中: addr={} value={:?}", addr, value);
+                if let Some((value, _, updated)) = cache.get(&(slave_id, addr + i as u16)) {
+                    trace!(channel_id = self.channel_id, addr, value = %value, "缓存命中");
+                    age = age.max(updated.elapsed());
                     if let Some(num) = value.as_u64() {
                         registers.push(num as u16);
                     } else {
@@ -302,37 +1013,224 @@ impl ModbusProtocol {
             }
 
             // 将寄存器数据转换为指定类型
-            let converted = Self::registers_to_value(&registers, data_type_enum)?;
-            return Ok(Some(converted));
+            let converted = Self::registers_to_value(&registers, data_type_enum, order)?;
+            return Ok(Some((converted, age)));
         }
 
         Ok(None)
     }
 
     /// 获取所有缓存数据
-    pub async fn get_all_cache(&self) -> HashMap<u16, (Value, String, std::time::Instant)> {
+    pub async fn get_all_cache(&self) -> ModbusCache {
         self.cache.read().await.clone()
     }
 
-    /// 创建 Modbus TCP 连接
-    async fn connect(&self) -> Result<client::Context> {
-        let socket_addr = format!("{}:{}", self.addr, self.port);
-        debug!("连接到 Modbus TCP 服务器: {}", socket_addr);
+    /// 缓存快照（按从站、地址排序，含数据年龄）
+    pub async fn cache_snapshot(&self) -> Value {
+        let mut entries: Vec<_> = self.get_all_cache().await.into_iter().collect();
+        entries.sort_by_key(|(key, _)| *key);
+
+        let entries: Vec<Value> = entries
+            .into_iter()
+            .map(|((slave_id, addr), (value, kind, updated))| {
+                let age = updated.elapsed();
+                serde_json::json!({
+                    "slave_id": slave_id,
+                    "addr": addr,
+                    "value": value,
+                    "type": kind,
+                    "updated_at": time::instant_rfc3339(updated),
+                    "age_ms": age.as_millis() as u64,
+                    "stale": self.is_stale(age),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "count": entries.len(),
+            "ttl_ms": self.cache_ttl.map(|ttl| ttl.as_millis() as u64),
+            "stale_policy": format!("{:?}", self.stale_policy),
+            "entries": entries,
+        })
+    }
+
+    /// 请求参数中的从站地址，未指定（或为 null）时使用通道默认值
+    fn slave_for(&self, params: &Value) -> Result<u8> {
+        match params.get("slave_id").filter(|v| !v.is_null()) {
+            None => Ok(self.slave_id),
+            Some(v) => v
+                .as_u64()
+                .and_then(|id| u8::try_from(id).ok())
+                .ok_or_else(|| DeviceError::ConfigError(format!("无效的slave_id: {}", v))),
+        }
+    }
+
+    /// 数据年龄是否超过缓存有效期
+    fn is_stale(&self, age: std::time::Duration) -> bool {
+        self.cache_ttl.is_some_and(|ttl| age > ttl)
+    }
 
-        let socket_addr = socket_addr
-            .parse()
-            .map_err(|e| DeviceError::ConfigError(format!("无效的地址: {}", e)))?;
+    /// 按缓存策略读取
+    ///
+    /// - 未命中: 从设备读取
+    /// - 命中且未过期: 返回缓存
+    /// - 命中但已过期: 按 [`StalePolicy`] 处理
+    ///
+    async fn read_with_policy(
+        &self,
+        slave_id: u8,
+        addr: u16,
+        data_type: &str,
+        order: RegisterOrder,
+    ) -> Result<ReadOutcome> {
+        let cached = self
+            .read_from_cache(slave_id, addr, data_type, order)
+            .await?;
+        let stale_value = match cached {
+            Some((value, age)) if !self.is_stale(age) => {
+                debug!(
+                    "从缓存读取数据: slave_id={} addr={} type={}",
+                    slave_id, addr, data_type
+                );
+                return Ok(ReadOutcome::cached(value, false));
+            }
+            Some((value, age)) => {
+                debug!(
+                    "通道 {} 缓存已过期: slave_id={} addr={} age={}ms 策略={:?}",
+                    self.channel_id,
+                    slave_id,
+                    addr,
+                    age.as_millis(),
+                    self.stale_policy
+                );
+                if self.stale_policy == StalePolicy::ServeStale {
+                    return Ok(ReadOutcome::cached(value, true));
+                }
+                Some(value)
+            }
+            None => None,
+        };
 
-        let ctx = tcp::connect_slave(socket_addr, Slave(self.slave_id))
+        match self
+            .read_from_device(slave_id, addr, data_type, order)
             .await
-            .map_err(|e| DeviceError::ConnectionError(format!("Modbus TCP 连接失败: {}", e)))?;
+        {
+            Ok(outcome) => Ok(outcome),
+            Err(e) => match (stale_value, self.stale_policy) {
+                (Some(value), StalePolicy::RefreshOnStale) => {
+                    warn!(
+                        "通道 {} 刷新过期缓存失败，返回过期数据: slave_id={} addr={} ({})",
+                        self.channel_id, slave_id, addr, e
+                    );
+                    Ok(ReadOutcome::cached(value, true))
+                }
+                _ => Err(e),
+            },
+        }
+    }
 
-        info!("Modbus TCP 连接成功");
-        Ok(ctx)
+    /// 从设备读取指定类型的数据并更新缓存
+    async fn read_from_device(
+        &self,
+        slave_id: u8,
+        addr: u16,
+        data_type_str: &str,
+        order: RegisterOrder,
+    ) -> Result<ReadOutcome> {
+        let data_type = ModbusDataType::from_str(data_type_str)?;
+        let mut ctx = self.link.acquire(slave_id, CommandPriority::Read).await?;
+
+        if data_type.is_coil() {
+            let coil = self.link.transact("读取", ctx.read_coils(addr, 1)).await?;
+
+            let value = coil.get(0).copied().unwrap_or(false);
+
+            // 更新缓存
+            let mut cache = self.cache.write().await;
+            cache.insert(
+                (slave_id, addr),
+                (
+                    Value::Bool(value),
+                    data_type_str.to_string(),
+                    std::time::Instant::now(),
+                ),
+            );
+
+            Ok(ReadOutcome {
+                value: Value::Bool(value),
+                registers: None,
+                from_cache: false,
+                stale: false,
+            })
+        } else {
+            let count = data_type.register_count();
+            let registers = self
+                .link
+                .transact("读取", ctx.read_holding_registers(addr, count))
+                .await?;
+
+            let value = Self::registers_to_value(&registers, data_type, order)?;
+
+            // 更新缓存
+            let mut cache = self.cache.write().await;
+            let now = std::time::Instant::now();
+            for (i, &reg) in registers.iter().enumerate() {
+                cache.insert(
+                    (slave_id, addr + i as u16),
+                    (Value::Number(reg.into()), "uint16".to_string(), now),
+                );
+            }
+
+            Ok(ReadOutcome {
+                value,
+                registers: Some(registers),
+                from_cache: false,
+                stale: false,
+            })
+        }
+    }
+
+    /// 清除缓存，`slave_id` 为 `None` 表示全部从站，`range` 为闭区间地址范围，
+    /// `None` 表示全部地址
+    ///
+    /// 返回清除的条目数
+    pub async fn invalidate_cache(&self, slave_id: Option<u8>, range: Option<(u16, u16)>) -> usize {
+        let mut cache = self.cache.write().await;
+        let before = cache.len();
+        cache.retain(|(slave, addr), _| {
+            let slave_match = slave_id.is_none_or(|id| id == *slave);
+            let addr_match = range.is_none_or(|(start, end)| (start..=end).contains(addr));
+            !(slave_match && addr_match)
+        });
+        let removed = before - cache.len();
+        info!(
+            "通道 {} Modbus 缓存已清除 {} 条 (从站: {:?}, 范围: {:?})",
+            self.channel_id, removed, slave_id, range
+        );
+        removed
+    }
+
+    /// 将寄存器数据按排列 `order` 转换为指定类型的值
+    pub(crate) fn registers_to_value(
+        registers: &[u16],
+        data_type: ModbusDataType,
+        order: RegisterOrder,
+    ) -> Result<Value> {
+        let count = (data_type.register_count() as usize).min(registers.len());
+        Self::decode_registers(&order.apply(&registers[..count]), data_type)
+    }
+
+    /// 将值按排列 `order` 转换为寄存器数据
+    pub(crate) fn value_to_registers(
+        value: Value,
+        data_type: ModbusDataType,
+        order: RegisterOrder,
+    ) -> Result<Vec<u16>> {
+        Self::encode_registers(value, data_type).map(|registers| order.apply(&registers))
     }
 
-    /// 将寄存器数据转换为指定类型的值
-    fn registers_to_value(registers: &[u16], data_type: ModbusDataType) -> Result<Value> {
+    /// 解码大端排列（ABCD）的寄存器数据
+    fn decode_registers(registers: &[u16], data_type: ModbusDataType) -> Result<Value> {
         match data_type {
             ModbusDataType::UInt16 => {
                 Ok(Value::Number(registers.get(0).copied().unwrap_or(0).into()))
@@ -424,8 +1322,8 @@ impl ModbusProtocol {
         }
     }
 
-    /// 将值转换为寄存器数据
-    fn value_to_registers(value: Value, data_type: ModbusDataType) -> Result<Vec<u16>> {
+    /// 将值编码为大端排列（ABCD）的寄存器数据
+    fn encode_registers(value: Value, data_type: ModbusDataType) -> Result<Vec<u16>> {
         match data_type {
             ModbusDataType::UInt16 => {
                 let val = value
@@ -563,13 +1461,75 @@ impl Protocol for ModbusProtocol {
                     Vec::new()
                 };
 
+                // 缓存有效期与过期策略
+                let cache_ttl = params
+                    .get("cache_ttl_ms")
+                    .and_then(|v| v.as_u64())
+                    .filter(|ms| *ms > 0)
+                    .map(std::time::Duration::from_millis);
+                let stale_policy = match params.get("stale_policy").and_then(|v| v.as_str()) {
+                    Some(s) => s.parse::<StalePolicy>()?,
+                    None => StalePolicy::RefreshOnStale,
+                };
+
+                // 低优先级请求的最长让行时间
+                let priority_aging = params
+                    .get("priority_aging_ms")
+                    .and_then(|v| v.as_u64())
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_PRIORITY_AGING);
+
+                // 合并重叠 / 相邻的召唤区间
+                let requested = auto_call_configs.len();
+                let auto_call_configs = merge_auto_calls(&auto_call_configs);
+                if auto_call_configs.len() < requested {
+                    info!(
+                        "通道 {} 自动召唤区间 {} 个合并为 {} 个",
+                        channel_id,
+                        requested,
+                        auto_call_configs.len()
+                    );
+                }
+                let auto_call_states = auto_call_configs
+                    .iter()
+                    .map(|config| AutoCallState {
+                        config: config.clone(),
+                        consecutive_failures: 0,
+                        backoff: Duration::from_millis(config.interval_ms),
+                        last_success: None,
+                        last_error: None,
+                        last_change: None,
+                    })
+                    .collect();
+
                 let protocol = Self {
                     channel_id,
+                    link: ModbusLink::new(
+                        addr.clone(),
+                        port,
+                        KeepaliveConfig::from_params(params),
+                        CommandQueue::new(priority_aging),
+                        EventSink::new(channel_id),
+                    ),
                     addr,
                     port,
                     slave_id,
                     cache: Arc::new(RwLock::new(HashMap::new())),
                     auto_call_configs: auto_call_configs.clone(),
+                    auto_call_states: Arc::new(RwLock::new(auto_call_states)),
+                    poll_gate: PollGate::new(),
+                    tasks: TaskRegistry::new(),
+                    cache_trace: params
+                        .get("cache_trace")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                    cache_ttl,
+                    stale_policy,
+                    identify_on_status: params
+                        .get("device_identification")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                    device_identification: Arc::new(RwLock::new(None)),
                 };
 
                 // 启动自动召唤任务
@@ -578,6 +1538,19 @@ impl Protocol for ModbusProtocol {
                     protocol.start_auto_call_tasks();
                 }
 
+                // 应用层心跳（0 或未配置时关闭）
+                if let Some(interval_ms) = params
+                    .get("ping_interval_ms")
+                    .and_then(|v| v.as_u64())
+                    .filter(|ms| *ms > 0)
+                {
+                    let ping_addr = params
+                        .get("ping_addr")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0) as u16;
+                    protocol.start_ping_task(Duration::from_millis(interval_ms), ping_addr);
+                }
+
                 Ok(Box::new(protocol))
             }
             "serial" => Err(DeviceError::ConfigError("Modbus串口模式暂未实现".into())),
@@ -589,9 +1562,37 @@ impl Protocol for ModbusProtocol {
     }
 
     async fn execute(&mut self, command: &str, params: Value) -> Result<Value> {
-        let mut ctx = self.connect().await?;
-
+        // 缓存相关命令无需连接设备
         match command {
+            "get_cache" => return Ok(self.cache_snapshot().await),
+            "invalidate_cache" => {
+                let start = params.get("start_addr").and_then(|v| v.as_u64());
+                let end = params.get("end_addr").and_then(|v| v.as_u64());
+                let range = match (start, end) {
+                    (None, None) => None,
+                    (Some(start), end) => {
+                        let end = end.unwrap_or(start);
+                        if start > end || end > u16::MAX as u64 {
+                            return Err(DeviceError::ConfigError(format!(
+                                "无效的地址范围: {}..={}",
+                                start, end
+                            )));
+                        }
+                        Some((start as u16, end as u16))
+                    }
+                    (None, Some(_)) => {
+                        return Err(DeviceError::ConfigError(
+                            "指定 end_addr 时必须同时指定 start_addr".into(),
+                        ))
+                    }
+                };
+                let slave_id = match params.get("slave_id").filter(|v| !v.is_null()) {
+                    Some(_) => Some(self.slave_for(&params)?),
+                    None => None,
+                };
+                let removed = self.invalidate_cache(slave_id, range).await;
+                return Ok(serde_json::json!({ "removed": removed }));
+            }
             "read" | "read_typed" => {
                 // 支持指定数据类型的读取
                 let addr = params
@@ -610,77 +1611,54 @@ impl Protocol for ModbusProtocol {
                     .and_then(|v| v.as_bool())
                     .unwrap_or(true); // 默认使用缓存
 
-                // 尝试从缓存读取
-                if use_cache {
-                    if let Some(cached_value) = self.read_from_cache(addr, data_type_str).await? {
-                        debug!("从缓存读取数据: addr={} type={}", addr, data_type_str);
-                        return Ok(serde_json::json!({
-                            "status": "success",
-                            "value": cached_value,
-                            "type": data_type_str,
-                            "from_cache": true
-                        }));
-                    }
-                }
-
-                let data_type = ModbusDataType::from_str(data_type_str)?;
-
-                // 根据数据类型读取
-                if data_type.is_coil() {
-                    let coil = ctx
-                        .read_coils(addr, 1)
-                        .await
-                        .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                        .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
-
-                    let value = coil.get(0).copied().unwrap_or(false);
-
-                    // 更新缓存
-                    let mut cache = self.cache.write().await;
-                    cache.insert(
-                        addr,
-                        (
-                            Value::Bool(value),
-                            data_type_str.to_string(),
-                            std::time::Instant::now(),
-                        ),
-                    );
-
-                    Ok(serde_json::json!({
-                        "status": "success",
-                        "value": value,
-                        "type": data_type_str,
-                        "from_cache": false
-                    }))
+                let slave_id = self.slave_for(&params)?;
+                let order = RegisterOrder::from_params(&params)?;
+                let outcome = if use_cache {
+                    self.read_with_policy(slave_id, addr, data_type_str, order)
+                        .await?
                 } else {
-                    let count = data_type.register_count();
-                    let registers = ctx
-                        .read_holding_registers(addr, count)
-                        .await
-                        .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                        .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
-
-                    let value = Self::registers_to_value(&registers, data_type)?;
-
-                    // 更新缓存
-                    let mut cache = self.cache.write().await;
-                    let now = std::time::Instant::now();
-                    for (i, &reg) in registers.iter().enumerate() {
-                        cache.insert(
-                            addr + i as u16,
-                            (Value::Number(reg.into()), "uint16".to_string(), now),
-                        );
-                    }
+                    self.read_from_device(slave_id, addr, data_type_str, order)
+                        .await?
+                };
 
-                    Ok(serde_json::json!({
-                        "status": "success",
-                        "value": value,
-                        "type": data_type_str,
-                        "registers": registers,
-                        "from_cache": false
-                    }))
+                let mut result = serde_json::json!({
+                    "status": "success",
+                    "value": outcome.value,
+                    "type": data_type_str,
+                    "from_cache": outcome.from_cache,
+                    "stale": outcome.stale
+                });
+                if let Some(registers) = outcome.registers {
+                    result["registers"] = serde_json::json!(registers);
                 }
+                return Ok(result);
             }
+            "read_device_identification" | "device_identification" => {
+                let level = params.get("level").and_then(|v| v.as_u64()).unwrap_or(1);
+                if !(1..=3).contains(&level) {
+                    return Err(DeviceError::ConfigError(format!(
+                        "无效的 level: {}（1 基本、2 常规、3 扩展）",
+                        level
+                    )));
+                }
+                let slave_id = self.slave_for(&params)?;
+                return self.read_device_identification(slave_id, level as u8).await;
+            }
+            _ => {}
+        }
+
+        // 含写入的命令（write_*、mask_write、read_write_multiple）按写入排队
+        let priority = if command.contains("write") {
+            CommandPriority::Write
+        } else {
+            CommandPriority::Read
+        };
+        let mut ctx = self
+            .link
+            .acquire(self.slave_for(&params)?, priority)
+            .await?;
+
+        match command {
             "write" | "write_typed" => {
                 // 支持指定数据类型的写入
                 let addr = params
@@ -700,6 +1678,7 @@ impl Protocol for ModbusProtocol {
                     .unwrap_or("uint16");
 
                 let data_type = ModbusDataType::from_str(data_type_str)?;
+                let order = RegisterOrder::from_params(&params)?;
 
                 // 根据数据类型写入
                 if data_type.is_coil() {
@@ -707,27 +1686,20 @@ impl Protocol for ModbusProtocol {
                         .as_bool()
                         .ok_or_else(|| DeviceError::ConfigError("Bool类型需要布尔值".into()))?;
 
-                    ctx.write_single_coil(addr, bool_val)
-                        .await
-                        .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-                        .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                    self.link
+                        .transact("写入", ctx.write_single_coil(addr, bool_val))
+                        .await?;
                 } else {
-                    let registers = Self::value_to_registers(value, data_type)?;
+                    let registers = Self::value_to_registers(value, data_type, order)?;
 
                     if registers.len() == 1 {
-                        ctx.write_single_register(addr, registers[0])
-                            .await
-                            .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-                            .map_err(|e| {
-                                DeviceError::ProtocolError(format!("Modbus异常: {:?}", e))
-                            })?;
+                        self.link
+                            .transact("写入", ctx.write_single_register(addr, registers[0]))
+                            .await?;
                     } else {
-                        ctx.write_multiple_registers(addr, &registers)
-                            .await
-                            .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-                            .map_err(|e| {
-                                DeviceError::ProtocolError(format!("Modbus异常: {:?}", e))
-                            })?;
+                        self.link
+                            .transact("写入", ctx.write_multiple_registers(addr, &registers))
+                            .await?;
                     }
                 }
 
@@ -744,11 +1716,10 @@ impl Protocol for ModbusProtocol {
 
                 let count = params.get("count").and_then(|v| v.as_u64()).unwrap_or(1) as u16;
 
-                let data = ctx
-                    .read_holding_registers(addr, count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let data = self
+                    .link
+                    .transact("读取", ctx.read_holding_registers(addr, count))
+                    .await?;
 
                 Ok(serde_json::json!({
                     "status": "success",
@@ -764,11 +1735,10 @@ impl Protocol for ModbusProtocol {
 
                 let count = params.get("count").and_then(|v| v.as_u64()).unwrap_or(1) as u16;
 
-                let data = ctx
-                    .read_input_registers(addr, count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let data = self
+                    .link
+                    .transact("读取", ctx.read_input_registers(addr, count))
+                    .await?;
 
                 Ok(serde_json::json!({
                     "status": "success",
@@ -788,10 +1758,9 @@ impl Protocol for ModbusProtocol {
                     .ok_or_else(|| DeviceError::ConfigError("缺少value参数".into()))?
                     as u16;
 
-                ctx.write_single_register(addr, value)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                self.link
+                    .transact("写入", ctx.write_single_register(addr, value))
+                    .await?;
 
                 Ok(serde_json::json!({"status": "success"}))
             }
@@ -810,10 +1779,88 @@ impl Protocol for ModbusProtocol {
                     .filter_map(|v| v.as_u64().map(|n| n as u16))
                     .collect();
 
-                ctx.write_multiple_registers(addr, &values)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                self.link
+                    .transact("写入", ctx.write_multiple_registers(addr, &values))
+                    .await?;
+
+                Ok(serde_json::json!({"status": "success"}))
+            }
+            "read_write_multiple" | "read_write_multiple_registers" => {
+                // 功能码 0x17：先写后读，一次往返完成
+                let read_addr = params
+                    .get("read_addr")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| DeviceError::ConfigError("缺少read_addr参数".into()))?
+                    as u16;
+                let read_count = params
+                    .get("read_count")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| DeviceError::ConfigError("缺少read_count参数".into()))?
+                    as u16;
+                let write_addr = params
+                    .get("write_addr")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| DeviceError::ConfigError("缺少write_addr参数".into()))?
+                    as u16;
+                let values: Vec<u16> = params
+                    .get("values")
+                    .and_then(|v| v.as_array())
+                    .ok_or_else(|| DeviceError::ConfigError("缺少values参数".into()))?
+                    .iter()
+                    .filter_map(|v| v.as_u64().map(|n| n as u16))
+                    .collect();
+                if read_count == 0 || read_count > MAX_READ_REGISTERS {
+                    return Err(DeviceError::ConfigError(format!(
+                        "read_count 必须在 1..={} 之间",
+                        MAX_READ_REGISTERS
+                    )));
+                }
+                if values.is_empty() || values.len() > 121 {
+                    return Err(DeviceError::ConfigError(
+                        "values 数量必须在 1..=121 之间".into(),
+                    ));
+                }
+
+                let registers = self
+                    .link
+                    .transact(
+                        "读写",
+                        ctx.read_write_multiple_registers(
+                            read_addr, read_count, write_addr, &values,
+                        ),
+                    )
+                    .await?;
+
+                Ok(serde_json::json!({
+                    "status": "success",
+                    "read_addr": read_addr,
+                    "values": registers
+                }))
+            }
+            "mask_write_register" | "mask_write" => {
+                // 功能码 0x16：结果 = (当前值 AND and_mask) OR (or_mask AND NOT and_mask)
+                let addr = params
+                    .get("addr")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| DeviceError::ConfigError("缺少addr参数".into()))?
+                    as u16;
+                let and_mask = params
+                    .get("and_mask")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| DeviceError::ConfigError("缺少and_mask参数".into()))?
+                    as u16;
+                let or_mask = params
+                    .get("or_mask")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| DeviceError::ConfigError("缺少or_mask参数".into()))?
+                    as u16;
+
+                self.link
+                    .transact(
+                        "掩码写入",
+                        ctx.masked_write_register(addr, and_mask, or_mask),
+                    )
+                    .await?;
 
                 Ok(serde_json::json!({"status": "success"}))
             }
@@ -826,11 +1873,10 @@ impl Protocol for ModbusProtocol {
 
                 let count = params.get("count").and_then(|v| v.as_u64()).unwrap_or(1) as u16;
 
-                let data = ctx
-                    .read_coils(addr, count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let data = self
+                    .link
+                    .transact("读取", ctx.read_coils(addr, count))
+                    .await?;
 
                 Ok(serde_json::json!({
                     "status": "success",
@@ -846,11 +1892,10 @@ impl Protocol for ModbusProtocol {
 
                 let count = params.get("count").and_then(|v| v.as_u64()).unwrap_or(1) as u16;
 
-                let data = ctx
-                    .read_discrete_inputs(addr, count)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                let data = self
+                    .link
+                    .transact("读取", ctx.read_discrete_inputs(addr, count))
+                    .await?;
 
                 Ok(serde_json::json!({
                     "status": "success",
@@ -869,10 +1914,9 @@ impl Protocol for ModbusProtocol {
                     .and_then(|v| v.as_bool())
                     .ok_or_else(|| DeviceError::ConfigError("缺少value参数".into()))?;
 
-                ctx.write_single_coil(addr, value)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                self.link
+                    .transact("写入", ctx.write_single_coil(addr, value))
+                    .await?;
 
                 Ok(serde_json::json!({"status": "success"}))
             }
@@ -891,10 +1935,9 @@ impl Protocol for ModbusProtocol {
                     .filter_map(|v| v.as_bool())
                     .collect();
 
-                ctx.write_multiple_coils(addr, &values)
-                    .await
-                    .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-                    .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+                self.link
+                    .transact("写入", ctx.write_multiple_coils(addr, &values))
+                    .await?;
 
                 Ok(serde_json::json!({"status": "success"}))
             }
@@ -906,62 +1949,285 @@ impl Protocol for ModbusProtocol {
     }
 
     async fn get_status(&self) -> Result<Value> {
-        match self.connect().await {
-            Ok(_) => Ok(serde_json::json!({
-                "connected": true,
-                "addr": self.addr,
-                "port": self.port,
-                "slave_id": self.slave_id
-            })),
+        let auto_call: Vec<Value> = self
+            .auto_call_states
+            .read()
+            .await
+            .iter()
+            .map(AutoCallState::to_json)
+            .collect();
+
+        // 连接守卫需在读取设备标识前释放
+        let connected = self
+            .link
+            .acquire(self.slave_id, CommandPriority::Read)
+            .await
+            .map(drop);
+        match connected {
+            Ok(()) => {
+                if self.identify_on_status && self.device_identification.read().await.is_none() {
+                    if let Err(e) = self.read_device_identification(self.slave_id, 1).await {
+                        warn!("通道 {} 读取设备标识失败: {}", self.channel_id, e);
+                        *self.device_identification.write().await =
+                            Some(serde_json::json!({ "error": e.to_string() }));
+                    }
+                }
+                Ok(serde_json::json!({
+                    "connected": true,
+                    "addr": self.addr,
+                    "port": self.port,
+                    "slave_id": self.slave_id,
+                    "command_queue": self.link.queue.stats(),
+                    "device_identification": *self.device_identification.read().await,
+                    "auto_call": auto_call
+                }))
+            }
             Err(e) => Ok(serde_json::json!({
                 "connected": false,
-                "error": e.to_string()
+                "error": e.to_string(),
+                "auto_call": auto_call
             })),
         }
     }
 
     async fn write(&mut self, id: u32, value: i32) -> Result<()> {
-        let mut ctx = self.connect().await?;
-        ctx.write_single_register(id as u16, value as u16)
-            .await
-            .map_err(|e| DeviceError::ConnectionError(format!("写入失败: {}", e)))?
-            .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+        let mut ctx = self
+            .link
+            .acquire(self.slave_id, CommandPriority::Write)
+            .await?;
+        self.link
+            .transact("写入", ctx.write_single_register(id as u16, value as u16))
+            .await?;
         Ok(())
     }
 
     async fn read(&self, id: u32) -> Result<i32> {
-        // 优先从缓存读取
-        if let Some(cached) = self.read_from_cache(id as u16, "int16").await? {
-            if let Some(num) = cached.as_i64() {
-                return Ok(num as i32);
-            }
+        // 优先从缓存读取，过期时按策略处理
+        let value = self
+            .read_with_policy(self.slave_id, id as u16, "int16", RegisterOrder::default())
+            .await?
+            .value;
+        value
+            .as_i64()
+            .map(|v| v as i32)
+            .ok_or_else(|| DeviceError::ProtocolError(format!("无效的寄存器值: {}", value)))
+    }
+
+    fn name(&self) -> &str {
+        "modbus"
+    }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<DeviceEvent>) {
+        self.link.events.attach(event_tx);
+    }
+
+    fn set_polling_paused(&self, paused: bool) {
+        self.poll_gate.set_paused(paused);
+    }
+
+    fn open_connections(&self) -> Option<usize> {
+        Some(self.link.open_connections())
+    }
+
+    fn command_queue_stats(&self) -> Option<CommandQueueStats> {
+        Some(self.link.queue.stats())
+    }
+
+    async fn shutdown(&self) {
+        self.tasks.shutdown().await;
+    }
+
+    async fn release(&self) {
+        self.link.release().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(function: &str, start_addr: u16, count: u16, interval_ms: u64) -> AutoCallConfig {
+        AutoCallConfig {
+            function: function.to_string(),
+            start_addr,
+            count,
+            interval_ms,
+            slave_id: None,
+            adaptive: None,
         }
+    }
 
-        // 缓存未命中，从设备读取
-        let mut ctx = self.connect().await?;
-        let data = ctx
-            .read_holding_registers(id as u16, 1)
-            .await
-            .map_err(|e| DeviceError::ConnectionError(format!("读取失败: {}", e)))?
-            .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
+    #[tokio::test]
+    async fn unanswered_request_times_out_and_breaks_link() {
+        let link = ModbusLink::new(
+            "127.0.0.1".into(),
+            502,
+            KeepaliveConfig::default(),
+            CommandQueue::default(),
+            EventSink::new(0),
+        );
+        let result = link
+            .transact("读取", std::future::pending::<tokio_modbus::Result<Vec<u16>>>())
+            .await;
+        assert!(matches!(result, Err(DeviceError::Timeout)));
+        assert!(link.broken.load(Ordering::Relaxed));
+        assert_eq!(link.open_connections(), 0);
+    }
 
-        let value = data.get(0).copied().unwrap_or(0) as i32;
+    #[test]
+    fn merge_overlapping_and_adjacent_ranges() {
+        let merged = merge_auto_calls(&[
+            call("holding", 10, 10, 1000),
+            call("holding", 0, 10, 2000),
+            call("holding", 15, 10, 500),
+            call("input", 0, 10, 1000),
+            call("holding", 25, 120, 1000),
+        ]);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(
+            (merged[0].start_addr, merged[0].count, merged[0].interval_ms),
+            (0, 25, 500)
+        );
+        // 合并后超过单次读取上限时保持独立
+        assert_eq!((merged[1].start_addr, merged[1].count), (25, 120));
+        assert_eq!(merged[2].function, "input");
+
+        // 不同从站的区间不合并
+        let mut other_slave = call("holding", 10, 10, 1000);
+        other_slave.slave_id = Some(2);
+        let merged = merge_auto_calls(&[call("holding", 0, 10, 1000), other_slave]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1].slave_id, Some(2));
+    }
 
-        // 更新缓存
-        let mut cache = self.cache.write().await;
-        cache.insert(
-            id as u16,
-            (
-                Value::Number(value.into()),
-                "int16".to_string(),
-                std::time::Instant::now(),
-            ),
+    #[test]
+    fn adaptive_interval_backs_off_until_change() {
+        let adaptive = AdaptivePollConfig {
+            min_interval_ms: None,
+            max_interval_ms: 5000,
+            factor: 2.0,
+        };
+        let base = Duration::from_millis(1000);
+        let mut delay = base;
+        let mut delays = Vec::new();
+        for _ in 0..4 {
+            delay = adaptive_interval(&adaptive, base, delay, false);
+            delays.push(delay.as_millis());
+        }
+        assert_eq!(delays, vec![2000, 4000, 5000, 5000]);
+        assert_eq!(adaptive_interval(&adaptive, base, delay, true), base);
+    }
+
+    #[test]
+    fn parse_device_identification_page() {
+        let data = [
+            0x0E, 0x01, 0x81, 0xFF, 0x02, 0x02, // 头部：还有后续，下一对象 0x02
+            0x00, 0x04, b'A', b'C', b'M', b'E', // VendorName
+            0x01, 0x03, b'P', b'0', b'1', // ProductCode
+        ];
+        let page = parse_device_identification(&data).unwrap();
+        assert_eq!(page.conformity_level, 0x81);
+        assert_eq!(page.next_object_id, Some(0x02));
+        assert_eq!(
+            page.objects,
+            vec![(0x00, "ACME".to_string()), (0x01, "P01".to_string())]
         );
+        assert!(parse_device_identification(&data[..10]).is_err());
+    }
 
-        Ok(value)
+    #[test]
+    fn register_encoding_matches_golden_frames() {
+        use crate::protocols::fixtures::{bytes_to_registers, registers_to_bytes, Captures};
+
+        let captures = Captures::load("modbus");
+        let cases = [
+            ("write_uint16", "uint16", serde_json::json!(4660)),
+            ("write_int16", "int16", serde_json::json!(-2)),
+            ("write_uint32", "uint32", serde_json::json!(305419896)),
+            ("write_int32", "int32", serde_json::json!(-100000)),
+            ("write_uint32le", "uint32le", serde_json::json!(305419896)),
+            ("write_int32le", "int32le", serde_json::json!(-100000)),
+            ("write_float32", "float32", serde_json::json!(1.5)),
+            ("write_float32le", "float32le", serde_json::json!(1.5)),
+            ("write_float64", "float64", serde_json::json!(-2.25)),
+        ];
+        for (name, data_type, value) in cases {
+            let data_type = ModbusDataType::from_str(data_type).unwrap();
+            let order = RegisterOrder::default();
+            let registers =
+                ModbusProtocol::value_to_registers(value.clone(), data_type, order).unwrap();
+            captures.assert_frame(name, &registers_to_bytes(&registers));
+            // 响应方向：设备返回同样的寄存器字节时解码回原值
+            let decoded = ModbusProtocol::registers_to_value(
+                &bytes_to_registers(captures.frame(name)),
+                data_type,
+                order,
+            )
+            .unwrap();
+            assert_eq!(decoded, value, "{}", name);
+        }
     }
 
-    fn name(&self) -> &str {
-        "modbus"
+    #[test]
+    fn word_orders_round_trip() {
+        let order = |word_order: &str, byte_swap: bool| {
+            RegisterOrder::from_params(&serde_json::json!({
+                "word_order": word_order,
+                "byte_swap": byte_swap,
+            }))
+            .unwrap()
+        };
+        let uint32 = ModbusDataType::UInt32;
+        let value = serde_json::json!(0x11223344u32);
+        let cases = [
+            ("ABCD", false, [0x1122, 0x3344]),
+            ("CDAB", false, [0x3344, 0x1122]),
+            ("BADC", false, [0x2211, 0x4433]),
+            ("DCBA", false, [0x4433, 0x2211]),
+            // byte_swap 叠加在字序上：CDAB + byte_swap 等同 DCBA
+            ("cdab", true, [0x4433, 0x2211]),
+            ("ABCD", true, [0x2211, 0x4433]),
+        ];
+        for (word_order, byte_swap, registers) in cases {
+            let order = order(word_order, byte_swap);
+            assert_eq!(
+                ModbusProtocol::value_to_registers(value.clone(), uint32, order).unwrap(),
+                registers,
+                "{} byte_swap={}",
+                word_order,
+                byte_swap
+            );
+            assert_eq!(
+                ModbusProtocol::registers_to_value(&registers, uint32, order).unwrap(),
+                value
+            );
+        }
+
+        let encode = |value: serde_json::Value, data_type, order| {
+            ModbusProtocol::value_to_registers(value, data_type, order).unwrap()
+        };
+        let registers = encode(
+            serde_json::json!(1.5),
+            ModbusDataType::Float32,
+            order("CDAB", false),
+        );
+        assert_eq!(registers, [0x0000, 0x3FC0]);
+        let dcba = order("DCBA", false);
+        let registers = encode(serde_json::json!(-2.25), ModbusDataType::Float64, dcba);
+        assert_eq!(registers, [0, 0, 0, 0x02C0]);
+        assert_eq!(
+            ModbusProtocol::registers_to_value(&registers, ModbusDataType::Float64, dcba).unwrap(),
+            serde_json::json!(-2.25)
+        );
+        // 16 位值只受字节交换影响
+        let registers = encode(
+            serde_json::json!(0x1234),
+            ModbusDataType::UInt16,
+            order("CDAB", true),
+        );
+        assert_eq!(registers, [0x3412]);
+        let invalid = serde_json::json!({ "word_order": "ACBD" });
+        assert!(RegisterOrder::from_params(&invalid).is_err());
     }
 }
@@ -15,6 +15,7 @@ use tracing::{debug, info, warn};
 
 use crate::protocols::Protocol;
 use crate::utils::error::DeviceError;
+use crate::utils::{dns, net};
 
 // 协议常量
 const FRAME_HEADER: [u8; 2] = [0x55, 0xAA]; // 帧头
@@ -125,22 +126,21 @@ impl NovastarProtocol {
     async fn send_command_tcp(&self, addr: &str, port: u16, command: &[u8]) -> Result<Vec<u8>> {
         debug!("连接到 TCP 设备: {}:{}", addr, port);
 
-        let mut stream = match tokio::time::timeout(
-            Duration::from_secs(5),
-            TcpStream::connect(format!("{}:{}", addr, port)),
-        )
-        .await
-        {
-            Ok(Ok(s)) => s,
-            Ok(Err(e)) => {
-                warn!("TCP 连接失败: {}", e);
-                return Err(DeviceError::ConnectionError(format!("连接失败: {}", e)).into());
-            }
-            Err(_) => {
-                warn!("TCP 连接超时");
-                return Err(DeviceError::Other("TCP 连接超时 (5秒)".to_string()).into());
-            }
-        };
+        let socket_addr = dns::resolve(addr, port).await?;
+        let mut stream =
+            match tokio::time::timeout(Duration::from_secs(5), TcpStream::connect(socket_addr))
+                .await
+            {
+                Ok(Ok(s)) => s,
+                Ok(Err(e)) => {
+                    warn!("TCP 连接失败: {}", e);
+                    return Err(DeviceError::ConnectionError(format!("连接失败: {}", e)).into());
+                }
+                Err(_) => {
+                    warn!("TCP 连接超时");
+                    return Err(DeviceError::Other("TCP 连接超时 (5秒)".to_string()).into());
+                }
+            };
 
         info!("TCP 连接成功");
         debug!("发送命令: {:02X?}", command);
@@ -171,9 +171,10 @@ impl NovastarProtocol {
 
     /// 发送命令并接收响应 (UDP)
     async fn send_command_udp(&self, addr: &str, port: u16, command: &[u8]) -> Result<Vec<u8>> {
-        debug!("UDP 连接到设备: {}:{}", addr, port);
+        debug!("UDP 连接到设备: {}", net::display_addr(addr, port));
 
-        let socket = match UdpSocket::bind("0.0.0.0:0").await {
+        let target = dns::resolve(addr, port).await?;
+        let socket = match UdpSocket::bind(net::unspecified_for(&target, 0)).await {
             Ok(s) => s,
             Err(e) => {
                 warn!("UDP 绑定失败: {}", e);
@@ -181,8 +182,7 @@ impl NovastarProtocol {
             }
         };
 
-        let target = format!("{}:{}", addr, port);
-        match socket.connect(&target).await {
+        match socket.connect(target).await {
             Ok(_) => {
                 info!("UDP 连接成功: {}", target);
             }
@@ -521,6 +521,17 @@ impl Protocol for NovastarProtocol {
         "Novastar"
     }
 
+    async fn send_raw(
+        &mut self,
+        payload: &[u8],
+        timeout: Duration,
+    ) -> crate::utils::Result<Vec<u8>> {
+        tokio::time::timeout(timeout, self.send_command(payload))
+            .await
+            .map_err(|_| DeviceError::Timeout)?
+            .map_err(|e| DeviceError::Other(e.to_string()))
+    }
+
     async fn call_method(&mut self, method_name: &str, args: Value) -> crate::utils::Result<Value> {
         NovastarProtocol::execute(self, method_name, args)
             .await
@@ -1,9 +1,10 @@
 use crate::protocols::Protocol;
-use crate::utils::{DeviceError, Result};
+use crate::utils::{dns, net, DeviceError, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
@@ -35,7 +36,7 @@ impl PjlinkProtocol {
     }
 
     async fn send_command(&self, cmd: &str) -> Result<String> {
-        let mut stream = TcpStream::connect(format!("{}:{}", self.addr, self.port))
+        let mut stream = TcpStream::connect(dns::resolve(&self.addr, self.port).await?)
             .await
             .map_err(|e| DeviceError::ConnectionError(e.to_string()))?;
 
@@ -131,4 +132,8 @@ impl Protocol for PjlinkProtocol {
     fn name(&self) -> &str {
         "pjlink"
     }
+
+    async fn send_raw(&mut self, payload: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        net::exchange_raw(&self.addr, self.port, payload, timeout).await
+    }
 }
@@ -1,22 +1,33 @@
 // QN Smart PLC 协议实现
 // 基于 Modbus TCP，支持 40 路开关控制和传感器数据读取
+//
+// 支持在通道参数中定义符号标签 (tags)，节点通过 tag 名称引用，
+// 加载时解析为内部节点 ID（TAG_ID_BASE 起），避免在配置中直接写寄存器地址。
 
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tracing::{debug, error, info, warn};
 
+use crate::protocols::modbus::{ModbusDataType, ModbusProtocol, RegisterOrder};
 use crate::protocols::Protocol;
 use crate::utils::error::DeviceError;
-use crate::utils::Result;
+use crate::utils::{dns, net, Result};
 
 // Modbus 功能码
 const FC_READ_COILS: u8 = 0x01;
 const FC_READ_HOLDING_REGISTERS: u8 = 0x03;
 const FC_WRITE_SINGLE_COIL: u8 = 0x05;
+const FC_WRITE_SINGLE_REGISTER: u8 = 0x06;
+const FC_WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+/// 标签解析后的节点 ID 起始值（1-40 为开关通道）
+const TAG_ID_BASE: u32 = 1000;
 
 // 特殊控制地址
 const ADDR_ONE_KEY_START: u16 = 0x03E8; // 一键启动
@@ -32,11 +43,131 @@ const ADDR_ZERO_LINE_TEMP: u16 = 0x035B; // 零线温度
 const ADDR_EXTERNAL_SENSORS: u16 = 0x04B0; // 外部温湿度、电压、电量
 const ADDR_CURRENT: u16 = 0x05DC; // 电流
 
+/// 标签配置
+#[derive(Debug, Clone, Deserialize)]
+struct QnTagConfig {
+    /// 区域: switch (开关通道 1-40) / coil (线圈) / register (保持寄存器)
+    area: String,
+    /// 地址: 开关通道号，或线圈/寄存器地址（数字或 "0x03E8" 形式）
+    addr: Value,
+    /// 数据类型（仅 register，默认 uint16）
+    #[serde(default, rename = "type")]
+    data_type: Option<String>,
+    /// 缩放比例：读取值 = 原始值 × scale，写入时反向换算
+    #[serde(default)]
+    scale: Option<f64>,
+    #[serde(default)]
+    readonly: bool,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// 标签区域
+#[derive(Debug, Clone, Copy)]
+enum QnTagArea {
+    Switch(u32),
+    Coil(u16),
+    Register(u16, ModbusDataType),
+}
+
+/// 已解析的标签
+#[derive(Debug, Clone)]
+struct QnTag {
+    name: String,
+    id: u32,
+    area: QnTagArea,
+    scale: Option<f64>,
+    readonly: bool,
+    description: Option<String>,
+}
+
+impl QnTag {
+    fn parse(name: &str, id: u32, config: QnTagConfig) -> Result<Self> {
+        let addr = match &config.addr {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => {
+                let s = s.trim();
+                match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                    None => s.parse().ok(),
+                }
+            }
+            _ => None,
+        }
+        .filter(|a| *a <= u16::MAX as u64)
+        .ok_or_else(|| {
+            DeviceError::ConfigError(format!("标签 '{}' 地址无效: {}", name, config.addr))
+        })?;
+
+        let area = match config.area.to_lowercase().as_str() {
+            "switch" | "channel" => {
+                if !(1..=40).contains(&addr) {
+                    return Err(DeviceError::ConfigError(format!(
+                        "标签 '{}' 开关通道号必须在1-40之间",
+                        name
+                    )));
+                }
+                QnTagArea::Switch(addr as u32)
+            }
+            "coil" => QnTagArea::Coil(addr as u16),
+            "register" | "holding" => {
+                let data_type =
+                    ModbusDataType::from_str(config.data_type.as_deref().unwrap_or("uint16"))
+                        .map_err(|e| DeviceError::ConfigError(format!("标签 '{}': {}", name, e)))?;
+                if data_type.is_coil() {
+                    return Err(DeviceError::ConfigError(format!(
+                        "标签 '{}' 为 register 区域，不能使用 bool 类型，请使用 coil 区域",
+                        name
+                    )));
+                }
+                QnTagArea::Register(addr as u16, data_type)
+            }
+            other => {
+                return Err(DeviceError::ConfigError(format!(
+                    "标签 '{}' 区域无效: {}，应为 switch/coil/register",
+                    name, other
+                )))
+            }
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            id,
+            area,
+            scale: config.scale,
+            readonly: config.readonly,
+            description: config.description,
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let (area, addr, data_type) = match self.area {
+            QnTagArea::Switch(ch) => ("switch", ch, "bool".to_string()),
+            QnTagArea::Coil(a) => ("coil", a as u32, "bool".to_string()),
+            QnTagArea::Register(a, t) => ("register", a as u32, format!("{:?}", t).to_lowercase()),
+        };
+        json!({
+            "tag": self.name,
+            "id": self.id,
+            "area": area,
+            "addr": addr,
+            "type": data_type,
+            "scale": self.scale,
+            "readonly": self.readonly,
+            "description": self.description
+        })
+    }
+}
+
 pub struct QnSmartPlcProtocol {
     addr: String,
     port: u16,
     slave_id: u8,
-    transaction_id: u16,
+    transaction_id: AtomicU16,
+    /// 符号标签 (名称 -> 标签)
+    tags: HashMap<String, QnTag>,
+    /// 节点 ID -> 标签名称
+    tag_ids: HashMap<u32, String>,
 }
 
 impl QnSmartPlcProtocol {
@@ -45,18 +176,21 @@ impl QnSmartPlcProtocol {
             addr,
             port,
             slave_id,
-            transaction_id: 0,
+            transaction_id: AtomicU16::new(0),
+            tags: HashMap::new(),
+            tag_ids: HashMap::new(),
         }
     }
 
     /// 获取下一个事务ID
-    fn next_transaction_id(&mut self) -> u16 {
-        self.transaction_id = self.transaction_id.wrapping_add(1);
+    fn next_transaction_id(&self) -> u16 {
         self.transaction_id
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_add(1)
     }
 
     /// 构建 Modbus TCP 请求帧
-    fn build_request(&mut self, function_code: u8, data: &[u8]) -> Vec<u8> {
+    fn build_request(&self, function_code: u8, data: &[u8]) -> Vec<u8> {
         let transaction_id = self.next_transaction_id();
         let length = 2 + data.len() as u16; // Unit ID + Function Code + Data
 
@@ -72,11 +206,11 @@ impl QnSmartPlcProtocol {
     }
 
     /// 发送请求并接收响应
-    async fn send_request(&mut self, request: &[u8]) -> Result<Vec<u8>> {
-        let addr = format!("{}:{}", self.addr, self.port);
+    async fn send_request(&self, request: &[u8]) -> Result<Vec<u8>> {
+        let addr = net::display_addr(&self.addr, self.port);
         info!("连接到 QN Smart PLC: {}", addr);
 
-        let mut stream = TcpStream::connect(&addr)
+        let mut stream = TcpStream::connect(dns::resolve(&self.addr, self.port).await?)
             .await
             .map_err(|e| DeviceError::ConnectionError(format!("连接失败: {}", e)))?;
 
@@ -104,7 +238,7 @@ impl QnSmartPlcProtocol {
     }
 
     /// 写单个线圈 (Function Code 0x05)
-    async fn write_coil(&mut self, address: u16, value: bool) -> Result<()> {
+    async fn write_coil(&self, address: u16, value: bool) -> Result<()> {
         let coil_value: u16 = if value { 0xFF00 } else { 0x0000 };
         let data = [
             (address >> 8) as u8,
@@ -134,7 +268,7 @@ impl QnSmartPlcProtocol {
     }
 
     /// 读取线圈状态 (Function Code 0x01)
-    async fn read_coils(&mut self, address: u16, count: u16) -> Result<Vec<bool>> {
+    async fn read_coils(&self, address: u16, count: u16) -> Result<Vec<bool>> {
         let data = [
             (address >> 8) as u8,
             address as u8,
@@ -166,7 +300,7 @@ impl QnSmartPlcProtocol {
     }
 
     /// 读取保持寄存器 (Function Code 0x03)
-    async fn read_holding_registers(&mut self, address: u16, count: u16) -> Result<Vec<u16>> {
+    async fn read_holding_registers(&self, address: u16, count: u16) -> Result<Vec<u16>> {
         let data = [
             (address >> 8) as u8,
             address as u8,
@@ -195,6 +329,150 @@ impl QnSmartPlcProtocol {
         }
     }
 
+    /// 写保持寄存器 (Function Code 0x06 / 0x10)
+    async fn write_registers(&self, address: u16, values: &[u16]) -> Result<()> {
+        let (function_code, data) = if values.len() == 1 {
+            (
+                FC_WRITE_SINGLE_REGISTER,
+                vec![
+                    (address >> 8) as u8,
+                    address as u8,
+                    (values[0] >> 8) as u8,
+                    values[0] as u8,
+                ],
+            )
+        } else {
+            let count = values.len() as u16;
+            let mut data = vec![
+                (address >> 8) as u8,
+                address as u8,
+                (count >> 8) as u8,
+                count as u8,
+                (count * 2) as u8,
+            ];
+            for v in values {
+                data.extend_from_slice(&v.to_be_bytes());
+            }
+            (FC_WRITE_MULTIPLE_REGISTERS, data)
+        };
+
+        let request = self.build_request(function_code, &data);
+        let response = self.send_request(&request).await?;
+
+        if response.len() >= 12 && response[7] == function_code {
+            info!(
+                "写入寄存器成功: addr=0x{:04X}, values={:?}",
+                address, values
+            );
+            Ok(())
+        } else if response.len() >= 9 && response[7] == (function_code | 0x80) {
+            let error_code = response[8];
+            error!("Modbus异常: 0x{:02X}", error_code);
+            Err(DeviceError::ProtocolError(format!(
+                "Modbus异常: 0x{:02X}",
+                error_code
+            )))
+        } else {
+            error!("无效响应: {:02X?}", response);
+            Err(DeviceError::ProtocolError("无效响应".to_string()))
+        }
+    }
+
+    /// 加载标签配置，按名称排序后依次分配节点 ID
+    fn load_tags(&mut self, tags: &Value) -> Result<()> {
+        let tags = tags
+            .as_object()
+            .ok_or_else(|| DeviceError::ConfigError("tags 必须是对象".to_string()))?;
+        for (index, (name, config)) in tags.iter().enumerate() {
+            let config: QnTagConfig = serde_json::from_value(config.clone()).map_err(|e| {
+                DeviceError::ConfigError(format!("标签 '{}' 解析失败: {}", name, e))
+            })?;
+            let tag = QnTag::parse(name, TAG_ID_BASE + index as u32, config)?;
+            self.tag_ids.insert(tag.id, name.clone());
+            self.tags.insert(name.clone(), tag);
+        }
+        Ok(())
+    }
+
+    /// 按名称查找标签
+    fn find_tag(&self, name: &str) -> Result<&QnTag> {
+        self.tags
+            .get(name)
+            .ok_or_else(|| DeviceError::ConfigError(format!("未定义的标签: {}", name)))
+    }
+
+    /// 读取标签值（已应用 scale）
+    async fn read_tag(&self, tag: &QnTag) -> Result<Value> {
+        let raw = match tag.area {
+            QnTagArea::Switch(ch) => return Ok(Value::Bool(self.read(ch).await? == 1)),
+            QnTagArea::Coil(addr) => {
+                let coils = self.read_coils(addr, 1).await?;
+                let on = coils
+                    .first()
+                    .copied()
+                    .ok_or_else(|| DeviceError::ProtocolError("读取线圈失败".to_string()))?;
+                return Ok(Value::Bool(on));
+            }
+            QnTagArea::Register(addr, data_type) => {
+                let registers = self
+                    .read_holding_registers(addr, data_type.register_count())
+                    .await?;
+                if registers.len() < data_type.register_count() as usize {
+                    return Err(DeviceError::ProtocolError(format!(
+                        "标签 '{}' 读取寄存器数量不足",
+                        tag.name
+                    )));
+                }
+                ModbusProtocol::registers_to_value(&registers, data_type, RegisterOrder::default())?
+            }
+        };
+
+        match (tag.scale, raw.as_f64()) {
+            (Some(scale), Some(v)) => Ok(json!(v * scale)),
+            _ => Ok(raw),
+        }
+    }
+
+    /// 写入标签值（工程值，按 scale 反向换算）
+    async fn write_tag(&self, tag: &QnTag, value: &Value) -> Result<()> {
+        if tag.readonly {
+            return Err(DeviceError::Other(format!("标签 '{}' 为只读", tag.name)));
+        }
+
+        let as_bool = || {
+            value
+                .as_bool()
+                .or_else(|| value.as_f64().map(|v| v != 0.0))
+                .ok_or_else(|| DeviceError::Other(format!("标签 '{}' 需要布尔值", tag.name)))
+        };
+
+        match tag.area {
+            QnTagArea::Switch(ch) => self.control_channel(ch, as_bool()?).await,
+            QnTagArea::Coil(addr) => self.write_coil(addr, as_bool()?).await,
+            QnTagArea::Register(addr, data_type) => {
+                let value = match (tag.scale, value.as_f64()) {
+                    (Some(scale), Some(v)) if scale != 0.0 => {
+                        let raw = v / scale;
+                        if matches!(
+                            data_type,
+                            ModbusDataType::Float32
+                                | ModbusDataType::Float32LE
+                                | ModbusDataType::Float64
+                        ) {
+                            json!(raw)
+                        } else {
+                            json!(raw.round() as i64)
+                        }
+                    }
+                    _ => value.clone(),
+                };
+                let registers =
+                    ModbusProtocol::value_to_registers(value, data_type, RegisterOrder::default())?;
+                self.write_registers(addr, &registers).await
+            }
+        }
+    }
+
     /// 计算通道的启动/关闭地址
     fn get_channel_address(channel: u32, on: bool) -> Result<u16> {
         if channel < 1 || channel > 40 {
@@ -302,7 +580,7 @@ impl QnSmartPlcProtocol {
     }
 
     /// 控制单个通道
-    pub async fn control_channel(&mut self, channel: u32, on: bool) -> Result<()> {
+    pub async fn control_channel(&self, channel: u32, on: bool) -> Result<()> {
         let addr = Self::get_channel_address(channel, on)?;
         info!(
             "控制通道{}: {} (addr=0x{:04X})",
@@ -465,11 +743,20 @@ impl Protocol for QnSmartPlcProtocol {
             .and_then(|v| v.as_u64())
             .unwrap_or(0x32) as u8; // 默认 0x32 (50)
 
+        let mut protocol = Self::new(addr, port, slave_id);
+
+        if let Some(tags) = params.get("tags") {
+            protocol.load_tags(tags)?;
+        }
+
         info!(
-            "创建 QN Smart PLC 协议: {}:{}, slave_id=0x{:02X}",
-            addr, port, slave_id
+            "创建 QN Smart PLC 协议: {}:{}, slave_id=0x{:02X}, 标签数: {}",
+            protocol.addr,
+            protocol.port,
+            protocol.slave_id,
+            protocol.tags.len()
         );
-        Ok(Box::new(Self::new(addr, port, slave_id)))
+        Ok(Box::new(protocol))
     }
 
     async fn execute(&mut self, command: &str, params: Value) -> Result<Value> {
@@ -518,6 +805,40 @@ impl Protocol for QnSmartPlcProtocol {
             }
             "read_external_sensors" => self.read_external_sensors().await,
             "read_current" => self.read_current().await,
+            "resolve_tag" => {
+                let name = params
+                    .get("tag")
+                    .and_then(|v| v.as_str())
+                    .ok_or(DeviceError::ConfigError("缺少 tag 参数".to_string()))?;
+                Ok(self.find_tag(name)?.to_json())
+            }
+            "list_tags" => {
+                let mut tags: Vec<&QnTag> = self.tags.values().collect();
+                tags.sort_by_key(|t| t.id);
+                let tags: Vec<Value> = tags.iter().map(|t| t.to_json()).collect();
+                Ok(json!({"status": "success", "tags": tags}))
+            }
+            "read_tag" => {
+                let name = params
+                    .get("tag")
+                    .and_then(|v| v.as_str())
+                    .ok_or(DeviceError::ConfigError("缺少 tag 参数".to_string()))?;
+                let tag = self.find_tag(name)?;
+                let value = self.read_tag(tag).await?;
+                Ok(json!({"status": "success", "tag": name, "value": value}))
+            }
+            "write_tag" => {
+                let name = params
+                    .get("tag")
+                    .and_then(|v| v.as_str())
+                    .ok_or(DeviceError::ConfigError("缺少 tag 参数".to_string()))?;
+                let value = params
+                    .get("value")
+                    .ok_or(DeviceError::ConfigError("缺少 value 参数".to_string()))?;
+                let tag = self.find_tag(name)?;
+                self.write_tag(tag, value).await?;
+                Ok(json!({"status": "success", "tag": name, "value": value}))
+            }
             _ => Err(DeviceError::Other(format!("未知命令: {}", command))),
         }
     }
@@ -527,11 +848,19 @@ impl Protocol for QnSmartPlcProtocol {
             "protocol": "qn-smart-plc",
             "addr": self.addr,
             "port": self.port,
-            "slave_id": self.slave_id
+            "slave_id": self.slave_id,
+            "tags": self.tags.len()
         }))
     }
 
     async fn write(&mut self, id: u32, value: i32) -> Result<()> {
+        // id >= TAG_ID_BASE: 标签节点
+        if let Some(name) = self.tag_ids.get(&id) {
+            let tag = self.find_tag(name)?;
+            info!("写入标签 {} (id={}): {}", name, id, value);
+            return self.write_tag(tag, &json!(value)).await;
+        }
+
         // id: 通道号 (1-40)
         // value: 1=启动, 0=关闭
         //
@@ -571,6 +900,18 @@ impl Protocol for QnSmartPlcProtocol {
         //
         // 响应解析: 最后一个字节的 Bit 对应通道状态
         //   Bit3 = 组内第1路, Bit2 = 第2路, Bit1 = 第3路, Bit0 = 第4路
+        // id >= TAG_ID_BASE: 标签节点（浮点值四舍五入为整数）
+        if let Some(name) = self.tag_ids.get(&id) {
+            let tag = self.find_tag(name)?;
+            let value = self.read_tag(tag).await?;
+            return match value {
+                Value::Bool(b) => Ok(b as i32),
+                v => v.as_f64().map(|f| f.round() as i32).ok_or_else(|| {
+                    DeviceError::ProtocolError(format!("标签 {} 的值无法转换为整数", name))
+                }),
+            };
+        }
+
         info!("读取通道 {} 状态", id);
 
         if id < 1 || id > 40 {
@@ -588,9 +929,8 @@ impl Protocol for QnSmartPlcProtocol {
         // 构建读取请求
         // 注意：这里需要 &mut self，但 trait 定义是 &self
         // 所以我们需要手动构建请求
-        let addr_str = format!("{}:{}", self.addr, self.port);
-
-        let mut stream = TcpStream::connect(&addr_str).await.map_err(|e| {
+        let socket_addr = dns::resolve(&self.addr, self.port).await?;
+        let mut stream = TcpStream::connect(socket_addr).await.map_err(|e| {
             error!("连接失败: {}", e);
             DeviceError::ConnectionError(format!("连接失败: {}", e))
         })?;
@@ -675,6 +1015,130 @@ impl Protocol for QnSmartPlcProtocol {
             "read_zero_line_temp".to_string(),
             "read_external_sensors".to_string(),
             "read_current".to_string(),
+            "resolve_tag".to_string(),
+            "list_tags".to_string(),
+            "read_tag".to_string(),
+            "write_tag".to_string(),
         ]
     }
+
+    async fn call_method(&mut self, method_name: &str, args: Value) -> Result<Value> {
+        self.execute(method_name, args).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_peer::MockPeer;
+
+    fn tag_config(value: Value) -> QnTagConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn protocol(port: u16) -> QnSmartPlcProtocol {
+        let mut protocol = QnSmartPlcProtocol::new("127.0.0.1".into(), port, 0x32);
+        protocol
+            .load_tags(&json!({
+                "stage_power": { "area": "switch", "addr": 1 },
+                "cabinet_temp": { "area": "register", "addr": "0x0400", "scale": 0.1 },
+                "lights": { "area": "coil", "addr": 16, "readonly": true }
+            }))
+            .unwrap();
+        protocol
+    }
+
+    /// 模拟 PLC：记录请求帧；写寄存器原样回显，读寄存器返回 0x00EB (235)
+    async fn mock_plc() -> MockPeer {
+        MockPeer::start(|request| {
+            let response = if request[7] == FC_READ_HOLDING_REGISTERS {
+                let mut r = request[..4].to_vec();
+                r.extend_from_slice(&[0x00, 0x05, request[6], 0x03, 0x02, 0x00, 0xEB]);
+                r
+            } else {
+                request.to_vec()
+            };
+            vec![response]
+        })
+        .await
+    }
+
+    #[test]
+    fn test_tag_addresses_and_areas_are_validated() {
+        let tag = QnTag::parse(
+            "t",
+            TAG_ID_BASE,
+            tag_config(json!({ "area": "holding", "addr": "0x03E8", "type": "int32" })),
+        )
+        .unwrap();
+        assert!(matches!(
+            tag.area,
+            QnTagArea::Register(0x03E8, ModbusDataType::Int32)
+        ));
+        assert_eq!(tag.to_json()["addr"], 1000);
+
+        for config in [
+            json!({ "area": "switch", "addr": 41 }),
+            json!({ "area": "coil", "addr": "0xZZ" }),
+            json!({ "area": "coil", "addr": 70000 }),
+            json!({ "area": "register", "addr": 1, "type": "bool" }),
+            json!({ "area": "input", "addr": 1 }),
+        ] {
+            assert!(
+                QnTag::parse("t", TAG_ID_BASE, tag_config(config.clone())).is_err(),
+                "{}",
+                config
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tags_resolve_by_name_and_unknown_tags_fail() {
+        let mut protocol = protocol(502);
+
+        let resolved = protocol
+            .execute("resolve_tag", json!({ "tag": "stage_power" }))
+            .await
+            .unwrap();
+        assert_eq!(resolved["area"], "switch");
+        let listed = protocol.execute("list_tags", json!({})).await.unwrap();
+        let ids: Vec<u64> = listed["tags"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(ids, vec![1000, 1001, 1002]);
+
+        assert!(protocol
+            .execute("resolve_tag", json!({ "tag": "missing" }))
+            .await
+            .is_err());
+        assert!(protocol
+            .execute("write_tag", json!({ "tag": "lights", "value": true }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_tags_encode_scaled_modbus_frames() {
+        let plc = mock_plc().await;
+        let protocol = protocol(plc.port());
+        let tag = protocol.find_tag("cabinet_temp").unwrap().clone();
+
+        // 23.5 / 0.1 = 235 -> 写单个寄存器 0x0400
+        protocol.write_tag(&tag, &json!(23.5)).await.unwrap();
+        let value = protocol.read_tag(&tag).await.unwrap();
+        assert!((value.as_f64().unwrap() - 23.5).abs() < 1e-9);
+
+        let requests = plc.requests();
+        assert_eq!(
+            requests[0],
+            [0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x32, 0x06, 0x04, 0x00, 0x00, 0xEB]
+        );
+        assert_eq!(
+            requests[1],
+            [0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x32, 0x03, 0x04, 0x00, 0x00, 0x01]
+        );
+    }
 }
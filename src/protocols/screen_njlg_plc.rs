@@ -1,25 +1,92 @@
 /// 南京龙港 PLC 屏幕控制协议
 /// 基于 Modbus ASCII 变种
+///
+/// 设备不提供位置回读，位置反馈按行程时间估算：
+/// 0 = 完全关闭（收起），100 = 完全打开（降下）。
 use async_trait::async_trait;
-use serde_json::Value;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
+use tokio::sync::{broadcast, Mutex};
 use tracing::{debug, info, warn};
 
-use crate::protocols::Protocol;
-use crate::utils::{DeviceError, Result};
+use crate::device::DeviceEvent;
+use crate::protocols::{EventSink, Protocol};
+use crate::utils::checksum::ChecksumAlgorithm;
+use crate::utils::{dns, net, DeviceError, Result};
 
 /// 协议常量
 const START_BYTE: u8 = 0x3A; // ':'
 const END_BYTES: [u8; 2] = [0x0D, 0x0A]; // '\r\n'
-const COMMAND_LENGTH: usize = 21;
+const COMMAND_LENGTH: usize = 19;
 const RESPONSE_LENGTH: usize = 17;
 
 /// 操作码
 const OP_OPEN: &str = "01";
 const OP_CLOSE: &str = "02";
 
+/// 反馈节点 ID 偏移：101-110 位置 (0-100)，201-210 运动中 (1/0)
+const NODE_POSITION_BASE: u32 = 100;
+const NODE_MOVING_BASE: u32 = 200;
+
+/// 默认全行程时间（毫秒）
+const DEFAULT_TRAVEL_TIME_MS: u64 = 30000;
+
+const POSITION_CLOSED: f64 = 0.0;
+const POSITION_OPEN: f64 = 100.0;
+
+/// 单个设备的运动安全配置
+#[derive(Debug, Clone, Default, Deserialize)]
+struct DeviceMotionConfig {
+    /// 全行程时间（毫秒），缺省使用通道级 travel_time_ms
+    #[serde(default)]
+    travel_time_ms: Option<u64>,
+    /// 允许到达的最小位置 (0-100)
+    #[serde(default)]
+    min_position: Option<f64>,
+    /// 允许到达的最大位置 (0-100)
+    #[serde(default)]
+    max_position: Option<f64>,
+    /// 互锁设备：其中任一设备运动中时禁止本设备动作
+    #[serde(default)]
+    interlock: Vec<u32>,
+}
+
+/// 设备运动状态（估算）
+#[derive(Debug, Clone)]
+struct MotionState {
+    from: f64,
+    to: f64,
+    started: Instant,
+    /// 本次运动的持续时间
+    duration: Duration,
+    /// 运动序号，用于判断完成事件是否已被新命令取代
+    generation: u64,
+}
+
+impl MotionState {
+    /// 按经过时间线性估算位置
+    fn position_at(&self, elapsed: Duration) -> f64 {
+        if self.duration.is_zero() || elapsed >= self.duration {
+            return self.to;
+        }
+        let ratio = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.from + (self.to - self.from) * ratio
+    }
+
+    fn position(&self) -> f64 {
+        self.position_at(self.started.elapsed())
+    }
+
+    fn is_moving(&self) -> bool {
+        self.started.elapsed() < self.duration
+    }
+}
+
 /// 南京龙港 PLC 协议配置
 #[derive(Debug)]
 struct NjlgPlcConfig {
@@ -34,6 +101,13 @@ pub struct ScreenNjlgPlcProtocol {
     addr: String,
     port: u16,
     timeout: std::time::Duration,
+    /// 通道级默认全行程时间
+    travel_time: Duration,
+    /// 设备运动安全配置 (device_id -> 配置)
+    devices: HashMap<u32, DeviceMotionConfig>,
+    /// 设备运动状态 (device_id -> 状态)
+    motion: Arc<Mutex<HashMap<u32, MotionState>>>,
+    events: EventSink,
 }
 
 impl ScreenNjlgPlcProtocol {
@@ -44,7 +118,7 @@ impl ScreenNjlgPlcProtocol {
     /// - operation: 操作 ("01"=开, "02"=关)
     ///
     /// # 返回
-    /// 21字节的完整命令
+    /// 19字节的完整命令
     ///
     /// # 命令格式
     /// :00100B[X]000100[Y][CK]\r\n
@@ -88,7 +162,7 @@ impl ScreenNjlgPlcProtocol {
         let lrc_str = format!("{:02X}", lrc);
 
         // 完整命令: : + 数据 + 校验和 + \r\n
-        let mut cmd = Vec::with_capacity(21);
+        let mut cmd = Vec::with_capacity(COMMAND_LENGTH);
         cmd.push(b':');
         cmd.extend_from_slice(data_str.as_bytes());
         cmd.extend_from_slice(lrc_str.as_bytes());
@@ -107,7 +181,7 @@ impl ScreenNjlgPlcProtocol {
     ///
     /// # 参数
     /// - ascii_str: ASCII 十六进制字符串（不含起始符、校验和、结束符）
-    ///   例如: "0010000B0000100001"
+    ///   例如: "00100B00001001"
     ///
     /// # 返回
     /// LRC 校验和（8位）
@@ -125,24 +199,21 @@ impl ScreenNjlgPlcProtocol {
             )));
         }
 
-        let mut sum: u8 = 0;
-
         // 每2个 ASCII 字符转换为1个字节
-        for i in (0..ascii_str.len()).step_by(2) {
-            let hex_byte = &ascii_str[i..i + 2];
-            let byte = u8::from_str_radix(hex_byte, 16).map_err(|e| {
-                DeviceError::ConfigError(format!("无效的十六进制字符串 '{}': {}", hex_byte, e))
-            })?;
-            sum = sum.wrapping_add(byte);
-        }
+        let bytes = (0..ascii_str.len())
+            .step_by(2)
+            .map(|i| {
+                let hex_byte = &ascii_str[i..i + 2];
+                u8::from_str_radix(hex_byte, 16).map_err(|e| {
+                    DeviceError::ConfigError(format!("无效的十六进制字符串 '{}': {}", hex_byte, e))
+                })
+            })
+            .collect::<Result<Vec<u8>>>()?;
 
         // LRC = 负的和的二进制补码 = (!sum) + 1
-        let lrc = (!sum).wrapping_add(1);
+        let lrc = ChecksumAlgorithm::Lrc.compute(&bytes) as u8;
 
-        debug!(
-            "LRC 计算 - 输入: {}, 字节和: 0x{:02X}, LRC: 0x{:02X}",
-            ascii_str, sum, lrc
-        );
+        debug!("LRC 计算 - 输入: {}, LRC: 0x{:02X}", ascii_str, lrc);
 
         Ok(lrc)
     }
@@ -210,10 +281,11 @@ impl ScreenNjlgPlcProtocol {
         let command = Self::build_command(device_id, operation)?;
 
         // 连接设备
-        let addr = format!("{}:{}", self.addr, self.port);
+        let addr = net::display_addr(&self.addr, self.port);
         debug!("连接到 {}", addr);
 
-        let mut stream = tokio::time::timeout(self.timeout, TcpStream::connect(&addr))
+        let socket_addr = dns::resolve(&self.addr, self.port).await?;
+        let mut stream = tokio::time::timeout(self.timeout, TcpStream::connect(socket_addr))
             .await
             .map_err(|_| DeviceError::Timeout)?
             .map_err(|e| DeviceError::ConnectionError(format!("连接失败: {}", e)))?;
@@ -238,6 +310,170 @@ impl ScreenNjlgPlcProtocol {
         // 解析响应
         Self::parse_response(&response)
     }
+
+    fn travel_time_for(&self, device_id: u32) -> Duration {
+        self.devices
+            .get(&device_id)
+            .and_then(|c| c.travel_time_ms)
+            .map(Duration::from_millis)
+            .unwrap_or(self.travel_time)
+    }
+
+    /// 检查行程限制与互锁，通过后下发命令并开始估算运动
+    ///
+    /// `force` 为 true 时跳过互锁检查（行程限制始终生效）
+    async fn move_device(&self, device_id: u32, operation: &str, force: bool) -> Result<bool> {
+        let target = if operation == OP_OPEN {
+            POSITION_OPEN
+        } else {
+            POSITION_CLOSED
+        };
+        let config = self.devices.get(&device_id).cloned().unwrap_or_default();
+
+        // 行程限制
+        let min = config.min_position.unwrap_or(POSITION_CLOSED);
+        let max = config.max_position.unwrap_or(POSITION_OPEN);
+        if target < min || target > max {
+            warn!(
+                "通道 {} 设备 {} 目标位置 {} 超出行程限制 [{}, {}]",
+                self.channel_id, device_id, target, min, max
+            );
+            return Err(DeviceError::Other(format!(
+                "设备 {} 目标位置 {} 超出行程限制 [{}, {}]",
+                device_id, target, min, max
+            )));
+        }
+
+        // 互锁
+        if !force {
+            let motion = self.motion.lock().await;
+            if let Some(blocker) = config
+                .interlock
+                .iter()
+                .find(|id| motion.get(id).map(|m| m.is_moving()).unwrap_or(false))
+            {
+                warn!(
+                    "通道 {} 设备 {} 被互锁：设备 {} 正在运动",
+                    self.channel_id, device_id, blocker
+                );
+                self.events.emit(
+                    "motion_blocked",
+                    json!({ "device_id": device_id, "blocked_by": blocker }),
+                );
+                return Err(DeviceError::Other(format!(
+                    "设备 {} 被互锁：设备 {} 正在运动",
+                    device_id, blocker
+                )));
+            }
+        }
+
+        let success = self.execute_control(device_id, operation).await?;
+        if success {
+            self.start_motion(device_id, target).await;
+        }
+        Ok(success)
+    }
+
+    /// 记录运动起点并在预计到位时发送完成事件
+    async fn start_motion(&self, device_id: u32, target: f64) {
+        let full_travel = self.travel_time_for(device_id);
+
+        let (state, from) = {
+            let mut motion = self.motion.lock().await;
+            let previous = motion.get(&device_id);
+            // 起点未知时按全行程估算
+            let from = previous
+                .map(|m| m.position())
+                .unwrap_or(if target == POSITION_OPEN {
+                    POSITION_CLOSED
+                } else {
+                    POSITION_OPEN
+                });
+            let generation = previous.map(|m| m.generation + 1).unwrap_or(1);
+            let distance = (target - from).abs() / (POSITION_OPEN - POSITION_CLOSED);
+            let state = MotionState {
+                from,
+                to: target,
+                started: Instant::now(),
+                duration: full_travel.mul_f64(distance),
+                generation,
+            };
+            motion.insert(device_id, state.clone());
+            (state, from)
+        };
+
+        let action = if target == POSITION_OPEN {
+            "open"
+        } else {
+            "close"
+        };
+        info!(
+            "通道 {} 设备 {} 开始{}: {:.0} -> {:.0}, 预计 {}ms",
+            self.channel_id,
+            device_id,
+            if target == POSITION_OPEN {
+                "打开"
+            } else {
+                "关闭"
+            },
+            from,
+            target,
+            state.duration.as_millis()
+        );
+        self.events.emit(
+            "motion_started",
+            json!({
+                "device_id": device_id,
+                "action": action,
+                "from": from,
+                "to": target,
+                "duration_ms": state.duration.as_millis() as u64
+            }),
+        );
+
+        let motion = self.motion.clone();
+        let events = self.events.clone();
+        let channel_id = self.channel_id;
+        tokio::spawn(async move {
+            tokio::time::sleep(state.duration).await;
+            // 运动期间收到新命令时，由新命令负责发送完成事件
+            let current = motion.lock().await.get(&device_id).map(|m| m.generation);
+            if current != Some(state.generation) {
+                return;
+            }
+            info!(
+                "通道 {} 设备 {} 运动完成, 位置 {:.0}",
+                channel_id, device_id, target
+            );
+            events.emit(
+                "motion_complete",
+                json!({
+                    "device_id": device_id,
+                    "action": action,
+                    "position": target
+                }),
+            );
+        });
+    }
+
+    /// 设备位置与运动状态
+    async fn device_feedback(&self, device_id: u32) -> Value {
+        let motion = self.motion.lock().await;
+        match motion.get(&device_id) {
+            Some(m) => json!({
+                "device_id": device_id,
+                "position": m.position().round(),
+                "target": m.to,
+                "moving": m.is_moving()
+            }),
+            None => json!({
+                "device_id": device_id,
+                "position": Value::Null,
+                "target": Value::Null,
+                "moving": false
+            }),
+        }
+    }
 }
 
 #[async_trait]
@@ -263,9 +499,29 @@ impl Protocol for ScreenNjlgPlcProtocol {
             .and_then(|v| v.as_u64())
             .unwrap_or(3000);
 
+        let travel_time_ms = params
+            .get("travel_time_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_TRAVEL_TIME_MS);
+
+        let devices: HashMap<String, DeviceMotionConfig> = params
+            .get("devices")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| DeviceError::ConfigError(format!("devices 解析失败: {}", e)))?
+            .unwrap_or_default();
+        let devices = devices
+            .into_iter()
+            .map(|(k, v)| {
+                k.parse::<u32>()
+                    .map(|id| (id, v))
+                    .map_err(|_| DeviceError::ConfigError(format!("无效的设备编号: {}", k)))
+            })
+            .collect::<Result<HashMap<u32, DeviceMotionConfig>>>()?;
+
         info!(
-            "初始化南京龙港PLC协议 - 通道{}, 地址: {}:{}",
-            channel_id, addr, port
+            "初始化南京龙港PLC协议 - 通道{}, 地址: {}:{}, 行程时间: {}ms",
+            channel_id, addr, port, travel_time_ms
         );
 
         Ok(Box::new(Self {
@@ -273,6 +529,10 @@ impl Protocol for ScreenNjlgPlcProtocol {
             addr,
             port,
             timeout: std::time::Duration::from_millis(timeout_ms),
+            travel_time: Duration::from_millis(travel_time_ms),
+            devices,
+            motion: Arc::new(Mutex::new(HashMap::new())),
+            events: EventSink::new(channel_id),
         }))
     }
 
@@ -289,8 +549,9 @@ impl Protocol for ScreenNjlgPlcProtocol {
                     .ok_or_else(|| DeviceError::Other("缺少 value 参数".to_string()))?;
 
                 let operation = if value == 1 { OP_OPEN } else { OP_CLOSE };
+                let force = params["force"].as_bool().unwrap_or(false);
 
-                let success = self.execute_control(device_id, operation).await?;
+                let success = self.move_device(device_id, operation, force).await?;
 
                 Ok(serde_json::json!({
                     "success": success,
@@ -298,6 +559,22 @@ impl Protocol for ScreenNjlgPlcProtocol {
                     "value": value
                 }))
             }
+            "get_position" => {
+                let device_id = params["device_id"]
+                    .as_u64()
+                    .ok_or_else(|| DeviceError::Other("缺少 device_id 参数".to_string()))?
+                    as u32;
+                Ok(self.device_feedback(device_id).await)
+            }
+            "get_positions" => {
+                let mut ids: Vec<u32> = self.motion.lock().await.keys().copied().collect();
+                ids.sort_unstable();
+                let mut list = Vec::new();
+                for id in ids {
+                    list.push(self.device_feedback(id).await);
+                }
+                Ok(json!({ "devices": list }))
+            }
             _ => Err(DeviceError::ProtocolError(format!(
                 "不支持的命令: {}",
                 command
@@ -306,25 +583,61 @@ impl Protocol for ScreenNjlgPlcProtocol {
     }
 
     async fn get_status(&self) -> Result<Value> {
+        let moving: Vec<u32> = self
+            .motion
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, m)| m.is_moving())
+            .map(|(id, _)| *id)
+            .collect();
         Ok(serde_json::json!({
             "protocol": "screen_njlg_plc",
             "channel_id": self.channel_id,
-            "addr": format!("{}:{}", self.addr, self.port),
-            "connected": true
+            "addr": net::display_addr(&self.addr, self.port),
+            "connected": true,
+            "moving": moving
         }))
     }
 
     async fn write(&mut self, device_id: u32, value: i32) -> Result<()> {
         let operation = if value == 1 { OP_OPEN } else { OP_CLOSE };
-        self.execute_control(device_id, operation).await?;
+        self.move_device(device_id, operation, false).await?;
         Ok(())
     }
 
-    async fn read(&self, _device_id: u32) -> Result<i32> {
-        // 该协议不支持读取状态
-        Err(DeviceError::ProtocolError(
-            "南京龙港PLC协议不支持读取操作".to_string(),
-        ))
+    /// 读取估算反馈（设备本身不支持读取状态）
+    ///
+    /// - 1-10: 最近一次命令的目标状态 (1=开, 0=关)
+    /// - 101-110: 位置 (0-100)
+    /// - 201-210: 是否运动中 (1/0)
+    async fn read(&self, id: u32) -> Result<i32> {
+        let (device_id, kind) = match id {
+            1..=10 => (id, 0),
+            i if i > NODE_POSITION_BASE && i <= NODE_POSITION_BASE + 10 => {
+                (i - NODE_POSITION_BASE, 1)
+            }
+            i if i > NODE_MOVING_BASE && i <= NODE_MOVING_BASE + 10 => (i - NODE_MOVING_BASE, 2),
+            _ => {
+                return Err(DeviceError::ProtocolError(format!(
+                    "南京龙港PLC协议不支持读取节点 {}",
+                    id
+                )))
+            }
+        };
+
+        let motion = self.motion.lock().await;
+        let state = motion.get(&device_id);
+        match (kind, state) {
+            (2, None) => Ok(0),
+            (2, Some(m)) => Ok(m.is_moving() as i32),
+            (_, None) => Err(DeviceError::ProtocolError(format!(
+                "设备 {} 尚无位置反馈（未下发过命令）",
+                device_id
+            ))),
+            (1, Some(m)) => Ok(m.position().round() as i32),
+            (_, Some(m)) => Ok((m.to == POSITION_OPEN) as i32),
+        }
     }
 
     fn name(&self) -> &str {
@@ -339,7 +652,8 @@ impl Protocol for ScreenNjlgPlcProtocol {
                     .ok_or_else(|| DeviceError::Other("缺少 device_id 参数".to_string()))?
                     as u32;
 
-                self.execute_control(device_id, OP_OPEN).await?;
+                let force = args["force"].as_bool().unwrap_or(false);
+                self.move_device(device_id, OP_OPEN, force).await?;
 
                 Ok(serde_json::json!({
                     "result": "ok",
@@ -353,7 +667,8 @@ impl Protocol for ScreenNjlgPlcProtocol {
                     .ok_or_else(|| DeviceError::Other("缺少 device_id 参数".to_string()))?
                     as u32;
 
-                self.execute_control(device_id, OP_CLOSE).await?;
+                let force = args["force"].as_bool().unwrap_or(false);
+                self.move_device(device_id, OP_CLOSE, force).await?;
 
                 Ok(serde_json::json!({
                     "result": "ok",
@@ -381,6 +696,7 @@ impl Protocol for ScreenNjlgPlcProtocol {
                     }
                 };
 
+                let force = args["force"].as_bool().unwrap_or(false);
                 let mut results = Vec::new();
                 for device in devices {
                     let device_id = device
@@ -388,7 +704,7 @@ impl Protocol for ScreenNjlgPlcProtocol {
                         .ok_or_else(|| DeviceError::Other("设备ID必须是数字".to_string()))?
                         as u32;
 
-                    match self.execute_control(device_id, operation).await {
+                    match self.move_device(device_id, operation, force).await {
                         Ok(_) => results.push(serde_json::json!({
                             "device_id": device_id,
                             "success": true
@@ -412,6 +728,7 @@ impl Protocol for ScreenNjlgPlcProtocol {
                     "results": results
                 }))
             }
+            "get_position" | "get_positions" => self.execute(method_name, args).await,
             _ => Err(DeviceError::Other(format!(
                 "协议 {} 不支持自定义方法: {}",
                 self.name(),
@@ -425,40 +742,49 @@ impl Protocol for ScreenNjlgPlcProtocol {
             "open_device".to_string(),
             "close_device".to_string(),
             "batch_control".to_string(),
+            "get_position".to_string(),
+            "get_positions".to_string(),
         ]
     }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<DeviceEvent>) {
+        self.events.attach(event_tx);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // 期望帧按命令格式与 LRC 算法手工推算：数据 7 字节，LRC = 0x100 - 字节和（低 8 位）
+
     #[test]
     fn test_build_command_device1_open() {
         let cmd = ScreenNjlgPlcProtocol::build_command(1, "01").unwrap();
-        let expected = b":00100B0000100013E\r\n";
+        let expected = b":00100B00001001D4\r\n";
         assert_eq!(cmd, expected, "设备1打开命令错误");
+        assert_eq!(cmd.len(), COMMAND_LENGTH);
     }
 
     #[test]
     fn test_build_command_device1_close() {
         let cmd = ScreenNjlgPlcProtocol::build_command(1, "02").unwrap();
-        let expected = b":00100B0000100023D\r\n";
+        let expected = b":00100B00001002D3\r\n";
         assert_eq!(cmd, expected, "设备1关闭命令错误");
     }
 
     #[test]
     fn test_build_command_device2_open() {
         let cmd = ScreenNjlgPlcProtocol::build_command(2, "01").unwrap();
-        let expected = b":00100B1000100013D\r\n";
+        let expected = b":00100B10001001C4\r\n";
         assert_eq!(cmd, expected, "设备2打开命令错误");
     }
 
     #[test]
     fn test_lrc_calculation() {
-        // 测试设备1打开的 LRC
+        // 设备1打开: 00 10 0B 00 00 10 01，字节和 0x2C，LRC = 0x100 - 0x2C = 0xD4
         let lrc = ScreenNjlgPlcProtocol::calculate_lrc_from_ascii("00100B00001001").unwrap();
-        assert_eq!(lrc, 0x3E, "LRC 计算错误");
+        assert_eq!(lrc, 0xD4, "LRC 计算错误");
     }
 
     #[test]
@@ -470,9 +796,35 @@ mod tests {
         assert!(result.is_err(), "应拒绝设备ID大于10");
     }
 
+    #[test]
+    fn test_motion_position_estimate() {
+        let motion = MotionState {
+            from: POSITION_CLOSED,
+            to: POSITION_OPEN,
+            started: Instant::now(),
+            duration: Duration::from_millis(10000),
+            generation: 1,
+        };
+        assert_eq!(motion.position_at(Duration::ZERO), 0.0);
+        assert_eq!(motion.position_at(Duration::from_millis(2500)), 25.0);
+        assert_eq!(motion.position_at(Duration::from_millis(20000)), 100.0);
+    }
+
     #[test]
     fn test_invalid_operation() {
         let result = ScreenNjlgPlcProtocol::build_command(1, "99");
         assert!(result.is_err(), "应拒绝无效操作码");
     }
+
+    #[test]
+    fn frames_match_golden_captures() {
+        let captures = crate::protocols::fixtures::Captures::load("screen_njlg_plc");
+        // 设备 1、2 的命令帧由上面的组帧测试覆盖
+        for (operation, label) in [(OP_OPEN, "open"), (OP_CLOSE, "close")] {
+            let cmd = ScreenNjlgPlcProtocol::build_command(10, operation).unwrap();
+            captures.assert_frame(&format!("device10_{}", label), &cmd);
+        }
+        assert!(ScreenNjlgPlcProtocol::parse_response(captures.frame("response_ok")).unwrap());
+        assert!(ScreenNjlgPlcProtocol::parse_response(captures.frame("response_bad_end")).is_err());
+    }
 }
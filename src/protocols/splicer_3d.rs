@@ -7,10 +7,17 @@
 // 指令格式: /SetPreset:d,{scene_id},{group};
 // 成功返回: /ack:d,1;
 // 失败返回: /ack:d,0;
+//
+// 功能：保存场景 (SavePreset)
+// 指令格式: /SavePreset:d,{scene_id},{group};
+//
+// 功能：窗口位置/大小 (setWinPos)
+// 指令格式: /setWinPos:d,{window},{x},{y},{width},{height},{group};
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -20,6 +27,7 @@ use tracing::{debug, info, warn};
 
 use crate::protocols::Protocol;
 use crate::utils::error::DeviceError;
+use crate::utils::{dns, net};
 
 // 协议常量
 const DEFAULT_TCP_PORT: u16 = 5000;
@@ -45,6 +53,41 @@ enum ConnectionType {
     },
 }
 
+/// 窗口信号源 (槽位 + 接口 + 信号类型)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SourceRoute {
+    slot: u32,
+    interface: u32,
+    #[serde(rename = "type", alias = "signal_type")]
+    signal_type: u32,
+}
+
+/// 窗口状态（控制器侧记录的最后一次下发值）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WindowState {
+    window: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<SourceRoute>,
+}
+
+/// 窗口布局（对应设备上的一个场景）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Layout {
+    scene_id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    windows: Vec<WindowState>,
+}
+
 /// 3D拼接处理器协议
 pub struct Splicer3dProtocol {
     connection_type: ConnectionType,
@@ -52,6 +95,12 @@ pub struct Splicer3dProtocol {
     group: u32,
     /// 当前场景值
     current_scene: AtomicI32,
+    /// 布局列表 (scene_id -> 布局)，来自配置及 saveLayout
+    layouts: BTreeMap<u32, Layout>,
+    /// 命名信号源 (名称 -> 槽位/接口/类型)
+    sources: HashMap<String, SourceRoute>,
+    /// 当前窗口状态 (window -> 状态)
+    windows: BTreeMap<u32, WindowState>,
 }
 
 impl Splicer3dProtocol {
@@ -61,6 +110,9 @@ impl Splicer3dProtocol {
             connection_type: ConnectionType::Tcp { addr, port },
             group,
             current_scene: AtomicI32::new(0),
+            layouts: BTreeMap::new(),
+            sources: HashMap::new(),
+            windows: BTreeMap::new(),
         }
     }
 
@@ -74,6 +126,9 @@ impl Splicer3dProtocol {
             },
             group,
             current_scene: AtomicI32::new(0),
+            layouts: BTreeMap::new(),
+            sources: HashMap::new(),
+            windows: BTreeMap::new(),
         }
     }
 
@@ -86,6 +141,9 @@ impl Splicer3dProtocol {
             },
             group,
             current_scene: AtomicI32::new(0),
+            layouts: BTreeMap::new(),
+            sources: HashMap::new(),
+            windows: BTreeMap::new(),
         }
     }
 
@@ -95,6 +153,28 @@ impl Splicer3dProtocol {
         format!("/SetPreset:d,{},{};", scene_id, self.group)
     }
 
+    /// 构建保存场景命令
+    /// 格式: /SavePreset:d,{scene_id},{group};
+    fn build_save_preset_command(&self, scene_id: u32) -> String {
+        format!("/SavePreset:d,{},{};", scene_id, self.group)
+    }
+
+    /// 构建设置窗口位置/大小命令
+    /// 格式: /setWinPos:d,{window},{x},{y},{width},{height},{group};
+    fn build_set_win_pos_command(
+        &self,
+        window: u32,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> String {
+        format!(
+            "/setWinPos:d,{},{},{},{},{},{};",
+            window, x, y, width, height, self.group
+        )
+    }
+
     /// 构建设置窗口信号源命令
     /// 格式: /setWinSrc:d,{window},{slot},{interface},{type},{group};
     fn build_set_win_src_command(
@@ -119,22 +199,21 @@ impl Splicer3dProtocol {
     ) -> Result<String, DeviceError> {
         debug!("连接到 TCP 设备: {}:{}", addr, port);
 
-        let mut stream = match tokio::time::timeout(
-            Duration::from_secs(5),
-            TcpStream::connect(format!("{}:{}", addr, port)),
-        )
-        .await
-        {
-            Ok(Ok(s)) => s,
-            Ok(Err(e)) => {
-                warn!("TCP 连接失败: {}", e);
-                return Err(DeviceError::ConnectionError(format!("连接失败: {}", e)));
-            }
-            Err(_) => {
-                warn!("TCP 连接超时");
-                return Err(DeviceError::Other("TCP 连接超时 (5秒)".to_string()));
-            }
-        };
+        let socket_addr = dns::resolve(addr, port).await?;
+        let mut stream =
+            match tokio::time::timeout(Duration::from_secs(5), TcpStream::connect(socket_addr))
+                .await
+            {
+                Ok(Ok(s)) => s,
+                Ok(Err(e)) => {
+                    warn!("TCP 连接失败: {}", e);
+                    return Err(DeviceError::ConnectionError(format!("连接失败: {}", e)));
+                }
+                Err(_) => {
+                    warn!("TCP 连接超时");
+                    return Err(DeviceError::Other("TCP 连接超时 (5秒)".to_string()));
+                }
+            };
 
         info!("TCP 连接成功");
         info!("TCP 发送数据: {}", command);
@@ -176,20 +255,20 @@ impl Splicer3dProtocol {
         local_port: Option<u16>,
         command: &str,
     ) -> Result<String, DeviceError> {
-        info!("UDP 发送数据 {}:{}: {}", addr, port, command);
+        info!(
+            "UDP 发送数据 {}: {}",
+            net::display_addr(addr, port),
+            command
+        );
 
-        let bind_addr = match local_port {
-            Some(port) => format!("0.0.0.0:{}", port),
-            None => "0.0.0.0:0".to_string(),
-        };
+        let target_addr = dns::resolve(addr, port).await?;
+        let bind_addr = net::unspecified_for(&target_addr, local_port.unwrap_or(0));
 
-        let socket = tokio::net::UdpSocket::bind(&bind_addr)
+        let socket = tokio::net::UdpSocket::bind(bind_addr)
             .await
             .map_err(|e| DeviceError::ConnectionError(format!("绑定 UDP socket 失败: {}", e)))?;
         debug!("UDP 本地绑定地址: {:?}", socket.local_addr());
 
-        let target_addr = format!("{}:{}", addr, port);
-
         socket
             .send_to(command.as_bytes(), &target_addr)
             .await
@@ -341,6 +420,117 @@ impl Splicer3dProtocol {
         info!("窗口信号源设置命令已发送");
         Ok(())
     }
+
+    /// 保存当前画面为场景
+    pub async fn save_preset(&self, scene_id: u32) -> Result<bool, DeviceError> {
+        let command = self.build_save_preset_command(scene_id);
+        info!("保存场景: scene_id={}, command={}", scene_id, command);
+
+        let response = self.send_command(&command).await?;
+        let success = self.parse_ack_response(&response);
+
+        if success {
+            info!("场景 {} 保存成功", scene_id);
+        } else {
+            warn!("场景 {} 保存失败，响应: {}", scene_id, response);
+        }
+
+        Ok(success)
+    }
+
+    /// 设置窗口位置/大小
+    pub async fn set_win_pos(
+        &self,
+        window: u32,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Result<bool, DeviceError> {
+        let command = self.build_set_win_pos_command(window, x, y, width, height);
+        info!(
+            "设置窗口位置: window={}, x={}, y={}, width={}, height={}, command={}",
+            window, x, y, width, height, command
+        );
+
+        let response = self.send_command(&command).await?;
+        let success = self.parse_ack_response(&response);
+        if !success {
+            warn!("窗口 {} 位置设置失败，响应: {}", window, response);
+        }
+        Ok(success)
+    }
+
+    /// 解析信号源参数：命名信号源 (source) 或 slot/interface/type
+    fn resolve_source(&self, params: &Value) -> Result<SourceRoute, DeviceError> {
+        if let Some(name) = params["source"].as_str() {
+            return self
+                .sources
+                .get(name)
+                .cloned()
+                .ok_or_else(|| DeviceError::Other(format!("未找到信号源: {}", name)));
+        }
+
+        let slot = params["slot"]
+            .as_u64()
+            .ok_or(DeviceError::Other("缺少 slot 参数".to_string()))? as u32;
+        let interface = params["interface"]
+            .as_u64()
+            .ok_or(DeviceError::Other("缺少 interface 参数".to_string()))?
+            as u32;
+        let signal_type = params["type"]
+            .as_u64()
+            .or_else(|| params["signal_type"].as_u64())
+            .ok_or(DeviceError::Other("缺少 type 参数".to_string()))?
+            as u32;
+
+        Ok(SourceRoute {
+            slot,
+            interface,
+            signal_type,
+        })
+    }
+
+    /// 根据 scene_id 或 name 查找布局
+    fn find_layout(&self, params: &Value) -> Result<Option<&Layout>, DeviceError> {
+        if let Some(name) = params["name"].as_str() {
+            return self
+                .layouts
+                .values()
+                .find(|l| l.name == name)
+                .map(Some)
+                .ok_or_else(|| DeviceError::Other(format!("未找到布局: {}", name)));
+        }
+        let scene_id = params["scene_id"]
+            .as_u64()
+            .or_else(|| params["sceneId"].as_u64())
+            .ok_or(DeviceError::Other("缺少 scene_id 或 name 参数".to_string()))?
+            as u32;
+        Ok(self.layouts.get(&scene_id))
+    }
+
+    /// 切换场景成功后同步控制器侧状态
+    fn apply_scene(&mut self, scene_id: u32) {
+        self.current_scene.store(scene_id as i32, Ordering::SeqCst);
+        if let Some(layout) = self.layouts.get(&scene_id) {
+            if !layout.windows.is_empty() {
+                self.windows = layout
+                    .windows
+                    .iter()
+                    .map(|w| (w.window, w.clone()))
+                    .collect();
+            }
+        }
+    }
+
+    fn layout_to_json(&self, layout: &Layout) -> Value {
+        json!({
+            "scene_id": layout.scene_id,
+            "name": layout.name,
+            "windows": layout.windows,
+            "active": self.current_scene.load(Ordering::SeqCst) == layout.scene_id as i32
+        })
+    }
 }
 
 #[async_trait]
@@ -374,7 +564,21 @@ impl Protocol for Splicer3dProtocol {
             .and_then(|v| v.as_u64())
             .unwrap_or(DEFAULT_GROUP as u64) as u32;
 
-        if use_udp || conn_type.as_deref() == Some("udp") {
+        // 预定义布局与命名信号源
+        let layouts: Vec<Layout> = params
+            .get("layouts")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| DeviceError::ConfigError(format!("layouts 解析失败: {}", e)))?
+            .unwrap_or_default();
+        let sources: HashMap<String, SourceRoute> = params
+            .get("sources")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| DeviceError::ConfigError(format!("sources 解析失败: {}", e)))?
+            .unwrap_or_default();
+
+        let mut protocol = if use_udp || conn_type.as_deref() == Some("udp") {
             // UDP 模式
             let addr = params
                 .get("addr")
@@ -407,7 +611,7 @@ impl Protocol for Splicer3dProtocol {
                     addr, port, group
                 );
             }
-            Ok(Box::new(Self::new_udp(addr, port, group, local_port)))
+            Self::new_udp(addr, port, group, local_port)
         } else if use_tcp && conn_type.as_deref() != Some("serial") {
             // TCP 模式
             let addr = params
@@ -426,7 +630,7 @@ impl Protocol for Splicer3dProtocol {
                 "创建 3D拼接处理器 TCP 协议: {}:{}, group={}",
                 addr, port, group
             );
-            Ok(Box::new(Self::new_tcp(addr, port, group)))
+            Self::new_tcp(addr, port, group)
         } else {
             // 串口模式
             let port_name = params
@@ -447,8 +651,20 @@ impl Protocol for Splicer3dProtocol {
                 "创建 3D拼接处理器 串口协议: {}, 波特率: {}, group={}",
                 port_name, baud_rate, group
             );
-            Ok(Box::new(Self::new_serial(port_name, baud_rate, group)))
+            Self::new_serial(port_name, baud_rate, group)
+        };
+
+        if !layouts.is_empty() || !sources.is_empty() {
+            info!(
+                "3D拼接处理器: 已加载 {} 个布局, {} 个命名信号源",
+                layouts.len(),
+                sources.len()
+            );
         }
+        protocol.layouts = layouts.into_iter().map(|l| (l.scene_id, l)).collect();
+        protocol.sources = sources;
+
+        Ok(Box::new(protocol))
     }
 
     async fn execute(&mut self, command: &str, params: Value) -> crate::utils::Result<Value> {
@@ -465,7 +681,7 @@ impl Protocol for Splicer3dProtocol {
 
                 let success = self.set_preset(scene_id).await?;
                 if success {
-                    self.current_scene.store(scene_id as i32, Ordering::SeqCst);
+                    self.apply_scene(scene_id);
                 }
                 Ok(json!({
                     "success": success,
@@ -481,27 +697,161 @@ impl Protocol for Splicer3dProtocol {
                     .as_u64()
                     .ok_or(DeviceError::Other("缺少 window 参数".to_string()))?
                     as u32;
-                let slot = params["slot"]
+                let route = self.resolve_source(&params)?;
+
+                self.set_win_src(window, route.slot, route.interface, route.signal_type)
+                    .await?;
+                let state = self.windows.entry(window).or_insert_with(|| WindowState {
+                    window,
+                    ..Default::default()
+                });
+                state.source = Some(route.clone());
+                Ok(json!({
+                    "success": true,
+                    "message": format!("窗口 {} 信号源设置成功", window),
+                    "window": window,
+                    "source": route
+                }))
+            }
+            "listLayouts" | "list_layouts" => {
+                let layouts: Vec<Value> = self
+                    .layouts
+                    .values()
+                    .map(|l| self.layout_to_json(l))
+                    .collect();
+                Ok(json!({
+                    "success": true,
+                    "current_scene": self.current_scene.load(Ordering::SeqCst),
+                    "layouts": layouts
+                }))
+            }
+            "saveLayout" | "save_layout" | "savePreset" | "save_preset" => {
+                let scene_id = params["scene_id"]
                     .as_u64()
-                    .ok_or(DeviceError::Other("缺少 slot 参数".to_string()))?
+                    .or_else(|| params["sceneId"].as_u64())
+                    .or_else(|| params["value"].as_u64())
+                    .ok_or(DeviceError::Other("缺少 scene_id 参数".to_string()))?
                     as u32;
-                let interface = params["interface"]
+                if scene_id < 1 {
+                    return Err(DeviceError::Other("场景编号必须大于等于1".to_string()));
+                }
+
+                let success = self.save_preset(scene_id).await?;
+                if success {
+                    let name = params["name"]
+                        .as_str()
+                        .map(|s| s.to_string())
+                        .or_else(|| self.layouts.get(&scene_id).map(|l| l.name.clone()))
+                        .unwrap_or_else(|| format!("场景{}", scene_id));
+                    let layout = Layout {
+                        scene_id,
+                        name,
+                        windows: self.windows.values().cloned().collect(),
+                    };
+                    self.layouts.insert(scene_id, layout);
+                    self.current_scene.store(scene_id as i32, Ordering::SeqCst);
+                }
+                Ok(json!({
+                    "success": success,
+                    "message": if success {
+                        format!("场景 {} 保存成功", scene_id)
+                    } else {
+                        format!("场景 {} 保存失败", scene_id)
+                    },
+                    "layout": self.layouts.get(&scene_id).map(|l| self.layout_to_json(l))
+                }))
+            }
+            "recallLayout" | "recall_layout" => {
+                let scene_id = match self.find_layout(&params)? {
+                    Some(layout) => layout.scene_id,
+                    None => params["scene_id"]
+                        .as_u64()
+                        .or_else(|| params["sceneId"].as_u64())
+                        .unwrap_or_default() as u32,
+                };
+
+                let success = self.set_preset(scene_id).await?;
+                if success {
+                    self.apply_scene(scene_id);
+                }
+                Ok(json!({
+                    "success": success,
+                    "message": if success {
+                        format!("场景 {} 切换成功", scene_id)
+                    } else {
+                        format!("场景 {} 切换失败", scene_id)
+                    },
+                    "layout": self.layouts.get(&scene_id).map(|l| self.layout_to_json(l))
+                }))
+            }
+            "setWinPos" | "set_win_pos" | "moveWindow" | "move_window" | "resizeWindow"
+            | "resize_window" => {
+                let window = params["window"]
                     .as_u64()
-                    .ok_or(DeviceError::Other("缺少 interface 参数".to_string()))?
+                    .ok_or(DeviceError::Other("缺少 window 参数".to_string()))?
                     as u32;
-                let signal_type = params["type"]
+                let current = self.windows.get(&window).cloned().unwrap_or(WindowState {
+                    window,
+                    ..Default::default()
+                });
+
+                // 未提供的坐标/尺寸沿用上次下发值，便于只移动或只缩放
+                let x = params["x"].as_i64().map(|v| v as i32).or(current.x);
+                let y = params["y"].as_i64().map(|v| v as i32).or(current.y);
+                let width = params["width"]
                     .as_u64()
-                    .or_else(|| params["signal_type"].as_u64())
-                    .ok_or(DeviceError::Other("缺少 type 参数".to_string()))?
-                    as u32;
+                    .or_else(|| params["w"].as_u64())
+                    .map(|v| v as u32)
+                    .or(current.width);
+                let height = params["height"]
+                    .as_u64()
+                    .or_else(|| params["h"].as_u64())
+                    .map(|v| v as u32)
+                    .or(current.height);
 
-                self.set_win_src(window, slot, interface, signal_type)
-                    .await?;
+                let (x, y, width, height) = match (x, y, width, height) {
+                    (Some(x), Some(y), Some(w), Some(h)) => (x, y, w, h),
+                    _ => {
+                        return Err(DeviceError::Other(format!(
+                            "窗口 {} 缺少 x/y/width/height 参数，且无已知位置可沿用",
+                            window
+                        )))
+                    }
+                };
+                if width == 0 || height == 0 {
+                    return Err(DeviceError::Other("窗口宽高必须大于0".to_string()));
+                }
+
+                let success = self.set_win_pos(window, x, y, width, height).await?;
+                if success {
+                    let state = self.windows.entry(window).or_insert(current);
+                    state.x = Some(x);
+                    state.y = Some(y);
+                    state.width = Some(width);
+                    state.height = Some(height);
+                }
+                Ok(json!({
+                    "success": success,
+                    "message": if success {
+                        format!("窗口 {} 位置设置成功", window)
+                    } else {
+                        format!("窗口 {} 位置设置失败", window)
+                    },
+                    "window": self.windows.get(&window)
+                }))
+            }
+            "getWindows" | "get_windows" => {
+                let windows: Vec<&WindowState> = self.windows.values().collect();
                 Ok(json!({
                     "success": true,
-                    "message": format!("窗口 {} 信号源设置成功", window)
+                    "current_scene": self.current_scene.load(Ordering::SeqCst),
+                    "windows": windows
                 }))
             }
+            "listSources" | "list_sources" => Ok(json!({
+                "success": true,
+                "sources": self.sources
+            })),
             _ => Err(DeviceError::Other(format!("未知命令: {}", command))),
         }
     }
@@ -594,6 +944,124 @@ impl Protocol for Splicer3dProtocol {
             "loadScene".to_string(),
             "setWinSrc".to_string(),
             "set_win_src".to_string(),
+            "listLayouts".to_string(),
+            "saveLayout".to_string(),
+            "recallLayout".to_string(),
+            "setWinPos".to_string(),
+            "moveWindow".to_string(),
+            "resizeWindow".to_string(),
+            "getWindows".to_string(),
+            "listSources".to_string(),
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_peer::MockPeer;
+
+    /// 模拟拼接处理器：记录收到的命令，全部应答 /ack:d,1;
+    async fn mock_device() -> MockPeer {
+        MockPeer::start(|_| vec![b"/ack:d,1;".to_vec()]).await
+    }
+
+    fn protocol(port: u16) -> Splicer3dProtocol {
+        let mut protocol = Splicer3dProtocol::new_tcp("127.0.0.1".into(), port, 2);
+        protocol.sources = serde_json::from_value(json!({
+            "camera": { "slot": 3, "interface": 1, "type": 4 }
+        }))
+        .unwrap();
+        protocol
+    }
+
+    #[test]
+    fn test_commands_encode_group_last() {
+        let protocol = protocol(DEFAULT_TCP_PORT);
+        assert_eq!(protocol.build_set_preset_command(5), "/SetPreset:d,5,2;");
+        assert_eq!(protocol.build_save_preset_command(5), "/SavePreset:d,5,2;");
+        assert_eq!(
+            protocol.build_set_win_pos_command(1, -10, 20, 1920, 1080),
+            "/setWinPos:d,1,-10,20,1920,1080,2;"
+        );
+        assert_eq!(
+            protocol.build_set_win_src_command(1, 3, 1, 4),
+            "/setWinSrc:d,1,3,1,4,2;"
+        );
+
+        assert!(protocol.parse_ack_response("/ack:d,1;"));
+        assert!(!protocol.parse_ack_response("/ack:d,0;"));
+        assert!(protocol.parse_ack_response(""));
+    }
+
+    #[test]
+    fn test_named_sources_resolve_and_unknown_names_fail() {
+        let protocol = protocol(DEFAULT_TCP_PORT);
+
+        let route = protocol
+            .resolve_source(&json!({ "source": "camera" }))
+            .unwrap();
+        assert_eq!((route.slot, route.interface, route.signal_type), (3, 1, 4));
+        let route = protocol
+            .resolve_source(&json!({ "slot": 1, "interface": 2, "signal_type": 3 }))
+            .unwrap();
+        assert_eq!((route.slot, route.interface, route.signal_type), (1, 2, 3));
+
+        // 命名信号源优先，未定义时报错而不是回退到 slot/interface
+        assert!(protocol
+            .resolve_source(&json!({ "source": "missing", "slot": 1, "interface": 1, "type": 1 }))
+            .is_err());
+        assert!(protocol.resolve_source(&json!({ "slot": 1 })).is_err());
+        assert!(protocol.find_layout(&json!({ "name": "missing" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_saved_layout_restores_windows_on_recall() {
+        let device = mock_device().await;
+        let mut protocol = protocol(device.port());
+
+        protocol
+            .execute(
+                "setWinPos",
+                json!({ "window": 1, "x": 0, "y": 0, "width": 1920, "height": 1080 }),
+            )
+            .await
+            .unwrap();
+        protocol
+            .execute("setWinSrc", json!({ "window": 1, "source": "camera" }))
+            .await
+            .unwrap();
+        let saved = protocol
+            .execute("saveLayout", json!({ "scene_id": 3, "name": "会议" }))
+            .await
+            .unwrap();
+        assert_eq!(saved["success"], true);
+        assert_eq!(saved["layout"]["windows"][0]["source"]["slot"], 3);
+
+        // 只移动窗口时沿用上次下发的尺寸
+        protocol
+            .execute("moveWindow", json!({ "window": 1, "x": 100, "y": 50 }))
+            .await
+            .unwrap();
+
+        let recalled = protocol
+            .execute("recallLayout", json!({ "name": "会议" }))
+            .await
+            .unwrap();
+        assert_eq!(recalled["layout"]["active"], true);
+        let windows = protocol.execute("getWindows", json!({})).await.unwrap();
+        assert_eq!(windows["current_scene"], 3);
+        assert_eq!(windows["windows"][0]["x"], 0);
+
+        assert_eq!(
+            device.text_requests(),
+            vec![
+                "/setWinPos:d,1,0,0,1920,1080,2;",
+                "/setWinSrc:d,1,3,1,4,2;",
+                "/SavePreset:d,3,2;",
+                "/setWinPos:d,1,100,50,1920,1080,2;",
+                "/SetPreset:d,3,2;",
+            ]
+        );
+    }
+}
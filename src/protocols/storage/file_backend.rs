@@ -0,0 +1,235 @@
+//! 文件存储后端：每个命名空间一个 JSON 文件，带内存缓存
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, error, info, warn};
+
+use super::{expires_at, is_expired, KvStorage};
+
+/// 文件中记录过期时间的保留键（key -> Unix 毫秒），无过期条目时不写入
+const EXPIRES_KEY: &str = "__expires_at";
+
+/// 单个命名空间的数据
+#[derive(Default, Clone)]
+struct Namespace {
+    values: HashMap<String, Value>,
+    expires: HashMap<String, i64>,
+}
+
+impl Namespace {
+    fn is_live(&self, key: &str) -> bool {
+        self.expires.get(key).is_none_or(|&at| !is_expired(at))
+    }
+
+    /// 清除已过期的条目
+    fn purge_expired(&mut self) {
+        let expired: Vec<String> = self
+            .expires
+            .iter()
+            .filter(|(_, &at)| is_expired(at))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.values.remove(&key);
+            self.expires.remove(&key);
+        }
+    }
+}
+
+/// 文件存储后端
+pub struct FileStorage {
+    /// 存储目录
+    storage_dir: PathBuf,
+    /// 内存缓存 (namespace -> 数据)，首次访问时从文件加载
+    cache: RwLock<HashMap<String, Namespace>>,
+}
+
+impl FileStorage {
+    pub fn new(storage_dir: PathBuf) -> Self {
+        // 确保存储目录存在
+        if !storage_dir.exists() {
+            if let Err(e) = std::fs::create_dir_all(&storage_dir) {
+                error!("创建存储目录失败: {:?}, 错误: {}", storage_dir, e);
+            }
+        }
+
+        info!("协议存储初始化, 目录: {:?}", storage_dir);
+
+        Self {
+            storage_dir,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 获取命名空间的存储文件路径
+    fn namespace_file(&self, namespace: &str) -> PathBuf {
+        self.storage_dir.join(format!("{}.json", namespace))
+    }
+
+    /// 从文件加载命名空间数据
+    async fn load(&self, namespace: &str) -> Namespace {
+        let file_path = self.namespace_file(namespace);
+
+        if !file_path.exists() {
+            debug!("{} 存储文件不存在，返回空数据", namespace);
+            return Namespace::default();
+        }
+
+        let mut values: HashMap<String, Value> = match tokio::fs::read_to_string(&file_path).await {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(data) => {
+                    debug!("{} 存储数据已加载", namespace);
+                    data
+                }
+                Err(e) => {
+                    warn!("{} 存储数据解析失败: {}", namespace, e);
+                    HashMap::new()
+                }
+            },
+            Err(e) => {
+                warn!("{} 存储文件读取失败: {}", namespace, e);
+                HashMap::new()
+            }
+        };
+        let expires = values
+            .remove(EXPIRES_KEY)
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+
+        Namespace { values, expires }
+    }
+
+    /// 保存命名空间数据到文件
+    async fn save(&self, namespace: &str, data: Namespace) {
+        let file_path = self.namespace_file(namespace);
+
+        let mut values = data.values;
+        if !data.expires.is_empty() {
+            values.insert(EXPIRES_KEY.to_string(), serde_json::json!(data.expires));
+        }
+        let content = match serde_json::to_string_pretty(&values) {
+            Ok(c) => c,
+            Err(e) => {
+                error!("{} 存储数据序列化失败: {}", namespace, e);
+                return;
+            }
+        };
+
+        if let Err(e) = tokio::fs::write(&file_path, content).await {
+            error!("{} 存储文件写入失败: {}", namespace, e);
+        } else {
+            debug!("{} 存储数据已保存", namespace);
+        }
+    }
+
+    /// 确保命名空间已加载到缓存
+    async fn ensure_loaded(&self, namespace: &str) {
+        if self.cache.read().await.contains_key(namespace) {
+            return;
+        }
+        let data = self.load(namespace).await;
+        self.cache
+            .write()
+            .await
+            .entry(namespace.to_string())
+            .or_insert(data);
+    }
+
+    /// 修改命名空间数据并写回文件
+    async fn update<R>(&self, namespace: &str, f: impl FnOnce(&mut Namespace) -> R) -> R {
+        self.ensure_loaded(namespace).await;
+        let mut cache = self.cache.write().await;
+        let data = cache.entry(namespace.to_string()).or_default();
+        data.purge_expired();
+        let result = f(data);
+        let snapshot = data.clone();
+        drop(cache);
+        self.save(namespace, snapshot).await;
+        result
+    }
+}
+
+#[async_trait]
+impl KvStorage for FileStorage {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn get(&self, namespace: &str, key: &str) -> Option<Value> {
+        self.ensure_loaded(namespace).await;
+        let cache = self.cache.read().await;
+        let data = cache.get(namespace)?;
+        data.is_live(key)
+            .then(|| data.values.get(key).cloned())
+            .flatten()
+    }
+
+    async fn set(&self, namespace: &str, key: &str, value: Value, ttl: Option<Duration>) {
+        debug!("{} 存储设置: {} = {}", namespace, key, value);
+        self.update(namespace, |data| {
+            data.values.insert(key.to_string(), value);
+            match ttl {
+                Some(ttl) => data.expires.insert(key.to_string(), expires_at(ttl)),
+                None => data.expires.remove(key),
+            };
+        })
+        .await;
+    }
+
+    async fn set_many(&self, namespace: &str, values: HashMap<String, Value>) {
+        self.update(namespace, |data| {
+            for (key, value) in values {
+                data.expires.remove(&key);
+                data.values.insert(key, value);
+            }
+        })
+        .await;
+        debug!("{} 批量存储完成", namespace);
+    }
+
+    async fn remove(&self, namespace: &str, key: &str) -> Option<Value> {
+        let removed = self
+            .update(namespace, |data| {
+                data.expires.remove(key);
+                data.values.remove(key)
+            })
+            .await;
+        if removed.is_some() {
+            debug!("{} 存储删除: {}", namespace, key);
+        }
+        removed
+    }
+
+    async fn get_all(&self, namespace: &str) -> HashMap<String, Value> {
+        self.ensure_loaded(namespace).await;
+        let cache = self.cache.read().await;
+        cache
+            .get(namespace)
+            .map(|data| {
+                data.values
+                    .iter()
+                    .filter(|(key, _)| data.is_live(key))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    async fn clear(&self, namespace: &str) {
+        self.cache
+            .write()
+            .await
+            .insert(namespace.to_string(), Namespace::default());
+
+        let file_path = self.namespace_file(namespace);
+        if file_path.exists() {
+            if let Err(e) = tokio::fs::remove_file(&file_path).await {
+                warn!("删除 {} 存储文件失败: {}", namespace, e);
+            }
+        }
+    }
+}
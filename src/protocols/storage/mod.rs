@@ -0,0 +1,319 @@
+//! 协议持久化存储模块
+//! 提供统一的键值存储接口，后端可选文件、嵌入式 sled 或 Redis（多实例共享）
+
+mod file_backend;
+#[cfg(feature = "redis-storage")]
+mod redis_backend;
+mod sled_backend;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::{StorageBackend, StorageConfig};
+use crate::utils::{DeviceError, Result};
+
+pub use file_backend::FileStorage;
+#[cfg(feature = "redis-storage")]
+pub use redis_backend::RedisStorage;
+pub use sled_backend::SledStorage;
+
+/// 键值存储后端
+///
+/// 数据按命名空间隔离（协议存储中每个通道一个命名空间）。
+/// 存储失败只记录日志，不影响协议主流程。
+#[async_trait]
+pub trait KvStorage: Send + Sync {
+    /// 后端名称（日志用）
+    fn name(&self) -> &'static str;
+
+    /// 获取值（已过期视为不存在）
+    async fn get(&self, namespace: &str, key: &str) -> Option<Value>;
+
+    /// 设置值，`ttl` 为空表示永不过期
+    async fn set(&self, namespace: &str, key: &str, value: Value, ttl: Option<Duration>);
+
+    /// 批量设置值（永不过期）
+    async fn set_many(&self, namespace: &str, values: HashMap<String, Value>);
+
+    /// 删除值，返回删除前的值
+    async fn remove(&self, namespace: &str, key: &str) -> Option<Value>;
+
+    /// 获取命名空间内所有未过期的值
+    async fn get_all(&self, namespace: &str) -> HashMap<String, Value>;
+
+    /// 清空命名空间
+    async fn clear(&self, namespace: &str);
+}
+
+/// 带过期时间的存储条目（sled 等二进制后端使用）
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredEntry {
+    value: Value,
+    /// 过期时间（Unix 毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<i64>,
+}
+
+impl StoredEntry {
+    fn new(value: Value, ttl: Option<Duration>) -> Self {
+        Self {
+            value,
+            expires_at: ttl.map(expires_at),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(is_expired)
+    }
+}
+
+/// 当前时间加 ttl 的 Unix 毫秒时间戳
+fn expires_at(ttl: Duration) -> i64 {
+    chrono::Utc::now().timestamp_millis() + ttl.as_millis() as i64
+}
+
+fn is_expired(expires_at: i64) -> bool {
+    expires_at <= chrono::Utc::now().timestamp_millis()
+}
+
+/// 协议存储管理器
+/// 每个通道/协议有独立的存储空间
+pub struct ProtocolStorage {
+    backend: Arc<dyn KvStorage>,
+}
+
+impl ProtocolStorage {
+    /// 创建基于文件的存储管理器
+    pub fn new(storage_dir: PathBuf) -> Self {
+        Self::with_backend(Arc::new(FileStorage::new(storage_dir)))
+    }
+
+    /// 使用指定后端创建存储管理器
+    pub fn with_backend(backend: Arc<dyn KvStorage>) -> Self {
+        Self { backend }
+    }
+
+    /// 按配置创建存储管理器
+    pub async fn from_config(config: &StorageConfig) -> Result<Self> {
+        let path = |default: &str| {
+            config
+                .path
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| default_data_dir().join(default))
+        };
+
+        let backend: Arc<dyn KvStorage> = match config.backend {
+            StorageBackend::File => Arc::new(FileStorage::new(path("protocol_storage"))),
+            StorageBackend::Sled => Arc::new(SledStorage::open(path("protocol_storage.sled"))?),
+            #[cfg(feature = "redis-storage")]
+            StorageBackend::Redis => {
+                let url = config.redis_url.as_deref().ok_or_else(|| {
+                    DeviceError::ConfigError("storage.redis_url 未配置".to_string())
+                })?;
+                Arc::new(RedisStorage::connect(url, &config.key_prefix).await?)
+            }
+            #[cfg(not(feature = "redis-storage"))]
+            StorageBackend::Redis => {
+                return Err(DeviceError::ConfigError(
+                    "Redis 存储后端需要启用 redis-storage 编译特性".to_string(),
+                ))
+            }
+        };
+
+        info!("协议存储后端: {}", backend.name());
+        Ok(Self::with_backend(backend))
+    }
+
+    /// 通道对应的命名空间
+    fn namespace(channel_id: u32) -> String {
+        format!("channel_{}", channel_id)
+    }
+
+    /// 加载通道的存储数据
+    pub async fn load_channel(&self, channel_id: u32) -> HashMap<String, Value> {
+        self.get_all(channel_id).await
+    }
+
+    /// 初始化通道存储（预加载已有数据）
+    pub async fn init_channel(&self, channel_id: u32) {
+        self.load_channel(channel_id).await;
+    }
+
+    /// 设置值
+    pub async fn set(&self, channel_id: u32, key: &str, value: Value) {
+        self.backend
+            .set(&Self::namespace(channel_id), key, value, None)
+            .await;
+    }
+
+    /// 设置带有效期的值，过期后读取返回 None
+    pub async fn set_with_ttl(&self, channel_id: u32, key: &str, value: Value, ttl: Duration) {
+        self.backend
+            .set(&Self::namespace(channel_id), key, value, Some(ttl))
+            .await;
+    }
+
+    /// 获取值
+    pub async fn get(&self, channel_id: u32, key: &str) -> Option<Value> {
+        self.backend.get(&Self::namespace(channel_id), key).await
+    }
+
+    /// 获取字符串值
+    pub async fn get_string(&self, channel_id: u32, key: &str) -> Option<String> {
+        self.get(channel_id, key)
+            .await
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+    }
+
+    /// 获取整数值
+    pub async fn get_i64(&self, channel_id: u32, key: &str) -> Option<i64> {
+        self.get(channel_id, key).await.and_then(|v| v.as_i64())
+    }
+
+    /// 获取布尔值
+    pub async fn get_bool(&self, channel_id: u32, key: &str) -> Option<bool> {
+        self.get(channel_id, key).await.and_then(|v| v.as_bool())
+    }
+
+    /// 删除值
+    pub async fn remove(&self, channel_id: u32, key: &str) -> Option<Value> {
+        self.backend.remove(&Self::namespace(channel_id), key).await
+    }
+
+    /// 清空通道的所有存储
+    pub async fn clear_channel(&self, channel_id: u32) {
+        self.backend.clear(&Self::namespace(channel_id)).await;
+        info!("通道 {} 存储已清空", channel_id);
+    }
+
+    /// 获取通道的所有键
+    pub async fn keys(&self, channel_id: u32) -> Vec<String> {
+        self.get_all(channel_id).await.into_keys().collect()
+    }
+
+    /// 获取通道的所有数据
+    pub async fn get_all(&self, channel_id: u32) -> HashMap<String, Value> {
+        self.backend.get_all(&Self::namespace(channel_id)).await
+    }
+
+    /// 批量设置值
+    pub async fn set_many(&self, channel_id: u32, values: HashMap<String, Value>) {
+        self.backend
+            .set_many(&Self::namespace(channel_id), values)
+            .await;
+    }
+}
+
+/// 全局存储实例
+static GLOBAL_STORAGE: tokio::sync::OnceCell<Arc<ProtocolStorage>> =
+    tokio::sync::OnceCell::const_new();
+
+/// 默认数据目录（工作目录下的 data）
+fn default_data_dir() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("data")
+}
+
+/// 初始化全局存储
+pub async fn init_global_storage(storage_dir: PathBuf) {
+    let _ = GLOBAL_STORAGE
+        .set(Arc::new(ProtocolStorage::new(storage_dir)))
+        .map_err(|_| warn!("全局存储已初始化"));
+}
+
+/// 按配置初始化全局存储（启动时调用，需在协议使用存储之前）
+pub async fn init_global_storage_from_config(config: &StorageConfig) -> Result<()> {
+    let storage = ProtocolStorage::from_config(config).await?;
+    let _ = GLOBAL_STORAGE
+        .set(Arc::new(storage))
+        .map_err(|_| warn!("全局存储已初始化"));
+    Ok(())
+}
+
+/// 获取全局存储实例
+pub fn get_storage() -> Option<Arc<ProtocolStorage>> {
+    GLOBAL_STORAGE.get().cloned()
+}
+
+/// 获取存储或使用默认路径初始化
+pub async fn get_or_init_storage() -> Arc<ProtocolStorage> {
+    GLOBAL_STORAGE
+        .get_or_init(|| async {
+            Arc::new(ProtocolStorage::new(
+                default_data_dir().join("protocol_storage"),
+            ))
+        })
+        .await
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_storage_basic() {
+        let dir = tempdir().unwrap();
+        let storage = ProtocolStorage::new(dir.path().to_path_buf());
+
+        // 设置值
+        storage
+            .set(1, "test_key", serde_json::json!("test_value"))
+            .await;
+        storage.set(1, "count", serde_json::json!(42)).await;
+
+        // 读取值
+        assert_eq!(
+            storage.get_string(1, "test_key").await,
+            Some("test_value".to_string())
+        );
+        assert_eq!(storage.get_i64(1, "count").await, Some(42));
+
+        // 删除值
+        storage.remove(1, "test_key").await;
+        assert_eq!(storage.get_string(1, "test_key").await, None);
+    }
+
+    #[tokio::test]
+    async fn ttl_and_reload_across_backends() {
+        type Open = fn(PathBuf) -> ProtocolStorage;
+        let dir = tempdir().unwrap();
+        let backends: Vec<(PathBuf, Open)> = vec![
+            (dir.path().join("file"), ProtocolStorage::new),
+            (dir.path().join("sled"), |path| {
+                ProtocolStorage::with_backend(Arc::new(SledStorage::open(path).unwrap()))
+            }),
+        ];
+
+        for (path, open) in backends {
+            let storage = open(path.clone());
+            storage.set(1, "token", serde_json::json!("abc")).await;
+            storage
+                .set_with_ttl(1, "session", serde_json::json!(1), Duration::ZERO)
+                .await;
+            storage.set(2, "token", serde_json::json!("other")).await;
+            assert_eq!(storage.get(1, "session").await, None);
+            assert_eq!(storage.keys(1).await, vec!["token".to_string()]);
+            drop(storage);
+
+            // 重新打开后数据仍在，且通道之间互不影响
+            let storage = open(path);
+            assert_eq!(storage.get_string(1, "token").await.as_deref(), Some("abc"));
+            storage.clear_channel(1).await;
+            assert!(storage.get_all(1).await.is_empty());
+            assert_eq!(
+                storage.get_string(2, "token").await.as_deref(),
+                Some("other")
+            );
+        }
+    }
+}
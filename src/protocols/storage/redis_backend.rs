@@ -0,0 +1,176 @@
+//! Redis 存储后端：多实例共享，键格式为 `<前缀><命名空间>:<键>`
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use super::KvStorage;
+use crate::utils::{DeviceError, Result};
+
+/// Redis 存储后端
+pub struct RedisStorage {
+    conn: ConnectionManager,
+    key_prefix: String,
+}
+
+impl RedisStorage {
+    /// 连接 Redis（断线后自动重连）
+    pub async fn connect(url: &str, key_prefix: &str) -> Result<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| DeviceError::ConfigError(format!("Redis 地址无效: {}", e)))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .map_err(|e| DeviceError::ConnectionError(format!("连接 Redis 失败: {}", e)))?;
+        info!("协议存储初始化, Redis: {} (前缀 '{}')", url, key_prefix);
+        Ok(Self {
+            conn,
+            key_prefix: key_prefix.to_string(),
+        })
+    }
+
+    fn namespace_prefix(&self, namespace: &str) -> String {
+        format!("{}{}:", self.key_prefix, namespace)
+    }
+
+    fn full_key(&self, namespace: &str, key: &str) -> String {
+        format!("{}{}", self.namespace_prefix(namespace), key)
+    }
+
+    fn decode(key: &str, raw: Option<String>) -> Option<Value> {
+        let raw = raw?;
+        serde_json::from_str(&raw)
+            .map_err(|e| warn!("Redis 存储数据解析失败: {}: {}", key, e))
+            .ok()
+    }
+
+    /// 列出命名空间下的全部 Redis 键
+    async fn scan_namespace(&self, namespace: &str) -> Vec<String> {
+        // 命名空间由程序生成（channel_<id>），不含通配符
+        let pattern = format!("{}*", self.namespace_prefix(namespace));
+        let mut conn = self.conn.clone();
+        let keys = match conn.scan_match::<_, String>(&pattern).await {
+            Ok(mut iter) => {
+                let mut keys = Vec::new();
+                while let Some(key) = iter.next_item().await {
+                    keys.push(key);
+                }
+                keys
+            }
+            Err(e) => {
+                error!("Redis 扫描 {} 失败: {}", pattern, e);
+                Vec::new()
+            }
+        };
+        keys
+    }
+}
+
+#[async_trait]
+impl KvStorage for RedisStorage {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get(&self, namespace: &str, key: &str) -> Option<Value> {
+        let full_key = self.full_key(namespace, key);
+        let raw: Option<String> = self
+            .conn
+            .clone()
+            .get(&full_key)
+            .await
+            .map_err(|e| error!("Redis 读取 {} 失败: {}", full_key, e))
+            .ok()?;
+        Self::decode(&full_key, raw)
+    }
+
+    async fn set(&self, namespace: &str, key: &str, value: Value, ttl: Option<Duration>) {
+        let full_key = self.full_key(namespace, key);
+        debug!("Redis 存储设置: {} = {}", full_key, value);
+        let raw = value.to_string();
+        let mut conn = self.conn.clone();
+        let result: redis::RedisResult<()> = match ttl {
+            // PX 至少为 1 毫秒
+            Some(ttl) => {
+                conn.pset_ex(&full_key, raw, (ttl.as_millis() as u64).max(1))
+                    .await
+            }
+            None => conn.set(&full_key, raw).await,
+        };
+        if let Err(e) = result {
+            error!("Redis 写入 {} 失败: {}", full_key, e);
+        }
+    }
+
+    async fn set_many(&self, namespace: &str, values: HashMap<String, Value>) {
+        if values.is_empty() {
+            return;
+        }
+        let mut pipe = redis::pipe();
+        for (key, value) in &values {
+            pipe.set(self.full_key(namespace, key), value.to_string())
+                .ignore();
+        }
+        let result: redis::RedisResult<()> = pipe.query_async(&mut self.conn.clone()).await;
+        match result {
+            Ok(()) => debug!("{} 批量存储完成", namespace),
+            Err(e) => error!("Redis 批量写入 {} 失败: {}", namespace, e),
+        }
+    }
+
+    async fn remove(&self, namespace: &str, key: &str) -> Option<Value> {
+        let full_key = self.full_key(namespace, key);
+        let (raw,): (Option<String>,) = redis::pipe()
+            .atomic()
+            .get(&full_key)
+            .del(&full_key)
+            .ignore()
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| error!("Redis 删除 {} 失败: {}", full_key, e))
+            .ok()?;
+        Self::decode(&full_key, raw)
+    }
+
+    async fn get_all(&self, namespace: &str) -> HashMap<String, Value> {
+        let keys = self.scan_namespace(namespace).await;
+        if keys.is_empty() {
+            return HashMap::new();
+        }
+        // 显式使用 MGET：单个键时 mget() 会退化为 GET，返回值无法解析为数组
+        let raws: Vec<Option<String>> = match redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut self.conn.clone())
+            .await
+        {
+            Ok(raws) => raws,
+            Err(e) => {
+                error!("Redis 批量读取 {} 失败: {}", namespace, e);
+                return HashMap::new();
+            }
+        };
+        let prefix_len = self.namespace_prefix(namespace).len();
+        keys.into_iter()
+            .zip(raws)
+            .filter_map(|(full_key, raw)| {
+                // 扫描与读取之间过期的键 MGET 返回空
+                let value = Self::decode(&full_key, raw)?;
+                Some((full_key[prefix_len..].to_string(), value))
+            })
+            .collect()
+    }
+
+    async fn clear(&self, namespace: &str) {
+        let keys = self.scan_namespace(namespace).await;
+        if keys.is_empty() {
+            return;
+        }
+        let result: redis::RedisResult<()> = self.conn.clone().del(&keys).await;
+        if let Err(e) = result {
+            warn!("删除 {} 存储失败: {}", namespace, e);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! sled 嵌入式数据库存储后端：每个命名空间一棵树
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use super::{KvStorage, StoredEntry};
+use crate::utils::{DeviceError, Result};
+
+/// 数据库文件锁被占用时的重试次数与间隔
+const OPEN_RETRIES: u32 = 20;
+const OPEN_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// sled 存储后端
+pub struct SledStorage {
+    db: sled::Db,
+}
+
+impl SledStorage {
+    /// 打开（不存在时创建）数据库
+    ///
+    /// 旧实例释放后 sled 的后台刷盘线程稍后才释放文件锁（如配置热重载时重新打开同一目录），
+    /// 锁被占用时短暂重试。
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let mut attempts = 0;
+        let db = loop {
+            match sled::open(&path) {
+                Ok(db) => break db,
+                // sled 把文件锁冲突包装为 ErrorKind::Other，只能按消息识别
+                Err(sled::Error::Io(e))
+                    if e.to_string().contains("could not acquire lock")
+                        && attempts < OPEN_RETRIES =>
+                {
+                    attempts += 1;
+                    std::thread::sleep(OPEN_RETRY_DELAY);
+                }
+                Err(e) => {
+                    return Err(DeviceError::ConfigError(format!(
+                        "打开 sled 数据库 {:?} 失败: {}",
+                        path, e
+                    )))
+                }
+            }
+        };
+        info!("协议存储初始化, sled 数据库: {:?}", path);
+        Ok(Self { db })
+    }
+
+    fn tree(&self, namespace: &str) -> Option<sled::Tree> {
+        self.db
+            .open_tree(namespace)
+            .map_err(|e| error!("{} 打开 sled 树失败: {}", namespace, e))
+            .ok()
+    }
+
+    fn decode(namespace: &str, key: &[u8], bytes: &[u8]) -> Option<StoredEntry> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| {
+                warn!(
+                    "{} 存储数据解析失败: {}: {}",
+                    namespace,
+                    String::from_utf8_lossy(key),
+                    e
+                )
+            })
+            .ok()
+    }
+
+    fn encode(entry: &StoredEntry) -> Vec<u8> {
+        serde_json::to_vec(entry).unwrap_or_default()
+    }
+
+    async fn flush(namespace: &str, tree: &sled::Tree) {
+        if let Err(e) = tree.flush_async().await {
+            error!("{} sled 数据写入失败: {}", namespace, e);
+        }
+    }
+}
+
+#[async_trait]
+impl KvStorage for SledStorage {
+    fn name(&self) -> &'static str {
+        "sled"
+    }
+
+    async fn get(&self, namespace: &str, key: &str) -> Option<Value> {
+        let tree = self.tree(namespace)?;
+        let bytes = tree.get(key).ok()??;
+        let entry = Self::decode(namespace, key.as_bytes(), &bytes)?;
+        if entry.is_expired() {
+            let _ = tree.remove(key);
+            return None;
+        }
+        Some(entry.value)
+    }
+
+    async fn set(&self, namespace: &str, key: &str, value: Value, ttl: Option<Duration>) {
+        let Some(tree) = self.tree(namespace) else {
+            return;
+        };
+        debug!("{} 存储设置: {} = {}", namespace, key, value);
+        let entry = StoredEntry::new(value, ttl);
+        if let Err(e) = tree.insert(key, Self::encode(&entry)) {
+            error!("{} 存储写入失败: {}", namespace, e);
+            return;
+        }
+        Self::flush(namespace, &tree).await;
+    }
+
+    async fn set_many(&self, namespace: &str, values: HashMap<String, Value>) {
+        let Some(tree) = self.tree(namespace) else {
+            return;
+        };
+        let mut batch = sled::Batch::default();
+        for (key, value) in values {
+            batch.insert(key.as_bytes(), Self::encode(&StoredEntry::new(value, None)));
+        }
+        if let Err(e) = tree.apply_batch(batch) {
+            error!("{} 批量存储失败: {}", namespace, e);
+            return;
+        }
+        Self::flush(namespace, &tree).await;
+        debug!("{} 批量存储完成", namespace);
+    }
+
+    async fn remove(&self, namespace: &str, key: &str) -> Option<Value> {
+        let tree = self.tree(namespace)?;
+        let bytes = tree.remove(key).ok()??;
+        Self::flush(namespace, &tree).await;
+        debug!("{} 存储删除: {}", namespace, key);
+        Self::decode(namespace, key.as_bytes(), &bytes)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value)
+    }
+
+    async fn get_all(&self, namespace: &str) -> HashMap<String, Value> {
+        let Some(tree) = self.tree(namespace) else {
+            return HashMap::new();
+        };
+        tree.iter()
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, bytes)| {
+                let entry = Self::decode(namespace, &key, &bytes)?;
+                (!entry.is_expired())
+                    .then(|| (String::from_utf8_lossy(&key).into_owned(), entry.value))
+            })
+            .collect()
+    }
+
+    async fn clear(&self, namespace: &str) {
+        if let Err(e) = self.db.drop_tree(namespace) {
+            warn!("删除 {} 存储失败: {}", namespace, e);
+        }
+    }
+}
@@ -12,15 +12,25 @@
 // - 单独控制开关: 功能码 06, 寄存器地址 0x0034
 //   值的高字节=开关编号(1-8), 低字节=动作(01=关闭, 02=开启)
 //   例: 第1个开关关闭 -> 值=0x0101, 第1个开关开启 -> 值=0x0102
+//
+// - 分路计量 (可选, 寄存器地址由 metering 配置给出):
+//   电压/电流: 每路 1 个寄存器, 从基地址开始连续排列
+//   电能: 每路 2 个寄存器 (32位, 高字在前), 从基地址开始连续排列
 
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
 use tokio_modbus::prelude::*;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::protocols::Protocol;
-use crate::utils::{DeviceError, Result};
+use crate::device::DeviceEvent;
+use crate::protocols::{EventSink, PollGate, Protocol};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::{dns, net, time, DeviceError, Result};
 
 // 寄存器地址常量
 /// 8位开关状态/批量控制寄存器
@@ -32,11 +42,115 @@ const ADDR_SWITCH_SINGLE: u16 = 0x0034;
 const ACTION_OFF: u8 = 0x01;
 const ACTION_ON: u8 = 0x02;
 
+/// 计量数据点节点 ID 偏移：101-108 电流, 201-208 电压, 301-308 电能（原始寄存器值）
+const NODE_CURRENT_BASE: u32 = 100;
+const NODE_VOLTAGE_BASE: u32 = 200;
+const NODE_ENERGY_BASE: u32 = 300;
+
+/// 开关路数
+const OUTLET_COUNT: u8 = 8;
+
+fn default_voltage_scale() -> f64 {
+    0.1
+}
+
+fn default_current_scale() -> f64 {
+    0.01
+}
+
+fn default_energy_scale() -> f64 {
+    0.01
+}
+
+fn default_metering_interval_ms() -> u64 {
+    5000
+}
+
+/// 分路计量配置
+#[derive(Debug, Clone, Deserialize)]
+struct MeteringConfig {
+    /// 电压寄存器基地址（每路 1 个寄存器）
+    #[serde(default)]
+    voltage_addr: Option<u16>,
+    /// 电流寄存器基地址（每路 1 个寄存器）
+    #[serde(default)]
+    current_addr: Option<u16>,
+    /// 电能寄存器基地址（每路 2 个寄存器，高字在前）
+    #[serde(default)]
+    energy_addr: Option<u16>,
+    /// 电压缩放系数（原始值 × 系数 = V）
+    #[serde(default = "default_voltage_scale")]
+    voltage_scale: f64,
+    /// 电流缩放系数（原始值 × 系数 = A）
+    #[serde(default = "default_current_scale")]
+    current_scale: f64,
+    /// 电能缩放系数（原始值 × 系数 = kWh）
+    #[serde(default = "default_energy_scale")]
+    energy_scale: f64,
+    /// 后台计量轮询间隔（毫秒），0 表示禁用
+    #[serde(default = "default_metering_interval_ms")]
+    interval_ms: u64,
+}
+
+/// 电流告警阈值配置（单位 A）
+#[derive(Debug, Clone, Default, Deserialize)]
+struct AlarmConfig {
+    /// 所有开关通用的电流阈值
+    #[serde(default)]
+    current_max: Option<f64>,
+    /// 单路电流阈值，覆盖通用阈值 ("1" -> 5.0)
+    #[serde(default)]
+    outlets: HashMap<String, f64>,
+    /// 分组总电流阈值 ("rackA" -> 16.0)
+    #[serde(default)]
+    groups: HashMap<String, f64>,
+}
+
+impl AlarmConfig {
+    fn outlet_threshold(&self, outlet: u8) -> Option<f64> {
+        self.outlets
+            .get(&outlet.to_string())
+            .copied()
+            .or(self.current_max)
+    }
+}
+
+/// 单路计量读数
+#[derive(Debug, Clone, Default)]
+struct OutletReading {
+    voltage_raw: Option<u16>,
+    current_raw: Option<u16>,
+    energy_raw: Option<u32>,
+}
+
+/// 计量快照
+#[derive(Debug, Clone)]
+struct MeteringSnapshot {
+    /// 下标 0 对应开关 1
+    outlets: Vec<OutletReading>,
+    updated_at: Instant,
+}
+
 /// 特普瑞斯 PDU 协议
 pub struct TprisPduProtocol {
     addr: String,
     port: u16,
     slave_id: u8,
+    /// 分路计量配置（未配置时不支持计量）
+    metering: Option<MeteringConfig>,
+    /// 开关分组 (group -> 开关编号列表)
+    groups: HashMap<String, Vec<u8>>,
+    /// 电流告警阈值
+    alarms: AlarmConfig,
+    /// 最近一次计量快照
+    last_metering: Arc<RwLock<Option<MeteringSnapshot>>>,
+    /// 当前处于告警状态的对象 ("outlet:1" / "group:rackA")
+    active_alarms: Arc<RwLock<HashSet<String>>>,
+    events: EventSink,
+    /// 计量轮询暂停开关
+    poll_gate: PollGate,
+    /// 后台任务
+    tasks: TaskRegistry,
 }
 
 impl TprisPduProtocol {
@@ -45,25 +159,300 @@ impl TprisPduProtocol {
             addr,
             port,
             slave_id,
+            metering: None,
+            groups: HashMap::new(),
+            alarms: AlarmConfig::default(),
+            last_metering: Arc::new(RwLock::new(None)),
+            active_alarms: Arc::new(RwLock::new(HashSet::new())),
+            events: EventSink::default(),
+            poll_gate: PollGate::new(),
+            tasks: TaskRegistry::new(),
         }
     }
 
     /// 创建 Modbus TCP 连接
     async fn connect(&self) -> Result<client::Context> {
-        let socket_addr = format!("{}:{}", self.addr, self.port);
-        debug!("连接到 Tpris PDU: {}", socket_addr);
+        Self::connect_to(&self.addr, self.port, self.slave_id).await
+    }
+
+    /// 创建 Modbus TCP 连接（供后台任务使用）
+    async fn connect_to(addr: &str, port: u16, slave_id: u8) -> Result<client::Context> {
+        debug!("连接到 Tpris PDU: {}:{}", addr, port);
 
-        let socket_addr = socket_addr
-            .parse()
-            .map_err(|e| DeviceError::ConfigError(format!("无效的地址: {}", e)))?;
+        let socket_addr = dns::resolve(addr, port).await?;
 
-        let ctx = tcp::connect_slave(socket_addr, Slave(self.slave_id))
+        let ctx = tcp::connect_slave(socket_addr, Slave(slave_id))
             .await
             .map_err(|e| DeviceError::ConnectionError(format!("Modbus TCP 连接失败: {}", e)))?;
 
         Ok(ctx)
     }
 
+    /// 读取连续的保持寄存器
+    async fn read_registers(ctx: &mut client::Context, addr: u16, count: u16) -> Result<Vec<u16>> {
+        ctx.read_holding_registers(addr, count)
+            .await
+            .map_err(|e| DeviceError::ConnectionError(format!("读取寄存器失败: {}", e)))?
+            .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))
+    }
+
+    /// 读取全部分路的电压/电流/电能
+    async fn read_metering_from(
+        ctx: &mut client::Context,
+        config: &MeteringConfig,
+    ) -> Result<MeteringSnapshot> {
+        let voltage = match config.voltage_addr {
+            Some(addr) => Some(Self::read_registers(ctx, addr, OUTLET_COUNT as u16).await?),
+            None => None,
+        };
+        let current = match config.current_addr {
+            Some(addr) => Some(Self::read_registers(ctx, addr, OUTLET_COUNT as u16).await?),
+            None => None,
+        };
+        let energy = match config.energy_addr {
+            Some(addr) => Some(Self::read_registers(ctx, addr, OUTLET_COUNT as u16 * 2).await?),
+            None => None,
+        };
+
+        Ok(Self::decode_metering(
+            voltage.as_deref(),
+            current.as_deref(),
+            energy.as_deref(),
+        ))
+    }
+
+    /// 将计量寄存器解析为各路读数（未配置的量为 None）
+    fn decode_metering(
+        voltage: Option<&[u16]>,
+        current: Option<&[u16]>,
+        energy: Option<&[u16]>,
+    ) -> MeteringSnapshot {
+        let count = OUTLET_COUNT as usize;
+        let mut outlets = vec![OutletReading::default(); count];
+
+        if let Some(regs) = voltage {
+            for (i, v) in regs.iter().take(count).enumerate() {
+                outlets[i].voltage_raw = Some(*v);
+            }
+        }
+        if let Some(regs) = current {
+            for (i, v) in regs.iter().take(count).enumerate() {
+                outlets[i].current_raw = Some(*v);
+            }
+        }
+        if let Some(regs) = energy {
+            for (i, pair) in regs.chunks(2).take(count).enumerate() {
+                if pair.len() == 2 {
+                    outlets[i].energy_raw = Some(((pair[0] as u32) << 16) | pair[1] as u32);
+                }
+            }
+        }
+
+        MeteringSnapshot {
+            outlets,
+            updated_at: Instant::now(),
+        }
+    }
+
+    /// 实时读取计量数据并更新缓存
+    async fn read_metering(&self) -> Result<MeteringSnapshot> {
+        let config = self
+            .metering
+            .as_ref()
+            .ok_or_else(|| DeviceError::ConfigError("未配置 metering，不支持分路计量".into()))?;
+        let mut ctx = self.connect().await?;
+        let snapshot = Self::read_metering_from(&mut ctx, config).await?;
+        *self.last_metering.write().await = Some(snapshot.clone());
+        Self::evaluate_alarms(
+            &snapshot,
+            config,
+            &self.alarms,
+            &self.groups,
+            &self.active_alarms,
+            &self.events,
+        )
+        .await;
+        Ok(snapshot)
+    }
+
+    /// 单路电流（A）
+    fn outlet_current(reading: &OutletReading, config: &MeteringConfig) -> Option<f64> {
+        reading.current_raw.map(|v| v as f64 * config.current_scale)
+    }
+
+    /// 将计量快照转换为 JSON
+    fn metering_to_json(
+        snapshot: &MeteringSnapshot,
+        config: &MeteringConfig,
+        outlets: &[u8],
+    ) -> Value {
+        let list: Vec<Value> = outlets
+            .iter()
+            .filter_map(|outlet| {
+                let reading = snapshot.outlets.get(*outlet as usize - 1)?;
+                Some(json!({
+                    "outlet": outlet,
+                    "voltage": reading.voltage_raw.map(|v| v as f64 * config.voltage_scale),
+                    "current": Self::outlet_current(reading, config),
+                    "energy": reading.energy_raw.map(|v| v as f64 * config.energy_scale),
+                }))
+            })
+            .collect();
+
+        let total_current: f64 = outlets
+            .iter()
+            .filter_map(|o| snapshot.outlets.get(*o as usize - 1))
+            .filter_map(|r| Self::outlet_current(r, config))
+            .sum();
+
+        json!({
+            "outlets": list,
+            "total_current": total_current,
+            "updated_at": time::instant_rfc3339(snapshot.updated_at),
+            "age_ms": snapshot.updated_at.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// 检查电流阈值，状态变化时发送告警/恢复事件
+    async fn evaluate_alarms(
+        snapshot: &MeteringSnapshot,
+        config: &MeteringConfig,
+        alarms: &AlarmConfig,
+        groups: &HashMap<String, Vec<u8>>,
+        active_alarms: &Arc<RwLock<HashSet<String>>>,
+        events: &EventSink,
+    ) {
+        // (告警键, 当前电流, 阈值, 附加信息)
+        let mut checks: Vec<(String, f64, f64, Value)> = Vec::new();
+
+        for outlet in 1..=OUTLET_COUNT {
+            let threshold = match alarms.outlet_threshold(outlet) {
+                Some(t) => t,
+                None => continue,
+            };
+            if let Some(current) = snapshot
+                .outlets
+                .get(outlet as usize - 1)
+                .and_then(|r| Self::outlet_current(r, config))
+            {
+                checks.push((
+                    format!("outlet:{}", outlet),
+                    current,
+                    threshold,
+                    json!({ "outlet": outlet }),
+                ));
+            }
+        }
+
+        for (name, threshold) in &alarms.groups {
+            let members = match groups.get(name) {
+                Some(m) => m,
+                None => continue,
+            };
+            let current: f64 = members
+                .iter()
+                .filter_map(|o| snapshot.outlets.get(*o as usize - 1))
+                .filter_map(|r| Self::outlet_current(r, config))
+                .sum();
+            checks.push((
+                format!("group:{}", name),
+                current,
+                *threshold,
+                json!({ "group": name }),
+            ));
+        }
+
+        let mut active = active_alarms.write().await;
+        for (key, current, threshold, mut data) in checks {
+            let exceeded = current > threshold;
+            let was_active = active.contains(&key);
+            if exceeded == was_active {
+                continue;
+            }
+
+            data["current"] = json!(current);
+            data["threshold"] = json!(threshold);
+            if exceeded {
+                warn!(
+                    "Tpris PDU 电流告警: {} 当前 {:.2}A 超过阈值 {:.2}A",
+                    key, current, threshold
+                );
+                active.insert(key);
+                events.emit("current_alarm", data);
+            } else {
+                info!(
+                    "Tpris PDU 电流告警恢复: {} 当前 {:.2}A (阈值 {:.2}A)",
+                    key, current, threshold
+                );
+                active.remove(&key);
+                events.emit("current_alarm_cleared", data);
+            }
+        }
+    }
+
+    /// 启动后台计量轮询任务（用于电流告警检测）
+    fn start_metering_poller(&self, config: MeteringConfig) {
+        let addr = self.addr.clone();
+        let port = self.port;
+        let slave_id = self.slave_id;
+        let alarms = self.alarms.clone();
+        let groups = self.groups.clone();
+        let last_metering = self.last_metering.clone();
+        let active_alarms = self.active_alarms.clone();
+        let events = self.events.clone();
+        let poll_gate = self.poll_gate.clone();
+        let name = format!("tpris_pdu:{}:metering", net::display_addr(&addr, port));
+
+        self.tasks.spawn(name, async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(config.interval_ms));
+
+            loop {
+                interval.tick().await;
+                if poll_gate.is_paused() {
+                    continue;
+                }
+
+                let result = async {
+                    let mut ctx = Self::connect_to(&addr, port, slave_id).await?;
+                    Self::read_metering_from(&mut ctx, &config).await
+                }
+                .await;
+
+                match result {
+                    Ok(snapshot) => {
+                        Self::evaluate_alarms(
+                            &snapshot,
+                            &config,
+                            &alarms,
+                            &groups,
+                            &active_alarms,
+                            &events,
+                        )
+                        .await;
+                        *last_metering.write().await = Some(snapshot);
+                    }
+                    Err(e) => {
+                        debug!("Tpris PDU 计量轮询失败 ({}:{}): {}", addr, port, e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// 获取分组成员
+    fn group_members(&self, params: &Value) -> Result<(String, Vec<u8>)> {
+        let group = params
+            .get("group")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| DeviceError::ConfigError("缺少 group 参数".to_string()))?;
+        let members = self
+            .groups
+            .get(group)
+            .cloned()
+            .ok_or_else(|| DeviceError::ConfigError(format!("未找到分组: {}", group)))?;
+        Ok((group.to_string(), members))
+    }
+
     /// 读取8位开关状态
     ///
     /// 读取寄存器 0x0030，返回值低字节解析为8位开关状态
@@ -78,13 +467,7 @@ impl TprisPduProtocol {
             .map_err(|e| DeviceError::ConnectionError(format!("读取开关状态失败: {}", e)))?
             .map_err(|e| DeviceError::ProtocolError(format!("Modbus异常: {:?}", e)))?;
 
-        let raw_value = (registers[0] & 0xFF) as u8;
-
-        let mut switches = HashMap::new();
-        for i in 0..8u8 {
-            let is_on = (raw_value >> i) & 1 == 1;
-            switches.insert(format!("{}", i + 1), is_on);
-        }
+        let (raw_value, switches) = Self::decode_switch_status(&registers);
 
         info!(
             "读取开关状态成功: raw=0x{:02X}, switches={:?}",
@@ -94,6 +477,19 @@ impl TprisPduProtocol {
         Ok((raw_value, switches))
     }
 
+    /// 解析开关状态寄存器：低字节为位掩码
+    fn decode_switch_status(registers: &[u16]) -> (u8, HashMap<String, bool>) {
+        let raw_value = (registers.first().copied().unwrap_or(0) & 0xFF) as u8;
+
+        let mut switches = HashMap::new();
+        for i in 0..8u8 {
+            let is_on = (raw_value >> i) & 1 == 1;
+            switches.insert(format!("{}", i + 1), is_on);
+        }
+
+        (raw_value, switches)
+    }
+
     /// 批量写入8位开关 (组合命令)
     ///
     /// 写入寄存器 0x0030，值为8位位掩码
@@ -117,6 +513,12 @@ impl TprisPduProtocol {
         Ok(())
     }
 
+    /// 单独控制寄存器的值：高字节为开关编号，低字节为动作码
+    fn single_switch_value(switch_id: u8, on: bool) -> u16 {
+        let action = if on { ACTION_ON } else { ACTION_OFF };
+        ((switch_id as u16) << 8) | (action as u16)
+    }
+
     /// 单独控制某个开关
     ///
     /// 写入寄存器 0x0034，值的高字节=开关编号(1-8)，低字节=动作码
@@ -128,8 +530,7 @@ impl TprisPduProtocol {
             ));
         }
 
-        let action = if on { ACTION_ON } else { ACTION_OFF };
-        let value = ((switch_id as u16) << 8) | (action as u16);
+        let value = Self::single_switch_value(switch_id, on);
 
         let mut ctx = self.connect().await?;
 
@@ -151,7 +552,7 @@ impl TprisPduProtocol {
 
 #[async_trait]
 impl Protocol for TprisPduProtocol {
-    fn from_config(_channel_id: u32, params: &HashMap<String, Value>) -> Result<Box<dyn Protocol>>
+    fn from_config(channel_id: u32, params: &HashMap<String, Value>) -> Result<Box<dyn Protocol>>
     where
         Self: Sized,
     {
@@ -166,12 +567,56 @@ impl Protocol for TprisPduProtocol {
 
         let slave_id = params.get("slave_id").and_then(|v| v.as_u64()).unwrap_or(2) as u8; // 默认 slave_id=2 (根据协议示例)
 
+        let metering: Option<MeteringConfig> = params
+            .get("metering")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| DeviceError::ConfigError(format!("metering 解析失败: {}", e)))?;
+
+        let groups: HashMap<String, Vec<u8>> = params
+            .get("groups")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| DeviceError::ConfigError(format!("groups 解析失败: {}", e)))?
+            .unwrap_or_default();
+        for (name, members) in &groups {
+            if let Some(o) = members.iter().find(|o| **o < 1 || **o > OUTLET_COUNT) {
+                return Err(DeviceError::ConfigError(format!(
+                    "分组 {} 包含无效的开关编号 {}",
+                    name, o
+                )));
+            }
+        }
+
+        let alarms: AlarmConfig = params
+            .get("alarms")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| DeviceError::ConfigError(format!("alarms 解析失败: {}", e)))?
+            .unwrap_or_default();
+
         info!(
-            "创建 Tpris PDU 协议: {}:{}, slave_id={}",
-            addr, port, slave_id
+            "创建 Tpris PDU 协议: {}:{}, slave_id={}, 计量: {}, 分组: {}",
+            addr,
+            port,
+            slave_id,
+            metering.is_some(),
+            groups.len()
         );
 
-        Ok(Box::new(Self::new(addr, port, slave_id)))
+        let mut protocol = Self::new(addr, port, slave_id);
+        protocol.metering = metering.clone();
+        protocol.groups = groups;
+        protocol.alarms = alarms;
+        protocol.events = EventSink::new(channel_id);
+
+        if let Some(config) = metering {
+            if config.interval_ms > 0 {
+                protocol.start_metering_poller(config);
+            }
+        }
+
+        Ok(Box::new(protocol))
     }
 
     async fn execute(&mut self, command: &str, params: Value) -> Result<Value> {
@@ -286,6 +731,77 @@ impl Protocol for TprisPduProtocol {
                 }))
             }
 
+            // 读取分路计量（电压/电流/电能）
+            "read_metering" => {
+                let snapshot = self.read_metering().await?;
+                let config = self.metering.as_ref().unwrap();
+                let outlets: Vec<u8> = (1..=OUTLET_COUNT).collect();
+                Ok(Self::metering_to_json(&snapshot, config, &outlets))
+            }
+
+            // 分组开关控制（读-改-写位掩码，一次写入整组）
+            "group_on" | "group_off" => {
+                let (group, members) = self.group_members(&params)?;
+                let on = command == "group_on";
+
+                let (raw_value, _) = self.read_switch_status(ADDR_SWITCH_STATUS).await?;
+                let mut mask = raw_value as u16;
+                for outlet in &members {
+                    if on {
+                        mask |= 1 << (outlet - 1);
+                    } else {
+                        mask &= !(1 << (outlet - 1));
+                    }
+                }
+                self.write_switch_all(ADDR_SWITCH_STATUS, mask).await?;
+
+                Ok(json!({
+                    "status": "success",
+                    "group": group,
+                    "outlets": members,
+                    "action": if on { "on" } else { "off" },
+                    "binary": format!("{:08b}", mask & 0xFF)
+                }))
+            }
+
+            // 读取分组状态（开关 + 计量汇总）
+            "read_group" => {
+                let (group, members) = self.group_members(&params)?;
+                let (raw_value, _) = self.read_switch_status(ADDR_SWITCH_STATUS).await?;
+                let switches: serde_json::Map<String, Value> = members
+                    .iter()
+                    .map(|o| (o.to_string(), Value::Bool((raw_value >> (o - 1)) & 1 == 1)))
+                    .collect();
+
+                let metering = match &self.metering {
+                    Some(config) => {
+                        let snapshot = self.read_metering().await?;
+                        Self::metering_to_json(&snapshot, config, &members)
+                    }
+                    None => Value::Null,
+                };
+
+                Ok(json!({
+                    "status": "success",
+                    "group": group,
+                    "switches": Value::Object(switches),
+                    "metering": metering
+                }))
+            }
+
+            // 查询告警阈值与当前告警
+            "get_alarms" => {
+                let mut active: Vec<String> =
+                    self.active_alarms.read().await.iter().cloned().collect();
+                active.sort();
+                Ok(json!({
+                    "current_max": self.alarms.current_max,
+                    "outlets": self.alarms.outlets,
+                    "groups": self.alarms.groups,
+                    "active": active
+                }))
+            }
+
             _ => Err(DeviceError::Other(format!("未知命令: {}", command))),
         }
     }
@@ -327,6 +843,26 @@ impl Protocol for TprisPduProtocol {
     }
 
     async fn read(&self, id: u32) -> Result<i32> {
+        // id: 101-108 / 201-208 / 301-308 为计量数据点（原始寄存器值）
+        if id > NODE_CURRENT_BASE {
+            let outlet = (id % 100) as usize;
+            if outlet < 1 || outlet > OUTLET_COUNT as usize || id > NODE_ENERGY_BASE + 100 {
+                return Err(DeviceError::ConfigError(format!("无效的计量节点: {}", id)));
+            }
+            let snapshot = self.read_metering().await?;
+            let reading = &snapshot.outlets[outlet - 1];
+            let value = if id > NODE_ENERGY_BASE {
+                reading.energy_raw.map(|v| v as i32)
+            } else if id > NODE_VOLTAGE_BASE {
+                reading.voltage_raw.map(|v| v as i32)
+            } else {
+                reading.current_raw.map(|v| v as i32)
+            };
+            return value.ok_or_else(|| {
+                DeviceError::ConfigError(format!("节点 {} 对应的计量寄存器未配置", id))
+            });
+        }
+
         // id: 开关编号 (1-8)
         // 返回: 1=开, 0=关
         if id < 1 || id > 8 {
@@ -345,11 +881,169 @@ impl Protocol for TprisPduProtocol {
         "tpris-pdu"
     }
 
+    async fn call_method(&mut self, method_name: &str, args: Value) -> Result<Value> {
+        self.execute(method_name, args).await
+    }
+
     fn get_methods(&self) -> Vec<String> {
         vec![
             "read_switch_status".to_string(),
             "write_switch_all".to_string(),
             "write_switch_single".to_string(),
+            "read_metering".to_string(),
+            "group_on".to_string(),
+            "group_off".to_string(),
+            "read_group".to_string(),
+            "get_alarms".to_string(),
         ]
     }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<DeviceEvent>) {
+        self.events.attach(event_tx);
+    }
+
+    fn set_polling_paused(&self, paused: bool) {
+        self.poll_gate.set_paused(paused);
+    }
+
+    async fn shutdown(&self) {
+        self.tasks.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::fixtures::{bytes_to_registers, registers_to_bytes, Captures};
+
+    fn metering_config() -> MeteringConfig {
+        serde_json::from_value(json!({
+            "voltage_addr": 0x0100,
+            "current_addr": 0x0110,
+            "energy_addr": 0x0120,
+        }))
+        .unwrap()
+    }
+
+    fn snapshot(captures: &Captures) -> MeteringSnapshot {
+        let voltage = bytes_to_registers(captures.frame("voltage"));
+        let current = bytes_to_registers(captures.frame("current"));
+        let energy = bytes_to_registers(captures.frame("energy"));
+        TprisPduProtocol::decode_metering(Some(&voltage), Some(&current), Some(&energy))
+    }
+
+    fn assert_close(actual: &Value, expected: f64) {
+        let actual = actual.as_f64().unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_switch_registers_match_golden_frames() {
+        let captures = Captures::load("tpris_pdu");
+
+        let registers = bytes_to_registers(captures.frame("switch_status_1234_on"));
+        let (raw, switches) = TprisPduProtocol::decode_switch_status(&registers);
+        assert_eq!(raw, 0x0F);
+        for outlet in 1..=8 {
+            assert_eq!(
+                switches[&outlet.to_string()],
+                outlet <= 4,
+                "开关 {}",
+                outlet
+            );
+        }
+
+        for (name, switch_id, on) in [
+            ("switch1_off", 1, false),
+            ("switch1_on", 1, true),
+            ("switch8_on", 8, true),
+        ] {
+            let value = TprisPduProtocol::single_switch_value(switch_id, on);
+            captures.assert_frame(name, &registers_to_bytes(&[value]));
+        }
+    }
+
+    #[test]
+    fn test_metering_registers_scale_to_engineering_units() {
+        let captures = Captures::load("tpris_pdu");
+        let snapshot = snapshot(&captures);
+
+        assert_eq!(snapshot.outlets[0].energy_raw, Some(100_000));
+        assert_eq!(snapshot.outlets[1].energy_raw, Some(1234));
+
+        let value =
+            TprisPduProtocol::metering_to_json(&snapshot, &metering_config(), &[1, 2, 3, 8]);
+        let outlets = &value["outlets"];
+        assert_eq!(outlets[0]["outlet"], 1);
+        assert_close(&outlets[0]["voltage"], 220.0);
+        assert_close(&outlets[0]["current"], 1.5);
+        assert_close(&outlets[0]["energy"], 1000.0);
+        assert_close(&outlets[1]["voltage"], 219.5);
+        assert_close(&outlets[1]["current"], 2.25);
+        assert_close(&outlets[1]["energy"], 12.34);
+        assert_close(&outlets[2]["current"], 0.0);
+        assert_close(&outlets[3]["current"], 10.0);
+        // 总电流为所选开关之和
+        assert_close(&value["total_current"], 13.75);
+    }
+
+    #[test]
+    fn test_unconfigured_registers_are_reported_as_null() {
+        let current = [150u16; 8];
+        let snapshot = TprisPduProtocol::decode_metering(None, Some(&current), None);
+        let value = TprisPduProtocol::metering_to_json(&snapshot, &metering_config(), &[1]);
+        assert!(value["outlets"][0]["voltage"].is_null());
+        assert!(value["outlets"][0]["energy"].is_null());
+        assert_close(&value["outlets"][0]["current"], 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_current_alarms_fire_once_and_clear() {
+        let captures = Captures::load("tpris_pdu");
+        let config = metering_config();
+        let alarms: AlarmConfig = serde_json::from_value(json!({
+            "current_max": 5.0,
+            "outlets": { "2": 2.0 },
+            "groups": { "rackA": 3.0 },
+        }))
+        .unwrap();
+        let groups = HashMap::from([("rackA".to_string(), vec![1, 2])]);
+        let active = Arc::new(RwLock::new(HashSet::new()));
+        let events = EventSink::new(7);
+        let (tx, mut rx) = broadcast::channel(16);
+        events.attach(tx);
+
+        // 开关 2 (2.25A > 2.0A)、开关 8 (10A > 5A)、rackA (3.75A > 3A) 超限
+        let loaded = snapshot(&captures);
+        for _ in 0..2 {
+            TprisPduProtocol::evaluate_alarms(&loaded, &config, &alarms, &groups, &active, &events)
+                .await;
+        }
+        let mut raised = Vec::new();
+        while let Ok(DeviceEvent::ProtocolEvent { event, data, .. }) = rx.try_recv() {
+            assert_eq!(event, "current_alarm");
+            raised.push(data);
+        }
+        // 持续超限只告警一次
+        assert_eq!(raised.len(), 3);
+        assert!(raised
+            .iter()
+            .any(|d| d["group"] == "rackA" && d["threshold"] == 3.0));
+        assert_eq!(active.read().await.len(), 3);
+
+        let idle = TprisPduProtocol::decode_metering(None, Some(&[0u16; 8]), None);
+        TprisPduProtocol::evaluate_alarms(&idle, &config, &alarms, &groups, &active, &events).await;
+        let mut cleared = 0;
+        while let Ok(DeviceEvent::ProtocolEvent { event, .. }) = rx.try_recv() {
+            assert_eq!(event, "current_alarm_cleared");
+            cleared += 1;
+        }
+        assert_eq!(cleared, 3);
+        assert!(active.read().await.is_empty());
+    }
 }
@@ -22,7 +22,7 @@ use tracing::{debug, error, info, warn};
 
 use crate::protocols::Protocol;
 use crate::utils::error::DeviceError;
-use crate::utils::Result;
+use crate::utils::{dns, net, Result};
 
 // 帧常量
 const FRAME_HEADER: u8 = 0x55;
@@ -65,10 +65,10 @@ impl Wdy8enProtocol {
 
     /// 发送命令并接收响应
     async fn send_command(&self, command: &[u8], expect_len: usize) -> Result<Vec<u8>> {
-        let addr = format!("{}:{}", self.addr, self.port);
+        let addr = net::display_addr(&self.addr, self.port);
         debug!("连接到 WDY-8EN: {}", addr);
 
-        let mut stream = TcpStream::connect(&addr)
+        let mut stream = TcpStream::connect(dns::resolve(&self.addr, self.port).await?)
             .await
             .map_err(|e| DeviceError::ConnectionError(format!("连接失败: {}", e)))?;
 
@@ -440,6 +440,10 @@ impl Protocol for Wdy8enProtocol {
         "wdy-8en"
     }
 
+    async fn send_raw(&mut self, payload: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        net::exchange_raw(&self.addr, self.port, payload, timeout).await
+    }
+
     fn get_methods(&self) -> Vec<String> {
         vec![
             "power_on".to_string(),
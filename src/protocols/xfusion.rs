@@ -1,23 +1,28 @@
+use crate::config::HttpClientConfig;
 use crate::protocols::storage::get_or_init_storage;
-use crate::protocols::Protocol;
-use crate::utils::{DeviceError, Result};
+use crate::protocols::xfusion_agent::AgentRegistry;
+use crate::protocols::{PollGate, Protocol};
+use crate::utils::net::KeepaliveConfig;
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::{http, net, time, DeviceError, Result};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
 use mac_address::MacAddress;
 use tracing::{debug, error, info, warn};
 
+#[derive(Clone)]
 struct XFusionNode {
     id: u32,
     mac_text: String,
     mac_bytes: [u8; 6],
-    ip: Option<Ipv4Addr>,
+    ip: Option<IpAddr>,
     port: Option<u16>,
     last_heartbeat: Option<Instant>,
     // iBMC Redfish API 配置
@@ -44,6 +49,138 @@ fn default_system_id() -> String {
     "1".to_string()
 }
 
+/// 默认电源状态轮询间隔（毫秒）
+const DEFAULT_POLL_INTERVAL_MS: u64 = 5000;
+/// 默认缓存最大有效期（毫秒），超过后 read 回退到实时查询
+const DEFAULT_CACHE_MAX_AGE_MS: u64 = 15000;
+/// 批量电源操作默认错峰间隔（毫秒），避免多台服务器同时上电导致跳闸
+const DEFAULT_STAGGER_MS: u64 = 3000;
+/// 代理命令默认超时（毫秒）
+const DEFAULT_AGENT_TIMEOUT_MS: u64 = 5000;
+
+/// 节点电源/音频状态快照（由后台轮询任务维护）
+#[derive(Debug, Clone)]
+struct PowerSnapshot {
+    /// iBMC 返回的 PowerState（查询失败时为 None）
+    power_state: Option<String>,
+    /// 是否开机（iBMC 失败时回退到 ping 检测）
+    powered_on: bool,
+    volume: Option<i32>,
+    mute: Option<bool>,
+    /// 最近一次查询错误
+    error: Option<String>,
+    updated_at: Instant,
+}
+
+impl PowerSnapshot {
+    fn age_ms(&self) -> u64 {
+        self.updated_at.elapsed().as_millis() as u64
+    }
+}
+
+/// iBMC Redfish / UDP 通信客户端
+///
+/// 与协议实例分离，以便后台轮询任务共享会话 Token 和 HTTP 连接。
+#[derive(Clone)]
+struct XFusionClient {
+    channel_id: u32,
+    http_client: reqwest::Client,
+    /// 缓存的会话 Token (node_id -> token) - 内存缓存
+    token_cache: Arc<RwLock<HashMap<u32, String>>>,
+    /// OS 代理长连接（未配置 agent_port 时始终为空）
+    agents: AgentRegistry,
+}
+
+/// 电源操作与随后的缓存失效
+///
+/// 只持有共享句柄，批量电源操作在后台任务中使用，错峰等待期间不占用协议锁。
+#[derive(Clone)]
+struct PowerActions {
+    client: XFusionClient,
+    power_cache: Arc<RwLock<HashMap<u32, PowerSnapshot>>>,
+    refresh_notify: Arc<HashMap<u32, Arc<Notify>>>,
+}
+
+impl PowerActions {
+    /// 执行电源操作，完成后使缓存失效并触发立即刷新
+    async fn power_action(&self, node: &XFusionNode, reset_type: &str) -> Result<()> {
+        let result = self.client.power_action(node, reset_type).await;
+        self.power_cache.write().await.remove(&node.id);
+        if let Some(notify) = self.refresh_notify.get(&node.id) {
+            notify.notify_one();
+        }
+        result
+    }
+
+    /// 按顺序对多个节点执行电源操作，节点之间错峰等待
+    ///
+    /// 单个节点失败不会中断后续节点，除非指定 `stop_on_error`。
+    async fn batch(
+        &self,
+        nodes: &[XFusionNode],
+        reset_type: &str,
+        stagger_ms: u64,
+        stop_on_error: bool,
+    ) -> Value {
+        info!(
+            "通道 {} [xFusion]: 批量电源操作 '{}', 节点: {:?}, 错峰间隔: {}ms",
+            self.client.channel_id,
+            reset_type,
+            nodes.iter().map(|n| n.id).collect::<Vec<_>>(),
+            stagger_ms
+        );
+
+        let mut results = Vec::new();
+        let mut success_count = 0;
+        let mut aborted = false;
+
+        for (index, node) in nodes.iter().enumerate() {
+            let id = node.id;
+            if aborted {
+                results.push(serde_json::json!({
+                    "id": id,
+                    "success": false,
+                    "skipped": true,
+                }));
+                continue;
+            }
+
+            if index > 0 && stagger_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(stagger_ms)).await;
+            }
+
+            match self.power_action(node, reset_type).await {
+                Ok(()) => {
+                    success_count += 1;
+                    results.push(serde_json::json!({ "id": id, "success": true }));
+                }
+                Err(e) => {
+                    warn!(
+                        "通道 {} [xFusion]: 批量操作中节点 ID:{} 执行 '{}' 失败: {}",
+                        self.client.channel_id, id, reset_type, e
+                    );
+                    results.push(serde_json::json!({
+                        "id": id,
+                        "success": false,
+                        "error": e.to_string(),
+                    }));
+                    if stop_on_error {
+                        aborted = true;
+                    }
+                }
+            }
+        }
+
+        serde_json::json!({
+            "action": reset_type,
+            "total": nodes.len(),
+            "success": success_count,
+            "failed": nodes.len() - success_count,
+            "results": results,
+        })
+    }
+}
+
 /// xFusion 服务器控制协议（iBMC Redfish API + 状态监控）
 pub struct XFusionProtocol {
     channel_id: u32,
@@ -51,12 +188,36 @@ pub struct XFusionProtocol {
     broadcast_addr: Ipv4Addr,
     wol_port: u16,
     shutdown_port: u16,
-    http_client: reqwest::Client,
-    /// 缓存的会话 Token (node_id -> token) - 内存缓存
-    token_cache: Arc<RwLock<HashMap<u32, String>>>,
+    client: XFusionClient,
+    /// 轮询间隔（毫秒），0 表示禁用后台轮询
+    poll_interval_ms: u64,
+    /// 缓存最大有效期（毫秒）
+    cache_max_age_ms: u64,
+    /// 电源状态缓存 (node_id -> snapshot)
+    power_cache: Arc<RwLock<HashMap<u32, PowerSnapshot>>>,
+    /// 触发立即刷新的通知 (node_id -> notify)
+    refresh_notify: Arc<HashMap<u32, Arc<Notify>>>,
+    /// 最近一次批量电源操作的报告（执行中时 `running` 为 true）
+    last_batch: Arc<RwLock<Option<Value>>>,
+    /// 命名节点分组 (group -> 有序节点 ID 列表)
+    groups: HashMap<String, Vec<u32>>,
+    /// 批量电源操作默认错峰间隔（毫秒）
+    stagger_ms: u64,
+    /// 电源轮询暂停开关
+    poll_gate: PollGate,
+    /// 后台任务
+    tasks: TaskRegistry,
+    /// OS 代理监听端口（未配置时不监听）
+    agent_port: Option<u16>,
+    /// 代理握手共享令牌（配置 `agent_port` 时必填）
+    agent_token: String,
+    /// 代理连接的 TCP 保活参数
+    agent_keepalive: KeepaliveConfig,
+    /// 代理监听与连接任务（`tasks` 的子注册表，释放端口时单独停止）
+    agent_tasks: std::sync::Mutex<TaskRegistry>,
 }
 
-impl XFusionProtocol {
+impl XFusionClient {
     /// 获取 Token 的存储键
     fn token_storage_key(node_id: u32) -> String {
         format!("token_{}", node_id)
@@ -373,67 +534,22 @@ impl XFusionProtocol {
         Err(DeviceError::ProtocolError("电源操作重试次数已用尽".into()))
     }
 
-    /// 开机 (使用 iBMC Redfish API)
-    async fn power_on(&self, node: &XFusionNode) -> Result<()> {
-        debug!(
-            "通道 {} [xFusion]: 节点 ID:{} 调用开机 (On)",
-            self.channel_id, node.id
-        );
-        self.power_action(node, "On").await
-    }
-
-    /// 关机 (使用 iBMC Redfish API)
-    async fn power_off(&self, node: &XFusionNode) -> Result<()> {
-        debug!(
-            "通道 {} [xFusion]: 节点 ID:{} 调用关机 (GracefulShutdown)",
-            self.channel_id, node.id
-        );
-        self.power_action(node, "GracefulShutdown").await
-    }
-
-    /// 强制关机
-    async fn force_off(&self, node: &XFusionNode) -> Result<()> {
-        debug!(
-            "通道 {} [xFusion]: 节点 ID:{} 调用强制关机 (ForceOff)",
-            self.channel_id, node.id
-        );
-        self.power_action(node, "ForceOff").await
-    }
-
-    /// 强制重启
-    async fn force_restart(&self, node: &XFusionNode) -> Result<()> {
-        debug!(
-            "通道 {} [xFusion]: 节点 ID:{} 调用强制重启 (ForceRestart)",
-            self.channel_id, node.id
-        );
-        self.power_action(node, "ForceRestart").await
-    }
-
-    /// 强制下电再上电
-    async fn force_power_cycle(&self, node: &XFusionNode) -> Result<()> {
-        debug!(
-            "通道 {} [xFusion]: 节点 ID:{} 调用强制下电再上电 (ForcePowerCycle)",
-            self.channel_id, node.id
-        );
-        self.power_action(node, "ForcePowerCycle").await
-    }
-
     async fn send_udp(
         &self,
-        ip: Ipv4Addr,
+        ip: IpAddr,
         port: u16,
         command: &str,
         wait_response: bool,
     ) -> Result<Option<String>> {
+        let to = SocketAddr::new(ip, port);
         debug!(
-            "通道 {} [UDP]: 发送命令 '{}' 到 {}:{}",
-            self.channel_id, command, ip, port
+            "通道 {} [UDP]: 发送命令 '{}' 到 {}",
+            self.channel_id, command, to
         );
-        let socket = tokio::net::UdpSocket::bind((Ipv4Addr::new(0, 0, 0, 0), 0))
+        let socket = tokio::net::UdpSocket::bind(net::unspecified_for(&to, 0))
             .await
             .map_err(|e| DeviceError::ProtocolError(format!("绑定 UDP socket 失败: {}", e)))?;
 
-        let to = std::net::SocketAddrV4::new(ip, port);
         socket
             .send_to(command.as_bytes(), to)
             .await
@@ -446,16 +562,13 @@ impl XFusionProtocol {
                 Ok(Ok((n, _))) => {
                     let response = String::from_utf8_lossy(&buf[..n]).trim().to_string();
                     debug!(
-                        "通道 {} [UDP]: 收到来自 {}:{} 的响应: {}",
-                        self.channel_id, ip, port, response
+                        "通道 {} [UDP]: 收到来自 {} 的响应: {}",
+                        self.channel_id, to, response
                     );
                     Ok(Some(response))
                 }
                 _ => {
-                    debug!(
-                        "通道 {} [UDP]: 等待 {}:{} 响应超时",
-                        self.channel_id, ip, port
-                    );
+                    debug!("通道 {} [UDP]: 等待 {} 响应超时", self.channel_id, to);
                     Ok(None)
                 }
             }
@@ -465,10 +578,19 @@ impl XFusionProtocol {
     }
 
     async fn ping_node(&self, node: &XFusionNode) -> bool {
+        if self.agents.is_connected(node.id).await {
+            debug!(
+                "通道 {} [xFusion]: 节点 ID:{} 代理已连接, 视为在线",
+                self.channel_id, node.id
+            );
+            return true;
+        }
         if let (Some(ip), Some(port)) = (node.ip, node.port) {
             debug!(
-                "通道 {} [xFusion]: 节点 ID:{} 正在 ping {}:{}",
-                self.channel_id, node.id, ip, port
+                "通道 {} [xFusion]: 节点 ID:{} 正在 ping {}",
+                self.channel_id,
+                node.id,
+                SocketAddr::new(ip, port)
             );
             if let Ok(Some(resp)) = self.send_udp(ip, port, "ping", true).await {
                 let is_pong = resp.eq_ignore_ascii_case("pong");
@@ -494,28 +616,6 @@ impl XFusionProtocol {
         false
     }
 
-    fn update_heartbeat(&mut self, mac: &str) -> bool {
-        for node in &mut self.nodes {
-            if node.mac_text.eq_ignore_ascii_case(mac) {
-                debug!(
-                    "通道 {} [Heartbeat]: 更新节点 ID:{} (MAC:{}) 的心跳",
-                    self.channel_id, node.id, mac
-                );
-                node.last_heartbeat = Some(Instant::now());
-                return true;
-            }
-        }
-        warn!(
-            "通道 {} [Heartbeat]: 收到未知 MAC 地址的心跳: {}",
-            self.channel_id, mac
-        );
-        false
-    }
-
-    fn find_node_by_id(&self, id: u32) -> Option<&XFusionNode> {
-        self.nodes.iter().find(|c| c.id == id)
-    }
-
     /// 通过 iBMC Redfish API 获取电源状态
     async fn get_power_state(&self, node: &XFusionNode) -> Result<String> {
         let system_url = format!("{}/redfish/v1/Systems/{}", node.ibmc_url, node.system_id);
@@ -625,129 +725,430 @@ impl XFusionProtocol {
         ))
     }
 
-    /// 检查节点是否开机 (优先使用 iBMC API)
-    async fn is_node_powered_on(&self, node: &XFusionNode) -> bool {
-        match self.get_power_state(node).await {
-            Ok(state) => {
-                let is_on = state.eq_ignore_ascii_case("On");
+    /// 通过 UDP 查询音频状态（volume / mute）
+    async fn query_audio_status(&self, node: &XFusionNode) -> (Option<i32>, Option<bool>) {
+        // 优先通过代理长连接查询
+        if self.agents.is_connected(node.id).await {
+            match self
+                .agents
+                .request(
+                    node.id,
+                    "getAudio",
+                    Value::Null,
+                    Duration::from_millis(DEFAULT_AGENT_TIMEOUT_MS),
+                )
+                .await
+            {
+                Ok(result) => {
+                    let volume = result
+                        .get("volume")
+                        .and_then(|v| v.as_i64())
+                        .map(|v| v as i32);
+                    let mute = result.get("mute").and_then(|v| v.as_bool());
+                    return (volume, mute);
+                }
+                Err(e) => {
+                    debug!(
+                        "通道 {} [xFusion]: 节点 ID:{} 代理查询音频失败: {}, 回退到 UDP",
+                        self.channel_id, node.id, e
+                    );
+                }
+            }
+        }
+
+        let (ip, port) = match (node.ip, node.port) {
+            (Some(ip), Some(port)) => (ip, port),
+            _ => return (None, None),
+        };
+
+        match self.send_udp(ip, port, "get", true).await {
+            Ok(Some(resp)) => {
+                debug!(
+                    "通道 {} [xFusion]: 节点 ID:{} 音频状态响应: {}",
+                    self.channel_id, node.id, resp
+                );
+                let mut volume = None;
+                let mut mute = None;
+
+                for part in resp.split(',') {
+                    let kv: Vec<&str> = part.split(':').collect();
+                    if kv.len() == 2 {
+                        let key = kv[0].trim().to_lowercase();
+                        let value = kv[1].trim();
+                        if key == "volume" {
+                            volume = value.parse::<i32>().ok();
+                        } else if key == "mute" {
+                            mute = value.parse::<bool>().ok();
+                        }
+                    }
+                }
                 debug!(
-                    "通道 {} [xFusion]: 节点 ID:{} iBMC 电源状态: {}, 开机: {}",
-                    self.channel_id, node.id, state, is_on
+                    "通道 {} [xFusion]: 节点 ID:{} 解析结果 - volume: {:?}, mute: {:?}",
+                    self.channel_id, node.id, volume, mute
                 );
-                is_on
+                (volume, mute)
             }
-            Err(e) => {
-                warn!(
-                    "通道 {} [xFusion]: 节点 ID:{} 获取 iBMC 电源状态失败: {}, 回退到 ping 检测",
-                    self.channel_id, node.id, e
+            _ => {
+                debug!(
+                    "通道 {} [xFusion]: 节点 ID:{} 音频状态无响应",
+                    self.channel_id, node.id
                 );
+                (None, None)
+            }
+        }
+    }
+
+    /// 采集节点完整状态快照（电源 + 音频）
+    async fn snapshot(&self, node: &XFusionNode) -> PowerSnapshot {
+        let (power_state, powered_on, error) = match self.get_power_state(node).await {
+            Ok(state) => {
+                let is_on = state.eq_ignore_ascii_case("On");
+                (Some(state), is_on, None)
+            }
+            Err(e) => {
                 // 回退到 ping 检测
-                self.ping_node(node).await
+                let reachable = self.ping_node(node).await;
+                (None, reachable, Some(e.to_string()))
             }
+        };
+
+        let (volume, mute) = if powered_on {
+            self.query_audio_status(node).await
+        } else {
+            (None, None)
+        };
+
+        PowerSnapshot {
+            power_state,
+            powered_on,
+            volume,
+            mute,
+            error,
+            updated_at: Instant::now(),
         }
     }
+}
 
-    async fn is_node_online(&self, node: &XFusionNode) -> bool {
+impl XFusionProtocol {
+    /// 开机 (使用 iBMC Redfish API)
+    async fn power_on(&self, node: &XFusionNode) -> Result<()> {
         debug!(
-            "通道 {} [xFusion]: 检查节点 ID:{} 在线状态",
+            "通道 {} [xFusion]: 节点 ID:{} 调用开机 (On)",
             self.channel_id, node.id
         );
+        self.power_action(node, "On").await
+    }
 
-        // 优先使用 iBMC API 检测电源状态
-        if self.is_node_powered_on(node).await {
-            debug!(
-                "通道 {} [xFusion]: 节点 ID:{} 通过 iBMC API 检测开机",
-                self.channel_id, node.id
-            );
-            return true;
-        }
-
-        // 回退到 ping 检测
-        if self.ping_node(node).await {
-            debug!(
-                "通道 {} [xFusion]: 节点 ID:{} 通过 ping 检测在线",
-                self.channel_id, node.id
-            );
-            return true;
-        }
+    /// 关机 (使用 iBMC Redfish API)
+    async fn power_off(&self, node: &XFusionNode) -> Result<()> {
+        debug!(
+            "通道 {} [xFusion]: 节点 ID:{} 调用关机 (GracefulShutdown)",
+            self.channel_id, node.id
+        );
+        self.power_action(node, "GracefulShutdown").await
+    }
 
-        // 最后检查心跳
-        if let Some(last) = node.last_heartbeat {
-            let elapsed = Instant::now().duration_since(last);
-            let is_online = elapsed < Duration::from_secs(10);
-            debug!(
-                "通道 {} [xFusion]: 节点 ID:{} 上次心跳距今: {:?}, 在线: {}",
-                self.channel_id, node.id, elapsed, is_online
-            );
-            return is_online;
-        }
+    /// 强制关机
+    async fn force_off(&self, node: &XFusionNode) -> Result<()> {
+        debug!(
+            "通道 {} [xFusion]: 节点 ID:{} 调用强制关机 (ForceOff)",
+            self.channel_id, node.id
+        );
+        self.power_action(node, "ForceOff").await
+    }
 
+    /// 强制重启
+    async fn force_restart(&self, node: &XFusionNode) -> Result<()> {
         debug!(
-            "通道 {} [xFusion]: 节点 ID:{} 所有检测方式均失败, 离线",
+            "通道 {} [xFusion]: 节点 ID:{} 调用强制重启 (ForceRestart)",
             self.channel_id, node.id
         );
-        false
+        self.power_action(node, "ForceRestart").await
     }
 
-    async fn get_audio_status(&self, node: &XFusionNode) -> (Option<i32>, Option<bool>, bool) {
+    /// 强制下电再上电
+    async fn force_power_cycle(&self, node: &XFusionNode) -> Result<()> {
         debug!(
-            "通道 {} [xFusion]: 获取节点 ID:{} 音频状态",
+            "通道 {} [xFusion]: 节点 ID:{} 调用强制下电再上电 (ForcePowerCycle)",
             self.channel_id, node.id
         );
-        let is_online = self.is_node_online(node).await;
-        if !is_online {
-            debug!(
-                "通道 {} [xFusion]: 节点 ID:{} 离线, 跳过音频状态获取",
-                self.channel_id, node.id
-            );
-            return (None, None, false);
+        self.power_action(node, "ForcePowerCycle").await
+    }
+
+    fn update_heartbeat(&mut self, mac: &str) -> bool {
+        for node in &mut self.nodes {
+            if node.mac_text.eq_ignore_ascii_case(mac) {
+                debug!(
+                    "通道 {} [Heartbeat]: 更新节点 ID:{} (MAC:{}) 的心跳",
+                    self.channel_id, node.id, mac
+                );
+                node.last_heartbeat = Some(Instant::now());
+                return true;
+            }
         }
+        warn!(
+            "通道 {} [Heartbeat]: 收到未知 MAC 地址的心跳: {}",
+            self.channel_id, mac
+        );
+        false
+    }
 
-        if let (Some(ip), Some(port)) = (node.ip, node.port) {
-            match self.send_udp(ip, port, "get", true).await {
-                Ok(Some(resp)) => {
-                    debug!(
-                        "通道 {} [xFusion]: 节点 ID:{} 音频状态响应: {}",
-                        self.channel_id, node.id, resp
-                    );
-                    let mut volume = None;
-                    let mut mute = None;
-
-                    for part in resp.split(',') {
-                        let kv: Vec<&str> = part.split(':').collect();
-                        if kv.len() == 2 {
-                            let key = kv[0].trim().to_lowercase();
-                            let value = kv[1].trim();
-                            if key == "volume" {
-                                volume = value.parse::<i32>().ok();
-                            } else if key == "mute" {
-                                mute = value.parse::<bool>().ok();
-                            }
+    fn find_node_by_id(&self, id: u32) -> Option<&XFusionNode> {
+        self.nodes.iter().find(|c| c.id == id)
+    }
+
+    /// 检查节点心跳是否在有效期内
+    fn heartbeat_alive(&self, node: &XFusionNode) -> bool {
+        match node.last_heartbeat {
+            Some(last) => {
+                let elapsed = Instant::now().duration_since(last);
+                debug!(
+                    "通道 {} [xFusion]: 节点 ID:{} 上次心跳距今: {:?}",
+                    self.channel_id, node.id, elapsed
+                );
+                elapsed < Duration::from_secs(10)
+            }
+            None => false,
+        }
+    }
+
+    fn power_actions(&self) -> PowerActions {
+        PowerActions {
+            client: self.client.clone(),
+            power_cache: self.power_cache.clone(),
+            refresh_notify: self.refresh_notify.clone(),
+        }
+    }
+
+    /// 执行电源操作，完成后使缓存失效并触发立即刷新
+    async fn power_action(&self, node: &XFusionNode, reset_type: &str) -> Result<()> {
+        self.power_actions().power_action(node, reset_type).await
+    }
+
+    /// 获取缓存的状态快照（轮询禁用或缓存过期时返回 None）
+    async fn cached_snapshot(&self, node_id: u32) -> Option<PowerSnapshot> {
+        if self.poll_interval_ms == 0 {
+            return None;
+        }
+        self.power_cache
+            .read()
+            .await
+            .get(&node_id)
+            .filter(|s| s.age_ms() <= self.cache_max_age_ms)
+            .cloned()
+    }
+
+    /// 实时查询节点状态并更新缓存
+    async fn refresh_snapshot(&self, node: &XFusionNode) -> PowerSnapshot {
+        let snapshot = self.client.snapshot(node).await;
+        self.power_cache
+            .write()
+            .await
+            .insert(node.id, snapshot.clone());
+        snapshot
+    }
+
+    /// 获取节点状态快照（优先使用缓存）
+    async fn get_snapshot(&self, node: &XFusionNode) -> (PowerSnapshot, bool) {
+        match self.cached_snapshot(node.id).await {
+            Some(snapshot) => (snapshot, true),
+            None => (self.refresh_snapshot(node).await, false),
+        }
+    }
+
+    /// 通过代理长连接执行命令
+    async fn agent_command(&self, params: &Value, cmd: &str, args: Value) -> Result<Value> {
+        let id = params
+            .get("id")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32)
+            .ok_or_else(|| DeviceError::ProtocolError(format!("{} 需要 id 参数", cmd)))?;
+        if self.find_node_by_id(id).is_none() {
+            return Err(DeviceError::ProtocolError(format!(
+                "未找到 ID 为 {} 的节点",
+                id
+            )));
+        }
+        let timeout_ms = params
+            .get("timeout_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_AGENT_TIMEOUT_MS);
+
+        let result = self
+            .client
+            .agents
+            .request(id, cmd, args, Duration::from_millis(timeout_ms))
+            .await?;
+        Ok(serde_json::json!({ "id": id, "cmd": cmd, "result": result }))
+    }
+
+    /// 解析批量操作的目标节点
+    ///
+    /// 优先级：`ids` 显式列表 > `group` 命名分组 > 全部节点（按配置顺序）
+    fn resolve_batch_targets(&self, params: &Value) -> Result<Vec<u32>> {
+        if let Some(ids) = params.get("ids").and_then(|v| v.as_array()) {
+            let invalid: Vec<&Value> = ids
+                .iter()
+                .filter(|v| {
+                    v.as_u64()
+                        .and_then(|id| u32::try_from(id).ok())
+                        .and_then(|id| self.find_node_by_id(id))
+                        .is_none()
+                })
+                .collect();
+            if !invalid.is_empty() {
+                return Err(DeviceError::ProtocolError(format!(
+                    "ids 中存在无效或未配置的节点: {}",
+                    Value::from(invalid.into_iter().cloned().collect::<Vec<_>>())
+                )));
+            }
+            return Ok(ids
+                .iter()
+                .filter_map(|v| v.as_u64())
+                .map(|v| v as u32)
+                .collect());
+        }
+
+        if let Some(group) = params.get("group").and_then(|v| v.as_str()) {
+            return self
+                .groups
+                .get(group)
+                .cloned()
+                .ok_or_else(|| DeviceError::ProtocolError(format!("未找到分组: {}", group)));
+        }
+
+        Ok(self.nodes.iter().map(|n| n.id).collect())
+    }
+
+    /// 在后台任务中执行批量电源操作，立即返回；报告通过 `getBatchResult` 查询
+    ///
+    /// 错峰等待可能持续数分钟，期间不占用协议锁，其他命令照常执行。同一通道同时只允许一个
+    /// 批量操作，避免两批操作交错后失去错峰效果。
+    async fn start_batch(
+        &self,
+        ids: &[u32],
+        reset_type: &'static str,
+        stagger_ms: u64,
+        stop_on_error: bool,
+    ) -> Result<Value> {
+        let nodes: Vec<XFusionNode> = ids
+            .iter()
+            .filter_map(|id| self.find_node_by_id(*id).cloned())
+            .collect();
+        let started = serde_json::json!({
+            "action": reset_type,
+            "total": nodes.len(),
+            "ids": ids,
+            "stagger_ms": stagger_ms,
+            "running": true,
+        });
+        {
+            let mut last_batch = self.last_batch.write().await;
+            if let Some(running) = last_batch.as_ref().filter(|b| b["running"] == true) {
+                return Err(DeviceError::ProtocolError(format!(
+                    "批量电源操作 '{}' 正在执行，请等待完成",
+                    running["action"].as_str().unwrap_or_default()
+                )));
+            }
+            *last_batch = Some(started.clone());
+        }
+
+        let actions = self.power_actions();
+        let last_batch = self.last_batch.clone();
+        let name = format!("xfusion:{}:batch:{}", self.channel_id, reset_type);
+        self.tasks.spawn(name, async move {
+            let mut report = actions
+                .batch(&nodes, reset_type, stagger_ms, stop_on_error)
+                .await;
+            report["running"] = Value::Bool(false);
+            *last_batch.write().await = Some(report);
+        });
+        Ok(started)
+    }
+
+    /// 在 `agent_tasks` 中启动 OS 代理长连接监听（未配置 `agent_port` 时无操作）
+    fn start_agent_listener(&self) {
+        let Some(port) = self.agent_port else {
+            return;
+        };
+        let identities: Vec<(u32, String)> = self
+            .nodes
+            .iter()
+            .map(|n| (n.id, n.mac_text.clone()))
+            .collect();
+        let agent_tasks = self.agent_tasks.lock().unwrap().clone();
+        self.client.agents.start_listener(
+            self.channel_id,
+            port,
+            self.agent_keepalive,
+            &self.agent_token,
+            &agent_tasks,
+            move |hello| {
+                if let Some(id) = hello.get("id").and_then(|v| v.as_u64()) {
+                    return identities
+                        .iter()
+                        .find(|(node_id, _)| *node_id as u64 == id)
+                        .map(|(node_id, _)| *node_id);
+                }
+                let mac = hello.get("mac").and_then(|v| v.as_str())?;
+                identities
+                    .iter()
+                    .find(|(_, m)| m.eq_ignore_ascii_case(mac))
+                    .map(|(node_id, _)| *node_id)
+            },
+        );
+    }
+
+    /// 为每个节点启动后台电源状态轮询任务
+    fn start_power_pollers(&self) {
+        for node in &self.nodes {
+            let client = self.client.clone();
+            let node = node.clone();
+            let cache = self.power_cache.clone();
+            let notify = match self.refresh_notify.get(&node.id) {
+                Some(n) => n.clone(),
+                None => continue,
+            };
+            let interval_ms = self.poll_interval_ms;
+            let poll_gate = self.poll_gate.clone();
+            let name = format!("xfusion:{}:power:{}", self.channel_id, node.id);
+
+            self.tasks.spawn(name, async move {
+                let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {}
+                        _ = notify.notified() => {
+                            debug!(
+                                "通道 {} [xFusion]: 节点 ID:{} 电源操作后立即刷新状态",
+                                client.channel_id, node.id
+                            );
                         }
                     }
-                    debug!(
-                        "通道 {} [xFusion]: 节点 ID:{} 解析结果 - volume: {:?}, mute: {:?}",
-                        self.channel_id, node.id, volume, mute
-                    );
-                    (volume, mute, true)
-                }
-                _ => {
-                    debug!(
-                        "通道 {} [xFusion]: 节点 ID:{} 音频状态无响应",
-                        self.channel_id, node.id
-                    );
-                    (None, None, true)
+
+                    if poll_gate.is_paused() {
+                        continue;
+                    }
+
+                    let snapshot = client.snapshot(&node).await;
+                    if let Some(err) = &snapshot.error {
+                        debug!(
+                            "通道 {} [xFusion]: 节点 ID:{} 轮询 iBMC 失败: {}",
+                            client.channel_id, node.id, err
+                        );
+                    }
+                    cache.write().await.insert(node.id, snapshot);
                 }
-            }
-        } else {
-            (None, None, is_online)
+            });
         }
     }
 }
 
-#[async_trait]
-impl Protocol for XFusionProtocol {
-    fn from_config(channel_id: u32, params: &HashMap<String, Value>) -> Result<Box<dyn Protocol>> {
+impl XFusionProtocol {
+    /// 根据通道参数创建协议实例并启动后台任务
+    fn from_params(channel_id: u32, params: &HashMap<String, Value>) -> Result<Self> {
         let node_list_json = params
             .get("nodes")
             .or_else(|| params.get("mac_address"))
@@ -767,7 +1168,7 @@ impl Protocol for XFusionProtocol {
                 })?
                 .bytes();
 
-            let ip = item.ip.as_ref().and_then(|s| s.parse::<Ipv4Addr>().ok());
+            let ip = item.ip.as_deref().and_then(net::parse_ip);
 
             nodes.push(XFusionNode {
                 id: item.id,
@@ -810,12 +1211,25 @@ impl Protocol for XFusionProtocol {
             .map(|p| p as u16)
             .unwrap_or(wol_port);
 
-        // 创建 HTTP 客户端，忽略 SSL 证书验证（iBMC 通常使用自签名证书）
-        let http_client = reqwest::Client::builder()
-            .danger_accept_invalid_certs(true)
-            .timeout(Duration::from_secs(30))
-            .build()
-            .map_err(|e| DeviceError::ConfigError(format!("创建 HTTP 客户端失败: {}", e)))?;
+        let poll_interval_ms = params
+            .get("poll_interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_POLL_INTERVAL_MS);
+
+        let cache_max_age_ms = params
+            .get("cache_max_age_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_CACHE_MAX_AGE_MS);
+
+        // 创建 HTTP 客户端，默认忽略 SSL 证书验证（iBMC 通常使用自签名证书）
+        let http_client = http::client_from_params(
+            params,
+            &HttpClientConfig {
+                timeout_ms: Some(30_000),
+                accept_invalid_certs: Some(true),
+                ..Default::default()
+            },
+        )?;
 
         info!(
             "通道 {} [Config]: 初始化 XFusionProtocol, 包含 {} 个节点",
@@ -829,15 +1243,94 @@ impl Protocol for XFusionProtocol {
             );
         }
 
-        Ok(Box::new(Self {
+        let agent_port = params
+            .get("agent_port")
+            .and_then(|v| v.as_u64())
+            .map(|p| p as u16);
+        let agent_token = params
+            .get("agent_token")
+            .and_then(|v| v.as_str())
+            .filter(|token| !token.is_empty())
+            .map(str::to_string);
+        if agent_port.is_some() && agent_token.is_none() {
+            return Err(DeviceError::ConfigError(
+                "配置 agent_port 时必须配置 agent_token（代理握手共享令牌）".into(),
+            ));
+        }
+
+        let stagger_ms = params
+            .get("stagger_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_STAGGER_MS);
+
+        let groups: HashMap<String, Vec<u32>> = match params.get("groups") {
+            Some(v) => serde_json::from_value(v.clone())
+                .map_err(|e| DeviceError::ConfigError(format!("groups 解析失败: {}", e)))?,
+            None => HashMap::new(),
+        };
+        for (name, ids) in &groups {
+            if let Some(id) = ids.iter().find(|id| !nodes.iter().any(|n| n.id == **id)) {
+                return Err(DeviceError::ConfigError(format!(
+                    "xFusion 分组 {} 引用了不存在的节点 {}",
+                    name, id
+                )));
+            }
+        }
+
+        let refresh_notify = nodes
+            .iter()
+            .map(|n| (n.id, Arc::new(Notify::new())))
+            .collect();
+
+        let tasks = TaskRegistry::new();
+        let agent_tasks = tasks.child();
+        let protocol = Self {
             channel_id,
             nodes,
             broadcast_addr,
             wol_port,
             shutdown_port,
-            http_client,
-            token_cache: Arc::new(RwLock::new(HashMap::new())),
-        }))
+            client: XFusionClient {
+                channel_id,
+                http_client,
+                token_cache: Arc::new(RwLock::new(HashMap::new())),
+                agents: AgentRegistry::new(),
+            },
+            poll_interval_ms,
+            cache_max_age_ms,
+            power_cache: Arc::new(RwLock::new(HashMap::new())),
+            refresh_notify: Arc::new(refresh_notify),
+            last_batch: Arc::new(RwLock::new(None)),
+            groups,
+            stagger_ms,
+            poll_gate: PollGate::new(),
+            tasks,
+            agent_port,
+            agent_token: agent_token.unwrap_or_default(),
+            agent_keepalive: KeepaliveConfig::from_params(params),
+            agent_tasks: std::sync::Mutex::new(agent_tasks),
+        };
+
+        // 启动 OS 代理长连接监听
+        protocol.start_agent_listener();
+
+        // 启动后台电源状态轮询
+        if poll_interval_ms > 0 {
+            info!(
+                "通道 {} [Config]: 启动电源状态轮询, 间隔 {}ms, 缓存有效期 {}ms",
+                channel_id, poll_interval_ms, cache_max_age_ms
+            );
+            protocol.start_power_pollers();
+        }
+
+        Ok(protocol)
+    }
+}
+
+#[async_trait]
+impl Protocol for XFusionProtocol {
+    fn from_config(channel_id: u32, params: &HashMap<String, Value>) -> Result<Box<dyn Protocol>> {
+        Ok(Box::new(Self::from_params(channel_id, params)?))
     }
 
     async fn execute(&mut self, command: &str, params: Value) -> Result<Value> {
@@ -1013,20 +1506,27 @@ impl Protocol for XFusionProtocol {
                     DeviceError::ProtocolError(format!("未找到 ID 为 {} 的节点", id))
                 })?;
 
-                // 通过 iBMC API 获取电源状态
-                let power_state = self.get_power_state(node).await.ok();
-                let is_powered_on = power_state
-                    .as_ref()
-                    .map(|s| s.eq_ignore_ascii_case("On"))
+                // 优先使用后台轮询缓存，refresh=true 时强制实时查询
+                let force_refresh = params
+                    .get("refresh")
+                    .and_then(|v| v.as_bool())
                     .unwrap_or(false);
+                let (snapshot, cached) = if force_refresh {
+                    (self.refresh_snapshot(node).await, false)
+                } else {
+                    self.get_snapshot(node).await
+                };
 
-                let (volume, mute, _) = self.get_audio_status(node).await;
                 Ok(serde_json::json!({
                     "id": id,
-                    "online": is_powered_on,
-                    "powerState": power_state,
-                    "volume": volume,
-                    "mute": mute,
+                    "online": snapshot.powered_on,
+                    "powerState": snapshot.power_state,
+                    "volume": snapshot.volume,
+                    "mute": snapshot.mute,
+                    "error": snapshot.error,
+                    "cached": cached,
+                    "updated_at": time::instant_rfc3339(snapshot.updated_at),
+                    "age_ms": snapshot.age_ms(),
                 }))
             }
             "getPowerState" => {
@@ -1042,7 +1542,7 @@ impl Protocol for XFusionProtocol {
                     DeviceError::ProtocolError(format!("未找到 ID 为 {} 的节点", id))
                 })?;
 
-                let power_state = self.get_power_state(node).await?;
+                let power_state = self.client.get_power_state(node).await?;
                 Ok(serde_json::json!({
                     "id": id,
                     "powerState": power_state,
@@ -1050,6 +1550,96 @@ impl Protocol for XFusionProtocol {
                 }))
             }
             "getAllStatus" => self.get_status().await,
+            "powerOnAll" | "powerOffAll" => {
+                let ids = self.resolve_batch_targets(&params)?;
+                let stagger_ms = params
+                    .get("stagger_ms")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(self.stagger_ms);
+                let stop_on_error = params
+                    .get("stop_on_error")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                let (ids, reset_type) = if command == "powerOnAll" {
+                    (ids, "On")
+                } else {
+                    let force = params
+                        .get("force")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    // 关机顺序与开机相反，除非显式指定 reverse=false
+                    let reverse = params
+                        .get("reverse")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true);
+                    let ids = if reverse {
+                        ids.into_iter().rev().collect()
+                    } else {
+                        ids
+                    };
+                    (
+                        ids,
+                        if force {
+                            "ForceOff"
+                        } else {
+                            "GracefulShutdown"
+                        },
+                    )
+                };
+
+                self.start_batch(&ids, reset_type, stagger_ms, stop_on_error)
+                    .await
+            }
+            "getBatchResult" => Ok(self.last_batch.read().await.clone().unwrap_or(Value::Null)),
+            "agentCommand" => {
+                let cmd = params
+                    .get("cmd")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| DeviceError::ProtocolError("agentCommand 需要 cmd 参数".into()))?
+                    .to_string();
+                let args = params.get("args").cloned().unwrap_or(Value::Null);
+                self.agent_command(&params, &cmd, args).await
+            }
+            "setVolume" => {
+                let volume = params
+                    .get("volume")
+                    .and_then(|v| v.as_i64())
+                    .ok_or_else(|| {
+                        DeviceError::ProtocolError("setVolume 需要 volume 参数".into())
+                    })?;
+                self.agent_command(
+                    &params,
+                    "setVolume",
+                    serde_json::json!({ "volume": volume }),
+                )
+                .await
+            }
+            "setMute" => {
+                let mute = params
+                    .get("mute")
+                    .and_then(|v| v.as_bool())
+                    .ok_or_else(|| DeviceError::ProtocolError("setMute 需要 mute 参数".into()))?;
+                self.agent_command(&params, "setMute", serde_json::json!({ "mute": mute }))
+                    .await
+            }
+            "launchApp" => {
+                let path = params
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| DeviceError::ProtocolError("launchApp 需要 path 参数".into()))?;
+                let args = params.get("args").cloned().unwrap_or(Value::Null);
+                self.agent_command(
+                    &params,
+                    "launchApp",
+                    serde_json::json!({ "path": path, "args": args }),
+                )
+                .await
+            }
+            "getGroups" => Ok(serde_json::json!({
+                "stagger_ms": self.stagger_ms,
+                "groups": self.groups,
+            })),
 
             _ => {
                 warn!("通道 {} [Execute]: 未知命令: {}", self.channel_id, command);
@@ -1067,10 +1657,12 @@ impl Protocol for XFusionProtocol {
         let mut status_list = Vec::new();
 
         for node in &self.nodes {
-            let is_online = self.is_node_online(node).await;
+            let (snapshot, cached) = self.get_snapshot(node).await;
+            let agent = self.client.agents.connection_info(node.id).await;
+            let is_online = snapshot.powered_on || agent.is_some() || self.heartbeat_alive(node);
             debug!(
-                "通道 {} [xFusion]: 节点 ID:{} 状态 - 在线: {}",
-                self.channel_id, node.id, is_online
+                "通道 {} [xFusion]: 节点 ID:{} 状态 - 在线: {}, 缓存: {}",
+                self.channel_id, node.id, is_online, cached
             );
 
             status_list.push(serde_json::json!({
@@ -1079,7 +1671,13 @@ impl Protocol for XFusionProtocol {
                 "ip": node.ip.map(|i| i.to_string()),
                 "port": node.port,
                 "online": is_online,
+                "powerState": snapshot.power_state,
                 "ibmc_url": node.ibmc_url,
+                "cached": cached,
+                "updated_at": time::instant_rfc3339(snapshot.updated_at),
+                "age_ms": snapshot.age_ms(),
+                "agent_connected": agent.is_some(),
+                "agent": agent,
             }));
         }
 
@@ -1149,15 +1747,19 @@ impl Protocol for XFusionProtocol {
             DeviceError::ProtocolError(format!("未找到 ID 为 {} 的节点", id))
         })?;
 
-        // 通过 iBMC API 获取电源状态
-        let is_powered_on = self.is_node_powered_on(node).await;
+        // 优先使用后台轮询缓存，缓存缺失或过期时通过 iBMC API 实时查询
+        let (snapshot, cached) = self.get_snapshot(node).await;
+        // 代理在线说明操作系统正在运行
+        let is_powered_on = snapshot.powered_on || self.client.agents.is_connected(node.id).await;
 
-        info!(
-            "通道 {} [Read]: 节点 ID:{} 电源状态: {} ({})",
+        debug!(
+            "通道 {} [Read]: 节点 ID:{} 电源状态: {} ({}), 缓存: {}, 缓存时长: {}ms",
             self.channel_id,
             id,
             if is_powered_on { "开机" } else { "关机" },
-            if is_powered_on { 1 } else { 0 }
+            if is_powered_on { 1 } else { 0 },
+            cached,
+            snapshot.age_ms()
         );
         Ok(if is_powered_on { 1 } else { 0 })
     }
@@ -1165,4 +1767,226 @@ impl Protocol for XFusionProtocol {
     fn name(&self) -> &str {
         "xFusion"
     }
+
+    async fn call_method(&mut self, method_name: &str, args: Value) -> Result<Value> {
+        self.execute(method_name, args).await
+    }
+
+    fn get_methods(&self) -> Vec<String> {
+        vec![
+            "powerOnAll".to_string(),
+            "powerOffAll".to_string(),
+            "getGroups".to_string(),
+            "getBatchResult".to_string(),
+            "agentCommand".to_string(),
+            "setVolume".to_string(),
+            "setMute".to_string(),
+            "launchApp".to_string(),
+        ]
+    }
+
+    fn set_polling_paused(&self, paused: bool) {
+        self.poll_gate.set_paused(paused);
+    }
+
+    async fn shutdown(&self) {
+        self.release().await;
+        self.tasks.shutdown().await;
+    }
+
+    async fn release(&self) {
+        let agent_tasks = self.agent_tasks.lock().unwrap().clone();
+        agent_tasks.shutdown().await;
+        self.client.agents.close_all().await;
+    }
+
+    async fn resume(&self) {
+        *self.agent_tasks.lock().unwrap() = self.tasks.child();
+        self.start_agent_listener();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_peer::{http_request_complete, MockPeer};
+
+    /// 模拟 iBMC：SystemID 为 `fail` 的节点电源操作返回 500，其余返回 200
+    async fn mock_ibmc() -> MockPeer {
+        MockPeer::start_framed(http_request_complete, |request| {
+            let request = String::from_utf8_lossy(request);
+            let request_line = request.lines().next().unwrap_or_default();
+            let status = if request_line.contains("/Systems/fail/") {
+                "500 Internal Server Error"
+            } else {
+                "200 OK"
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}",
+                status
+            );
+            vec![response.into_bytes()]
+        })
+        .await
+    }
+
+    /// 创建三节点协议实例（节点 2 的电源操作失败），并预置会话 Token
+    async fn protocol(ibmc_url: &str) -> XFusionProtocol {
+        let node = |id: u32, system_id: &str| {
+            serde_json::json!({
+                "id": id,
+                "mac": format!("00:11:22:33:44:{:02x}", id),
+                "ibmc_url": ibmc_url,
+                "ibmc_username": "admin",
+                "ibmc_password": "secret",
+                "system_id": system_id,
+            })
+        };
+        let params: HashMap<String, Value> = serde_json::from_value(serde_json::json!({
+            "nodes": [node(1, "1"), node(2, "fail"), node(3, "1")],
+            "groups": { "front": [3, 1] },
+            "poll_interval_ms": 0,
+            "stagger_ms": 0,
+        }))
+        .unwrap();
+        let protocol = XFusionProtocol::from_params(90, &params).unwrap();
+        for id in [1, 2, 3] {
+            protocol
+                .client
+                .token_cache
+                .write()
+                .await
+                .insert(id, "token".into());
+        }
+        protocol
+    }
+
+    fn result_ids(value: &Value) -> Vec<u64> {
+        value["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_u64().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_batch_targets_follow_ids_then_group_then_all() {
+        let protocol = protocol("http://127.0.0.1:1").await;
+
+        let ids = protocol
+            .resolve_batch_targets(&serde_json::json!({ "ids": [2, 1], "group": "front" }))
+            .unwrap();
+        assert_eq!(ids, vec![2, 1]);
+        let group = protocol
+            .resolve_batch_targets(&serde_json::json!({ "group": "front" }))
+            .unwrap();
+        assert_eq!(group, vec![3, 1]);
+        let all = protocol
+            .resolve_batch_targets(&serde_json::json!({}))
+            .unwrap();
+        assert_eq!(all, vec![1, 2, 3]);
+
+        // 所有无效或未配置的 ID 都列在错误中，不会被静默丢弃
+        let error = protocol
+            .resolve_batch_targets(&serde_json::json!({ "ids": [1, 9, "x"] }))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(r#"[9,"x"]"#), "{}", error);
+        assert!(protocol
+            .resolve_batch_targets(&serde_json::json!({ "group": "rear" }))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_power_reports_each_node() {
+        let ibmc = mock_ibmc().await;
+        let protocol = protocol(&ibmc.url()).await;
+
+        let report = protocol
+            .power_actions()
+            .batch(&protocol.nodes, "On", 0, false)
+            .await;
+        assert_eq!(report["action"], "On");
+        assert_eq!(report["total"], 3);
+        assert_eq!(report["success"], 2);
+        assert_eq!(report["failed"], 1);
+        assert_eq!(result_ids(&report), vec![1, 2, 3]);
+        assert_eq!(report["results"][0]["success"], true);
+        assert_eq!(report["results"][1]["success"], false);
+        assert!(report["results"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("500"));
+        assert_eq!(report["results"][2]["success"], true);
+        assert_eq!(ibmc.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_power_stops_after_failure_when_requested() {
+        let ibmc = mock_ibmc().await;
+        let protocol = protocol(&ibmc.url()).await;
+
+        let report = protocol
+            .power_actions()
+            .batch(&protocol.nodes, "On", 0, true)
+            .await;
+        assert_eq!(report["success"], 1);
+        assert_eq!(report["failed"], 2);
+        assert_eq!(report["results"][2]["skipped"], true);
+        // 被跳过的节点不会发出请求
+        assert_eq!(ibmc.requests().len(), 2);
+    }
+
+    /// 等待后台批量操作完成并返回报告
+    async fn batch_result(protocol: &mut XFusionProtocol) -> Value {
+        for _ in 0..100 {
+            let report = protocol
+                .execute("getBatchResult", Value::Null)
+                .await
+                .unwrap();
+            if report["running"] == false {
+                return report;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("批量操作未完成");
+    }
+
+    #[tokio::test]
+    async fn test_power_off_all_runs_in_reverse_order() {
+        let ibmc = mock_ibmc().await;
+        let mut protocol = protocol(&ibmc.url()).await;
+
+        // 命令立即返回，批量操作在后台执行
+        let started = protocol
+            .execute("powerOffAll", serde_json::json!({ "group": "front" }))
+            .await
+            .unwrap();
+        assert_eq!(started["running"], true);
+        assert_eq!(started["ids"], serde_json::json!([1, 3]));
+        let report = batch_result(&mut protocol).await;
+        assert_eq!(report["action"], "GracefulShutdown");
+        assert_eq!(result_ids(&report), vec![1, 3]);
+
+        protocol
+            .execute(
+                "powerOffAll",
+                serde_json::json!({ "ids": [1, 3], "force": true, "reverse": false }),
+            )
+            .await
+            .unwrap();
+        let report = batch_result(&mut protocol).await;
+        assert_eq!(report["action"], "ForceOff");
+        assert_eq!(result_ids(&report), vec![1, 3]);
+        assert_eq!(ibmc.requests().len(), 4);
+
+        // 错峰等待期间不允许启动第二个批量操作
+        protocol
+            .execute("powerOnAll", serde_json::json!({ "stagger_ms": 200 }))
+            .await
+            .unwrap();
+        assert!(protocol.execute("powerOffAll", Value::Null).await.is_err());
+        assert_eq!(batch_result(&mut protocol).await["total"], 3);
+    }
 }
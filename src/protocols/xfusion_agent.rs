@@ -0,0 +1,568 @@
+//! xFusion OS 代理长连接
+//!
+//! 服务器上运行的代理程序主动连接控制器的 `agent_port`，之后控制器通过该连接
+//! 下发命令（音量、静音、启动程序等）。报文为按行分隔的 JSON：
+//!
+//! - 代理握手: `{"type": "hello", "mac": "00:11:22:33:44:55", "token": "..."}` 或
+//!   `{"type": "hello", "id": 101, "token": "..."}`，`token` 须与通道 `agent_token` 一致
+//! - 控制器请求: `{"seq": 1, "cmd": "setVolume", "args": {"volume": 50}}`
+//! - 代理响应: `{"seq": 1, "ok": true, "result": {...}}` / `{"seq": 1, "ok": false, "error": "..."}`
+//!
+//! 握手令牌校验通过后才会替换同一节点的已有连接；单行报文超过 [`MAX_LINE_BYTES`] 时断开连接。
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::utils::net::KeepaliveConfig;
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::{time, DeviceError, Result};
+
+/// 握手超时时间
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 单行报文最大字节数（不含换行符）
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// 监听端口失败后的重试间隔
+const BIND_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 单个代理连接
+struct AgentConnection {
+    /// 连接序号，用于断开时避免误删新连接
+    serial: u64,
+    peer: SocketAddr,
+    connected_at: Instant,
+    tx: mpsc::UnboundedSender<String>,
+    pending: Arc<Mutex<HashMap<u64, oneshot::Sender<Value>>>>,
+}
+
+/// 代理连接注册表 (node_id -> 连接)
+#[derive(Clone, Default)]
+pub(crate) struct AgentRegistry {
+    connections: Arc<RwLock<HashMap<u32, AgentConnection>>>,
+    next_seq: Arc<AtomicU64>,
+}
+
+impl AgentRegistry {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// 节点代理是否在线
+    pub(crate) async fn is_connected(&self, node_id: u32) -> bool {
+        self.connections.read().await.contains_key(&node_id)
+    }
+
+    /// 节点代理连接信息
+    pub(crate) async fn connection_info(&self, node_id: u32) -> Option<Value> {
+        self.connections.read().await.get(&node_id).map(|c| {
+            serde_json::json!({
+                "peer": c.peer.to_string(),
+                "connected_at": time::instant_rfc3339(c.connected_at),
+                "connected_secs": c.connected_at.elapsed().as_secs(),
+            })
+        })
+    }
+
+    /// 向节点代理发送命令并等待响应
+    pub(crate) async fn request(
+        &self,
+        node_id: u32,
+        cmd: &str,
+        args: Value,
+        timeout: Duration,
+    ) -> Result<Value> {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let (resp_tx, resp_rx) = oneshot::channel();
+
+        {
+            let connections = self.connections.read().await;
+            let conn = connections.get(&node_id).ok_or_else(|| {
+                DeviceError::ConnectionError(format!("节点 {} 的代理未连接", node_id))
+            })?;
+
+            conn.pending.lock().await.insert(seq, resp_tx);
+
+            let line = serde_json::json!({ "seq": seq, "cmd": cmd, "args": args }).to_string();
+            if conn.tx.send(line).is_err() {
+                conn.pending.lock().await.remove(&seq);
+                return Err(DeviceError::ConnectionError(format!(
+                    "节点 {} 的代理连接已关闭",
+                    node_id
+                )));
+            }
+        }
+
+        let response = match tokio::time::timeout(timeout, resp_rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(DeviceError::ConnectionError(format!(
+                    "节点 {} 的代理连接在响应前断开",
+                    node_id
+                )))
+            }
+            Err(_) => {
+                if let Some(conn) = self.connections.read().await.get(&node_id) {
+                    conn.pending.lock().await.remove(&seq);
+                }
+                return Err(DeviceError::Timeout);
+            }
+        };
+
+        if response
+            .get("ok")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            Ok(response.get("result").cloned().unwrap_or(Value::Null))
+        } else {
+            let error = response
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("未知错误");
+            Err(DeviceError::ProtocolError(format!(
+                "代理命令 '{}' 执行失败: {}",
+                cmd, error
+            )))
+        }
+    }
+
+    /// 启动代理监听
+    ///
+    /// 握手报文的 `token` 须与 `token` 一致，`resolve` 根据握手报文返回对应的节点 ID。监听与连接任务注册到 `tasks`，
+    /// 端口被占用（如热重载时旧监听尚未释放）时每隔 [`BIND_RETRY_INTERVAL`] 重试。
+    /// 代理连接开启 TCP 保活，服务器断电后的半开连接由内核探测后关闭。
+    pub(crate) fn start_listener<F>(
+        &self,
+        channel_id: u32,
+        port: u16,
+        keepalive: KeepaliveConfig,
+        token: &str,
+        tasks: &TaskRegistry,
+        resolve: F,
+    ) where
+        F: Fn(&Value) -> Option<u32> + Send + Sync + 'static,
+    {
+        let registry = self.clone();
+        let resolve = Arc::new(resolve);
+        let token: Arc<str> = Arc::from(token);
+        let conn_tasks = tasks.clone();
+
+        let name = format!("xfusion:{}:agent_listener", channel_id);
+
+        tasks.spawn(name, async move {
+            let listener = loop {
+                match crate::utils::net::bind_tcp_dual_stack(port).and_then(TcpListener::from_std) {
+                    Ok(l) => break l,
+                    Err(e) => {
+                        warn!(
+                            "通道 {} [xFusion Agent]: 监听端口 {} 失败: {}，{} 秒后重试",
+                            channel_id,
+                            port,
+                            e,
+                            BIND_RETRY_INTERVAL.as_secs()
+                        );
+                        tokio::time::sleep(BIND_RETRY_INTERVAL).await;
+                    }
+                }
+            };
+            info!(
+                "通道 {} [xFusion Agent]: 代理监听已启动, 端口 {}",
+                channel_id, port
+            );
+
+            let serial = AtomicU64::new(1);
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        warn!("通道 {} [xFusion Agent]: 接受连接失败: {}", channel_id, e);
+                        continue;
+                    }
+                };
+                if let Err(e) = keepalive.apply(&stream) {
+                    debug!(
+                        "通道 {} [xFusion Agent]: 连接 {} 开启保活失败: {}",
+                        channel_id, peer, e
+                    );
+                }
+
+                let registry = registry.clone();
+                let resolve = resolve.clone();
+                let token = token.clone();
+                let serial = serial.fetch_add(1, Ordering::Relaxed);
+                let name = format!("xfusion:{}:agent:{}", channel_id, peer);
+                conn_tasks.spawn(name, async move {
+                    if let Err(e) = registry
+                        .handle_connection(
+                            channel_id,
+                            stream,
+                            peer,
+                            serial,
+                            &token,
+                            resolve.as_ref(),
+                        )
+                        .await
+                    {
+                        debug!(
+                            "通道 {} [xFusion Agent]: 连接 {} 结束: {}",
+                            channel_id, peer, e
+                        );
+                    }
+                });
+            }
+        });
+    }
+
+    /// 断开全部代理连接（通道关闭时调用）
+    pub(crate) async fn close_all(&self) {
+        self.connections.write().await.clear();
+    }
+
+    /// 处理单个代理连接：握手、注册、收发
+    async fn handle_connection(
+        &self,
+        channel_id: u32,
+        stream: TcpStream,
+        peer: SocketAddr,
+        serial: u64,
+        token: &str,
+        resolve: &(dyn Fn(&Value) -> Option<u32> + Send + Sync),
+    ) -> Result<()> {
+        let _ = stream.set_nodelay(true);
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        // 握手：令牌校验通过前不触碰已有连接
+        let hello = match tokio::time::timeout(HELLO_TIMEOUT, read_line(&mut reader)).await {
+            Ok(Ok(Some(line))) => serde_json::from_str::<Value>(&line)?,
+            Ok(Ok(None)) => return Err(DeviceError::ConnectionError("握手前连接关闭".into())),
+            Ok(Err(e)) => return Err(e),
+            Err(_) => return Err(DeviceError::Timeout),
+        };
+        if hello.get("token").and_then(|v| v.as_str()) != Some(token) {
+            warn!(
+                "通道 {} [xFusion Agent]: 来自 {} 的握手令牌无效，拒绝连接",
+                channel_id, peer
+            );
+            return Err(DeviceError::ProtocolError("握手令牌无效".into()));
+        }
+        let node_id = resolve(&hello).ok_or_else(|| {
+            warn!(
+                "通道 {} [xFusion Agent]: 无法识别来自 {} 的握手: {}",
+                channel_id, peer, hello
+            );
+            DeviceError::ProtocolError("握手报文未匹配任何节点".into())
+        })?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let pending: Arc<Mutex<HashMap<u64, oneshot::Sender<Value>>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+
+        // 新连接替换旧连接
+        if let Some(old) = self.connections.write().await.insert(
+            node_id,
+            AgentConnection {
+                serial,
+                peer,
+                connected_at: Instant::now(),
+                tx,
+                pending: pending.clone(),
+            },
+        ) {
+            info!(
+                "通道 {} [xFusion Agent]: 节点 ID:{} 代理重新连接, 替换旧连接 {}",
+                channel_id, node_id, old.peer
+            );
+        }
+        info!(
+            "通道 {} [xFusion Agent]: 节点 ID:{} 代理已连接 ({})",
+            channel_id, node_id, peer
+        );
+
+        let writer_task = tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                if writer.write_all(line.as_bytes()).await.is_err()
+                    || writer.write_all(b"\n").await.is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let result = loop {
+            match read_line(&mut reader).await {
+                Ok(Some(line)) => {
+                    let msg: Value = match serde_json::from_str(&line) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            debug!(
+                                "通道 {} [xFusion Agent]: 节点 ID:{} 无效报文: {} ({})",
+                                channel_id, node_id, line, e
+                            );
+                            continue;
+                        }
+                    };
+                    if let Some(seq) = msg.get("seq").and_then(|v| v.as_u64()) {
+                        if let Some(waiter) = pending.lock().await.remove(&seq) {
+                            let _ = waiter.send(msg);
+                        }
+                    }
+                }
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+
+        writer_task.abort();
+        {
+            let mut connections = self.connections.write().await;
+            if connections.get(&node_id).map(|c| c.serial) == Some(serial) {
+                connections.remove(&node_id);
+            }
+        }
+        info!(
+            "通道 {} [xFusion Agent]: 节点 ID:{} 代理已断开 ({})",
+            channel_id, node_id, peer
+        );
+
+        result
+    }
+}
+
+/// 读取一行报文（不含换行符），连接关闭时返回 None，超过 [`MAX_LINE_BYTES`] 时返回错误
+async fn read_line<R>(reader: &mut R) -> Result<Option<String>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = String::new();
+    let read = reader
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_line(&mut line)
+        .await?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') && line.len() > MAX_LINE_BYTES {
+        return Err(DeviceError::ProtocolError(format!(
+            "报文超过 {} 字节",
+            MAX_LINE_BYTES
+        )));
+    }
+    let trimmed = line.trim_end_matches(['\r', '\n']).len();
+    line.truncate(trimmed);
+    Ok(Some(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "secret";
+
+    /// 启动一个注册表和已完成握手的代理连接，返回代理侧读写端
+    async fn connect_agent(
+        registry: &AgentRegistry,
+        hello: Value,
+    ) -> (
+        tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        tokio::net::tcp::OwnedWriteHalf,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = registry.clone();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            let resolve =
+                |hello: &Value| hello.get("id").and_then(|v| v.as_u64()).map(|v| v as u32);
+            let _ = server
+                .handle_connection(1, stream, peer, 1, TOKEN, &resolve)
+                .await;
+        });
+
+        let (reader, mut writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        writer
+            .write_all(format!("{}\n", hello).as_bytes())
+            .await
+            .unwrap();
+        for _ in 0..100 {
+            if registry.is_connected(101).await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        (BufReader::new(reader).lines(), writer)
+    }
+
+    #[tokio::test]
+    async fn request_round_trips_over_agent_connection() {
+        let registry = AgentRegistry::new();
+        let (mut lines, mut writer) = connect_agent(
+            &registry,
+            serde_json::json!({ "type": "hello", "id": 101, "token": TOKEN }),
+        )
+        .await;
+        assert!(registry.is_connected(101).await);
+
+        let client = registry.clone();
+        let request = tokio::spawn(async move {
+            client
+                .request(
+                    101,
+                    "setVolume",
+                    serde_json::json!({ "volume": 50 }),
+                    Duration::from_secs(2),
+                )
+                .await
+        });
+
+        // 控制器请求为单行 JSON: {"seq", "cmd", "args"}
+        let line = lines.next_line().await.unwrap().unwrap();
+        let frame: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(frame["cmd"], "setVolume");
+        assert_eq!(frame["args"], serde_json::json!({ "volume": 50 }));
+        let seq = frame["seq"].as_u64().unwrap();
+
+        // 序号不匹配的响应被忽略，不影响后续匹配
+        let stray = serde_json::json!({ "seq": seq + 1000, "ok": true, "result": "stray" });
+        let reply = serde_json::json!({ "seq": seq, "ok": true, "result": { "volume": 50 } });
+        writer
+            .write_all(format!("not json\n{}\n{}\n", stray, reply).as_bytes())
+            .await
+            .unwrap();
+
+        let result = request.await.unwrap().unwrap();
+        assert_eq!(result, serde_json::json!({ "volume": 50 }));
+    }
+
+    #[tokio::test]
+    async fn agent_error_response_becomes_protocol_error() {
+        let registry = AgentRegistry::new();
+        let (mut lines, mut writer) = connect_agent(
+            &registry,
+            serde_json::json!({ "type": "hello", "id": 101, "token": TOKEN }),
+        )
+        .await;
+
+        let client = registry.clone();
+        let request = tokio::spawn(async move {
+            client
+                .request(101, "launchApp", Value::Null, Duration::from_secs(2))
+                .await
+        });
+
+        let frame: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        let reply = serde_json::json!({ "seq": frame["seq"], "ok": false, "error": "not found" });
+        writer
+            .write_all(format!("{}\n", reply).as_bytes())
+            .await
+            .unwrap();
+
+        match request.await.unwrap() {
+            Err(DeviceError::ProtocolError(msg)) => {
+                assert!(
+                    msg.contains("launchApp") && msg.contains("not found"),
+                    "{}",
+                    msg
+                )
+            }
+            other => panic!("应返回协议错误: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_node_and_disconnect_are_reported() {
+        let registry = AgentRegistry::new();
+        assert!(matches!(
+            registry
+                .request(101, "setMute", Value::Null, Duration::from_millis(100))
+                .await,
+            Err(DeviceError::ConnectionError(_))
+        ));
+
+        let (lines, writer) = connect_agent(
+            &registry,
+            serde_json::json!({ "type": "hello", "id": 101, "token": TOKEN }),
+        )
+        .await;
+        assert!(registry.is_connected(101).await);
+
+        // 代理断开后注册表移除该节点
+        drop(writer);
+        drop(lines);
+        for _ in 0..100 {
+            if !registry.is_connected(101).await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!registry.is_connected(101).await);
+    }
+
+    #[tokio::test]
+    async fn rejects_unauthenticated_takeover_and_oversized_lines() {
+        let registry = AgentRegistry::new();
+        let (_lines, mut writer) = connect_agent(
+            &registry,
+            serde_json::json!({ "type": "hello", "id": 101, "token": TOKEN }),
+        )
+        .await;
+        let serial = |registry: &AgentRegistry| {
+            let registry = registry.clone();
+            async move {
+                registry
+                    .connections
+                    .read()
+                    .await
+                    .get(&101)
+                    .map(|c| c.serial)
+            }
+        };
+        assert_eq!(serial(&registry).await, Some(1));
+
+        // 令牌错误的握手不能替换在线连接
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = registry.clone();
+        let intruder = tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            let resolve = |_: &Value| Some(101);
+            server
+                .handle_connection(1, stream, peer, 2, TOKEN, &resolve)
+                .await
+        });
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"{\"type\": \"hello\", \"id\": 101, \"token\": \"guess\"}\n")
+            .await
+            .unwrap();
+        assert!(matches!(
+            intruder.await.unwrap(),
+            Err(DeviceError::ProtocolError(_))
+        ));
+        assert_eq!(serial(&registry).await, Some(1));
+
+        // 超长报文断开连接
+        writer
+            .write_all(&vec![b'x'; MAX_LINE_BYTES + 1])
+            .await
+            .unwrap();
+        for _ in 0..100 {
+            if !registry.is_connected(101).await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!registry.is_connected(101).await);
+    }
+}
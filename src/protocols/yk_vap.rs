@@ -1,30 +1,130 @@
 use async_trait::async_trait;
+use serde::Serialize;
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info, trace, warn};
 
-use crate::protocols::Protocol;
-use crate::utils::{DeviceError, Result};
+use crate::protocols::{PollGate, Protocol};
+use crate::utils::tasks::TaskRegistry;
+use crate::utils::{dns, net, time, DeviceError, Result};
 
 /// YK-VAP（文本协议）
 ///
-/// 目前实现的核心指令：
+/// 已实现的指令：
 /// - CALL: 调用场景/读取窗口列表（scene_id=0）
 /// - RSCS: 读取当前场景
+/// - SWCH: 窗口信号源切换
+/// - AMTX: 音频矩阵路由（输入 -> 输出）
+/// - PRST: 调用预设
 ///
 /// 报文格式示例：
 /// - <CALL,1> / <CALL,1,2>
 /// - <RSCS,1> / <RSCS,1,2>
+/// - <SWCH,W_ID,Channel,SubChannel> / <SWCH,W_ID,Channel,SubChannel,group>
+/// - <AMTX,Input,Output>
+/// - <PRST,1> / <PRST,1,2>
+///
+/// 所有指令均以 <X,OK> 结束。
 ///
 /// 注意：该协议是按行读取（以 \n 结尾）。
 pub struct YkVapProtocol {
+    channel_id: u32,
+    link: YkVapLink,
+    /// 轮询读取当前场景使用的拼接墙序号
+    wall_index: u64,
+    /// 状态缓存
+    status: Arc<RwLock<YkVapStatus>>,
+    /// 状态轮询暂停开关
+    poll_gate: PollGate,
+    /// 后台任务
+    tasks: TaskRegistry,
+}
+
+/// 节点 ID 映射
+/// - 1: 当前场景
+/// - 2: 在线状态 (1/0)
+/// - 101-199: 窗口 (id-100) 当前信号源通道
+/// - 201-299: 音频输出 (id-200) 当前路由的输入
+const NODE_SCENE: u32 = 1;
+const NODE_ONLINE: u32 = 2;
+const NODE_WINDOW_BASE: u32 = 100;
+const NODE_AUDIO_BASE: u32 = 200;
+
+const DEFAULT_POLL_INTERVAL_MS: u64 = 10000;
+
+/// 窗口信息 <CALL,W_ID,Channel,x0,y0,x1,y1,SubChannel>
+#[derive(Debug, Clone, Serialize)]
+struct YkVapWindow {
+    w_id: u32,
+    channel: u32,
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+    sub_channel: u32,
+}
+
+impl YkVapWindow {
+    fn parse(a: &[String]) -> Option<Self> {
+        if a.len() != 7 {
+            return None;
+        }
+        Some(Self {
+            w_id: a[0].parse().ok()?,
+            channel: a[1].parse().ok()?,
+            x0: a[2].parse().ok()?,
+            y0: a[3].parse().ok()?,
+            x1: a[4].parse().ok()?,
+            y1: a[5].parse().ok()?,
+            sub_channel: a[6].parse().ok()?,
+        })
+    }
+}
+
+/// 设备状态缓存（后台轮询刷新，控制命令成功后同步更新）
+#[derive(Debug, Default)]
+struct YkVapStatus {
+    online: bool,
+    current_scene: Option<u64>,
+    current_preset: Option<u64>,
+    windows: BTreeMap<u32, YkVapWindow>,
+    /// 音频输出 -> 输入
+    audio_routes: BTreeMap<u32, u32>,
+    last_error: Option<String>,
+    updated_at: Option<Instant>,
+}
+
+impl YkVapStatus {
+    fn to_json(&self) -> Value {
+        let windows: Vec<&YkVapWindow> = self.windows.values().collect();
+        json!({
+            "online": self.online,
+            "current_scene": self.current_scene,
+            "current_preset": self.current_preset,
+            "windows": windows,
+            "audio_routes": self.audio_routes,
+            "last_error": self.last_error,
+            "updated_at": self.updated_at.map(time::instant_rfc3339),
+            "age_ms": self.updated_at.map(|t| t.elapsed().as_millis() as u64),
+        })
+    }
+}
+
+/// 通信链路（可克隆，供后台轮询任务使用）
+#[derive(Clone)]
+struct YkVapLink {
     channel_id: u32,
     addr: String,
     port: u16,
-    timeout: std::time::Duration,
+    timeout: Duration,
     transport: YkVapTransport,
+    /// 串行化收发，避免轮询与控制命令交错
+    io_lock: Arc<Mutex<()>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -33,7 +133,7 @@ enum YkVapTransport {
     Udp,
 }
 
-impl YkVapProtocol {
+impl YkVapLink {
     fn build_frame(cmd: &str, args: &[String]) -> String {
         // 添加换行符，部分设备需要以 \n 结尾才会响应
         let frame = if args.is_empty() {
@@ -65,10 +165,11 @@ impl YkVapProtocol {
     }
 
     async fn connect_tcp(&self) -> Result<TcpStream> {
-        let addr = format!("{}:{}", self.addr, self.port);
+        let addr = net::display_addr(&self.addr, self.port);
         debug!("[channel {}] TCP 正在连接 {}...", self.channel_id, addr);
 
-        match tokio::time::timeout(self.timeout, TcpStream::connect(&addr)).await {
+        let socket_addr = dns::resolve(&self.addr, self.port).await?;
+        match tokio::time::timeout(self.timeout, TcpStream::connect(socket_addr)).await {
             Ok(Ok(stream)) => {
                 // 禁用 Nagle 算法，确保小数据包立即发送
                 if let Err(e) = stream.set_nodelay(true) {
@@ -92,10 +193,11 @@ impl YkVapProtocol {
     }
 
     async fn connect_udp(&self) -> Result<UdpSocket> {
-        let addr = format!("{}:{}", self.addr, self.port);
+        let addr = net::display_addr(&self.addr, self.port);
+        let socket_addr = dns::resolve(&self.addr, self.port).await?;
         debug!("[channel {}] UDP 正在绑定本地端口...", self.channel_id);
 
-        let socket = match UdpSocket::bind("0.0.0.0:0").await {
+        let socket = match UdpSocket::bind(net::unspecified_for(&socket_addr, 0)).await {
             Ok(s) => {
                 debug!(
                     "[channel {}] UDP 绑定成功 local={:?}",
@@ -111,7 +213,7 @@ impl YkVapProtocol {
         };
 
         debug!("[channel {}] UDP 正在连接 {}...", self.channel_id, addr);
-        match socket.connect(&addr).await {
+        match socket.connect(socket_addr).await {
             Ok(_) => {
                 info!("[channel {}] UDP 连接成功 {}", self.channel_id, addr);
                 Ok(socket)
@@ -135,6 +237,7 @@ impl YkVapProtocol {
             "[channel {}] 准备发送帧: transport={:?}, expected_cmd={}",
             self.channel_id, self.transport, expected_cmd
         );
+        let _guard = self.io_lock.lock().await;
         let result = match self.transport {
             YkVapTransport::Tcp => self.send_and_read_lines_tcp(frame, expected_cmd).await,
             YkVapTransport::Udp => self.send_and_read_lines_udp(frame, expected_cmd).await,
@@ -417,6 +520,161 @@ impl YkVapProtocol {
             }
         }
     }
+
+    /// 发送指令，要求以 <X,OK> 结束，返回 OK 之前的数据帧参数
+    async fn command(&self, cmd: &str, args: &[String]) -> Result<Vec<Vec<String>>> {
+        let frame = Self::build_frame(cmd, args);
+        let frames = self.send_and_read_lines(&frame, cmd).await?;
+
+        let mut ok = false;
+        let mut data = Vec::new();
+        for (_cmd, a) in frames {
+            if a.len() == 1 && a[0].eq_ignore_ascii_case("OK") {
+                ok = true;
+            } else {
+                data.push(a);
+            }
+        }
+
+        if !ok {
+            error!("[channel {}] {} 未收到 OK 结束帧", self.channel_id, cmd);
+            return Err(DeviceError::ProtocolError(format!(
+                "未收到 <{},OK> 结束帧",
+                cmd
+            )));
+        }
+        Ok(data)
+    }
+
+    /// 读取当前场景 (RSCS)
+    async fn query_scene(&self, wall_index: u64) -> Result<Option<u64>> {
+        let data = self.command("RSCS", &[wall_index.to_string()]).await?;
+        Ok(data
+            .iter()
+            .find(|a| a.len() == 1)
+            .and_then(|a| a[0].parse::<u64>().ok()))
+    }
+
+    /// 读取窗口列表 (CALL,0)
+    async fn query_windows(&self) -> Result<Vec<YkVapWindow>> {
+        let data = self.command("CALL", &["0".to_string()]).await?;
+        Ok(data.iter().filter_map(|a| YkVapWindow::parse(a)).collect())
+    }
+
+    /// 刷新状态缓存
+    async fn refresh_status(&self, wall_index: u64, status: &RwLock<YkVapStatus>) -> Result<()> {
+        let result = async {
+            let scene = self.query_scene(wall_index).await?;
+            let windows = self.query_windows().await?;
+            Ok::<_, DeviceError>((scene, windows))
+        }
+        .await;
+
+        let mut st = status.write().await;
+        match result {
+            Ok((scene, windows)) => {
+                if !st.online {
+                    info!("[channel {}] YK-VAP 设备在线", self.channel_id);
+                }
+                st.online = true;
+                st.current_scene = scene;
+                st.windows = windows.into_iter().map(|w| (w.w_id, w)).collect();
+                st.last_error = None;
+                st.updated_at = Some(Instant::now());
+                Ok(())
+            }
+            Err(e) => {
+                if st.online {
+                    warn!("[channel {}] YK-VAP 设备离线: {}", self.channel_id, e);
+                }
+                st.online = false;
+                st.last_error = Some(e.to_string());
+                st.updated_at = Some(Instant::now());
+                Err(e)
+            }
+        }
+    }
+}
+
+impl YkVapProtocol {
+    /// 启动后台状态轮询
+    fn start_status_poller(&self, interval_ms: u64) {
+        let link = self.link.clone();
+        let wall_index = self.wall_index;
+        let status = self.status.clone();
+        let poll_gate = self.poll_gate.clone();
+        let name = format!("yk_vap:{}:status", link.channel_id);
+
+        self.tasks.spawn(name, async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+            loop {
+                interval.tick().await;
+                if poll_gate.is_paused() {
+                    continue;
+                }
+                if let Err(e) = link.refresh_status(wall_index, &status).await {
+                    debug!("[channel {}] YK-VAP 状态轮询失败: {}", link.channel_id, e);
+                }
+            }
+        });
+    }
+
+    /// 调用场景并同步缓存
+    async fn call_scene(&self, scene_id: u64, group: Option<u64>) -> Result<Vec<Vec<String>>> {
+        let mut args = vec![scene_id.to_string()];
+        if let Some(g) = group {
+            args.push(g.to_string());
+        }
+        let data = self.link.command("CALL", &args).await?;
+        if scene_id != 0 {
+            let mut st = self.status.write().await;
+            st.current_scene = Some(scene_id);
+            st.current_preset = None;
+        }
+        Ok(data)
+    }
+
+    /// 窗口信号源切换 (SWCH)
+    async fn switch_source(
+        &self,
+        window: u32,
+        channel: u32,
+        sub_channel: u32,
+        group: Option<u64>,
+    ) -> Result<()> {
+        let mut args = vec![
+            window.to_string(),
+            channel.to_string(),
+            sub_channel.to_string(),
+        ];
+        if let Some(g) = group {
+            args.push(g.to_string());
+        }
+        self.link.command("SWCH", &args).await?;
+
+        let mut st = self.status.write().await;
+        if let Some(w) = st.windows.get_mut(&window) {
+            w.channel = channel;
+            w.sub_channel = sub_channel;
+        }
+        Ok(())
+    }
+
+    /// 音频矩阵路由 (AMTX)
+    async fn audio_route(&self, input: u32, output: u32) -> Result<()> {
+        self.link
+            .command("AMTX", &[input.to_string(), output.to_string()])
+            .await?;
+        self.status.write().await.audio_routes.insert(output, input);
+        Ok(())
+    }
+
+    fn param_u32(params: &Value, keys: &[&str]) -> Result<u32> {
+        keys.iter()
+            .find_map(|k| params.get(*k).and_then(|v| v.as_u64()))
+            .map(|v| v as u32)
+            .ok_or_else(|| DeviceError::Other(format!("缺少 {} 参数", keys[0])))
+    }
 }
 
 #[async_trait]
@@ -459,18 +717,44 @@ impl Protocol for YkVapProtocol {
             .and_then(|v| v.as_u64())
             .unwrap_or(3000);
 
+        let wall_index = params
+            .get("wall_index")
+            .or_else(|| params.get("wallIndex"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1);
+
+        // 0 表示禁用后台轮询
+        let poll_interval_ms = params
+            .get("poll_interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_POLL_INTERVAL_MS);
+
         info!(
-            "YK-VAP 协议初始化: channel_id={}, addr={}:{}, transport={:?}, timeout={}ms",
-            channel_id, addr, port, transport, timeout_ms
+            "YK-VAP 协议初始化: channel_id={}, addr={}:{}, transport={:?}, timeout={}ms, poll={}ms",
+            channel_id, addr, port, transport, timeout_ms, poll_interval_ms
         );
 
-        Ok(Box::new(Self {
+        let protocol = Self {
             channel_id,
-            addr,
-            port,
-            timeout: std::time::Duration::from_millis(timeout_ms),
-            transport,
-        }))
+            link: YkVapLink {
+                channel_id,
+                addr,
+                port,
+                timeout: Duration::from_millis(timeout_ms),
+                transport,
+                io_lock: Arc::new(Mutex::new(())),
+            },
+            wall_index,
+            status: Arc::new(RwLock::new(YkVapStatus::default())),
+            poll_gate: PollGate::new(),
+            tasks: TaskRegistry::new(),
+        };
+
+        if poll_interval_ms > 0 {
+            protocol.start_status_poller(poll_interval_ms);
+        }
+
+        Ok(Box::new(protocol))
     }
 
     async fn execute(&mut self, command: &str, params: Value) -> Result<Value> {
@@ -495,28 +779,21 @@ impl Protocol for YkVapProtocol {
 
                 let group = params.get("group").and_then(|v| v.as_u64());
 
-                let mut args = vec![scene_id.to_string()];
-                if let Some(g) = group {
-                    args.push(g.to_string());
-                }
-
                 debug!(
                     "[channel {}] call_scene: scene_id={}, group={:?}",
                     self.channel_id, scene_id, group
                 );
 
-                let frame = Self::build_frame("CALL", &args);
-                let frames = self.send_and_read_lines(&frame, "CALL").await?;
+                let frames = self.call_scene(scene_id, group).await?;
 
                 // 1) scene_id == 0：返回窗口列表直到 OK
                 // 2) scene_id != 0：可能只返回 <CALL,OK> 或也返回窗口信息
                 let mut windows = Vec::new();
-                let mut ok = false;
+                let mut parsed = BTreeMap::new();
 
-                for (_cmd, a) in frames {
-                    if a.len() == 1 && a[0].eq_ignore_ascii_case("OK") {
-                        ok = true;
-                        continue;
+                for a in frames {
+                    if let Some(w) = YkVapWindow::parse(&a) {
+                        parsed.insert(w.w_id, w);
                     }
                     // <CALL,W_ID,Channel,x0,y0,x1,y1,SubChannel>
                     if a.len() == 7 {
@@ -544,11 +821,8 @@ impl Protocol for YkVapProtocol {
                     }
                 }
 
-                if !ok {
-                    error!("[channel {}] call_scene 未收到 OK 结束帧", self.channel_id);
-                    return Err(DeviceError::ProtocolError(
-                        "未收到 <CALL,OK> 结束帧".to_string(),
-                    ));
+                if !parsed.is_empty() {
+                    self.status.write().await.windows = parsed;
                 }
 
                 info!(
@@ -591,27 +865,17 @@ impl Protocol for YkVapProtocol {
                     self.channel_id, wall_index, group
                 );
 
-                let frame = Self::build_frame("RSCS", &args);
-                let frames = self.send_and_read_lines(&frame, "RSCS").await?;
-
                 // 期望：
                 // <RSCS,index>
                 // <RSCS,OK>
-                let mut index: Option<u64> = None;
-                let mut ok = false;
-                for (_cmd, a) in frames {
-                    if a.len() == 1 && a[0].eq_ignore_ascii_case("OK") {
-                        ok = true;
-                    } else if a.len() == 1 {
-                        index = a[0].parse::<u64>().ok();
-                    }
-                }
-
-                if !ok {
-                    error!("[channel {}] read_scene 未收到 OK 结束帧", self.channel_id);
-                    return Err(DeviceError::ProtocolError(
-                        "未收到 <RSCS,OK> 结束帧".to_string(),
-                    ));
+                let frames = self.link.command("RSCS", &args).await?;
+                let index: Option<u64> = frames
+                    .iter()
+                    .find(|a| a.len() == 1)
+                    .and_then(|a| a[0].parse::<u64>().ok());
+
+                if wall_index == self.wall_index && index.is_some() {
+                    self.status.write().await.current_scene = index;
                 }
 
                 info!(
@@ -627,6 +891,98 @@ impl Protocol for YkVapProtocol {
                 }))
             }
 
+            "switch_source" | "swch" => {
+                let window = Self::param_u32(&params, &["window", "w_id"])?;
+                let channel = Self::param_u32(&params, &["channel", "source"])?;
+                let sub_channel = params
+                    .get("sub_channel")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0) as u32;
+                let group = params.get("group").and_then(|v| v.as_u64());
+
+                self.switch_source(window, channel, sub_channel, group)
+                    .await?;
+                info!(
+                    "[channel {}] switch_source 成功: window={}, channel={}, sub_channel={}",
+                    self.channel_id, window, channel, sub_channel
+                );
+
+                Ok(json!({
+                    "status": "ok",
+                    "window": window,
+                    "channel": channel,
+                    "sub_channel": sub_channel,
+                    "group": group
+                }))
+            }
+
+            "audio_route" | "amtx" => {
+                let input = Self::param_u32(&params, &["input"])?;
+                // output 支持单个编号或数组
+                let outputs: Vec<u32> = match params.get("output").or_else(|| params.get("outputs"))
+                {
+                    Some(Value::Array(list)) => list
+                        .iter()
+                        .filter_map(|v| v.as_u64())
+                        .map(|v| v as u32)
+                        .collect(),
+                    Some(v) => v.as_u64().map(|v| vec![v as u32]).unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                if outputs.is_empty() {
+                    return Err(DeviceError::Other("缺少 output 参数".to_string()));
+                }
+
+                for output in &outputs {
+                    self.audio_route(input, *output).await?;
+                }
+                info!(
+                    "[channel {}] audio_route 成功: input={}, outputs={:?}",
+                    self.channel_id, input, outputs
+                );
+
+                Ok(json!({
+                    "status": "ok",
+                    "input": input,
+                    "outputs": outputs
+                }))
+            }
+
+            "recall_preset" | "prst" => {
+                let preset_id = params
+                    .get("preset_id")
+                    .or_else(|| params.get("preset"))
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| DeviceError::Other("缺少 preset_id 参数".to_string()))?;
+                let group = params.get("group").and_then(|v| v.as_u64());
+
+                let mut args = vec![preset_id.to_string()];
+                if let Some(g) = group {
+                    args.push(g.to_string());
+                }
+                self.link.command("PRST", &args).await?;
+                self.status.write().await.current_preset = Some(preset_id);
+                info!(
+                    "[channel {}] recall_preset 成功: preset_id={}",
+                    self.channel_id, preset_id
+                );
+
+                Ok(json!({
+                    "status": "ok",
+                    "preset_id": preset_id,
+                    "group": group
+                }))
+            }
+
+            "get_cached_status" => Ok(self.status.read().await.to_json()),
+
+            "refresh_status" => {
+                self.link
+                    .refresh_status(self.wall_index, &self.status)
+                    .await?;
+                Ok(self.status.read().await.to_json())
+            }
+
             _ => {
                 error!("[channel {}] 不支持的命令: {}", self.channel_id, command);
                 Err(DeviceError::ProtocolError(format!(
@@ -638,56 +994,222 @@ impl Protocol for YkVapProtocol {
     }
 
     async fn get_status(&self) -> Result<Value> {
+        let status = self.status.read().await;
         Ok(json!({
             "protocol": "yk-vap",
             "channel_id": self.channel_id,
-            "addr": format!("{}:{}", self.addr, self.port),
-            "type": match self.transport {
+            "addr": net::display_addr(&self.link.addr, self.link.port),
+            "type": match self.link.transport {
                 YkVapTransport::Tcp => "tcp",
                 YkVapTransport::Udp => "udp",
             },
-            "connected": true
+            "connected": status.online,
+            "status": status.to_json()
         }))
     }
 
-    async fn write(&mut self, _id: u32, value: i32) -> Result<()> {
-        // 将 value 作为 scene_id 调用场景
-        let scene_id = value as u64;
-        info!("[channel {}] write: scene_id={}", self.channel_id, scene_id);
-
-        let args = vec![scene_id.to_string()];
-        let frame = Self::build_frame("CALL", &args);
-        let frames = self.send_and_read_lines(&frame, "CALL").await?;
+    async fn write(&mut self, id: u32, value: i32) -> Result<()> {
+        if value < 0 {
+            return Err(DeviceError::Other(format!("无效的写入值: {}", value)));
+        }
 
-        // 检查是否收到 OK 响应
-        for (_cmd, a) in frames {
-            if a.len() == 1 && a[0].eq_ignore_ascii_case("OK") {
+        match id {
+            // 101-199: 切换窗口信号源
+            i if i > NODE_WINDOW_BASE && i < NODE_AUDIO_BASE => {
+                let window = i - NODE_WINDOW_BASE;
+                info!(
+                    "[channel {}] write: window={}, channel={}",
+                    self.channel_id, window, value
+                );
+                self.switch_source(window, value as u32, 0, None).await
+            }
+            // 201-299: 音频路由到输出
+            i if i > NODE_AUDIO_BASE && i < NODE_AUDIO_BASE + 100 => {
+                let output = i - NODE_AUDIO_BASE;
+                info!(
+                    "[channel {}] write: audio input={} -> output={}",
+                    self.channel_id, value, output
+                );
+                self.audio_route(value as u32, output).await
+            }
+            // 其它节点：将 value 作为 scene_id 调用场景
+            _ => {
+                let scene_id = value as u64;
+                info!("[channel {}] write: scene_id={}", self.channel_id, scene_id);
+                self.call_scene(scene_id, None).await?;
                 info!("[channel {}] write 成功", self.channel_id);
-                return Ok(());
+                Ok(())
             }
         }
-
-        error!("[channel {}] write 未收到 OK 结束帧", self.channel_id);
-        Err(DeviceError::ProtocolError(
-            "未收到 <CALL,OK> 结束帧".to_string(),
-        ))
     }
 
-    async fn read(&self, _id: u32) -> Result<i32> {
-        warn!(
-            "[channel {}] read 不支持, 请使用 execute(read_scene)",
-            self.channel_id
-        );
-        Err(DeviceError::ProtocolError(
-            "YK-VAP 不支持简化 read(id)；请使用 execute(read_scene 等)".to_string(),
-        ))
+    /// 从状态缓存读取（见节点 ID 映射）
+    async fn read(&self, id: u32) -> Result<i32> {
+        let status = self.status.read().await;
+        let value = match id {
+            NODE_SCENE => status.current_scene.map(|v| v as i32),
+            NODE_ONLINE => Some(status.online as i32),
+            i if i > NODE_WINDOW_BASE && i < NODE_AUDIO_BASE => status
+                .windows
+                .get(&(i - NODE_WINDOW_BASE))
+                .map(|w| w.channel as i32),
+            i if i > NODE_AUDIO_BASE && i < NODE_AUDIO_BASE + 100 => status
+                .audio_routes
+                .get(&(i - NODE_AUDIO_BASE))
+                .map(|v| *v as i32),
+            _ => {
+                return Err(DeviceError::ProtocolError(format!(
+                    "YK-VAP 不支持的节点ID: {}",
+                    id
+                )))
+            }
+        };
+
+        value.ok_or_else(|| {
+            DeviceError::ProtocolError(format!(
+                "YK-VAP 节点 {} 暂无状态数据（等待轮询或先执行控制命令）",
+                id
+            ))
+        })
     }
 
     fn name(&self) -> &str {
         "yk-vap"
     }
 
+    async fn call_method(&mut self, method_name: &str, args: Value) -> Result<Value> {
+        self.execute(method_name, args).await
+    }
+
     fn get_methods(&self) -> Vec<String> {
-        vec!["call_scene".to_string(), "read_scene".to_string()]
+        vec![
+            "call_scene".to_string(),
+            "read_scene".to_string(),
+            "switch_source".to_string(),
+            "audio_route".to_string(),
+            "recall_preset".to_string(),
+            "get_cached_status".to_string(),
+            "refresh_status".to_string(),
+        ]
+    }
+
+    fn set_polling_paused(&self, paused: bool) {
+        self.poll_gate.set_paused(paused);
+    }
+
+    async fn shutdown(&self) {
+        self.tasks.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_peer::MockPeer;
+
+    /// 模拟 YK-VAP 设备：按指令返回数据帧和 <X,OK>，PRST 只返回错误帧
+    async fn mock_device() -> MockPeer {
+        MockPeer::start(|request| {
+            let (cmd, _) = YkVapLink::parse_frame(&String::from_utf8_lossy(request)).unwrap();
+            let reply = match cmd.as_str() {
+                "RSCS" => "<RSCS,5><RSCS,OK>".to_string(),
+                "CALL" => "<CALL,1,3,0,0,960,540,0>\r\n<CALL,2,4,960,0,1920,540,1>\r\n<CALL,OK>"
+                    .to_string(),
+                "PRST" => "<PRST,ERR>".to_string(),
+                other => format!("<{},OK>", other),
+            };
+            // 不带换行、分两段发送，验证按 <...> 拆帧
+            let (head, tail) = reply.split_at(reply.len() / 2);
+            vec![head.as_bytes().to_vec(), tail.as_bytes().to_vec()]
+        })
+        .await
+    }
+
+    fn protocol(port: u16) -> Box<dyn Protocol> {
+        let params: HashMap<String, Value> = serde_json::from_value(json!({
+            "addr": "127.0.0.1",
+            "port": port,
+            "timeout": 500,
+            "poll_interval_ms": 0,
+        }))
+        .unwrap();
+        YkVapProtocol::from_config(1, &params).unwrap()
+    }
+
+    #[test]
+    fn test_frames_encode_and_parse() {
+        let args = ["1", "3", "0"].map(String::from);
+        assert_eq!(YkVapLink::build_frame("SWCH", &args), "<SWCH,1,3,0>\n");
+        assert_eq!(YkVapLink::build_frame("RSCS", &[]), "<RSCS>\n");
+
+        let (cmd, args) = YkVapLink::parse_frame(" <CALL, 1,2 >\r\n").unwrap();
+        assert_eq!(cmd, "CALL");
+        assert_eq!(args, vec!["1", "2"]);
+        assert!(YkVapLink::parse_frame("CALL,1>").is_none());
+        assert!(YkVapLink::parse_frame("<CALL,1").is_none());
+
+        let window = ["1", "3", "0", "0", "960", "540", "2"].map(String::from);
+        let window = YkVapWindow::parse(&window).unwrap();
+        assert_eq!(
+            (window.w_id, window.channel, window.x1, window.sub_channel),
+            (1, 3, 960, 2)
+        );
+        assert!(YkVapWindow::parse(&["1", "3"].map(String::from)).is_none());
+        assert!(
+            YkVapWindow::parse(&["1", "x", "0", "0", "1", "1", "0"].map(String::from)).is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_node_writes_send_commands_and_update_cache() {
+        let device = mock_device().await;
+        let mut protocol = protocol(device.port());
+
+        // 轮询前无状态数据
+        assert!(protocol.read(NODE_SCENE).await.is_err());
+
+        protocol.execute("refresh_status", json!({})).await.unwrap();
+        assert_eq!(protocol.read(NODE_SCENE).await.unwrap(), 5);
+        assert_eq!(protocol.read(NODE_ONLINE).await.unwrap(), 1);
+        assert_eq!(protocol.read(NODE_WINDOW_BASE + 2).await.unwrap(), 4);
+
+        protocol.write(NODE_WINDOW_BASE + 2, 7).await.unwrap();
+        assert_eq!(protocol.read(NODE_WINDOW_BASE + 2).await.unwrap(), 7);
+        protocol.write(NODE_AUDIO_BASE + 3, 2).await.unwrap();
+        assert_eq!(protocol.read(NODE_AUDIO_BASE + 3).await.unwrap(), 2);
+        protocol.write(NODE_SCENE, 9).await.unwrap();
+        assert_eq!(protocol.read(NODE_SCENE).await.unwrap(), 9);
+
+        assert_eq!(
+            device.text_requests(),
+            vec![
+                "<RSCS,1>\n",
+                "<CALL,0>\n",
+                "<SWCH,2,7,0>\n",
+                "<AMTX,2,3>\n",
+                "<CALL,9>\n",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_ok_frame_is_an_error() {
+        let device = mock_device().await;
+        let port = device.port();
+        let protocol = protocol(port);
+
+        let link = YkVapLink {
+            channel_id: 1,
+            addr: "127.0.0.1".into(),
+            port,
+            timeout: Duration::from_millis(200),
+            transport: YkVapTransport::Tcp,
+            io_lock: Arc::new(Mutex::new(())),
+        };
+        assert!(matches!(
+            link.command("PRST", &["1".to_string()]).await,
+            Err(DeviceError::ProtocolError(_))
+        ));
+        assert!(protocol.read(999).await.is_err());
     }
 }
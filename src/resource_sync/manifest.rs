@@ -0,0 +1,163 @@
+//! 资源目录扫描与清单
+//!
+//! 校验和按（大小, 修改时间）缓存，重新扫描时只对新增或变化的文件计算 SHA-256。
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+/// 清单中的文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FileEntry {
+    /// 相对资源目录的路径（以 `/` 分隔）
+    pub path: String,
+    pub size: u64,
+    /// SHA-256（十六进制）
+    pub sha256: String,
+}
+
+/// 缓存的校验和
+#[derive(Debug, Clone)]
+pub(super) struct CachedChecksum {
+    size: u64,
+    modified: Option<SystemTime>,
+    sha256: String,
+}
+
+/// 扫描资源目录（跳过以 `.` 开头的文件和目录），返回按路径排序的清单和新的校验和缓存
+pub(super) fn scan(
+    root: &Path,
+    cache: &HashMap<String, CachedChecksum>,
+) -> std::io::Result<(Vec<FileEntry>, HashMap<String, CachedChecksum>)> {
+    let mut files = Vec::new();
+    let mut checksums = HashMap::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+
+            let path = entry.path();
+            let Some(relative) = relative_path(root, &path) else {
+                continue;
+            };
+            let metadata = entry.metadata()?;
+            let size = metadata.len();
+            let modified = metadata.modified().ok();
+            let sha256 = match cache.get(&relative) {
+                Some(cached) if cached.size == size && cached.modified == modified => {
+                    cached.sha256.clone()
+                }
+                _ => sha256_file(&path)?,
+            };
+            checksums.insert(
+                relative.clone(),
+                CachedChecksum {
+                    size,
+                    modified,
+                    sha256: sha256.clone(),
+                },
+            );
+            files.push(FileEntry {
+                path: relative,
+                size,
+                sha256,
+            });
+        }
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((files, checksums))
+}
+
+/// 清单版本：全部路径与校验和的摘要（前 16 位）
+pub(super) fn version(files: &[FileEntry]) -> String {
+    let mut hasher = Sha256::new();
+    for file in files {
+        hasher.update(file.path.as_bytes());
+        hasher.update([0]);
+        hasher.update(file.sha256.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())[..16].to_string()
+}
+
+/// 对路径各段做 URL 编码（保留 `/`）
+pub(super) fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// 相对资源目录的路径，各段以 `/` 连接（非 UTF-8 路径返回 None）
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Option<Vec<&str>> = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect();
+    Some(parts?.join("/"))
+}
+
+pub(crate) fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_with_checksums_and_skips_hidden_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("videos")).unwrap();
+        std::fs::write(dir.path().join("videos/intro.mp4"), b"abc").unwrap();
+        std::fs::write(dir.path().join(".DS_Store"), b"x").unwrap();
+
+        let (files, cache) = scan(dir.path(), &HashMap::new()).unwrap();
+        assert_eq!(
+            files,
+            vec![FileEntry {
+                path: "videos/intro.mp4".to_string(),
+                size: 3,
+                sha256: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    .to_string(),
+            }]
+        );
+
+        let (again, _) = scan(dir.path(), &cache).unwrap();
+        assert_eq!(version(&files), version(&again));
+    }
+}
@@ -0,0 +1,463 @@
+//! 资源同步
+//! 外部媒体播放器注册并上报本地资源清单（路径、大小、SHA-256），控制器与资源目录比对后
+//! 返回需要下载与删除的文件；播放器注册了回调地址时，资源目录变化后主动推送新的同步计划。
+//! 文件下载支持 HTTP Range 断点续传与带宽限制，取代现场用 U 盘拷贝展项内容。
+//!
+//! 接口前缀 `/lspcapi/resource/sync`：
+//! - `POST /register`：注册播放器（名称、回调地址）
+//! - `POST /manifest`：上报播放器清单，返回同步计划
+//! - `GET /manifest`：控制器资源清单
+//! - `GET /file/<路径>`：下载文件
+//! - `GET /status`：同步状态
+
+mod manifest;
+
+pub(crate) use manifest::sha256_file;
+pub use manifest::FileEntry;
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::config::{ResourceConfig, ResourceSyncConfig};
+use crate::utils::{http, time, DeviceError, Result};
+
+/// 下载地址前缀
+pub const FILE_URL_PREFIX: &str = "/lspcapi/resource/sync/file";
+
+/// 控制器资源清单
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SourceManifest {
+    /// 清单版本（全部文件路径与校验和的摘要），内容变化时改变
+    pub version: String,
+    /// 扫描时间（RFC 3339），尚未扫描完成时为空
+    pub scanned_at: Option<String>,
+    /// 文件总大小（字节）
+    pub total_bytes: u64,
+    pub files: Vec<FileEntry>,
+}
+
+/// 待下载文件
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// 下载地址（相对控制器根地址）
+    pub url: String,
+}
+
+/// 播放器同步计划
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncPlan {
+    pub player_id: String,
+    /// 对应的控制器清单版本
+    pub version: String,
+    /// 需要下载（缺失或校验和不同）的文件
+    pub download: Vec<SyncFile>,
+    /// 控制器清单中不存在、应删除的文件
+    pub delete: Vec<String>,
+    /// 需要下载的总字节数
+    pub download_bytes: u64,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.download.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// 注册播放器请求
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RegisterPlayer {
+    /// 播放器唯一 ID
+    pub player_id: String,
+    /// 显示名称
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 回调地址：资源目录变化后控制器 POST 新的同步计划到该地址
+    #[serde(default)]
+    pub callback_url: Option<String>,
+}
+
+/// 播放器同步状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncPlayerStatus {
+    pub player_id: String,
+    pub name: String,
+    pub callback_url: Option<String>,
+    /// 在超时时间内有上报
+    pub online: bool,
+    pub registered_at: String,
+    /// 最近一次上报清单的时间
+    pub last_report: Option<String>,
+    /// 最近一次上报后与控制器清单一致
+    pub in_sync: bool,
+    pub pending_downloads: usize,
+    pub pending_deletes: usize,
+    pub pending_bytes: u64,
+    /// 已提供给该播放器下载的字节数
+    pub bytes_served: u64,
+    /// 最近一次推送失败原因
+    pub last_push_error: Option<String>,
+}
+
+/// 同步状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncStatus {
+    pub version: String,
+    pub scanned_at: Option<String>,
+    pub files: usize,
+    pub total_bytes: u64,
+    pub players: Vec<SyncPlayerStatus>,
+}
+
+/// 已注册的播放器
+struct Player {
+    name: String,
+    callback_url: Option<String>,
+    registered_at: String,
+    last_seen: Instant,
+    last_report: Option<String>,
+    /// 最近一次上报的清单（未上报时为空）
+    files: Option<Vec<FileEntry>>,
+    bytes_served: u64,
+    last_push_error: Option<String>,
+}
+
+/// 资源同步服务
+pub struct ResourceSync {
+    root: PathBuf,
+    config: ResourceSyncConfig,
+    manifest: RwLock<SourceManifest>,
+    /// 路径 -> (大小, 修改时间, SHA-256)，未变化的文件不重新计算校验和
+    checksums: Mutex<HashMap<String, manifest::CachedChecksum>>,
+    players: DashMap<String, Player>,
+    client: reqwest::Client,
+}
+
+/// 按配置启动资源同步（未启用时返回 None）：后台扫描资源目录并定期重新扫描
+pub fn start(resource: Option<&ResourceConfig>) -> Result<Option<Arc<ResourceSync>>> {
+    let Some(resource) = resource.filter(|r| r.enable) else {
+        return Ok(None);
+    };
+    let Some(config) = resource.sync.as_ref().filter(|s| s.enable) else {
+        return Ok(None);
+    };
+
+    let sync = Arc::new(ResourceSync {
+        root: PathBuf::from(&resource.path),
+        config: config.clone(),
+        manifest: RwLock::new(SourceManifest::default()),
+        checksums: Mutex::new(HashMap::new()),
+        players: DashMap::new(),
+        client: http::build_client(&http::global_defaults())?,
+    });
+    info!(
+        "资源同步已启用: {} (带宽上限: {} KB/s, 重新扫描间隔: {} 毫秒)",
+        resource.path, config.bandwidth_limit_kbps, config.rescan_interval_ms
+    );
+
+    let interval = Duration::from_millis(config.rescan_interval_ms.max(1000));
+    tokio::spawn({
+        let sync = sync.clone();
+        async move {
+            loop {
+                sync.rescan().await;
+                tokio::time::sleep(interval).await;
+            }
+        }
+    });
+    Ok(Some(sync))
+}
+
+impl ResourceSync {
+    /// 单个下载的带宽上限（字节/秒）
+    pub fn bandwidth_limit(&self) -> Option<u64> {
+        (self.config.bandwidth_limit_kbps > 0).then_some(self.config.bandwidth_limit_kbps * 1024)
+    }
+
+    /// 控制器资源清单
+    pub async fn manifest(&self) -> SourceManifest {
+        self.manifest.read().await.clone()
+    }
+
+    /// 重新扫描资源目录，清单变化时向注册了回调地址的播放器推送同步计划
+    async fn rescan(&self) {
+        let root = self.root.clone();
+        let cache = self.checksums.lock().await.clone();
+        let scanned = tokio::task::spawn_blocking(move || manifest::scan(&root, &cache)).await;
+        let (files, cache) = match scanned {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                warn!("扫描资源目录失败: {}", e);
+                return;
+            }
+            Err(e) => {
+                warn!("扫描资源目录任务异常: {}", e);
+                return;
+            }
+        };
+        *self.checksums.lock().await = cache;
+
+        let version = manifest::version(&files);
+        let changed = {
+            let mut manifest = self.manifest.write().await;
+            let changed = manifest.version != version;
+            *manifest = SourceManifest {
+                version,
+                scanned_at: Some(time::now_rfc3339()),
+                total_bytes: files.iter().map(|f| f.size).sum(),
+                files,
+            };
+            changed
+        };
+        if changed {
+            info!("资源清单已更新，推送同步计划");
+            self.push_plans().await;
+        }
+    }
+
+    /// 注册（或更新）播放器
+    pub fn register(&self, request: RegisterPlayer) -> Result<SyncPlayerStatus> {
+        let player_id = request.player_id.trim().to_string();
+        if player_id.is_empty() {
+            return Err(DeviceError::ConfigError("player_id 不能为空".to_string()));
+        }
+        let name = request.name.unwrap_or_else(|| player_id.clone());
+        let mut player = self
+            .players
+            .entry(player_id.clone())
+            .or_insert_with(|| Player {
+                name: name.clone(),
+                callback_url: None,
+                registered_at: time::now_rfc3339(),
+                last_seen: Instant::now(),
+                last_report: None,
+                files: None,
+                bytes_served: 0,
+                last_push_error: None,
+            });
+        player.name = name;
+        player.callback_url = request.callback_url.filter(|url| !url.is_empty());
+        player.last_seen = Instant::now();
+        info!("播放器 {} ({}) 已注册", player_id, player.name);
+        drop(player);
+        Ok(self.player_status(&player_id, None))
+    }
+
+    /// 记录播放器上报的清单（未注册时自动注册），返回同步计划
+    pub async fn report(&self, player_id: &str, files: Vec<FileEntry>) -> Result<SyncPlan> {
+        let manifest = self.manifest.read().await;
+        if manifest.scanned_at.is_none() {
+            return Err(DeviceError::Other(
+                "资源清单尚未生成，请稍后重试".to_string(),
+            ));
+        }
+        if !self.players.contains_key(player_id) {
+            self.register(RegisterPlayer {
+                player_id: player_id.to_string(),
+                name: None,
+                callback_url: None,
+            })?;
+        }
+        let plan = plan(player_id, &manifest, &files);
+        if let Some(mut player) = self.players.get_mut(player_id) {
+            player.last_seen = Instant::now();
+            player.last_report = Some(time::now_rfc3339());
+            player.files = Some(files);
+        }
+        debug!(
+            "播放器 {} 同步计划: 下载 {} 个文件（{} 字节），删除 {} 个文件",
+            player_id,
+            plan.download.len(),
+            plan.download_bytes,
+            plan.delete.len()
+        );
+        Ok(plan)
+    }
+
+    /// 查找清单中的文件（只能下载清单中列出的文件）
+    pub async fn file(&self, path: &str) -> Option<(FileEntry, PathBuf)> {
+        let manifest = self.manifest.read().await;
+        let entry = manifest.files.iter().find(|f| f.path == path)?.clone();
+        let full_path = self.root.join(Path::new(&entry.path));
+        Some((entry, full_path))
+    }
+
+    /// 累计提供给播放器的下载字节数
+    pub fn record_served(&self, player_id: &str, bytes: u64) {
+        if let Some(mut player) = self.players.get_mut(player_id) {
+            player.bytes_served += bytes;
+            player.last_seen = Instant::now();
+        }
+    }
+
+    /// 同步状态
+    pub async fn status(&self) -> SyncStatus {
+        let manifest = self.manifest.read().await;
+        let mut players: Vec<SyncPlayerStatus> = self
+            .players
+            .iter()
+            .map(|p| self.player_status(p.key(), Some(&manifest)))
+            .collect();
+        players.sort_by(|a, b| a.player_id.cmp(&b.player_id));
+        SyncStatus {
+            version: manifest.version.clone(),
+            scanned_at: manifest.scanned_at.clone(),
+            files: manifest.files.len(),
+            total_bytes: manifest.total_bytes,
+            players,
+        }
+    }
+
+    fn player_status(
+        &self,
+        player_id: &str,
+        manifest: Option<&SourceManifest>,
+    ) -> SyncPlayerStatus {
+        let player = self.players.get(player_id);
+        let player = player.as_deref();
+        let pending = match (manifest, player.and_then(|p| p.files.as_ref())) {
+            (Some(manifest), Some(files)) => Some(plan(player_id, manifest, files)),
+            _ => None,
+        };
+        let timeout = Duration::from_millis(self.config.player_timeout_ms);
+        SyncPlayerStatus {
+            player_id: player_id.to_string(),
+            name: player.map(|p| p.name.clone()).unwrap_or_default(),
+            callback_url: player.and_then(|p| p.callback_url.clone()),
+            online: player.is_some_and(|p| p.last_seen.elapsed() < timeout),
+            registered_at: player.map(|p| p.registered_at.clone()).unwrap_or_default(),
+            last_report: player.and_then(|p| p.last_report.clone()),
+            in_sync: pending.as_ref().is_some_and(SyncPlan::is_empty),
+            pending_downloads: pending.as_ref().map_or(0, |p| p.download.len()),
+            pending_deletes: pending.as_ref().map_or(0, |p| p.delete.len()),
+            pending_bytes: pending.as_ref().map_or(0, |p| p.download_bytes),
+            bytes_served: player.map_or(0, |p| p.bytes_served),
+            last_push_error: player.and_then(|p| p.last_push_error.clone()),
+        }
+    }
+
+    /// 向注册了回调地址且上报过清单的播放器推送同步计划
+    async fn push_plans(&self) {
+        let targets: Vec<(String, String, Vec<FileEntry>)> = self
+            .players
+            .iter()
+            .filter_map(|p| Some((p.key().clone(), p.callback_url.clone()?, p.files.clone()?)))
+            .collect();
+        let manifest = self.manifest.read().await.clone();
+
+        for (player_id, url, files) in targets {
+            let plan = plan(&player_id, &manifest, &files);
+            if plan.is_empty() {
+                continue;
+            }
+            let result = self
+                .client
+                .post(&url)
+                .json(&plan)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            let error = match result {
+                Ok(_) => {
+                    info!(
+                        "已向播放器 {} 推送同步计划（下载 {} 个，删除 {} 个）",
+                        player_id,
+                        plan.download.len(),
+                        plan.delete.len()
+                    );
+                    None
+                }
+                Err(e) => {
+                    warn!("向播放器 {} 推送同步计划失败: {}", player_id, e);
+                    Some(e.to_string())
+                }
+            };
+            if let Some(mut player) = self.players.get_mut(&player_id) {
+                player.last_push_error = error;
+            }
+        }
+    }
+}
+
+/// 比对控制器清单与播放器清单
+fn plan(player_id: &str, manifest: &SourceManifest, player_files: &[FileEntry]) -> SyncPlan {
+    let player: HashMap<&str, &FileEntry> =
+        player_files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let download: Vec<SyncFile> = manifest
+        .files
+        .iter()
+        .filter(|f| {
+            player
+                .get(f.path.as_str())
+                .is_none_or(|p| p.size != f.size || !p.sha256.eq_ignore_ascii_case(&f.sha256))
+        })
+        .map(|f| SyncFile {
+            path: f.path.clone(),
+            size: f.size,
+            sha256: f.sha256.clone(),
+            url: format!("{}/{}", FILE_URL_PREFIX, manifest::encode_path(&f.path)),
+        })
+        .collect();
+    let source: std::collections::HashSet<&str> =
+        manifest.files.iter().map(|f| f.path.as_str()).collect();
+    let mut delete: Vec<String> = player_files
+        .iter()
+        .filter(|f| !source.contains(f.path.as_str()))
+        .map(|f| f.path.clone())
+        .collect();
+    delete.sort();
+
+    SyncPlan {
+        player_id: player_id.to_string(),
+        version: manifest.version.clone(),
+        download_bytes: download.iter().map(|f| f.size).sum(),
+        download,
+        delete,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: u64, sha256: &str) -> FileEntry {
+        FileEntry {
+            path: path.to_string(),
+            size,
+            sha256: sha256.to_string(),
+        }
+    }
+
+    #[test]
+    fn plans_downloads_and_deletes() {
+        let manifest = SourceManifest {
+            version: "v1".to_string(),
+            scanned_at: Some(time::now_rfc3339()),
+            total_bytes: 30,
+            files: vec![
+                entry("intro.mp4", 10, "aa"),
+                entry("展厅 A/loop.mp4", 20, "bb"),
+            ],
+        };
+        let player = vec![entry("intro.mp4", 10, "AA"), entry("old.mp4", 5, "cc")];
+
+        let plan = plan("p1", &manifest, &player);
+        assert_eq!(plan.download.len(), 1);
+        assert_eq!(plan.download[0].path, "展厅 A/loop.mp4");
+        assert_eq!(
+            plan.download[0].url,
+            "/lspcapi/resource/sync/file/%E5%B1%95%E5%8E%85%20A/loop.mp4"
+        );
+        assert_eq!(plan.download_bytes, 20);
+        assert_eq!(plan.delete, vec!["old.mp4".to_string()]);
+    }
+}
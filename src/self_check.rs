@@ -0,0 +1,297 @@
+//! 启动自检（`dm-rust --check`）
+//!
+//! 部署流水线在替换服务程序前运行：加载并校验配置、测试数据库连接、检查文件与资源目录
+//! 可写，可选地用短超时连接每个启用的通道，然后输出 JSON 报告并退出（全部通过时退出码为 0）。
+//! 自检不启动 Web 服务，也不修改任何设备状态。
+
+use futures::future::join_all;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::{self, Config, StorageBackend};
+use crate::device::ChannelManager;
+use crate::utils::time;
+
+/// 自检选项
+#[derive(Debug, Clone)]
+pub struct SelfCheckOptions {
+    /// 连接每个启用的通道（否则只校验通道参数能否创建协议实例）
+    pub connect_channels: bool,
+    /// 数据库与通道连接的超时
+    pub timeout: Duration,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    /// 未启用或前置检查失败，未执行
+    Skipped,
+}
+
+/// 单项检查
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    /// 检查项，如 `config`、`database`、`channel:3`、`path:resource`
+    pub name: String,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfCheckReport {
+    /// 全部检查通过（跳过的检查不影响结果）
+    pub ok: bool,
+    /// 配置文件路径
+    pub config: String,
+    /// 自检时间（RFC 3339）
+    pub checked_at: String,
+    pub checks: Vec<CheckResult>,
+}
+
+/// 执行自检
+pub async fn run(config_path: &str, options: &SelfCheckOptions) -> SelfCheckReport {
+    let mut checks = Vec::new();
+    let started = Instant::now();
+    let loaded = config::load_config_from_file(config_path);
+    checks.push(result(
+        "config",
+        started,
+        loaded.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+    ));
+
+    if let Ok(cfg) = &loaded {
+        let started = Instant::now();
+        let problems = validate_config(cfg);
+        checks.push(result(
+            "config.validate",
+            started,
+            if problems.is_empty() {
+                Ok(())
+            } else {
+                Err(problems.join("; "))
+            },
+        ));
+        checks.push(check_database(cfg, options.timeout).await);
+        checks.extend(check_paths(cfg));
+        checks.extend(check_channels(cfg, options).await);
+    }
+
+    SelfCheckReport {
+        ok: checks.iter().all(|c| c.status != CheckStatus::Failed),
+        config: config_path.to_string(),
+        checked_at: time::now_rfc3339(),
+        checks,
+    }
+}
+
+/// 配置的静态校验：ID 重复与引用不存在的通道、节点
+pub fn validate_config(cfg: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let mut channel_ids = HashSet::new();
+    for channel in &cfg.channels {
+        if !channel_ids.insert(channel.channel_id) {
+            problems.push(format!("通道 ID {} 重复", channel.channel_id));
+        }
+    }
+
+    let mut node_ids = HashSet::new();
+    for node in &cfg.nodes {
+        if !node_ids.insert(node.global_id) {
+            problems.push(format!("节点 global_id {} 重复", node.global_id));
+        }
+        if !channel_ids.contains(&node.channel_id) {
+            problems.push(format!(
+                "节点 {} ({}) 引用的通道 {} 不存在",
+                node.global_id, node.alias, node.channel_id
+            ));
+        }
+    }
+    for node in &cfg.nodes {
+        for dep in node.depend.iter().flatten() {
+            if let (Some(channel_id), Some(id)) = (dep.channel_id, dep.id) {
+                if !cfg
+                    .nodes
+                    .iter()
+                    .any(|n| n.channel_id == channel_id && n.id == id)
+                {
+                    problems.push(format!(
+                        "节点 {} 的依赖（通道 {} 节点 {}）不存在",
+                        node.global_id, channel_id, id
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut scene_names = HashSet::new();
+    for scene in &cfg.scenes {
+        if !scene_names.insert(scene.name.as_str()) {
+            problems.push(format!("场景 '{}' 重复", scene.name));
+        }
+        for id in scene.nodes.iter().flat_map(|step| step.targets()) {
+            if !node_ids.contains(&id) {
+                problems.push(format!("场景 '{}' 引用的节点 {} 不存在", scene.name, id));
+            }
+        }
+    }
+    problems
+}
+
+async fn check_database(cfg: &Config, timeout: Duration) -> CheckResult {
+    let started = Instant::now();
+    let Some(db) = cfg.database.as_ref().filter(|db| db.enable) else {
+        return skipped("database", "未启用数据库");
+    };
+    let connected = match tokio::time::timeout(timeout, crate::db::Database::new(&db.url)).await {
+        Ok(Ok(database)) => {
+            database.pool.close().await;
+            Ok(())
+        }
+        Ok(Err(e)) => Err(format!("连接失败: {}", e)),
+        Err(_) => Err(format!("{} 毫秒内未连接", timeout.as_millis())),
+    };
+    result("database", started, connected)
+}
+
+/// 需要写入的目录
+fn writable_paths(cfg: &Config) -> Vec<(&'static str, PathBuf)> {
+    let mut paths = Vec::new();
+    if let Some(file) = cfg.file.as_ref().filter(|f| f.enable) {
+        paths.push(("file", PathBuf::from(&file.path)));
+    }
+    if let Some(resource) = cfg.resource.as_ref().filter(|r| r.enable) {
+        paths.push(("resource", PathBuf::from(&resource.path)));
+    }
+    if let Some(log) = cfg.log.as_ref().filter(|l| l.target != "console") {
+        let dir = Path::new(&log.file)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        paths.push(("log", dir.to_path_buf()));
+    }
+    let storage = cfg.storage.clone().unwrap_or_default();
+    if storage.backend == StorageBackend::File {
+        let dir = storage
+            .path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("data/protocol_storage"));
+        paths.push(("storage", dir));
+    }
+    if let Some(sessions) = cfg.session_recording.as_ref().filter(|s| s.enable) {
+        paths.push(("sessions", PathBuf::from(&sessions.dir)));
+    }
+    paths
+}
+
+fn check_paths(cfg: &Config) -> Vec<CheckResult> {
+    writable_paths(cfg)
+        .into_iter()
+        .map(|(label, dir)| {
+            let started = Instant::now();
+            let writable = check_writable(&dir).map_err(|e| format!("{}: {}", dir.display(), e));
+            result(&format!("path:{}", label), started, writable)
+        })
+        .collect()
+}
+
+/// 目录不存在时创建，写入并删除一个临时文件
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".dm-rust-check-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"check")?;
+    std::fs::remove_file(&probe)
+}
+
+async fn check_channels(cfg: &Config, options: &SelfCheckOptions) -> Vec<CheckResult> {
+    let timeout = options.connect_channels.then_some(options.timeout);
+    let checks = cfg.channels.iter().map(|channel| async move {
+        let name = format!("channel:{}", channel.channel_id);
+        if !channel.enable {
+            return skipped(&name, "通道未启用");
+        }
+        let started = Instant::now();
+        let probed = ChannelManager::probe_channel(channel, timeout)
+            .await
+            .map_err(|e| e.to_string());
+        result(&name, started, probed)
+    });
+    join_all(checks).await
+}
+
+fn result(name: &str, started: Instant, outcome: Result<(), String>) -> CheckResult {
+    let (status, message) = match outcome {
+        Ok(()) => (CheckStatus::Ok, None),
+        Err(message) => (CheckStatus::Failed, Some(message)),
+    };
+    CheckResult {
+        name: name.to_string(),
+        status,
+        message,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+fn skipped(name: &str, reason: &str) -> CheckResult {
+    CheckResult {
+        name: name.to_string(),
+        status: CheckStatus::Skipped,
+        message: Some(reason.to_string()),
+        duration_ms: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_invalid_references_and_channels() {
+        let dir = std::env::temp_dir().join(format!("dm-rust-check-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+        std::fs::write(
+            &config_path,
+            serde_json::json!({
+                "web_server": { "port": 8080 },
+                "storage": { "path": dir.join("storage") },
+                "channels": [
+                    { "channel_id": 1, "enable": true, "statute": "mock", "arguments": {} },
+                    { "channel_id": 2, "enable": false, "statute": "mock", "arguments": {} }
+                ],
+                "nodes": [
+                    { "global_id": 1, "channel_id": 1, "id": 1, "alias": "灯光" },
+                    { "global_id": 2, "channel_id": 9, "id": 1, "alias": "幕布" }
+                ],
+                "scenes": [{ "name": "开馆", "nodes": [{ "id": 7, "value": 1 }] }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let options = SelfCheckOptions {
+            connect_channels: true,
+            timeout: Duration::from_secs(1),
+        };
+        let report = run(config_path.to_str().unwrap(), &options).await;
+        let check = |name: &str| report.checks.iter().find(|c| c.name == name).unwrap();
+
+        assert!(!report.ok);
+        let problems = check("config.validate").message.clone().unwrap();
+        assert!(problems.contains("通道 9") && problems.contains("节点 7"));
+        assert_eq!(check("channel:1").status, CheckStatus::Ok);
+        assert_eq!(check("channel:2").status, CheckStatus::Skipped);
+        assert_eq!(check("path:storage").status, CheckStatus::Ok);
+        assert_eq!(check("database").status, CheckStatus::Skipped);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -12,7 +12,8 @@ use std::time::Duration;
 use windows_service::{
     define_windows_service,
     service::{
-        ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+        ServiceAccess, ServiceAction, ServiceActionType, ServiceControl, ServiceControlAccept,
+        ServiceErrorControl, ServiceExitCode, ServiceFailureActions, ServiceFailureResetPeriod,
         ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
     },
     service_control_handler::{self, ServiceControlHandlerResult},
@@ -34,6 +35,10 @@ define_windows_service!(ffi_service_main, service_main);
 /// Windows服务主函数
 #[cfg(windows)]
 fn service_main(_arguments: Vec<OsString>) {
+    // 服务模式下 panic 后退出进程，由服务控制管理器按失败操作自动重启
+    crate::utils::crash::set_service_mode();
+    crate::utils::crash::install_panic_hook();
+
     if let Err(e) = run_service() {
         // 记录错误到Windows事件日志
         eprintln!("服务运行错误: {:?}", e);
@@ -320,7 +325,11 @@ pub fn install_service() -> Result<()> {
     };
 
     let service = service_manager
-        .create_service(&service_info, ServiceAccess::CHANGE_CONFIG)
+        .create_service(
+            &service_info,
+            // 设置重启类失败操作需要 START 权限
+            ServiceAccess::CHANGE_CONFIG | ServiceAccess::START,
+        )
         .context("创建服务失败")?;
 
     // 设置服务描述
@@ -328,10 +337,28 @@ pub fn install_service() -> Result<()> {
         .set_description(SERVICE_DESCRIPTION)
         .context("设置服务描述失败")?;
 
+    // 进程异常退出（含 panic）后自动重启：前两次 5 秒后重启，之后 60 秒后重启，一天无故障后重新计数
+    let restart = |secs| ServiceAction {
+        action_type: ServiceActionType::Restart,
+        delay: Duration::from_secs(secs),
+    };
+    service
+        .update_failure_actions(ServiceFailureActions {
+            reset_period: ServiceFailureResetPeriod::After(Duration::from_secs(24 * 60 * 60)),
+            reboot_msg: None,
+            command: None,
+            actions: Some(vec![restart(5), restart(5), restart(60)]),
+        })
+        .context("设置服务失败恢复操作失败")?;
+    service
+        .set_failure_actions_on_non_crash_failures(true)
+        .context("设置服务失败恢复操作失败")?;
+
     println!("✓ 服务安装成功: {}", SERVICE_NAME);
     println!("  显示名称: {}", SERVICE_DISPLAY_NAME);
     println!("  描述: {}", SERVICE_DESCRIPTION);
     println!("  执行文件: {}", exe_path.display());
+    println!("  失败恢复: 异常退出后自动重启");
     println!(
         "\n提示: 使用 'sc start {}' 或 '{} -s start' 启动服务",
         SERVICE_NAME,
@@ -0,0 +1,750 @@
+//! 操作会话录制与回放
+//!
+//! 录制期间通过 API 执行的写入、场景与通道方法调用按发生时间保存为命名会话（每个会话一个
+//! JSON 文件），之后可按原始节奏或加速回放，用于新操作员的演出流程培训与问题复现。
+//! 回放也可以只做演练（dry-run）：逐条检查节点、场景与通道方法是否存在，不下发到设备。
+//!
+//! 回放中需要写入确认的节点只登记待确认写入，由操作员在确认接口中处理。
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::config::SessionRecordingConfig;
+use crate::device::DeviceController;
+use crate::utils::{time, DeviceError, Result};
+use crate::web::state::SharedController;
+
+/// 会话名称最大长度
+const MAX_NAME_LEN: usize = 64;
+/// 与接口路径冲突、不能用作会话名称的名字
+const RESERVED_NAMES: &[&str] = &["record", "replay"];
+
+/// 录制的设备接口（`/lspcapi/device` 之后的路径）
+pub const RECORDED_PATHS: &[&str] = &[
+    "/write",
+    "/writeMany",
+    "/scene",
+    "/callMethod",
+    "/executeCommand",
+];
+
+/// 录制的动作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionAction {
+    /// 写入节点（值为换算到节点单位后的数值，状态名称录制时已解析）
+    Write { global_id: u32, value: f64 },
+    /// 执行场景
+    Scene { name: String },
+    /// 调用通道方法
+    CallMethod {
+        channel_id: u32,
+        method_name: String,
+        #[schema(value_type = Object)]
+        arguments: serde_json::Value,
+    },
+    /// 执行通道命令
+    ChannelCommand {
+        channel_id: u32,
+        command: String,
+        #[schema(value_type = Object)]
+        params: serde_json::Value,
+    },
+}
+
+impl SessionAction {
+    /// 从设备接口请求中解析要录制的动作（`path` 为 `/lspcapi/device` 之后的部分）
+    ///
+    /// `write_value` 按请求方的单位制与节点状态名称把写入值换算为节点单位下的数值，与写入接口
+    /// 的处理一致；无法换算的写入（如未定义的状态名称）不记入会话。
+    pub fn from_request(
+        path: &str,
+        body: &serde_json::Value,
+        write_value: &dyn Fn(u32, &serde_json::Value) -> Result<f64>,
+    ) -> Vec<SessionAction> {
+        let u32_field = |v: &serde_json::Value, key: &str| {
+            v.get(key).and_then(|x| x.as_u64()).map(|x| x as u32)
+        };
+        let str_field = |v: &serde_json::Value, key: &str| {
+            v.get(key).and_then(|x| x.as_str()).map(str::to_string)
+        };
+        let value_field = |v: &serde_json::Value, key: &str| {
+            v.get(key).cloned().unwrap_or(serde_json::Value::Null)
+        };
+        let write = |global_id: u32, value: serde_json::Value| match write_value(global_id, &value)
+        {
+            Ok(value) => Some(SessionAction::Write { global_id, value }),
+            Err(e) => {
+                warn!(
+                    "[会话录制] 节点 {} 写入值 {} 无法录制: {}",
+                    global_id, value, e
+                );
+                None
+            }
+        };
+
+        let action = match path {
+            "/write" => u32_field(body, "global_id")
+                .and_then(|global_id| write(global_id, value_field(body, "value"))),
+            "/writeMany" => {
+                return body
+                    .get("items")
+                    .and_then(|v| v.as_array())
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|item| {
+                                write(u32_field(item, "id")?, value_field(item, "value"))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+            "/scene" => str_field(body, "name").map(|name| SessionAction::Scene { name }),
+            "/callMethod" => u32_field(body, "channel_id")
+                .zip(str_field(body, "method_name"))
+                .map(|(channel_id, method_name)| SessionAction::CallMethod {
+                    channel_id,
+                    method_name,
+                    arguments: value_field(body, "arguments"),
+                }),
+            "/executeCommand" => u32_field(body, "channel_id")
+                .zip(str_field(body, "command"))
+                .map(|(channel_id, command)| SessionAction::ChannelCommand {
+                    channel_id,
+                    command,
+                    params: value_field(body, "params"),
+                }),
+            _ => None,
+        };
+        action.into_iter().collect()
+    }
+
+    /// 按录制内容执行（写入需要确认时只登记待确认写入）
+    async fn execute(&self, controller: &DeviceController, operator: &str) -> Result<()> {
+        match self {
+            SessionAction::Write { global_id, value } => {
+                match controller.hold_for_confirmation(*global_id, *value, Some(operator.into()))? {
+                    Some(pending) => Err(DeviceError::Other(format!(
+                        "需要确认（令牌 {}）",
+                        pending.token
+                    ))),
+                    None => controller.write_node_value(*global_id, *value).await,
+                }
+            }
+            SessionAction::Scene { name } => controller.execute_scene(name).await,
+            SessionAction::CallMethod {
+                channel_id,
+                method_name,
+                arguments,
+            } => controller
+                .call_channel_method(*channel_id, method_name, arguments.clone())
+                .await
+                .map(|_| ()),
+            SessionAction::ChannelCommand {
+                channel_id,
+                command,
+                params,
+            } => controller
+                .execute_channel_command(*channel_id, command, params.clone())
+                .await
+                .map(|_| ()),
+        }
+    }
+
+    /// 演练：只检查目标是否存在，不下发到设备
+    async fn check(&self, controller: &DeviceController) -> Result<()> {
+        match self {
+            SessionAction::Write { global_id, .. } => {
+                if controller
+                    .get_all_node_configs()
+                    .iter()
+                    .any(|n| n.global_id == *global_id)
+                {
+                    Ok(())
+                } else {
+                    Err(DeviceError::DeviceNotFound(format!("节点 {}", global_id)))
+                }
+            }
+            SessionAction::Scene { name } => {
+                if controller.get_all_scenes().iter().any(|s| &s.name == name) {
+                    Ok(())
+                } else {
+                    Err(DeviceError::Other(format!("场景 '{}' 不存在", name)))
+                }
+            }
+            SessionAction::CallMethod {
+                channel_id,
+                method_name,
+                ..
+            } => {
+                let methods = controller.get_channel_methods(*channel_id).await?;
+                if methods.iter().any(|m| m == method_name) {
+                    Ok(())
+                } else {
+                    Err(DeviceError::Other(format!(
+                        "通道 {} 不支持方法 '{}'",
+                        channel_id, method_name
+                    )))
+                }
+            }
+            SessionAction::ChannelCommand { channel_id, .. } => controller
+                .get_channel_methods(*channel_id)
+                .await
+                .map(|_| ()),
+        }
+    }
+}
+
+/// 会话中的一条动作
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecordedAction {
+    /// 距录制开始的毫秒数
+    pub offset_ms: u64,
+    /// 执行该动作的用户或 API Key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    #[serde(flatten)]
+    pub action: SessionAction,
+}
+
+/// 录制的会话
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecordedSession {
+    pub name: String,
+    /// 录制开始时间（RFC 3339）
+    pub recorded_at: String,
+    /// 录制时长（毫秒）
+    pub duration_ms: u64,
+    pub actions: Vec<RecordedAction>,
+}
+
+impl RecordedSession {
+    fn summary(&self) -> SessionSummary {
+        SessionSummary {
+            name: self.name.clone(),
+            recorded_at: self.recorded_at.clone(),
+            duration_ms: self.duration_ms,
+            actions: self.actions.len(),
+        }
+    }
+}
+
+/// 会话概要
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionSummary {
+    pub name: String,
+    pub recorded_at: String,
+    pub duration_ms: u64,
+    /// 动作数
+    pub actions: usize,
+}
+
+/// 正在进行的录制
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RecordingStatus {
+    pub name: String,
+    /// 开始时间（RFC 3339）
+    pub started_at: String,
+    /// 已录制的动作数
+    pub actions: usize,
+}
+
+struct ActiveRecording {
+    session: RecordedSession,
+    started: Instant,
+}
+
+/// 回放中单条动作的结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReplayActionResult {
+    /// 动作在会话中的序号（0-based）
+    pub index: usize,
+    pub action: SessionAction,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 实际执行时间距回放开始的毫秒数
+    pub at_ms: u64,
+}
+
+/// 回放状态
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReplayStatus {
+    pub session: String,
+    /// 回放速度倍数（1 为原始节奏）
+    pub speed: f64,
+    /// 演练模式（不下发到设备）
+    pub dry_run: bool,
+    pub running: bool,
+    /// 被停止
+    pub stopped: bool,
+    /// 会话中的动作总数
+    pub total: usize,
+    /// 开始时间（RFC 3339）
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// 已执行动作的结果
+    pub results: Vec<ReplayActionResult>,
+}
+
+/// 会话录制与回放
+pub struct SessionRecorder {
+    dir: PathBuf,
+    max_actions: usize,
+    controller: SharedController,
+    recording: Mutex<Option<ActiveRecording>>,
+    replay: Arc<Mutex<Option<ReplayStatus>>>,
+    replay_token: Mutex<Option<CancellationToken>>,
+}
+
+impl SessionRecorder {
+    pub fn new(config: &SessionRecordingConfig, controller: SharedController) -> Arc<Self> {
+        Arc::new(Self {
+            dir: PathBuf::from(&config.dir),
+            max_actions: config.max_actions,
+            controller,
+            recording: Mutex::new(None),
+            replay: Arc::new(Mutex::new(None)),
+            replay_token: Mutex::new(None),
+        })
+    }
+
+    /// 开始录制（同时只能录制一个会话）
+    pub fn start(&self, name: &str) -> Result<RecordingStatus> {
+        validate_name(name)?;
+        let mut recording = self.recording.lock().unwrap();
+        if let Some(active) = recording.as_ref() {
+            return Err(DeviceError::Other(format!(
+                "正在录制会话 '{}'，请先停止",
+                active.session.name
+            )));
+        }
+        let active = ActiveRecording {
+            session: RecordedSession {
+                name: name.to_string(),
+                recorded_at: time::now_rfc3339(),
+                duration_ms: 0,
+                actions: Vec::new(),
+            },
+            started: Instant::now(),
+        };
+        let status = recording_status(&active);
+        *recording = Some(active);
+        info!("开始录制会话 '{}'", name);
+        Ok(status)
+    }
+
+    /// 当前录制状态
+    pub fn recording(&self) -> Option<RecordingStatus> {
+        self.recording
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(recording_status)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.lock().unwrap().is_some()
+    }
+
+    /// 录制一条动作（未在录制时忽略）
+    pub fn record(&self, operator: Option<String>, actions: Vec<SessionAction>) {
+        let mut recording = self.recording.lock().unwrap();
+        let Some(active) = recording.as_mut() else {
+            return;
+        };
+        let offset_ms = active.started.elapsed().as_millis() as u64;
+        for action in actions {
+            if active.session.actions.len() >= self.max_actions {
+                warn!(
+                    "会话 '{}' 已达到最大动作数 {}，忽略后续动作",
+                    active.session.name, self.max_actions
+                );
+                return;
+            }
+            active.session.actions.push(RecordedAction {
+                offset_ms,
+                operator: operator.clone(),
+                action,
+            });
+        }
+    }
+
+    /// 停止录制并保存会话
+    pub async fn stop(&self) -> Result<SessionSummary> {
+        let active = self
+            .recording
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| DeviceError::Other("当前没有正在录制的会话".into()))?;
+        let mut session = active.session;
+        session.duration_ms = active.started.elapsed().as_millis() as u64;
+        self.save(&session).await?;
+        info!(
+            "会话 '{}' 录制完成: {} 个动作，时长 {}ms",
+            session.name,
+            session.actions.len(),
+            session.duration_ms
+        );
+        Ok(session.summary())
+    }
+
+    async fn save(&self, session: &RecordedSession) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let json = serde_json::to_vec_pretty(session)?;
+        tokio::fs::write(self.path(&session.name), json).await?;
+        Ok(())
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// 已保存的会话（按名称排序）
+    pub async fn list(&self) -> Result<Vec<SessionSummary>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut sessions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match read_session(&path).await {
+                Ok(session) => sessions.push(session.summary()),
+                Err(e) => warn!("读取会话文件 {} 失败: {}", path.display(), e),
+            }
+        }
+        sessions.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(sessions)
+    }
+
+    /// 读取会话
+    pub async fn get(&self, name: &str) -> Result<RecordedSession> {
+        validate_name(name)?;
+        let path = self.path(name);
+        if !path.exists() {
+            return Err(DeviceError::Other(format!("会话 '{}' 不存在", name)));
+        }
+        read_session(&path).await
+    }
+
+    /// 删除会话
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        validate_name(name)?;
+        match tokio::fs::remove_file(self.path(name)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(DeviceError::Other(format!("会话 '{}' 不存在", name)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 回放会话
+    ///
+    /// 正式回放在后台按 `offset_ms / speed` 的节奏执行，立即返回初始状态；演练模式逐条检查后
+    /// 直接返回完整结果。同时只能有一个正式回放。
+    pub async fn replay(&self, name: &str, speed: f64, dry_run: bool) -> Result<ReplayStatus> {
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(DeviceError::Other(format!("回放速度无效: {}", speed)));
+        }
+        let session = self.get(name).await?;
+        let mut status = ReplayStatus {
+            session: session.name.clone(),
+            speed,
+            dry_run,
+            running: !dry_run,
+            stopped: false,
+            total: session.actions.len(),
+            started_at: time::now_rfc3339(),
+            finished_at: None,
+            results: Vec::new(),
+        };
+
+        if dry_run {
+            let started = Instant::now();
+            let controller = self.controller.read().await;
+            for (index, recorded) in session.actions.iter().enumerate() {
+                let result = recorded.action.check(&controller).await;
+                status
+                    .results
+                    .push(action_result(index, &recorded.action, result, started));
+            }
+            status.finished_at = Some(time::now_rfc3339());
+            return Ok(status);
+        }
+
+        {
+            let mut replay = self.replay.lock().unwrap();
+            if let Some(current) = replay.as_ref().filter(|r| r.running) {
+                return Err(DeviceError::Other(format!(
+                    "正在回放会话 '{}'，请先停止",
+                    current.session
+                )));
+            }
+            *replay = Some(status.clone());
+        }
+        let token = CancellationToken::new();
+        if let Some(previous) = self.replay_token.lock().unwrap().replace(token.clone()) {
+            previous.cancel();
+        }
+
+        info!(
+            "开始回放会话 '{}'（{} 个动作，{} 倍速）",
+            session.name,
+            session.actions.len(),
+            speed
+        );
+        tokio::spawn(run_replay(
+            self.controller.clone(),
+            self.replay.clone(),
+            session,
+            speed,
+            token,
+        ));
+        Ok(status)
+    }
+
+    /// 最近一次正式回放的状态
+    pub fn replay_status(&self) -> Option<ReplayStatus> {
+        self.replay.lock().unwrap().clone()
+    }
+
+    /// 停止正在进行的回放，没有回放时返回 `false`
+    pub fn stop_replay(&self) -> bool {
+        let running = self
+            .replay
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|r| r.running);
+        if running {
+            if let Some(token) = self.replay_token.lock().unwrap().as_ref() {
+                token.cancel();
+            }
+        }
+        running
+    }
+}
+
+async fn run_replay(
+    controller: SharedController,
+    status: Arc<Mutex<Option<ReplayStatus>>>,
+    session: RecordedSession,
+    speed: f64,
+    token: CancellationToken,
+) {
+    let started = Instant::now();
+    let operator = format!("回放:{}", session.name);
+    let mut stopped = false;
+    for (index, recorded) in session.actions.iter().enumerate() {
+        let due = started + Duration::from_secs_f64(recorded.offset_ms as f64 / 1000.0 / speed);
+        tokio::select! {
+            _ = token.cancelled() => {
+                stopped = true;
+                break;
+            }
+            _ = tokio::time::sleep_until(due.into()) => {}
+        }
+        let result = {
+            let controller = controller.read().await;
+            recorded.action.execute(&controller, &operator).await
+        };
+        if let Err(e) = &result {
+            warn!("回放会话 '{}' 第 {} 个动作失败: {}", session.name, index, e);
+        }
+        let result = action_result(index, &recorded.action, result, started);
+        if let Some(status) = status.lock().unwrap().as_mut() {
+            status.results.push(result);
+        }
+    }
+
+    if let Some(status) = status.lock().unwrap().as_mut() {
+        status.running = false;
+        status.stopped = stopped;
+        status.finished_at = Some(time::now_rfc3339());
+    }
+    info!(
+        "会话 '{}' 回放{}",
+        session.name,
+        if stopped { "已停止" } else { "完成" }
+    );
+}
+
+fn action_result(
+    index: usize,
+    action: &SessionAction,
+    result: Result<()>,
+    started: Instant,
+) -> ReplayActionResult {
+    ReplayActionResult {
+        index,
+        action: action.clone(),
+        success: result.is_ok(),
+        error: result.err().map(|e| e.to_string()),
+        at_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+fn recording_status(active: &ActiveRecording) -> RecordingStatus {
+    RecordingStatus {
+        name: active.session.name.clone(),
+        started_at: active.session.recorded_at.clone(),
+        actions: active.session.actions.len(),
+    }
+}
+
+async fn read_session(path: &std::path::Path) -> Result<RecordedSession> {
+    let content = tokio::fs::read(path).await?;
+    Ok(serde_json::from_slice(&content)?)
+}
+
+/// 会话名称用作文件名：只允许字母、数字、`-` 与 `_`
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !RESERVED_NAMES.contains(&name);
+    if valid {
+        Ok(())
+    } else {
+        Err(DeviceError::Other(format!(
+            "会话名称无效: '{}'（1-{} 个字母、数字、- 或 _）",
+            name, MAX_NAME_LEN
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 测试用写入值解析：数值原样，状态名称只认 "on"
+    fn write_value(_: u32, value: &serde_json::Value) -> Result<f64> {
+        match value {
+            serde_json::Value::String(label) if label == "on" => Ok(1.0),
+            value => value
+                .as_f64()
+                .ok_or_else(|| DeviceError::Other(format!("写入值无效: {}", value))),
+        }
+    }
+
+    #[test]
+    fn parses_recordable_requests() {
+        let actions = SessionAction::from_request(
+            "/writeMany",
+            &json!({ "items": [
+                { "id": 1, "value": 21.5 },
+                { "id": 2, "value": "on" },
+                { "id": 3, "value": "half" }
+            ] }),
+            &write_value,
+        );
+        assert_eq!(
+            actions,
+            vec![
+                SessionAction::Write {
+                    global_id: 1,
+                    value: 21.5
+                },
+                SessionAction::Write {
+                    global_id: 2,
+                    value: 1.0
+                },
+            ]
+        );
+        assert_eq!(
+            SessionAction::from_request("/scene", &json!({ "name": "开馆" }), &write_value),
+            vec![SessionAction::Scene {
+                name: "开馆".into()
+            }]
+        );
+        assert!(
+            SessionAction::from_request("/read", &json!({ "global_id": 1 }), &write_value)
+                .is_empty()
+        );
+
+        let recorded = RecordedAction {
+            offset_ms: 1200,
+            operator: Some("alice".into()),
+            action: SessionAction::CallMethod {
+                channel_id: 3,
+                method_name: "power_on".into(),
+                arguments: json!({}),
+            },
+        };
+        let value = serde_json::to_value(&recorded).unwrap();
+        assert_eq!(value["kind"], "call_method");
+        let parsed: RecordedAction = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.action, recorded.action);
+
+        assert!(validate_name("show-1_a").is_ok());
+        assert!(validate_name("../x").is_err() && validate_name("replay").is_err());
+    }
+
+    #[tokio::test]
+    async fn replays_writes_in_node_units() {
+        let config: crate::config::Config = serde_json::from_value(json!({
+            "channels": [{
+                "channel_id": 9201, "enable": true, "statute": "mock",
+                "arguments": { "persist": false }
+            }],
+            "nodes": [{
+                "global_id": 1, "channel_id": 9201, "id": 1, "alias": "展厅温度设定",
+                "metadata": { "unit": "°C" },
+                "transform": [{ "op": "scale", "factor": 0.1 }]
+            }],
+            "scenes": [],
+            "web_server": { "port": 8080 }
+        }))
+        .unwrap();
+        let controller = DeviceController::new(config).await.unwrap();
+
+        // 英制请求 70.7°F 按节点单位录制为 21.5°C
+        let actions = SessionAction::from_request(
+            "/write",
+            &json!({ "global_id": 1, "value": 70.7 }),
+            &|global_id, value| {
+                let value = serde_json::from_value(value.clone())?;
+                crate::web::device_api::resolve_write_value(
+                    &controller,
+                    global_id,
+                    value,
+                    Some(crate::config::UnitSystem::Imperial),
+                )
+            },
+        );
+        let [SessionAction::Write {
+            global_id: 1,
+            value,
+        }] = actions.as_slice()
+        else {
+            panic!("应录制一条写入: {:?}", actions);
+        };
+        assert!((value - 21.5).abs() < 1e-9);
+
+        // 回放保留小数：设备原始值 = 21.5 / 0.1
+        actions[0].execute(&controller, "alice").await.unwrap();
+        let raw = controller
+            .call_channel_method(9201, "get_value", json!({ "addr": 1 }))
+            .await
+            .unwrap();
+        assert_eq!(raw["value"], 215);
+
+        controller.shutdown().await;
+    }
+}
@@ -0,0 +1,41 @@
+//! 审计日志
+//!
+//! 绕过节点模型直接操作设备等敏感操作追加到 `logs/audit.log`（每行一条 JSON），
+//! 同时以 `audit` 为 target 输出 info 日志。写入失败只记录警告，不影响操作本身。
+
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// 审计文件名
+const AUDIT_FILE: &str = "audit.log";
+
+/// 串行化写入，避免并发请求的记录交错
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 记录一条审计日志
+pub fn record(actor: &str, action: &str, detail: Value) {
+    let entry = json!({
+        "timestamp": chrono::Local::now().to_rfc3339(),
+        "actor": actor,
+        "action": action,
+        "detail": detail,
+    });
+    info!(target: "audit", "{} {}: {}", actor, action, entry["detail"]);
+
+    if let Err(e) = append(&entry) {
+        warn!("写入审计日志失败: {}", e);
+    }
+}
+
+fn append(entry: &Value) -> std::io::Result<()> {
+    let dir = super::crash::default_logs_dir();
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    std::fs::create_dir_all(&dir)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(AUDIT_FILE))?;
+    writeln!(file, "{}", entry)
+}
@@ -0,0 +1,231 @@
+//! 校验算法库
+//!
+//! 二进制协议常用的 CRC 与累加类校验，按名称（如 `crc16_modbus`、`lrc`）在协议配置中选择。
+//! 校验值统一以 `u32` 返回，实际宽度见 [`ChecksumAlgorithm::width`]。
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 校验算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum ChecksumAlgorithm {
+    /// CRC-16/MODBUS（多项式 0x8005 反射，初值 0xFFFF）
+    Crc16Modbus,
+    /// CRC-16/CCITT-FALSE（多项式 0x1021，初值 0xFFFF）
+    Crc16CcittFalse,
+    /// CRC-16/XMODEM（多项式 0x1021，初值 0）
+    Crc16Xmodem,
+    /// CRC-16/KERMIT（多项式 0x1021 反射，初值 0）
+    Crc16Kermit,
+    /// CRC-16/ARC（多项式 0x8005 反射，初值 0）
+    Crc16Arc,
+    /// CRC-32（IEEE 802.3，以太网、ZIP）
+    Crc32,
+    /// 纵向冗余校验：字节和的二进制补码（Modbus ASCII）
+    Lrc,
+    /// 逐字节异或
+    Xor,
+    /// 字节和取低 8 位
+    Sum,
+    /// 字节和取反（反码）
+    SumComplement,
+}
+
+/// CRC 参数（Rocksoft 模型）
+struct CrcParams {
+    width: u32,
+    poly: u32,
+    init: u32,
+    reflect: bool,
+    xor_out: u32,
+}
+
+impl ChecksumAlgorithm {
+    pub const ALL: [ChecksumAlgorithm; 10] = [
+        ChecksumAlgorithm::Crc16Modbus,
+        ChecksumAlgorithm::Crc16CcittFalse,
+        ChecksumAlgorithm::Crc16Xmodem,
+        ChecksumAlgorithm::Crc16Kermit,
+        ChecksumAlgorithm::Crc16Arc,
+        ChecksumAlgorithm::Crc32,
+        ChecksumAlgorithm::Lrc,
+        ChecksumAlgorithm::Xor,
+        ChecksumAlgorithm::Sum,
+        ChecksumAlgorithm::SumComplement,
+    ];
+
+    /// 配置中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc16Modbus => "crc16_modbus",
+            ChecksumAlgorithm::Crc16CcittFalse => "crc16_ccitt_false",
+            ChecksumAlgorithm::Crc16Xmodem => "crc16_xmodem",
+            ChecksumAlgorithm::Crc16Kermit => "crc16_kermit",
+            ChecksumAlgorithm::Crc16Arc => "crc16_arc",
+            ChecksumAlgorithm::Crc32 => "crc32",
+            ChecksumAlgorithm::Lrc => "lrc",
+            ChecksumAlgorithm::Xor => "xor",
+            ChecksumAlgorithm::Sum => "sum",
+            ChecksumAlgorithm::SumComplement => "sum_complement",
+        }
+    }
+
+    /// 校验值字节数
+    pub fn width(self) -> usize {
+        match self.crc_params() {
+            Some(params) => params.width as usize / 8,
+            None => 1,
+        }
+    }
+
+    /// 报文中惯例为低字节在前（反射型 CRC-16 与 CRC-32）
+    pub fn little_endian_by_default(self) -> bool {
+        self.crc_params().is_some_and(|p| p.reflect)
+    }
+
+    fn crc_params(self) -> Option<CrcParams> {
+        let crc16 = |poly, init, reflect| CrcParams {
+            width: 16,
+            poly,
+            init,
+            reflect,
+            xor_out: 0,
+        };
+        match self {
+            ChecksumAlgorithm::Crc16Modbus => Some(crc16(0x8005, 0xFFFF, true)),
+            ChecksumAlgorithm::Crc16CcittFalse => Some(crc16(0x1021, 0xFFFF, false)),
+            ChecksumAlgorithm::Crc16Xmodem => Some(crc16(0x1021, 0, false)),
+            ChecksumAlgorithm::Crc16Kermit => Some(crc16(0x1021, 0, true)),
+            ChecksumAlgorithm::Crc16Arc => Some(crc16(0x8005, 0, true)),
+            ChecksumAlgorithm::Crc32 => Some(CrcParams {
+                width: 32,
+                poly: 0x04C1_1DB7,
+                init: 0xFFFF_FFFF,
+                reflect: true,
+                xor_out: 0xFFFF_FFFF,
+            }),
+            _ => None,
+        }
+    }
+
+    /// 计算校验值
+    pub fn compute(self, data: &[u8]) -> u32 {
+        if let Some(params) = self.crc_params() {
+            return crc(&params, data);
+        }
+        let sum = data.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+        let value = match self {
+            ChecksumAlgorithm::Lrc => sum.wrapping_neg(),
+            ChecksumAlgorithm::Xor => data.iter().fold(0u8, |acc, b| acc ^ b),
+            ChecksumAlgorithm::SumComplement => !sum,
+            _ => sum,
+        };
+        value as u32
+    }
+}
+
+/// 逐位计算 CRC（协议帧通常只有几十字节，无需查表）
+fn crc(params: &CrcParams, data: &[u8]) -> u32 {
+    let mask = if params.width == 32 {
+        u32::MAX
+    } else {
+        (1 << params.width) - 1
+    };
+    let mut crc = params.init;
+    if params.reflect {
+        let poly = params.poly.reverse_bits() >> (32 - params.width);
+        for byte in data {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ poly
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+    } else {
+        let top = 1 << (params.width - 1);
+        for byte in data {
+            crc ^= (*byte as u32) << (params.width - 8);
+            for _ in 0..8 {
+                crc = if crc & top != 0 {
+                    (crc << 1) ^ params.poly
+                } else {
+                    crc << 1
+                };
+            }
+            crc &= mask;
+        }
+    }
+    (crc ^ params.xor_out) & mask
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = String;
+
+    /// 按名称选择算法，忽略大小写，`-` 与 `_` 等价
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase().replace('-', "_");
+        Self::ALL
+            .into_iter()
+            .find(|a| a.name() == normalized)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|a| a.name()).collect();
+                format!("未知的校验算法 '{}'，可选: {}", s, names.join(", "))
+            })
+    }
+}
+
+impl TryFrom<String> for ChecksumAlgorithm {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_standard_check_values() {
+        // 各算法对 "123456789" 的标准校验值
+        let expected = [
+            (ChecksumAlgorithm::Crc16Modbus, 0x4B37),
+            (ChecksumAlgorithm::Crc16CcittFalse, 0x29B1),
+            (ChecksumAlgorithm::Crc16Xmodem, 0x31C3),
+            (ChecksumAlgorithm::Crc16Kermit, 0x2189),
+            (ChecksumAlgorithm::Crc16Arc, 0xBB3D),
+            (ChecksumAlgorithm::Crc32, 0xCBF4_3926),
+            (ChecksumAlgorithm::Lrc, 0x23),
+            (ChecksumAlgorithm::Xor, 0x31),
+            (ChecksumAlgorithm::Sum, 0xDD),
+            (ChecksumAlgorithm::SumComplement, 0x22),
+        ];
+        for (algorithm, value) in expected {
+            assert_eq!(algorithm.compute(b"123456789"), value, "{}", algorithm);
+            assert_eq!(algorithm.name().parse::<ChecksumAlgorithm>(), Ok(algorithm));
+        }
+        // Modbus RTU 读保持寄存器请求 01 03 00 00 00 0A 的 CRC 按低字节在前发送为 C5 CD
+        assert_eq!(
+            ChecksumAlgorithm::Crc16Modbus.compute(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A]),
+            0xCDC5
+        );
+        assert_eq!("CRC16-Modbus".parse(), Ok(ChecksumAlgorithm::Crc16Modbus));
+        assert!("crc8".parse::<ChecksumAlgorithm>().is_err());
+        assert_eq!(
+            serde_json::from_str::<ChecksumAlgorithm>("\"Crc16-Kermit\"").unwrap(),
+            ChecksumAlgorithm::Crc16Kermit
+        );
+    }
+}
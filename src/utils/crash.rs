@@ -0,0 +1,276 @@
+//! 崩溃报告
+//!
+//! 进程启动时通过 [`install_panic_hook`] 安装 panic hook，任意线程 / 异步任务 panic 时：
+//! 1. 将 panic 信息与完整调用栈写入崩溃目录下的独立文件（`crash-<时间>-<pid>-<序号>.log`）
+//! 2. 记录 error 日志
+//! 3. 调用已注册的通知函数（设备控制器借此发出最后一条事件）
+//! 4. 配置了 `crash.webhook_url` 时同步 POST 崩溃摘要（带超时）
+//! 5. `exit_on_panic` 生效时以 [`CRASH_EXIT_CODE`] 退出进程，由服务管理器负责重启
+
+use serde::Serialize;
+use std::any::Any;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Once, RwLock};
+use std::time::Duration;
+use tracing::error;
+
+use crate::config::CrashConfig;
+
+/// panic 后主动退出时的进程退出码
+pub const CRASH_EXIT_CODE: i32 = 101;
+
+/// 默认 Webhook 超时
+const DEFAULT_WEBHOOK_TIMEOUT_MS: u64 = 3000;
+
+type Notifier = Box<dyn Fn(&CrashReport) + Send + Sync>;
+
+static SETTINGS: once_cell::sync::Lazy<RwLock<CrashConfig>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(CrashConfig::default()));
+static NOTIFIER: once_cell::sync::Lazy<RwLock<Option<Notifier>>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(None));
+/// 是否作为 Windows 服务运行
+static SERVICE_MODE: AtomicBool = AtomicBool::new(false);
+/// 崩溃文件序号（同一秒内多次 panic 时避免文件名冲突）
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+static INSTALL: Once = Once::new();
+
+/// 崩溃摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReport {
+    /// 发生时间（RFC 3339）
+    pub timestamp: String,
+    pub version: String,
+    pub pid: u32,
+    /// 线程名称
+    pub thread: String,
+    /// panic 信息
+    pub message: String,
+    /// 源码位置（文件:行:列）
+    pub location: Option<String>,
+    /// 崩溃文件路径（写入失败时为空）
+    pub report_file: Option<String>,
+    /// 调用栈（仅写入崩溃文件，不随通知发送）
+    #[serde(skip)]
+    pub backtrace: String,
+}
+
+impl CrashReport {
+    /// 采集当前线程的 panic 信息与调用栈
+    fn capture(message: String, location: Option<String>) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            pid: std::process::id(),
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("<unnamed>")
+                .to_string(),
+            message,
+            location,
+            report_file: None,
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        }
+    }
+
+    /// 崩溃文件内容
+    fn render(&self) -> String {
+        format!(
+            "=== dm-rust 崩溃报告 ===\n时间: {}\n版本: {}\n进程: {}\n线程: {}\n位置: {}\n信息: {}\n\n调用栈:\n{}\n",
+            self.timestamp,
+            self.version,
+            self.pid,
+            self.thread,
+            self.location.as_deref().unwrap_or("未知"),
+            self.message,
+            self.backtrace
+        )
+    }
+}
+
+/// 安装 panic hook（重复调用无副作用），原有 hook（标准错误输出）仍会执行
+pub fn install_panic_hook() {
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let report = CrashReport::capture(
+                payload_message(info.payload()),
+                info.location().map(|l| l.to_string()),
+            );
+            handle_panic(report);
+            previous(info);
+            if exit_on_panic() {
+                std::process::exit(CRASH_EXIT_CODE);
+            }
+        }));
+    });
+}
+
+/// 应用崩溃报告配置
+pub fn configure(config: &CrashConfig) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+}
+
+/// 标记为 Windows 服务运行（默认 panic 后退出，并把崩溃目录定位到程序目录）
+pub fn set_service_mode() {
+    SERVICE_MODE.store(true, Ordering::Relaxed);
+}
+
+/// 注册 panic 通知函数（后注册的覆盖先注册的）
+///
+/// 通知函数在 panic 线程中同步执行，不得阻塞或再次 panic。
+pub fn set_notifier(notifier: impl Fn(&CrashReport) + Send + Sync + 'static) {
+    *NOTIFIER.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(notifier));
+}
+
+/// 是否作为 Windows 服务运行
+pub fn is_service_mode() -> bool {
+    SERVICE_MODE.load(Ordering::Relaxed)
+}
+
+fn settings() -> CrashConfig {
+    SETTINGS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn exit_on_panic() -> bool {
+    settings().exit_on_panic.unwrap_or_else(is_service_mode)
+}
+
+/// 默认日志目录 `logs`（Windows 服务的工作目录是 System32，改用程序所在目录）
+pub(crate) fn default_logs_dir() -> PathBuf {
+    let base = if is_service_mode() {
+        std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(Path::to_path_buf))
+    } else {
+        None
+    };
+    base.unwrap_or_default().join("logs")
+}
+
+/// 崩溃报告目录
+fn report_dir(config: &CrashConfig) -> PathBuf {
+    match config.dir {
+        Some(ref dir) => PathBuf::from(dir),
+        None => default_logs_dir().join("crash"),
+    }
+}
+
+fn handle_panic(mut report: CrashReport) {
+    let config = settings();
+
+    match write_report(&report_dir(&config), &report) {
+        Ok(path) => report.report_file = Some(path.to_string_lossy().into_owned()),
+        Err(e) => error!("写入崩溃报告失败: {}", e),
+    }
+
+    error!(
+        "线程 '{}' panic: {} ({}), 崩溃报告: {}",
+        report.thread,
+        report.message,
+        report.location.as_deref().unwrap_or("未知位置"),
+        report.report_file.as_deref().unwrap_or("未写入")
+    );
+
+    if let Some(ref notify) = *NOTIFIER.read().unwrap_or_else(|e| e.into_inner()) {
+        notify(&report);
+    }
+
+    if let Some(ref url) = config.webhook_url {
+        let timeout = Duration::from_millis(
+            config
+                .webhook_timeout_ms
+                .unwrap_or(DEFAULT_WEBHOOK_TIMEOUT_MS),
+        );
+        if let Err(e) = send_webhook(url, timeout, &report) {
+            error!("崩溃 Webhook 发送失败: {}", e);
+        }
+    }
+}
+
+/// 写入崩溃文件，返回文件路径
+fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}-{}-{}.log",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        report.pid,
+        SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut file = std::fs::File::create(&path)?;
+    file.write_all(report.render().as_bytes())?;
+    file.sync_all()?;
+    Ok(path)
+}
+
+/// 在独立线程中同步发送 Webhook（panic 线程可能是运行时工作线程，不能直接 block_on）
+fn send_webhook(url: &str, timeout: Duration, report: &CrashReport) -> Result<(), String> {
+    let url = url.to_string();
+    let body = serde_json::to_value(report).map_err(|e| e.to_string())?;
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())
+            .and_then(|runtime| {
+                runtime.block_on(async {
+                    let client = reqwest::Client::builder()
+                        .timeout(timeout)
+                        .build()
+                        .map_err(|e| e.to_string())?;
+                    client
+                        .post(&url)
+                        .json(&serde_json::json!({ "event": "crash", "data": body }))
+                        .send()
+                        .await
+                        .and_then(|response| response.error_for_status())
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+            });
+        let _ = tx.send(result);
+    });
+
+    rx.recv_timeout(timeout + Duration::from_millis(500))
+        .map_err(|_| "超时".to_string())?
+}
+
+/// 提取 panic 信息文本
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<非字符串 panic 信息>".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_file_contains_message_and_backtrace() {
+        let dir = tempfile::tempdir().unwrap();
+        let payload: Box<dyn Any + Send> = Box::new(String::from("通道 3 状态异常"));
+        let report = CrashReport::capture(
+            payload_message(payload.as_ref()),
+            Some("src/device/mod.rs:1:1".to_string()),
+        );
+
+        let path = write_report(dir.path(), &report).unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("信息: 通道 3 状态异常"));
+        assert!(content.contains("位置: src/device/mod.rs:1:1"));
+        assert!(content.contains("调用栈:"));
+        assert!(!serde_json::to_value(&report)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .contains_key("backtrace"));
+    }
+}
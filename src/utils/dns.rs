@@ -0,0 +1,123 @@
+//! 共享主机名解析
+//!
+//! 所有协议连接设备前通过 [`resolve`] 解析地址：
+//! 1. IP 字面量直接返回
+//! 2. 查询配置中的主机别名表 (`dns.hosts`)
+//! 3. 查询解析缓存（TTL 由 `dns.cache_ttl_ms` 控制）
+//! 4. 调用系统 DNS 并写入缓存
+//!
+//! HTTP 客户端通过 [`CachingResolver`] 使用同一套逻辑。
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::config::DnsConfig;
+use crate::utils::{DeviceError, Result};
+
+struct DnsState {
+    /// 主机别名表（小写主机名 -> IP）
+    hosts: HashMap<String, IpAddr>,
+    ttl: Duration,
+    /// 解析缓存（小写主机名 -> (地址列表, 解析时间)）
+    cache: HashMap<String, (Vec<IpAddr>, Instant)>,
+}
+
+static DNS: once_cell::sync::Lazy<RwLock<DnsState>> = once_cell::sync::Lazy::new(|| {
+    let defaults = DnsConfig::default();
+    RwLock::new(DnsState {
+        hosts: HashMap::new(),
+        ttl: Duration::from_millis(defaults.cache_ttl_ms),
+        cache: HashMap::new(),
+    })
+});
+
+/// 应用解析配置（同时清空缓存）
+pub fn configure(config: &DnsConfig) -> Result<()> {
+    let mut hosts = HashMap::new();
+    for (name, ip) in &config.hosts {
+        let ip: IpAddr = ip.trim().parse().map_err(|_| {
+            DeviceError::ConfigError(format!("主机别名 '{}' 的 IP 地址无效: '{}'", name, ip))
+        })?;
+        hosts.insert(name.trim().to_ascii_lowercase(), ip);
+    }
+
+    let mut state = DNS.write().unwrap();
+    state.hosts = hosts;
+    state.ttl = Duration::from_millis(config.cache_ttl_ms);
+    state.cache.clear();
+    Ok(())
+}
+
+/// 解析主机名为 IP 地址列表
+pub async fn resolve_host(host: &str) -> Result<Vec<IpAddr>> {
+    let host = host.trim().trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![ip]);
+    }
+
+    let key = host.to_ascii_lowercase();
+    {
+        let state = DNS.read().unwrap();
+        if let Some(ip) = state.hosts.get(&key) {
+            return Ok(vec![*ip]);
+        }
+        if let Some((ips, resolved_at)) = state.cache.get(&key) {
+            if resolved_at.elapsed() < state.ttl {
+                return Ok(ips.clone());
+            }
+        }
+    }
+
+    let started = Instant::now();
+    let ips: Vec<IpAddr> = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| DeviceError::ConnectionError(format!("解析主机 '{}' 失败: {}", host, e)))?
+        .map(|addr| addr.ip())
+        .collect();
+    if ips.is_empty() {
+        return Err(DeviceError::ConnectionError(format!(
+            "解析主机 '{}' 未返回地址",
+            host
+        )));
+    }
+
+    let elapsed = started.elapsed();
+    if elapsed > Duration::from_secs(1) {
+        warn!(
+            "解析主机 '{}' 耗时 {:?}，建议在 dns.hosts 中固定地址",
+            host, elapsed
+        );
+    } else {
+        debug!("解析主机 '{}' -> {:?} ({:?})", host, ips, elapsed);
+    }
+
+    let mut state = DNS.write().unwrap();
+    if !state.ttl.is_zero() {
+        state.cache.insert(key, (ips.clone(), Instant::now()));
+    }
+    Ok(ips)
+}
+
+/// 解析 主机:端口 为套接字地址（取第一个地址）
+pub async fn resolve(host: &str, port: u16) -> Result<SocketAddr> {
+    let ips = resolve_host(host).await?;
+    Ok(SocketAddr::new(ips[0], port))
+}
+
+/// reqwest 使用的解析器
+pub struct CachingResolver;
+
+impl reqwest::dns::Resolve for CachingResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let ips = resolve_host(&host).await?;
+            let addrs: reqwest::dns::Addrs =
+                Box::new(ips.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
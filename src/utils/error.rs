@@ -1,5 +1,8 @@
 /// 错误类型定义
+use serde::Serialize;
+use std::fmt;
 use thiserror::Error;
+use utoipa::ToSchema;
 
 #[derive(Error, Debug)]
 pub enum DeviceError {
@@ -21,8 +24,17 @@ pub enum DeviceError {
     #[error("配置错误: {0}")]
     ConfigError(String),
 
-    #[error("依赖条件未满足")]
-    DependencyNotMet,
+    #[error("依赖条件未满足: {}", describe_unmet(.0))]
+    DependencyNotMet(Vec<UnmetDependency>),
+
+    #[error("通道 {0} 处于计划离线时段")]
+    ScheduledOffline(u32),
+
+    #[error("通道 {0} 已停用")]
+    ChannelDisabled(u32),
+
+    #[error("通道 {0} 正在切换配置，请稍后重试")]
+    ChannelReloading(u32),
 
     #[error("IO错误: {0}")]
     Io(#[from] std::io::Error),
@@ -36,6 +48,44 @@ pub enum DeviceError {
 
 pub type Result<T> = std::result::Result<T, DeviceError>;
 
+/// 未满足的依赖条件说明
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UnmetDependency {
+    /// 依赖节点全局 ID
+    pub global_id: u32,
+    /// 依赖节点别名
+    pub alias: String,
+    /// 检查的条件：`value`（节点值）或 `online`（在线状态）
+    pub condition: String,
+    /// 期望值
+    pub expected: serde_json::Value,
+    /// 实际值（未知时为 null）
+    pub actual: serde_json::Value,
+}
+
+impl fmt::Display for UnmetDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let actual = if self.actual.is_null() {
+            "未知".to_string()
+        } else {
+            self.actual.to_string()
+        };
+        write!(
+            f,
+            "节点 {}（{}）{} 期望 {}，实际 {}",
+            self.global_id, self.alias, self.condition, self.expected, actual
+        )
+    }
+}
+
+fn describe_unmet(unmet: &[UnmetDependency]) -> String {
+    unmet
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("；")
+}
+
 /// 错误代码常量
 pub mod error_codes {
     pub const SUCCESS: i32 = 0;
@@ -46,4 +96,12 @@ pub mod error_codes {
     pub const DEPENDENCY_NOT_MET: i32 = 30004;
     pub const INVALID_PARAMS: i32 = 400;
     pub const CROSSING: i32 = 30005;
+    /// 节点写入需要确认，已生成待确认请求
+    pub const CONFIRMATION_REQUIRED: i32 = 30007;
+    /// 未认证或令牌无效
+    pub const UNAUTHORIZED: i32 = 30008;
+    /// 权限不足
+    pub const FORBIDDEN: i32 = 30009;
+    /// 登录失败次数过多，账号已临时锁定
+    pub const ACCOUNT_LOCKED: i32 = 30010;
 }
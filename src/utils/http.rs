@@ -0,0 +1,100 @@
+//! 共享 HTTP 客户端工厂
+//!
+//! 所有基于 HTTP 的协议通过 [`client_from_params`] 创建 reqwest 客户端，配置优先级：
+//! 协议内置默认值 < 全局配置 `http` 段 < 通道参数 `http` 对象。
+//!
+//! # 用法
+//! ```ignore
+//! use crate::utils::http;
+//!
+//! let driver_defaults = HttpClientConfig {
+//!     timeout_ms: Some(30_000),
+//!     ..Default::default()
+//! };
+//! let client = http::client_from_params(params, &driver_defaults)?;
+//! ```
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::config::HttpClientConfig;
+use crate::utils::{dns, DeviceError, Result};
+
+/// 全局默认配置（由 DeviceController 初始化时从配置文件设置）
+static GLOBAL_DEFAULTS: once_cell::sync::Lazy<RwLock<HttpClientConfig>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(HttpClientConfig::default()));
+
+/// 设置全局默认配置
+pub fn set_global_defaults(config: HttpClientConfig) {
+    *GLOBAL_DEFAULTS.write().unwrap() = config;
+}
+
+/// 获取全局默认配置
+pub fn global_defaults() -> HttpClientConfig {
+    GLOBAL_DEFAULTS.read().unwrap().clone()
+}
+
+/// 按通道参数中的 `http` 对象创建客户端
+pub fn client_from_params(
+    params: &HashMap<String, Value>,
+    driver_defaults: &HttpClientConfig,
+) -> Result<reqwest::Client> {
+    let channel_overrides = match params.get("http") {
+        Some(v) => serde_json::from_value::<HttpClientConfig>(v.clone())
+            .map_err(|e| DeviceError::ConfigError(format!("http 参数解析失败: {}", e)))?,
+        None => HttpClientConfig::default(),
+    };
+    let config = driver_defaults
+        .merge(&global_defaults())
+        .merge(&channel_overrides);
+    build_client(&config)
+}
+
+/// 按配置创建客户端
+pub fn build_client(config: &HttpClientConfig) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().dns_resolver(Arc::new(dns::CachingResolver));
+
+    if let Some(ms) = config.timeout_ms {
+        builder = builder.timeout(Duration::from_millis(ms));
+    }
+    if let Some(ms) = config.connect_timeout_ms {
+        builder = builder.connect_timeout(Duration::from_millis(ms));
+    }
+    if let Some(n) = config.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(n);
+    }
+    if let Some(ms) = config.pool_idle_timeout_ms {
+        builder = builder.pool_idle_timeout(Duration::from_millis(ms));
+    }
+    match config.proxy.as_deref() {
+        Some("") => builder = builder.no_proxy(),
+        Some(url) => {
+            let proxy = reqwest::Proxy::all(url)
+                .map_err(|e| DeviceError::ConfigError(format!("代理地址无效 '{}': {}", url, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        None => {}
+    }
+    if let Some(path) = &config.ca_bundle {
+        let pem = std::fs::read(path).map_err(|e| {
+            DeviceError::ConfigError(format!("读取 CA 证书文件 '{}' 失败: {}", path, e))
+        })?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            DeviceError::ConfigError(format!("解析 CA 证书文件 '{}' 失败: {}", path, e))
+        })?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if config.accept_invalid_certs.unwrap_or(false) {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    debug!("创建 HTTP 客户端: {:?}", config);
+    builder
+        .build()
+        .map_err(|e| DeviceError::ConfigError(format!("创建 HTTP 客户端失败: {}", e)))
+}
@@ -0,0 +1,119 @@
+//! 模拟 TCP 对端（仅测试使用）
+//!
+//! 驱动测试在本机随机端口启动模拟设备 / 服务端：每个连接读取一个请求，按测试给出的脚本
+//! 应答后关闭连接，收到的请求按顺序记录，供测试检查实际发出的报文。
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 多段应答之间的间隔，用于验证驱动按帧边界而不是按读取次数拆包
+const CHUNK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// 模拟 TCP 对端
+pub(crate) struct MockPeer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl MockPeer {
+    /// 启动对端，一次读取到的数据视为一个完整请求
+    ///
+    /// `reply` 根据请求返回应答分段，依次写出，分段之间间隔 [`CHUNK_INTERVAL`]。
+    pub(crate) async fn start<F>(reply: F) -> Self
+    where
+        F: Fn(&[u8]) -> Vec<Vec<u8>> + Send + Sync + 'static,
+    {
+        Self::start_framed(|_| true, reply).await
+    }
+
+    /// 启动对端，持续读取直到 `complete` 判断已收到完整请求（如带请求体的 HTTP 请求）
+    pub(crate) async fn start_framed<C, F>(complete: C, reply: F) -> Self
+    where
+        C: Fn(&[u8]) -> bool + Send + Sync + 'static,
+        F: Fn(&[u8]) -> Vec<Vec<u8>> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let complete = Arc::new(complete);
+        let reply = Arc::new(reply);
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let log = log.clone();
+                let complete = complete.clone();
+                let reply = reply.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut chunk = [0u8; 1024];
+                    loop {
+                        let n = stream.read(&mut chunk).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&chunk[..n]);
+                        if complete(&request) {
+                            break;
+                        }
+                    }
+                    log.lock().unwrap().push(request.clone());
+                    for (index, part) in reply(&request).iter().enumerate() {
+                        if index > 0 {
+                            tokio::time::sleep(CHUNK_INTERVAL).await;
+                        }
+                        if stream.write_all(part).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        Self { addr, requests }
+    }
+
+    /// 监听端口
+    pub(crate) fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// HTTP 基础地址，如 `http://127.0.0.1:12345`
+    pub(crate) fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 已收到的请求
+    pub(crate) fn requests(&self) -> Vec<Vec<u8>> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 已收到的请求（按 UTF-8 解码，文本协议使用）
+    pub(crate) fn text_requests(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .map(|r| String::from_utf8_lossy(r).to_string())
+            .collect()
+    }
+}
+
+/// 是否已收到完整的 HTTP 请求：请求头结束且请求体达到 `Content-Length`
+pub(crate) fn http_request_complete(buf: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(buf);
+    let Some(end) = text.find("\r\n\r\n") else {
+        return false;
+    };
+    let length = text[..end]
+        .lines()
+        .find_map(|l| {
+            l.to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+        })
+        .unwrap_or(0);
+    buf.len() >= end + 4 + length
+}
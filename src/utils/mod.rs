@@ -1,5 +1,17 @@
+pub mod audit;
 pub mod cache;
+pub mod checksum;
+pub mod crash;
+pub mod dns;
 pub mod error;
+pub mod http;
 pub mod logger;
+#[cfg(test)]
+pub(crate) mod mock_peer;
+pub mod net;
+pub mod tasks;
+pub mod time;
+pub mod units;
+pub mod watchdog;
 
-pub use error::{DeviceError, Result};
+pub use error::{DeviceError, Result, UnmetDependency};
@@ -0,0 +1,209 @@
+//! 网络地址工具（IPv4 / IPv6 通用）
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tracing::warn;
+
+/// 与目标地址同协议族的本地通配地址（用于 UDP 绑定）
+pub fn unspecified_for(target: &SocketAddr, port: u16) -> SocketAddr {
+    match target {
+        SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port),
+        SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port),
+    }
+}
+
+/// 格式化 主机:端口，IPv6 字面量加方括号
+pub fn display_addr(host: &str, port: u16) -> String {
+    match host.parse::<Ipv6Addr>() {
+        Ok(_) => format!("[{}]:{}", host, port),
+        Err(_) => format!("{}:{}", host, port),
+    }
+}
+
+/// 解析 IP 字面量，IPv6 允许带方括号（如 `[fe80::1]`）
+pub fn parse_ip(s: &str) -> Option<IpAddr> {
+    s.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// 监听 TCP 端口，优先双栈 (`[::]`，同时接受 IPv4)，主机禁用 IPv6 时回退到 `0.0.0.0`
+pub fn bind_tcp_dual_stack(port: u16) -> std::io::Result<std::net::TcpListener> {
+    match bind_tcp(
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port),
+        true,
+    ) {
+        Ok(listener) => Ok(listener),
+        Err(e) => {
+            warn!("IPv6 双栈监听端口 {} 失败 ({})，回退到 IPv4", port, e);
+            bind_tcp(
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port),
+                false,
+            )
+        }
+    }
+}
+
+fn bind_tcp(addr: SocketAddr, dual_stack: bool) -> std::io::Result<std::net::TcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if dual_stack {
+        socket.set_only_v6(false)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket.into())
+}
+
+/// TCP 保活配置（通道参数 `keepalive`）
+///
+/// 用于长连接：设备断电重启后旧连接处于半开状态，写入会一直挂起，
+/// 开启保活后由内核探测并中断失效连接，协议随后重新连接。
+#[derive(Debug, Clone, Copy)]
+pub struct KeepaliveConfig {
+    pub enable: bool,
+    /// 连接空闲多久后开始探测
+    pub idle: Duration,
+    /// 探测间隔
+    pub interval: Duration,
+    /// 连续探测失败次数，达到后判定连接断开
+    pub retries: u32,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            idle: Duration::from_secs(30),
+            interval: Duration::from_secs(10),
+            retries: 3,
+        }
+    }
+}
+
+impl KeepaliveConfig {
+    /// 从通道参数解析
+    ///
+    /// `keepalive` 可以是布尔值（仅开关），或对象
+    /// `{"enable", "idle_secs", "interval_secs", "retries"}`，未配置的字段取默认值。
+    pub fn from_params(params: &HashMap<String, Value>) -> Self {
+        let mut config = Self::default();
+        match params.get("keepalive") {
+            Some(Value::Bool(enable)) => config.enable = *enable,
+            Some(Value::Object(obj)) => {
+                if let Some(enable) = obj.get("enable").and_then(|v| v.as_bool()) {
+                    config.enable = enable;
+                }
+                if let Some(secs) = obj.get("idle_secs").and_then(|v| v.as_u64()) {
+                    config.idle = Duration::from_secs(secs.max(1));
+                }
+                if let Some(secs) = obj.get("interval_secs").and_then(|v| v.as_u64()) {
+                    config.interval = Duration::from_secs(secs.max(1));
+                }
+                if let Some(retries) = obj.get("retries").and_then(|v| v.as_u64()) {
+                    config.retries = retries.clamp(1, u32::MAX as u64) as u32;
+                }
+            }
+            _ => {}
+        }
+        config
+    }
+
+    /// 判定连接失效的最长时间（空闲时间 + 全部探测）
+    pub fn dead_after(&self) -> Duration {
+        self.idle + self.interval * self.retries
+    }
+
+    /// 对已建立的连接开启 SO_KEEPALIVE
+    ///
+    /// Linux 上同时设置 TCP_USER_TIMEOUT，发送数据长时间未被确认时同样中断连接
+    /// （保活探测只在连接空闲时生效，无法覆盖写入挂起的情况）。
+    pub fn apply(&self, stream: &TcpStream) -> std::io::Result<()> {
+        if !self.enable {
+            return Ok(());
+        }
+
+        let socket = socket2::SockRef::from(stream);
+        let keepalive = socket2::TcpKeepalive::new()
+            .with_time(self.idle)
+            .with_interval(self.interval);
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        let keepalive = keepalive.with_retries(self.retries);
+        socket.set_tcp_keepalive(&keepalive)?;
+
+        #[cfg(target_os = "linux")]
+        socket.set_tcp_user_timeout(Some(self.dead_after()))?;
+
+        Ok(())
+    }
+}
+
+/// 原始命令收完响应的静默间隔：收到数据后超过该时长没有新数据即视为响应结束
+const RAW_IDLE_GAP: Duration = Duration::from_millis(200);
+
+/// 在 `timeout` 内建立到目标的 TCP 连接后立即关闭（通道延迟测试用）
+pub async fn probe_connect(host: &str, port: u16, timeout: Duration) -> crate::utils::Result<()> {
+    use crate::utils::DeviceError;
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let addr = tokio::time::timeout_at(deadline, crate::utils::dns::resolve(host, port))
+        .await
+        .map_err(|_| DeviceError::Timeout)??;
+    tokio::time::timeout_at(deadline, TcpStream::connect(addr))
+        .await
+        .map_err(|_| DeviceError::Timeout)?
+        .map_err(|e| DeviceError::ConnectionError(format!("连接 {} 失败: {}", addr, e)))?;
+    Ok(())
+}
+
+/// 建立短连接发送原始数据并收集响应（调试控制台用）
+///
+/// 连接与等待首个响应字节共用 `timeout`；设备在超时内未响应时返回空响应，
+/// 对端关闭连接或静默超过 [`RAW_IDLE_GAP`] 时结束接收。
+pub async fn exchange_raw(
+    host: &str,
+    port: u16,
+    payload: &[u8],
+    timeout: Duration,
+) -> crate::utils::Result<Vec<u8>> {
+    use crate::utils::DeviceError;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let addr = crate::utils::dns::resolve(host, port).await?;
+    let mut stream = tokio::time::timeout_at(deadline, TcpStream::connect(addr))
+        .await
+        .map_err(|_| DeviceError::Timeout)?
+        .map_err(|e| DeviceError::ConnectionError(format!("连接 {} 失败: {}", addr, e)))?;
+    stream
+        .write_all(payload)
+        .await
+        .map_err(|e| DeviceError::ConnectionError(format!("发送失败: {}", e)))?;
+
+    let mut response = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let read = if response.is_empty() {
+            tokio::time::timeout_at(deadline, stream.read(&mut buf)).await
+        } else {
+            tokio::time::timeout(RAW_IDLE_GAP, stream.read(&mut buf)).await
+        };
+        match read {
+            Ok(Ok(0)) | Err(_) => break,
+            Ok(Ok(n)) => response.extend_from_slice(&buf[..n]),
+            Ok(Err(e)) if response.is_empty() => {
+                return Err(DeviceError::ConnectionError(format!("接收失败: {}", e)))
+            }
+            Ok(Err(_)) => break,
+        }
+    }
+    Ok(response)
+}
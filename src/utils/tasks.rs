@@ -0,0 +1,118 @@
+//! 后台任务注册表
+//!
+//! 轮询、看门狗、监视器等长期运行的后台任务统一通过 [`TaskRegistry::spawn`] 启动，
+//! 通道删除或配置热重载时调用 [`TaskRegistry::shutdown`] 取消并回收，避免任务泄漏。
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// 等待任务退出的最长时间，超时后强制中止
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// 任务名称与句柄
+type NamedHandle = (String, JoinHandle<()>);
+
+#[derive(Clone, Default)]
+pub struct TaskRegistry {
+    token: CancellationToken,
+    handles: Arc<Mutex<Vec<NamedHandle>>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建子注册表：父注册表取消时子注册表中的任务一并取消
+    pub fn child(&self) -> Self {
+        Self {
+            token: self.token.child_token(),
+            handles: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// 启动后台任务，注册表取消时任务在下一个 await 点退出
+    pub fn spawn<F>(&self, name: impl Into<String>, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        if self.token.is_cancelled() {
+            debug!("任务注册表已关闭，忽略任务 '{}'", name);
+            return;
+        }
+
+        let token = self.token.clone();
+        let handle = tokio::spawn(async move {
+            tokio::select! {
+                _ = token.cancelled() => {}
+                _ = task => {}
+            }
+        });
+
+        let mut handles = self.handles.lock().unwrap();
+        handles.retain(|(_, h)| !h.is_finished());
+        handles.push((name, handle));
+    }
+
+    /// 是否已取消
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// 取消时完成的 future，供任务内部的 select 使用
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+
+    /// 当前仍在运行的任务数
+    pub fn active_count(&self) -> usize {
+        let handles = self.handles.lock().unwrap();
+        handles.iter().filter(|(_, h)| !h.is_finished()).count()
+    }
+
+    /// 取消全部任务并等待退出，超时未退出的任务被强制中止
+    pub async fn shutdown(&self) {
+        self.token.cancel();
+
+        let handles = std::mem::take(&mut *self.handles.lock().unwrap());
+        for (name, handle) in handles {
+            let abort = handle.abort_handle();
+            match tokio::time::timeout(SHUTDOWN_GRACE, handle).await {
+                Ok(_) => debug!("后台任务 '{}' 已停止", name),
+                Err(_) => {
+                    warn!("后台任务 '{}' 未在规定时间内退出，强制中止", name);
+                    abort.abort();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_stops_running_tasks() {
+        let registry = TaskRegistry::new();
+        let child = registry.child();
+        child.spawn("loop", async {
+            loop {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        assert_eq!(child.active_count(), 1);
+
+        registry.shutdown().await;
+        child.shutdown().await;
+        assert_eq!(child.active_count(), 0);
+
+        child.spawn("late", async {});
+        assert_eq!(child.active_count(), 0);
+    }
+}
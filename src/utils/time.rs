@@ -0,0 +1,51 @@
+//! 时间戳工具
+//!
+//! API 中的时间点统一为 UTC RFC3339 字符串（毫秒精度）；表示"多久之前"时另附
+//! 基于单调时钟的 `*_ms` 时长字段，不受服务器或客户端墙钟调整影响。
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// 服务启动时刻（单调时钟 + 墙钟）
+static STARTED: OnceLock<(Instant, DateTime<Utc>)> = OnceLock::new();
+
+fn started() -> &'static (Instant, DateTime<Utc>) {
+    STARTED.get_or_init(|| (Instant::now(), Utc::now()))
+}
+
+/// 记录服务启动时刻，启动时调用一次
+pub fn mark_started() {
+    started();
+}
+
+/// 服务启动时间
+pub fn started_at() -> DateTime<Utc> {
+    started().1
+}
+
+/// 服务已运行时长（单调时钟）
+pub fn uptime() -> Duration {
+    started().0.elapsed()
+}
+
+/// 格式化为 RFC3339（UTC，毫秒精度，如 `2024-05-01T08:30:00.123Z`）
+pub fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// 当前时间的 RFC3339 表示
+pub fn now_rfc3339() -> String {
+    rfc3339(Utc::now())
+}
+
+/// 将单调时钟时刻换算为 UTC 时间（当前墙钟时间减去已经过的时长）
+pub fn instant_to_utc(instant: Instant) -> DateTime<Utc> {
+    let elapsed = chrono::Duration::from_std(instant.elapsed()).unwrap_or_default();
+    Utc::now() - elapsed
+}
+
+/// 单调时钟时刻的 RFC3339 表示
+pub fn instant_rfc3339(instant: Instant) -> String {
+    rfc3339(instant_to_utc(instant))
+}
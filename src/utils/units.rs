@@ -0,0 +1,144 @@
+//! 单位制换算 - API 按调用方偏好的单位制返回和接收节点值
+//!
+//! 节点单位取自 `data_point.unit`（其次为 metadata 中的 `unit`）。读取时把节点单位的值
+//! 换算为偏好单位制中对应的单位，写入时换算回节点单位；节点单位已属于偏好单位制
+//! 或不在单位对表中时不换算。单位对表为内置单位对加上配置 `units.conversions`。
+
+use std::sync::RwLock;
+
+use crate::config::{UnitConversionConfig, UnitSystem, UnitsConfig};
+use crate::utils::{DeviceError, Result};
+
+/// 一对可互换的单位：imperial = metric × factor + offset
+#[derive(Debug, Clone, PartialEq)]
+struct UnitPair {
+    metric: String,
+    imperial: String,
+    factor: f64,
+    offset: f64,
+}
+
+/// 内置单位对（同一英制单位对应多个公制单位时，前者用于英制换算为公制）
+const BUILTIN: &[(&str, &str, f64, f64)] = &[
+    ("°C", "°F", 1.8, 32.0),
+    ("m", "ft", 3.280_84, 0.0),
+    ("cm", "in", 0.393_700_8, 0.0),
+    ("mm", "in", 0.039_370_08, 0.0),
+    ("km", "mi", 0.621_371, 0.0),
+    ("m²", "ft²", 10.763_91, 0.0),
+    ("m³", "ft³", 35.314_67, 0.0),
+    ("L", "gal", 0.264_172, 0.0),
+    ("kg", "lb", 2.204_62, 0.0),
+    ("km/h", "mph", 0.621_371, 0.0),
+    ("m/s", "ft/s", 3.280_84, 0.0),
+    ("kPa", "psi", 0.145_038, 0.0),
+];
+
+static PAIRS: once_cell::sync::Lazy<RwLock<Vec<UnitPair>>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(pairs(&[])));
+
+fn pairs(custom: &[UnitConversionConfig]) -> Vec<UnitPair> {
+    custom
+        .iter()
+        .map(|c| UnitPair {
+            metric: normalize(&c.metric).to_string(),
+            imperial: normalize(&c.imperial).to_string(),
+            factor: c.factor,
+            offset: c.offset,
+        })
+        .chain(
+            BUILTIN
+                .iter()
+                .map(|&(metric, imperial, factor, offset)| UnitPair {
+                    metric: metric.to_string(),
+                    imperial: imperial.to_string(),
+                    factor,
+                    offset,
+                }),
+        )
+        .collect()
+}
+
+/// 应用单位换算配置
+pub fn configure(config: &UnitsConfig) -> Result<()> {
+    for c in &config.conversions {
+        if !c.factor.is_finite() || c.factor == 0.0 || !c.offset.is_finite() {
+            return Err(DeviceError::ConfigError(format!(
+                "单位对 '{}' / '{}' 的 factor 或 offset 无效",
+                c.metric, c.imperial
+            )));
+        }
+    }
+    *PAIRS.write().unwrap() = pairs(&config.conversions);
+    Ok(())
+}
+
+/// 温度单位的常见写法统一为 °C / °F（不含单独的 "C" / "F"，避免与库仑、法拉等单位混淆）
+fn normalize(unit: &str) -> &str {
+    match unit.trim() {
+        "℃" | "degC" | "celsius" => "°C",
+        "℉" | "degF" | "fahrenheit" => "°F",
+        other => other,
+    }
+}
+
+/// 把 `unit` 单位的值换算为 `system` 单位制，返回换算后的值与单位；无需或无法换算时返回 `None`
+pub fn to_system(value: f64, unit: &str, system: UnitSystem) -> Option<(f64, String)> {
+    let unit = normalize(unit);
+    let pairs = PAIRS.read().unwrap();
+    match system {
+        UnitSystem::Imperial => pairs
+            .iter()
+            .find(|p| p.metric == unit)
+            .map(|p| (value * p.factor + p.offset, p.imperial.clone())),
+        UnitSystem::Metric => pairs
+            .iter()
+            .find(|p| p.imperial == unit)
+            .map(|p| ((value - p.offset) / p.factor, p.metric.clone())),
+    }
+}
+
+/// 把 `system` 单位制下的值换算回节点单位 `unit`；无需或无法换算时返回 `None`
+pub fn from_system(value: f64, unit: &str, system: UnitSystem) -> Option<f64> {
+    let unit = normalize(unit);
+    let pairs = PAIRS.read().unwrap();
+    match system {
+        UnitSystem::Imperial => pairs
+            .iter()
+            .find(|p| p.metric == unit)
+            .map(|p| (value - p.offset) / p.factor),
+        UnitSystem::Metric => pairs
+            .iter()
+            .find(|p| p.imperial == unit)
+            .map(|p| value * p.factor + p.offset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_unit_systems() {
+        let (f, unit) = to_system(25.0, "℃", UnitSystem::Imperial).unwrap();
+        assert!((f - 77.0).abs() < 1e-9);
+        assert_eq!(unit, "°F");
+        assert!((from_system(77.0, "°C", UnitSystem::Imperial).unwrap() - 25.0).abs() < 1e-9);
+
+        let (m, unit) = to_system(10.0, "ft", UnitSystem::Metric).unwrap();
+        assert!((m - 3.048).abs() < 1e-3);
+        assert_eq!(unit, "m");
+
+        // 写入换算保留小数：72°F 写入后按 °C 保存，读回仍为 72°F
+        let stored = from_system(72.0, "°C", UnitSystem::Imperial).unwrap();
+        assert!((stored - 22.222).abs() < 1e-3);
+        let (f, _) = to_system(stored, "°C", UnitSystem::Imperial).unwrap();
+        assert!((f - 72.0).abs() < 1e-9);
+
+        // 已属于偏好单位制或未知单位不换算，单独的 "C" / "F" 不视为温度
+        assert!(to_system(25.0, "°C", UnitSystem::Metric).is_none());
+        assert!(to_system(3.0, "lux", UnitSystem::Imperial).is_none());
+        assert!(to_system(3.0, "C", UnitSystem::Imperial).is_none());
+        assert!(to_system(3.0, "F", UnitSystem::Metric).is_none());
+    }
+}
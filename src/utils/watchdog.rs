@@ -0,0 +1,249 @@
+//! 自检看门狗
+//!
+//! 在独立的系统线程中运行（不依赖可能已卡死的异步运行时），每个周期依次检查：
+//! 1. 事件循环：向运行时投递空任务，超时未执行视为无响应
+//! 2. Web 服务：连接本机监听端口发送 HTTP 请求，超时未响应视为无响应
+//! 3. 调用方通过 [`Watchdog::probe`] 注册的检查（如通道协议锁）
+//!
+//! 检查通过时通知 systemd（`WATCHDOG=1`）；连续失败超过 `stall_timeout_ms` 判定为卡死：
+//! 停止通知 systemd（由其在 WatchdogSec 后重启），`exit_on_stall` 生效时直接以
+//! [`STALL_EXIT_CODE`] 退出，由 Windows 服务失败恢复重启。
+
+use futures::future::BoxFuture;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+
+use crate::config::WatchdogConfig;
+
+/// 判定卡死后主动退出时的进程退出码
+pub const STALL_EXIT_CODE: i32 = 102;
+
+/// 运行时调度检查任务的额外宽限时间
+const SCHEDULE_GRACE: Duration = Duration::from_secs(1);
+
+type Probe = Box<dyn Fn(Duration) -> BoxFuture<'static, Result<(), String>> + Send>;
+
+pub struct Watchdog {
+    config: WatchdogConfig,
+    runtime: Handle,
+    web_port: Option<u16>,
+    probes: Vec<(&'static str, Probe)>,
+}
+
+impl Watchdog {
+    /// 创建看门狗（需在 Tokio 运行时内调用）
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self {
+            config,
+            runtime: Handle::current(),
+            web_port: None,
+            probes: Vec::new(),
+        }
+    }
+
+    /// 检查本机 Web 服务端口
+    pub fn web_port(mut self, port: u16) -> Self {
+        self.web_port = Some(port);
+        self
+    }
+
+    /// 注册检查，参数为单项检查超时；检查在运行时中执行
+    pub fn probe<F>(mut self, name: &'static str, probe: F) -> Self
+    where
+        F: Fn(Duration) -> BoxFuture<'static, Result<(), String>> + Send + 'static,
+    {
+        self.probes.push((name, Box::new(probe)));
+        self
+    }
+
+    /// 启动看门狗线程；未启用且不在 systemd 看门狗 / Windows 服务下运行时不启动
+    pub fn start(self) {
+        let systemd_interval = systemd_watchdog_interval();
+        let service_mode = crate::utils::crash::is_service_mode();
+        if !self.config.enable && systemd_interval.is_none() && !service_mode {
+            return;
+        }
+
+        let mut interval = Duration::from_millis(self.config.interval_ms.max(100));
+        if let Some(systemd_interval) = systemd_interval {
+            interval = interval.min(systemd_interval);
+        }
+        let exit_on_stall = self.config.exit_on_stall.unwrap_or(service_mode);
+        info!(
+            "看门狗已启动: 间隔 {:?}, 卡死判定 {} ms, systemd: {}, 卡死退出: {}",
+            interval,
+            self.config.stall_timeout_ms,
+            systemd_interval.is_some(),
+            exit_on_stall
+        );
+
+        let spawned = std::thread::Builder::new()
+            .name("watchdog".to_string())
+            .spawn(move || self.run(interval, exit_on_stall));
+        if let Err(e) = spawned {
+            error!("启动看门狗线程失败: {}", e);
+        }
+    }
+
+    fn run(self, interval: Duration, exit_on_stall: bool) {
+        let stall_timeout = Duration::from_millis(self.config.stall_timeout_ms);
+        let mut last_healthy = Instant::now();
+        let mut stalled = false;
+
+        loop {
+            std::thread::sleep(interval);
+
+            let failures = self.check();
+            if failures.is_empty() {
+                if stalled {
+                    info!("看门狗: 服务已恢复响应");
+                    stalled = false;
+                }
+                last_healthy = Instant::now();
+                notify_watchdog();
+                continue;
+            }
+
+            warn!("看门狗检查失败: {}", failures.join("; "));
+            if last_healthy.elapsed() < stall_timeout {
+                // 短暂异常不影响 systemd 喂狗，超过判定时长后才停止
+                notify_watchdog();
+                continue;
+            }
+            if !stalled {
+                stalled = true;
+                error!(
+                    "看门狗: 已连续 {} 秒检查失败，判定进程卡死",
+                    last_healthy.elapsed().as_secs()
+                );
+            }
+            if exit_on_stall {
+                error!("看门狗: 退出进程，等待服务管理器重启");
+                std::process::exit(STALL_EXIT_CODE);
+            }
+        }
+    }
+
+    /// 执行全部检查，返回失败项
+    fn check(&self) -> Vec<String> {
+        let timeout = Duration::from_millis(self.config.probe_timeout_ms.max(100));
+        let mut failures = Vec::new();
+
+        if let Err(e) = self.run_on_runtime(Box::pin(async { Ok(()) }), timeout) {
+            // 运行时无响应时其余检查必然失败，不再逐项等待
+            failures.push(format!("事件循环: {}", e));
+            return failures;
+        }
+        if let Some(port) = self.web_port {
+            if let Err(e) = probe_http(port, timeout) {
+                failures.push(format!("Web 服务: {}", e));
+            }
+        }
+        for (name, probe) in &self.probes {
+            if let Err(e) = self.run_on_runtime(probe(timeout), timeout) {
+                failures.push(format!("{}: {}", name, e));
+            }
+        }
+        failures
+    }
+
+    fn run_on_runtime(
+        &self,
+        probe: BoxFuture<'static, Result<(), String>>,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.runtime.spawn(async move {
+            let result = tokio::time::timeout(timeout + SCHEDULE_GRACE, probe)
+                .await
+                .unwrap_or_else(|_| Err("检查超时".to_string()));
+            let _ = tx.send(result);
+        });
+        rx.recv_timeout(timeout + SCHEDULE_GRACE * 2)
+            .unwrap_or_else(|_| Err("运行时未调度检查任务".to_string()))
+    }
+}
+
+/// 向本机端口发送 HTTP 请求，收到任意 HTTP 响应即视为正常
+fn probe_http(port: u16, timeout: Duration) -> Result<(), String> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&addr, timeout).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| e.to_string())?;
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n")
+        .map_err(|e| e.to_string())?;
+
+    let mut head = [0u8; 5];
+    stream
+        .read_exact(&mut head)
+        .map_err(|e| format!("未响应: {}", e))?;
+    if &head != b"HTTP/" {
+        return Err("响应不是 HTTP".to_string());
+    }
+    Ok(())
+}
+
+/// systemd 看门狗通知间隔（WatchdogSec 的一半），未启用时为空
+#[cfg(unix)]
+fn systemd_watchdog_interval() -> Option<Duration> {
+    let mut usec = 0;
+    sd_notify::watchdog_enabled(false, &mut usec).then(|| Duration::from_micros(usec / 2))
+}
+
+#[cfg(not(unix))]
+fn systemd_watchdog_interval() -> Option<Duration> {
+    None
+}
+
+#[cfg(unix)]
+fn notify_watchdog() {
+    let _ = sd_notify::notify(false, &[sd_notify::NotifyState::Watchdog]);
+}
+
+#[cfg(not(unix))]
+fn notify_watchdog() {}
+
+/// 通知 systemd 服务已就绪（`Type=notify`），未由 systemd 启动时无操作
+#[cfg(unix)]
+pub fn notify_ready() {
+    let _ = sd_notify::notify(false, &[sd_notify::NotifyState::Ready]);
+}
+
+#[cfg(not(unix))]
+pub fn notify_ready() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn http_probe_requires_response() {
+        let timeout = Duration::from_millis(300);
+
+        // 正常响应
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 256];
+            let _ = conn.read(&mut buf);
+            conn.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+        });
+        assert_eq!(probe_http(port, timeout), Ok(()));
+        server.join().unwrap();
+
+        // 接受连接但不响应（如请求处理卡死）
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(probe_http(port, timeout).is_err());
+        drop(listener);
+    }
+}
@@ -0,0 +1,1179 @@
+//! 访问认证
+//!
+//! 启用 `auth` 配置后，`/lspcapi` 下的接口需携带 `Authorization: Bearer <访问令牌>`
+//! 或 `X-API-Key: <密钥>` 请求头。访问令牌通过登录接口获取，有效期较短，
+//! 过期前可用刷新令牌换取新的令牌对（刷新令牌只能使用一次）。浏览器 WebSocket
+//! 无法设置请求头，事件流接口也接受查询参数 `access_token` / `api_key`。
+
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+};
+use argon2::Argon2;
+use axum::{
+    body::Body,
+    extract::{Extension, Query},
+    http::{header, HeaderMap, Method, Request, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use super::ldap_auth::{self, LdapOutcome};
+use super::response::ApiResponse;
+use super::server::API_PREFIX;
+use super::state::{SharedConfig, SharedController};
+use crate::config::{
+    ApiKeyScope, AuthConfig, AuthRole, Config, LockoutConfig, NodeConfig, Permission, UnitSystem,
+};
+use crate::utils::error::error_codes;
+
+pub type SharedAuth = Arc<AuthManager>;
+
+/// API Key 请求头
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// 无需认证的页面（页面本身不含数据，由页面内的登录流程获取令牌）
+const PUBLIC_PATHS: &[&str] = &[
+    "/auth/login",
+    "/auth/refresh",
+    "/public/status",
+    "/debug",
+    "/config-manager",
+    "/files",
+];
+
+/// 可通过查询参数携带凭据的接口（浏览器 WebSocket 无法设置请求头）
+const QUERY_CREDENTIAL_PATHS: &[&str] = &["/device/events/ws"];
+
+/// 使用 POST 但只读取数据的接口
+const READ_ONLY_POST_PATHS: &[&str] = &[
+    "/device/getAllStatus",
+    "/device/getAllNodeStates",
+    "/device/getNodeState",
+    "/device/read",
+    "/device/readMany",
+    "/device/batchRead",
+    "/device/getMethods",
+    "/device/scenes/export",
+    "/device/scenes/import/preview",
+];
+
+/// 需要配置权限的接口（配置中包含密码哈希和 API Key，读取也需配置权限；诊断信息包含配置文件路径等内部细节）
+const CONFIGURE_PATHS: &[&str] = &[
+    "/device/config",
+    "/device/scenes/import",
+    "/system/diagnostics",
+];
+
+/// 请求身份类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PrincipalKind {
+    /// 登录会话
+    Session,
+    /// API Key
+    ApiKey,
+    /// 未启用认证
+    Anonymous,
+}
+
+/// 当前请求的身份（由认证中间件写入请求扩展）
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub name: String,
+    pub role: AuthRole,
+    pub kind: PrincipalKind,
+    /// API Key 访问范围（`None` 表示不限制）
+    pub scope: Option<ApiKeyScope>,
+    /// API Key 偏好的单位制
+    pub unit_system: Option<UnitSystem>,
+}
+
+impl Principal {
+    fn anonymous() -> Self {
+        Self {
+            name: "anonymous".to_string(),
+            role: AuthRole::Admin,
+            kind: PrincipalKind::Anonymous,
+            scope: None,
+            unit_system: None,
+        }
+    }
+
+    /// 操作员名称：已认证时为身份名称，未启用认证时才采用请求中声明的名称
+    pub fn operator(&self, claimed: Option<String>) -> Option<String> {
+        match self.kind {
+            PrincipalKind::Anonymous => claimed,
+            PrincipalKind::Session | PrincipalKind::ApiKey => Some(self.name.clone()),
+        }
+    }
+
+    /// 是否可访问节点
+    pub fn can_access_node(&self, node: &NodeConfig) -> bool {
+        self.scope.as_ref().is_none_or(|s| s.allows_node(node))
+    }
+
+    /// 是否可调用通道级命令和方法
+    pub fn can_access_channel(&self, channel_id: u32) -> bool {
+        self.scope
+            .as_ref()
+            .is_none_or(|s| s.allows_channel(channel_id))
+    }
+
+    /// 通道是否出现在列表中（通道在范围内或包含可访问的节点）
+    pub fn can_see_channel(&self, channel_id: u32, nodes: &[NodeConfig]) -> bool {
+        self.can_access_channel(channel_id)
+            || nodes
+                .iter()
+                .any(|n| n.channel_id == channel_id && self.can_access_node(n))
+    }
+}
+
+/// 认证失败原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// 认证未启用
+    Disabled,
+    /// 用户名或密码错误
+    InvalidCredentials,
+    /// 登录失败次数过多，剩余锁定秒数
+    Locked(u64),
+    /// 令牌无效或已过期
+    InvalidToken,
+}
+
+impl AuthError {
+    fn code(&self) -> i32 {
+        match self {
+            AuthError::Disabled => error_codes::GENERAL_ERROR,
+            AuthError::InvalidCredentials | AuthError::InvalidToken => error_codes::UNAUTHORIZED,
+            AuthError::Locked(_) => error_codes::ACCOUNT_LOCKED,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            AuthError::Disabled => "认证未启用".to_string(),
+            AuthError::InvalidCredentials => "用户名或密码错误".to_string(),
+            AuthError::Locked(secs) => format!("登录失败次数过多，请 {} 秒后再试", secs),
+            AuthError::InvalidToken => "令牌无效或已过期".to_string(),
+        }
+    }
+}
+
+/// 签发的令牌对
+#[derive(Debug, Clone)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
+    pub refresh_expires_in: u64,
+    pub username: String,
+    pub role: AuthRole,
+}
+
+struct Session {
+    username: String,
+    role: AuthRole,
+    refresh_token: String,
+    expires_at: Instant,
+}
+
+/// 账号来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountSource {
+    Local,
+    Ldap,
+}
+
+struct RefreshGrant {
+    username: String,
+    role: AuthRole,
+    source: AccountSource,
+    access_token: String,
+    expires_at: Instant,
+}
+
+struct LoginFailures {
+    count: u32,
+    first_at: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// 会话与登录失败记录（仅保存在内存中，重启后需重新登录）
+#[derive(Default)]
+pub struct AuthManager {
+    sessions: DashMap<String, Session>,
+    refresh_grants: DashMap<String, RefreshGrant>,
+    failures: DashMap<String, LoginFailures>,
+}
+
+impl AuthManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 用户名密码登录
+    ///
+    /// 启用 LDAP 时优先使用 LDAP 认证；目录中不存在该用户或 LDAP 不可达时回退到本地用户。
+    pub async fn login(
+        &self,
+        config: &AuthConfig,
+        username: &str,
+        password: &str,
+    ) -> Result<TokenPair, AuthError> {
+        if !config.enable {
+            return Err(AuthError::Disabled);
+        }
+        self.purge_expired();
+        self.check_locked(username)?;
+
+        if let Some(ldap) = config.ldap.as_ref().filter(|l| l.enable) {
+            match ldap_auth::authenticate(ldap, username, password).await {
+                LdapOutcome::Authenticated(role) => {
+                    self.failures.remove(username);
+                    tracing::info!(
+                        "[认证] LDAP 用户 '{}' 登录成功，角色: {}",
+                        username,
+                        role.as_str()
+                    );
+                    return Ok(self.issue(config, username, role, AccountSource::Ldap));
+                }
+                LdapOutcome::Rejected => return Err(self.record_failure(username, &config.lockout)),
+                LdapOutcome::UserNotFound => {}
+                LdapOutcome::Unreachable(e) => {
+                    tracing::warn!("[认证] LDAP 不可用，回退到本地账号: {}", e);
+                }
+            }
+        }
+
+        let user = config.users.iter().find(|u| u.username == username);
+        match user {
+            Some(user) if verify_password(&user.password_hash, password) => {
+                self.failures.remove(username);
+                tracing::info!("[认证] 用户 '{}' 登录成功", username);
+                Ok(self.issue(config, &user.username, user.role, AccountSource::Local))
+            }
+            _ => Err(self.record_failure(username, &config.lockout)),
+        }
+    }
+
+    /// 使用刷新令牌换取新的令牌对，旧的访问令牌同时失效
+    pub fn refresh(
+        &self,
+        config: &AuthConfig,
+        refresh_token: &str,
+    ) -> Result<TokenPair, AuthError> {
+        if !config.enable {
+            return Err(AuthError::Disabled);
+        }
+        let (_, grant) = self
+            .refresh_grants
+            .remove(refresh_token)
+            .ok_or(AuthError::InvalidToken)?;
+        self.sessions.remove(&grant.access_token);
+        if grant.expires_at <= Instant::now() {
+            return Err(AuthError::InvalidToken);
+        }
+
+        match grant.source {
+            // 本地用户以当前配置为准：用户被删除则不能续期，角色变更立即生效
+            AccountSource::Local => {
+                let user = config
+                    .users
+                    .iter()
+                    .find(|u| u.username == grant.username)
+                    .ok_or(AuthError::InvalidToken)?;
+                Ok(self.issue(config, &user.username, user.role, AccountSource::Local))
+            }
+            // LDAP 用户沿用登录时映射的角色，关闭 LDAP 后不能续期
+            AccountSource::Ldap => {
+                if !config.ldap.as_ref().is_some_and(|l| l.enable) {
+                    return Err(AuthError::InvalidToken);
+                }
+                Ok(self.issue(config, &grant.username, grant.role, AccountSource::Ldap))
+            }
+        }
+    }
+
+    /// 注销访问令牌及其刷新令牌
+    pub fn logout(&self, access_token: &str) -> bool {
+        match self.sessions.remove(access_token) {
+            Some((_, session)) => {
+                self.refresh_grants.remove(&session.refresh_token);
+                tracing::info!("[认证] 用户 '{}' 已注销", session.username);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 根据请求头识别身份
+    pub fn authenticate(&self, config: &AuthConfig, headers: &HeaderMap) -> Option<Principal> {
+        if let Some(token) = bearer_token(headers) {
+            return self.session(token);
+        }
+        let key = headers.get(API_KEY_HEADER)?.to_str().ok()?;
+        Self::api_key(config, key)
+    }
+
+    /// 根据查询参数 `access_token` / `api_key` 识别身份（仅限 [`QUERY_CREDENTIAL_PATHS`]）
+    pub fn authenticate_query(&self, config: &AuthConfig, uri: &Uri) -> Option<Principal> {
+        let path = uri.path().strip_prefix(API_PREFIX)?;
+        if !QUERY_CREDENTIAL_PATHS.contains(&path) {
+            return None;
+        }
+        let Query(credentials) = Query::<QueryCredentials>::try_from_uri(uri).ok()?;
+        if let Some(token) = credentials.access_token {
+            return self.session(&token);
+        }
+        Self::api_key(config, &credentials.api_key?)
+    }
+
+    fn api_key(config: &AuthConfig, key: &str) -> Option<Principal> {
+        config
+            .api_keys
+            .iter()
+            .find(|k| k.key == key)
+            .map(|k| Principal {
+                name: k.name.clone().unwrap_or_else(|| "api-key".to_string()),
+                role: k.role,
+                kind: PrincipalKind::ApiKey,
+                scope: k.scope.clone(),
+                unit_system: k.unit_system,
+            })
+    }
+
+    fn session(&self, access_token: &str) -> Option<Principal> {
+        let session = self.sessions.get(access_token)?;
+        if session.expires_at <= Instant::now() {
+            return None;
+        }
+        Some(Principal {
+            name: session.username.clone(),
+            role: session.role,
+            kind: PrincipalKind::Session,
+            scope: None,
+            unit_system: None,
+        })
+    }
+
+    fn issue(
+        &self,
+        config: &AuthConfig,
+        username: &str,
+        role: AuthRole,
+        source: AccountSource,
+    ) -> TokenPair {
+        let now = Instant::now();
+        let access_token = new_token();
+        let refresh_token = new_token();
+        self.sessions.insert(
+            access_token.clone(),
+            Session {
+                username: username.to_string(),
+                role,
+                refresh_token: refresh_token.clone(),
+                expires_at: now + Duration::from_secs(config.access_token_ttl),
+            },
+        );
+        self.refresh_grants.insert(
+            refresh_token.clone(),
+            RefreshGrant {
+                username: username.to_string(),
+                role,
+                source,
+                access_token: access_token.clone(),
+                expires_at: now + Duration::from_secs(config.refresh_token_ttl),
+            },
+        );
+        TokenPair {
+            access_token,
+            refresh_token,
+            expires_in: config.access_token_ttl,
+            refresh_expires_in: config.refresh_token_ttl,
+            username: username.to_string(),
+            role,
+        }
+    }
+
+    fn check_locked(&self, username: &str) -> Result<(), AuthError> {
+        let now = Instant::now();
+        match self.failures.get(username).and_then(|f| f.locked_until) {
+            Some(until) if until > now => Err(AuthError::Locked((until - now).as_secs().max(1))),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_failure(&self, username: &str, lockout: &LockoutConfig) -> AuthError {
+        if lockout.max_attempts == 0 {
+            return AuthError::InvalidCredentials;
+        }
+        let now = Instant::now();
+        let mut failures = self
+            .failures
+            .entry(username.to_string())
+            .or_insert(LoginFailures {
+                count: 0,
+                first_at: now,
+                locked_until: None,
+            });
+        if failures.locked_until.is_some()
+            || now.duration_since(failures.first_at) > Duration::from_secs(lockout.window)
+        {
+            *failures = LoginFailures {
+                count: 0,
+                first_at: now,
+                locked_until: None,
+            };
+        }
+        failures.count += 1;
+        if failures.count >= lockout.max_attempts {
+            failures.locked_until = Some(now + Duration::from_secs(lockout.duration));
+            tracing::warn!(
+                "[认证] 用户 '{}' 连续 {} 次登录失败，锁定 {} 秒",
+                username,
+                failures.count,
+                lockout.duration
+            );
+            return AuthError::Locked(lockout.duration);
+        }
+        tracing::warn!("[认证] 用户 '{}' 登录失败", username);
+        AuthError::InvalidCredentials
+    }
+
+    fn purge_expired(&self) {
+        let now = Instant::now();
+        self.sessions.retain(|_, s| s.expires_at > now);
+        self.refresh_grants.retain(|_, g| g.expires_at > now);
+        self.failures.retain(|_, f| match f.locked_until {
+            Some(until) => until > now,
+            None => now.duration_since(f.first_at) < Duration::from_secs(3600),
+        });
+    }
+}
+
+fn new_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// 查询参数中的凭据
+#[derive(Debug, Deserialize)]
+struct QueryCredentials {
+    access_token: Option<String>,
+    api_key: Option<String>,
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    value
+        .strip_prefix("Bearer ")
+        .or_else(|| value.strip_prefix("bearer "))
+        .map(str::trim)
+}
+
+/// 生成 Argon2 密码哈希（PHC 字符串）
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| format!("生成密码哈希失败: {}", e))
+}
+
+fn verify_password(password_hash: &str, password: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(e) => {
+            tracing::error!("[认证] 密码哈希格式无效: {}", e);
+            false
+        }
+    }
+}
+
+/// 请求所需权限，`None` 表示无需认证
+fn required_permission(method: &Method, path: &str) -> Option<Permission> {
+    if method == Method::OPTIONS {
+        return None;
+    }
+    let path = path.strip_prefix(API_PREFIX)?;
+    if PUBLIC_PATHS.contains(&path) {
+        return None;
+    }
+    if path.starts_with("/config/") || CONFIGURE_PATHS.contains(&path) || is_raw_console(path) {
+        return Some(Permission::Configure);
+    }
+    if method == Method::GET || method == Method::HEAD || READ_ONLY_POST_PATHS.contains(&path) {
+        return Some(Permission::Read);
+    }
+    Some(Permission::Control)
+}
+
+/// 原始命令控制台绕过节点模型直接操作设备，仅管理员可用
+fn is_raw_console(path: &str) -> bool {
+    matches!(
+        path.trim_start_matches('/')
+            .split('/')
+            .collect::<Vec<_>>()
+            .as_slice(),
+        ["device", "channels", _, "raw"]
+    )
+}
+
+/// 受限 API Key 请求涉及的访问对象
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ScopeTarget {
+    Node(u32),
+    Channel(u32),
+    Scene(String),
+    /// 场景执行编号（按执行中的场景校验）
+    SceneRun(u64),
+    Confirmation(String),
+    Profile(String),
+}
+
+/// 受限 API Key 可访问的列表接口（由处理器按范围过滤结果）
+const SCOPE_FILTERED_PATHS: &[&str] = &[
+    "/auth/me",
+    "/auth/logout",
+    "/system/info",
+    "/device/getAllStatus",
+    "/device/getAllNodeStates",
+    "/device/model",
+    "/device/sites",
+    "/device/confirmations",
+    "/device/offlineWrites",
+    "/device/profiles",
+    "/device/events/ws",
+];
+
+/// 解析请求涉及的节点、通道、场景，`None` 表示该接口不支持按范围访问
+fn scope_targets(path: &str, body: &serde_json::Value) -> Option<Vec<ScopeTarget>> {
+    let u32_field =
+        |v: &serde_json::Value, key: &str| v.get(key).and_then(|x| x.as_u64()).map(|x| x as u32);
+    let items = |key: &str| {
+        body.get(key)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    if SCOPE_FILTERED_PATHS.contains(&path) {
+        return Some(Vec::new());
+    }
+    let targets = match path {
+        "/device/write" | "/device/read" => u32_field(body, "global_id")
+            .map(ScopeTarget::Node)
+            .into_iter()
+            .collect(),
+        // GET 过滤结果，批量解除只作用于可访问节点
+        "/device/forces" => u32_field(body, "global_id")
+            .map(ScopeTarget::Node)
+            .into_iter()
+            .collect(),
+        "/device/getNodeState" => u32_field(body, "id")
+            .map(ScopeTarget::Node)
+            .into_iter()
+            .collect(),
+        "/device/writeMany" => items("items")
+            .iter()
+            .filter_map(|item| u32_field(item, "id"))
+            .map(ScopeTarget::Node)
+            .collect(),
+        "/device/readMany" => items("ids")
+            .iter()
+            .filter_map(|id| id.as_u64())
+            .map(|id| ScopeTarget::Node(id as u32))
+            .collect(),
+        "/device/scene" => body
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(|name| ScopeTarget::Scene(name.to_string()))
+            .into_iter()
+            .collect(),
+        "/device/executeCommand" | "/device/callMethod" | "/device/getMethods" => {
+            u32_field(body, "channel_id")
+                .map(ScopeTarget::Channel)
+                .into_iter()
+                .collect()
+        }
+        "/device/batchRead" | "/device/channels/invokeMany" => items("items")
+            .iter()
+            .filter_map(|item| u32_field(item, "channel_id"))
+            .map(ScopeTarget::Channel)
+            .collect(),
+        _ => {
+            let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+            match segments.as_slice() {
+                ["device", "scene", name, "diff"] => vec![ScopeTarget::Scene(name.to_string())],
+                ["device", "scene", run_id, "next" | "cancel"] => {
+                    vec![ScopeTarget::SceneRun(run_id.parse().ok()?)]
+                }
+                ["device", "channels", id, "cache"]
+                | ["device", "channels", id, "cache", "invalidate"]
+                | ["device", "channels", id, "enable" | "disable" | "ping"] => {
+                    vec![ScopeTarget::Channel(id.parse().ok()?)]
+                }
+                ["device", "forces" | "offlineWrites", id] => {
+                    vec![ScopeTarget::Node(id.parse().ok()?)]
+                }
+                ["device", "profiles", name, "start" | "stop"] => {
+                    vec![ScopeTarget::Profile(name.to_string())]
+                }
+                ["device", "confirmations", token, "confirm" | "cancel"] => {
+                    vec![ScopeTarget::Confirmation(token.to_string())]
+                }
+                _ => return None,
+            }
+        }
+    };
+    Some(targets)
+}
+
+/// 校验受限 API Key 的请求对象，返回无权访问的原因
+async fn check_scope(
+    principal: &Principal,
+    path: &str,
+    body: &serde_json::Value,
+    config: &Config,
+    controller: &SharedController,
+) -> Result<(), String> {
+    let targets = scope_targets(path, body)
+        .ok_or_else(|| format!("API Key '{}' 访问范围受限，不能访问该接口", principal.name))?;
+    let node_allowed = |global_id: u32| {
+        config
+            .nodes
+            .iter()
+            .find(|n| n.global_id == global_id)
+            .is_some_and(|n| principal.can_access_node(n))
+    };
+
+    for target in targets {
+        let target = match target {
+            ScopeTarget::SceneRun(run_id) => match controller.read().await.scene_run(run_id) {
+                Some(run) => ScopeTarget::Scene(run.scene),
+                None => continue,
+            },
+            target => target,
+        };
+        match target {
+            ScopeTarget::Node(id) if !node_allowed(id) => {
+                return Err(format!("无权访问节点 {}", id));
+            }
+            ScopeTarget::Channel(id) if !principal.can_access_channel(id) => {
+                return Err(format!("无权访问通道 {}", id));
+            }
+            ScopeTarget::Scene(name) => {
+                // 场景涉及的全部节点都在范围内才可执行
+                if let Some(scene) = config.scenes.iter().find(|s| s.name == name) {
+                    let profile_targets = scene.nodes.iter().filter_map(|step| {
+                        let profile = step.profile.as_ref()?;
+                        config.profiles.iter().find(|p| &p.name == profile)
+                    });
+                    if let Some(id) = scene
+                        .nodes
+                        .iter()
+                        .flat_map(|step| step.targets())
+                        .chain(profile_targets.map(|p| p.global_id))
+                        .find(|id| !node_allowed(*id))
+                    {
+                        return Err(format!("无权执行场景 '{}': 包含范围外节点 {}", name, id));
+                    }
+                }
+            }
+            ScopeTarget::Profile(name) => {
+                if let Some(profile) = config.profiles.iter().find(|p| p.name == name) {
+                    if !node_allowed(profile.global_id) {
+                        return Err(format!("无权控制时间曲线 '{}'", name));
+                    }
+                }
+            }
+            ScopeTarget::Confirmation(token) => {
+                let pending = controller.read().await.list_confirmations();
+                if let Some(p) = pending.iter().find(|p| p.token == token) {
+                    if !node_allowed(p.global_id) {
+                        return Err(format!("无权确认节点 {} 的写入", p.global_id));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn reject(status: StatusCode, code: i32, message: impl Into<String>) -> Response {
+    let body = ApiResponse::<()> {
+        state: code,
+        message: message.into(),
+        data: None,
+    };
+    (status, Json(body)).into_response()
+}
+
+/// 认证中间件
+///
+/// 未启用认证时以匿名管理员身份放行，便于处理器统一读取 [`Principal`]。
+/// 受限 API Key 的请求体会被缓存解析，以校验涉及的节点和通道。
+pub async fn require_auth(
+    Extension(config): Extension<SharedConfig>,
+    Extension(auth): Extension<SharedAuth>,
+    Extension(controller): Extension<SharedController>,
+    mut req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let auth_config = config.read().await.auth.clone();
+    let auth_config = match auth_config {
+        Some(c) if c.enable => c,
+        _ => {
+            req.extensions_mut().insert(Principal::anonymous());
+            return next.run(req).await;
+        }
+    };
+
+    let permission = match required_permission(req.method(), req.uri().path()) {
+        Some(p) => p,
+        None => return next.run(req).await,
+    };
+
+    let principal = auth
+        .authenticate(&auth_config, req.headers())
+        .or_else(|| auth.authenticate_query(&auth_config, req.uri()));
+    let mut principal = match principal {
+        Some(p) => p,
+        None => {
+            return reject(
+                StatusCode::UNAUTHORIZED,
+                error_codes::UNAUTHORIZED,
+                "未认证或令牌已过期",
+            )
+        }
+    };
+    if !principal.role.allows(permission) {
+        return reject(
+            StatusCode::FORBIDDEN,
+            error_codes::FORBIDDEN,
+            format!(
+                "权限不足: 需要 {} 权限，当前角色为 {}",
+                permission.as_str(),
+                principal.role.as_str()
+            ),
+        );
+    }
+
+    if let Some(scope) = principal.scope.as_mut() {
+        scope.expand_sites(&*config.read().await);
+        let (parts, body) = req.into_parts();
+        let bytes = match hyper::body::to_bytes(body).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return reject(
+                    StatusCode::BAD_REQUEST,
+                    error_codes::INVALID_PARAMS,
+                    format!("读取请求体失败: {}", e),
+                )
+            }
+        };
+        let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+        let path = parts
+            .uri
+            .path()
+            .strip_prefix(API_PREFIX)
+            .unwrap_or_default();
+        let checked = {
+            let config = config.read().await;
+            check_scope(&principal, path, &json, &config, &controller).await
+        };
+        if let Err(message) = checked {
+            tracing::warn!("[认证] {}: {}", principal.name, message);
+            return reject(StatusCode::FORBIDDEN, error_codes::FORBIDDEN, message);
+        }
+        req = Request::from_parts(parts, Body::from(bytes));
+    }
+
+    req.extensions_mut().insert(principal);
+    next.run(req).await
+}
+
+/// 登录请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// 刷新令牌请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// 令牌响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenResponse {
+    /// 访问令牌（请求头 `Authorization: Bearer <access_token>`）
+    pub access_token: String,
+    /// 刷新令牌（只能使用一次）
+    pub refresh_token: String,
+    /// 固定为 "Bearer"
+    pub token_type: String,
+    /// 访问令牌有效期（秒）
+    pub expires_in: u64,
+    /// 刷新令牌有效期（秒）
+    pub refresh_expires_in: u64,
+    pub username: String,
+    pub role: String,
+}
+
+impl From<TokenPair> for TokenResponse {
+    fn from(pair: TokenPair) -> Self {
+        Self {
+            access_token: pair.access_token,
+            refresh_token: pair.refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in: pair.expires_in,
+            refresh_expires_in: pair.refresh_expires_in,
+            username: pair.username,
+            role: pair.role.as_str().to_string(),
+        }
+    }
+}
+
+/// 当前身份响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MeResponse {
+    /// 是否启用认证
+    pub auth_enabled: bool,
+    /// 用户名或 API Key 名称
+    pub name: String,
+    pub kind: PrincipalKind,
+    /// 角色: viewer / operator / admin
+    pub role: String,
+    /// 权限: read / control / configure
+    pub permissions: Vec<String>,
+    /// API Key 访问范围（channels / nodes / categories），不限制时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub scope: Option<ApiKeyScope>,
+    /// API Key 偏好的单位制（metric / imperial），未配置时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub unit_system: Option<UnitSystem>,
+}
+
+fn token_result(result: Result<TokenPair, AuthError>) -> Json<ApiResponse<TokenResponse>> {
+    match result {
+        Ok(pair) => Json(ApiResponse::success("成功", pair.into())),
+        Err(e) => Json(ApiResponse {
+            state: e.code(),
+            message: e.message(),
+            data: None,
+        }),
+    }
+}
+
+/// 登录
+#[utoipa::path(
+    post,
+    path = "/lspcapi/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "登录结果（state=30008 用户名或密码错误，30010 账号已锁定）", body = inline(ApiResponse<TokenResponse>))
+    ),
+    tag = "Auth"
+)]
+pub async fn login(
+    Extension(config): Extension<SharedConfig>,
+    Extension(auth): Extension<SharedAuth>,
+    Json(req): Json<LoginRequest>,
+) -> Json<ApiResponse<TokenResponse>> {
+    let auth_config = config.read().await.auth.clone().unwrap_or_default();
+    token_result(auth.login(&auth_config, &req.username, &req.password).await)
+}
+
+/// 刷新令牌
+#[utoipa::path(
+    post,
+    path = "/lspcapi/auth/refresh",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "新的令牌对", body = inline(ApiResponse<TokenResponse>))
+    ),
+    tag = "Auth"
+)]
+pub async fn refresh_token(
+    Extension(config): Extension<SharedConfig>,
+    Extension(auth): Extension<SharedAuth>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Json<ApiResponse<TokenResponse>> {
+    let auth_config = config.read().await.auth.clone().unwrap_or_default();
+    token_result(auth.refresh(&auth_config, &req.refresh_token))
+}
+
+/// 注销当前访问令牌
+#[utoipa::path(
+    post,
+    path = "/lspcapi/auth/logout",
+    responses(
+        (status = 200, description = "注销成功", body = inline(ApiResponse<()>))
+    ),
+    tag = "Auth"
+)]
+pub async fn logout(
+    Extension(auth): Extension<SharedAuth>,
+    headers: HeaderMap,
+) -> Json<ApiResponse<()>> {
+    match bearer_token(&headers) {
+        Some(token) if auth.logout(token) => Json(ApiResponse::<()>::success_empty("已注销")),
+        _ => Json(ApiResponse::<()> {
+            state: error_codes::INVALID_PARAMS,
+            message: "请求未携带有效的访问令牌".to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 获取当前身份、角色和权限
+#[utoipa::path(
+    get,
+    path = "/lspcapi/auth/me",
+    responses(
+        (status = 200, description = "当前身份", body = inline(ApiResponse<MeResponse>))
+    ),
+    tag = "Auth"
+)]
+pub async fn me(Extension(principal): Extension<Principal>) -> Json<ApiResponse<MeResponse>> {
+    Json(ApiResponse::success(
+        "成功",
+        MeResponse {
+            auth_enabled: principal.kind != PrincipalKind::Anonymous,
+            name: principal.name,
+            kind: principal.kind,
+            role: principal.role.as_str().to_string(),
+            permissions: principal
+                .role
+                .permissions()
+                .iter()
+                .map(|p| p.as_str().to_string())
+                .collect(),
+            scope: principal.scope,
+            unit_system: principal.unit_system,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AuthUserConfig;
+    use argon2::{Algorithm, Params, Version};
+
+    fn test_config() -> AuthConfig {
+        // 测试使用低成本参数，避免调试构建下哈希过慢
+        let params = Params::new(1024, 1, 1, None).unwrap();
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password(b"secret", &salt)
+            .unwrap()
+            .to_string();
+        let mut config = AuthConfig {
+            enable: true,
+            users: vec![AuthUserConfig {
+                username: "alice".to_string(),
+                password_hash: hash,
+                role: AuthRole::Operator,
+            }],
+            ..Default::default()
+        };
+        config.lockout.max_attempts = 3;
+        config
+    }
+
+    #[tokio::test]
+    async fn lockout_after_repeated_failures() {
+        let config = test_config();
+        let auth = AuthManager::new();
+
+        assert_eq!(
+            auth.login(&config, "alice", "wrong").await.unwrap_err(),
+            AuthError::InvalidCredentials
+        );
+        auth.login(&config, "alice", "wrong").await.unwrap_err();
+        assert!(matches!(
+            auth.login(&config, "alice", "wrong").await,
+            Err(AuthError::Locked(_))
+        ));
+        // 锁定期间正确密码也被拒绝
+        assert!(matches!(
+            auth.login(&config, "alice", "secret").await,
+            Err(AuthError::Locked(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn refresh_rotates_tokens() {
+        let config = test_config();
+        let auth = AuthManager::new();
+        let pair = auth.login(&config, "alice", "secret").await.unwrap();
+        assert!(auth.session(&pair.access_token).is_some());
+
+        let renewed = auth.refresh(&config, &pair.refresh_token).unwrap();
+        assert!(auth.session(&pair.access_token).is_none());
+        assert!(auth.refresh(&config, &pair.refresh_token).is_err());
+        assert_eq!(renewed.role, AuthRole::Operator);
+
+        assert!(auth.logout(&renewed.access_token));
+        assert!(auth.session(&renewed.access_token).is_none());
+    }
+
+    #[tokio::test]
+    async fn event_stream_accepts_query_credentials() {
+        let mut config = test_config();
+        config.api_keys.push(
+            serde_json::from_value(serde_json::json!({
+                "key": "k1", "name": "dashboard", "role": "viewer"
+            }))
+            .unwrap(),
+        );
+        let auth = AuthManager::new();
+        let pair = auth.login(&config, "alice", "secret").await.unwrap();
+        let uri = |q: String| -> Uri { format!("{}{}", API_PREFIX, q).parse().unwrap() };
+
+        let session = auth
+            .authenticate_query(
+                &config,
+                &uri(format!(
+                    "/device/events/ws?access_token={}",
+                    pair.access_token
+                )),
+            )
+            .unwrap();
+        assert_eq!(session.name, "alice");
+        let key = auth
+            .authenticate_query(&config, &uri("/device/events/ws?api_key=k1".into()))
+            .unwrap();
+        assert_eq!(key.kind, PrincipalKind::ApiKey);
+        // 其他接口仍只接受请求头
+        assert!(auth
+            .authenticate_query(&config, &uri("/device/getAllStatus?api_key=k1".into()))
+            .is_none());
+        assert!(auth
+            .authenticate_query(&config, &uri("/device/events/ws?api_key=bad".into()))
+            .is_none());
+    }
+
+    #[test]
+    fn scoped_request_targets() {
+        let body =
+            serde_json::json!({ "items": [{ "id": 3, "value": 1 }, { "id": 4, "value": 0 }] });
+        assert_eq!(
+            scope_targets("/device/writeMany", &body),
+            Some(vec![ScopeTarget::Node(3), ScopeTarget::Node(4)])
+        );
+        assert_eq!(
+            scope_targets(
+                "/device/channels/2/cache/invalidate",
+                &serde_json::Value::Null
+            ),
+            Some(vec![ScopeTarget::Channel(2)])
+        );
+        assert_eq!(
+            scope_targets("/device/channels/5/disable", &serde_json::Value::Null),
+            Some(vec![ScopeTarget::Channel(5)])
+        );
+        assert_eq!(
+            scope_targets("/device/getAllNodeStates", &serde_json::Value::Null),
+            Some(vec![])
+        );
+        assert_eq!(
+            scope_targets("/device/scenes/export", &serde_json::Value::Null),
+            None
+        );
+    }
+
+    #[test]
+    fn site_scope_expands_to_channels() {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "channels": [
+                { "channel_id": 1, "enable": true, "statute": "mock", "site": "hall-a" },
+                { "channel_id": 2, "enable": true, "statute": "mock", "site": "hall-b" },
+                { "channel_id": 3, "enable": true, "statute": "mock", "site": "hall-a" }
+            ],
+            "nodes": [],
+            "scenes": [],
+            "sites": [{ "id": "hall-a" }, { "id": "hall-b", "name": "B 馆" }],
+            "web_server": { "port": 8080 }
+        }))
+        .unwrap();
+        assert!(config.validate_sites().is_ok());
+
+        let mut scope = ApiKeyScope {
+            nodes: vec![7],
+            sites: vec!["hall-a".to_string()],
+            ..Default::default()
+        };
+        scope.expand_sites(&config);
+        assert_eq!(scope.channels, vec![1, 3]);
+        assert!(!scope.allows_channel(2));
+
+        config.channels[1].site = Some("hall-c".to_string());
+        assert!(config.validate_sites().is_err());
+    }
+
+    #[test]
+    fn operator_comes_from_authenticated_identity() {
+        let anonymous = Principal::anonymous();
+        assert_eq!(
+            anonymous.operator(Some("bob".into())).as_deref(),
+            Some("bob")
+        );
+
+        let session = Principal {
+            name: "alice".to_string(),
+            kind: PrincipalKind::Session,
+            ..Principal::anonymous()
+        };
+        assert_eq!(
+            session.operator(Some("bob".into())).as_deref(),
+            Some("alice")
+        );
+        assert_eq!(session.operator(None).as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn permission_by_route() {
+        let path = |p: &str| format!("{}{}", API_PREFIX, p);
+        assert_eq!(
+            required_permission(&Method::POST, &path("/auth/login")),
+            None
+        );
+        assert_eq!(
+            required_permission(&Method::POST, &path("/device/read")),
+            Some(Permission::Read)
+        );
+        assert_eq!(
+            required_permission(&Method::POST, &path("/device/write")),
+            Some(Permission::Control)
+        );
+        assert_eq!(
+            required_permission(&Method::POST, &path("/config/reload")),
+            Some(Permission::Configure)
+        );
+        assert_eq!(
+            required_permission(&Method::POST, &path("/device/channels/3/raw")),
+            Some(Permission::Configure)
+        );
+        assert_eq!(required_permission(&Method::GET, "/static/a.png"), None);
+    }
+}
@@ -0,0 +1,90 @@
+//! 内容推送 API 处理器
+//!
+//! 把资源库中的素材推送到支持文件上传的显示设备，并查询推送进度。
+
+use axum::{
+    extract::{Extension, Path},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::response::ApiResponse;
+use crate::content_push::{ContentPushStatus, ContentPusher};
+use crate::utils::error::error_codes;
+
+/// 内容推送请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ContentPushRequest {
+    /// 目标通道（协议需支持内容推送）
+    pub channel_id: u32,
+    /// 素材 ID
+    pub material_id: String,
+}
+
+/// 推送素材到设备
+///
+/// 立即返回推送记录，上传在后台进行；进度通过 `content_push_progress` /
+/// `content_push_finished` 事件或查询接口获取。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/content/pushes",
+    request_body = ContentPushRequest,
+    responses(
+        (status = 200, description = "推送已开始", body = inline(ApiResponse<ContentPushStatus>))
+    ),
+    tag = "Content"
+)]
+pub async fn start_content_push(
+    Extension(pusher): Extension<Arc<ContentPusher>>,
+    Json(request): Json<ContentPushRequest>,
+) -> Json<ApiResponse<ContentPushStatus>> {
+    match pusher.push(request.channel_id, &request.material_id).await {
+        Ok(status) => Json(ApiResponse::success("推送已开始", status)),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 内容推送记录
+#[utoipa::path(
+    get,
+    path = "/lspcapi/content/pushes",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<Vec<ContentPushStatus>>))
+    ),
+    tag = "Content"
+)]
+pub async fn list_content_pushes(
+    Extension(pusher): Extension<Arc<ContentPusher>>,
+) -> Json<ApiResponse<Vec<ContentPushStatus>>> {
+    Json(ApiResponse::success("获取推送记录成功", pusher.list()))
+}
+
+/// 单个内容推送的进度
+#[utoipa::path(
+    get,
+    path = "/lspcapi/content/pushes/{id}",
+    params(("id" = String, Path, description = "推送 ID")),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<ContentPushStatus>))
+    ),
+    tag = "Content"
+)]
+pub async fn get_content_push(
+    Extension(pusher): Extension<Arc<ContentPusher>>,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<ContentPushStatus>> {
+    match pusher.status(&id) {
+        Some(status) => Json(ApiResponse::success("获取推送记录成功", status)),
+        None => Json(ApiResponse {
+            state: error_codes::INVALID_PARAMS,
+            message: format!("推送记录 {} 不存在", id),
+            data: None,
+        }),
+    }
+}
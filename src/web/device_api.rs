@@ -1,25 +1,55 @@
 //! 设备控制 API 处理器
 
-use axum::{extract::Extension, Json};
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Number;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
+use super::auth::{Principal, PrincipalKind};
 use super::response::ApiResponse;
-use super::state::SharedController;
+use super::state::{SharedConfig, SharedConfigPath, SharedConfigStore, SharedController};
+use crate::config::{SceneNode, StatuteType, UnitSystem};
 use crate::db::Database;
+use crate::device::scene_transfer::{self, NodeMapping, ScenePackage};
+use crate::device::{
+    AnalyticsReport, CommandResult, DeviceController, ExecutionStatsReport, ForcedNode, NodeState,
+    PendingWrite, PingMode, PingReport, ProfileStatus, QueuedWrite, SceneCue, SceneRunResult,
+    SceneStepDiff, StartupOrderReport,
+};
 use crate::utils::error::error_codes;
+use crate::utils::time;
+use crate::utils::units;
+use crate::utils::{DeviceError, UnmetDependency};
 
 // ===== 请求/响应类型定义 =====
 
+/// 写入值：数值或节点 value_labels 中定义的状态名称
+#[derive(Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum WriteValue {
+    /// 数值（可带小数，按节点值变换 / 数据点缩放写入设备）
+    Number(f64),
+    /// 状态名称（如 "on"）
+    Label(String),
+}
+
 /// 写入请求
 #[derive(Deserialize, ToSchema)]
 pub struct WriteRequest {
     /// 节点全局 ID
     pub global_id: u32,
     /// 写入值
-    pub value: i32,
+    pub value: WriteValue,
+    /// 操作员（节点需要写入确认时记录为发起人；启用认证时以登录用户 / API Key 为准）
+    #[serde(default)]
+    pub operator: Option<String>,
 }
 
 /// 批量写入项
@@ -28,7 +58,7 @@ pub struct WriteManyItem {
     /// 节点全局 ID
     pub id: u32,
     /// 写入值
-    pub value: i32,
+    pub value: WriteValue,
 }
 
 /// 批量写入请求
@@ -36,6 +66,9 @@ pub struct WriteManyItem {
 pub struct WriteManyRequest {
     /// 写入项列表
     pub items: Vec<WriteManyItem>,
+    /// 操作员（节点需要写入确认时记录为发起人；启用认证时以登录用户 / API Key 为准）
+    #[serde(default)]
+    pub operator: Option<String>,
 }
 
 /// 批量写入结果项
@@ -48,6 +81,112 @@ pub struct WriteManyResultItem {
     /// 错误信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// 节点需要写入确认时的待确认请求（此时未写入，success 为 false）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation: Option<PendingWriteResponse>,
+    /// 依赖未满足（`depend_strategy = "reject"`）时的说明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unmet_dependencies: Option<Vec<UnmetDependency>>,
+}
+
+/// 单个写入的响应数据
+#[derive(Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum WriteResponse {
+    /// 节点需要写入确认时的待确认请求
+    Confirmation(PendingWriteResponse),
+    /// 依赖未满足（`depend_strategy = "reject"`）时的说明
+    UnmetDependencies {
+        unmet_dependencies: Vec<UnmetDependency>,
+    },
+}
+
+/// 待确认写入
+#[derive(Serialize, ToSchema)]
+pub struct PendingWriteResponse {
+    /// 确认令牌
+    pub token: String,
+    /// 节点全局 ID
+    pub global_id: u32,
+    /// 待写入值
+    pub value: f64,
+    /// 发起人
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_by: Option<String>,
+    /// 是否要求由发起人以外的操作员确认
+    pub different_operator: bool,
+    /// 过期时间（UTC RFC3339）
+    pub expires_at: String,
+}
+
+impl From<PendingWrite> for PendingWriteResponse {
+    fn from(pending: PendingWrite) -> Self {
+        Self {
+            token: pending.token,
+            global_id: pending.global_id,
+            value: pending.value,
+            requested_by: pending.requested_by,
+            different_operator: pending.different_operator,
+            expires_at: time::instant_rfc3339(pending.expires_at),
+        }
+    }
+}
+
+/// 暂存的离线写入
+#[derive(Serialize, ToSchema)]
+pub struct QueuedWriteResponse {
+    /// 节点全局 ID
+    pub global_id: u32,
+    /// 所属通道
+    pub channel_id: u32,
+    /// 待下发值
+    pub value: i32,
+    /// 暂存原因（写入失败的错误）
+    pub reason: String,
+    /// 重试下发次数
+    pub attempts: u32,
+    /// 最近一次重试失败的错误
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// 暂存时间（UTC RFC3339）
+    pub queued_at: String,
+    /// 过期时间（UTC RFC3339）
+    pub expires_at: String,
+}
+
+impl From<QueuedWrite> for QueuedWriteResponse {
+    fn from(queued: QueuedWrite) -> Self {
+        Self {
+            global_id: queued.global_id,
+            channel_id: queued.channel_id,
+            value: queued.value,
+            reason: queued.reason,
+            attempts: queued.attempts,
+            last_error: queued.last_error,
+            queued_at: time::instant_rfc3339(queued.queued_at),
+            expires_at: time::instant_rfc3339(queued.expires_at),
+        }
+    }
+}
+
+/// 确认 / 取消写入请求
+#[derive(Deserialize, Default, ToSchema)]
+pub struct ConfirmWriteRequest {
+    /// 确认操作员（启用认证时以登录用户 / API Key 为准）
+    #[serde(default)]
+    pub operator: Option<String>,
+}
+
+/// 强制节点值请求
+#[derive(Deserialize, ToSchema)]
+pub struct ForceNodeRequest {
+    /// 节点全局 ID
+    pub global_id: u32,
+    /// 强制值
+    pub value: WriteValue,
+    /// 强制原因
+    #[serde(default)]
+    pub reason: Option<String>,
 }
 
 /// 读取请求
@@ -74,6 +213,12 @@ pub struct ReadManyResultItem {
     /// 读取到的值
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<f64>,
+    /// 值的单位（按偏好单位制换算后的单位）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// 值对应的状态名称（节点配置了 value_labels 时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     /// 错误信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
@@ -91,6 +236,133 @@ pub struct StatusRequest {
 pub struct SceneRequest {
     /// 场景名称
     pub name: String,
+    /// cue 模式：每个步骤执行前暂停，等待 `POST /scene/{run_id}/next` 放行
+    #[serde(default)]
+    pub cue: bool,
+}
+
+/// 场景启动响应
+#[derive(Serialize, ToSchema)]
+pub struct SceneStartResponse {
+    /// 执行编号
+    pub run_id: u64,
+    /// 是否为 cue 模式
+    pub cue: bool,
+}
+
+/// cue 模式下的场景步骤
+#[derive(Serialize, ToSchema)]
+pub struct SceneCueResponse {
+    /// 执行编号
+    pub run_id: u64,
+    pub scene: String,
+    /// 步骤索引（0-based）
+    pub step: usize,
+    pub total_steps: usize,
+    /// 步骤标签
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// 步骤定义（与场景配置相同）
+    #[schema(value_type = Object)]
+    pub definition: SceneNode,
+    /// 步骤涉及的节点（目标值与当前值）
+    pub targets: Vec<SceneStepDiffResponse>,
+}
+
+/// 场景预览中的单个步骤
+#[derive(Serialize, ToSchema)]
+pub struct SceneStepDiffResponse {
+    /// 步骤索引（0-based）
+    pub step: usize,
+    /// 节点全局 ID
+    pub global_id: u32,
+    /// 节点别名（节点不存在时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// 场景目标值
+    pub target_value: i32,
+    /// 目标值对应的状态名称（节点配置了 value_labels 时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_label: Option<String>,
+    /// 执行到该步骤前的节点值（未知时为 null）
+    pub current_value: Option<i32>,
+    /// 当前值对应的状态名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_label: Option<String>,
+    /// 节点是否在线
+    pub online: bool,
+    /// 执行该步骤会改变节点值
+    pub changed: bool,
+}
+
+/// 场景预览响应
+#[derive(Serialize, ToSchema)]
+pub struct SceneDiffResponse {
+    /// 场景名称
+    pub scene: String,
+    /// 会改变节点值的步骤数
+    pub changed_count: usize,
+    /// 各步骤对比结果
+    pub steps: Vec<SceneStepDiffResponse>,
+}
+
+/// 场景导出请求
+#[derive(Deserialize, Default, ToSchema)]
+pub struct SceneExportRequest {
+    /// 要导出的场景名称，省略或为空时导出全部场景
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+/// 场景导入预览请求
+#[derive(Deserialize, ToSchema)]
+pub struct SceneImportPreviewRequest {
+    /// 导出得到的场景包
+    pub package: ScenePackage,
+    /// 手动节点映射（源节点 ID → 本系统节点 ID）
+    #[serde(default)]
+    pub node_map: HashMap<u32, u32>,
+    /// 场景重命名（原名称 → 新名称）
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+}
+
+/// 场景导入预览响应
+#[derive(Serialize, ToSchema)]
+pub struct SceneImportPreviewResponse {
+    /// 场景包中的场景名称（已应用重命名）
+    pub scenes: Vec<String>,
+    /// 与本系统已有场景重名的场景
+    pub conflicts: Vec<String>,
+    /// 节点映射结果
+    pub nodes: Vec<NodeMapping>,
+    /// 全部节点均已映射，可直接导入（重名场景需重命名或 overwrite）
+    pub ready: bool,
+}
+
+/// 场景导入请求
+#[derive(Deserialize, ToSchema)]
+pub struct SceneImportRequest {
+    /// 导出得到的场景包
+    pub package: ScenePackage,
+    /// 手动节点映射（源节点 ID → 本系统节点 ID）
+    #[serde(default)]
+    pub node_map: HashMap<u32, u32>,
+    /// 场景重命名（原名称 → 新名称）
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+    /// 覆盖同名场景
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// 场景导入响应
+#[derive(Serialize, ToSchema)]
+pub struct SceneImportResponse {
+    /// 已导入的场景名称
+    pub imported: Vec<String>,
+    /// 使用的节点映射
+    pub nodes: Vec<NodeMapping>,
 }
 
 /// 通道命令请求
@@ -115,6 +387,66 @@ pub struct CallMethodRequest {
     pub arguments: serde_json::Value,
 }
 
+/// 批量调用方法项
+#[derive(Deserialize, ToSchema)]
+pub struct InvokeManyItem {
+    /// 通道 ID
+    pub channel_id: u32,
+    /// 方法名称
+    pub method: String,
+    /// 引用的共享参数模板名称
+    #[serde(default)]
+    pub template: Option<String>,
+    /// 方法参数；与模板均为对象时按字段覆盖模板
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub args: Option<serde_json::Value>,
+    /// 模板变量，替换参数字符串中的 `{{名称}}`（内置 `channel_id`、`method`）
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub vars: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 批量调用方法请求
+#[derive(Deserialize, ToSchema)]
+pub struct InvokeManyRequest {
+    /// 调用项列表
+    pub items: Vec<InvokeManyItem>,
+    /// 共享参数模板（名称 → 参数 JSON）
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub templates: HashMap<String, serde_json::Value>,
+    /// 最大并发调用数（默认 4，最大 32）
+    #[serde(default = "default_invoke_concurrency")]
+    pub concurrency: usize,
+}
+
+fn default_invoke_concurrency() -> usize {
+    4
+}
+
+/// 批量调用并发数上限
+const MAX_INVOKE_CONCURRENCY: usize = 32;
+
+/// 批量调用方法结果项
+#[derive(Serialize, ToSchema)]
+pub struct InvokeManyResultItem {
+    /// 调用项序号（与请求中的顺序一致）
+    pub index: usize,
+    pub channel_id: u32,
+    pub method: String,
+    /// 是否成功
+    pub success: bool,
+    /// 方法返回值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// 错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 调用耗时（毫秒）
+    pub elapsed_ms: u64,
+}
+
 /// 获取方法列表请求
 #[derive(Deserialize, ToSchema)]
 pub struct GetMethodsRequest {
@@ -170,6 +502,90 @@ pub struct SceneExecutionStatusResponse {
     /// 当前执行场景总步骤数
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_steps: Option<usize>,
+    /// 全部排队中和执行中的场景
+    pub runs: Vec<SceneRunResponse>,
+    /// 最近一次结束的场景执行结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_result: Option<SceneRunResultResponse>,
+}
+
+/// 场景执行结果
+#[derive(Serialize, ToSchema)]
+pub struct SceneRunResultResponse {
+    /// 执行编号
+    pub run_id: u64,
+    /// 场景名称
+    pub scene: String,
+    /// 全部步骤成功且未被取消、中止
+    pub success: bool,
+    /// 被同组场景取消
+    pub cancelled: bool,
+    /// 因步骤失败而中止
+    pub aborted: bool,
+    /// 失败的步骤
+    pub failed_steps: Vec<SceneStepFailureResponse>,
+}
+
+/// 失败的场景步骤
+#[derive(Serialize, ToSchema)]
+pub struct SceneStepFailureResponse {
+    /// 步骤索引（0-based）
+    pub step: usize,
+    /// 步骤标签
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// 节点全局 ID
+    pub global_id: u32,
+    /// 失败原因
+    pub error: String,
+    /// 尝试次数（含重试）
+    pub attempts: u32,
+}
+
+impl From<SceneRunResult> for SceneRunResultResponse {
+    fn from(result: SceneRunResult) -> Self {
+        Self {
+            run_id: result.run_id,
+            scene: result.scene,
+            success: result.success,
+            cancelled: result.cancelled,
+            aborted: result.aborted,
+            failed_steps: result
+                .failed_steps
+                .into_iter()
+                .map(|f| SceneStepFailureResponse {
+                    step: f.step,
+                    label: f.label,
+                    global_id: f.global_id,
+                    error: f.error,
+                    attempts: f.attempts,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 单次场景执行状态
+#[derive(Serialize, ToSchema)]
+pub struct SceneRunResponse {
+    /// 执行编号
+    pub run_id: u64,
+    /// 场景名称
+    pub scene: String,
+    /// 互斥组
+    pub group: String,
+    /// 是否在排队等待同组场景结束
+    pub queued: bool,
+    /// 当前执行步骤索引（0-based）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_step_index: Option<usize>,
+    /// 总步骤数
+    pub total_steps: usize,
+    /// 是否为 cue 模式
+    pub cue: bool,
+    /// cue 模式下等待继续的步骤
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub awaiting_cue: Option<SceneCueResponse>,
 }
 
 /// 系统设置响应
@@ -193,6 +609,121 @@ pub struct SystemSettingsResponse {
     pub actuator: String,
 }
 
+/// 设备模型导出查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeviceModelQuery {
+    /// 导出格式: 默认为内置格式，"wot" 导出 W3C WoT Thing Description
+    pub format: Option<String>,
+    /// 只导出该站点的通道、节点与场景
+    pub site: Option<String>,
+}
+
+/// 列表接口的站点过滤参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct SiteQuery {
+    /// 只返回该站点的通道或节点（省略时返回全部站点）
+    pub site: Option<String>,
+}
+
+/// 站点信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SiteResponse {
+    pub id: String,
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub timezone: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+    /// 站点下的通道 ID（受限 API Key 只列出可见的通道）
+    pub channels: Vec<u32>,
+}
+
+/// 通道缓存清除请求（省略请求体或地址时清除全部）
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct CacheInvalidateRequest {
+    /// 起始地址（含）
+    pub start_addr: Option<u16>,
+    /// 结束地址（含），省略时只清除 start_addr
+    pub end_addr: Option<u16>,
+    /// 从站地址，省略时作用于全部从站
+    pub slave_id: Option<u8>,
+}
+
+/// 原始命令负载编码
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RawEncoding {
+    /// 十六进制，可用空格、`:`、`-`、`,` 分隔，允许 `0x` 前缀
+    #[default]
+    Hex,
+    /// 文本，支持 `\r`、`\n`、`\t`、`\0`、`\\` 与 `\xHH` 转义
+    Ascii,
+}
+
+/// 原始命令请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RawCommandRequest {
+    /// 发送内容
+    pub payload: String,
+    /// 负载编码，默认 hex
+    #[serde(default)]
+    pub encoding: RawEncoding,
+    /// 等待响应的超时（毫秒），默认 3000
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// 原始命令响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RawCommandResponse {
+    /// 实际发送的字节（十六进制）
+    pub sent_hex: String,
+    pub bytes_sent: usize,
+    /// 设备响应（十六进制）
+    pub response_hex: String,
+    /// 设备响应（文本，不可打印字符转义为 `\xHH`）
+    pub response_ascii: String,
+    pub bytes_received: usize,
+    /// 发送到收完响应的耗时（毫秒）
+    pub elapsed_ms: f64,
+}
+
+/// 原始命令默认超时
+const RAW_DEFAULT_TIMEOUT_MS: u64 = 3000;
+/// 原始命令最长超时
+const RAW_MAX_TIMEOUT_MS: u64 = 30_000;
+
+/// 通道延迟测试请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChannelPingRequest {
+    /// 测试方式，默认 status
+    #[serde(default)]
+    pub mode: PingMode,
+    /// 测试次数，默认 4，最多 50
+    #[serde(default)]
+    pub count: Option<u32>,
+    /// 两次测试的间隔（毫秒），默认 200，最长 10000
+    #[serde(default)]
+    pub interval_ms: Option<u64>,
+    /// 单次测试超时（毫秒），默认 2000
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// 延迟测试默认次数
+const PING_DEFAULT_COUNT: u32 = 4;
+/// 延迟测试最多次数
+const PING_MAX_COUNT: u32 = 50;
+/// 延迟测试默认间隔
+const PING_DEFAULT_INTERVAL_MS: u64 = 200;
+/// 延迟测试最长间隔
+const PING_MAX_INTERVAL_MS: u64 = 10_000;
+/// 延迟测试默认单次超时
+const PING_DEFAULT_TIMEOUT_MS: u64 = 2000;
+
+/// 设备模型结构版本，字段发生不兼容变化时递增
+const DEVICE_MODEL_VERSION: u32 = 1;
+
 // ===== API 处理函数 =====
 
 /// 获取系统设置
@@ -264,6 +795,7 @@ pub async fn get_all_settings(
 #[utoipa::path(
     post,
     path = "/lspcapi/device/getAllStatus",
+    params(SiteQuery),
     request_body = serde_json::Value,
     responses(
         (status = 200, description = "获取成功", body = inline(ApiResponse<serde_json::Value>))
@@ -272,13 +804,33 @@ pub async fn get_all_settings(
 )]
 pub async fn get_all_status(
     Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SiteQuery>,
 ) -> Json<ApiResponse<serde_json::Value>> {
-    match controller.read().await.get_all_channel_status().await {
-        Ok(data) => Json(ApiResponse {
-            state: error_codes::SUCCESS,
-            message: "成功".to_string(),
-            data: Some(data),
-        }),
+    let controller = controller.read().await;
+    match controller.get_all_channel_status().await {
+        Ok(mut data) => {
+            if principal.scope.is_some() || query.site.is_some() {
+                let nodes = controller.get_all_node_configs();
+                let site = query.site.as_deref();
+                if let Some(statuses) = data.as_array_mut() {
+                    statuses.retain(|status| {
+                        status
+                            .get("channel_id")
+                            .and_then(|id| id.as_u64())
+                            .is_some_and(|id| {
+                                principal.can_see_channel(id as u32, &nodes)
+                                    && in_site(&controller, id as u32, site)
+                            })
+                    });
+                }
+            }
+            Json(ApiResponse {
+                state: error_codes::SUCCESS,
+                message: "成功".to_string(),
+                data: Some(data),
+            })
+        }
         Err(e) => Json(ApiResponse {
             state: error_codes::GENERAL_ERROR,
             message: format!("错误: {:?}", e),
@@ -287,10 +839,93 @@ pub async fn get_all_status(
     }
 }
 
+/// 受限 API Key 可访问、且属于指定站点的节点（不限制范围且不按站点过滤时返回 `None`）
+fn visible_nodes(
+    principal: &Principal,
+    controller: &DeviceController,
+    site: Option<&str>,
+) -> Option<HashSet<u32>> {
+    if principal.scope.is_none() && site.is_none() {
+        return None;
+    }
+    Some(
+        controller
+            .get_all_node_configs()
+            .iter()
+            .filter(|n| principal.can_access_node(n) && in_site(controller, n.channel_id, site))
+            .map(|n| n.global_id)
+            .collect(),
+    )
+}
+
+/// 通道是否属于指定站点（未指定站点时总是成立）
+fn in_site(controller: &DeviceController, channel_id: u32, site: Option<&str>) -> bool {
+    site.is_none_or(|site| controller.channel_site(channel_id).as_deref() == Some(site))
+}
+
+/// 节点状态列表项（全部节点状态接口与事件流快照共用）
+pub(super) fn node_state_json(
+    controller: &DeviceController,
+    global_id: u32,
+    state: &NodeState,
+    units: Option<UnitSystem>,
+) -> serde_json::Value {
+    let converted = state
+        .current_value
+        .and_then(|v| convert_to_units(controller, global_id, v as f64, units));
+    let (current_value, unit) = match converted {
+        Some((value, unit)) => (serde_json::json!(value), Some(unit)),
+        None => (
+            serde_json::json!(state.current_value),
+            controller.node_unit(global_id),
+        ),
+    };
+    serde_json::json!({
+        "global_id": global_id,
+        "channel_id": state.channel_id,
+        "device_id": state.device_id,
+        "category": state.category,
+        "alias": state.alias,
+        "current_value": current_value,
+        "unit": unit,
+        "online": state.online,
+        "restored": state.restored,
+        "forced": state.forced,
+        "label": state.current_value.and_then(|v| controller.get_value_label(global_id, v)),
+        "metadata": state.metadata,
+        "updated_at": state.last_update.map(time::instant_rfc3339),
+        "age_ms": state.last_update.map(|t| t.elapsed().as_millis() as u64),
+    })
+}
+
+/// 偏好单位制的请求头
+const UNIT_SYSTEM_HEADER: &str = "x-unit-system";
+
+/// 请求偏好的单位制：`X-Unit-System` 请求头优先，其次为 API Key 配置的 `unit_system`
+pub(crate) fn preferred_units(headers: &HeaderMap, principal: &Principal) -> Option<UnitSystem> {
+    headers
+        .get(UNIT_SYSTEM_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .or(principal.unit_system)
+}
+
+/// 节点值按偏好单位制换算，返回换算后的值与单位（无需或无法换算时返回 `None`）
+fn convert_to_units(
+    controller: &DeviceController,
+    global_id: u32,
+    value: f64,
+    units: Option<UnitSystem>,
+) -> Option<(f64, String)> {
+    let unit = controller.node_unit(global_id)?;
+    units::to_system(value, &unit, units?)
+}
+
 /// 获取所有节点状态
 #[utoipa::path(
     post,
     path = "/lspcapi/device/getAllNodeStates",
+    params(SiteQuery),
     request_body = serde_json::Value,
     responses(
         (status = 200, description = "获取成功", body = inline(ApiResponse<serde_json::Value>))
@@ -299,21 +934,18 @@ pub async fn get_all_status(
 )]
 pub async fn get_all_node_states(
     Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SiteQuery>,
+    headers: HeaderMap,
 ) -> Json<ApiResponse<serde_json::Value>> {
-    let states = controller.read().await.get_all_node_states();
+    let units = preferred_units(&headers, &principal);
+    let controller = controller.read().await;
+    let visible = visible_nodes(&principal, &controller, query.site.as_deref());
+    let states = controller.get_all_node_states();
     let data: Vec<_> = states
         .into_iter()
-        .map(|(global_id, state)| {
-            serde_json::json!({
-                "global_id": global_id,
-                "channel_id": state.channel_id,
-                "device_id": state.device_id,
-                "category": state.category,
-                "alias": state.alias,
-                "current_value": state.current_value,
-                "online": state.online,
-            })
-        })
+        .filter(|(global_id, _)| visible.as_ref().is_none_or(|v| v.contains(global_id)))
+        .map(|(global_id, state)| node_state_json(&controller, global_id, &state, units))
         .collect();
 
     Json(ApiResponse {
@@ -336,22 +968,22 @@ pub async fn get_all_node_states(
 )]
 pub async fn get_node_state(
     Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
     Json(payload): Json<StatusRequest>,
 ) -> Json<ApiResponse<serde_json::Value>> {
     if let Some(id) = payload.id {
-        match controller.read().await.get_node_state(id) {
+        let controller = controller.read().await;
+        match controller.get_node_state(id) {
             Some(state) => Json(ApiResponse {
                 state: error_codes::SUCCESS,
                 message: "成功".to_string(),
-                data: Some(serde_json::json!({
-                    "global_id": state.global_id,
-                    "channel_id": state.channel_id,
-                    "device_id": state.device_id,
-                    "category": state.category,
-                    "alias": state.alias,
-                    "current_value": state.current_value,
-                    "online": state.online,
-                })),
+                data: Some(node_state_json(
+                    &controller,
+                    id,
+                    &state,
+                    preferred_units(&headers, &principal),
+                )),
             }),
             None => Json(ApiResponse {
                 state: error_codes::DEVICE_NOT_FOUND,
@@ -380,13 +1012,20 @@ pub async fn get_node_state(
 )]
 pub async fn read_device(
     Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
     Json(payload): Json<ReadRequest>,
 ) -> Json<ApiResponse<f64>> {
-    match controller.read().await.read_node(payload.global_id).await {
+    let units = preferred_units(&headers, &principal);
+    let controller = controller.read().await;
+    match controller.read_node(payload.global_id).await {
         Ok(value) => Json(ApiResponse {
             state: error_codes::SUCCESS,
             message: "读取成功".to_string(),
-            data: Some(value),
+            data: Some(
+                convert_to_units(&controller, payload.global_id, value, units)
+                    .map_or(value, |(converted, _)| converted),
+            ),
         }),
         Err(e) => Json(ApiResponse {
             state: error_codes::GENERAL_ERROR,
@@ -408,19 +1047,29 @@ pub async fn read_device(
 )]
 pub async fn read_many(
     Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
     Json(payload): Json<ReadManyRequest>,
 ) -> Json<ApiResponse<Vec<ReadManyResultItem>>> {
+    let units = preferred_units(&headers, &principal);
     let mut results = Vec::new();
     let mut success_count = 0;
     let mut fail_count = 0;
 
     for id in payload.ids {
-        match controller.read().await.read_node(id).await {
+        let controller = controller.read().await;
+        match controller.read_node(id).await {
             Ok(value) => {
+                let (converted, unit) = match convert_to_units(&controller, id, value, units) {
+                    Some((converted, unit)) => (converted, Some(unit)),
+                    None => (value, controller.node_unit(id)),
+                };
                 results.push(ReadManyResultItem {
                     id,
                     success: true,
-                    value: Some(value),
+                    value: Some(converted),
+                    unit,
+                    label: controller.get_value_label(id, value.round() as i32),
                     error: None,
                 });
                 success_count += 1;
@@ -430,6 +1079,8 @@ pub async fn read_many(
                     id,
                     success: false,
                     value: None,
+                    unit: None,
+                    label: None,
                     error: Some(format!("{:?}", e)),
                 });
                 fail_count += 1;
@@ -450,25 +1101,55 @@ pub async fn read_many(
     path = "/lspcapi/device/write",
     request_body = WriteRequest,
     responses(
-        (status = 200, description = "写入成功", body = inline(ApiResponse<()>))
+        (status = 200, description = "写入成功", body = inline(ApiResponse<WriteResponse>))
     ),
     tag = "Device"
 )]
 pub async fn write_device(
     Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
     Json(payload): Json<WriteRequest>,
-) -> Json<ApiResponse<()>> {
-    match controller
-        .read()
-        .await
-        .write_node(payload.global_id, payload.value)
-        .await
-    {
+) -> Json<ApiResponse<WriteResponse>> {
+    let units = preferred_units(&headers, &principal);
+    let controller = controller.read().await;
+    let result = match resolve_write_value(&controller, payload.global_id, payload.value, units) {
+        Ok(value) => {
+            match controller.hold_for_confirmation(
+                payload.global_id,
+                value,
+                principal.operator(payload.operator),
+            ) {
+                Ok(Some(pending)) => {
+                    return Json(ApiResponse {
+                        state: error_codes::CONFIRMATION_REQUIRED,
+                        message: "该节点写入需要确认，请在有效期内调用确认接口".to_string(),
+                        data: Some(WriteResponse::Confirmation(pending.into())),
+                    })
+                }
+                Ok(None) => controller.write_node_value(payload.global_id, value).await,
+                Err(e) => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    };
+    match result {
         Ok(_) => Json(ApiResponse {
             state: error_codes::SUCCESS,
-            message: "操作成功".to_string(),
+            message: if controller.offline_write(payload.global_id).is_some() {
+                "通道离线，写入已暂存，通道恢复后自动下发".to_string()
+            } else {
+                "操作成功".to_string()
+            },
             data: None,
         }),
+        Err(DeviceError::DependencyNotMet(unmet)) => Json(ApiResponse {
+            state: error_codes::DEPENDENCY_NOT_MET,
+            message: format!("操作失败: {}", DeviceError::DependencyNotMet(unmet.clone())),
+            data: Some(WriteResponse::UnmetDependencies {
+                unmet_dependencies: unmet,
+            }),
+        }),
         Err(e) => Json(ApiResponse {
             state: error_codes::GENERAL_ERROR,
             message: format!("操作失败: {:?}", e),
@@ -489,27 +1170,67 @@ pub async fn write_device(
 )]
 pub async fn write_many(
     Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
     Json(payload): Json<WriteManyRequest>,
 ) -> Json<ApiResponse<Vec<WriteManyResultItem>>> {
+    let units = preferred_units(&headers, &principal);
     let mut results = Vec::new();
     let mut success_count = 0;
     let mut fail_count = 0;
+    let mut pending_count = 0;
 
     for item in payload.items {
-        match controller.read().await.write_node(item.id, item.value).await {
+        let controller = controller.read().await;
+        let result = match resolve_write_value(&controller, item.id, item.value, units) {
+            Ok(value) => {
+                match controller.hold_for_confirmation(
+                    item.id,
+                    value,
+                    principal.operator(payload.operator.clone()),
+                ) {
+                    Ok(Some(pending)) => {
+                        results.push(WriteManyResultItem {
+                            id: item.id,
+                            success: false,
+                            error: Some("需要确认".to_string()),
+                            confirmation: Some(pending.into()),
+                            unmet_dependencies: None,
+                        });
+                        pending_count += 1;
+                        continue;
+                    }
+                    Ok(None) => controller.write_node_value(item.id, value).await,
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        };
+        match result {
             Ok(_) => {
                 results.push(WriteManyResultItem {
                     id: item.id,
                     success: true,
                     error: None,
+                    confirmation: None,
+                    unmet_dependencies: None,
                 });
                 success_count += 1;
             }
             Err(e) => {
+                let (error, unmet_dependencies) = match e {
+                    DeviceError::DependencyNotMet(unmet) => (
+                        DeviceError::DependencyNotMet(unmet.clone()).to_string(),
+                        Some(unmet),
+                    ),
+                    e => (format!("{:?}", e), None),
+                };
                 results.push(WriteManyResultItem {
                     id: item.id,
                     success: false,
-                    error: Some(format!("{:?}", e)),
+                    error: Some(error),
+                    confirmation: None,
+                    unmet_dependencies,
                 });
                 fail_count += 1;
             }
@@ -518,211 +1239,2006 @@ pub async fn write_many(
 
     Json(ApiResponse {
         state: error_codes::SUCCESS,
-        message: format!("批量写入完成: 成功 {}, 失败 {}", success_count, fail_count),
+        message: format!(
+            "批量写入完成: 成功 {}, 失败 {}, 待确认 {}",
+            success_count, fail_count, pending_count
+        ),
         data: Some(results),
     })
 }
 
-/// 执行场景（异步执行）
-///
-/// 场景会在后台异步执行，此接口立即返回。
-/// 使用 /lspcapi/device/sceneStatus 接口查询执行状态。
-#[utoipa::path(
-    post,
-    path = "/lspcapi/device/scene",
-    request_body = SceneRequest,
-    responses(
-        (status = 200, description = "场景已开始执行", body = inline(ApiResponse<()>))
-    ),
-    tag = "Device"
-)]
-pub async fn execute_scene(
-    Extension(controller): Extension<SharedController>,
-    Json(payload): Json<SceneRequest>,
-) -> Json<ApiResponse<()>> {
-    match controller.read().await.execute_scene(&payload.name).await {
-        Ok(_) => Json(ApiResponse {
-            state: error_codes::SUCCESS,
-            message: format!("场景 '{}' 已开始执行", payload.name),
-            data: None,
-        }),
-        Err(e) => Json(ApiResponse {
-            state: error_codes::GENERAL_ERROR,
-            message: format!("场景启动失败: {:?}", e),
-            data: None,
-        }),
-    }
-}
-
-/// 获取场景执行状态
+/// 查询待确认写入
 #[utoipa::path(
     get,
-    path = "/lspcapi/device/sceneStatus",
+    path = "/lspcapi/device/confirmations",
+    params(SiteQuery),
     responses(
-        (status = 200, description = "获取成功", body = inline(ApiResponse<SceneExecutionStatusResponse>))
+        (status = 200, description = "获取成功", body = inline(ApiResponse<Vec<PendingWriteResponse>>))
     ),
     tag = "Device"
 )]
-pub async fn get_scene_status(
+pub async fn list_confirmations(
     Extension(controller): Extension<SharedController>,
-) -> Json<ApiResponse<SceneExecutionStatusResponse>> {
-    let status = controller.read().await.get_scene_execution_status().await;
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SiteQuery>,
+) -> Json<ApiResponse<Vec<PendingWriteResponse>>> {
+    let controller = controller.read().await;
+    let visible = visible_nodes(&principal, &controller, query.site.as_deref());
+    let mut pending = controller.list_confirmations();
+    pending.retain(|p| visible.as_ref().is_none_or(|v| v.contains(&p.global_id)));
     Json(ApiResponse {
         state: error_codes::SUCCESS,
-        message: "获取场景执行状态成功".to_string(),
-        data: Some(SceneExecutionStatusResponse {
-            is_executing: status.is_executing,
-            current_scene: status.current_scene,
-            current_step_index: status.current_step_index,
-            total_steps: status.total_steps,
-        }),
+        message: format!("共 {} 个待确认写入", pending.len()),
+        data: Some(pending.into_iter().map(Into::into).collect()),
     })
 }
 
-/// 执行通道命令
+/// 确认写入
+///
+/// 令牌只能使用一次；节点配置了 different_operator 时，确认人必须与发起人不同。
+/// 启用认证时发起人与确认人均取自登录用户 / API Key，请求中的 operator 仅在未启用认证时使用。
 #[utoipa::path(
     post,
-    path = "/lspcapi/device/executeCommand",
-    request_body = ChannelCommandRequest,
+    path = "/lspcapi/device/confirmations/{token}/confirm",
+    params(("token" = String, Path, description = "确认令牌")),
+    request_body = Option<ConfirmWriteRequest>,
     responses(
-        (status = 200, description = "命令执行成功", body = inline(ApiResponse<serde_json::Value>))
+        (status = 200, description = "确认成功并已写入", body = inline(ApiResponse<PendingWriteResponse>))
     ),
     tag = "Device"
 )]
-pub async fn execute_channel_command(
+pub async fn confirm_write(
     Extension(controller): Extension<SharedController>,
-    Json(payload): Json<ChannelCommandRequest>,
-) -> Json<ApiResponse<serde_json::Value>> {
+    Extension(principal): Extension<Principal>,
+    Path(token): Path<String>,
+    payload: Option<Json<ConfirmWriteRequest>>,
+) -> Json<ApiResponse<PendingWriteResponse>> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let operator = principal.operator(payload.operator);
     match controller
         .read()
         .await
-        .execute_channel_command(payload.channel_id, &payload.command, payload.params)
+        .confirm_write(&token, operator.as_deref())
         .await
     {
-        Ok(result) => Json(ApiResponse {
+        Ok(pending) => Json(ApiResponse {
             state: error_codes::SUCCESS,
-            message: "命令执行成功".to_string(),
-            data: Some(result),
+            message: "确认成功，已写入".to_string(),
+            data: Some(pending.into()),
         }),
         Err(e) => Json(ApiResponse {
             state: error_codes::GENERAL_ERROR,
-            message: format!("命令执行失败: {:?}", e),
+            message: format!("确认失败: {:?}", e),
             data: None,
         }),
     }
 }
 
-/// 调用通道方法
+/// 取消待确认写入
 #[utoipa::path(
     post,
-    path = "/lspcapi/device/callMethod",
-    request_body = CallMethodRequest,
+    path = "/lspcapi/device/confirmations/{token}/cancel",
+    params(("token" = String, Path, description = "确认令牌")),
     responses(
-        (status = 200, description = "方法调用成功", body = inline(ApiResponse<serde_json::Value>))
+        (status = 200, description = "已取消", body = inline(ApiResponse<PendingWriteResponse>))
     ),
     tag = "Device"
 )]
-pub async fn call_method(
+pub async fn cancel_confirmation(
     Extension(controller): Extension<SharedController>,
-    Json(payload): Json<CallMethodRequest>,
-) -> Json<ApiResponse<serde_json::Value>> {
-    match controller
-        .read()
-        .await
-        .call_channel_method(payload.channel_id, &payload.method_name, payload.arguments)
-        .await
-    {
-        Ok(result) => Json(ApiResponse {
+    Path(token): Path<String>,
+) -> Json<ApiResponse<PendingWriteResponse>> {
+    match controller.read().await.cancel_confirmation(&token) {
+        Some(pending) => Json(ApiResponse {
             state: error_codes::SUCCESS,
-            message: "方法调用成功".to_string(),
-            data: Some(result),
+            message: "已取消".to_string(),
+            data: Some(pending.into()),
         }),
-        Err(e) => Json(ApiResponse {
+        None => Json(ApiResponse {
             state: error_codes::GENERAL_ERROR,
-            message: format!("方法调用失败: {:?}", e),
+            message: format!("确认令牌 '{}' 不存在或已使用", token),
             data: None,
         }),
     }
 }
 
-/// 获取通道方法列表
+/// 查询强制中的节点
 #[utoipa::path(
-    post,
-    path = "/lspcapi/device/getMethods",
-    request_body = GetMethodsRequest,
+    get,
+    path = "/lspcapi/device/forces",
+    params(SiteQuery),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<Vec<ForcedNode>>))
+    ),
+    tag = "Device"
+)]
+pub async fn list_forced_nodes(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SiteQuery>,
+) -> Json<ApiResponse<Vec<ForcedNode>>> {
+    let controller = controller.read().await;
+    let visible = visible_nodes(&principal, &controller, query.site.as_deref());
+    let mut forced = controller.forced_nodes();
+    forced.retain(|f| visible.as_ref().is_none_or(|v| v.contains(&f.global_id)));
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: format!("共 {} 个节点处于强制状态", forced.len()),
+        data: Some(forced),
+    })
+}
+
+/// 强制节点值
+///
+/// 强制期间读取返回强制值（节点状态 `forced` 为 true），对该节点的写入不会下发到设备，
+/// 轮询到的设备实际值记录在 `actual_value` 中，解除强制后恢复。
+/// 强制只保存在内存中，配置热重载或重启服务后失效。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/forces",
+    request_body = ForceNodeRequest,
+    responses(
+        (status = 200, description = "强制成功", body = inline(ApiResponse<ForcedNode>))
+    ),
+    tag = "Device"
+)]
+pub async fn force_node(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    headers: HeaderMap,
+    Json(payload): Json<ForceNodeRequest>,
+) -> Json<ApiResponse<ForcedNode>> {
+    let units = preferred_units(&headers, &principal);
+    let controller = controller.read().await;
+    let result = resolve_write_value(&controller, payload.global_id, payload.value, units)
+        .and_then(|value| {
+            // 强制值与节点状态一样按整数保存
+            controller.force_node(
+                payload.global_id,
+                value.round() as i32,
+                &principal.name,
+                payload.reason.clone(),
+            )
+        });
+    match result {
+        Ok(forced) => {
+            crate::utils::audit::record(
+                &principal.name,
+                "force_node",
+                serde_json::json!({
+                    "global_id": forced.global_id,
+                    "value": forced.value,
+                    "reason": forced.reason,
+                }),
+            );
+            Json(ApiResponse {
+                state: error_codes::SUCCESS,
+                message: format!("节点 {} 已强制为 {}", forced.global_id, forced.value),
+                data: Some(forced),
+            })
+        }
+        Err(e) => Json(ApiResponse {
+            state: match e {
+                crate::utils::DeviceError::DeviceNotFound(_) => error_codes::DEVICE_NOT_FOUND,
+                _ => error_codes::INVALID_PARAMS,
+            },
+            message: format!("强制失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 解除节点强制
+#[utoipa::path(
+    delete,
+    path = "/lspcapi/device/forces/{global_id}",
+    params(("global_id" = u32, Path, description = "节点全局 ID")),
+    responses(
+        (status = 200, description = "已解除", body = inline(ApiResponse<ForcedNode>))
+    ),
+    tag = "Device"
+)]
+pub async fn release_node_force(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Path(global_id): Path<u32>,
+) -> Json<ApiResponse<ForcedNode>> {
+    match controller.read().await.release_node_force(global_id) {
+        Some(released) => {
+            crate::utils::audit::record(
+                &principal.name,
+                "release_node_force",
+                serde_json::json!({ "global_id": global_id }),
+            );
+            Json(ApiResponse {
+                state: error_codes::SUCCESS,
+                message: format!("节点 {} 已解除强制", global_id),
+                data: Some(released),
+            })
+        }
+        None => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("节点 {} 未处于强制状态", global_id),
+            data: None,
+        }),
+    }
+}
+
+/// 查询暂存的离线写入
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/offlineWrites",
+    params(SiteQuery),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<Vec<QueuedWriteResponse>>))
+    ),
+    tag = "Device"
+)]
+pub async fn list_offline_writes(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SiteQuery>,
+) -> Json<ApiResponse<Vec<QueuedWriteResponse>>> {
+    let controller = controller.read().await;
+    let visible = visible_nodes(&principal, &controller, query.site.as_deref());
+    let queued: Vec<QueuedWriteResponse> = controller
+        .offline_writes()
+        .into_iter()
+        .filter(|q| visible.as_ref().is_none_or(|v| v.contains(&q.global_id)))
+        .map(Into::into)
+        .collect();
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: format!("共 {} 个写入等待通道恢复", queued.len()),
+        data: Some(queued),
+    })
+}
+
+/// 取消暂存的离线写入
+#[utoipa::path(
+    delete,
+    path = "/lspcapi/device/offlineWrites/{global_id}",
+    params(("global_id" = u32, Path, description = "节点全局 ID")),
+    responses(
+        (status = 200, description = "已取消", body = inline(ApiResponse<QueuedWriteResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn cancel_offline_write(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Path(global_id): Path<u32>,
+) -> Json<ApiResponse<QueuedWriteResponse>> {
+    match controller.read().await.cancel_offline_write(global_id) {
+        Some(cancelled) => {
+            crate::utils::audit::record(
+                &principal.name,
+                "cancel_offline_write",
+                serde_json::json!({ "global_id": global_id, "value": cancelled.value }),
+            );
+            Json(ApiResponse {
+                state: error_codes::SUCCESS,
+                message: format!("节点 {} 暂存的写入已取消", global_id),
+                data: Some(cancelled.into()),
+            })
+        }
+        None => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("节点 {} 没有暂存的写入", global_id),
+            data: None,
+        }),
+    }
+}
+
+/// 查询时间曲线
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/profiles",
+    params(SiteQuery),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<Vec<ProfileStatus>>))
+    ),
+    tag = "Device"
+)]
+pub async fn list_profiles(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SiteQuery>,
+) -> Json<ApiResponse<Vec<ProfileStatus>>> {
+    let controller = controller.read().await;
+    let visible = visible_nodes(&principal, &controller, query.site.as_deref());
+    let mut profiles = controller.profiles();
+    profiles.retain(|p| visible.as_ref().is_none_or(|v| v.contains(&p.global_id)));
+    Json(ApiResponse::success("获取时间曲线成功", profiles))
+}
+
+/// 启动时间曲线
+///
+/// 曲线已在运行时直接返回当前状态。运行状态只保存在内存中，配置热重载或重启服务后
+/// 只有 `autostart` 的曲线自动运行。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/profiles/{name}/start",
+    params(("name" = String, Path, description = "时间曲线名称")),
+    responses(
+        (status = 200, description = "已启动", body = inline(ApiResponse<ProfileStatus>))
+    ),
+    tag = "Device"
+)]
+pub async fn start_profile(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<ProfileStatus>> {
+    match controller.read().await.start_profile(&name) {
+        Ok(status) => {
+            crate::utils::audit::record(
+                &principal.name,
+                "start_profile",
+                serde_json::json!({ "profile": name }),
+            );
+            Json(ApiResponse::success(
+                format!("时间曲线 '{}' 已启动", name),
+                status,
+            ))
+        }
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 停止时间曲线（节点保持最后写入的值）
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/profiles/{name}/stop",
+    params(("name" = String, Path, description = "时间曲线名称")),
+    responses(
+        (status = 200, description = "已停止", body = inline(ApiResponse<ProfileStatus>))
+    ),
+    tag = "Device"
+)]
+pub async fn stop_profile(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<ProfileStatus>> {
+    match controller.read().await.stop_profile(&name).await {
+        Ok(status) => {
+            crate::utils::audit::record(
+                &principal.name,
+                "stop_profile",
+                serde_json::json!({ "profile": name }),
+            );
+            Json(ApiResponse::success(
+                format!("时间曲线 '{}' 已停止", name),
+                status,
+            ))
+        }
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 解除全部节点强制
+///
+/// 受限 API Key 只解除其可访问节点的强制。
+#[utoipa::path(
+    delete,
+    path = "/lspcapi/device/forces",
+    responses(
+        (status = 200, description = "已解除", body = inline(ApiResponse<Vec<ForcedNode>>))
+    ),
+    tag = "Device"
+)]
+pub async fn release_all_forces(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+) -> Json<ApiResponse<Vec<ForcedNode>>> {
+    let controller = controller.read().await;
+    let released = match visible_nodes(&principal, &controller, None) {
+        None => controller.release_all_forces(),
+        Some(visible) => controller
+            .forced_nodes()
+            .into_iter()
+            .filter(|f| visible.contains(&f.global_id))
+            .filter_map(|f| controller.release_node_force(f.global_id))
+            .collect(),
+    };
+    crate::utils::audit::record(
+        &principal.name,
+        "release_all_forces",
+        serde_json::json!({
+            "global_ids": released.iter().map(|f| f.global_id).collect::<Vec<_>>(),
+        }),
+    );
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: format!("已解除 {} 个节点的强制", released.len()),
+        data: Some(released),
+    })
+}
+
+/// 将写入值解析为节点数值（状态名称按 value_labels 反查，数值从偏好单位制换算回节点单位）
+pub(crate) fn resolve_write_value(
+    controller: &DeviceController,
+    global_id: u32,
+    value: WriteValue,
+    units: Option<UnitSystem>,
+) -> crate::utils::Result<f64> {
+    match value {
+        WriteValue::Number(v) => Ok(units
+            .zip(controller.node_unit(global_id))
+            .and_then(|(system, unit)| units::from_system(v, &unit, system))
+            .unwrap_or(v)),
+        WriteValue::Label(label) => controller
+            .resolve_value_label(global_id, &label)
+            .map(f64::from),
+    }
+}
+
+/// 执行场景（异步执行）
+///
+/// 场景会在后台异步执行，此接口立即返回执行编号。
+/// 使用 /lspcapi/device/sceneStatus 接口查询执行状态。
+/// `cue` 为 true 时每个步骤执行前暂停，通过 /lspcapi/device/scene/{run_id}/next 逐步放行。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/scene",
+    request_body = SceneRequest,
+    responses(
+        (status = 200, description = "场景已开始执行", body = inline(ApiResponse<SceneStartResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn execute_scene(
+    Extension(controller): Extension<SharedController>,
+    Json(payload): Json<SceneRequest>,
+) -> Json<ApiResponse<SceneStartResponse>> {
+    match controller
+        .read()
+        .await
+        .start_scene(&payload.name, payload.cue)
+        .await
+    {
+        Ok(run_id) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: if payload.cue {
+                format!("场景 '{}' 已以 cue 模式开始执行", payload.name)
+            } else {
+                format!("场景 '{}' 已开始执行", payload.name)
+            },
+            data: Some(SceneStartResponse {
+                run_id,
+                cue: payload.cue,
+            }),
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("场景启动失败: {:?}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 获取场景执行状态
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/sceneStatus",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<SceneExecutionStatusResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn get_scene_status(
+    Extension(controller): Extension<SharedController>,
+) -> Json<ApiResponse<SceneExecutionStatusResponse>> {
+    let controller = controller.read().await;
+    let mut status = controller.get_scene_execution_status().await;
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "获取场景执行状态成功".to_string(),
+        data: Some(SceneExecutionStatusResponse {
+            is_executing: status.is_executing,
+            current_scene: status.current_scene,
+            current_step_index: status.current_step_index,
+            total_steps: status.total_steps,
+            runs: status
+                .runs
+                .into_iter()
+                .map(|r| SceneRunResponse {
+                    run_id: r.run_id,
+                    scene: r.scene,
+                    group: r.group,
+                    queued: r.queued,
+                    current_step_index: r.current_step_index,
+                    total_steps: r.total_steps,
+                    cue: r.cue,
+                    awaiting_cue: status
+                        .cues
+                        .iter()
+                        .position(|c| c.run_id == r.run_id)
+                        .map(|i| cue_response(&controller, status.cues.swap_remove(i))),
+                })
+                .collect(),
+            last_result: status.last_result.map(Into::into),
+        }),
+    })
+}
+
+/// 放行 cue 模式场景的下一步骤
+///
+/// 场景在每个步骤执行前暂停，调用此接口后执行当前等待的步骤，返回该步骤详情；
+/// 下一个等待的步骤可通过 /lspcapi/device/sceneStatus 查看。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/scene/{run_id}/next",
+    params(("run_id" = u64, Path, description = "执行编号")),
+    responses(
+        (status = 200, description = "已放行", body = inline(ApiResponse<SceneCueResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn next_scene_cue(
+    Extension(controller): Extension<SharedController>,
+    Path(run_id): Path<u64>,
+) -> Json<ApiResponse<SceneCueResponse>> {
+    let controller = controller.read().await;
+    match controller.advance_scene(run_id) {
+        Ok(cue) => Json(ApiResponse::success(
+            format!("场景 '{}' 执行步骤 {}", cue.scene, cue.step),
+            cue_response(&controller, cue),
+        )),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 取消一次排队中或执行中的场景
+///
+/// 用于结束 cue 模式的排练等，已执行的步骤不回滚。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/scene/{run_id}/cancel",
+    params(("run_id" = u64, Path, description = "执行编号")),
+    responses(
+        (status = 200, description = "已取消", body = inline(ApiResponse<()>))
+    ),
+    tag = "Device"
+)]
+pub async fn cancel_scene_run(
+    Extension(controller): Extension<SharedController>,
+    Path(run_id): Path<u64>,
+) -> Json<ApiResponse<()>> {
+    match controller.read().await.cancel_scene_run(run_id) {
+        Ok(scene) => Json(ApiResponse::<()>::success_empty(format!(
+            "场景 '{}' (执行 {}) 已取消",
+            scene, run_id
+        ))),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+fn cue_response(controller: &DeviceController, cue: SceneCue) -> SceneCueResponse {
+    SceneCueResponse {
+        run_id: cue.run_id,
+        scene: cue.scene,
+        step: cue.step,
+        total_steps: cue.total_steps,
+        label: cue.definition.label.clone(),
+        definition: cue.definition,
+        targets: cue
+            .targets
+            .into_iter()
+            .map(|d| step_diff_response(controller, d))
+            .collect(),
+    }
+}
+
+fn step_diff_response(controller: &DeviceController, d: SceneStepDiff) -> SceneStepDiffResponse {
+    SceneStepDiffResponse {
+        step: d.step,
+        global_id: d.global_id,
+        alias: d.alias,
+        target_value: d.target_value,
+        target_label: controller.get_value_label(d.global_id, d.target_value),
+        current_value: d.current_value,
+        current_label: d
+            .current_value
+            .and_then(|v| controller.get_value_label(d.global_id, v)),
+        online: d.online,
+        changed: d.changed,
+    }
+}
+
+/// 预览场景执行效果
+///
+/// 逐步对比场景目标值与节点当前值，返回执行场景时哪些节点会发生变化，不执行任何写入。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/scene/{name}/diff",
+    params(("name" = String, Path, description = "场景名称")),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<SceneDiffResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn get_scene_diff(
+    Extension(controller): Extension<SharedController>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<SceneDiffResponse>> {
+    let controller = controller.read().await;
+    let steps = match controller.diff_scene(&name) {
+        Ok(steps) => steps,
+        Err(e) => {
+            return Json(ApiResponse {
+                state: error_codes::GENERAL_ERROR,
+                message: format!("场景预览失败: {:?}", e),
+                data: None,
+            })
+        }
+    };
+
+    let steps: Vec<SceneStepDiffResponse> = steps
+        .into_iter()
+        .map(|d| step_diff_response(&controller, d))
+        .collect();
+
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "获取场景预览成功".to_string(),
+        data: Some(SceneDiffResponse {
+            scene: name,
+            changed_count: steps.iter().filter(|s| s.changed).count(),
+            steps,
+        }),
+    })
+}
+
+/// 导出场景
+///
+/// 返回可移植的场景包文件，附带场景引用节点的别名，用于导入到其他系统。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/scenes/export",
+    request_body = Option<SceneExportRequest>,
+    responses(
+        (status = 200, description = "场景包文件", body = ScenePackage, content_type = "application/json")
+    ),
+    tag = "Device"
+)]
+pub async fn export_scenes(
+    Extension(controller): Extension<SharedController>,
+    Extension(config): Extension<SharedConfig>,
+    payload: Option<Json<SceneExportRequest>>,
+) -> Response {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let scenes = controller.read().await.get_all_scenes();
+    let package = {
+        let config = config.read().await;
+        scene_transfer::export(&scenes, &config.nodes, &payload.names)
+    };
+
+    match package {
+        Ok(package) => {
+            let content_disposition = format!(
+                "attachment; filename=\"scenes-{}.json\"",
+                chrono::Utc::now().format("%Y%m%d%H%M%S")
+            );
+            let headers = [(
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&content_disposition)
+                    .unwrap_or_else(|_| HeaderValue::from_static("attachment")),
+            )];
+            (headers, Json(package)).into_response()
+        }
+        Err(e) => Json(ApiResponse::<()> {
+            state: error_codes::INVALID_PARAMS,
+            message: format!("场景导出失败: {}", e),
+            data: None,
+        })
+        .into_response(),
+    }
+}
+
+/// 预览场景导入
+///
+/// 按别名自动匹配本系统节点；别名不同但 ID 相同的节点仅作为建议返回，需在 node_map 中确认。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/scenes/import/preview",
+    request_body = SceneImportPreviewRequest,
+    responses(
+        (status = 200, description = "预览成功", body = inline(ApiResponse<SceneImportPreviewResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn preview_scene_import(
+    Extension(controller): Extension<SharedController>,
+    Extension(config): Extension<SharedConfig>,
+    Json(payload): Json<SceneImportPreviewRequest>,
+) -> Json<ApiResponse<SceneImportPreviewResponse>> {
+    let mappings = {
+        let config = config.read().await;
+        scene_transfer::plan(&payload.package, &config.nodes, &payload.node_map)
+    };
+    let nodes = match mappings {
+        Ok(nodes) => nodes,
+        Err(e) => {
+            return Json(ApiResponse {
+                state: error_codes::INVALID_PARAMS,
+                message: format!("场景包无效: {}", e),
+                data: None,
+            })
+        }
+    };
+
+    let existing: Vec<String> = controller
+        .read()
+        .await
+        .get_all_scenes()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    let scenes: Vec<String> = payload
+        .package
+        .scenes
+        .iter()
+        .map(|s| payload.rename.get(&s.name).unwrap_or(&s.name).clone())
+        .collect();
+    let conflicts = scenes
+        .iter()
+        .filter(|name| existing.contains(name))
+        .cloned()
+        .collect();
+    let ready = nodes.iter().all(|m| m.is_resolved());
+
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "预览成功".to_string(),
+        data: Some(SceneImportPreviewResponse {
+            scenes,
+            conflicts,
+            nodes,
+            ready,
+        }),
+    })
+}
+
+/// 导入场景
+///
+/// 按节点映射改写场景后写入配置文件并立即生效，同名场景需重命名或指定 overwrite。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/scenes/import",
+    request_body = SceneImportRequest,
+    responses(
+        (status = 200, description = "导入成功", body = inline(ApiResponse<SceneImportResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn import_scenes(
+    Extension(controller): Extension<SharedController>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(config_path): Extension<SharedConfigPath>,
+    Extension(config_store): Extension<SharedConfigStore>,
+    Extension(principal): Extension<Principal>,
+    Json(payload): Json<SceneImportRequest>,
+) -> Json<ApiResponse<SceneImportResponse>> {
+    let controller = controller.read().await;
+    let existing: Vec<String> = controller
+        .get_all_scenes()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+
+    let mut config = config.write().await;
+    let plan = scene_transfer::plan(&payload.package, &config.nodes, &payload.node_map);
+    let result = plan.and_then(|nodes| {
+        scene_transfer::apply(
+            &payload.package,
+            &nodes,
+            &payload.rename,
+            &existing,
+            payload.overwrite,
+        )
+        .map(|scenes| (scenes, nodes))
+    });
+    let (scenes, nodes) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            return Json(ApiResponse {
+                state: error_codes::INVALID_PARAMS,
+                message: format!("场景导入失败: {}", e),
+                data: None,
+            })
+        }
+    };
+
+    let mut next_config = config.clone();
+    for scene in &scenes {
+        match next_config.scenes.iter_mut().find(|s| s.name == scene.name) {
+            Some(slot) => *slot = scene.clone(),
+            None => next_config.scenes.push(scene.clone()),
+        }
+    }
+    let imported: Vec<String> = scenes.iter().map(|s| s.name.clone()).collect();
+
+    // 数据库配置源：先保存为新版本，失败时不修改配置文件
+    if let Some(ref store) = config_store {
+        let comment = format!("导入场景: {}", imported.join(", "));
+        if let Err(e) = store.save(&next_config, &principal.name, &comment).await {
+            tracing::error!("[场景] 保存到数据库失败: {:?}", e);
+            return Json(ApiResponse {
+                state: error_codes::GENERAL_ERROR,
+                message: format!("保存到数据库失败: {}", e),
+                data: None,
+            });
+        }
+    }
+
+    if let Err(e) = crate::config::upsert_scenes_in_file(config_path.as_ref(), &scenes) {
+        tracing::error!("[场景] 写入配置文件失败: {}", e);
+        return Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("写入配置文件失败: {}", e),
+            data: None,
+        });
+    }
+
+    *config = next_config;
+    controller.upsert_scenes(scenes);
+    tracing::info!("[场景] 已导入 {} 个场景: {:?}", imported.len(), imported);
+
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: format!("已导入 {} 个场景", imported.len()),
+        data: Some(SceneImportResponse { imported, nodes }),
+    })
+}
+
+/// 执行通道命令
+///
+/// 无论协议类型，结果统一为 `CommandResult`（成功标志、驱动数据、原始报文、耗时），
+/// 失败时 `data` 中同样返回结果结构，并写入审计日志。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/executeCommand",
+    request_body = ChannelCommandRequest,
+    responses(
+        (status = 200, description = "命令执行成功", body = inline(ApiResponse<CommandResult>))
+    ),
+    tag = "Device"
+)]
+pub async fn execute_channel_command(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Json(payload): Json<ChannelCommandRequest>,
+) -> Json<ApiResponse<CommandResult>> {
+    let result = controller
+        .read()
+        .await
+        .run_channel_command(payload.channel_id, &payload.command, payload.params)
+        .await;
+    command_result_response(&principal, "execute_command", "命令执行", result)
+}
+
+/// 调用通道方法
+///
+/// 结果结构与 `executeCommand` 相同。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/callMethod",
+    request_body = CallMethodRequest,
+    responses(
+        (status = 200, description = "方法调用成功", body = inline(ApiResponse<CommandResult>))
+    ),
+    tag = "Device"
+)]
+pub async fn call_method(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Json(payload): Json<CallMethodRequest>,
+) -> Json<ApiResponse<CommandResult>> {
+    let result = controller
+        .read()
+        .await
+        .invoke_channel_method(payload.channel_id, &payload.method_name, payload.arguments)
+        .await;
+    command_result_response(&principal, "call_method", "方法调用", result)
+}
+
+/// 记录审计日志并包装通道命令结果
+fn command_result_response(
+    principal: &Principal,
+    action: &str,
+    label: &str,
+    result: CommandResult,
+) -> Json<ApiResponse<CommandResult>> {
+    crate::utils::audit::record(
+        &principal.name,
+        action,
+        serde_json::to_value(&result).unwrap_or_default(),
+    );
+    match &result.error {
+        None => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: format!("{}成功", label),
+            data: Some(result),
+        }),
+        Some(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("{}失败: {}", label, e),
+            data: Some(result),
+        }),
+    }
+}
+
+/// 批量调用通道方法
+///
+/// 如"所有投影机切换到 HDMI2"：共享参数放在 `templates` 中，各项通过 `template` 引用，
+/// 参数中的 `{{名称}}` 按项的 `vars` 替换。按 `concurrency` 并发调用，结果按请求顺序返回，
+/// 单项失败不影响其他项。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/channels/invokeMany",
+    request_body = InvokeManyRequest,
+    responses(
+        (status = 200, description = "批量调用完成", body = inline(ApiResponse<Vec<InvokeManyResultItem>>))
+    ),
+    tag = "Device"
+)]
+pub async fn invoke_many(
+    Extension(controller): Extension<SharedController>,
+    Json(payload): Json<InvokeManyRequest>,
+) -> Json<ApiResponse<Vec<InvokeManyResultItem>>> {
+    use futures::StreamExt;
+
+    let controller = controller.read().await;
+    let templates = &payload.templates;
+    let concurrency = payload.concurrency.clamp(1, MAX_INVOKE_CONCURRENCY);
+    let controller = &*controller;
+    let results: Vec<InvokeManyResultItem> =
+        futures::stream::iter(payload.items.into_iter().enumerate())
+            .map(|(index, item)| async move {
+                let started = std::time::Instant::now();
+                let result = match render_invoke_args(templates, &item) {
+                    Ok(args) => controller
+                        .call_channel_method(item.channel_id, &item.method, args)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+                let (result, error) = match result {
+                    Ok(value) => (Some(value), None),
+                    Err(e) => (None, Some(e)),
+                };
+                InvokeManyResultItem {
+                    index,
+                    channel_id: item.channel_id,
+                    method: item.method,
+                    success: error.is_none(),
+                    result,
+                    error,
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                }
+            })
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+    Json(ApiResponse::success(
+        format!(
+            "批量调用完成: 成功 {}, 失败 {}",
+            succeeded,
+            results.len() - succeeded
+        ),
+        results,
+    ))
+}
+
+/// 合并参数模板与调用项参数，并替换模板变量
+fn render_invoke_args(
+    templates: &HashMap<String, serde_json::Value>,
+    item: &InvokeManyItem,
+) -> Result<serde_json::Value, String> {
+    let base = match &item.template {
+        Some(name) => Some(
+            templates
+                .get(name)
+                .ok_or_else(|| format!("参数模板 '{}' 不存在", name))?,
+        ),
+        None => None,
+    };
+    let mut args = match (base, &item.args) {
+        (Some(serde_json::Value::Object(base)), Some(serde_json::Value::Object(args))) => {
+            let mut merged = base.clone();
+            merged.extend(args.clone());
+            serde_json::Value::Object(merged)
+        }
+        (_, Some(args)) => args.clone(),
+        (Some(base), None) => base.clone(),
+        (None, None) => serde_json::json!({}),
+    };
+
+    let mut vars = item.vars.clone();
+    vars.entry("channel_id")
+        .or_insert_with(|| item.channel_id.into());
+    vars.entry("method")
+        .or_insert_with(|| item.method.clone().into());
+    substitute_vars(&mut args, &vars);
+    Ok(args)
+}
+
+/// 替换字符串中的 `{{名称}}`；整个字符串就是一个变量时保留变量的 JSON 类型
+fn substitute_vars(
+    value: &mut serde_json::Value,
+    vars: &serde_json::Map<String, serde_json::Value>,
+) {
+    match value {
+        serde_json::Value::String(s) => {
+            let whole = s
+                .strip_prefix("{{")
+                .and_then(|s| s.strip_suffix("}}"))
+                .and_then(|name| vars.get(name.trim()));
+            if let Some(var) = whole {
+                *value = var.clone();
+                return;
+            }
+            for (name, var) in vars {
+                let placeholder = format!("{{{{{}}}}}", name);
+                if s.contains(&placeholder) {
+                    let text = match var {
+                        serde_json::Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    *s = s.replace(&placeholder, &text);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            items.iter_mut().for_each(|v| substitute_vars(v, vars));
+        }
+        serde_json::Value::Object(map) => {
+            map.values_mut().for_each(|v| substitute_vars(v, vars));
+        }
+        _ => {}
+    }
+}
+
+/// 获取通道方法列表
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/getMethods",
+    request_body = GetMethodsRequest,
     responses(
         (status = 200, description = "获取成功", body = inline(ApiResponse<Vec<String>>))
     ),
     tag = "Device"
 )]
-pub async fn get_methods(
+pub async fn get_methods(
+    Extension(controller): Extension<SharedController>,
+    Json(payload): Json<GetMethodsRequest>,
+) -> Json<ApiResponse<Vec<String>>> {
+    match controller
+        .read()
+        .await
+        .get_channel_methods(payload.channel_id)
+        .await
+    {
+        Ok(methods) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "获取方法列表成功".to_string(),
+            data: Some(methods),
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("获取方法列表失败: {:?}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 批量读取（通过通道命令）
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/batchRead",
+    request_body = BatchReadRequest,
+    responses(
+        (status = 200, description = "批量读取完成", body = inline(ApiResponse<Vec<BatchReadResultItem>>))
+    ),
+    tag = "Device"
+)]
+pub async fn batch_read(
+    Extension(controller): Extension<SharedController>,
+    Json(payload): Json<BatchReadRequest>,
+) -> Json<ApiResponse<Vec<BatchReadResultItem>>> {
+    let mut results = Vec::new();
+
+    for item in payload.items {
+        let result = controller
+            .read()
+            .await
+            .execute_channel_command(item.channel_id, "read", item.params)
+            .await;
+
+        match result {
+            Ok(value) => {
+                results.push(BatchReadResultItem {
+                    name: item.name,
+                    success: true,
+                    value: Some(value),
+                    error: None,
+                });
+            }
+            Err(e) => {
+                results.push(BatchReadResultItem {
+                    name: item.name,
+                    success: false,
+                    value: None,
+                    error: Some(format!("{:?}", e)),
+                });
+            }
+        }
+    }
+
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: format!(
+            "批量读取完成: 成功 {}, 失败 {}",
+            results.iter().filter(|r| r.success).count(),
+            results.iter().filter(|r| !r.success).count()
+        ),
+        data: Some(results),
+    })
+}
+
+/// 站点列表
+///
+/// 返回配置中声明的站点及其下属通道。受限 API Key 只返回包含可见通道的站点。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/sites",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<Vec<SiteResponse>>))
+    ),
+    tag = "Device"
+)]
+pub async fn list_sites(
+    Extension(controller): Extension<SharedController>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(principal): Extension<Principal>,
+) -> Json<ApiResponse<Vec<SiteResponse>>> {
+    let nodes = controller.read().await.get_all_node_configs();
+    let config = config.read().await;
+    let sites: Vec<SiteResponse> = config
+        .sites
+        .iter()
+        .filter_map(|site| {
+            let channels: Vec<u32> = config
+                .site_channels(&site.id)
+                .into_iter()
+                .filter(|id| principal.can_see_channel(*id, &nodes))
+                .collect();
+            if principal.scope.is_some() && channels.is_empty() {
+                return None;
+            }
+            Some(SiteResponse {
+                id: site.id.clone(),
+                name: site.name.clone(),
+                location: site.location.clone(),
+                timezone: site.timezone.clone(),
+                metadata: site.metadata.clone(),
+                channels,
+            })
+        })
+        .collect();
+    Json(ApiResponse::success(
+        format!("共 {} 个站点", sites.len()),
+        sites,
+    ))
+}
+
+/// 导出完整设备模型
+///
+/// 一次返回通道、节点（含分类/单位/依赖/当前值）与场景，供 BIM / 数字孪生平台同步。
+/// `format=wot` 时返回 W3C WoT Thing Description。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/model",
+    params(DeviceModelQuery),
+    responses(
+        (status = 200, description = "导出成功", body = inline(ApiResponse<serde_json::Value>))
+    ),
+    tag = "Device"
+)]
+pub async fn get_device_model(
+    Extension(controller): Extension<SharedController>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<DeviceModelQuery>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let controller = controller.read().await;
+    let config = config.read().await;
+    let site = query.site.as_deref();
+    let node_configs: Vec<_> = controller
+        .get_all_node_configs()
+        .into_iter()
+        .filter(|n| principal.can_access_node(n) && in_site(&controller, n.channel_id, site))
+        .collect();
+
+    // 通道
+    let mut channel_configs: Vec<_> = config
+        .channels
+        .iter()
+        .filter(|c| {
+            principal.can_see_channel(c.channel_id, &node_configs)
+                && site.is_none_or(|site| c.site.as_deref() == Some(site))
+        })
+        .collect();
+    channel_configs.sort_by_key(|c| c.channel_id);
+    let mut channels = Vec::with_capacity(channel_configs.len());
+    for channel in channel_configs {
+        let methods = if channel.enable {
+            controller
+                .get_channel_methods(channel.channel_id)
+                .await
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        channels.push(serde_json::json!({
+            "channel_id": channel.channel_id,
+            "site": channel.site,
+            "statute": channel.statute,
+            "enable": channel.enable,
+            "methods": methods,
+            "custom_methods": channel.methods.clone().unwrap_or_default(),
+        }));
+    }
+
+    // 节点（配置 + 运行时状态）
+    let nodes: Vec<_> = node_configs
+        .iter()
+        .map(|node| {
+            let state = controller.get_node_state(node.global_id);
+            let data_point = node.data_point.as_ref();
+            serde_json::json!({
+                "global_id": node.global_id,
+                "channel_id": node.channel_id,
+                "device_id": node.id,
+                "alias": node.alias,
+                "category": node.category,
+                "tag": node.tag,
+                "data_type": data_point.map(|dp| dp.r#type.clone()),
+                "unit": data_point.and_then(|dp| dp.unit.clone()),
+                "scale": data_point.and_then(|dp| dp.scale),
+                "depend": node.depend.clone().unwrap_or_default(),
+                "depend_strategy": node.depend_strategy,
+                "current_value": state.as_ref().and_then(|s| s.current_value),
+                "online": state.map(|s| s.online).unwrap_or(false),
+                "metadata": node.metadata,
+                "value_labels": node.value_labels,
+            })
+        })
+        .collect();
+
+    // 场景（受限 API Key 或按站点导出时只列出全部节点都在范围内的场景）
+    let scenes: Vec<_> = config
+        .scenes
+        .iter()
+        .filter(|scene| {
+            (principal.scope.is_none() && site.is_none())
+                || scene
+                    .nodes
+                    .iter()
+                    .flat_map(|step| step.targets())
+                    .all(|id| node_configs.iter().any(|n| n.global_id == id))
+        })
+        .map(|scene| {
+            serde_json::json!({
+                "name": scene.name,
+                "interval": scene.interval,
+                "steps": scene.nodes,
+            })
+        })
+        .collect();
+
+    let data = match query.format.as_deref() {
+        None | Some("") | Some("native") => serde_json::json!({
+            "version": DEVICE_MODEL_VERSION,
+            "channels": channels,
+            "nodes": nodes,
+            "scenes": scenes,
+        }),
+        Some("wot") => build_thing_description(&nodes, &scenes),
+        Some(other) => {
+            return Json(ApiResponse {
+                state: error_codes::GENERAL_ERROR,
+                message: format!("不支持的导出格式: {}", other),
+                data: None,
+            })
+        }
+    };
+
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "成功".to_string(),
+        data: Some(data),
+    })
+}
+
+/// 校验通道存在且支持缓存操作（目前仅 Modbus 通道）
+async fn ensure_cache_channel(config: &SharedConfig, channel_id: u32) -> Result<(), String> {
+    let config = config.read().await;
+    match config.channels.iter().find(|c| c.channel_id == channel_id) {
+        None => Err(format!("通道 {} 不存在", channel_id)),
+        Some(c) if c.statute != StatuteType::Modbus => Err(format!(
+            "通道 {} ({:?}) 不支持缓存操作",
+            channel_id, c.statute
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+/// 查看通道缓存
+///
+/// 返回 Modbus 通道缓存的全部地址及其值、类型与数据年龄（毫秒）。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/channels/{id}/cache",
+    params(("id" = u32, Path, description = "通道 ID")),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<serde_json::Value>))
+    ),
+    tag = "Device"
+)]
+pub async fn get_channel_cache(
+    Extension(controller): Extension<SharedController>,
+    Extension(config): Extension<SharedConfig>,
+    Path(channel_id): Path<u32>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if let Err(message) = ensure_cache_channel(&config, channel_id).await {
+        return Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message,
+            data: None,
+        });
+    }
+
+    match controller
+        .read()
+        .await
+        .execute_channel_command(channel_id, "get_cache", serde_json::json!({}))
+        .await
+    {
+        Ok(result) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "获取缓存成功".to_string(),
+            data: Some(result),
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("获取缓存失败: {:?}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 清除通道缓存
+///
+/// 请求体可指定地址范围，省略时清除全部缓存。清除后的地址在下次读取时直接访问设备。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/channels/{id}/cache/invalidate",
+    params(("id" = u32, Path, description = "通道 ID")),
+    request_body = Option<CacheInvalidateRequest>,
+    responses(
+        (status = 200, description = "清除成功", body = inline(ApiResponse<serde_json::Value>))
+    ),
+    tag = "Device"
+)]
+pub async fn invalidate_channel_cache(
     Extension(controller): Extension<SharedController>,
-    Json(payload): Json<GetMethodsRequest>,
-) -> Json<ApiResponse<Vec<String>>> {
-    match controller.read().await.get_channel_methods(payload.channel_id).await {
-        Ok(methods) => Json(ApiResponse {
+    Extension(config): Extension<SharedConfig>,
+    Path(channel_id): Path<u32>,
+    payload: Option<Json<CacheInvalidateRequest>>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if let Err(message) = ensure_cache_channel(&config, channel_id).await {
+        return Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message,
+            data: None,
+        });
+    }
+
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    match controller
+        .read()
+        .await
+        .execute_channel_command(
+            channel_id,
+            "invalidate_cache",
+            serde_json::json!({
+                "start_addr": payload.start_addr,
+                "end_addr": payload.end_addr,
+                "slave_id": payload.slave_id,
+            }),
+        )
+        .await
+    {
+        Ok(result) => Json(ApiResponse {
             state: error_codes::SUCCESS,
-            message: "获取方法列表成功".to_string(),
-            data: Some(methods),
+            message: "清除缓存成功".to_string(),
+            data: Some(result),
         }),
         Err(e) => Json(ApiResponse {
             state: error_codes::GENERAL_ERROR,
-            message: format!("获取方法列表失败: {:?}", e),
+            message: format!("清除缓存失败: {:?}", e),
             data: None,
         }),
     }
 }
 
-/// 批量读取（通过通道命令）
+/// 运行时停用通道
+///
+/// 停止通道的协议实例及其轮询任务，通道下的节点标记为离线，读写返回"通道已停用"。
+/// 不修改配置文件，重新启用、配置热重载或重启服务后恢复。
 #[utoipa::path(
     post,
-    path = "/lspcapi/device/batchRead",
-    request_body = BatchReadRequest,
+    path = "/lspcapi/device/channels/{id}/disable",
+    params(("id" = u32, Path, description = "通道 ID")),
     responses(
-        (status = 200, description = "批量读取完成", body = inline(ApiResponse<Vec<BatchReadResultItem>>))
+        (status = 200, description = "停用成功", body = inline(ApiResponse<()>))
     ),
     tag = "Device"
 )]
-pub async fn batch_read(
+pub async fn disable_channel(
     Extension(controller): Extension<SharedController>,
-    Json(payload): Json<BatchReadRequest>,
-) -> Json<ApiResponse<Vec<BatchReadResultItem>>> {
-    let mut results = Vec::new();
+    Extension(principal): Extension<Principal>,
+    Path(channel_id): Path<u32>,
+) -> Json<ApiResponse<()>> {
+    match controller.read().await.disable_channel(channel_id).await {
+        Ok(()) => {
+            tracing::info!("[通道] {} 停用通道 {}", principal.name, channel_id);
+            Json(ApiResponse {
+                state: error_codes::SUCCESS,
+                message: format!("通道 {} 已停用", channel_id),
+                data: None,
+            })
+        }
+        Err(e) => Json(ApiResponse {
+            state: error_codes::CHANNEL_NOT_FOUND,
+            message: format!("停用通道失败: {}", e),
+            data: None,
+        }),
+    }
+}
 
-    for item in payload.items {
-        let result = controller
-            .read()
-            .await
-            .execute_channel_command(item.channel_id, "read", item.params)
-            .await;
+/// 重新启用运行时停用的通道
+///
+/// 按当前配置重建协议实例并恢复轮询。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/channels/{id}/enable",
+    params(("id" = u32, Path, description = "通道 ID")),
+    responses(
+        (status = 200, description = "启用成功", body = inline(ApiResponse<()>))
+    ),
+    tag = "Device"
+)]
+pub async fn enable_channel(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Path(channel_id): Path<u32>,
+) -> Json<ApiResponse<()>> {
+    match controller.read().await.enable_channel(channel_id).await {
+        Ok(()) => {
+            tracing::info!("[通道] {} 启用通道 {}", principal.name, channel_id);
+            Json(ApiResponse {
+                state: error_codes::SUCCESS,
+                message: format!("通道 {} 已启用", channel_id),
+                data: None,
+            })
+        }
+        Err(e) => Json(ApiResponse {
+            state: match e {
+                crate::utils::DeviceError::ChannelNotFound(_) => error_codes::CHANNEL_NOT_FOUND,
+                _ => error_codes::GENERAL_ERROR,
+            },
+            message: format!("启用通道失败: {}", e),
+            data: None,
+        }),
+    }
+}
 
-        match result {
-            Ok(value) => {
-                results.push(BatchReadResultItem {
-                    name: item.name,
-                    success: true,
-                    value: Some(value),
-                    error: None,
-                });
+/// 节点值异常分析报告
+///
+/// 返回当前检测到的节点异常：持续轮询但值长时间不变（`stuck_value`，疑似传感器失效）
+/// 与变化快于物理上可能（`implausible_rate`，疑似接线故障）。未启用 `analytics` 时 `enabled` 为 false。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/analytics",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<AnalyticsReport>))
+    ),
+    tag = "Device"
+)]
+pub async fn get_analytics_report(
+    Extension(controller): Extension<SharedController>,
+) -> Json<ApiResponse<AnalyticsReport>> {
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "获取异常分析报告成功".to_string(),
+        data: Some(controller.read().await.analytics_report()),
+    })
+}
+
+/// 场景与节点写入执行统计
+///
+/// 返回各场景的执行次数、最近执行时间与结果、平均耗时、失败率，以及各节点下发到设备的
+/// 写入次数与成功率。受限 API Key 只返回范围内的节点，以及全部节点都在范围内的场景。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/stats",
+    params(SiteQuery),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<ExecutionStatsReport>))
+    ),
+    tag = "Device"
+)]
+pub async fn get_execution_stats(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SiteQuery>,
+) -> Json<ApiResponse<ExecutionStatsReport>> {
+    let controller = controller.read().await;
+    let mut report = controller.execution_stats();
+    if let Some(visible) = visible_nodes(&principal, &controller, query.site.as_deref()) {
+        report.nodes.retain(|n| visible.contains(&n.global_id));
+        let scenes = controller.get_all_scenes();
+        report.scenes.retain(|stats| {
+            scenes.iter().any(|scene| {
+                scene.name == stats.scene
+                    && scene
+                        .nodes
+                        .iter()
+                        .flat_map(|step| step.targets())
+                        .all(|id| visible.contains(&id))
+            })
+        });
+    }
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "获取执行统计成功".to_string(),
+        data: Some(report),
+    })
+}
+
+/// 重置执行统计
+///
+/// 清空全部场景与节点统计，统计起点更新为当前时间。受限 API Key 不能重置。
+#[utoipa::path(
+    delete,
+    path = "/lspcapi/device/stats",
+    responses(
+        (status = 200, description = "重置成功", body = inline(ApiResponse<String>))
+    ),
+    tag = "Device"
+)]
+pub async fn reset_execution_stats(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+) -> Json<ApiResponse<String>> {
+    if principal.scope.is_some() {
+        return Json(ApiResponse {
+            state: error_codes::FORBIDDEN,
+            message: "受限 API Key 不能重置执行统计".to_string(),
+            data: None,
+        });
+    }
+    controller.read().await.reset_execution_stats();
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "执行统计已重置".to_string(),
+        data: None,
+    })
+}
+
+/// 通道启动顺序
+///
+/// 返回按启动依赖（`startup.after`）计算出的通道启动顺序、各通道等待依赖的耗时，
+/// 以及缺失依赖、循环依赖、依赖初始化失败与等待超时等违例。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/channels/startup-order",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<StartupOrderReport>))
+    ),
+    tag = "Device"
+)]
+pub async fn get_channel_startup_order(
+    Extension(controller): Extension<SharedController>,
+) -> Json<ApiResponse<StartupOrderReport>> {
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "获取启动顺序成功".to_string(),
+        data: Some(controller.read().await.channel_startup_order()),
+    })
+}
+
+/// 解析十六进制负载
+fn parse_hex_payload(payload: &str) -> Result<Vec<u8>, String> {
+    let digits: String = payload
+        .replace("0x", "")
+        .replace("0X", "")
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, ':' | '-' | ','))
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("十六进制位数必须为偶数".to_string());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("无效的十六进制: '{}'", &digits[i..i + 2]))
+        })
+        .collect()
+}
+
+/// 解析带转义的文本负载
+fn parse_ascii_payload(payload: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(payload.len());
+    let mut chars = payload.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('r') => bytes.push(b'\r'),
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 2)
+                    .ok_or_else(|| format!("无效的转义: '\\x{}'", hex))?;
+                bytes.push(byte);
             }
-            Err(e) => {
-                results.push(BatchReadResultItem {
-                    name: item.name,
-                    success: false,
-                    value: None,
-                    error: Some(format!("{:?}", e)),
-                });
+            Some(other) => return Err(format!("不支持的转义: '\\{}'", other)),
+            None => return Err("负载以未完成的转义结尾".to_string()),
+        }
+    }
+    Ok(bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 以文本显示原始字节，不可打印字符转义
+fn to_display_ascii(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            b'\r' => "\\r".to_string(),
+            b'\n' => "\\n".to_string(),
+            b'\t' => "\\t".to_string(),
+            b'\\' => "\\\\".to_string(),
+            0x20..=0x7E => (b as char).to_string(),
+            _ => format!("\\x{:02X}", b),
+        })
+        .collect()
+}
+
+/// 原始命令控制台
+///
+/// 通过通道的传输层发送原始字节并返回设备原始响应与耗时，替代现场用 netcat 直连设备调试。
+/// 仅管理员可用（未启用认证时不可用），每次调用写入审计日志。协议需支持原始数据（PJLink、WDY-8EN、Novastar、Mock）。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/channels/{id}/raw",
+    params(("id" = u32, Path, description = "通道 ID")),
+    request_body = RawCommandRequest,
+    responses(
+        (status = 200, description = "发送成功", body = inline(ApiResponse<RawCommandResponse>))
+    ),
+    tag = "Device"
+)]
+pub async fn send_raw_command(
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+    Path(channel_id): Path<u32>,
+    Json(payload): Json<RawCommandRequest>,
+) -> Json<ApiResponse<RawCommandResponse>> {
+    if principal.kind == PrincipalKind::Anonymous {
+        return Json(ApiResponse {
+            state: error_codes::FORBIDDEN,
+            message: "原始命令控制台需启用认证并以管理员身份使用".to_string(),
+            data: None,
+        });
+    }
+    let bytes = match payload.encoding {
+        RawEncoding::Hex => parse_hex_payload(&payload.payload),
+        RawEncoding::Ascii => parse_ascii_payload(&payload.payload),
+    }
+    .and_then(|bytes| {
+        if bytes.is_empty() {
+            Err("负载为空".to_string())
+        } else {
+            Ok(bytes)
+        }
+    });
+    let bytes = match bytes {
+        Ok(bytes) => bytes,
+        Err(message) => {
+            return Json(ApiResponse {
+                state: error_codes::INVALID_PARAMS,
+                message,
+                data: None,
+            })
+        }
+    };
+    let timeout_ms = payload
+        .timeout_ms
+        .unwrap_or(RAW_DEFAULT_TIMEOUT_MS)
+        .clamp(1, RAW_MAX_TIMEOUT_MS);
+
+    let timeout = std::time::Duration::from_millis(timeout_ms);
+    let started = std::time::Instant::now();
+    let result = controller
+        .read()
+        .await
+        .send_raw(channel_id, &bytes, timeout)
+        .await;
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    crate::utils::audit::record(
+        &principal.name,
+        "raw_command",
+        serde_json::json!({
+            "channel_id": channel_id,
+            "sent": to_hex(&bytes),
+            "received": result.as_ref().ok().map(|r| to_hex(r)),
+            "error": result.as_ref().err().map(|e| e.to_string()),
+            "elapsed_ms": elapsed_ms,
+        }),
+    );
+
+    match result {
+        Ok(response) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "发送成功".to_string(),
+            data: Some(RawCommandResponse {
+                sent_hex: to_hex(&bytes),
+                bytes_sent: bytes.len(),
+                response_hex: to_hex(&response),
+                response_ascii: to_display_ascii(&response),
+                bytes_received: response.len(),
+                elapsed_ms,
+            }),
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("发送失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 通道延迟测试
+///
+/// 按间隔多次查询通道状态（`status`）或只建立 TCP 连接（`connect`），返回最小 / 平均 / 最大耗时与丢失率，
+/// 用于区分网络问题与设备问题。测试不计入通道健康度。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/channels/{id}/ping",
+    params(("id" = u32, Path, description = "通道 ID")),
+    request_body = ChannelPingRequest,
+    responses(
+        (status = 200, description = "测试完成", body = inline(ApiResponse<PingReport>))
+    ),
+    tag = "Device"
+)]
+pub async fn ping_channel(
+    Extension(controller): Extension<SharedController>,
+    Path(channel_id): Path<u32>,
+    Json(payload): Json<ChannelPingRequest>,
+) -> Json<ApiResponse<PingReport>> {
+    let count = payload
+        .count
+        .unwrap_or(PING_DEFAULT_COUNT)
+        .clamp(1, PING_MAX_COUNT);
+    let interval = std::time::Duration::from_millis(
+        payload
+            .interval_ms
+            .unwrap_or(PING_DEFAULT_INTERVAL_MS)
+            .min(PING_MAX_INTERVAL_MS),
+    );
+    let timeout = std::time::Duration::from_millis(
+        payload
+            .timeout_ms
+            .unwrap_or(PING_DEFAULT_TIMEOUT_MS)
+            .clamp(1, RAW_MAX_TIMEOUT_MS),
+    );
+    let result = controller
+        .read()
+        .await
+        .ping_channel(channel_id, payload.mode, count, interval, timeout)
+        .await;
+    match result {
+        Ok(report) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: format!("已测试 {} 次，成功 {} 次", report.sent, report.received),
+            data: Some(report),
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("延迟测试失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 将设备模型转换为 W3C WoT Thing Description
+///
+/// 节点映射为属性（读写走 `/device/read`、`/device/write`），场景映射为动作。
+fn build_thing_description(
+    nodes: &[serde_json::Value],
+    scenes: &[serde_json::Value],
+) -> serde_json::Value {
+    let mut properties = serde_json::Map::new();
+    for node in nodes {
+        let global_id = node["global_id"].as_u64().unwrap_or_default();
+        let mut property = serde_json::json!({
+            "title": node["alias"],
+            "type": "number",
+            "observable": true,
+            "forms": [
+                {
+                    "href": "/lspcapi/device/read",
+                    "op": "readproperty",
+                    "htv:methodName": "POST",
+                    "contentType": "application/json"
+                },
+                {
+                    "href": "/lspcapi/device/write",
+                    "op": "writeproperty",
+                    "htv:methodName": "POST",
+                    "contentType": "application/json"
+                }
+            ],
+            "dm:globalId": global_id,
+            "dm:channelId": node["channel_id"],
+            "dm:deviceId": node["device_id"],
+        });
+        if let Some(category) = node["category"].as_str() {
+            property["@type"] = serde_json::json!(category);
+        }
+        if let Some(unit) = node["unit"].as_str() {
+            property["unit"] = serde_json::json!(unit);
+        }
+        if let Some(metadata) = node["metadata"].as_object() {
+            if let Some(min) = metadata.get("min") {
+                property["minimum"] = min.clone();
+            }
+            if let Some(max) = metadata.get("max") {
+                property["maximum"] = max.clone();
             }
+            if let Some(writeable) = metadata.get("writeable").and_then(|v| v.as_bool()) {
+                property["readOnly"] = serde_json::json!(!writeable);
+            }
+            property["dm:metadata"] = serde_json::json!(metadata);
         }
+        properties.insert(format!("node_{}", global_id), property);
     }
 
-    Json(ApiResponse {
-        state: error_codes::SUCCESS,
-        message: format!(
-            "批量读取完成: 成功 {}, 失败 {}",
-            results.iter().filter(|r| r.success).count(),
-            results.iter().filter(|r| !r.success).count()
-        ),
-        data: Some(results),
+    let mut actions = serde_json::Map::new();
+    for scene in scenes {
+        let name = scene["name"].as_str().unwrap_or_default();
+        actions.insert(
+            format!("scene_{}", name),
+            serde_json::json!({
+                "title": name,
+                "forms": [
+                    {
+                        "href": "/lspcapi/device/scene",
+                        "op": "invokeaction",
+                        "htv:methodName": "POST",
+                        "contentType": "application/json"
+                    }
+                ],
+                "dm:steps": scene["steps"],
+            }),
+        );
+    }
+
+    serde_json::json!({
+        "@context": [
+            "https://www.w3.org/2019/wot/td/v1",
+            { "dm": "urn:dm-rust:model#" }
+        ],
+        "@type": "Thing",
+        "id": "urn:dm-rust:device-model",
+        "title": "dm-rust 设备模型",
+        "version": { "instance": DEVICE_MODEL_VERSION.to_string() },
+        "securityDefinitions": { "nosec_sc": { "scheme": "nosec" } },
+        "security": "nosec_sc",
+        "properties": properties,
+        "actions": actions,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_payload_encodings() {
+        assert_eq!(
+            parse_hex_payload("0x55 FF-01:aa,").unwrap(),
+            vec![0x55, 0xFF, 0x01, 0xAA]
+        );
+        assert!(parse_hex_payload("55F").is_err());
+        assert!(parse_hex_payload("5G").is_err());
+
+        assert_eq!(
+            parse_ascii_payload(r"%1POWR ?\r\x00").unwrap(),
+            b"%1POWR ?\r\x00".to_vec()
+        );
+        assert!(parse_ascii_payload(r"\q").is_err());
+        assert!(parse_ascii_payload(r"\x4").is_err());
+
+        assert_eq!(to_hex(&[0x0A, 0xFF]), "0A FF");
+        assert_eq!(to_display_ascii(b"OK\r\n\x01"), "OK\\r\\n\\x01");
+    }
+
+    #[test]
+    fn invoke_args_merge_template_and_substitute_vars() {
+        let templates = HashMap::from([(
+            "hdmi".to_string(),
+            serde_json::json!({ "input": "HDMI{{port}}", "id": "{{channel_id}}", "mute": false }),
+        )]);
+        let item: InvokeManyItem = serde_json::from_value(serde_json::json!({
+            "channel_id": 7,
+            "method": "set_input",
+            "template": "hdmi",
+            "args": { "mute": true },
+            "vars": { "port": 2 },
+        }))
+        .unwrap();
+        assert_eq!(
+            render_invoke_args(&templates, &item).unwrap(),
+            serde_json::json!({ "input": "HDMI2", "id": 7, "mute": true })
+        );
+
+        let missing: InvokeManyItem = serde_json::from_value(serde_json::json!({
+            "channel_id": 7, "method": "set_input", "template": "vga",
+        }))
+        .unwrap();
+        assert!(render_invoke_args(&templates, &missing).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! 设备事件 WebSocket 推送
+//!
+//! 客户端连接后默认接收全部设备事件（与事件总线桥接相同的补充字段）。大型现场的看板
+//! 可通过订阅消息只接收关心的节点：
+//!
+//! - `{"action": "subscribe", "global_ids": [1, 2], "tags": ["Line1.Motor"], "snapshot": true}`
+//! - `{"action": "unsubscribe", "global_ids": [2]}`（不带列表时取消全部节点订阅）
+//! - `{"action": "subscribe", "all": true}`（恢复接收全部节点事件）
+//! - `{"action": "snapshot"}`（重新获取订阅节点的当前状态）
+//!
+//! 订阅只过滤节点事件，通道、场景等系统事件始终推送；受限 API Key 只能收到访问范围内的事件。
+//! 启用认证时浏览器可通过查询参数 `access_token` / `api_key` 携带凭据（见 [`super::auth`]）。
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Extension,
+    },
+    response::Response,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashSet};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+use super::auth::Principal;
+use super::device_api::node_state_json;
+use super::state::SharedController;
+use crate::config::NodeConfig;
+use crate::device::{record_lagged_events, DeviceController, DeviceEvent};
+
+/// 客户端订阅消息
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe {
+        #[serde(default)]
+        global_ids: Vec<u32>,
+        /// 节点配置中的符号标签名称（`tag`）
+        #[serde(default)]
+        tags: Vec<String>,
+        /// 恢复接收全部节点事件
+        #[serde(default)]
+        all: bool,
+        /// 订阅后立即推送订阅节点的当前状态
+        #[serde(default)]
+        snapshot: bool,
+    },
+    Unsubscribe {
+        #[serde(default)]
+        global_ids: Vec<u32>,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    Snapshot,
+}
+
+/// 连接的订阅状态
+#[derive(Debug)]
+struct Subscription {
+    principal: Principal,
+    /// 是否按订阅过滤节点事件（首次订阅前推送全部节点事件）
+    filtered: bool,
+    global_ids: BTreeSet<u32>,
+    tags: BTreeSet<String>,
+    /// 订阅的节点（global_ids 与按标签解析出的节点，仅含访问范围内的节点）
+    nodes: HashSet<u32>,
+    /// 受限 API Key 可访问的节点（不限制范围时为 `None`）
+    visible: Option<HashSet<u32>>,
+    /// 可访问节点所在的通道
+    visible_channels: HashSet<u32>,
+}
+
+impl Subscription {
+    fn new(principal: Principal) -> Self {
+        Self {
+            principal,
+            filtered: false,
+            global_ids: BTreeSet::new(),
+            tags: BTreeSet::new(),
+            nodes: HashSet::new(),
+            visible: None,
+            visible_channels: HashSet::new(),
+        }
+    }
+
+    /// 按当前节点配置重新解析标签与访问范围（连接建立与配置热重载后调用）
+    fn resolve(&mut self, configs: &[NodeConfig]) {
+        let accessible: Vec<&NodeConfig> = configs
+            .iter()
+            .filter(|n| self.principal.can_access_node(n))
+            .collect();
+        self.visible = self
+            .principal
+            .scope
+            .as_ref()
+            .map(|_| accessible.iter().map(|n| n.global_id).collect());
+        self.visible_channels = accessible.iter().map(|n| n.channel_id).collect();
+        self.nodes = accessible
+            .iter()
+            .filter(|n| {
+                self.global_ids.contains(&n.global_id)
+                    || n.tag.as_ref().is_some_and(|tag| self.tags.contains(tag))
+            })
+            .map(|n| n.global_id)
+            .collect();
+    }
+
+    fn apply(&mut self, message: &ClientMessage) {
+        match message {
+            ClientMessage::Subscribe { all: true, .. } => {
+                self.filtered = false;
+                self.global_ids.clear();
+                self.tags.clear();
+            }
+            ClientMessage::Subscribe {
+                global_ids, tags, ..
+            } => {
+                self.filtered = true;
+                self.global_ids.extend(global_ids);
+                self.tags.extend(tags.iter().cloned());
+            }
+            ClientMessage::Unsubscribe { global_ids, tags }
+                if global_ids.is_empty() && tags.is_empty() =>
+            {
+                self.filtered = true;
+                self.global_ids.clear();
+                self.tags.clear();
+            }
+            ClientMessage::Unsubscribe { global_ids, tags } => {
+                for id in global_ids {
+                    self.global_ids.remove(id);
+                }
+                for tag in tags {
+                    self.tags.remove(tag);
+                }
+            }
+            ClientMessage::Snapshot => {}
+        }
+    }
+
+    /// 节点在访问范围内且被订阅
+    fn wants_node(&self, global_id: u32) -> bool {
+        self.visible.as_ref().is_none_or(|v| v.contains(&global_id))
+            && (!self.filtered || self.nodes.contains(&global_id))
+    }
+
+    fn wants(&self, event: &DeviceEvent) -> bool {
+        match (event.global_id(), event.channel_id()) {
+            (Some(global_id), _) => self.wants_node(global_id),
+            (None, Some(channel_id)) => {
+                self.principal.can_access_channel(channel_id)
+                    || self.visible_channels.contains(&channel_id)
+            }
+            (None, None) => self.visible.is_none(),
+        }
+    }
+
+    /// 订阅确认消息
+    fn describe(&self) -> Value {
+        let mut nodes: Vec<u32> = self.nodes.iter().copied().collect();
+        nodes.sort_unstable();
+        json!({
+            "type": "subscription",
+            "all": !self.filtered,
+            "global_ids": self.global_ids,
+            "tags": self.tags,
+            "nodes": nodes,
+        })
+    }
+
+    fn snapshot(&self, controller: &DeviceController) -> Value {
+        let mut states = controller.get_all_node_states();
+        states.retain(|(global_id, _)| self.wants_node(*global_id));
+        states.sort_by_key(|(global_id, _)| *global_id);
+        let nodes: Vec<Value> = states
+            .iter()
+            .map(|(global_id, state)| node_state_json(controller, *global_id, state, None))
+            .collect();
+        json!({ "type": "snapshot", "nodes": nodes })
+    }
+}
+
+/// 设备事件流（WebSocket）
+///
+/// 连接后推送设备事件，客户端可发送订阅消息按节点 ID 或标签过滤，并请求订阅节点的当前状态快照。
+/// 事件积压丢失时推送 `{"type": "lagged", "missed": N}`，客户端应重新请求快照。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/events/ws",
+    responses(
+        (status = 101, description = "升级为 WebSocket 连接")
+    ),
+    tag = "Device"
+)]
+pub async fn event_stream(
+    ws: WebSocketUpgrade,
+    Extension(controller): Extension<SharedController>,
+    Extension(principal): Extension<Principal>,
+) -> Response {
+    ws.on_upgrade(move |socket| serve(socket, controller, principal))
+}
+
+async fn serve(mut socket: WebSocket, controller: SharedController, principal: Principal) {
+    debug!("[事件流] {} 已连接", principal.name);
+    let mut subscription = Subscription::new(principal);
+    // 配置热重载替换控制器后事件总线关闭，重新订阅并按新配置解析标签
+    loop {
+        let mut event_rx = {
+            let controller = controller.read().await;
+            subscription.resolve(&controller.get_all_node_configs());
+            controller.subscribe_events()
+        };
+        loop {
+            let outgoing = tokio::select! {
+                event = event_rx.recv() => match event {
+                    Ok(event) if subscription.wants(&event) => {
+                        vec![controller.read().await.enrich_event(&event)]
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(n)) => {
+                        record_lagged_events(n);
+                        vec![json!({ "type": "lagged", "missed": n })]
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        handle_message(&text, &mut subscription, &controller).await
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                        debug!("[事件流] {} 已断开", subscription.principal.name);
+                        return;
+                    }
+                    Some(Ok(_)) => continue,
+                },
+            };
+            for value in outgoing {
+                if socket.send(Message::Text(value.to_string())).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// 处理客户端消息，返回需要推送的回复
+async fn handle_message(
+    text: &str,
+    subscription: &mut Subscription,
+    controller: &SharedController,
+) -> Vec<Value> {
+    let message: ClientMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            return vec![json!({ "type": "error", "message": format!("无效的订阅消息: {}", e) })]
+        }
+    };
+    let controller = controller.read().await;
+    let mut replies = Vec::new();
+    if !matches!(message, ClientMessage::Snapshot) {
+        subscription.apply(&message);
+        subscription.resolve(&controller.get_all_node_configs());
+        replies.push(subscription.describe());
+    }
+    if matches!(
+        message,
+        ClientMessage::Snapshot | ClientMessage::Subscribe { snapshot: true, .. }
+    ) {
+        replies.push(subscription.snapshot(&controller));
+    }
+    replies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AuthRole;
+    use crate::web::auth::PrincipalKind;
+
+    fn node_changed(global_id: u32) -> DeviceEvent {
+        DeviceEvent::NodeStateChanged {
+            global_id,
+            old_value: 0,
+            new_value: 1,
+            forced: false,
+        }
+    }
+
+    #[test]
+    fn filters_node_events_by_subscription() {
+        let configs: Vec<NodeConfig> = serde_json::from_value(json!([
+            { "global_id": 1, "channel_id": 1, "id": 1, "alias": "灯光" },
+            { "global_id": 2, "channel_id": 1, "id": 2, "alias": "电机", "tag": "Line1.Motor" },
+            { "global_id": 3, "channel_id": 2, "id": 1, "alias": "幕布" }
+        ]))
+        .unwrap();
+        let principal = Principal {
+            name: "dashboard".to_string(),
+            role: AuthRole::Viewer,
+            kind: PrincipalKind::Session,
+            scope: None,
+            unit_system: None,
+        };
+        let mut subscription = Subscription::new(principal);
+        subscription.resolve(&configs);
+        assert!(subscription.wants(&node_changed(3)));
+
+        let subscribe: ClientMessage = serde_json::from_value(
+            json!({ "action": "subscribe", "global_ids": [1], "tags": ["Line1.Motor"] }),
+        )
+        .unwrap();
+        subscription.apply(&subscribe);
+        subscription.resolve(&configs);
+        assert!(subscription.wants(&node_changed(1)));
+        assert!(subscription.wants(&node_changed(2)));
+        assert!(!subscription.wants(&node_changed(3)));
+        assert!(subscription.wants(&DeviceEvent::ChannelConnected { channel_id: 2 }));
+
+        let unsubscribe: ClientMessage =
+            serde_json::from_value(json!({ "action": "unsubscribe", "tags": ["Line1.Motor"] }))
+                .unwrap();
+        subscription.apply(&unsubscribe);
+        subscription.resolve(&configs);
+        assert!(!subscription.wants(&node_changed(2)));
+        assert_eq!(subscription.describe()["nodes"], json!([1]));
+    }
+}
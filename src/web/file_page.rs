@@ -1542,7 +1542,7 @@ pub const CONFIG_MANAGER_HTML: &str = r##"<!DOCTYPE html>
         function closeModal(id) { document.getElementById(id).classList.remove('show'); editIndex = -1; }
         function showAddNode() { editIndex = -1; document.getElementById('nodeModalTitle').textContent = 'Add Device'; document.getElementById('nodeId').value = ''; document.getElementById('nodeChannel').value = ''; document.getElementById('nodeDeviceId').value = ''; document.getElementById('nodeAlias').value = ''; document.getElementById('nodeModal').classList.add('show'); }
         function editNode(i) { editIndex = i; const n = config.nodes[i]; document.getElementById('nodeModalTitle').textContent = 'Edit Device'; document.getElementById('nodeId').value = n.global_id; document.getElementById('nodeChannel').value = n.channel_id; document.getElementById('nodeDeviceId').value = n.id; document.getElementById('nodeAlias').value = n.alias; document.getElementById('nodeModal').classList.add('show'); }
-        function saveNode() { const node = { global_id: parseInt(document.getElementById('nodeId').value), channel_id: parseInt(document.getElementById('nodeChannel').value), id: parseInt(document.getElementById('nodeDeviceId').value), alias: document.getElementById('nodeAlias').value }; if (editIndex >= 0) config.nodes[editIndex] = node; else config.nodes.push(node); closeModal('nodeModal'); renderNodes(); }
+        function saveNode() { const node = { ...(editIndex >= 0 ? config.nodes[editIndex] : {}), global_id: parseInt(document.getElementById('nodeId').value), channel_id: parseInt(document.getElementById('nodeChannel').value), id: parseInt(document.getElementById('nodeDeviceId').value), alias: document.getElementById('nodeAlias').value }; if (editIndex >= 0) config.nodes[editIndex] = node; else config.nodes.push(node); closeModal('nodeModal'); renderNodes(); }
         function deleteNode(i) { if (confirm('Delete this device?')) { config.nodes.splice(i, 1); renderNodes(); } }
         function showAddScene() { editIndex = -1; document.getElementById('sceneModalTitle').textContent = 'Add Scene'; document.getElementById('sceneName').value = ''; document.getElementById('sceneNodes').value = '[]'; document.getElementById('sceneModal').classList.add('show'); }
         function editScene(i) { editIndex = i; const s = config.scenes[i]; document.getElementById('sceneModalTitle').textContent = 'Edit Scene'; document.getElementById('sceneName').value = s.name; document.getElementById('sceneNodes').value = JSON.stringify(s.nodes || [], null, 2); document.getElementById('sceneModal').classList.add('show'); }
@@ -0,0 +1,208 @@
+//! HTTP 缓存校验 - ETag / Last-Modified 与 304 Not Modified
+//!
+//! - [`etag`] 中间件：按内容为 GET 成功响应计算 ETag，请求带匹配的 `If-None-Match` 时返回 304
+//!   （响应仍需完整生成，节省的是传输，适合经站点 VPN 访问的仪表盘反复拉取配置）
+//! - [`FileValidators`]：静态文件按大小与修改时间生成 ETag 和 Last-Modified
+//!
+//! ETag 均为弱校验值：压缩层在其后按 `Accept-Encoding` 改变编码，内容语义不变。
+
+use axum::{
+    body::{self, Body, Full, HttpBody},
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 超过该大小（或大小未知的流式响应）不计算内容 ETag
+const MAX_ETAG_BODY: u64 = 4 * 1024 * 1024;
+
+/// 要求客户端每次使用缓存前先校验
+const REVALIDATE: &str = "no-cache";
+
+/// 按响应内容计算 ETag 的中间件
+pub async fn etag(request: Request<Body>, next: Next<Body>) -> Response {
+    let cacheable = matches!(*request.method(), Method::GET | Method::HEAD);
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let response = next.run(request).await;
+
+    let small = matches!(response.body().size_hint().exact(), Some(size) if size <= MAX_ETAG_BODY);
+    if !cacheable
+        || !small
+        || response.status() != StatusCode::OK
+        || response.headers().contains_key(header::ETAG)
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = hyper::body::to_bytes(body).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let tag = format!("W/\"{:016x}\"", fnv1a(&bytes));
+    if let Ok(value) = HeaderValue::from_str(&tag) {
+        parts.headers.insert(header::ETAG, value);
+    }
+    parts
+        .headers
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static(REVALIDATE));
+
+    if if_none_match.is_some_and(|value| etag_matches(&value, &tag)) {
+        return not_modified(&parts.headers);
+    }
+    Response::from_parts(parts, body::boxed(Full::from(bytes)))
+}
+
+/// 静态文件的缓存校验值
+pub struct FileValidators {
+    etag: String,
+    last_modified: Option<SystemTime>,
+}
+
+impl FileValidators {
+    pub fn new(metadata: &std::fs::Metadata) -> Self {
+        let last_modified = metadata.modified().ok();
+        let mtime = last_modified
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        Self {
+            etag: format!("W/\"{:x}-{:x}\"", metadata.len(), mtime),
+            last_modified,
+        }
+    }
+
+    /// 请求的条件头表明客户端缓存仍然有效（`If-None-Match` 优先于 `If-Modified-Since`）
+    pub fn is_not_modified(&self, request_headers: &HeaderMap) -> bool {
+        if let Some(value) = request_headers.get(header::IF_NONE_MATCH) {
+            return etag_matches(value, &self.etag);
+        }
+        let since = request_headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok());
+        match (since, self.last_modified) {
+            // HTTP 日期精确到秒
+            (Some(since), Some(modified)) => {
+                DateTime::<Utc>::from(modified).timestamp() <= since.timestamp()
+            }
+            _ => false,
+        }
+    }
+
+    /// 写入 ETag、Last-Modified 与 Cache-Control 响应头
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        if let Ok(value) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, value);
+        }
+        if let Some(modified) = self.last_modified {
+            let date = DateTime::<Utc>::from(modified)
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string();
+            if let Ok(value) = HeaderValue::from_str(&date) {
+                headers.insert(header::LAST_MODIFIED, value);
+            }
+        }
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(REVALIDATE));
+    }
+
+    /// 304 响应
+    pub fn not_modified(&self) -> Response {
+        let mut headers = HeaderMap::new();
+        self.apply(&mut headers);
+        not_modified(&headers)
+    }
+}
+
+/// 只带缓存校验头的 304 响应
+fn not_modified(headers: &HeaderMap) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    for name in [header::ETAG, header::LAST_MODIFIED, header::CACHE_CONTROL] {
+        if let Some(value) = headers.get(&name) {
+            response.headers_mut().insert(name, value.clone());
+        }
+    }
+    response
+}
+
+/// `If-None-Match` 是否匹配（弱比较，支持列表与 `*`）
+fn etag_matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    let Ok(value) = if_none_match.to_str() else {
+        return false;
+    };
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    value
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+}
+
+/// FNV-1a 64 位哈希（内容 ETag 用，不要求抗碰撞）
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditional_request_headers() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"{\"nodes\": []}").unwrap();
+        let validators = FileValidators::new(&file.path().metadata().unwrap());
+
+        let mut response_headers = HeaderMap::new();
+        validators.apply(&mut response_headers);
+        let etag = response_headers[header::ETAG].clone();
+        let last_modified = response_headers[header::LAST_MODIFIED].clone();
+
+        let mut request = HeaderMap::new();
+        assert!(!validators.is_not_modified(&request));
+        request.insert(header::IF_MODIFIED_SINCE, last_modified);
+        assert!(validators.is_not_modified(&request));
+        request.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Mon, 01 Jan 2001 00:00:00 GMT"),
+        );
+        assert!(!validators.is_not_modified(&request));
+
+        // If-None-Match 优先于 If-Modified-Since
+        request.insert(header::IF_NONE_MATCH, etag.clone());
+        assert!(validators.is_not_modified(&request));
+        request.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"other\""));
+        assert!(!validators.is_not_modified(&request));
+
+        let tag = etag.to_str().unwrap().trim_start_matches("W/").to_string();
+        let list = HeaderValue::from_str(&format!("\"a\", {}", tag)).unwrap();
+        assert!(etag_matches(&list, etag.to_str().unwrap()));
+        assert!(etag_matches(&HeaderValue::from_static("*"), "W/\"1\""));
+    }
+
+    #[tokio::test]
+    async fn etag_middleware_returns_not_modified() {
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new().route(
+            "/config",
+            get(|| async { "{\"nodes\": []}" }).layer(axum::middleware::from_fn(etag)),
+        );
+        let response = app
+            .clone()
+            .oneshot(Request::get("/config").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let tag = response.headers()[header::ETAG].clone();
+
+        let request = Request::get("/config")
+            .header(header::IF_NONE_MATCH, tag.clone())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], tag);
+    }
+}
@@ -0,0 +1,191 @@
+//! LDAP / Active Directory 绑定认证
+
+use ldap3::{
+    dn_escape, ldap_escape, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry,
+};
+use std::time::Duration;
+
+use crate::config::{AuthRole, LdapConfig};
+
+/// LDAP 结果码: invalidCredentials
+const RC_INVALID_CREDENTIALS: u32 = 49;
+
+/// LDAP 认证结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LdapOutcome {
+    /// 认证成功，附带映射后的角色
+    Authenticated(AuthRole),
+    /// 密码错误或未映射到任何角色
+    Rejected,
+    /// 目录中不存在该用户
+    UserNotFound,
+    /// 服务器不可达或服务账号配置错误
+    Unreachable(String),
+}
+
+/// 以用户名密码绑定 LDAP 并按组映射角色
+pub async fn authenticate(config: &LdapConfig, username: &str, password: &str) -> LdapOutcome {
+    // 空密码会被视为匿名绑定而“成功”，必须提前拒绝
+    if username.is_empty() || password.is_empty() {
+        return LdapOutcome::Rejected;
+    }
+    match try_authenticate(config, username, password).await {
+        Ok(outcome) => outcome,
+        Err(LdapError::LdapResult { result }) if result.rc == RC_INVALID_CREDENTIALS => {
+            LdapOutcome::Rejected
+        }
+        Err(e) => LdapOutcome::Unreachable(e.to_string()),
+    }
+}
+
+async fn try_authenticate(
+    config: &LdapConfig,
+    username: &str,
+    password: &str,
+) -> ldap3::result::Result<LdapOutcome> {
+    let timeout = Duration::from_secs(config.timeout);
+    let settings = LdapConnSettings::new()
+        .set_conn_timeout(timeout)
+        .set_starttls(config.starttls)
+        .set_no_tls_verify(config.no_tls_verify);
+    let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &config.url).await?;
+    ldap3::drive!(conn);
+
+    let filter = config
+        .user_filter
+        .replace("{username}", &ldap_escape(username));
+    let attrs = vec![config.group_attribute.as_str()];
+
+    let groups = match (&config.bind_dn, &config.user_dn) {
+        (Some(bind_dn), _) => {
+            let bind_password = config.bind_password.as_deref().unwrap_or_default();
+            if let Err(e) = ldap
+                .with_timeout(timeout)
+                .simple_bind(bind_dn, bind_password)
+                .await?
+                .success()
+            {
+                // 服务账号无法绑定属于配置问题，按不可达处理以便回退到本地账号
+                return Ok(LdapOutcome::Unreachable(format!("服务账号绑定失败: {}", e)));
+            }
+            let (entries, _) = ldap
+                .with_timeout(timeout)
+                .search(&config.base_dn, Scope::Subtree, &filter, attrs)
+                .await?
+                .success()?;
+            let entry = match entries.into_iter().next() {
+                Some(entry) => SearchEntry::construct(entry),
+                None => return Ok(LdapOutcome::UserNotFound),
+            };
+            ldap.with_timeout(timeout)
+                .simple_bind(&entry.dn, password)
+                .await?
+                .success()?;
+            group_values(entry, &config.group_attribute)
+        }
+        (None, Some(template)) => {
+            let user_dn = template.replace("{username}", &dn_escape(username));
+            ldap.with_timeout(timeout)
+                .simple_bind(&user_dn, password)
+                .await?
+                .success()?;
+            let (entries, _) = ldap
+                .with_timeout(timeout)
+                .search(&config.base_dn, Scope::Subtree, &filter, attrs)
+                .await?
+                .success()?;
+            entries
+                .into_iter()
+                .next()
+                .map(|e| group_values(SearchEntry::construct(e), &config.group_attribute))
+                .unwrap_or_default()
+        }
+        (None, None) => {
+            return Ok(LdapOutcome::Unreachable(
+                "未配置 bind_dn 或 user_dn".to_string(),
+            ))
+        }
+    };
+    let _ = ldap.unbind().await;
+
+    match map_role(config, &groups) {
+        Some(role) => Ok(LdapOutcome::Authenticated(role)),
+        None => {
+            tracing::warn!(
+                "[认证] LDAP 用户 '{}' 未映射到任何角色: {:?}",
+                username,
+                groups
+            );
+            Ok(LdapOutcome::Rejected)
+        }
+    }
+}
+
+fn group_values(mut entry: SearchEntry, attribute: &str) -> Vec<String> {
+    // 属性名大小写不敏感
+    let key = entry
+        .attrs
+        .keys()
+        .find(|k| k.eq_ignore_ascii_case(attribute))
+        .cloned();
+    key.and_then(|k| entry.attrs.remove(&k)).unwrap_or_default()
+}
+
+/// 按组映射角色，取最高角色
+fn map_role(config: &LdapConfig, groups: &[String]) -> Option<AuthRole> {
+    config
+        .group_roles
+        .iter()
+        .filter(|mapping| groups.iter().any(|g| group_matches(g, &mapping.group)))
+        .map(|mapping| mapping.role)
+        .max()
+        .or(config.default_role)
+}
+
+fn group_matches(group_dn: &str, expected: &str) -> bool {
+    if group_dn.eq_ignore_ascii_case(expected) {
+        return true;
+    }
+    // CN=Operators,OU=Groups,DC=example,DC=com -> Operators
+    group_dn
+        .split(',')
+        .next()
+        .and_then(|rdn| rdn.split_once('='))
+        .is_some_and(|(_, name)| name.trim().eq_ignore_ascii_case(expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LdapGroupRole;
+
+    #[test]
+    fn highest_mapped_role_wins() {
+        let mut config: LdapConfig = serde_json::from_value(serde_json::json!({
+            "enable": true,
+            "url": "ldap://127.0.0.1",
+            "base_dn": "dc=example,dc=com"
+        }))
+        .unwrap();
+        config.group_roles = vec![
+            LdapGroupRole {
+                group: "Operators".to_string(),
+                role: AuthRole::Operator,
+            },
+            LdapGroupRole {
+                group: "cn=admins,ou=groups,dc=example,dc=com".to_string(),
+                role: AuthRole::Admin,
+            },
+        ];
+        let groups = vec![
+            "CN=Operators,OU=Groups,DC=example,DC=com".to_string(),
+            "CN=Admins,OU=Groups,DC=example,DC=com".to_string(),
+        ];
+
+        assert_eq!(map_role(&config, &groups), Some(AuthRole::Admin));
+        assert_eq!(map_role(&config, &groups[..1]), Some(AuthRole::Operator));
+        assert_eq!(map_role(&config, &[]), None);
+        config.default_role = Some(AuthRole::Viewer);
+        assert_eq!(map_role(&config, &[]), Some(AuthRole::Viewer));
+    }
+}
@@ -1,12 +1,23 @@
+pub mod auth;
+pub mod content_api;
 pub mod db_api;
 pub mod device_api;
+pub mod event_stream;
 pub mod file_api;
 pub mod file_page;
+pub(crate) mod http_cache;
+pub(crate) mod ldap_auth;
+pub mod playlist_api;
+pub mod public_api;
+pub(crate) mod request_log;
 pub mod resource_api;
 pub mod response;
 pub mod schema_api;
 pub mod server;
+pub mod session_api;
 pub mod state;
 pub mod swagger;
+pub mod sync_api;
+pub mod system_api;
 
 pub use server::WebServer;
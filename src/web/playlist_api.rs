@@ -0,0 +1,338 @@
+//! 播放列表 API 路由
+//!
+//! 播放列表的 CRUD，以及指定屏幕当前播放的列表与查询调度状态。
+
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use super::response::ApiResponse;
+use super::state::SharedPlaylistScheduler;
+use crate::db::{CreatePlaylistRequest, Database, PlaylistResponse, UpdatePlaylistRequest};
+use crate::playlist::PlaylistSchedulerStatus;
+
+/// 错误码
+mod error_codes {
+    pub const SUCCESS: i32 = 0;
+    pub const GENERAL_ERROR: i32 = 1;
+    pub const NOT_FOUND: i32 = 404;
+    pub const INVALID_PARAMS: i32 = 400;
+}
+
+/// 播放列表查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PlaylistQuery {
+    /// 按屏幕过滤
+    pub screen_id: Option<String>,
+}
+
+fn scheduler_disabled<T>() -> Json<ApiResponse<T>> {
+    Json(ApiResponse {
+        state: error_codes::GENERAL_ERROR,
+        message: "未启用播放列表调度（playlist.enable）".to_string(),
+        data: None,
+    })
+}
+
+/// 获取播放列表
+#[utoipa::path(
+    get,
+    path = "/lspcapi/playlists",
+    params(PlaylistQuery),
+    responses(
+        (status = 200, description = "查询成功", body = inline(ApiResponse<Vec<PlaylistResponse>>))
+    ),
+    tag = "Playlist"
+)]
+pub async fn list_playlists(
+    Extension(db): Extension<Arc<Database>>,
+    Query(query): Query<PlaylistQuery>,
+) -> Json<ApiResponse<Vec<PlaylistResponse>>> {
+    let result = match query.screen_id {
+        Some(ref screen_id) => db.playlists().find_by_screen_id(screen_id).await,
+        None => db.playlists().find_all().await,
+    };
+    match result {
+        Ok(playlists) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "成功".to_string(),
+            data: Some(playlists),
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("查询失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 获取单个播放列表
+#[utoipa::path(
+    get,
+    path = "/lspcapi/playlists/{id}",
+    params(("id" = String, Path, description = "播放列表 ID")),
+    responses(
+        (status = 200, description = "查询成功", body = inline(ApiResponse<PlaylistResponse>)),
+        (status = 404, description = "播放列表不存在")
+    ),
+    tag = "Playlist"
+)]
+pub async fn get_playlist(
+    Extension(db): Extension<Arc<Database>>,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<PlaylistResponse>> {
+    match db.playlists().find_by_id(&id).await {
+        Ok(Some(playlist)) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "成功".to_string(),
+            data: Some(playlist),
+        }),
+        Ok(None) => Json(ApiResponse {
+            state: error_codes::NOT_FOUND,
+            message: "播放列表不存在".to_string(),
+            data: None,
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("查询失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 创建播放列表
+///
+/// 条目按数组顺序播放，`duration_ms` 为每个条目的播放时长。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/playlists",
+    request_body = CreatePlaylistRequest,
+    responses(
+        (status = 200, description = "创建成功", body = inline(ApiResponse<PlaylistResponse>))
+    ),
+    tag = "Playlist"
+)]
+pub async fn create_playlist(
+    Extension(db): Extension<Arc<Database>>,
+    Json(req): Json<CreatePlaylistRequest>,
+) -> Json<ApiResponse<PlaylistResponse>> {
+    if let Some(message) = validate_items(req.items.iter().map(|i| i.duration_ms)) {
+        return Json(ApiResponse {
+            state: error_codes::INVALID_PARAMS,
+            message,
+            data: None,
+        });
+    }
+    match db.playlists().create(&req).await {
+        Ok(playlist) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "创建成功".to_string(),
+            data: Some(playlist),
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("创建失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 更新播放列表
+///
+/// 提供 `items` 时整体替换条目。
+#[utoipa::path(
+    put,
+    path = "/lspcapi/playlists/{id}",
+    params(("id" = String, Path, description = "播放列表 ID")),
+    request_body = UpdatePlaylistRequest,
+    responses(
+        (status = 200, description = "更新成功", body = inline(ApiResponse<PlaylistResponse>)),
+        (status = 404, description = "播放列表不存在")
+    ),
+    tag = "Playlist"
+)]
+pub async fn update_playlist(
+    Extension(db): Extension<Arc<Database>>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdatePlaylistRequest>,
+) -> Json<ApiResponse<PlaylistResponse>> {
+    let durations = req.items.iter().flatten().map(|i| i.duration_ms);
+    if let Some(message) = validate_items(durations) {
+        return Json(ApiResponse {
+            state: error_codes::INVALID_PARAMS,
+            message,
+            data: None,
+        });
+    }
+    match db.playlists().update(&id, &req).await {
+        Ok(Some(playlist)) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "更新成功".to_string(),
+            data: Some(playlist),
+        }),
+        Ok(None) => Json(ApiResponse {
+            state: error_codes::NOT_FOUND,
+            message: "播放列表不存在".to_string(),
+            data: None,
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("更新失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 删除播放列表
+#[utoipa::path(
+    delete,
+    path = "/lspcapi/playlists/{id}",
+    params(("id" = String, Path, description = "播放列表 ID")),
+    responses(
+        (status = 200, description = "删除成功"),
+        (status = 404, description = "播放列表不存在")
+    ),
+    tag = "Playlist"
+)]
+pub async fn delete_playlist(
+    Extension(db): Extension<Arc<Database>>,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match db.playlists().delete(&id).await {
+        Ok(true) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "删除成功".to_string(),
+            data: None,
+        }),
+        Ok(false) => Json(ApiResponse {
+            state: error_codes::NOT_FOUND,
+            message: "播放列表不存在".to_string(),
+            data: None,
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: format!("删除失败: {}", e),
+            data: None,
+        }),
+    }
+}
+
+/// 立即播放指定播放列表
+///
+/// 在播放列表所属屏幕上从第一条开始播放，忽略启用状态与生效日期，
+/// 直到调用 `auto` 恢复自动选择或服务重启。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/playlists/{id}/play",
+    params(("id" = String, Path, description = "播放列表 ID")),
+    responses(
+        (status = 200, description = "切换成功"),
+        (status = 404, description = "播放列表不存在")
+    ),
+    tag = "Playlist"
+)]
+pub async fn play_playlist(
+    Extension(db): Extension<Arc<Database>>,
+    Extension(scheduler): Extension<SharedPlaylistScheduler>,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<()>> {
+    let Some(scheduler) = scheduler else {
+        return scheduler_disabled();
+    };
+    let playlist = match db.playlists().find_by_id(&id).await {
+        Ok(Some(playlist)) => playlist.playlist,
+        Ok(None) => {
+            return Json(ApiResponse {
+                state: error_codes::NOT_FOUND,
+                message: "播放列表不存在".to_string(),
+                data: None,
+            })
+        }
+        Err(e) => {
+            return Json(ApiResponse {
+                state: error_codes::GENERAL_ERROR,
+                message: format!("查询失败: {}", e),
+                data: None,
+            })
+        }
+    };
+    match scheduler.switch(&playlist.screen_id, Some(playlist.id)) {
+        Ok(()) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "切换成功".to_string(),
+            data: None,
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::INVALID_PARAMS,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 恢复屏幕按日期自动选择播放列表
+#[utoipa::path(
+    post,
+    path = "/lspcapi/playlists/screens/{screen_id}/auto",
+    params(("screen_id" = String, Path, description = "屏幕 ID")),
+    responses(
+        (status = 200, description = "切换成功")
+    ),
+    tag = "Playlist"
+)]
+pub async fn resume_auto_playlist(
+    Extension(scheduler): Extension<SharedPlaylistScheduler>,
+    Path(screen_id): Path<String>,
+) -> Json<ApiResponse<()>> {
+    let Some(scheduler) = scheduler else {
+        return scheduler_disabled();
+    };
+    match scheduler.switch(&screen_id, None) {
+        Ok(()) => Json(ApiResponse {
+            state: error_codes::SUCCESS,
+            message: "已恢复自动选择".to_string(),
+            data: None,
+        }),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::INVALID_PARAMS,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 播放列表调度状态
+///
+/// 返回各屏幕当前播放的列表、条目与剩余时长。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/playlists/status",
+    responses(
+        (status = 200, description = "查询成功", body = inline(ApiResponse<PlaylistSchedulerStatus>))
+    ),
+    tag = "Playlist"
+)]
+pub async fn get_playlist_status(
+    Extension(scheduler): Extension<SharedPlaylistScheduler>,
+) -> Json<ApiResponse<PlaylistSchedulerStatus>> {
+    let Some(scheduler) = scheduler else {
+        return scheduler_disabled();
+    };
+    Json(ApiResponse {
+        state: error_codes::SUCCESS,
+        message: "成功".to_string(),
+        data: Some(scheduler.status().await),
+    })
+}
+
+/// 条目时长至少 1 秒
+fn validate_items(durations: impl IntoIterator<Item = i64>) -> Option<String> {
+    durations
+        .into_iter()
+        .any(|d| d < 1000)
+        .then(|| "条目播放时长 duration_ms 不能小于 1000".to_string())
+}
@@ -0,0 +1,87 @@
+//! 免认证公开状态 API 处理器
+
+use axum::{extract::Extension, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::response::ApiResponse;
+use super::state::{SharedConfig, SharedController};
+use crate::utils::error::error_codes;
+use crate::utils::time;
+
+/// 公开节点状态（不含通道、设备地址、元数据等内部信息）
+#[derive(Serialize, ToSchema)]
+pub struct PublicNodeStatus {
+    /// 对外名称
+    pub name: String,
+    /// 当前值
+    pub value: Option<i32>,
+    /// 当前值对应的状态名称
+    pub label: Option<String>,
+    /// 是否在线
+    pub online: bool,
+    /// 最后更新时间（UTC RFC3339）
+    pub updated_at: Option<String>,
+}
+
+/// 公开状态响应
+#[derive(Serialize, ToSchema)]
+pub struct PublicStatusResponse {
+    /// 服务器当前时间（UTC RFC3339）
+    pub server_time: String,
+    pub items: Vec<PublicNodeStatus>,
+}
+
+/// 获取公开状态
+///
+/// 无需认证，仅返回配置 `public_status.publish` 中列出的节点。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/public/status",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<PublicStatusResponse>))
+    ),
+    tag = "System"
+)]
+pub async fn get_public_status(
+    Extension(config): Extension<SharedConfig>,
+    Extension(controller): Extension<SharedController>,
+) -> Json<ApiResponse<PublicStatusResponse>> {
+    let public = match config.read().await.public_status.clone() {
+        Some(p) if p.enable => p,
+        _ => {
+            return Json(ApiResponse {
+                state: error_codes::GENERAL_ERROR,
+                message: "公开状态未启用".to_string(),
+                data: None,
+            })
+        }
+    };
+
+    let controller = controller.read().await;
+    let items = public
+        .publish
+        .iter()
+        .filter_map(|item| {
+            // 已从配置中删除的节点直接跳过，不暴露错误信息
+            let state = controller.get_node_state(item.node)?;
+            Some(PublicNodeStatus {
+                name: item.name.clone().unwrap_or(state.alias),
+                value: state.current_value,
+                label: state
+                    .current_value
+                    .and_then(|v| controller.get_value_label(item.node, v)),
+                online: state.online,
+                updated_at: state.last_update.map(time::instant_rfc3339),
+            })
+        })
+        .collect();
+
+    Json(ApiResponse::success(
+        "成功",
+        PublicStatusResponse {
+            server_time: time::rfc3339(chrono::Utc::now()),
+            items,
+        },
+    ))
+}
@@ -0,0 +1,120 @@
+//! HTTP 请求日志 - 记录每个请求的方法、路径、状态码、耗时、来源 IP 与关联 ID
+//!
+//! 关联 ID 取请求头 `X-Request-Id`（没有时生成），写回响应头，并作为 `http` span 的字段，
+//! 处理请求期间输出的日志都带有该 ID。耗时统计到响应头生成为止，不含流式响应体的传输。
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+use crate::config::{RequestLogConfig, RequestLogLevel};
+
+/// 关联 ID 请求 / 响应头
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 客户端提供的关联 ID 最大长度，超过时重新生成
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// 按配置的级别输出
+macro_rules! log_at {
+    ($level:expr, $($arg:tt)+) => {
+        match $level {
+            RequestLogLevel::Trace => tracing::trace!($($arg)+),
+            RequestLogLevel::Debug => tracing::debug!($($arg)+),
+            RequestLogLevel::Info => tracing::info!($($arg)+),
+        }
+    };
+}
+
+/// 请求日志中间件
+pub async fn log_requests(
+    State(config): State<Arc<RequestLogConfig>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let method = request.method().clone();
+    // 查询参数可能含令牌，只记录路径
+    let path = request.uri().path().to_string();
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let forwarded_for = request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let span = tracing::info_span!("http", request_id = %request_id);
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span).await;
+    let latency = started.elapsed();
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    let status = response.status().as_u16();
+    let latency_ms = latency.as_secs_f64() * 1000.0;
+    let forwarded_for = forwarded_for.as_deref().unwrap_or("-");
+    if config.slow_threshold_ms > 0 && latency >= Duration::from_millis(config.slow_threshold_ms) {
+        tracing::warn!(
+            %method, %path, status, latency_ms, %ip, forwarded_for, %request_id,
+            "慢请求（超过 {}ms）", config.slow_threshold_ms
+        );
+    } else if config.enable {
+        log_at!(
+            config.level,
+            %method, %path, status, latency_ms, %ip, forwarded_for, %request_id,
+            "HTTP 请求"
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn echoes_or_generates_request_id() {
+        let app = Router::new()
+            .route("/ping", get(|| async { "pong" }))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(RequestLogConfig::default()),
+                log_requests,
+            ));
+
+        let request = Request::get("/ping")
+            .header(REQUEST_ID_HEADER, "abc-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+
+        let response = app
+            .oneshot(Request::get("/ping").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok());
+    }
+}
@@ -6,8 +6,8 @@ use axum::body::Bytes;
 use axum::{
     body::StreamBody,
     extract::{Extension, Multipart, Path},
-    http::{header, HeaderValue, StatusCode},
-    response::IntoResponse,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use std::path::PathBuf;
@@ -16,6 +16,7 @@ use tokio::fs;
 use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
+use super::http_cache::FileValidators;
 use super::response::ApiResponse;
 use crate::config::ResourceConfig;
 use crate::db::{CreateMaterialRequest, Database, UploadMaterialResponse};
@@ -323,6 +324,7 @@ pub async fn upload_material(
     params(("path" = String, Path, description = "资源文件路径")),
     responses(
         (status = 200, description = "文件内容"),
+        (status = 304, description = "文件未变化（If-None-Match / If-Modified-Since 匹配）"),
         (status = 404, description = "文件不存在")
     ),
     tag = "Resource"
@@ -330,19 +332,29 @@ pub async fn upload_material(
 pub async fn serve_static_resource(
     Extension(state): Extension<ResourceManagerState>,
     Path(path): Path<String>,
-) -> impl IntoResponse {
+    request_headers: HeaderMap,
+) -> Response {
     let full_path = match get_safe_path(&state.config.path, &path) {
         Some(p) => p,
-        None => return Err((StatusCode::BAD_REQUEST, "无效的路径")),
+        None => return (StatusCode::BAD_REQUEST, "无效的路径").into_response(),
     };
 
     if !full_path.is_file() {
-        return Err((StatusCode::NOT_FOUND, "文件不存在"));
+        return (StatusCode::NOT_FOUND, "文件不存在").into_response();
+    }
+
+    // 缓存校验：文件未变化时返回 304
+    let validators = match fs::metadata(&full_path).await {
+        Ok(metadata) => FileValidators::new(&metadata),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "无法读取文件信息").into_response(),
+    };
+    if validators.is_not_modified(&request_headers) {
+        return validators.not_modified();
     }
 
     let file = match tokio::fs::File::open(&full_path).await {
         Ok(f) => f,
-        Err(_) => return Err((StatusCode::INTERNAL_SERVER_ERROR, "无法打开文件")),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "无法打开文件").into_response(),
     };
 
     let stream = ReaderStream::new(file);
@@ -352,11 +364,13 @@ pub async fn serve_static_resource(
     let ext = full_path.extension().and_then(|e| e.to_str()).unwrap_or("");
     let content_type = get_mime_type(ext);
 
-    let headers = [(
+    let mut headers = HeaderMap::new();
+    headers.insert(
         header::CONTENT_TYPE,
         HeaderValue::from_str(content_type)
             .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
-    )];
+    );
+    validators.apply(&mut headers);
 
-    Ok((headers, body))
+    (headers, body).into_response()
 }
@@ -9,6 +9,7 @@ const SCHEMA_SOURCES: &[(&str, &str)] = &[
         "hs-power-sequencer",
         include_str!("../protocols/schemas/hs-power-sequencer.json"),
     ),
+    ("custom", include_str!("../protocols/schemas/custom.json")),
     ("mock", include_str!("../protocols/schemas/mock.json")),
     ("modbus", include_str!("../protocols/schemas/modbus.json")),
     (
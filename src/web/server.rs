@@ -3,7 +3,8 @@
 //! 负责路由配置和服务器启动
 
 use axum::{
-    extract::Extension,
+    extract::{Extension, Path, Query},
+    middleware,
     response::Html,
     routing::{delete, get, post, put},
     Router,
@@ -11,37 +12,68 @@ use axum::{
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 
 use crate::config::{Config, ResourceConfig};
+use crate::content_push::ContentPusher;
 use crate::db::Database;
 use crate::device::DeviceController;
+use crate::session::SessionRecorder;
+use crate::utils::watchdog::Watchdog;
 
 // 导入子模块
+use super::auth::{
+    login, logout, me, refresh_token, require_auth, AuthManager, Principal, SharedAuth,
+};
+use super::content_api::{get_content_push, list_content_pushes, start_content_push};
 use super::db_api::{
     create_screen, delete_material, delete_screen, get_material, get_materials_by_screen_id,
     get_screen, list_materials, list_screens, replace_all_materials, replace_all_screens,
     set_screen_active, update_material, update_screen,
 };
 use super::device_api::{
-    batch_read, call_method, execute_channel_command, execute_scene, get_all_node_states,
-    get_all_settings, get_all_status, get_methods, get_node_state, get_scene_status, read_device,
-    read_many, write_device, write_many,
+    batch_read, call_method, cancel_confirmation, cancel_offline_write, cancel_scene_run,
+    confirm_write, disable_channel, enable_channel, execute_channel_command, execute_scene,
+    export_scenes, force_node, get_all_node_states, get_all_settings, get_all_status,
+    get_analytics_report, get_channel_cache, get_channel_startup_order, get_device_model,
+    get_execution_stats, get_methods, get_node_state, get_scene_diff, get_scene_status,
+    import_scenes, invalidate_channel_cache, invoke_many, list_confirmations, list_forced_nodes,
+    list_offline_writes, list_profiles, list_sites, next_scene_cue, ping_channel,
+    preview_scene_import, read_device, read_many, release_all_forces, release_node_force,
+    reset_execution_stats, send_raw_command, start_profile, stop_profile, write_device, write_many,
 };
+use super::event_stream::event_stream;
 use super::file_api::{
     file_delete, file_download, file_info, file_list, file_mkdir, file_preview, file_rename,
     file_upload, file_view, FileManagerState,
 };
 use super::file_page::{CONFIG_MANAGER_HTML, DEBUG_CONSOLE_HTML, FILE_MANAGER_HTML};
+use super::http_cache;
+use super::playlist_api::{
+    create_playlist, delete_playlist, get_playlist, get_playlist_status, list_playlists,
+    play_playlist, resume_auto_playlist, update_playlist,
+};
+use super::public_api::get_public_status;
+use super::request_log;
 use super::resource_api::{serve_static_resource, upload_material, ResourceManagerState};
 use super::schema_api::{get_protocol_schema, list_protocol_schemas};
-use super::state::{SharedConfig, SharedConfigPath, SharedController};
+use super::session_api::{
+    delete_session, get_replay_status, get_session, list_sessions, record_actions, replay_session,
+    start_session_recording, stop_session_recording, stop_session_replay,
+};
+use super::state::{SharedConfig, SharedConfigPath, SharedConfigStore, SharedController};
 #[cfg(feature = "swagger")]
 use super::swagger::swagger_routes;
+use super::sync_api::{
+    download_sync_file, get_sync_manifest, get_sync_status, register_sync_player,
+    report_sync_manifest,
+};
+use super::system_api::{get_diagnostics, get_system_info};
 
 /// API 路由前缀
-const API_PREFIX: &str = "/lspcapi";
+pub(crate) const API_PREFIX: &str = "/lspcapi";
 
 /// Web 服务器
 #[derive(Clone)]
@@ -51,6 +83,7 @@ pub struct WebServer {
     controller: DeviceController,
     database: Option<Arc<Database>>,
     resource_config: Option<ResourceConfig>,
+    config_store: SharedConfigStore,
 }
 
 impl WebServer {
@@ -63,6 +96,7 @@ impl WebServer {
             controller,
             database: None,
             resource_config,
+            config_store: None,
         }
     }
 
@@ -80,11 +114,19 @@ impl WebServer {
             controller,
             database: Some(Arc::new(database)),
             resource_config,
+            config_store: None,
         }
     }
 
+    /// 设置数据库配置源（保存与热重载配置时使用）
+    pub fn with_config_store(mut self, config_store: SharedConfigStore) -> Self {
+        self.config_store = config_store;
+        self
+    }
+
     /// 运行 Web 服务器
     pub async fn run(self) -> anyhow::Result<()> {
+        crate::utils::time::mark_started();
         let controller: SharedController = Arc::new(RwLock::new(self.controller));
         let runtime_config: SharedConfig = Arc::new(RwLock::new(self.config.clone()));
         let config_path: SharedConfigPath = Arc::new(self.config_path.clone());
@@ -93,6 +135,10 @@ impl WebServer {
             config: file_config.clone(),
         };
         let db_ref = self.database.clone();
+        let auth: SharedAuth = Arc::new(AuthManager::new());
+        if self.config.auth.as_ref().is_some_and(|a| a.enable) {
+            tracing::info!("访问认证已启用");
+        }
 
         // 设备控制路由
         let mut device_routes = Router::new()
@@ -101,15 +147,59 @@ impl WebServer {
             .route("/getNodeState", post(get_node_state))
             .route("/write", post(write_device))
             .route("/writeMany", post(write_many))
+            .route("/confirmations", get(list_confirmations))
+            .route("/confirmations/:token/confirm", post(confirm_write))
+            .route("/confirmations/:token/cancel", post(cancel_confirmation))
+            .route(
+                "/forces",
+                get(list_forced_nodes)
+                    .post(force_node)
+                    .delete(release_all_forces),
+            )
+            .route("/forces/:global_id", delete(release_node_force))
+            .route("/offlineWrites", get(list_offline_writes))
+            .route("/offlineWrites/:global_id", delete(cancel_offline_write))
+            .route("/profiles", get(list_profiles))
+            .route("/profiles/:name/start", post(start_profile))
+            .route("/profiles/:name/stop", post(stop_profile))
             .route("/read", post(read_device))
             .route("/readMany", post(read_many))
             .route("/scene", post(execute_scene))
             .route("/sceneStatus", get(get_scene_status))
+            .route("/scene/:name/diff", get(get_scene_diff))
+            // 同一位置的路径参数须同名，此处 :name 为执行编号 run_id
+            .route("/scene/:name/next", post(next_scene_cue))
+            .route("/scene/:name/cancel", post(cancel_scene_run))
+            .route("/scenes/export", post(export_scenes))
+            .route("/scenes/import/preview", post(preview_scene_import))
+            .route("/scenes/import", post(import_scenes))
             .route("/executeCommand", post(execute_channel_command))
             .route("/callMethod", post(call_method))
             .route("/getMethods", post(get_methods))
             .route("/batchRead", post(batch_read))
-            .route("/config", get(get_config));
+            .route("/model", get(get_device_model))
+            .route("/sites", get(list_sites))
+            .route("/channels/startup-order", get(get_channel_startup_order))
+            .route("/channels/invokeMany", post(invoke_many))
+            .route("/analytics", get(get_analytics_report))
+            .route(
+                "/stats",
+                get(get_execution_stats).delete(reset_execution_stats),
+            )
+            .route("/channels/:id/cache", get(get_channel_cache))
+            .route(
+                "/channels/:id/cache/invalidate",
+                post(invalidate_channel_cache),
+            )
+            .route("/channels/:id/enable", post(enable_channel))
+            .route("/channels/:id/disable", post(disable_channel))
+            .route("/channels/:id/raw", post(send_raw_command))
+            .route("/channels/:id/ping", post(ping_channel))
+            .route("/events/ws", get(event_stream))
+            .route(
+                "/config",
+                get(get_config).layer(middleware::from_fn(http_cache::etag)),
+            );
 
         // 如果有数据库，添加需要数据库的路由
         if let Some(ref db) = db_ref {
@@ -118,6 +208,27 @@ impl WebServer {
                 .layer(Extension(db.clone()));
         }
 
+        // 操作会话录制与回放（可选）
+        if let Some(sc) = self
+            .config
+            .session_recording
+            .as_ref()
+            .filter(|sc| sc.enable)
+        {
+            let recorder = SessionRecorder::new(sc, controller.clone());
+            device_routes = device_routes
+                .route("/sessions", get(list_sessions))
+                .route("/sessions/record/start", post(start_session_recording))
+                .route("/sessions/record/stop", post(stop_session_recording))
+                .route("/sessions/replay", get(get_replay_status))
+                .route("/sessions/replay/stop", post(stop_session_replay))
+                .route("/sessions/:name", get(get_session).delete(delete_session))
+                .route("/sessions/:name/replay", post(replay_session))
+                .layer(middleware::from_fn(record_actions))
+                .layer(Extension(recorder));
+            tracing::info!("操作会话录制已启用，会话目录: {}", sc.dir);
+        }
+
         // 基础应用路由
         let mut app = Router::new()
             .route("/", get(hello))
@@ -126,27 +237,48 @@ impl WebServer {
                 &format!("{}/config-manager", API_PREFIX),
                 get(config_manager_page),
             )
+            .route(&format!("{}/system/info", API_PREFIX), get(get_system_info))
             .route(
-                &format!("{}/schema", API_PREFIX),
-                get(list_protocol_schemas),
+                &format!("{}/system/diagnostics", API_PREFIX),
+                get(get_diagnostics),
             )
             .route(
-                &format!("{}/schema/:name", API_PREFIX),
-                get(get_protocol_schema),
+                &format!("{}/public/status", API_PREFIX),
+                get(get_public_status),
+            )
+            .route(&format!("{}/auth/login", API_PREFIX), post(login))
+            .route(&format!("{}/auth/refresh", API_PREFIX), post(refresh_token))
+            .route(&format!("{}/auth/logout", API_PREFIX), post(logout))
+            .route(&format!("{}/auth/me", API_PREFIX), get(me))
+            .route(
+                &format!("{}/schema", API_PREFIX),
+                get(list_protocol_schemas).layer(middleware::from_fn(http_cache::etag)),
             )
             .route(
-                &format!("{}/config/save", API_PREFIX),
-                post(save_config),
+                &format!("{}/schema/:name", API_PREFIX),
+                get(get_protocol_schema).layer(middleware::from_fn(http_cache::etag)),
             )
+            .route(&format!("{}/config/save", API_PREFIX), post(save_config))
             .route(
                 &format!("{}/config/reload", API_PREFIX),
                 post(reload_config),
             )
+            .route(
+                &format!("{}/config/history", API_PREFIX),
+                get(list_config_history),
+            )
+            .route(
+                &format!("{}/config/history/:id", API_PREFIX),
+                get(get_config_revision),
+            )
+            .route(
+                &format!("{}/config/history/:id/restore", API_PREFIX),
+                post(restore_config_revision),
+            )
             .nest(&format!("{}/device", API_PREFIX), device_routes)
-            .layer(Extension(controller))
-            .layer(Extension(runtime_config))
-            .layer(Extension(config_path))
-            .layer(CorsLayer::permissive());
+            .layer(Extension(controller.clone()))
+            .layer(Extension(runtime_config.clone()))
+            .layer(Extension(config_path));
 
         // 配置管理前端（Vue SPA）
         let exe_dir = std::env::current_exe()
@@ -294,12 +426,116 @@ impl WebServer {
             }
         }
 
-        let addr: SocketAddr = format!("0.0.0.0:{}", self.config.web_server.port).parse()?;
-        tracing::info!("HTTP 控制服务器监听于 {}", addr);
+        // 节点当前值写入数据库（可选）
+        if let Some(ref db) = self.database {
+            crate::db::write_behind::start(
+                self.config
+                    .database
+                    .as_ref()
+                    .and_then(|d| d.current_values.as_ref()),
+                db,
+                controller.clone(),
+            );
+        }
+
+        // 播放列表路由（需要数据库，调度可选）
+        if let Some(ref db) = self.database {
+            let scheduler = crate::playlist::start(
+                self.config.playlist.as_ref(),
+                self.resource_config.as_ref(),
+                db.clone(),
+                controller.clone(),
+            )?;
+            let playlist_routes = Router::new()
+                .route("/", get(list_playlists))
+                .route("/", post(create_playlist))
+                .route("/status", get(get_playlist_status))
+                .route("/screens/:screen_id/auto", post(resume_auto_playlist))
+                .route("/:id", get(get_playlist))
+                .route("/:id", put(update_playlist))
+                .route("/:id", delete(delete_playlist))
+                .route("/:id/play", post(play_playlist))
+                .layer(Extension(scheduler))
+                .layer(Extension(db.clone()));
+            app = app.nest(&format!("{}/playlists", API_PREFIX), playlist_routes);
+        }
+
+        // 内容推送路由（需要数据库与资源管理）
+        if let (Some(ref db), Some(rc)) = (
+            &self.database,
+            self.resource_config.as_ref().filter(|rc| rc.enable),
+        ) {
+            let pusher = ContentPusher::new(db.clone(), rc, controller.clone());
+            let content_routes = Router::new()
+                .route("/pushes", get(list_content_pushes))
+                .route("/pushes", post(start_content_push))
+                .route("/pushes/:id", get(get_content_push))
+                .layer(Extension(pusher));
+            app = app.nest(&format!("{}/content", API_PREFIX), content_routes);
+        }
+
+        // 资源同步路由（可选，不依赖数据库）
+        if let Some(sync) = crate::resource_sync::start(self.resource_config.as_ref())? {
+            let sync_routes = Router::new()
+                .route("/register", post(register_sync_player))
+                .route("/manifest", get(get_sync_manifest))
+                .route("/manifest", post(report_sync_manifest))
+                .route("/status", get(get_sync_status))
+                .route("/file/*path", get(download_sync_file))
+                .layer(Extension(sync));
+            app = app.nest(&format!("{}/resource/sync", API_PREFIX), sync_routes);
+        }
+
+        let watchdog_controller = controller.clone();
+        let bridge_controller = controller.clone();
+        let mqtt_controller = controller.clone();
+
+        // 认证中间件覆盖全部路由，CORS 在最外层以便拒绝响应也带跨域头
+        app = app
+            .layer(middleware::from_fn(require_auth))
+            .layer(Extension(auth))
+            .layer(Extension(controller))
+            .layer(Extension(runtime_config))
+            .layer(Extension(self.config_store.clone()));
+        // 响应压缩（按 Accept-Encoding，图片等已压缩内容与过小的响应不压缩）
+        if self.config.web_server.compression {
+            app = app.layer(CompressionLayer::new());
+        }
+        app = app.layer(CorsLayer::permissive());
+        // 请求日志在最外层，记录包括认证拒绝在内的全部请求
+        app = app.layer(middleware::from_fn_with_state(
+            Arc::new(self.config.web_server.request_log.clone()),
+            request_log::log_requests,
+        ));
+
+        let listener = crate::utils::net::bind_tcp_dual_stack(self.config.web_server.port)?;
+        tracing::info!("HTTP 控制服务器监听于 {}", listener.local_addr()?);
         tracing::info!("API 前缀: {}", API_PREFIX);
 
-        axum::Server::bind(&addr)
-            .serve(app.into_make_service())
+        // 自检看门狗：事件循环、Web 服务与通道协议锁
+        Watchdog::new(self.config.watchdog.clone().unwrap_or_default())
+            .web_port(listener.local_addr()?.port())
+            .probe("通道", move |timeout| {
+                let controller = watchdog_controller.clone();
+                Box::pin(async move {
+                    let channels = controller.read().await.unresponsive_channels(timeout).await;
+                    if channels.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(format!("通道 {:?} 无响应", channels))
+                    }
+                })
+            })
+            .start();
+        crate::utils::watchdog::notify_ready();
+
+        // 事件总线桥接（NATS / Redis）
+        crate::bridge::start(self.config.event_bridge.as_ref(), bridge_controller)?;
+        // MQTT 北向发布 / Home Assistant 自动发现
+        crate::mqtt::start(self.config.mqtt.as_ref(), mqtt_controller)?;
+
+        axum::Server::from_tcp(listener)?
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
             .await?;
 
         Ok(())
@@ -324,10 +560,20 @@ async fn debug_console_page() -> Html<&'static str> {
 /// 获取配置信息（用于调试控制台）
 async fn get_config(
     Extension(config): Extension<SharedConfig>,
+    Extension(config_store): Extension<SharedConfigStore>,
 ) -> axum::Json<serde_json::Value> {
     let config = config.read().await.clone();
     tracing::info!("[调试] 获取配置信息请求");
 
+    // 数据库配置源的最新版本号，保存时作为 base_revision 传回
+    let revision = match config_store {
+        Some(ref store) => store.latest_id().await.unwrap_or_else(|e| {
+            tracing::warn!("[配置] 查询数据库配置版本失败: {:?}", e);
+            None
+        }),
+        None => None,
+    };
+
     let response = serde_json::json!({
         "state": 0,
         "message": "成功",
@@ -338,7 +584,8 @@ async fn get_config(
             "channels": config.channels,
             "file": config.file,
             "database": config.database,
-            "resource": config.resource
+            "resource": config.resource,
+            "config_revision": revision
         }
     });
 
@@ -357,21 +604,88 @@ async fn config_manager_page() -> Html<&'static str> {
     Html(CONFIG_MANAGER_HTML)
 }
 
+/// 保存配置参数
+#[derive(Debug, serde::Deserialize)]
+struct SaveConfigQuery {
+    /// 版本备注（数据库配置源）
+    comment: Option<String>,
+    /// 编辑所基于的版本号：数据库中已有更新的版本时拒绝保存，避免覆盖他人的修改
+    base_revision: Option<i64>,
+}
+
 /// 保存配置到文件
+///
+/// 启用数据库配置源时，通道/节点/场景先保存为数据库中的新版本，配置文件同时更新作为回退。
 async fn save_config(
     Extension(config_path): Extension<SharedConfigPath>,
+    Extension(config_store): Extension<SharedConfigStore>,
+    Extension(principal): Extension<Principal>,
+    Query(query): Query<SaveConfigQuery>,
     axum::Json(payload): axum::Json<serde_json::Value>,
 ) -> axum::Json<serde_json::Value> {
     tracing::info!("[配置] 保存配置请求");
 
+    let mut revision = None;
+    if let Some(ref store) = config_store {
+        let config: Config = match serde_json::from_value(payload.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                return axum::Json(serde_json::json!({
+                    "state": 1,
+                    "message": format!("配置格式错误: {}", e)
+                }));
+            }
+        };
+        if let Some(base) = query.base_revision {
+            match store.latest_id().await {
+                Ok(Some(latest)) if latest != base => {
+                    return axum::Json(serde_json::json!({
+                        "state": 1,
+                        "message": format!("配置已被修改（当前版本 {}，编辑基于版本 {}），请刷新后重试", latest, base)
+                    }));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return axum::Json(serde_json::json!({
+                        "state": 1,
+                        "message": format!("读取数据库配置失败: {}", e)
+                    }));
+                }
+            }
+        }
+        let comment = query.comment.as_deref().unwrap_or_default();
+        match store.save(&config, &principal.name, comment).await {
+            Ok(id) => {
+                tracing::info!("[配置] 已保存到数据库版本 {} ({})", id, principal.name);
+                revision = Some(id);
+            }
+            Err(e) => {
+                tracing::error!("[配置] 保存到数据库失败: {:?}", e);
+                return axum::Json(serde_json::json!({
+                    "state": 1,
+                    "message": format!("保存到数据库失败: {}", e)
+                }));
+            }
+        }
+    }
+
     // 将配置写入文件
     match serde_json::to_string_pretty(&payload) {
         Ok(json_str) => match std::fs::write(config_path.as_ref(), json_str) {
             Ok(_) => {
                 tracing::info!("[配置] 配置已保存到: {}", config_path.as_ref());
+                let message = match revision {
+                    Some(id) => format!(
+                        "配置已保存到数据库（版本 {}）和 {}",
+                        id,
+                        config_path.as_ref()
+                    ),
+                    None => format!("配置已保存到 {}", config_path.as_ref()),
+                };
                 axum::Json(serde_json::json!({
                     "state": 0,
-                    "message": format!("配置已保存到 {}", config_path.as_ref())
+                    "message": message,
+                    "data": { "revision": revision }
                 }))
             }
             Err(e) => {
@@ -397,10 +711,11 @@ async fn reload_config(
     Extension(config_path): Extension<SharedConfigPath>,
     Extension(controller): Extension<SharedController>,
     Extension(runtime_config): Extension<SharedConfig>,
+    Extension(config_store): Extension<SharedConfigStore>,
 ) -> axum::Json<serde_json::Value> {
     tracing::info!("[配置] 热重载请求");
 
-    let next_config = match crate::config::load_config_from_file(config_path.as_ref()) {
+    let mut next_config = match crate::config::load_config_from_file(config_path.as_ref()) {
         Ok(cfg) => cfg,
         Err(e) => {
             tracing::error!("[配置] 加载配置失败: {:?}", e);
@@ -410,6 +725,14 @@ async fn reload_config(
             }));
         }
     };
+    // 数据库配置源：通道/节点/场景以数据库最新版本为准，失败时回退到配置文件
+    let mut revision = None;
+    if let Some(ref store) = config_store {
+        match store.load_into(&mut next_config).await {
+            Ok(id) => revision = Some(id),
+            Err(e) => tracing::error!("[配置] 从数据库加载配置失败，使用配置文件: {:?}", e),
+        }
+    }
 
     let old_port = {
         let cfg = runtime_config.read().await;
@@ -417,6 +740,72 @@ async fn reload_config(
     };
     let port_changed = old_port != next_config.web_server.port;
 
+    // 只有通道配置变化时逐个替换通道（新实例验证连接后再切换），其余通道的控制不中断
+    let changed = {
+        let current = runtime_config.read().await;
+        crate::config::changed_channels(&current, &next_config)
+    };
+    if let Some(changed) = changed.filter(|c| !c.is_empty()) {
+        let active = controller.read().await;
+        if changed
+            .iter()
+            .all(|c| active.can_replace_channel(c.channel_id))
+        {
+            let results =
+                futures::future::join_all(changed.iter().map(|c| active.replace_channel(c))).await;
+            drop(active);
+
+            let mut replaced = Vec::new();
+            let mut failed = Vec::new();
+            {
+                let mut active_config = runtime_config.write().await;
+                for (channel, result) in changed.into_iter().zip(results) {
+                    let channel_id = channel.channel_id;
+                    match result {
+                        Ok(()) => {
+                            if let Some(slot) = active_config
+                                .channels
+                                .iter_mut()
+                                .find(|c| c.channel_id == channel_id)
+                            {
+                                *slot = channel;
+                            }
+                            replaced.push(channel_id);
+                        }
+                        Err(e) => {
+                            tracing::warn!("[配置] {}", e);
+                            failed.push(serde_json::json!({
+                                "channel_id": channel_id,
+                                "error": e.to_string()
+                            }));
+                        }
+                    }
+                }
+            }
+
+            tracing::info!(
+                "[配置] 热重载完成（仅替换通道）: replaced={:?}, failed={}",
+                replaced,
+                failed.len()
+            );
+            return axum::Json(serde_json::json!({
+                "state": if failed.is_empty() { 0 } else { 1 },
+                "message": if failed.is_empty() {
+                    "热重载成功，已替换变更的通道。".to_string()
+                } else {
+                    format!("{} 个通道的新配置验证失败，仍使用原配置。", failed.len())
+                },
+                "data": {
+                    "port_changed": false,
+                    "requires_restart": false,
+                    "revision": revision,
+                    "replaced_channels": replaced,
+                    "failed_channels": failed
+                }
+            }));
+        }
+    }
+
     let next_controller = match DeviceController::new(next_config.clone()).await {
         Ok(c) => c,
         Err(e) => {
@@ -428,10 +817,12 @@ async fn reload_config(
         }
     };
 
-    {
+    let previous_controller = {
         let mut active_controller = controller.write().await;
-        *active_controller = next_controller;
-    }
+        std::mem::replace(&mut *active_controller, next_controller)
+    };
+    // 停止旧控制器的轮询、监视器等后台任务，避免旧通道继续访问设备
+    previous_controller.shutdown().await;
     {
         let mut active_config = runtime_config.write().await;
         *active_config = next_config.clone();
@@ -456,7 +847,129 @@ async fn reload_config(
         "message": message,
         "data": {
             "port_changed": port_changed,
-            "requires_restart": port_changed
+            "requires_restart": port_changed,
+            "revision": revision
         }
     }))
 }
+
+/// 历史版本查询参数
+#[derive(Debug, serde::Deserialize)]
+struct ConfigHistoryQuery {
+    /// 返回的版本数，默认 50
+    limit: Option<u32>,
+}
+
+/// 未启用数据库配置源时的响应
+fn config_store_disabled() -> axum::Json<serde_json::Value> {
+    axum::Json(serde_json::json!({
+        "state": 1,
+        "message": "未启用数据库配置源（database.config_store）"
+    }))
+}
+
+/// 配置历史版本列表（新版本在前）
+async fn list_config_history(
+    Extension(config_store): Extension<SharedConfigStore>,
+    Query(query): Query<ConfigHistoryQuery>,
+) -> axum::Json<serde_json::Value> {
+    let Some(store) = config_store else {
+        return config_store_disabled();
+    };
+    match store.history(query.limit.unwrap_or(50)).await {
+        Ok(revisions) => axum::Json(serde_json::json!({
+            "state": 0,
+            "message": "成功",
+            "data": revisions
+        })),
+        Err(e) => axum::Json(serde_json::json!({
+            "state": 1,
+            "message": format!("查询配置历史失败: {}", e)
+        })),
+    }
+}
+
+/// 获取指定版本的通道/节点/场景配置
+async fn get_config_revision(
+    Extension(config_store): Extension<SharedConfigStore>,
+    Path(id): Path<i64>,
+) -> axum::Json<serde_json::Value> {
+    let Some(store) = config_store else {
+        return config_store_disabled();
+    };
+    match store.revision(id).await {
+        Ok(Some((revision, content))) => axum::Json(serde_json::json!({
+            "state": 0,
+            "message": "成功",
+            "data": {
+                "id": revision.id,
+                "operator": revision.operator,
+                "comment": revision.comment,
+                "created_at": revision.created_at,
+                "channels": content.channels,
+                "nodes": content.nodes,
+                "scenes": content.scenes
+            }
+        })),
+        Ok(None) => axum::Json(serde_json::json!({
+            "state": 404,
+            "message": format!("配置版本 {} 不存在", id)
+        })),
+        Err(e) => axum::Json(serde_json::json!({
+            "state": 1,
+            "message": format!("查询配置版本失败: {}", e)
+        })),
+    }
+}
+
+/// 恢复历史版本：以该版本内容保存为新版本（需热重载后生效）
+async fn restore_config_revision(
+    Extension(config_store): Extension<SharedConfigStore>,
+    Extension(runtime_config): Extension<SharedConfig>,
+    Extension(principal): Extension<Principal>,
+    Path(id): Path<i64>,
+) -> axum::Json<serde_json::Value> {
+    let Some(store) = config_store else {
+        return config_store_disabled();
+    };
+    let content = match store.revision(id).await {
+        Ok(Some((_, content))) => content,
+        Ok(None) => {
+            return axum::Json(serde_json::json!({
+                "state": 404,
+                "message": format!("配置版本 {} 不存在", id)
+            }))
+        }
+        Err(e) => {
+            return axum::Json(serde_json::json!({
+                "state": 1,
+                "message": format!("查询配置版本失败: {}", e)
+            }))
+        }
+    };
+
+    let mut config = runtime_config.read().await.clone();
+    content.apply_to(&mut config);
+    match store
+        .save(&config, &principal.name, &format!("恢复版本 {}", id))
+        .await
+    {
+        Ok(revision) => {
+            tracing::info!(
+                "[配置] {} 恢复版本 {} 为新版本 {}",
+                principal.name,
+                id,
+                revision
+            );
+            axum::Json(serde_json::json!({
+                "state": 0,
+                "message": format!("已恢复为新版本 {}，热重载后生效", revision),
+                "data": { "revision": revision }
+            }))
+        }
+        Err(e) => axum::Json(serde_json::json!({
+            "state": 1,
+            "message": format!("保存到数据库失败: {}", e)
+        })),
+    }
+}
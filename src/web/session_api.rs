@@ -0,0 +1,274 @@
+//! 操作会话录制与回放 API 处理器
+//!
+//! 录制中间件挂在设备控制路由上，录制期间把写入、场景与方法调用请求记入当前会话。
+
+use axum::{
+    body::Body,
+    extract::{Extension, Path},
+    http::{Method, Request},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::auth::Principal;
+use super::device_api::{self, WriteValue};
+use super::response::ApiResponse;
+use super::state::SharedController;
+use crate::session::{
+    RecordedSession, RecordingStatus, ReplayStatus, SessionAction, SessionRecorder, SessionSummary,
+    RECORDED_PATHS,
+};
+use crate::utils::error::error_codes;
+
+/// 录制中间件：录制期间记录设备控制请求，请求体原样交给处理器
+pub async fn record_actions(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    if request.method() != Method::POST
+        || !RECORDED_PATHS.contains(&request.uri().path())
+        || !recorder.is_recording()
+    {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("[会话录制] 读取请求体失败: {}", e);
+            return next.run(Request::from_parts(parts, Body::empty())).await;
+        }
+    };
+    if let Ok(json) = serde_json::from_slice(&bytes) {
+        let principal = parts.extensions.get::<Principal>();
+        let operator = principal.map(|p| p.name.clone());
+        // 写入值按请求方单位制换算为节点单位后录制，回放时不再依赖请求头
+        let units = principal.and_then(|p| device_api::preferred_units(&parts.headers, p));
+        let actions = match parts.extensions.get::<SharedController>() {
+            Some(controller) => {
+                let controller = controller.read().await;
+                SessionAction::from_request(parts.uri.path(), &json, &|global_id, value| {
+                    let value: WriteValue = serde_json::from_value(value.clone())?;
+                    device_api::resolve_write_value(&controller, global_id, value, units)
+                })
+            }
+            None => Vec::new(),
+        };
+        recorder.record(operator, actions);
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// 会话列表响应
+#[derive(Serialize, ToSchema)]
+pub struct SessionListResponse {
+    /// 正在进行的录制
+    pub recording: Option<RecordingStatus>,
+    /// 已保存的会话
+    pub sessions: Vec<SessionSummary>,
+}
+
+/// 开始录制请求
+#[derive(Deserialize, ToSchema)]
+pub struct StartRecordingRequest {
+    /// 会话名称（字母、数字、- 或 _）
+    pub name: String,
+}
+
+/// 回放请求
+#[derive(Deserialize, ToSchema)]
+pub struct ReplayRequest {
+    /// 回放速度倍数，1 为原始节奏，2 为两倍速
+    #[serde(default = "default_replay_speed")]
+    pub speed: f64,
+    /// 演练模式：只检查节点、场景与通道方法，不下发到设备
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn default_replay_speed() -> f64 {
+    1.0
+}
+
+fn failure<T>(e: impl std::fmt::Display) -> Json<ApiResponse<T>> {
+    Json(ApiResponse {
+        state: error_codes::GENERAL_ERROR,
+        message: e.to_string(),
+        data: None,
+    })
+}
+
+/// 会话列表
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/sessions",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<SessionListResponse>))
+    ),
+    tag = "Session"
+)]
+pub async fn list_sessions(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+) -> Json<ApiResponse<SessionListResponse>> {
+    match recorder.list().await {
+        Ok(sessions) => Json(ApiResponse::success(
+            "获取会话列表成功",
+            SessionListResponse {
+                recording: recorder.recording(),
+                sessions,
+            },
+        )),
+        Err(e) => failure(format!("读取会话列表失败: {}", e)),
+    }
+}
+
+/// 开始录制会话
+///
+/// 录制期间通过设备接口执行的写入、批量写入、场景、通道方法与通道命令按时间记入会话，
+/// 同时只能录制一个会话；同名会话在停止录制时被覆盖。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/sessions/record/start",
+    request_body = StartRecordingRequest,
+    responses(
+        (status = 200, description = "录制已开始", body = inline(ApiResponse<RecordingStatus>))
+    ),
+    tag = "Session"
+)]
+pub async fn start_session_recording(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+    Json(request): Json<StartRecordingRequest>,
+) -> Json<ApiResponse<RecordingStatus>> {
+    match recorder.start(&request.name) {
+        Ok(status) => Json(ApiResponse::success("录制已开始", status)),
+        Err(e) => failure(e),
+    }
+}
+
+/// 停止录制并保存会话
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/sessions/record/stop",
+    responses(
+        (status = 200, description = "会话已保存", body = inline(ApiResponse<SessionSummary>))
+    ),
+    tag = "Session"
+)]
+pub async fn stop_session_recording(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+) -> Json<ApiResponse<SessionSummary>> {
+    match recorder.stop().await {
+        Ok(summary) => Json(ApiResponse::success("会话已保存", summary)),
+        Err(e) => failure(e),
+    }
+}
+
+/// 会话详情（全部动作）
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/sessions/{name}",
+    params(("name" = String, Path, description = "会话名称")),
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<RecordedSession>))
+    ),
+    tag = "Session"
+)]
+pub async fn get_session(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<RecordedSession>> {
+    match recorder.get(&name).await {
+        Ok(session) => Json(ApiResponse::success("获取会话成功", session)),
+        Err(e) => failure(e),
+    }
+}
+
+/// 删除会话
+#[utoipa::path(
+    delete,
+    path = "/lspcapi/device/sessions/{name}",
+    params(("name" = String, Path, description = "会话名称")),
+    responses(
+        (status = 200, description = "删除成功", body = inline(ApiResponse<()>))
+    ),
+    tag = "Session"
+)]
+pub async fn delete_session(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<()>> {
+    match recorder.delete(&name).await {
+        Ok(()) => Json(ApiResponse::<()>::success_empty("会话已删除")),
+        Err(e) => failure(e),
+    }
+}
+
+/// 回放会话
+///
+/// 按录制时的时间间隔（除以 `speed`）在后台依次执行，立即返回；进度通过回放状态接口查询。
+/// `dry_run` 为 true 时逐条检查后直接返回完整结果，不下发到设备。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/sessions/{name}/replay",
+    params(("name" = String, Path, description = "会话名称")),
+    request_body = ReplayRequest,
+    responses(
+        (status = 200, description = "回放已开始（演练时为检查结果）", body = inline(ApiResponse<ReplayStatus>))
+    ),
+    tag = "Session"
+)]
+pub async fn replay_session(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+    Path(name): Path<String>,
+    Json(request): Json<ReplayRequest>,
+) -> Json<ApiResponse<ReplayStatus>> {
+    match recorder.replay(&name, request.speed, request.dry_run).await {
+        Ok(status) if status.dry_run => Json(ApiResponse::success("演练完成", status)),
+        Ok(status) => Json(ApiResponse::success("回放已开始", status)),
+        Err(e) => failure(e),
+    }
+}
+
+/// 回放状态（最近一次正式回放）
+#[utoipa::path(
+    get,
+    path = "/lspcapi/device/sessions/replay",
+    responses(
+        (status = 200, description = "获取成功，没有回放记录时 data 为 null", body = inline(ApiResponse<Option<ReplayStatus>>))
+    ),
+    tag = "Session"
+)]
+pub async fn get_replay_status(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+) -> Json<ApiResponse<Option<ReplayStatus>>> {
+    Json(ApiResponse::success(
+        "获取回放状态成功",
+        recorder.replay_status(),
+    ))
+}
+
+/// 停止回放
+#[utoipa::path(
+    post,
+    path = "/lspcapi/device/sessions/replay/stop",
+    responses(
+        (status = 200, description = "回放已停止", body = inline(ApiResponse<()>))
+    ),
+    tag = "Session"
+)]
+pub async fn stop_session_replay(
+    Extension(recorder): Extension<Arc<SessionRecorder>>,
+) -> Json<ApiResponse<()>> {
+    if recorder.stop_replay() {
+        Json(ApiResponse::<()>::success_empty("回放已停止"))
+    } else {
+        failure("当前没有正在进行的回放")
+    }
+}
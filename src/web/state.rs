@@ -2,8 +2,14 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::config::Config;
+use crate::db::ConfigStore;
 use crate::device::DeviceController;
+use crate::playlist::PlaylistScheduler;
 
 pub type SharedController = Arc<RwLock<DeviceController>>;
 pub type SharedConfig = Arc<RwLock<Config>>;
 pub type SharedConfigPath = Arc<String>;
+/// 数据库配置源（未启用 database.config_store 时为 None）
+pub type SharedConfigStore = Option<Arc<ConfigStore>>;
+/// 播放列表调度（未启用 playlist 或未配置数据库时为 None）
+pub type SharedPlaylistScheduler = Option<Arc<PlaylistScheduler>>;
@@ -6,21 +6,55 @@ use axum::Router;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+use super::auth::{LoginRequest, MeResponse, PrincipalKind, RefreshTokenRequest, TokenResponse};
+use super::content_api::ContentPushRequest;
 use super::device_api::{
-    BatchReadItem, BatchReadRequest, BatchReadResultItem, CallMethodRequest, ChannelCommandRequest,
-    GetMethodsRequest, ReadManyRequest, ReadManyResultItem, ReadRequest,
-    SceneExecutionStatusResponse, SceneRequest, StatusRequest, SystemSettingsResponse,
-    WriteManyItem, WriteManyRequest, WriteManyResultItem, WriteRequest,
+    BatchReadItem, BatchReadRequest, BatchReadResultItem, CacheInvalidateRequest,
+    CallMethodRequest, ChannelCommandRequest, ChannelPingRequest, ConfirmWriteRequest,
+    ForceNodeRequest, GetMethodsRequest, InvokeManyItem, InvokeManyRequest, InvokeManyResultItem,
+    PendingWriteResponse, QueuedWriteResponse, RawCommandRequest, RawCommandResponse, RawEncoding,
+    ReadManyRequest, ReadManyResultItem, ReadRequest, SceneCueResponse, SceneDiffResponse,
+    SceneExecutionStatusResponse, SceneExportRequest, SceneImportPreviewRequest,
+    SceneImportPreviewResponse, SceneImportRequest, SceneImportResponse, SceneRequest,
+    SceneRunResponse, SceneRunResultResponse, SceneStartResponse, SceneStepDiffResponse,
+    SceneStepFailureResponse, SiteResponse, StatusRequest, SystemSettingsResponse, WriteManyItem,
+    WriteManyRequest, WriteManyResultItem, WriteRequest, WriteResponse, WriteValue,
 };
+use super::public_api::{PublicNodeStatus, PublicStatusResponse};
 use super::response::{
     MaterialArrayApiResponse, MaterialSingleApiResponse, ScreenApiResponse, ScreenListApiResponse,
     UploadMaterialApiResponse,
 };
+use super::session_api::{ReplayRequest, SessionListResponse, StartRecordingRequest};
+use super::sync_api::ReportManifestRequest;
+use super::system_api::{
+    ConfigFileDiagnostics, DiagnosticsResponse, ProcessDiagnostics, RuntimeDiagnostics,
+    SystemInfoResponse,
+};
+use crate::content_push::{ContentPushState, ContentPushStatus};
 use crate::db::{
     BatchReplaceMaterialsRequest, BatchReplaceScreensRequest, CreateMaterialRequest,
-    CreateScreenRequest, Material, MaterialResponse, Screen, UpdateMaterialRequest,
+    CreatePlaylistRequest, CreateScreenRequest, Material, MaterialResponse, Playlist, PlaylistItem,
+    PlaylistItemRequest, PlaylistResponse, Screen, UpdateMaterialRequest, UpdatePlaylistRequest,
     UpdateScreenRequest, UploadMaterialRequest, UploadMaterialResponse,
 };
+use crate::device::scene_transfer::{MatchKind, NodeMapping, PortableNode, ScenePackage};
+use crate::device::{
+    AnalyticsReport, AnomalyKind, ChannelDiagnostics, ChannelHealthSnapshot, ChannelStartup,
+    CommandResult, EventBusStats, ExecutionStatsReport, ForcedNode, NodeAnomaly, NodeWriteStats,
+    PingMode, PingReport, PingSample, ProfileStatus, SceneStats, StartupOrderReport,
+    StartupViolation, StartupViolationKind,
+};
+use crate::playlist::{PlaylistSchedulerStatus, ScreenPlaybackStatus};
+use crate::protocols::command_queue::{CommandClassStats, CommandPriority, CommandQueueStats};
+use crate::resource_sync::{
+    FileEntry, RegisterPlayer, SourceManifest, SyncFile, SyncPlan, SyncPlayerStatus, SyncStatus,
+};
+use crate::session::{
+    RecordedAction, RecordedSession, RecordingStatus, ReplayActionResult, ReplayStatus,
+    SessionAction, SessionSummary,
+};
+use crate::utils::UnmetDependency;
 
 /// OpenAPI 文档定义
 #[derive(OpenApi)]
@@ -49,6 +83,15 @@ use crate::db::{
         crate::web::db_api::replace_all_materials,
         crate::web::resource_api::upload_material,
         crate::web::resource_api::serve_static_resource,
+        // Playlist API
+        crate::web::playlist_api::list_playlists,
+        crate::web::playlist_api::get_playlist,
+        crate::web::playlist_api::create_playlist,
+        crate::web::playlist_api::update_playlist,
+        crate::web::playlist_api::delete_playlist,
+        crate::web::playlist_api::play_playlist,
+        crate::web::playlist_api::resume_auto_playlist,
+        crate::web::playlist_api::get_playlist_status,
         // Device API
         crate::web::device_api::get_all_settings,
         crate::web::device_api::get_all_status,
@@ -58,12 +101,72 @@ use crate::db::{
         crate::web::device_api::read_many,
         crate::web::device_api::write_device,
         crate::web::device_api::write_many,
+        crate::web::device_api::list_confirmations,
+        crate::web::device_api::confirm_write,
+        crate::web::device_api::cancel_confirmation,
+        crate::web::device_api::list_forced_nodes,
+        crate::web::device_api::force_node,
+        crate::web::device_api::release_node_force,
+        crate::web::device_api::release_all_forces,
+        crate::web::device_api::list_offline_writes,
+        crate::web::device_api::cancel_offline_write,
+        crate::web::device_api::list_profiles,
+        crate::web::device_api::start_profile,
+        crate::web::device_api::stop_profile,
         crate::web::device_api::execute_scene,
         crate::web::device_api::get_scene_status,
+        crate::web::device_api::next_scene_cue,
+        crate::web::device_api::cancel_scene_run,
+        crate::web::device_api::get_scene_diff,
+        crate::web::device_api::export_scenes,
+        crate::web::device_api::preview_scene_import,
+        crate::web::device_api::import_scenes,
         crate::web::device_api::execute_channel_command,
         crate::web::device_api::call_method,
         crate::web::device_api::get_methods,
+        crate::web::device_api::invoke_many,
         crate::web::device_api::batch_read,
+        crate::web::device_api::get_device_model,
+        crate::web::device_api::list_sites,
+        crate::web::device_api::get_channel_cache,
+        crate::web::device_api::invalidate_channel_cache,
+        crate::web::device_api::enable_channel,
+        crate::web::device_api::disable_channel,
+        crate::web::device_api::send_raw_command,
+        crate::web::device_api::ping_channel,
+        crate::web::device_api::get_channel_startup_order,
+        crate::web::device_api::get_analytics_report,
+        crate::web::device_api::get_execution_stats,
+        crate::web::device_api::reset_execution_stats,
+        crate::web::event_stream::event_stream,
+        // Session API
+        crate::web::session_api::list_sessions,
+        crate::web::session_api::start_session_recording,
+        crate::web::session_api::stop_session_recording,
+        crate::web::session_api::get_session,
+        crate::web::session_api::delete_session,
+        crate::web::session_api::replay_session,
+        crate::web::session_api::get_replay_status,
+        crate::web::session_api::stop_session_replay,
+        // Content Push API
+        crate::web::content_api::start_content_push,
+        crate::web::content_api::list_content_pushes,
+        crate::web::content_api::get_content_push,
+        // Resource Sync API
+        crate::web::sync_api::register_sync_player,
+        crate::web::sync_api::report_sync_manifest,
+        crate::web::sync_api::get_sync_manifest,
+        crate::web::sync_api::get_sync_status,
+        crate::web::sync_api::download_sync_file,
+        // System API
+        crate::web::system_api::get_system_info,
+        crate::web::system_api::get_diagnostics,
+        crate::web::public_api::get_public_status,
+        // Auth API
+        crate::web::auth::login,
+        crate::web::auth::refresh_token,
+        crate::web::auth::logout,
+        crate::web::auth::me,
     ),
     components(
         schemas(
@@ -86,30 +189,137 @@ use crate::db::{
             BatchReplaceMaterialsRequest,
             UploadMaterialResponse,
             UploadMaterialRequest,
+            // Playlist
+            Playlist,
+            PlaylistItem,
+            PlaylistResponse,
+            PlaylistItemRequest,
+            CreatePlaylistRequest,
+            UpdatePlaylistRequest,
+            PlaylistSchedulerStatus,
+            ScreenPlaybackStatus,
             // Device API
             WriteRequest,
             WriteManyRequest,
             WriteManyItem,
             WriteManyResultItem,
+            WriteResponse,
+            UnmetDependency,
+            PendingWriteResponse,
+            ConfirmWriteRequest,
+            ForceNodeRequest,
+            ForcedNode,
+            QueuedWriteResponse,
+            ProfileStatus,
+            WriteValue,
             ReadRequest,
             ReadManyRequest,
             ReadManyResultItem,
             StatusRequest,
             SceneRequest,
+            SceneDiffResponse,
+            SceneStepDiffResponse,
+            SceneExportRequest,
+            SceneImportPreviewRequest,
+            SceneImportPreviewResponse,
+            SceneImportRequest,
+            SceneImportResponse,
+            ScenePackage,
+            PortableNode,
+            NodeMapping,
+            MatchKind,
             SceneExecutionStatusResponse,
+            SceneRunResponse,
+            SceneStartResponse,
+            SceneCueResponse,
+            SceneRunResultResponse,
+            SceneStepFailureResponse,
             ChannelCommandRequest,
             CallMethodRequest,
+            CommandResult,
+            InvokeManyRequest,
+            InvokeManyItem,
+            InvokeManyResultItem,
             GetMethodsRequest,
             BatchReadRequest,
             BatchReadItem,
             BatchReadResultItem,
+            CacheInvalidateRequest,
+            RawCommandRequest,
+            RawCommandResponse,
+            ChannelPingRequest,
+            PingMode,
+            PingReport,
+            PingSample,
+            SiteResponse,
+            RawEncoding,
             SystemSettingsResponse,
+            // System API
+            SystemInfoResponse,
+            DiagnosticsResponse,
+            ProcessDiagnostics,
+            RuntimeDiagnostics,
+            ConfigFileDiagnostics,
+            EventBusStats,
+            ChannelDiagnostics,
+            ChannelHealthSnapshot,
+            CommandQueueStats,
+            CommandClassStats,
+            CommandPriority,
+            StartupOrderReport,
+            ChannelStartup,
+            StartupViolation,
+            StartupViolationKind,
+            AnalyticsReport,
+            NodeAnomaly,
+            AnomalyKind,
+            ExecutionStatsReport,
+            SceneStats,
+            NodeWriteStats,
+            // Session API
+            SessionListResponse,
+            StartRecordingRequest,
+            ReplayRequest,
+            SessionAction,
+            RecordedAction,
+            RecordedSession,
+            SessionSummary,
+            RecordingStatus,
+            ReplayStatus,
+            ReplayActionResult,
+            // Content Push API
+            ContentPushRequest,
+            ContentPushStatus,
+            ContentPushState,
+            // Resource Sync API
+            RegisterPlayer,
+            ReportManifestRequest,
+            FileEntry,
+            SourceManifest,
+            SyncFile,
+            SyncPlan,
+            SyncPlayerStatus,
+            SyncStatus,
+            PublicStatusResponse,
+            PublicNodeStatus,
+            // Auth API
+            LoginRequest,
+            RefreshTokenRequest,
+            TokenResponse,
+            MeResponse,
+            PrincipalKind,
         )
     ),
     tags(
         (name = "Screen", description = "屏幕管理 API"),
         (name = "Material", description = "素材管理 API"),
-        (name = "Device", description = "设备控制 API")
+        (name = "Playlist", description = "播放列表 API"),
+        (name = "Device", description = "设备控制 API"),
+        (name = "Session", description = "操作会话录制与回放 API"),
+        (name = "Content", description = "内容推送 API"),
+        (name = "ResourceSync", description = "资源同步 API"),
+        (name = "System", description = "系统信息 API"),
+        (name = "Auth", description = "认证 API")
     )
 )]
 pub struct ApiDoc;
@@ -0,0 +1,271 @@
+//! 资源同步 API 处理器
+//!
+//! 媒体播放器注册、上报清单获取同步计划，并按计划从控制器下载文件。
+
+use axum::{
+    body::StreamBody,
+    extract::{Extension, Path, Query},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use utoipa::{IntoParams, ToSchema};
+
+use super::response::ApiResponse;
+use crate::resource_sync::{
+    FileEntry, RegisterPlayer, ResourceSync, SourceManifest, SyncPlan, SyncPlayerStatus, SyncStatus,
+};
+use crate::utils::error::error_codes;
+
+/// 上报清单请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReportManifestRequest {
+    /// 播放器 ID（未注册时自动注册）
+    pub player_id: String,
+    /// 播放器本地文件清单
+    #[serde(default)]
+    pub files: Vec<FileEntry>,
+}
+
+/// 下载文件查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SyncFileQuery {
+    /// 播放器 ID（用于统计下载量）
+    pub player_id: Option<String>,
+}
+
+/// 注册播放器
+#[utoipa::path(
+    post,
+    path = "/lspcapi/resource/sync/register",
+    request_body = RegisterPlayer,
+    responses(
+        (status = 200, description = "注册成功", body = inline(ApiResponse<SyncPlayerStatus>))
+    ),
+    tag = "ResourceSync"
+)]
+pub async fn register_sync_player(
+    Extension(sync): Extension<Arc<ResourceSync>>,
+    Json(request): Json<RegisterPlayer>,
+) -> Json<ApiResponse<SyncPlayerStatus>> {
+    match sync.register(request) {
+        Ok(status) => Json(ApiResponse::success("注册成功", status)),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::INVALID_PARAMS,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 上报播放器清单，返回同步计划
+///
+/// 计划中的 `download` 为缺失或校验和不同的文件（附下载地址），`delete` 为控制器资源目录中
+/// 已不存在的文件。播放器完成同步后应再次上报清单。
+#[utoipa::path(
+    post,
+    path = "/lspcapi/resource/sync/manifest",
+    request_body = ReportManifestRequest,
+    responses(
+        (status = 200, description = "同步计划", body = inline(ApiResponse<SyncPlan>))
+    ),
+    tag = "ResourceSync"
+)]
+pub async fn report_sync_manifest(
+    Extension(sync): Extension<Arc<ResourceSync>>,
+    Json(request): Json<ReportManifestRequest>,
+) -> Json<ApiResponse<SyncPlan>> {
+    match sync.report(&request.player_id, request.files).await {
+        Ok(plan) => Json(ApiResponse::success("获取同步计划成功", plan)),
+        Err(e) => Json(ApiResponse {
+            state: error_codes::GENERAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+/// 控制器资源清单
+#[utoipa::path(
+    get,
+    path = "/lspcapi/resource/sync/manifest",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<SourceManifest>))
+    ),
+    tag = "ResourceSync"
+)]
+pub async fn get_sync_manifest(
+    Extension(sync): Extension<Arc<ResourceSync>>,
+) -> Json<ApiResponse<SourceManifest>> {
+    Json(ApiResponse::success(
+        "获取资源清单成功",
+        sync.manifest().await,
+    ))
+}
+
+/// 资源同步状态
+///
+/// 返回控制器清单摘要与各播放器的在线状态、待同步文件数与字节数。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/resource/sync/status",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<SyncStatus>))
+    ),
+    tag = "ResourceSync"
+)]
+pub async fn get_sync_status(
+    Extension(sync): Extension<Arc<ResourceSync>>,
+) -> Json<ApiResponse<SyncStatus>> {
+    Json(ApiResponse::success(
+        "获取同步状态成功",
+        sync.status().await,
+    ))
+}
+
+/// 下载同步文件
+///
+/// 只能下载控制器清单中的文件；支持单个 `Range` 断点续传，按 `bandwidth_limit_kbps` 限速。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/resource/sync/file/{path}",
+    params(
+        ("path" = String, Path, description = "清单中的文件路径"),
+        SyncFileQuery
+    ),
+    responses(
+        (status = 200, description = "文件内容"),
+        (status = 206, description = "部分内容（Range）"),
+        (status = 404, description = "文件不在清单中"),
+        (status = 409, description = "文件在扫描后已变化，请重新上报清单"),
+        (status = 416, description = "Range 无效")
+    ),
+    tag = "ResourceSync"
+)]
+pub async fn download_sync_file(
+    Extension(sync): Extension<Arc<ResourceSync>>,
+    Path(path): Path<String>,
+    Query(query): Query<SyncFileQuery>,
+    request_headers: HeaderMap,
+) -> Response {
+    let Some((entry, full_path)) = sync.file(path.trim_start_matches('/')).await else {
+        return (StatusCode::NOT_FOUND, "文件不在资源清单中").into_response();
+    };
+    let mut file = match tokio::fs::File::open(&full_path).await {
+        Ok(f) => f,
+        Err(_) => return (StatusCode::NOT_FOUND, "文件不存在").into_response(),
+    };
+    match file.metadata().await {
+        Ok(metadata) if metadata.len() == entry.size => {}
+        _ => return (StatusCode::CONFLICT, "文件已变化，请重新上报清单").into_response(),
+    }
+
+    let range = request_headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok());
+    let (start, end) = match range {
+        Some(range) => match parse_range(range, entry.size) {
+            Some(range) => range,
+            None => {
+                let mut response =
+                    (StatusCode::RANGE_NOT_SATISFIABLE, "Range 无效").into_response();
+                if let Ok(value) = HeaderValue::from_str(&format!("bytes */{}", entry.size)) {
+                    response.headers_mut().insert(header::CONTENT_RANGE, value);
+                }
+                return response;
+            }
+        },
+        None => (0, entry.size.saturating_sub(1)),
+    };
+    let length = if entry.size == 0 { 0 } else { end - start + 1 };
+    if start > 0 && file.seek(SeekFrom::Start(start)).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "无法读取文件").into_response();
+    }
+
+    let limit = sync.bandwidth_limit();
+    let player_id = query.player_id;
+    let stream = ReaderStream::new(file.take(length)).then(move |chunk| {
+        let sync = sync.clone();
+        let player_id = player_id.clone();
+        async move {
+            if let Ok(ref bytes) = chunk {
+                if let Some(player_id) = player_id {
+                    sync.record_served(&player_id, bytes.len() as u64);
+                }
+                // 按块限速：每块发送后等待其在带宽上限下应占用的时间
+                if let Some(limit) = limit {
+                    let secs = bytes.len() as f64 / limit as f64;
+                    tokio::time::sleep(Duration::from_secs_f64(secs)).await;
+                }
+            }
+            chunk
+        }
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    if let Ok(value) = HeaderValue::from_str(&entry.sha256) {
+        headers.insert("x-checksum-sha256", value);
+    }
+    let status = if range.is_some() {
+        let content_range = format!("bytes {}-{}/{}", start, end, entry.size);
+        if let Ok(value) = HeaderValue::from_str(&content_range) {
+            headers.insert(header::CONTENT_RANGE, value);
+        }
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    };
+
+    (status, headers, StreamBody::new(stream)).into_response()
+}
+
+/// 解析单个 `bytes=` Range，返回闭区间 [start, end]；不支持多段 Range
+fn parse_range(range: &str, size: u64) -> Option<(u64, u64)> {
+    let spec = range.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || size == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        // 最后 N 个字节
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            (size.saturating_sub(suffix), size - 1)
+        }
+        (start, "") => (start.parse().ok()?, size - 1),
+        (start, end) => {
+            let end: u64 = end.parse().ok()?;
+            (start.parse().ok()?, end.min(size - 1))
+        }
+    };
+    (start <= end && start < size).then_some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_byte_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=500-", 1000), Some((500, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=900-2000", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! 系统信息 API 处理器
+
+use axum::{extract::Extension, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::response::ApiResponse;
+use super::state::{SharedConfigPath, SharedController};
+use crate::device::{ChannelDiagnostics, EventBusStats};
+use crate::utils::time;
+
+/// 系统信息响应
+///
+/// 客户端可用 `server_time_ms` 与本地时间之差估算时钟偏差。
+#[derive(Serialize, ToSchema)]
+pub struct SystemInfoResponse {
+    /// 服务版本
+    pub version: String,
+    /// 服务器当前时间（UTC RFC3339，毫秒精度）
+    pub server_time: String,
+    /// 服务器当前时间（Unix 毫秒时间戳）
+    pub server_time_ms: i64,
+    /// 服务启动时间（UTC RFC3339）
+    pub started_at: String,
+    /// 已运行时长（毫秒，单调时钟）
+    pub uptime_ms: u64,
+}
+
+/// 获取系统信息
+#[utoipa::path(
+    get,
+    path = "/lspcapi/system/info",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<SystemInfoResponse>))
+    ),
+    tag = "System"
+)]
+pub async fn get_system_info() -> Json<ApiResponse<SystemInfoResponse>> {
+    let now = chrono::Utc::now();
+    Json(ApiResponse::success(
+        "成功",
+        SystemInfoResponse {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            server_time: time::rfc3339(now),
+            server_time_ms: now.timestamp_millis(),
+            started_at: time::rfc3339(time::started_at()),
+            uptime_ms: time::uptime().as_millis() as u64,
+        },
+    ))
+}
+
+/// 进程资源占用
+#[derive(Serialize, ToSchema)]
+pub struct ProcessDiagnostics {
+    /// 进程 ID
+    pub pid: u32,
+    /// 常驻内存（字节），平台不支持时为 null
+    pub rss_bytes: Option<u64>,
+    /// 虚拟内存（字节），平台不支持时为 null
+    pub virtual_bytes: Option<u64>,
+    /// 打开的文件描述符数（仅 Linux）
+    pub open_fds: Option<usize>,
+    /// 打开的套接字数（仅 Linux）
+    pub open_sockets: Option<usize>,
+}
+
+/// Tokio 运行时统计
+#[derive(Serialize, ToSchema)]
+pub struct RuntimeDiagnostics {
+    /// 工作线程数
+    pub workers: usize,
+    /// 存活的任务数（含后台轮询、连接等长期任务）
+    pub alive_tasks: usize,
+    /// 全局队列中等待调度的任务数
+    pub global_queue_depth: usize,
+}
+
+/// 配置文件信息
+#[derive(Serialize, ToSchema)]
+pub struct ConfigFileDiagnostics {
+    pub path: String,
+    /// 最后修改时间（UTC RFC3339），文件不可读时为 null
+    pub modified_at: Option<String>,
+}
+
+/// 诊断信息响应
+#[derive(Serialize, ToSchema)]
+pub struct DiagnosticsResponse {
+    /// 服务器当前时间（UTC RFC3339）
+    pub server_time: String,
+    /// 已运行时长（毫秒）
+    pub uptime_ms: u64,
+    pub process: ProcessDiagnostics,
+    pub runtime: RuntimeDiagnostics,
+    pub event_bus: EventBusStats,
+    pub channels: Vec<ChannelDiagnostics>,
+    pub config_file: ConfigFileDiagnostics,
+}
+
+/// 获取运行诊断信息
+///
+/// 用于排查长时间运行后的内存增长、任务泄漏、事件积压等问题，需要配置权限。
+#[utoipa::path(
+    get,
+    path = "/lspcapi/system/diagnostics",
+    responses(
+        (status = 200, description = "获取成功", body = inline(ApiResponse<DiagnosticsResponse>))
+    ),
+    tag = "System"
+)]
+pub async fn get_diagnostics(
+    Extension(controller): Extension<SharedController>,
+    Extension(config_path): Extension<SharedConfigPath>,
+) -> Json<ApiResponse<DiagnosticsResponse>> {
+    let memory = memory_stats::memory_stats();
+    let (open_fds, open_sockets) = match open_descriptors() {
+        Some((fds, sockets)) => (Some(fds), Some(sockets)),
+        None => (None, None),
+    };
+    let metrics = tokio::runtime::Handle::current().metrics();
+
+    let controller = controller.read().await;
+    let modified_at = std::fs::metadata(config_path.as_str())
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|t| time::rfc3339(t.into()));
+
+    Json(ApiResponse::success(
+        "成功",
+        DiagnosticsResponse {
+            server_time: time::rfc3339(chrono::Utc::now()),
+            uptime_ms: time::uptime().as_millis() as u64,
+            process: ProcessDiagnostics {
+                pid: std::process::id(),
+                rss_bytes: memory.map(|m| m.physical_mem as u64),
+                virtual_bytes: memory.map(|m| m.virtual_mem as u64),
+                open_fds,
+                open_sockets,
+            },
+            runtime: RuntimeDiagnostics {
+                workers: metrics.num_workers(),
+                alive_tasks: metrics.num_alive_tasks(),
+                global_queue_depth: metrics.global_queue_depth(),
+            },
+            event_bus: controller.event_bus_stats(),
+            channels: controller.channel_diagnostics(),
+            config_file: ConfigFileDiagnostics {
+                path: config_path.to_string(),
+                modified_at,
+            },
+        },
+    ))
+}
+
+/// 统计打开的文件描述符与其中的套接字数量（/proc/self/fd）
+#[cfg(target_os = "linux")]
+fn open_descriptors() -> Option<(usize, usize)> {
+    let entries = std::fs::read_dir("/proc/self/fd").ok()?;
+    let mut fds = 0;
+    let mut sockets = 0;
+    for entry in entries.flatten() {
+        fds += 1;
+        let is_socket = std::fs::read_link(entry.path())
+            .is_ok_and(|target| target.to_string_lossy().starts_with("socket:"));
+        if is_socket {
+            sockets += 1;
+        }
+    }
+    Some((fds, sockets))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_descriptors() -> Option<(usize, usize)> {
+    None
+}
@@ -23,7 +23,10 @@ struct WsMessage {
 }
 
 pub async fn run(config: WebSocketConfig, controller: DeviceController) -> Result<()> {
-    let url = format!("ws://{}:{}", config.ip, config.port);
+    let url = format!(
+        "ws://{}",
+        crate::utils::net::display_addr(&config.ip, config.port)
+    );
     
     loop {
         info!("连接到WebSocket服务器: {}", url);
@@ -0,0 +1,49 @@
+use std::process::Command;
+
+#[test]
+fn test_check_fails_for_unreachable_channel() {
+    // 绑定后立即释放，得到一个未监听的端口
+    let closed_port = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let dir = std::env::temp_dir().join(format!("dm-rust-check-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config_path = dir.join("config.json");
+    std::fs::write(
+        &config_path,
+        serde_json::json!({
+            "web_server": { "port": 18090 },
+            "storage": { "path": dir.join("storage") },
+            "channels": [{
+                "channel_id": 1,
+                "enable": true,
+                "statute": "modbus",
+                "arguments": { "type": "tcp", "addr": "127.0.0.1", "port": closed_port }
+            }],
+            "nodes": [],
+            "scenes": []
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dm-rust"))
+        .current_dir(&dir)
+        .args(["--check", "--check-channels", "--check-timeout-ms", "1000", "-c"])
+        .arg(&config_path)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let channel = report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == "channel:1")
+        .unwrap();
+    assert_eq!(channel["status"], "failed");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}